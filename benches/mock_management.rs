@@ -0,0 +1,47 @@
+//! Compares the cost of managing mocks on a local mock server, which is done with direct
+//! in-memory calls, to the cost of managing them through the HTTP admin API, which is used for
+//! remote (standalone) mock servers.
+//!
+//! Run with `cargo bench --bench mock_management`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use httpmock::prelude::*;
+
+fn create_and_delete(server: &MockServer) {
+    let mut mock = server.mock(|when, then| {
+        when.method(POST)
+            .path("/users")
+            .header("content-type", "application/json")
+            .json_body_partial(r#"{ "name": "Fred" }"#);
+        then.status(201);
+    });
+    mock.delete();
+}
+
+fn hits(server: &MockServer) -> impl FnMut() -> usize + '_ {
+    let mock = server.mock(|when, then| {
+        when.path("/hits");
+        then.status(200);
+    });
+    move || mock.hits()
+}
+
+fn mock_management(c: &mut Criterion) {
+    let local = MockServer::start();
+    let remote = MockServer::connect(&local.address().to_string());
+
+    let mut group = c.benchmark_group("create_and_delete");
+    group.bench_function("in_process", |b| b.iter(|| create_and_delete(&local)));
+    group.bench_function("http_admin_api", |b| b.iter(|| create_and_delete(&remote)));
+    group.finish();
+
+    let mut group = c.benchmark_group("hits");
+    let mut local_hits = hits(&local);
+    let mut remote_hits = hits(&remote);
+    group.bench_function("in_process", |b| b.iter(&mut local_hits));
+    group.bench_function("http_admin_api", |b| b.iter(&mut remote_hits));
+    group.finish();
+}
+
+criterion_group!(benches, mock_management);
+criterion_main!(benches);
@@ -0,0 +1,91 @@
+//! Measures how long it takes to find the mock for a request among many registered mocks.
+//! The request matches the mock that was added last, so it is compared with all of them.
+//!
+//! Run with `cargo bench --bench request_matching`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use httpmock::prelude::*;
+use hyper::{Body, Request};
+
+const MOCKS: usize = 500;
+const LARGE_BODY_MOCKS: usize = 200;
+const LARGE_BODY_BYTES: usize = 1024 * 1024;
+
+fn request(path: &str, body: &str) -> Request<Body> {
+    Request::post(format!("http://localhost{}", path))
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer token")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn request_matching(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("request_matching");
+
+    // Mocks that differ in their path, so most of them are ruled out early.
+    let server = MockServer::start_in_process();
+    for i in 0..MOCKS {
+        server.mock(|when, then| {
+            when.method(POST)
+                .path(format!("/users/{}", i))
+                .header("content-type", "application/json")
+                .json_body_partial(r#"{ "name": "Fred" }"#);
+            then.status(201);
+        });
+    }
+    let service = server.service();
+    let path = format!("/users/{}", MOCKS - 1);
+    group.bench_function("distinct_paths", |b| {
+        b.iter(|| runtime.block_on(service.handle(request(&path, r#"{"name":"Fred"}"#))))
+    });
+
+    // Mocks that only differ in their body, so all requirements of all mocks are checked.
+    let server = MockServer::start_in_process();
+    for i in 0..MOCKS {
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/users")
+                .header("content-type", "application/json")
+                .header_exists("authorization")
+                .json_body_partial(format!(r#"{{ "id": {} }}"#, i));
+            then.status(201);
+        });
+    }
+    let service = server.service();
+    let body = format!(r#"{{"id":{},"name":"Fred"}}"#, MOCKS - 1);
+    group.bench_function("same_path", |b| {
+        b.iter(|| runtime.block_on(service.handle(request("/users", &body))))
+    });
+
+    // Mocks that share their path and receive a large JSON body. Half of them are ruled out by
+    // their method, so their body requirements are not checked. The body is parsed only once.
+    let server = MockServer::start_in_process();
+    for i in 0..LARGE_BODY_MOCKS {
+        server.mock(|when, then| {
+            when.method(if i % 2 == 0 { PUT } else { POST })
+                .path("/items")
+                .json_body_partial(format!(r#"{{ "id": {} }}"#, i));
+            then.status(201);
+        });
+    }
+    let service = server.service();
+    let item = r#"{"name":"Fred","tags":["a","b"]},"#;
+    let items = item.repeat(LARGE_BODY_BYTES / item.len());
+    let body = format!(
+        r#"{{"id":{},"items":[{}null]}}"#,
+        LARGE_BODY_MOCKS - 1,
+        items
+    );
+    group.sample_size(20);
+    group.bench_function("large_json_body", |b| {
+        b.iter(|| runtime.block_on(service.handle(request("/items", &body))))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, request_matching);
+criterion_main!(benches);
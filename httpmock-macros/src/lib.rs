@@ -0,0 +1,132 @@
+//! Procedural macros for [httpmock](https://docs.rs/httpmock). Please use them through the
+//! `macros` feature of `httpmock` instead of depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Attribute, Error, Ident, ItemFn, Token, Visibility};
+
+/// Turns a function that receives a `MockServer` into a test. The test starts a pooled server
+/// (see `MockServer::start`) and passes it as the only argument of the function.
+///
+/// Synchronous functions become `#[test]` functions. Async functions are run by the runtime
+/// attribute that follows `#[httpmock::test]`, such as `#[tokio::test]` or
+/// `#[async_std::test]`. Other attributes, such as `#[should_panic]`, are kept.
+///
+/// With `#[httpmock::test(verify_all)]`, the test fails if a mock has not received any request
+/// by the end of the test (see `MockServer::verify_all`).
+///
+/// ```ignore
+/// use httpmock::prelude::*;
+///
+/// #[httpmock::test(verify_all)]
+/// #[tokio::test]
+/// async fn search_test(server: MockServer) {
+///     server
+///         .mock_async(|when, then| {
+///             when.path("/search");
+///             then.status(200);
+///         })
+///         .await;
+///
+///     isahc::get_async(server.url("/search")).await.unwrap();
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args with Punctuated::<Ident, Token![,]>::parse_terminated);
+    let item = parse_macro_input!(item as ItemFn);
+
+    match expand(args, item) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(
+    args: Punctuated<Ident, Token![,]>,
+    item: ItemFn,
+) -> Result<proc_macro2::TokenStream, Error> {
+    let mut verify_all = false;
+    for arg in args {
+        match arg.to_string().as_str() {
+            "verify_all" => verify_all = true,
+            _ => {
+                return Err(Error::new(
+                    arg.span(),
+                    "unknown argument, expected `verify_all`",
+                ))
+            }
+        }
+    }
+
+    if item.sig.inputs.len() != 1 {
+        return Err(Error::new_spanned(
+            &item.sig,
+            "the test function must have exactly one argument that receives the MockServer",
+        ));
+    }
+
+    let is_async = item.sig.asyncness.is_some();
+    let has_test_attribute = item.attrs.iter().any(is_test_attribute);
+    if is_async && !has_test_attribute {
+        return Err(Error::new_spanned(
+            &item.sig,
+            "async test functions require a runtime attribute such as `#[tokio::test]` or \
+             `#[async_std::test]` below `#[httpmock::test]`",
+        ));
+    }
+
+    // The original function is nested into the test function, which starts the server and
+    // passes it as the argument.
+    let mut inner = item.clone();
+    inner.attrs.clear();
+    inner.vis = Visibility::Inherited;
+
+    let attrs = &item.attrs;
+    let vis = &item.vis;
+    let name = &item.sig.ident;
+    let mut outer_sig = item.sig.clone();
+    outer_sig.inputs.clear();
+
+    let test_attribute = match has_test_attribute {
+        true => quote! {},
+        false => quote! { #[test] },
+    };
+    let server = Ident::new("__httpmock_server", Span::call_site());
+    let start = match is_async {
+        true => quote! { ::httpmock::MockServer::start_async().await },
+        false => quote! { ::httpmock::MockServer::start() },
+    };
+    let verify = match verify_all {
+        true => quote! { #server.verify_all_on_drop(); },
+        false => quote! {},
+    };
+    let call = match is_async {
+        true => quote! { #name(#server).await },
+        false => quote! { #name(#server) },
+    };
+
+    Ok(quote! {
+        #test_attribute
+        #(#attrs)*
+        #vis #outer_sig {
+            #inner
+
+            #[allow(unused_mut)]
+            let mut #server = #start;
+            #verify
+            #call
+        }
+    })
+}
+
+/// Returns true if the attribute marks a function as a test, such as `#[test]`,
+/// `#[tokio::test]` or `#[async_std::test]`.
+fn is_test_attribute(attr: &Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "test")
+}
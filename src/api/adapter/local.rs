@@ -1,23 +1,56 @@
 use std::borrow::Borrow;
 use std::fmt::Debug;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::SeqCst;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 
 use async_trait::async_trait;
 use isahc::prelude::*;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot::{Receiver, Sender};
 
-use crate::api::adapter::{build_http_client, http_ping, InternalHttpClient, MockServerAdapter};
-use crate::common::data::{ActiveMock, ClosestMatch, MockDefinition, MockRef, RequestRequirements};
+use crate::api::adapter::{
+    build_http_client, InternalHttpClient, MockOperationError, MockServerAdapter,
+};
+use crate::common::data::{
+    ActiveMock, ClosestMatch, HttpMockRequest, MockDefinition, MockRef, RecordedExchange,
+    RequestRequirements, ServerStats, StaticDirectory,
+};
+use crate::common::util::Join;
+use crate::server::request_log::RequestLogConfig;
 use crate::server::web::handlers::{
-    add_new_mock, delete_all_mocks, delete_history, delete_one_mock, read_one_mock, verify,
+    add_new_mock, add_static_dir, delete_all_mocks, delete_history, delete_one_mock, export_har,
+    read_all_mocks, read_mock_hits, read_mock_requests, read_one_mock, read_stats,
+    read_unmatched_requests, set_mock_enabled, verify,
+};
+use crate::server::{
+    MockServerState, MockService, PauseCommand, PauseRequest, ServerConfig, DEFAULT_DROP_DEADLINE,
+    DEFAULT_SHUTDOWN_TIMEOUT,
 };
-use crate::server::MockServerState;
 
 pub struct LocalMockServerAdapter {
     pub addr: SocketAddr,
     local_state: Arc<MockServerState>,
     client: Arc<InternalHttpClient>,
+    https: bool,
+    socket_path: Option<PathBuf>,
+    proxy_ca_pem: Option<String>,
+    config: Arc<ServerConfig>,
+    listeners: Vec<(Option<String>, SocketAddr)>,
+    in_process: bool,
+    server_handle: Mutex<Option<ServerHandle>>,
+    shut_down: AtomicBool,
+}
+
+/// Allows to stop a server and to wait until it has stopped.
+pub struct ServerHandle {
+    /// Receives the maximum time to wait for in-flight requests to complete.
+    pub shutdown_sender: Sender<Duration>,
+    pub stopped_receiver: Receiver<()>,
+    pub pause_sender: UnboundedSender<PauseRequest>,
 }
 
 impl LocalMockServerAdapter {
@@ -27,6 +60,113 @@ impl LocalMockServerAdapter {
             addr,
             local_state,
             client,
+            https: false,
+            socket_path: None,
+            proxy_ca_pem: None,
+            config: Arc::new(ServerConfig::default()),
+            listeners: Vec::new(),
+            in_process: false,
+            server_handle: Mutex::new(None),
+            shut_down: AtomicBool::new(false),
+        }
+    }
+
+    pub fn with_https(mut self, https: bool) -> Self {
+        self.https = https;
+        self
+    }
+
+    pub fn with_socket_path(mut self, socket_path: Option<PathBuf>) -> Self {
+        self.socket_path = socket_path;
+        self
+    }
+
+    pub fn with_proxy_ca_pem(mut self, proxy_ca_pem: Option<String>) -> Self {
+        self.proxy_ca_pem = proxy_ca_pem;
+        self
+    }
+
+    /// Sets the configuration that requests are handled with by the in-process service.
+    pub fn with_config(mut self, config: Arc<ServerConfig>) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the addresses of all listeners along with their names, if the server listens on
+    /// more than the primary address.
+    pub fn with_listeners(mut self, listeners: Vec<(Option<String>, SocketAddr)>) -> Self {
+        self.listeners = listeners;
+        self
+    }
+
+    /// Marks the server as in-process only, which means that no server is listening on
+    /// [addr](#structfield.addr).
+    pub fn with_in_process(mut self, in_process: bool) -> Self {
+        self.in_process = in_process;
+        self
+    }
+
+    /// Sets the handle that is used to stop the server on shutdown or when this adapter
+    /// is dropped.
+    pub fn with_server_handle(mut self, handle: ServerHandle) -> Self {
+        self.server_handle = Mutex::new(Some(handle));
+        self
+    }
+
+    /// Marks the server as shut down and signals it to stop after in-flight requests have been
+    /// completed or the timeout has elapsed. Returns a receiver that resolves when the server
+    /// has stopped, or `None` if the server was already shut down.
+    fn begin_shutdown(&self, timeout: Duration) -> Option<Receiver<()>> {
+        self.shut_down.store(true, SeqCst);
+        let handle = self.server_handle.lock().unwrap().take()?;
+        // The server may already have been stopped, so an error can be ignored here.
+        let _ = handle.shutdown_sender.send(timeout);
+        Some(handle.stopped_receiver)
+    }
+
+    /// Sends a pause command to the server and waits until it has been applied.
+    async fn send_pause_command(&self, command: PauseCommand) -> Result<(), String> {
+        self.ensure_running()?;
+
+        let pause_sender = match self.server_handle.lock().unwrap().as_ref() {
+            Some(handle) => handle.pause_sender.clone(),
+            None => return Err("The mock server does not listen for connections".to_string()),
+        };
+
+        let (result_sender, result_receiver) = tokio::sync::oneshot::channel();
+        pause_sender
+            .send(PauseRequest {
+                command,
+                result_sender,
+            })
+            .map_err(|_| "The mock server has stopped".to_string())?;
+
+        result_receiver
+            .await
+            .map_err(|_| "The mock server has stopped".to_string())?
+    }
+
+    fn ensure_running(&self) -> Result<(), String> {
+        if self.shut_down.load(SeqCst) {
+            return Err("The mock server has been shut down".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LocalMockServerAdapter {
+    fn drop(&mut self) {
+        let deadline = self.config.drop_deadline.unwrap_or(DEFAULT_DROP_DEADLINE);
+        if let Some(stopped_receiver) = self.begin_shutdown(deadline) {
+            // Clients may be served by the async runtime whose thread would be blocked by
+            // waiting here, so the server is left to stop in the background instead.
+            if tokio::runtime::Handle::try_current().is_ok() {
+                log::debug!(
+                    "Mock server dropped in an async context, stopping it in the background"
+                );
+                return;
+            }
+            let _ = stopped_receiver.join();
         }
     }
 }
@@ -45,42 +185,195 @@ impl MockServerAdapter for LocalMockServerAdapter {
         &self.addr
     }
 
-    async fn create_mock(&self, mock: &MockDefinition) -> Result<MockRef, String> {
-        let id = add_new_mock(&self.local_state, mock.clone(), false)?;
+    async fn create_mock(&self, mock: &MockDefinition) -> Result<MockRef, MockOperationError> {
+        self.ensure_running()
+            .map_err(MockOperationError::Connection)?;
+
+        let id = add_new_mock(&self.local_state, mock.clone(), false)
+            .map_err(MockOperationError::InvalidDefinition)?;
         Ok(MockRef::new(id))
     }
 
-    async fn fetch_mock(&self, mock_id: usize) -> Result<ActiveMock, String> {
-        match read_one_mock(&self.local_state, mock_id)? {
-            Some(mock) => Ok(mock),
-            None => Err("Cannot find mock".to_string()),
+    async fn fetch_mock(&self, mock_id: usize) -> Result<ActiveMock, MockOperationError> {
+        self.ensure_running()
+            .map_err(MockOperationError::Connection)?;
+
+        match read_one_mock(&self.local_state, mock_id) {
+            Ok(Some(mock)) => Ok(mock),
+            Ok(None) => Err(MockOperationError::Rejected(
+                404,
+                "Cannot find mock".to_string(),
+            )),
+            Err(e) => Err(MockOperationError::Rejected(500, e)),
+        }
+    }
+
+    async fn fetch_mock_hits(&self, mock_id: usize) -> Result<usize, MockOperationError> {
+        self.ensure_running()
+            .map_err(MockOperationError::Connection)?;
+
+        read_mock_hits(&self.local_state, mock_id)
+            .ok_or_else(|| MockOperationError::Rejected(404, "Cannot find mock".to_string()))
+    }
+
+    async fn fetch_all_mocks(&self) -> Result<Vec<ActiveMock>, String> {
+        self.ensure_running()?;
+
+        Ok(read_all_mocks(&self.local_state, None))
+    }
+
+    async fn fetch_mock_requests(&self, mock_id: usize) -> Result<Vec<HttpMockRequest>, String> {
+        self.ensure_running()?;
+
+        read_mock_requests(&self.local_state, mock_id).ok_or_else(|| "Cannot find mock".to_string())
+    }
+
+    async fn fetch_unmatched_requests(&self) -> Result<Vec<HttpMockRequest>, String> {
+        self.ensure_running()?;
+
+        Ok(read_unmatched_requests(&self.local_state, None))
+    }
+
+    async fn delete_mock(&self, mock_id: usize) -> Result<(), MockOperationError> {
+        self.ensure_running()
+            .map_err(MockOperationError::Connection)?;
+
+        match delete_one_mock(&self.local_state, mock_id) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(MockOperationError::Rejected(
+                404,
+                "Mock could not deleted".to_string(),
+            )),
+            Err(e) => Err(MockOperationError::Rejected(500, e)),
         }
     }
 
-    async fn delete_mock(&self, mock_id: usize) -> Result<(), String> {
-        let deleted = delete_one_mock(&self.local_state, mock_id)?;
-        if deleted {
+    async fn set_mock_enabled(
+        &self,
+        mock_id: usize,
+        enabled: bool,
+    ) -> Result<(), MockOperationError> {
+        self.ensure_running()
+            .map_err(MockOperationError::Connection)?;
+
+        if set_mock_enabled(&self.local_state, mock_id, enabled) {
             Ok(())
         } else {
-            Err("Mock could not deleted".to_string())
+            Err(MockOperationError::Rejected(
+                404,
+                "Cannot find mock".to_string(),
+            ))
         }
     }
 
     async fn delete_all_mocks(&self) -> Result<(), String> {
-        delete_all_mocks(&self.local_state);
+        self.ensure_running()?;
+
+        delete_all_mocks(&self.local_state, None);
         Ok(())
     }
 
     async fn verify(&self, mock_rr: &RequestRequirements) -> Result<Option<ClosestMatch>, String> {
+        self.ensure_running()?;
+
         verify(&self.local_state, mock_rr)
     }
 
     async fn delete_history(&self) -> Result<(), String> {
-        delete_history(&self.local_state);
+        self.ensure_running()?;
+
+        delete_history(&self.local_state, None);
         Ok(())
     }
 
+    async fn export_har(&self) -> Result<serde_json::Value, String> {
+        self.ensure_running()?;
+
+        Ok(export_har(&self.local_state, self.https))
+    }
+
+    async fn stats(&self) -> Result<ServerStats, String> {
+        self.ensure_running()?;
+
+        Ok(read_stats(&self.local_state))
+    }
+
+    async fn serve_dir(&self, dir: &StaticDirectory) -> Result<(), String> {
+        self.ensure_running()?;
+
+        add_static_dir(&self.local_state, dir.clone())
+    }
+
     async fn ping(&self) -> Result<(), String> {
-        http_ping(&self.addr, self.client.borrow()).await
+        // The listeners are bound before the server address is reported, so the server accepts
+        // connections as long as it has not been shut down.
+        self.ensure_running()
+    }
+
+    async fn shutdown(&self) -> Result<(), String> {
+        let timeout = self
+            .config
+            .shutdown_timeout
+            .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT);
+        if let Some(stopped_receiver) = self.begin_shutdown(timeout) {
+            // An error means the server thread has terminated, so it is stopped either way.
+            let _ = stopped_receiver.await;
+        }
+        Ok(())
+    }
+
+    async fn pause(&self, resume_after: Option<Duration>) -> Result<(), String> {
+        self.send_pause_command(PauseCommand::Pause { resume_after })
+            .await
+    }
+
+    async fn resume(&self) -> Result<(), String> {
+        self.send_pause_command(PauseCommand::Resume).await
+    }
+
+    async fn recordings(&self) -> Result<Vec<RecordedExchange>, String> {
+        Ok(self.local_state.recordings.lock().unwrap().clone())
+    }
+
+    fn is_shut_down(&self) -> bool {
+        self.shut_down.load(SeqCst)
+    }
+
+    fn overload_rejections(&self) -> Option<usize> {
+        Some(self.local_state.overload_rejections.load(SeqCst))
+    }
+
+    fn request_log(&self) -> Option<&RwLock<RequestLogConfig>> {
+        Some(&self.local_state.request_log)
+    }
+
+    fn listener_addresses(&self) -> Vec<(Option<String>, SocketAddr)> {
+        match self.listeners.is_empty() {
+            true => vec![(None, self.addr)],
+            false => self.listeners.clone(),
+        }
+    }
+
+    fn is_https(&self) -> bool {
+        self.https
+    }
+
+    fn socket_path(&self) -> Option<&Path> {
+        self.socket_path.as_deref()
+    }
+
+    fn proxy_ca_pem(&self) -> Option<&str> {
+        self.proxy_ca_pem.as_deref()
+    }
+
+    fn service(&self) -> Option<MockService> {
+        Some(MockService::new(
+            self.local_state.clone(),
+            self.config.clone(),
+        ))
+    }
+
+    fn is_in_process(&self) -> bool {
+        self.in_process
     }
 }
@@ -1,6 +1,7 @@
 use std::net::SocketAddr;
+use std::path::Path;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -8,22 +9,34 @@ use isahc::http::Request;
 use isahc::prelude::Configurable;
 use isahc::{AsyncReadResponseExt, ResponseExt};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::common::data::{ActiveMock, ClosestMatch, MockDefinition, MockRef, RequestRequirements};
+use crate::common::data::{
+    ActiveMock, ClosestMatch, HttpMockRequest, MockDefinition, MockRef, RecordedExchange,
+    RequestRequirements, ServerStats, StaticDirectory,
+};
+use crate::server::request_log::RequestLogConfig;
 use crate::server::web::handlers::{
     add_new_mock, delete_all_mocks, delete_history, delete_one_mock, read_one_mock, verify,
 };
+use crate::server::MockService;
 
 pub mod local;
 pub mod standalone;
 
 /// Type alias for [regex::Regex](../regex/struct.Regex.html).
+#[cfg(feature = "regex")]
 pub type Regex = regex::Regex;
 
 pub type InternalHttpClient = isahc::HttpClient;
 
-/// Represents an HTTP method.
-#[derive(Serialize, Deserialize, Debug)]
+/// Represents an HTTP method. Methods that are not defined in the HTTP specification, such as
+/// the WebDAV method `PROPFIND`, are represented by `Method::Custom`.
+///
+/// Methods are case-sensitive (see
+/// [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-9.1)), so parsing `get` results
+/// in a custom method that does not match `GET` requests.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Method {
     GET,
     HEAD,
@@ -34,6 +47,25 @@ pub enum Method {
     OPTIONS,
     TRACE,
     PATCH,
+    Custom(String),
+}
+
+impl Method {
+    /// Returns the name of the method as it appears in a request line.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::GET => "GET",
+            Method::HEAD => "HEAD",
+            Method::POST => "POST",
+            Method::PUT => "PUT",
+            Method::DELETE => "DELETE",
+            Method::CONNECT => "CONNECT",
+            Method::OPTIONS => "OPTIONS",
+            Method::TRACE => "TRACE",
+            Method::PATCH => "PATCH",
+            Method::Custom(method) => method,
+        }
+    }
 }
 
 impl FromStr for Method {
@@ -50,20 +82,97 @@ impl FromStr for Method {
             "OPTIONS" => Ok(Method::OPTIONS),
             "TRACE" => Ok(Method::TRACE),
             "PATCH" => Ok(Method::PATCH),
+            _ if is_token(input) => Ok(Method::Custom(input.to_string())),
             _ => Err(format!("Invalid HTTP method {}", input)),
         }
     }
 }
 
+/// Returns true if the value is a valid token as defined by
+/// [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-5.6.2), which method names must be.
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
 impl From<&str> for Method {
     fn from(value: &str) -> Self {
         value.parse().expect("Cannot parse HTTP method")
     }
 }
 
+impl From<String> for Method {
+    fn from(value: String) -> Self {
+        value.as_str().into()
+    }
+}
+
+impl From<&Method> for Method {
+    fn from(value: &Method) -> Self {
+        value.clone()
+    }
+}
+
 impl std::fmt::Display for Method {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        std::fmt::Debug::fmt(self, f)
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for Method {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Method {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "http-interop")]
+impl From<http::Method> for Method {
+    fn from(value: http::Method) -> Self {
+        value.as_str().into()
+    }
+}
+
+#[cfg(feature = "http-interop")]
+impl std::convert::TryFrom<Method> for http::Method {
+    type Error = String;
+
+    fn try_from(value: Method) -> Result<Self, Self::Error> {
+        http::Method::from_bytes(value.as_str().as_bytes())
+            .map_err(|_| format!("Invalid HTTP method {}", value))
+    }
+}
+
+/// The reason why a mock could not be created, fetched or deleted.
+#[derive(Debug)]
+pub enum MockOperationError {
+    /// The mock server could not be reached.
+    Connection(String),
+    /// The mock definition cannot be sent to or was not accepted by the mock server.
+    InvalidDefinition(String),
+    /// The mock server responded with an unexpected status code and body.
+    Rejected(u16, String),
+}
+
+impl std::fmt::Display for MockOperationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MockOperationError::Connection(reason) => write!(f, "{}", reason),
+            MockOperationError::InvalidDefinition(reason) => write!(f, "{}", reason),
+            MockOperationError::Rejected(status, body) => write!(
+                f,
+                "Mock server response: status = {}, message = {}",
+                status, body
+            ),
+        }
     }
 }
 
@@ -72,13 +181,126 @@ pub trait MockServerAdapter {
     fn host(&self) -> String;
     fn port(&self) -> u16;
     fn address(&self) -> &SocketAddr;
-    async fn create_mock(&self, mock: &MockDefinition) -> Result<MockRef, String>;
-    async fn fetch_mock(&self, mock_id: usize) -> Result<ActiveMock, String>;
-    async fn delete_mock(&self, mock_id: usize) -> Result<(), String>;
+    async fn create_mock(&self, mock: &MockDefinition) -> Result<MockRef, MockOperationError>;
+    async fn fetch_mock(&self, mock_id: usize) -> Result<ActiveMock, MockOperationError>;
+    async fn fetch_all_mocks(&self) -> Result<Vec<ActiveMock>, String>;
+
+    /// Returns the number of requests that matched a mock.
+    async fn fetch_mock_hits(&self, mock_id: usize) -> Result<usize, MockOperationError> {
+        let mock = self.fetch_mock(mock_id).await?;
+        Ok(mock.call_counter.into_inner())
+    }
+
+    async fn fetch_mock_requests(&self, mock_id: usize) -> Result<Vec<HttpMockRequest>, String>;
+    /// Returns the recorded requests that do not match any mock, starting with the oldest one.
+    async fn fetch_unmatched_requests(&self) -> Result<Vec<HttpMockRequest>, String>;
+    async fn delete_mock(&self, mock_id: usize) -> Result<(), MockOperationError>;
+    /// Enables or disables a mock. Disabled mocks do not match any request.
+    async fn set_mock_enabled(
+        &self,
+        mock_id: usize,
+        enabled: bool,
+    ) -> Result<(), MockOperationError>;
     async fn delete_all_mocks(&self) -> Result<(), String>;
     async fn verify(&self, rr: &RequestRequirements) -> Result<Option<ClosestMatch>, String>;
     async fn delete_history(&self) -> Result<(), String>;
     async fn ping(&self) -> Result<(), String>;
+
+    /// Returns all requests that the mock server received and the responses it sent as a
+    /// HAR 1.2 document.
+    async fn export_har(&self) -> Result<Value, String>;
+
+    /// Returns statistics about the requests that the mock server handled.
+    async fn stats(&self) -> Result<ServerStats, String>;
+
+    /// Serves the files of a directory for requests that do not match any mock.
+    async fn serve_dir(&self, dir: &StaticDirectory) -> Result<(), String>;
+
+    /// Deletes all mocks that are not static and the request history.
+    async fn reset(&self) -> Result<(), String> {
+        self.delete_all_mocks().await?;
+        self.delete_history().await
+    }
+
+    /// Stops the mock server and waits until it has stopped. All further calls to this
+    /// adapter will fail.
+    async fn shutdown(&self) -> Result<(), String> {
+        Err("This mock server cannot be shut down".to_string())
+    }
+
+    /// Stops the mock server from accepting connections and closes all open connections,
+    /// until it is resumed. If `resume_after` is set, the server is resumed automatically
+    /// after this duration.
+    async fn pause(&self, _resume_after: Option<Duration>) -> Result<(), String> {
+        Err("This mock server cannot be paused".to_string())
+    }
+
+    /// Lets a paused mock server accept connections on the same address again. Resuming a
+    /// server that is not paused has no effect.
+    async fn resume(&self) -> Result<(), String> {
+        Err("This mock server cannot be resumed".to_string())
+    }
+
+    /// Returns the requests that the mock server forwarded to the upstream server along with
+    /// the upstream responses, in the order in which they were received.
+    async fn recordings(&self) -> Result<Vec<RecordedExchange>, String> {
+        Err("This mock server does not record requests".to_string())
+    }
+
+    /// Returns true if the mock server has been shut down.
+    fn is_shut_down(&self) -> bool {
+        false
+    }
+
+    /// Returns the number of connections and requests that were rejected because the
+    /// connection limit was reached, if the mock server runs in the current process.
+    fn overload_rejections(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the settings of the request log, if the mock server runs in the current process.
+    fn request_log(&self) -> Option<&RwLock<RequestLogConfig>> {
+        None
+    }
+
+    /// Returns the addresses of all TCP listeners along with their names, starting with the
+    /// primary address.
+    fn listener_addresses(&self) -> Vec<(Option<String>, SocketAddr)> {
+        vec![(None, *self.address())]
+    }
+
+    /// Returns true if the mock server accepts HTTPS connections only.
+    fn is_https(&self) -> bool {
+        false
+    }
+
+    /// Returns the path of the Unix domain socket the mock server listens on, if any.
+    fn socket_path(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Returns the PEM encoded certificate of the proxy certificate authority, if the mock
+    /// server acts as a proxy that terminates TLS.
+    fn proxy_ca_pem(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns a service that handles requests in-process, if the mock server runs in the
+    /// current process.
+    fn service(&self) -> Option<MockService> {
+        None
+    }
+
+    /// Returns whether the mock server only serves requests in-process and does not listen
+    /// for network connections.
+    fn is_in_process(&self) -> bool {
+        false
+    }
+
+    /// Returns the namespace that all mocks and requests of this adapter are limited to, if any.
+    fn namespace(&self) -> Option<&str> {
+        None
+    }
 }
 
 async fn http_ping(
@@ -125,10 +347,28 @@ async fn execute_request(
     Ok((response.status().as_u16(), body))
 }
 
+lazy_static! {
+    /// Every HTTP client runs a background thread with its own event loop, so all adapters
+    /// share one client.
+    static ref HTTP_CLIENT: Arc<InternalHttpClient> = Arc::new(
+        InternalHttpClient::builder()
+            .tcp_keepalive(Duration::from_secs(60 * 60 * 24))
+            .build()
+            .expect("Cannot build HTTP client"),
+    );
+}
+
 fn build_http_client() -> Arc<InternalHttpClient> {
+    HTTP_CLIENT.clone()
+}
+
+/// Builds a client for the admin API of a remote mock server, which keeps its connections
+/// alive between requests.
+fn build_admin_http_client(timeout: Duration) -> Arc<InternalHttpClient> {
     Arc::new(
         InternalHttpClient::builder()
             .tcp_keepalive(Duration::from_secs(60 * 60 * 24))
+            .timeout(timeout)
             .build()
             .expect("Cannot build HTTP client"),
     )
@@ -1,37 +1,173 @@
 use std::borrow::Borrow;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
-use isahc::Request;
+use isahc::{AsyncReadResponseExt, Request};
+use serde::de::DeserializeOwned;
 
 use crate::api::adapter::{
-    build_http_client, execute_request, http_ping, InternalHttpClient, MockServerAdapter,
+    build_admin_http_client, http_ping, InternalHttpClient, MockOperationError, MockServerAdapter,
 };
-use crate::common::data::{ActiveMock, ClosestMatch, MockDefinition, MockRef, RequestRequirements};
+use crate::common::data::{
+    ActiveMock, ClosestMatch, HttpMockRequest, MockDefinition, MockRef, RequestRequirements,
+    ServerStats, StaticDirectory,
+};
+use crate::common::util::unique_id;
+use crate::server::IDEMPOTENCY_KEY_HEADER;
+
+/// The default maximum time that an admin API request to a remote mock server may take.
+pub(crate) const DEFAULT_ADMIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The settings of the HTTP client that sends admin API requests to a remote mock server.
+#[derive(Debug, Clone)]
+pub(crate) struct AdminClientConfig {
+    /// The maximum time that a request may take.
+    pub timeout: Duration,
+    /// How often a request is repeated if its connection could not be established or was
+    /// reset.
+    pub retries: usize,
+}
+
+impl Default for AdminClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_ADMIN_TIMEOUT,
+            retries: 1,
+        }
+    }
+}
 
+/// A [MockServerAdapter](trait.MockServerAdapter.html) that administrates a remote mock server
+/// using its admin REST API (see the crate documentation for a description of all endpoints).
+///
+/// Every adapter keeps its own pool of keep-alive connections to the remote server.
 #[derive(Debug)]
 pub struct RemoteMockServerAdapter {
     addr: SocketAddr,
     http_client: Arc<InternalHttpClient>,
+    namespace: Option<String>,
+    retries: usize,
 }
 
 impl RemoteMockServerAdapter {
     pub fn new(addr: SocketAddr) -> Self {
+        Self::with_config(addr, None, AdminClientConfig::default())
+    }
+
+    /// Creates an adapter whose mocks only match requests that are sent to the given
+    /// namespace. Deleting mocks or the request history only affects this namespace.
+    pub fn with_namespace(addr: SocketAddr, namespace: String) -> Self {
+        Self::with_config(addr, Some(namespace), AdminClientConfig::default())
+    }
+
+    pub(crate) fn with_config(
+        addr: SocketAddr,
+        namespace: Option<String>,
+        config: AdminClientConfig,
+    ) -> Self {
         Self {
             addr,
-            http_client: build_http_client(),
+            http_client: build_admin_http_client(config.timeout),
+            namespace,
+            retries: config.retries,
+        }
+    }
+
+    /// Returns the query string that limits an admin API request to the namespace.
+    fn namespace_query(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!(
+                "?{}",
+                form_urlencoded::Serializer::new(String::new())
+                    .append_pair("namespace", namespace)
+                    .finish()
+            ),
+            None => String::new(),
         }
     }
 
     fn validate_mock(&self, mock: &MockDefinition) -> Result<(), String> {
-        if mock.request.matchers.is_some() {
+        fn has_matchers(request: &RequestRequirements) -> bool {
+            request.matchers.is_some()
+                || request.custom_matchers.is_some()
+                || request.any_of.iter().flatten().any(has_matchers)
+        }
+        if has_matchers(&mock.request) {
             return Err(
                 "Anonymous function request matchers are not supported when using a remote mock server".to_string(),
             );
         }
         Ok(())
     }
+
+    /// Sends a request to an admin API endpoint of the mock server. Returns the status code
+    /// and the body of the response.
+    async fn send(
+        &self,
+        method: &str,
+        path: &str,
+        json_body: Option<String>,
+    ) -> Result<(u16, String), String> {
+        self.send_with_key(method, path, json_body, None).await
+    }
+
+    /// Sends a request like [send](#method.send). Requests whose connection could not be
+    /// established or was reset are repeated, so they must be idempotent. Requests that
+    /// create something carry an idempotency key, so that the mock server creates it once.
+    async fn send_with_key(
+        &self,
+        method: &str,
+        path: &str,
+        json_body: Option<String>,
+        idempotency_key: Option<&str>,
+    ) -> Result<(u16, String), String> {
+        let uri = format!("http://{}/__httpmock__{}", &self.addr, path);
+        let mut retries = self.retries;
+        loop {
+            let mut request = Request::builder().method(method).uri(&uri);
+            if json_body.is_some() {
+                request = request.header("content-type", "application/json");
+            }
+            if let Some(key) = idempotency_key {
+                request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+            }
+            let request = request.body(json_body.clone().unwrap_or_default()).unwrap();
+
+            match send_request(request, &self.http_client).await {
+                Err(err) if err.is_network() && retries > 0 => {
+                    log::debug!("Repeating admin API request to {}: {}", uri, err);
+                    retries -= 1;
+                }
+                result => {
+                    return result
+                        .map_err(|err| format!("Cannot send request to mock server: {}", err))
+                }
+            }
+        }
+    }
+}
+
+/// Sends a request and reads the whole response, so that the connection can be reused.
+async fn send_request(
+    request: Request<String>,
+    http_client: &InternalHttpClient,
+) -> Result<(u16, String), isahc::Error> {
+    let mut response = http_client.send_async(request).await?;
+    let body = response.text().await?;
+    Ok((response.status().as_u16(), body))
+}
+
+/// Serializes a request body for the admin API.
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|err| format!("Cannot serialize object to JSON: {}", err))
+}
+
+/// Deserializes a response body of the admin API.
+fn from_json<T: DeserializeOwned>(body: &str) -> Result<T, String> {
+    serde_json::from_str(body)
+        .map_err(|err| format!("Cannot deserialize mock server response: {}", err))
 }
 
 #[async_trait]
@@ -48,118 +184,110 @@ impl MockServerAdapter for RemoteMockServerAdapter {
         &self.addr
     }
 
-    async fn create_mock(&self, mock: &MockDefinition) -> Result<MockRef, String> {
+    async fn create_mock(&self, mock: &MockDefinition) -> Result<MockRef, MockOperationError> {
         // Check if the request can be sent via HTTP
-        self.validate_mock(mock).expect("Cannot create mock");
-
-        // Serialize to JSON
-        let json = match serde_json::to_string(mock) {
-            Err(err) => return Err(format!("cannot serialize mock object to JSON: {}", err)),
-            Ok(json) => json,
-        };
-
-        // Send the request to the mock server
-        let request_url = format!("http://{}/__httpmock__/mocks", &self.address());
-        let request = Request::builder()
-            .method("POST")
-            .uri(request_url)
-            .header("content-type", "application/json")
-            .body(json)
-            .unwrap();
-
-        let (status, body) = match execute_request(request, &self.http_client).await {
-            Err(err) => return Err(format!("cannot send request to mock server: {}", err)),
-            Ok(sb) => sb,
-        };
-
-        // Evaluate the response status
+        self.validate_mock(mock)
+            .map_err(MockOperationError::InvalidDefinition)?;
+
+        let mut mock = mock.clone();
+        mock.request.namespace = self.namespace.clone();
+        let json = to_json(&mock).map_err(MockOperationError::InvalidDefinition)?;
+        let (status, body) = self
+            .send_with_key("POST", "/mocks", Some(json), Some(&unique_id()))
+            .await
+            .map_err(MockOperationError::Connection)?;
         if status != 201 {
-            return Err(format!(
-                "Could not create mock. Mock server response: status = {}, message = {}",
-                status, body
-            ));
+            return Err(MockOperationError::Rejected(status, body));
         }
 
-        // Create response object
-        let response: serde_json::Result<MockRef> = serde_json::from_str(&body);
-        if let Err(err) = response {
-            return Err(format!("Cannot deserialize mock server response: {}", err));
+        from_json(&body).map_err(|_| MockOperationError::Rejected(status, body))
+    }
+
+    async fn fetch_mock(&self, mock_id: usize) -> Result<ActiveMock, MockOperationError> {
+        let path = format!("/mocks/{}", mock_id);
+        let (status, body) = self
+            .send("GET", &path, None)
+            .await
+            .map_err(MockOperationError::Connection)?;
+        if status != 200 {
+            return Err(MockOperationError::Rejected(status, body));
         }
 
-        Ok(response.unwrap())
+        from_json(&body).map_err(|_| MockOperationError::Rejected(status, body))
     }
 
-    async fn fetch_mock(&self, mock_id: usize) -> Result<ActiveMock, String> {
-        // Send the request to the mock server
-        let request_url = format!("http://{}/__httpmock__/mocks/{}", &self.address(), mock_id);
-        let request = Request::builder()
-            .method("GET")
-            .uri(request_url)
-            .body("".to_string())
-            .unwrap();
+    async fn fetch_all_mocks(&self) -> Result<Vec<ActiveMock>, String> {
+        let path = format!("/mocks{}", self.namespace_query());
+        let (status, body) = self.send("GET", &path, None).await?;
+        if status != 200 {
+            return Err(format!(
+                "Could not fetch mocks. Mock server response: status = {}, message = {}",
+                status, body
+            ));
+        }
 
-        let (status, body) = match execute_request(request, &self.http_client).await {
-            Err(err) => return Err(format!("Cannot send request to mock server: {}", err)),
-            Ok(r) => r,
-        };
+        from_json(&body)
+    }
 
-        // Evaluate response status code
+    async fn fetch_mock_requests(&self, mock_id: usize) -> Result<Vec<HttpMockRequest>, String> {
+        let path = format!("/mocks/{}/requests", mock_id);
+        let (status, body) = self.send("GET", &path, None).await?;
         if status != 200 {
             return Err(format!(
-                "Could not create mock. Mock server response: status = {}, message = {}",
+                "Could not fetch mock requests. Mock server response: status = {}, message = {}",
                 status, body
             ));
         }
 
-        // Create response object
-        let response: serde_json::Result<ActiveMock> = serde_json::from_str(&body);
-        if let Err(err) = response {
-            return Err(format!("Cannot deserialize mock server response: {}", err));
+        from_json(&body)
+    }
+
+    async fn fetch_unmatched_requests(&self) -> Result<Vec<HttpMockRequest>, String> {
+        let path = format!("/history/unmatched{}", self.namespace_query());
+        let (status, body) = self.send("GET", &path, None).await?;
+        if status != 200 {
+            return Err(format!(
+                "Could not fetch unmatched requests. Mock server response: status = {}, message = {}",
+                status, body
+            ));
         }
 
-        Ok(response.unwrap())
+        from_json(&body)
     }
 
-    async fn delete_mock(&self, mock_id: usize) -> Result<(), String> {
-        // Send the request to the mock server
-        let request_url = format!("http://{}/__httpmock__/mocks/{}", &self.address(), mock_id);
-        let request = Request::builder()
-            .method("DELETE")
-            .uri(request_url)
-            .body("".to_string())
-            .unwrap();
+    async fn delete_mock(&self, mock_id: usize) -> Result<(), MockOperationError> {
+        let path = format!("/mocks/{}", mock_id);
+        let (status, body) = self
+            .send("DELETE", &path, None)
+            .await
+            .map_err(MockOperationError::Connection)?;
+        if status != 202 {
+            return Err(MockOperationError::Rejected(status, body));
+        }
 
-        let (status, body) = match execute_request(request, &self.http_client).await {
-            Err(err) => return Err(format!("Cannot send request to mock server: {}", err)),
-            Ok(sb) => sb,
-        };
+        Ok(())
+    }
 
-        // Evaluate response status code
+    async fn set_mock_enabled(
+        &self,
+        mock_id: usize,
+        enabled: bool,
+    ) -> Result<(), MockOperationError> {
+        let path = format!("/mocks/{}/enabled", mock_id);
+        let (status, body) = self
+            .send("PUT", &path, Some(enabled.to_string()))
+            .await
+            .map_err(MockOperationError::Connection)?;
         if status != 202 {
-            return Err(format!(
-                "Could not delete mocks from server (status = {}, message = {})",
-                status, body
-            ));
+            return Err(MockOperationError::Rejected(status, body));
         }
 
         Ok(())
     }
 
     async fn delete_all_mocks(&self) -> Result<(), String> {
-        // Send the request to the mock server
-        let request_url = format!("http://{}/__httpmock__/mocks", &self.address());
-        let request = Request::builder()
-            .method("DELETE")
-            .uri(request_url)
-            .body("".to_string())
-            .unwrap();
-
-        let (status, body) = match execute_request(request, &self.http_client).await {
-            Err(err) => return Err(format!("Cannot send request to mock server: {}", err)),
-            Ok(sb) => sb,
-        };
-
-        // Evaluate response status code
+        let path = format!("/mocks{}", self.namespace_query());
+        let (status, body) = self.send("DELETE", &path, None).await?;
         if status != 202 {
             return Err(format!(
                 "Could not delete mocks from server (status = {}, message = {})",
@@ -171,31 +299,14 @@ impl MockServerAdapter for RemoteMockServerAdapter {
     }
 
     async fn verify(&self, mock_rr: &RequestRequirements) -> Result<Option<ClosestMatch>, String> {
-        // Serialize to JSON
-        let json = match serde_json::to_string(mock_rr) {
-            Err(err) => return Err(format!("Cannot serialize mock object to JSON: {}", err)),
-            Ok(json) => json,
-        };
-
-        // Send the request to the mock server
-        let request_url = format!("http://{}/__httpmock__/verify", &self.address());
-        let request = Request::builder()
-            .method("POST")
-            .uri(request_url)
-            .header("content-type", "application/json")
-            .body(json)
-            .unwrap();
-
-        let (status, body) = match execute_request(request, &self.http_client).await {
-            Err(err) => return Err(format!("Cannot send request to mock server: {}", err)),
-            Ok(sb) => sb,
-        };
-
-        // Evaluate the response status
+        let mut mock_rr = mock_rr.clone();
+        mock_rr.namespace = self.namespace.clone();
+        let (status, body) = self
+            .send("POST", "/verify", Some(to_json(&mock_rr)?))
+            .await?;
         if status == 404 {
             return Ok(None);
         }
-
         if status != 200 {
             return Err(format!(
                 "Could not execute verification (status = {}, message = {})",
@@ -203,30 +314,12 @@ impl MockServerAdapter for RemoteMockServerAdapter {
             ));
         }
 
-        // Create response object
-        let response: serde_json::Result<ClosestMatch> = serde_json::from_str(&body);
-        if let Err(err) = response {
-            return Err(format!("cannot deserialize mock server response: {}", err));
-        }
-
-        Ok(Some(response.unwrap()))
+        from_json(&body).map(Some)
     }
 
     async fn delete_history(&self) -> Result<(), String> {
-        // Send the request to the mock server
-        let request_url = format!("http://{}/__httpmock__/history", &self.address());
-        let request = Request::builder()
-            .method("DELETE")
-            .uri(request_url)
-            .body("".to_string())
-            .unwrap();
-
-        let (status, body) = match execute_request(request, &self.http_client).await {
-            Err(err) => return Err(format!("Cannot send request to mock server: {}", err)),
-            Ok(sb) => sb,
-        };
-
-        // Evaluate response status code
+        let path = format!("/history{}", self.namespace_query());
+        let (status, body) = self.send("DELETE", &path, None).await?;
         if status != 202 {
             return Err(format!(
                 "Could not delete history from server (status = {}, message = {})",
@@ -237,7 +330,64 @@ impl MockServerAdapter for RemoteMockServerAdapter {
         Ok(())
     }
 
+    async fn reset(&self) -> Result<(), String> {
+        let path = format!("/reset{}", self.namespace_query());
+        let (status, body) = self.send("POST", &path, None).await?;
+        if status != 202 {
+            return Err(format!(
+                "Could not reset mock server (status = {}, message = {})",
+                status, body
+            ));
+        }
+
+        Ok(())
+    }
+
     async fn ping(&self) -> Result<(), String> {
         http_ping(&self.addr, self.http_client.borrow()).await
     }
+
+    fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    async fn export_har(&self) -> Result<serde_json::Value, String> {
+        let (status, body) = self.send("GET", "/history/har", None).await?;
+        if status != 200 {
+            return Err(format!(
+                "Could not export traffic from server (status = {}, message = {})",
+                status, body
+            ));
+        }
+
+        from_json(&body)
+    }
+
+    async fn stats(&self) -> Result<ServerStats, String> {
+        let (status, body) = self.send("GET", "/stats", None).await?;
+        if status != 200 {
+            return Err(format!(
+                "Could not fetch statistics from server (status = {}, message = {})",
+                status, body
+            ));
+        }
+
+        from_json(&body)
+    }
+
+    async fn serve_dir(&self, dir: &StaticDirectory) -> Result<(), String> {
+        let mut dir = dir.clone();
+        dir.namespace = self.namespace.clone();
+        let (status, body) = self
+            .send("POST", "/static_dirs", Some(to_json(&dir)?))
+            .await?;
+        if status != 201 {
+            return Err(format!(
+                "Could not serve directory (status = {}, message = {})",
+                status, body
+            ));
+        }
+
+        Ok(())
+    }
 }
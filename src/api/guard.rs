@@ -0,0 +1,156 @@
+use std::sync::atomic::Ordering;
+use std::thread;
+
+use crate::api::{Mock, MockServer};
+use crate::common::data::describe_mock;
+use crate::common::util::Join;
+
+/// Verifies the requests that a [MockServer](struct.MockServer.html) received when it is
+/// dropped, so that the verification cannot be forgotten. Created by
+/// [MockServer::verify_on_drop](struct.MockServer.html#method.verify_on_drop).
+///
+/// By default, the guard checks that every mock received at least one request and that every
+/// request matched a mock. Expected numbers of requests for single mocks can be added with
+/// [VerificationGuard::expect](struct.VerificationGuard.html#method.expect). When the guard is
+/// dropped, it panics with a report of all failed checks, unless the current thread is already
+/// panicking, so that the original failure is not masked.
+///
+/// # Example
+/// ```should_panic
+/// use httpmock::prelude::*;
+///
+/// let server = MockServer::start();
+/// let mut guard = server.verify_on_drop();
+///
+/// let search = server.mock(|when, then| {
+///     when.path("/search");
+///     then.status(200);
+/// });
+/// guard.expect(&search, 2);
+///
+/// isahc::get(server.url("/search")).unwrap();
+///
+/// // Panics when the guard is dropped, because "/search" received only one request.
+/// ```
+pub struct VerificationGuard<'a> {
+    server: &'a MockServer,
+    check_unused_mocks: bool,
+    check_unmatched_requests: bool,
+    expected_hits: Vec<(usize, String, usize)>,
+}
+
+impl<'a> VerificationGuard<'a> {
+    pub(crate) fn new(server: &'a MockServer) -> Self {
+        Self {
+            server,
+            check_unused_mocks: true,
+            check_unmatched_requests: true,
+            expected_hits: Vec::new(),
+        }
+    }
+
+    /// Sets whether the guard checks that every mock received at least one request. Mocks
+    /// with an expected number of requests (see
+    /// [VerificationGuard::expect](struct.VerificationGuard.html#method.expect)) are only
+    /// checked against this number. Defaults to `true`.
+    pub fn check_unused_mocks(mut self, enabled: bool) -> Self {
+        self.check_unused_mocks = enabled;
+        self
+    }
+
+    /// Sets whether the guard checks that every request that the server received matched a
+    /// mock. Requests that were served from a static directory or forwarded to an upstream
+    /// server did not match a mock. Defaults to `true`.
+    pub fn check_unmatched_requests(mut self, enabled: bool) -> Self {
+        self.check_unmatched_requests = enabled;
+        self
+    }
+
+    /// Expects a mock to receive exactly `hits` requests by the time the guard is dropped.
+    pub fn expect(&mut self, mock: &Mock, hits: usize) -> &mut Self {
+        self.expected_hits.push((mock.id, mock.description(), hits));
+        self
+    }
+
+    /// Returns a report of every failed check, or `None` if all checks passed.
+    async fn report(&self) -> Option<String> {
+        let adapter = self.server.server_adapter.as_ref().unwrap();
+        let mut failures = Vec::new();
+
+        let mocks = adapter
+            .fetch_all_mocks()
+            .await
+            .expect("Cannot fetch mocks from mock server");
+
+        for (id, description, expected) in &self.expected_hits {
+            let actual = mocks
+                .iter()
+                .find(|mock| mock.id == *id)
+                .map(|mock| mock.call_counter.load(Ordering::SeqCst));
+            match actual {
+                Some(actual) if actual == *expected => {}
+                Some(actual) => failures.push(format!(
+                    "Mock {} received {} request(s), but {} were expected.",
+                    description, actual, expected
+                )),
+                None => failures.push(format!(
+                    "Mock {} was expected to receive {} request(s), but it has been deleted.",
+                    description, expected
+                )),
+            }
+        }
+
+        if self.check_unused_mocks {
+            let unused: Vec<String> = mocks
+                .iter()
+                .filter(|mock| !mock.is_static && mock.call_counter.load(Ordering::SeqCst) == 0)
+                .filter(|mock| !self.expected_hits.iter().any(|(id, _, _)| *id == mock.id))
+                .map(|mock| {
+                    format!(
+                        "{} (ID {})",
+                        describe_mock(&mock.definition.request),
+                        mock.id
+                    )
+                })
+                .collect();
+            if !unused.is_empty() {
+                failures.push(format!(
+                    "The following mocks have not received any request:\n{}",
+                    unused.join("\n")
+                ));
+            }
+        }
+
+        if self.check_unmatched_requests {
+            let unmatched: Vec<String> = adapter
+                .fetch_unmatched_requests()
+                .await
+                .expect("Cannot fetch unmatched requests from mock server")
+                .iter()
+                .map(|req| format!("{} {}", req.method, req.path_and_query()))
+                .collect();
+            if !unmatched.is_empty() {
+                failures.push(format!(
+                    "The following requests did not match any mock:\n{}",
+                    unmatched.join("\n")
+                ));
+            }
+        }
+
+        match failures.is_empty() {
+            true => None,
+            false => Some(failures.join("\n\n")),
+        }
+    }
+}
+
+impl Drop for VerificationGuard<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            return;
+        }
+        if let Some(report) = self.report().join() {
+            panic!("{}", report);
+        }
+    }
+}
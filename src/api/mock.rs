@@ -11,11 +11,13 @@ use colored::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::api::adapter::MockOperationError;
 use crate::api::server::MockServer;
-use crate::api::{Method, Regex};
 
-use crate::common::data::{ClosestMatch, Diff, DiffResult, Mismatch, Reason};
-use crate::common::util::{get_test_resource_file_path, read_file, Join};
+use crate::common::data::{
+    describe_mock, ClosestMatch, Diff, DiffResult, Mismatch, Reason, RequestRequirements,
+};
+use crate::common::util::{assert_not_in_runtime, Join};
 
 /// Represents a reference to the mock object on a [MockServer](struct.MockServer.html).
 /// It can be used to spy on the mock and also perform some management operations, such as
@@ -53,11 +55,136 @@ pub struct Mock<'a> {
     // https://github.com/alexliesenfeld/httpmock/issues/26.
     pub id: usize,
     pub(crate) server: &'a MockServer,
+    /// The method and path of the mock for error messages, if known.
+    pub(crate) description: Option<String>,
+}
+
+/// An error that occurred while creating, deleting or inspecting a mock (see
+/// [MockServer::try_mock](struct.MockServer.html#method.try_mock),
+/// [Mock::try_delete](struct.Mock.html#method.try_delete) and
+/// [Mock::try_hits](struct.Mock.html#method.try_hits)). Each variant holds a description of
+/// the mock (its method and path, e.g. `GET /search`), which is part of the error message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockError {
+    /// The mock server could not be reached, e.g. because a standalone server is not running
+    /// or the server has been shut down.
+    ConnectionFailed { mock: String, reason: String },
+    /// The mock definition is invalid or cannot be sent to the mock server, e.g. because it
+    /// contains a custom matcher function and the server is a standalone server.
+    InvalidMockDefinition { mock: String, reason: String },
+    /// The mock server responded with an unexpected status code, e.g. `404` if the mock does
+    /// not exist anymore.
+    ServerRejected {
+        mock: String,
+        status: u16,
+        body: String,
+    },
+}
+
+impl MockError {
+    pub(crate) fn new(mock: String, error: MockOperationError) -> Self {
+        match error {
+            MockOperationError::Connection(reason) => MockError::ConnectionFailed { mock, reason },
+            MockOperationError::InvalidDefinition(reason) => {
+                MockError::InvalidMockDefinition { mock, reason }
+            }
+            MockOperationError::Rejected(status, body) => {
+                MockError::ServerRejected { mock, status, body }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for MockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MockError::ConnectionFailed { mock, reason } => write!(
+                f,
+                "Cannot connect to the mock server for mock {}: {}",
+                mock, reason
+            ),
+            MockError::InvalidMockDefinition { mock, reason } => {
+                write!(f, "Invalid definition of mock {}: {}", mock, reason)
+            }
+            MockError::ServerRejected { mock, status, body } => write!(
+                f,
+                "The mock server rejected the request for mock {} (status = {}, message = {})",
+                mock, status, body
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MockError {}
+
+/// Prints the ID, the method and path and one line per request requirement of the mock, e.g.:
+/// ```text
+/// Mock 3: GET /search
+///   method equals GET
+///   path equals '/search'
+///   query parameter 'query' equals 'metallica'
+/// ```
+/// The requirements are fetched from the mock server.
+impl std::fmt::Display for Mock<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let adapter = self.server.server_adapter.as_ref().unwrap();
+        match adapter.fetch_mock(self.id).join() {
+            Ok(mock) => {
+                let request = &mock.definition.request;
+                write!(f, "Mock {}: {}", self.id, describe_mock(request))?;
+                for line in request.summary() {
+                    write!(f, "\n  {}", line)?;
+                }
+                Ok(())
+            }
+            Err(e) => write!(
+                f,
+                "Mock {} (cannot fetch the mock from the mock server: {})",
+                self.description(),
+                e
+            ),
+        }
+    }
+}
+
+impl std::fmt::Debug for Mock<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Mock")
+            .field("id", &self.id)
+            .field("description", &self.description)
+            .finish()
+    }
 }
 
 impl<'a> Mock<'a> {
     pub fn new(id: usize, server: &'a MockServer) -> Self {
-        Self { id, server }
+        Self {
+            id,
+            server,
+            description: None,
+        }
+    }
+
+    /// Creates a reference to a mock whose request requirements are known, so that error
+    /// messages can contain its method and path.
+    pub(crate) fn for_request(
+        id: usize,
+        server: &'a MockServer,
+        request: &RequestRequirements,
+    ) -> Self {
+        Self {
+            id,
+            server,
+            description: Some(describe_mock(request)),
+        }
+    }
+
+    /// Returns the description of this mock for error messages.
+    pub(crate) fn description(&self) -> String {
+        match &self.description {
+            Some(description) => format!("{} (ID {})", description, self.id),
+            None => format!("with ID {}", self.id),
+        }
     }
     /// This method asserts that the mock server received **exactly one** HTTP request that matched
     /// all the request requirements of this mock.
@@ -88,6 +215,7 @@ impl<'a> Mock<'a> {
     /// # Panics
     /// This method will panic if there is a problem with the (standalone) mock server.
     pub fn assert(&self) {
+        assert_not_in_runtime("Mock::assert", "Mock::assert_async");
         self.assert_async().join()
     }
 
@@ -156,6 +284,7 @@ impl<'a> Mock<'a> {
     /// # Panics
     /// This method will panic if there is a problem with the (standalone) mock server.
     pub fn assert_hits(&self, hits: usize) {
+        assert_not_in_runtime("Mock::assert_hits", "Mock::assert_hits_async");
         self.assert_hits_async(hits).join()
     }
 
@@ -190,37 +319,34 @@ impl<'a> Mock<'a> {
     /// # Panics
     /// This method will panic if there is a problem with the (standalone) mock server.
     pub async fn assert_hits_async(&self, hits: usize) {
-        let active_mock = self
-            .server
-            .server_adapter
-            .as_ref()
-            .unwrap()
-            .fetch_mock(self.id)
+        let adapter = self.server.server_adapter.as_ref().unwrap();
+        let call_counter = adapter
+            .fetch_mock_hits(self.id)
             .await
             .expect("cannot deserialize mock server response");
-
-        if active_mock.call_counter == hits {
+        if call_counter == hits {
             return;
         }
 
-        if active_mock.call_counter > hits {
+        if call_counter > hits {
             assert_eq!(
-                active_mock.call_counter, hits,
+                call_counter, hits,
                 "The number of matching requests was higher than expected (expected {} but was {})",
-                hits, active_mock.call_counter
+                hits, call_counter
             )
         }
 
-        let closest_match = self
-            .server
-            .server_adapter
-            .as_ref()
-            .unwrap()
+        // The request requirements are only needed to find the closest match.
+        let active_mock = adapter
+            .fetch_mock(self.id)
+            .await
+            .expect("cannot deserialize mock server response");
+        let closest_match = adapter
             .verify(&active_mock.definition.request)
             .await
             .expect("Cannot contact mock server");
 
-        fail_with(active_mock.call_counter, hits, closest_match)
+        fail_with(call_counter, hits, closest_match)
     }
 
     /// This method returns the number of times a mock has been called at the mock server.
@@ -246,6 +372,7 @@ impl<'a> Mock<'a> {
     /// # Panics
     /// This method will panic if there is a problem with the (standalone) mock server.
     pub fn hits(&self) -> usize {
+        assert_not_in_runtime("Mock::hits", "Mock::hits_async");
         self.hits_async().join()
     }
 
@@ -276,7 +403,88 @@ impl<'a> Mock<'a> {
     /// # Panics
     /// This method will panic if there is a problem with the (standalone) mock server.
     pub async fn hits_async(&self) -> usize {
-        let response = self
+        self.try_hits_async()
+            .await
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Returns the number of times this mock has been called at the mock server like
+    /// [Mock::hits](struct.Mock.html#method.hits), but returns an error instead of panicking if
+    /// the number cannot be fetched from the mock server.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use httpmock::MockError;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mut mock = server.mock(|when, then| {
+    ///     when.method(GET).path("/hits");
+    ///     then.status(200);
+    /// });
+    ///
+    /// assert_eq!(mock.try_hits(), Ok(0));
+    ///
+    /// mock.delete();
+    ///
+    /// match mock.try_hits() {
+    ///     Err(MockError::ServerRejected { status, .. }) => assert_eq!(status, 404),
+    ///     other => panic!("unexpected result: {:?}", other),
+    /// }
+    /// ```
+    pub fn try_hits(&self) -> Result<usize, MockError> {
+        assert_not_in_runtime("Mock::try_hits", "Mock::try_hits_async");
+        self.try_hits_async().join()
+    }
+
+    /// Returns the number of times this mock has been called at the mock server. Please refer
+    /// to [Mock::try_hits](struct.Mock.html#method.try_hits) for details.
+    pub async fn try_hits_async(&self) -> Result<usize, MockError> {
+        self.server
+            .server_adapter
+            .as_ref()
+            .unwrap()
+            .fetch_mock_hits(self.id)
+            .await
+            .map_err(|e| MockError::new(self.description(), e))
+    }
+
+    /// Returns a [curl](https://curl.se/) command that sends an example request matching this
+    /// mock, so that the expected request can be reproduced without a Rust toolchain. The
+    /// command contains the method, the URL with all required query parameters, the required
+    /// headers and cookies as well as a body that satisfies exact and JSON body matchers.
+    /// Matchers that cannot be satisfied by a concrete value (e.g. regex matchers) are described
+    /// in comments above the command and use `<value>` placeholders.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    /// let mock = server.mock(|when, then| {
+    ///     when.method(POST).path("/users").body("Fred");
+    ///     then.status(201);
+    /// });
+    ///
+    /// assert_eq!(
+    ///     mock.to_curl(),
+    ///     format!("curl -X POST '{}/users' \\\n  --data-raw 'Fred'", server.base_url())
+    /// );
+    /// ```
+    /// # Panics
+    /// This method will panic if there is a problem with the (standalone) mock server.
+    pub fn to_curl(&self) -> String {
+        self.to_curl_async().join()
+    }
+
+    /// Returns a [curl](https://curl.se/) command that sends an example request matching this
+    /// mock. Please refer to [Mock::to_curl](struct.Mock.html#method.to_curl) for details.
+    ///
+    /// # Panics
+    /// This method will panic if there is a problem with the (standalone) mock server.
+    pub async fn to_curl_async(&self) -> String {
+        let mock = self
             .server
             .server_adapter
             .as_ref()
@@ -285,7 +493,8 @@ impl<'a> Mock<'a> {
             .await
             .expect("cannot deserialize mock server response");
 
-        response.call_counter
+        let base_url = self.server.mock_base_url(&mock.definition.request);
+        crate::common::curl::mock_to_curl(&base_url, &mock.definition.request)
     }
 
     /// Deletes the associated mock object from the mock server.
@@ -318,6 +527,7 @@ impl<'a> Mock<'a> {
     /// assert_eq!(response2.status(), 404);
     /// ```
     pub fn delete(&mut self) {
+        assert_not_in_runtime("Mock::delete", "Mock::delete_async");
         self.delete_async().join();
     }
 
@@ -356,13 +566,153 @@ impl<'a> Mock<'a> {
     /// });
     /// ```
     pub async fn delete_async(&self) {
+        self.try_delete_async()
+            .await
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Deletes this mock from the mock server like
+    /// [Mock::delete](struct.Mock.html#method.delete), but returns an error instead of
+    /// panicking if the mock cannot be deleted, e.g. because it has already been deleted.
+    pub fn try_delete(&mut self) -> Result<(), MockError> {
+        assert_not_in_runtime("Mock::try_delete", "Mock::try_delete_async");
+        self.try_delete_async().join()
+    }
+
+    /// Deletes this mock from the mock server. Please refer to
+    /// [Mock::try_delete](struct.Mock.html#method.try_delete) for details.
+    pub async fn try_delete_async(&self) -> Result<(), MockError> {
         self.server
             .server_adapter
             .as_ref()
             .unwrap()
             .delete_mock(self.id)
             .await
-            .expect("could not delete mock from server");
+            .map_err(|e| MockError::new(self.description(), e))
+    }
+
+    /// Enables this mock, so that it matches requests again (as long as the current time is
+    /// within its activation window, see [When::active_from](struct.When.html#method.active_from)).
+    /// This allows to simulate a service that comes online in the middle of a test, without
+    /// creating the mock at that point. The hit counter of the mock is not reset.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/health").disabled();
+    ///     then.status(200);
+    /// });
+    ///
+    /// assert_eq!(isahc::get(server.url("/health")).unwrap().status(), 404);
+    ///
+    /// mock.enable();
+    ///
+    /// assert_eq!(isahc::get(server.url("/health")).unwrap().status(), 200);
+    /// mock.assert();
+    /// ```
+    /// # Panics
+    /// This method will panic if the mock cannot be found or if there is a problem with the
+    /// (standalone) mock server.
+    pub fn enable(&self) {
+        assert_not_in_runtime("Mock::enable", "Mock::enable_async");
+        self.enable_async().join()
+    }
+
+    /// Enables this mock. This method is the asynchronous equivalent of
+    /// [Mock::enable](struct.Mock.html#method.enable).
+    pub async fn enable_async(&self) {
+        self.set_enabled_async(true).await
+    }
+
+    /// Disables this mock, so that it does not match any request until it is enabled again
+    /// with [Mock::enable](struct.Mock.html#method.enable). Unlike
+    /// [Mock::delete](struct.Mock.html#method.delete), this keeps the hit counter of the mock.
+    ///
+    /// # Panics
+    /// This method will panic if the mock cannot be found or if there is a problem with the
+    /// (standalone) mock server.
+    pub fn disable(&self) {
+        assert_not_in_runtime("Mock::disable", "Mock::disable_async");
+        self.disable_async().join()
+    }
+
+    /// Disables this mock. This method is the asynchronous equivalent of
+    /// [Mock::disable](struct.Mock.html#method.disable).
+    pub async fn disable_async(&self) {
+        self.set_enabled_async(false).await
+    }
+
+    async fn set_enabled_async(&self, enabled: bool) {
+        self.server
+            .server_adapter
+            .as_ref()
+            .unwrap()
+            .set_mock_enabled(self.id, enabled)
+            .await
+            .map_err(|e| MockError::new(self.description(), e))
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Builds the URL of the path that the associated mock object matches (see
+    /// [MockServer::url](struct.MockServer.html#method.url)).
+    ///
+    /// # Example
+    /// ```
+    /// // Arrange
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hits");
+    ///     then.status(200);
+    /// });
+    ///
+    /// // Act: Send a request
+    /// let response = isahc::get(mock.url()).unwrap();
+    ///
+    /// // Assert
+    /// assert_eq!(mock.url(), server.url("/hits"));
+    /// mock.assert();
+    /// ```
+    /// # Panics
+    /// This method will panic if the mock does not match an exact path (see
+    /// [When::path](struct.When.html#method.path)) or if there is a problem with the
+    /// (standalone) mock server.
+    pub fn url(&self) -> String {
+        self.url_async().join()
+    }
+
+    /// Builds the URL of the path that the associated mock object matches (see
+    /// [Mock::url](struct.Mock.html#method.url)).
+    ///
+    /// # Panics
+    /// This method will panic if the mock does not match an exact path (see
+    /// [When::path](struct.When.html#method.path)) or if there is a problem with the
+    /// (standalone) mock server.
+    pub async fn url_async(&self) -> String {
+        let response = self
+            .server
+            .server_adapter
+            .as_ref()
+            .unwrap()
+            .fetch_mock(self.id)
+            .await
+            .expect("cannot deserialize mock server response");
+
+        let request = response.definition.request;
+        match &request.path {
+            Some(path) => format!(
+                "{}/{}",
+                self.server.mock_base_url(&request),
+                path.trim_start_matches('/')
+            ),
+            None => panic!("The mock does not match an exact path"),
+        }
     }
 
     /// Returns the address of the mock server where the associated mock object is store on.
@@ -379,7 +729,7 @@ impl<'a> Mock<'a> {
     /// // where 12345 is the port that the mock server is running on.
     /// ```
     pub fn server_address(&self) -> &SocketAddr {
-        self.server.server_adapter.as_ref().unwrap().address()
+        self.server.address()
     }
 }
 
@@ -434,6 +784,7 @@ impl<'a> MockExt<'a> for Mock<'a> {
         Mock {
             id,
             server: mock_server,
+            description: None,
         }
     }
 
@@ -531,6 +882,13 @@ fn fail_with(actual_hits: usize, expected_hits: usize, closest_match: Option<Clo
                 "Here is a comparison with the most similar non-matching request (request number {}): \n\n",
                 closest_match.request_index + 1
             ));
+            output.push_str(&format!("{}\n\n", closest_match.request));
+            if let Some(stage) = closest_match.mismatches.iter().find_map(|mm| mm.stage) {
+                output.push_str(&format!(
+                    "The request first fails the {} requirements of the mock.\n\n",
+                    stage
+                ));
+            }
 
             for (idx, mm) in closest_match.mismatches.iter().enumerate() {
                 output.push_str(&create_mismatch_output(idx, &mm));
@@ -552,16 +910,19 @@ fn fail_with(actual_hits: usize, expected_hits: usize, closest_match: Option<Clo
 mod test {
     use crate::api::mock::fail_with;
     use crate::common::data::{
-        ClosestMatch, Diff, DiffResult, HttpMockRequest, Mismatch, Reason, Tokenizer,
+        ClosestMatch, Diff, DiffResult, HttpMockRequest, MatchStage, Mismatch, Reason, Tokenizer,
     };
 
     #[test]
     #[cfg(not(feature = "color"))]
-    #[should_panic(expected = "1 : This is a title\n\
+    #[should_panic(
+        expected = "The request first fails the path requirements of the mock.\n\n\
+    1 : This is a title\n\
     ------------------------------------------------------------------------------------------\n\
     Expected:	[equals]		/toast\n\
     Actual:		             	/test\n\
-    Diff:\n   | t\n---| e\n+++| oa\n   | st")]
+    Diff:\n   | t\n---| e\n+++| oa\n   | st"
+    )]
     fn fail_with_message_test() {
         // Arrange
         let closest_match = ClosestMatch {
@@ -570,7 +931,17 @@ mod test {
                 method: "GET".to_string(),
                 headers: None,
                 query_params: None,
+                raw_query: None,
                 body: None,
+                version: None,
+                client_certificate: None,
+                body_truncated: false,
+                host: None,
+                listener: None,
+                remote_addr: None,
+                forwarded: false,
+                upstream_status: None,
+                namespace: None,
             },
             request_index: 0,
             mismatches: vec![Mismatch {
@@ -591,6 +962,7 @@ mod test {
                     distance: 5.0,
                     tokenizer: Tokenizer::Line,
                 }),
+                stage: Some(MatchStage::Path),
             }],
         };
 
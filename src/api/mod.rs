@@ -1,15 +1,18 @@
 // TODO: Remove this at some point
 #![allow(clippy::needless_lifetimes)]
 
+#[cfg(feature = "regex")]
+pub use adapter::Regex;
 pub use adapter::{
     local::LocalMockServerAdapter, standalone::RemoteMockServerAdapter, Method, MockServerAdapter,
-    Regex,
 };
-pub use mock::{Mock, MockExt};
-pub use server::MockServer;
-pub use spec::{Then, When};
+pub use guard::VerificationGuard;
+pub use mock::{Mock, MockError, MockExt};
+pub use server::{MockServer, MockServerBuilder};
+pub use spec::{IntoStatusCode, Then, When};
 
 mod adapter;
+mod guard;
 mod mock;
 mod server;
 pub mod spec;
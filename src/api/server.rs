@@ -1,63 +1,99 @@
+use crate::api::adapter::local::ServerHandle;
+use crate::api::adapter::standalone::AdminClientConfig;
+use crate::api::guard::VerificationGuard;
+use crate::api::mock::MockError;
 use crate::api::spec::{Then, When};
 use crate::api::{LocalMockServerAdapter, MockServerAdapter, RemoteMockServerAdapter};
-use crate::common::data::{MockDefinition, MockServerHttpResponse, RequestRequirements};
-use crate::common::util::{read_env, with_retry, Join};
-use crate::server::{start_server, MockServerState};
+use crate::common::data::{
+    describe_mock, MockDefinition, MockServerHttpResponse, RequestRequirements, ServerStats,
+    StaticDirectory,
+};
+#[cfg(feature = "graphql")]
+use crate::common::graphql::{GraphQLSchema, GraphQLSpec};
+#[cfg(feature = "grpc")]
+use crate::common::grpc::GrpcCode;
+use crate::common::har::HarOptions;
+#[cfg(feature = "yaml")]
+use crate::common::recording::RecordingOptions;
+#[cfg(feature = "https")]
+use crate::common::util::read_file;
+use crate::common::util::{assert_not_in_runtime, read_env, unique_id, with_retry, Join};
+use crate::server::forward::Upstream;
+use crate::server::request_log::{LogFormat, LogLevel, RequestLogConfig};
+#[cfg(feature = "https")]
+use crate::server::tls::{build_tls_config, ClientAuthConfig, ProxyCa};
+use crate::server::{
+    start_server, ListenerConfig, MockServerState, MockService, OverloadBehavior, ServerConfig,
+    NAMESPACE_BASE_PATH,
+};
 use crate::Mock;
+#[cfg(feature = "grpc")]
+use crate::{Method, Regex};
+#[cfg(feature = "graphql")]
+use async_graphql_parser::types::OperationType;
 use async_object_pool::Pool;
 use std::cell::Cell;
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
-use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
 use std::thread;
-use tokio::task::LocalSet;
+use std::time::Duration;
 
 /// A mock server that is able to receive and respond to HTTP requests.
 pub struct MockServer {
     pub(crate) server_adapter: Option<Arc<dyn MockServerAdapter + Send + Sync>>,
-    pool: Arc<Pool<Arc<dyn MockServerAdapter + Send + Sync>>>,
+    pool: Option<Arc<Pool<Arc<dyn MockServerAdapter + Send + Sync>>>>,
+    #[cfg(feature = "graphql")]
+    graphql_schema: Mutex<Option<Arc<GraphQLSchema>>>,
+    verify_on_drop: bool,
+    base_path: Option<String>,
+    warn_on_conflicts: bool,
 }
 
 impl MockServer {
     async fn from(
         server_adapter: Arc<dyn MockServerAdapter + Send + Sync>,
-        pool: Arc<Pool<Arc<dyn MockServerAdapter + Send + Sync>>>,
+        pool: Option<Arc<Pool<Arc<dyn MockServerAdapter + Send + Sync>>>>,
     ) -> Self {
         with_retry(5, || server_adapter.ping())
             .await
             .expect("Cannot ping mock server.");
-        with_retry(5, || server_adapter.delete_all_mocks())
-            .await
-            .expect("Cannot reset mock server (task: delete mocks).");
-        with_retry(5, || server_adapter.delete_history())
-            .await
-            .expect("Cannot reset mock server (task: delete request history).");
-        Self {
+        let server = Self {
             server_adapter: Some(server_adapter),
             pool,
+            #[cfg(feature = "graphql")]
+            graphql_schema: Mutex::new(None),
+            verify_on_drop: false,
+            base_path: None,
+            warn_on_conflicts: false,
+        };
+        // Pooled servers may still contain the state of a previous test, whereas a new server
+        // starts empty.
+        if server.pool.is_some() {
+            server.reset_async().await;
         }
+        server
     }
 
     /// Asynchronously connects to a remote mock server that is running in standalone mode using
     /// the provided address of the form <host>:<port> (e.g. "127.0.0.1:8080") to establish
     /// the connection.
     pub async fn connect_async(address: &str) -> Self {
-        let addr = address
-            .to_socket_addrs()
-            .expect("Cannot parse address")
-            .find(|addr| addr.is_ipv4())
-            .expect("Not able to resolve the provided host name to an IPv4 address");
-
+        let addr = resolve_address(address);
         let adapter = REMOTE_SERVER_POOL_REF
             .take_or_create(|| Arc::new(RemoteMockServerAdapter::new(addr)))
             .await;
-        Self::from(adapter, REMOTE_SERVER_POOL_REF.clone()).await
+        Self::from(adapter, Some(REMOTE_SERVER_POOL_REF.clone())).await
     }
 
     /// Synchronously connects to a remote mock server that is running in standalone mode using
     /// the provided address of the form <host>:<port> (e.g. "127.0.0.1:8080") to establish
     /// the connection.
     pub fn connect(address: &str) -> Self {
+        assert_not_in_runtime("MockServer::connect", "MockServer::connect_async");
         Self::connect_async(address).join()
     }
 
@@ -77,6 +113,98 @@ impl MockServer {
         Self::connect_from_env_async().join()
     }
 
+    /// Asynchronously connects to a remote mock server like
+    /// [MockServer::connect_async](struct.MockServer.html#method.connect_async), but isolates
+    /// this `MockServer` from all other clients of the remote server. Please refer to
+    /// [MockServer::connect_isolated](struct.MockServer.html#method.connect_isolated) for
+    /// more information.
+    pub async fn connect_isolated_async(address: &str) -> Self {
+        let addr = resolve_address(address);
+        let adapter = RemoteMockServerAdapter::with_namespace(addr, unique_id());
+        Self::from(Arc::new(adapter), None).await
+    }
+
+    /// Synchronously connects to a remote mock server that is running in standalone mode, but
+    /// isolates this `MockServer` from all other clients of the remote server, so that
+    /// concurrent test runs can share one remote server.
+    ///
+    /// Each isolated `MockServer` uses a unique namespace, which is a path prefix that is part
+    /// of its [base URL](struct.MockServer.html#method.base_url) (e.g.
+    /// `http://127.0.0.1:5000/__httpmock_ns__/5f2b0c1e9a7d4e36`). The remote server removes
+    /// the prefix before matching, so mocks are defined with the usual paths. Mocks of the
+    /// namespace only match requests that are sent to the namespace, and deleting mocks or
+    /// the request history, as well as the reset when the `MockServer` is created and
+    /// dropped, only affect the namespace. Server statistics are not namespaced.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::connect_isolated("127.0.0.1:5000");
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/hello")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn connect_isolated(address: &str) -> Self {
+        Self::connect_isolated_async(address).join()
+    }
+
+    /// Asynchronously starts a local `MockServer` or connects to a remote one, depending on
+    /// the environment. Please refer to
+    /// [MockServer::start_or_connect](struct.MockServer.html#method.start_or_connect) for
+    /// more information.
+    pub async fn start_or_connect_async() -> Self {
+        match std::env::var("HTTPMOCK_HOST") {
+            Ok(host) if !host.is_empty() => {
+                let port = read_env("HTTPMOCK_PORT", "5000")
+                    .parse::<u16>()
+                    .expect("Cannot parse environment variable HTTPMOCK_PORT to an integer");
+                Self::connect_isolated_async(&format!("{}:{}", host, port)).await
+            }
+            _ => Self::start_async().await,
+        }
+    }
+
+    /// Starts a local `MockServer` or connects to a remote one, depending on the environment,
+    /// so that the same tests can run against a local mock server on a development machine
+    /// and against a shared standalone mock server in CI.
+    ///
+    /// If the environment variable `HTTPMOCK_HOST` is set, this method connects to the remote
+    /// mock server at `HTTPMOCK_HOST` and `HTTPMOCK_PORT` (default: 5000) like
+    /// [MockServer::connect_isolated](struct.MockServer.html#method.connect_isolated), so
+    /// concurrent test runs cannot see each other's mocks, requests or hit counts.
+    /// Otherwise, a local mock server is taken from the pool like
+    /// [MockServer::start](struct.MockServer.html#method.start).
+    ///
+    /// Use [MockServer::url](struct.MockServer.html#method.url) or
+    /// [MockServer::base_url](struct.MockServer.html#method.base_url) to send requests to the
+    /// mock server, as the URL of a remote server contains a path prefix.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start_or_connect();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/hello")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn start_or_connect() -> Self {
+        Self::start_or_connect_async().join()
+    }
+
     /// Starts a new `MockServer` asynchronously.
     ///
     /// Attention: This library manages a pool of `MockServer` instances in the background.
@@ -90,12 +218,18 @@ impl MockServer {
     ///
     /// A `MockServer` instance is automatically taken from the pool whenever this method is called.
     /// The instance is put back into the pool automatically when the corresponding
-    /// 'MockServer' variable gets out of scope.
+    /// 'MockServer' variable gets out of scope. All mocks and the request history are deleted
+    /// when the instance is returned to and again when it is taken from the pool, so a
+    /// `MockServer` never contains any state from previous tests.
+    ///
+    /// The maximum number of pooled servers can be set using the environment variable
+    /// `HTTPMOCK_MAX_SERVERS` (default: 25). To always start a fresh `MockServer` that is not
+    /// managed by the pool, use [MockServer::builder](struct.MockServer.html#method.builder).
     pub async fn start_async() -> Self {
         let adapter = LOCAL_SERVER_POOL_REF
             .take_or_create(LOCAL_SERVER_ADAPTER_GENERATOR)
             .await;
-        Self::from(adapter, LOCAL_SERVER_POOL_REF.clone()).await
+        Self::from(adapter, Some(LOCAL_SERVER_POOL_REF.clone())).await
     }
 
     /// Starts a new `MockServer` synchronously.
@@ -111,193 +245,3333 @@ impl MockServer {
     ///
     /// A `MockServer` instance is automatically taken from the pool whenever this method is called.
     /// The instance is put back into the pool automatically when the corresponding
-    /// 'MockServer' variable gets out of scope.
+    /// 'MockServer' variable gets out of scope. All mocks and the request history are deleted
+    /// when the instance is returned to and again when it is taken from the pool, so a
+    /// `MockServer` never contains any state from previous tests.
+    ///
+    /// The maximum number of pooled servers can be set using the environment variable
+    /// `HTTPMOCK_MAX_SERVERS` (default: 25). To always start a fresh `MockServer` that is not
+    /// managed by the pool, use [MockServer::builder](struct.MockServer.html#method.builder).
     pub fn start() -> MockServer {
+        assert_not_in_runtime("MockServer::start", "MockServer::start_async");
         Self::start_async().join()
     }
 
-    /// The hostname of the `MockServer`. By default, this is `127.0.0.1`.
-    /// In standalone mode, the hostname will be the host where the standalone mock server is
-    /// running.
-    pub fn host(&self) -> String {
-        self.server_adapter.as_ref().unwrap().host()
+    /// Creates a [MockServerBuilder](struct.MockServerBuilder.html) that allows to configure
+    /// a new `MockServer` before starting it.
+    ///
+    /// In contrast to [MockServer::start](struct.MockServer.html#method.start), a server that is
+    /// started by the builder is not taken from the server pool but exclusively created for the
+    /// caller.
+    ///
+    /// **Example**:
+    /// ```
+    /// let server = httpmock::MockServer::builder().start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/hello")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder::new()
     }
 
-    /// The TCP port that the mock server is listening on.
-    pub fn port(&self) -> u16 {
-        self.server_adapter.as_ref().unwrap().port()
+    /// Starts a new `MockServer` asynchronously that acts as an HTTP proxy. Point the proxy
+    /// settings of a client (e.g., the `HTTP_PROXY` and `HTTPS_PROXY` environment variables) to
+    /// the [base URL](struct.MockServer.html#method.base_url) of the proxy to mock third-party
+    /// APIs without changing the URLs that the client uses.
+    ///
+    /// Requests to HTTP targets are sent to the proxy directly, requests to HTTPS targets
+    /// are sent through a tunnel that the client opens with a `CONNECT` request. The proxy
+    /// terminates TLS using certificates that are issued by a certificate authority that is
+    /// generated on startup (see
+    /// [MockServer::proxy_ca_pem](struct.MockServer.html#method.proxy_ca_pem)), so clients
+    /// need to trust it. Tunnels are only supported if the `https` Cargo feature is enabled.
+    ///
+    /// All requests are matched against the mocks of the proxy. Use
+    /// [When::host](struct.When.html#method.host) to tell requests for different hosts apart.
+    pub async fn start_proxy_async() -> Self {
+        Self::builder().proxy(true).start_async().await
     }
 
-    /// Builds the address for a specific path on the mock server.
+    /// Starts a new `MockServer` synchronously that acts as an HTTP proxy. Please refer to
+    /// [MockServer::start_proxy_async](struct.MockServer.html#method.start_proxy_async) for
+    /// more information.
     ///
     /// **Example**:
     /// ```
-    /// // Start a local mock server for exclusive use by this test function.
-    /// let server = httpmock::MockServer::start();
+    /// use httpmock::prelude::*;
+    /// use isahc::config::Configurable;
+    /// use isahc::{HttpClient, Request};
     ///
-    /// let expected_addr_str = format!("127.0.0.1:{}", server.port());
+    /// let proxy = MockServer::start_proxy();
     ///
-    /// // Get the address of the MockServer.
-    /// let addr = server.address();
+    /// let mock = proxy.mock(|when, then| {
+    ///     when.host("example.com").path("/hello");
+    ///     then.status(200);
+    /// });
     ///
-    /// // Ensure the returned URL is as expected
-    /// assert_eq!(expected_addr_str, addr.to_string());
+    /// let client = HttpClient::builder()
+    ///     .proxy(Some(proxy.base_url().parse().unwrap()))
+    ///     .build()
+    ///     .unwrap();
+    /// let response = client.get("http://example.com/hello").unwrap();
+    ///
+    /// assert_eq!(response.status(), 200);
+    /// mock.assert();
     /// ```
-    pub fn address(&self) -> &SocketAddr {
-        self.server_adapter.as_ref().unwrap().address()
+    pub fn start_proxy() -> Self {
+        Self::start_proxy_async().join()
+    }
+
+    /// Returns the PEM encoded certificate of the certificate authority that a proxy mock server
+    /// uses to terminate TLS (see
+    /// [MockServer::start_proxy_async](struct.MockServer.html#method.start_proxy_async)).
+    /// Clients need to trust this certificate to connect to HTTPS targets through the proxy.
+    ///
+    /// > Note: This function is only available if the `https` Cargo feature is enabled.
+    ///
+    /// # Panics
+    /// This method will panic if the mock server is not a proxy.
+    #[cfg(feature = "https")]
+    pub fn proxy_ca_pem(&self) -> String {
+        self.server_adapter
+            .as_ref()
+            .unwrap()
+            .proxy_ca_pem()
+            .expect("The mock server is not a proxy")
+            .to_string()
     }
 
-    /// Builds the URL for a specific path on the mock server.
+    /// Starts a new `MockServer` asynchronously that listens on a Unix domain socket at the
+    /// given path instead of a TCP port. The socket file is created when the server starts
+    /// and removed when the `MockServer` is dropped. A socket file that was left behind at the
+    /// same path is replaced.
+    ///
+    /// Such a server has no TCP address, so use
+    /// [MockServer::socket_path](struct.MockServer.html#method.socket_path) instead of
+    /// [MockServer::address](struct.MockServer.html#method.address) to connect to it.
+    ///
+    /// > Note: This function is only available on Unix platforms.
+    #[cfg(unix)]
+    pub async fn start_unix_async<P: AsRef<Path>>(path: P) -> Self {
+        Self::builder().unix_socket(path).start_async().await
+    }
+
+    /// Starts a new `MockServer` synchronously that listens on a Unix domain socket at the
+    /// given path instead of a TCP port. Please refer to
+    /// [MockServer::start_unix_async](struct.MockServer.html#method.start_unix_async) for more
+    /// information.
+    ///
+    /// > Note: This function is only available on Unix platforms.
     ///
     /// **Example**:
     /// ```
-    /// // Start a local mock server for exclusive use by this test function.
-    /// let server = httpmock::MockServer::start();
+    /// use std::io::{Read, Write};
+    /// use std::os::unix::net::UnixStream;
     ///
-    /// let expected_url = format!("http://127.0.0.1:{}/hello", server.port());
+    /// let path = std::env::temp_dir().join("httpmock-start-unix-doc.sock");
+    /// let server = httpmock::MockServer::start_unix(&path);
     ///
-    /// // Get the URL for path "/hello".
-    /// let url = server.url("/hello");
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/_ping");
+    ///     then.status(200).body("OK");
+    /// });
     ///
-    /// // Ensure the returned URL is as expected
-    /// assert_eq!(expected_url, url);
+    /// let mut stream = UnixStream::connect(server.socket_path().unwrap()).unwrap();
+    /// stream
+    ///     .write_all(b"GET /_ping HTTP/1.1\r\nHost: docker\r\nConnection: close\r\n\r\n")
+    ///     .unwrap();
+    /// let mut response = String::new();
+    /// stream.read_to_string(&mut response).unwrap();
+    ///
+    /// mock.assert();
+    /// assert!(response.starts_with("HTTP/1.1 200 OK"));
     /// ```
-    pub fn url<S: Into<String>>(&self, path: S) -> String {
-        format!("http://{}{}", self.address(), path.into())
+    #[cfg(unix)]
+    pub fn start_unix<P: AsRef<Path>>(path: P) -> Self {
+        Self::start_unix_async(path).join()
     }
 
-    /// Builds the base URL for the mock server.
+    /// The path of the Unix domain socket that the mock server is listening on, or `None` if
+    /// the mock server listens on a TCP port.
+    ///
+    /// > Note: This function is only available on Unix platforms.
+    #[cfg(unix)]
+    pub fn socket_path(&self) -> Option<&Path> {
+        self.server_adapter.as_ref().unwrap().socket_path()
+    }
+
+    /// Starts a new `MockServer` asynchronously that does not listen for network connections
+    /// at all. Requests are sent to it in-process using the service that is returned by
+    /// [MockServer::service](struct.MockServer.html#method.service), which avoids allocating
+    /// ports and the flakiness that comes with it. Mocks, hit counts and assertions work the
+    /// same way as for a server that listens on a TCP port.
+    ///
+    /// Such a server has no TCP address, so [MockServer::address](struct.MockServer.html#method.address)
+    /// will panic and URLs are built with `localhost` as host.
+    pub async fn start_in_process_async() -> Self {
+        Self::builder().start_in_process_async().await
+    }
+
+    /// Starts a new `MockServer` synchronously that does not listen for network connections.
+    /// Please refer to
+    /// [MockServer::start_in_process_async](struct.MockServer.html#method.start_in_process_async)
+    /// for more information.
     ///
     /// **Example**:
     /// ```
-    /// // Start a local mock server for exclusive use by this test function.
-    /// let server = httpmock::MockServer::start();
+    /// use httpmock::prelude::*;
+    /// use hyper::{Body, Request};
     ///
-    /// let expected_url = format!("http://127.0.0.1:{}", server.port());
+    /// let server = MockServer::start_in_process();
     ///
-    /// // Get the URL for path "/hello".
-    /// let url = server.base_url();
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200).body("world");
+    /// });
     ///
-    /// // Ensure the returned URL is as expected
-    /// assert_eq!(expected_url, url);
+    /// let runtime = tokio::runtime::Runtime::new().unwrap();
+    /// let response = runtime.block_on(async {
+    ///     let request = Request::get(server.url("/hello")).body(Body::empty()).unwrap();
+    ///     server.service().handle(request).await
+    /// });
+    ///
+    /// assert_eq!(response.status(), 200);
+    /// mock.assert();
     /// ```
-    pub fn base_url(&self) -> String {
-        self.url("")
+    pub fn start_in_process() -> Self {
+        Self::start_in_process_async().join()
     }
 
-    /// Creates a [Mock](struct.Mock.html) object on the mock server.
+    /// Returns a [MockService](struct.MockService.html) that handles requests in-process,
+    /// without a network connection. Requests are matched against the mocks of this
+    /// `MockServer` and are recorded in its request history, as if they had been received
+    /// over the network.
+    ///
+    /// # Panics
+    /// This method will panic if the `MockServer` was connected to a remote mock server
+    /// (see [MockServer::connect](struct.MockServer.html#method.connect)).
+    pub fn service(&self) -> MockService {
+        self.server_adapter
+            .as_ref()
+            .unwrap()
+            .service()
+            .expect("Only local mock servers can handle requests in-process")
+    }
+
+    /// Deletes all mocks and the request history of the `MockServer` asynchronously.
+    ///
+    /// A `MockServer` is reset automatically when it is started or connected to. This method
+    /// is useful when a `MockServer` is reused for several test cases, such as a remote mock
+    /// server that is shared by multiple test processes and that needs to be cleaned up before
+    /// each test.
+    pub async fn reset_async(&self) {
+        let adapter = self.server_adapter.as_ref().unwrap();
+        with_retry(5, || adapter.reset())
+            .await
+            .expect("Cannot reset mock server.");
+    }
+
+    /// Deletes all mocks and the request history of the `MockServer` synchronously. Please
+    /// refer to [MockServer::reset_async](struct.MockServer.html#method.reset_async) for more
+    /// information.
     ///
     /// **Example**:
     /// ```
-    /// use isahc::get;
-    ///
     /// let server = httpmock::MockServer::start();
     ///
-    /// let mock = server.mock(|when, then| {
+    /// server.mock(|when, then| {
     ///     when.path("/hello");
     ///     then.status(200);
     /// });
     ///
-    /// get(server.url("/hello")).unwrap();
+    /// server.reset();
     ///
-    /// mock.assert();
+    /// let response = isahc::get(server.url("/hello")).unwrap();
+    /// assert_eq!(response.status(), 404);
     /// ```
-    pub fn mock<F>(&self, config_fn: F) -> Mock
-    where
-        F: FnOnce(When, Then),
-    {
-        self.mock_async(config_fn).join()
+    pub fn reset(&self) {
+        assert_not_in_runtime("MockServer::reset", "MockServer::reset_async");
+        self.reset_async().join()
     }
 
-    /// Creates a [Mock](struct.Mock.html) object on the mock server.
+    /// Shuts down the `MockServer` asynchronously. The server stops accepting new connections
+    /// and frees its port right away. Requests that are in flight are completed if they finish
+    /// within the shutdown timeout (see
+    /// [MockServerBuilder::shutdown_timeout](struct.MockServerBuilder.html#method.shutdown_timeout)),
+    /// otherwise their connections are closed. This method returns when the server has
+    /// stopped.
+    ///
+    /// All further operations on this `MockServer` or its mocks will panic. Shutting down a
+    /// `MockServer` more than once has no effect. A `MockServer` is also shut down when it is
+    /// dropped, unless it was taken from the server pool. In that case, in-flight requests are
+    /// only given a short time to complete (see
+    /// [MockServerBuilder::drop_deadline](struct.MockServerBuilder.html#method.drop_deadline)).
+    /// If the `MockServer` is dropped in an async context, the server is stopped in the
+    /// background, so the port may still be in use for that time.
+    ///
+    /// # Panics
+    /// This method will panic if the `MockServer` was connected to a remote mock server
+    /// (see [MockServer::connect](struct.MockServer.html#method.connect)).
+    pub async fn shutdown_async(&self) {
+        self.server_adapter
+            .as_ref()
+            .unwrap()
+            .shutdown()
+            .await
+            .expect("Cannot shut down mock server");
+    }
+
+    /// Shuts down the `MockServer` synchronously. Please refer to
+    /// [MockServer::shutdown_async](struct.MockServer.html#method.shutdown_async) for more
+    /// information.
     ///
     /// **Example**:
     /// ```
-    /// use isahc::{get_async};
-    /// async_std::task::block_on(async {
-    ///     let server = httpmock::MockServer::start();
+    /// let server = httpmock::MockServer::start();
+    /// let url = server.url("/hello");
     ///
-    ///     let mock = server
-    ///         .mock_async(|when, then| {
-    ///             when.path("/hello");
-    ///             then.status(200);
-    ///         })
-    ///         .await;
+    /// server.shutdown();
     ///
-    ///     get_async(server.url("/hello")).await.unwrap();
+    /// assert!(isahc::get(url).is_err());
+    /// ```
+    pub fn shutdown(&self) {
+        self.shutdown_async().join()
+    }
+
+    /// Pauses the `MockServer` asynchronously to simulate an unavailable upstream server.
+    /// A paused server closes all open connections and actively refuses new ones, so
+    /// requests that are sent while the server is paused are neither answered nor counted.
+    /// Mocks, hit counters and the request history are preserved.
     ///
-    ///     mock.assert_async().await;
+    /// Use [MockServer::resume_async](struct.MockServer.html#method.resume_async) to accept
+    /// connections on the same address again. A `MockServer` that is returned to the server
+    /// pool is resumed automatically.
+    ///
+    /// # Panics
+    /// This method will panic if the `MockServer` does not listen for connections itself, such
+    /// as a remote or an in-process mock server.
+    pub async fn pause_async(&self) {
+        self.server_adapter
+            .as_ref()
+            .unwrap()
+            .pause(None)
+            .await
+            .expect("Cannot pause mock server");
+    }
+
+    /// Pauses the `MockServer` synchronously. Please refer to
+    /// [MockServer::pause_async](struct.MockServer.html#method.pause_async) for more
+    /// information.
+    ///
+    /// **Example**:
+    /// ```
+    /// let server = httpmock::MockServer::start();
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200);
     /// });
+    ///
+    /// server.pause();
+    /// assert!(isahc::get(server.url("/hello")).is_err());
+    ///
+    /// server.resume();
+    /// assert_eq!(isahc::get(server.url("/hello")).unwrap().status(), 200);
+    ///
+    /// mock.assert_hits(1);
     /// ```
-    pub async fn mock_async<'a, F>(&'a self, spec_fn: F) -> Mock<'a>
-    where
-        F: FnOnce(When, Then),
-    {
-        let mut req = Rc::new(Cell::new(RequestRequirements::new()));
-        let mut res = Rc::new(Cell::new(MockServerHttpResponse::new()));
+    pub fn pause(&self) {
+        self.pause_async().join()
+    }
 
-        spec_fn(
-            When {
-                expectations: req.clone(),
-            },
-            Then {
-                response_template: res.clone(),
-            },
-        );
+    /// Pauses the `MockServer` asynchronously and resumes it automatically after the given
+    /// duration. This method returns right after the server has been paused. Please refer to
+    /// [MockServer::pause_async](struct.MockServer.html#method.pause_async) for more
+    /// information.
+    pub async fn pause_for_async<D: Into<Duration>>(&self, duration: D) {
+        self.server_adapter
+            .as_ref()
+            .unwrap()
+            .pause(Some(duration.into()))
+            .await
+            .expect("Cannot pause mock server");
+    }
 
-        let response = self
-            .server_adapter
+    /// Pauses the `MockServer` synchronously and resumes it automatically after the given
+    /// duration. Please refer to
+    /// [MockServer::pause_for_async](struct.MockServer.html#method.pause_for_async) for more
+    /// information.
+    pub fn pause_for<D: Into<Duration>>(&self, duration: D) {
+        self.pause_for_async(duration).join()
+    }
+
+    /// Resumes a paused `MockServer` asynchronously, so that it accepts connections on the
+    /// same address again. Resuming a server that is not paused has no effect.
+    ///
+    /// # Panics
+    /// This method will panic if the `MockServer` does not listen for connections itself, or
+    /// if the address has been taken by another process in the meantime.
+    pub async fn resume_async(&self) {
+        self.server_adapter
             .as_ref()
             .unwrap()
-            .create_mock(&MockDefinition {
-                request: req.take(),
-                response: res.take(),
-            })
+            .resume()
             .await
-            .expect("Cannot deserialize mock server response");
+            .expect("Cannot resume mock server");
+    }
 
-        Mock {
-            id: response.mock_id,
-            server: self,
-        }
+    /// Resumes a paused `MockServer` synchronously. Please refer to
+    /// [MockServer::resume_async](struct.MockServer.html#method.resume_async) for more
+    /// information.
+    pub fn resume(&self) {
+        self.resume_async().join()
     }
-}
 
-impl Drop for MockServer {
-    fn drop(&mut self) {
-        let adapter = self.server_adapter.take().unwrap();
-        self.pool.put(adapter).join();
+    /// The number of connections that were refused and requests that were answered with
+    /// `503 Service Unavailable` because the connection limit of the `MockServer` was reached
+    /// (see [MockServerBuilder::max_connections](struct.MockServerBuilder.html#method.max_connections)).
+    /// Such requests do not match any mock and are not recorded in the request history. The
+    /// counter is reset together with the request history.
+    ///
+    /// # Panics
+    /// This method will panic if the `MockServer` was connected to a remote mock server
+    /// (see [MockServer::connect](struct.MockServer.html#method.connect)).
+    pub fn overload_rejections(&self) -> usize {
+        self.server_adapter
+            .as_ref()
+            .unwrap()
+            .overload_rejections()
+            .expect("Overload statistics are only available for local mock servers")
     }
-}
 
-const LOCAL_SERVER_ADAPTER_GENERATOR: fn() -> Arc<dyn MockServerAdapter + Send + Sync> = || {
-    let (addr_sender, addr_receiver) = tokio::sync::oneshot::channel::<SocketAddr>();
-    let state = Arc::new(MockServerState::default());
-    let server_state = state.clone();
+    /// Logs every request that the `MockServer` receives from now on, along with the mock that
+    /// it matched and the response status. Requests to the admin API are not logged.
+    ///
+    /// The log is written with the [log](https://crates.io/crates/log) crate at the given
+    /// level, so a logger (e.g. `env_logger`) needs to be initialized that shows records of the
+    /// `httpmock` target at this level. The format is one of
+    /// * [LogFormat::Pretty](enum.LogFormat.html#variant.Pretty): a readable record with the
+    /// method, path, headers and body of the request, in which JSON bodies are pretty-printed.
+    /// * [LogFormat::Json](enum.LogFormat.html#variant.Json): a JSON object per line with the
+    /// fields `method`, `path`, `headers`, `body`, `mock_id`, `mock` and `status`.
+    ///
+    /// Bodies are cut off after 4096 bytes (see
+    /// [MockServer::log_body_limit](struct.MockServer.html#method.log_body_limit)). Use
+    /// [MockServer::redact_headers](struct.MockServer.html#method.redact_headers) to keep
+    /// secrets out of the log. A `MockServer` that is returned to the server pool stops
+    /// logging requests.
+    ///
+    /// # Panics
+    /// This method will panic if the `MockServer` was connected to a remote mock server
+    /// (see [MockServer::connect](struct.MockServer.html#method.connect)).
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::{LogFormat, LogLevel, MockServer};
+    ///
+    /// let server = MockServer::start();
+    /// server.log_requests(LogLevel::Debug, LogFormat::Pretty);
+    /// server.redact_headers(&["Authorization"]);
+    ///
+    /// // Logs "GET /hello did not match any mock, response status 404" and the headers.
+    /// isahc::get(server.url("/hello")).unwrap();
+    /// ```
+    pub fn log_requests(&self, level: LogLevel, format: LogFormat) {
+        self.update_request_log(|config| config.enabled = Some((level, format)));
+    }
 
-    thread::spawn(move || {
-        let server_state = server_state.clone();
-        let srv = start_server(0, false, &server_state, Some(addr_sender), false);
+    /// Stops logging the requests that the `MockServer` receives (see
+    /// [MockServer::log_requests](struct.MockServer.html#method.log_requests)).
+    pub fn stop_logging_requests(&self) {
+        self.update_request_log(|config| config.enabled = None);
+    }
 
-        let mut runtime = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .expect("Cannot build local tokio runtime");
+    /// Sets the headers whose values are replaced by `[REDACTED]` in the request log (see
+    /// [MockServer::log_requests](struct.MockServer.html#method.log_requests)), e.g.
+    /// `Authorization`. Header names are case-insensitive. Replaces previously set names.
+    pub fn redact_headers<S: AsRef<str>>(&self, names: &[S]) {
+        let names = names.iter().map(|n| n.as_ref().to_lowercase()).collect();
+        self.update_request_log(|config| config.redact_headers = names);
+    }
 
-        LocalSet::new().block_on(&mut runtime, srv)
-    });
+    /// Sets the maximum number of body bytes that are written to the request log (see
+    /// [MockServer::log_requests](struct.MockServer.html#method.log_requests)). Longer bodies
+    /// are cut off and are not pretty-printed. Defaults to 4096.
+    pub fn log_body_limit(&self, max_bytes: usize) {
+        self.update_request_log(|config| config.max_body_bytes = max_bytes);
+    }
 
-    let addr = addr_receiver.join().expect("Cannot get server address");
-    Arc::new(LocalMockServerAdapter::new(addr, state))
-};
+    fn update_request_log<F: FnOnce(&mut RequestLogConfig)>(&self, update: F) {
+        let request_log = self
+            .server_adapter
+            .as_ref()
+            .unwrap()
+            .request_log()
+            .expect("Request logging is only available for local mock servers");
+        update(&mut request_log.write().unwrap());
+    }
 
-lazy_static! {
-    static ref LOCAL_SERVER_POOL_REF: Arc<Pool<Arc<dyn MockServerAdapter + Send + Sync>>> = {
-        let max_servers = read_env("HTTPMOCK_MAX_SERVERS", "25")
-            .parse::<usize>()
-            .expect("Cannot parse environment variable HTTPMOCK_MAX_SERVERS to an integer");
-        Arc::new(Pool::new(max_servers))
-    };
-    static ref REMOTE_SERVER_POOL_REF: Arc<Pool<Arc<dyn MockServerAdapter + Send + Sync>>> =
-        Arc::new(Pool::new(1));
+    /// The hostname of the `MockServer`. By default, this is `127.0.0.1`.
+    /// In standalone mode, the hostname will be the host where the standalone mock server is
+    /// running.
+    ///
+    /// # Panics
+    /// This method will panic if the mock server listens on a Unix domain socket.
+    pub fn host(&self) -> String {
+        self.expect_tcp();
+        self.server_adapter.as_ref().unwrap().host()
+    }
+
+    /// The TCP port that the mock server is listening on.
+    ///
+    /// # Panics
+    /// This method will panic if the mock server listens on a Unix domain socket.
+    pub fn port(&self) -> u16 {
+        self.expect_tcp();
+        self.server_adapter.as_ref().unwrap().port()
+    }
+
+    fn expect_tcp(&self) {
+        let adapter = self.server_adapter.as_ref().unwrap();
+        if let Some(path) = adapter.socket_path() {
+            panic!(
+                "The mock server listens on Unix domain socket {:?} and has no TCP address",
+                path
+            );
+        }
+        if adapter.is_in_process() {
+            panic!("The mock server only serves requests in-process and has no TCP address");
+        }
+    }
+
+    /// Builds the address for a specific path on the mock server.
+    ///
+    /// **Example**:
+    /// ```
+    /// // Start a local mock server for exclusive use by this test function.
+    /// let server = httpmock::MockServer::start();
+    ///
+    /// let expected_addr_str = format!("127.0.0.1:{}", server.port());
+    ///
+    /// // Get the address of the MockServer.
+    /// let addr = server.address();
+    ///
+    /// // Ensure the returned URL is as expected
+    /// assert_eq!(expected_addr_str, addr.to_string());
+    /// ```
+    ///
+    /// # Panics
+    /// This method will panic if the mock server listens on a Unix domain socket.
+    pub fn address(&self) -> &SocketAddr {
+        self.expect_tcp();
+        self.server_adapter.as_ref().unwrap().address()
+    }
+
+    /// The addresses of all TCP listeners of the mock server, starting with the primary
+    /// [address](struct.MockServer.html#method.address). A mock server has more than one
+    /// listener if it was started with
+    /// [MockServerBuilder::listen](struct.MockServerBuilder.html#method.listen).
+    ///
+    /// # Panics
+    /// This method will panic if the mock server listens on a Unix domain socket.
+    pub fn addresses(&self) -> Vec<SocketAddr> {
+        self.expect_tcp();
+        let adapter = self.server_adapter.as_ref().unwrap();
+        adapter
+            .listener_addresses()
+            .into_iter()
+            .map(|(_, addr)| addr)
+            .collect()
+    }
+
+    /// The address of the listener with the given name (see
+    /// [MockServerBuilder::listen_named](struct.MockServerBuilder.html#method.listen_named)).
+    ///
+    /// # Panics
+    /// This method will panic if there is no listener with the given name.
+    pub fn listener_address(&self, name: &str) -> SocketAddr {
+        self.expect_tcp();
+        let adapter = self.server_adapter.as_ref().unwrap();
+        adapter
+            .listener_addresses()
+            .into_iter()
+            .find(|(n, _)| n.as_deref() == Some(name))
+            .map(|(_, addr)| addr)
+            .unwrap_or_else(|| panic!("The mock server has no listener named {:?}", name))
+    }
+
+    /// Builds the URL for a specific path on the listener with the given name. Please refer to
+    /// [MockServer::url](struct.MockServer.html#method.url) for more information.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::builder()
+    ///     .listen_named("api", "127.0.0.1:0")
+    ///     .listen_named("cdn", "127.0.0.1:0")
+    ///     .start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.listener("cdn").path("/logo.png");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.listener_url("cdn", "/logo.png")).unwrap();
+    /// isahc::get(server.listener_url("api", "/logo.png")).unwrap();
+    ///
+    /// mock.assert_hits(1);
+    /// ```
+    ///
+    /// # Panics
+    /// This method will panic if there is no listener with the given name.
+    pub fn listener_url<S: Into<String>>(&self, name: &str, path: S) -> String {
+        let base_url = format!(
+            "{}://{}{}",
+            self.scheme(),
+            self.listener_address(name),
+            self.base_path.as_deref().unwrap_or("")
+        );
+        join_url(base_url, path.into())
+    }
+
+    /// Builds the URL for a specific path on the mock server. The path is joined to the
+    /// [base URL](struct.MockServer.html#method.base_url) with exactly one slash, regardless
+    /// of whether it starts with a slash or not. The URL scheme is `https` for HTTPS mock servers.
+    /// If the mock server listens on a Unix domain socket, the URL host is `localhost`.
+    ///
+    /// **Example**:
+    /// ```
+    /// // Start a local mock server for exclusive use by this test function.
+    /// let server = httpmock::MockServer::start();
+    ///
+    /// let expected_url = format!("http://127.0.0.1:{}/hello", server.port());
+    ///
+    /// // Get the URL for path "/hello".
+    /// let url = server.url("/hello");
+    ///
+    /// // Ensure the returned URL is as expected
+    /// assert_eq!(expected_url, url);
+    /// assert_eq!(expected_url, server.url("hello"));
+    /// ```
+    pub fn url<S: Into<String>>(&self, path: S) -> String {
+        join_url(self.base_url(), path.into())
+    }
+
+    /// Builds the base URL for the mock server.
+    ///
+    /// **Example**:
+    /// ```
+    /// // Start a local mock server for exclusive use by this test function.
+    /// let server = httpmock::MockServer::start();
+    ///
+    /// let expected_url = format!("http://127.0.0.1:{}", server.port());
+    ///
+    /// // Get the URL for path "/hello".
+    /// let url = server.base_url();
+    ///
+    /// // Ensure the returned URL is as expected
+    /// assert_eq!(expected_url, url);
+    /// ```
+    pub fn base_url(&self) -> String {
+        format!(
+            "{}{}",
+            self.root_url(),
+            self.base_path.as_deref().unwrap_or("")
+        )
+    }
+
+    /// Returns the base URL for requests that match a mock, which ends with the base path of
+    /// the mock.
+    pub(crate) fn mock_base_url(&self, request: &RequestRequirements) -> String {
+        format!(
+            "{}{}",
+            self.root_url(),
+            request.base_path.as_deref().unwrap_or("")
+        )
+    }
+
+    /// Returns the URL of the mock server without the base path.
+    fn root_url(&self) -> String {
+        let adapter = self.server_adapter.as_ref().unwrap();
+        if adapter.socket_path().is_some() || adapter.is_in_process() {
+            return format!("{}://localhost", self.scheme());
+        }
+
+        match adapter.namespace() {
+            Some(namespace) => format!(
+                "{}://{}{}/{}",
+                self.scheme(),
+                adapter.address(),
+                NAMESPACE_BASE_PATH,
+                namespace
+            ),
+            None => format!("{}://{}", self.scheme(), adapter.address()),
+        }
+    }
+
+    fn scheme(&self) -> &'static str {
+        match self.server_adapter.as_ref().unwrap().is_https() {
+            true => "https",
+            false => "http",
+        }
+    }
+
+    /// Creates a [Mock](struct.Mock.html) object on the mock server.
+    ///
+    /// **Example**:
+    /// ```
+    /// use isahc::get;
+    ///
+    /// let server = httpmock::MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200);
+    /// });
+    ///
+    /// get(server.url("/hello")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn mock<F>(&self, config_fn: F) -> Mock
+    where
+        F: FnOnce(When, Then),
+    {
+        assert_not_in_runtime("MockServer::mock", "MockServer::mock_async");
+        self.mock_async(config_fn).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) object on the mock server.
+    ///
+    /// **Example**:
+    /// ```
+    /// use isahc::{get_async};
+    /// async_std::task::block_on(async {
+    ///     let server = httpmock::MockServer::start();
+    ///
+    ///     let mock = server
+    ///         .mock_async(|when, then| {
+    ///             when.path("/hello");
+    ///             then.status(200);
+    ///         })
+    ///         .await;
+    ///
+    ///     get_async(server.url("/hello")).await.unwrap();
+    ///
+    ///     mock.assert_async().await;
+    /// });
+    /// ```
+    pub async fn mock_async<'a, F>(&'a self, spec_fn: F) -> Mock<'a>
+    where
+        F: FnOnce(When, Then),
+    {
+        self.try_mock_async(spec_fn)
+            .await
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a [Mock](struct.Mock.html) object on the mock server. This is the same as
+    /// [MockServer::mock](struct.MockServer.html#method.mock) and is meant for code that prefers
+    /// to name the two-closure style explicitly. Request requirements are set on
+    /// [When](struct.When.html) and the response on [Then](struct.Then.html), so mixing them up
+    /// does not compile.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock_with(|when, then| {
+    ///     when.method(GET).path("/search").query_param("query", "metallica");
+    ///     then.status(204).header("X-Trace", "1");
+    /// });
+    ///
+    /// let response = isahc::get(server.url("/search?query=metallica")).unwrap();
+    ///
+    /// mock.assert();
+    /// assert_eq!(response.status(), 204);
+    /// ```
+    pub fn mock_with<F>(&self, config_fn: F) -> Mock
+    where
+        F: FnOnce(When, Then),
+    {
+        assert_not_in_runtime("MockServer::mock_with", "MockServer::mock_with_async");
+        self.mock_async(config_fn).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) object on the mock server asynchronously. This is the
+    /// same as [MockServer::mock_async](struct.MockServer.html#method.mock_async).
+    pub async fn mock_with_async<'a, F>(&'a self, config_fn: F) -> Mock<'a>
+    where
+        F: FnOnce(When, Then),
+    {
+        self.mock_async(config_fn).await
+    }
+
+    /// Creates a [Mock](struct.Mock.html) that matches requests with the given method and path
+    /// and responds with status 200 and an empty body. The method can be any method name,
+    /// including nonstandard methods such as `PROPFIND`, `PURGE` or `REPORT` (see
+    /// [Method](enum.Method.html)). To set further requirements or the response, use
+    /// [MockServer::mock](struct.MockServer.html#method.mock) with
+    /// [When::method](struct.When.html#method.method) instead.
+    ///
+    /// **Example**:
+    /// ```
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = httpmock::MockServer::start();
+    ///
+    /// let mock = server.mock_with_method_str("PURGE", "/images/logo.png");
+    ///
+    /// let response = Request::builder()
+    ///     .method("PURGE")
+    ///     .uri(server.url("/images/logo.png"))
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// assert_eq!(response.status(), 200);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the method is not a valid HTTP method name.
+    pub fn mock_with_method_str(&self, method: &str, path: &str) -> Mock {
+        assert_not_in_runtime(
+            "MockServer::mock_with_method_str",
+            "MockServer::mock_with_method_str_async",
+        );
+        self.mock_with_method_str_async(method, path).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) that matches requests with the given method and path
+    /// asynchronously (see
+    /// [MockServer::mock_with_method_str](struct.MockServer.html#method.mock_with_method_str)).
+    ///
+    /// # Panics
+    /// Panics if the method is not a valid HTTP method name.
+    pub async fn mock_with_method_str_async<'a>(&'a self, method: &str, path: &str) -> Mock<'a> {
+        self.mock_async(|when, _| {
+            when.method(method).path(path);
+        })
+        .await
+    }
+
+    /// Creates a [Mock](struct.Mock.html) object on the mock server like
+    /// [MockServer::mock](struct.MockServer.html#method.mock), but returns an error instead of
+    /// panicking if the mock cannot be created. This allows to handle failures gracefully, e.g.
+    /// to retry if a standalone mock server is not reachable yet. Error messages contain the
+    /// method and path of the mock.
+    ///
+    /// Mocks whose request requirements contradict each other, so that no request can match
+    /// them, are rejected with [MockError::InvalidMockDefinition](enum.MockError.html). This
+    /// includes requirements with a single value that are set twice to different values (e.g.
+    /// two paths) and exact bodies that do not fulfill the other body requirements (e.g. a
+    /// body that is not the expected JSON body). Please refer to
+    /// [MockServerBuilder::warn_on_conflicts](struct.MockServerBuilder.html#method.warn_on_conflicts)
+    /// to log a warning instead.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use httpmock::MockError;
+    ///
+    /// let server = MockServer::builder().start();
+    /// server.shutdown();
+    ///
+    /// let result = server.try_mock(|when, then| {
+    ///     when.method(GET).path("/search");
+    ///     then.status(200);
+    /// });
+    ///
+    /// match result {
+    ///     Err(MockError::ConnectionFailed { mock, .. }) => assert_eq!(mock, "GET /search"),
+    ///     _ => panic!("expected a connection error"),
+    /// }
+    /// ```
+    pub fn try_mock<F>(&self, spec_fn: F) -> Result<Mock, MockError>
+    where
+        F: FnOnce(When, Then),
+    {
+        assert_not_in_runtime("MockServer::try_mock", "MockServer::try_mock_async");
+        self.try_mock_async(spec_fn).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) object on the mock server. Please refer to
+    /// [MockServer::try_mock](struct.MockServer.html#method.try_mock) for details.
+    pub async fn try_mock_async<'a, F>(&'a self, spec_fn: F) -> Result<Mock<'a>, MockError>
+    where
+        F: FnOnce(When, Then),
+    {
+        let mut requirements = RequestRequirements::new();
+        requirements.base_path = self.base_path.clone();
+        let mut req = Rc::new(Cell::new(requirements));
+        let mut res = Rc::new(Cell::new(MockServerHttpResponse::new()));
+        let conflicts = Rc::new(Cell::new(Vec::new()));
+        #[cfg(feature = "graphql")]
+        let graphql = Rc::new(Cell::new(GraphQLSpec::default()));
+
+        spec_fn(
+            When {
+                expectations: req.clone(),
+                conflicts: conflicts.clone(),
+                #[cfg(feature = "graphql")]
+                graphql: graphql.clone(),
+            },
+            Then {
+                response_template: res.clone(),
+                #[cfg(feature = "graphql")]
+                graphql: graphql.clone(),
+            },
+        );
+
+        let definition = MockDefinition {
+            request: req.take(),
+            response: res.take(),
+        };
+        let description = describe_mock(&definition.request);
+
+        let mut conflicts = conflicts.take();
+        conflicts.extend(definition.request.conflicts());
+        if !conflicts.is_empty() {
+            let reason = format!(
+                "the request requirements contradict each other, so that no request can match \
+                 the mock: {}",
+                conflicts.join("; ")
+            );
+            match self.warn_on_conflicts {
+                true => log::warn!("Mock {}: {}", description, reason),
+                false => {
+                    return Err(MockError::InvalidMockDefinition {
+                        mock: description,
+                        reason,
+                    })
+                }
+            }
+        }
+
+        #[cfg(feature = "graphql")]
+        self.validate_graphql_data(&graphql.take())
+            .map_err(|reason| MockError::InvalidMockDefinition {
+                mock: description.clone(),
+                reason,
+            })?;
+
+        let response = self
+            .server_adapter
+            .as_ref()
+            .unwrap()
+            .create_mock(&definition)
+            .await
+            .map_err(|e| MockError::new(description, e))?;
+
+        Ok(Mock::for_request(
+            response.mock_id,
+            self,
+            &definition.request,
+        ))
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for a unary gRPC method, identified by its full
+    /// name (`package.Service/Method`). The mock matches `POST` requests to the path of the
+    /// method with a gRPC content type. It responds with status `200`, the content type
+    /// `application/grpc` and a `grpc-status` trailer of `0` (OK), which can be changed with
+    /// [Then::grpc_status](struct.Then.html#method.grpc_status). Use
+    /// [When::grpc_request](struct.When.html#method.grpc_request) to match the
+    /// request message and [Then::grpc_response](struct.Then.html#method.grpc_response) to set
+    /// the response message.
+    ///
+    /// gRPC clients connect with HTTP/2 prior knowledge, which needs to be enabled with
+    /// [MockServerBuilder::http2_prior_knowledge](struct.MockServerBuilder.html#method.http2_prior_knowledge).
+    ///
+    /// This method requires the `grpc` Cargo feature.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// use httpmock::prelude::*;
+    /// use httpmock::GrpcCode;
+    ///
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// struct HelloRequest {
+    ///     #[prost(string, tag = "1")]
+    ///     name: String,
+    /// }
+    ///
+    /// let server = MockServer::builder().http2_prior_knowledge(true).start();
+    ///
+    /// let mock = server.mock_grpc("helloworld.Greeter/SayHello", |when, then| {
+    ///     when.grpc_request(&HelloRequest { name: "Fred".into() });
+    ///     then.grpc_status(GrpcCode::Unavailable, "Try again later");
+    /// });
+    /// ```
+    #[cfg(feature = "grpc")]
+    pub fn mock_grpc<F>(&self, method: &str, spec_fn: F) -> Mock
+    where
+        F: FnOnce(When, Then),
+    {
+        self.mock_grpc_async(method, spec_fn).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for a unary gRPC method. Please refer to
+    /// [MockServer::mock_grpc](struct.MockServer.html#method.mock_grpc) for details.
+    #[cfg(feature = "grpc")]
+    pub async fn mock_grpc_async<'a, F>(&'a self, method: &str, spec_fn: F) -> Mock<'a>
+    where
+        F: FnOnce(When, Then),
+    {
+        let path = format!("/{}", method.trim_start_matches('/'));
+        self.mock_async(|when, then| {
+            let when = when
+                .method(Method::POST)
+                .path(path)
+                .header_matches("content-type", Regex::new("^application/grpc").unwrap());
+            let then = then
+                .status(200)
+                .header("content-type", "application/grpc")
+                .grpc_status(GrpcCode::Ok, "");
+            spec_fn(when, then)
+        })
+        .await
+    }
+
+    /// Creates a [Mock](struct.Mock.html) on the mock server for every mock definition file in
+    /// the provided directory (files with a `yaml`, `yml` or `json` extension). The file format
+    /// is the same as for the standalone mock server (see
+    /// [here](https://github.com/alexliesenfeld/httpmock/blob/master/tests/resources/static_yaml_mock.yaml)
+    /// for an example).
+    ///
+    /// The returned map contains a handle for each mock, keyed by the mock's name. The name is
+    /// taken from the `name` field of the file and defaults to the file name without extension.
+    /// Relative paths in `then.body_file` are resolved against the directory of the mock file.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    ///
+    /// # Panics
+    /// Panics if a file cannot be read or is invalid. The panic message names the file and the
+    /// offending field and, if possible, the line where the error was found.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::start();
+    /// let mocks = server.load_mocks_from_dir("tests/mocks/");
+    ///
+    /// isahc::get(server.url("/hello")).unwrap();
+    ///
+    /// mocks["hello"].assert();
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub fn load_mocks_from_dir<P: AsRef<Path>>(&self, path: P) -> HashMap<String, Mock> {
+        self.load_mocks_from_dir_async(path).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) on the mock server for every mock definition file in
+    /// the provided directory. Please refer to
+    /// [MockServer::load_mocks_from_dir](struct.MockServer.html#method.load_mocks_from_dir)
+    /// for details.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    #[cfg(feature = "yaml")]
+    pub async fn load_mocks_from_dir_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> HashMap<String, Mock<'_>> {
+        let yaml_mocks =
+            crate::common::yaml::read_mock_dir(path.as_ref()).unwrap_or_else(|e| panic!("{}", e));
+
+        let mut mocks = HashMap::with_capacity(yaml_mocks.len());
+        for yaml_mock in yaml_mocks {
+            let response = self
+                .server_adapter
+                .as_ref()
+                .unwrap()
+                .create_mock(&yaml_mock.definition)
+                .await
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "Cannot create mock from file '{}': {}",
+                        yaml_mock.path.to_string_lossy(),
+                        e
+                    )
+                });
+            mocks.insert(
+                yaml_mock.name,
+                Mock::for_request(response.mock_id, self, &yaml_mock.definition.request),
+            );
+        }
+
+        mocks
+    }
+
+    /// Writes all mocks that are currently registered on the mock server to a YAML file, so
+    /// that they can be restored with
+    /// [MockServer::import_mocks](struct.MockServer.html#method.import_mocks). The file
+    /// contains one mock definition per YAML document (separated by `---`), in the order in
+    /// which the mocks are matched, using the same format as mock definition files.
+    ///
+    /// All request requirements and responses are written, except for the namespace of an
+    /// [isolated](struct.MockServer.html#method.connect_isolated) server. Delays are written
+    /// in whole milliseconds.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    ///
+    /// # Panics
+    /// Panics if the file cannot be written or if any mock cannot be serialized, such as mocks
+    /// with custom matcher functions (see [When::matches](struct.When.html#method.matches)).
+    /// The panic message lists the IDs of all such mocks.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::start();
+    /// server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200).body("Hi!");
+    /// });
+    ///
+    /// server.export_mocks("target/mocks.yaml");
+    ///
+    /// let restored = httpmock::MockServer::start();
+    /// let mocks = restored.import_mocks("target/mocks.yaml");
+    ///
+    /// isahc::get(restored.url("/hello")).unwrap();
+    ///
+    /// mocks[0].assert();
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub fn export_mocks<P: AsRef<Path>>(&self, path: P) {
+        self.export_mocks_async(path).join()
+    }
+
+    /// Writes all mocks that are currently registered on the mock server to a YAML file.
+    /// Please refer to [MockServer::export_mocks](struct.MockServer.html#method.export_mocks)
+    /// for more information.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    #[cfg(feature = "yaml")]
+    pub async fn export_mocks_async<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        let mocks = self
+            .server_adapter
+            .as_ref()
+            .unwrap()
+            .fetch_all_mocks()
+            .await
+            .expect("Cannot fetch mocks from mock server");
+        let content = crate::common::yaml::to_mocks_file(&mocks)
+            .unwrap_or_else(|e| panic!("Cannot export mocks: {}", e));
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|e| {
+                panic!(
+                    "Cannot create directory '{}': {}",
+                    parent.to_string_lossy(),
+                    e
+                )
+            });
+        }
+        std::fs::write(path, content).unwrap_or_else(|e| {
+            panic!("Cannot write mocks to '{}': {}", path.to_string_lossy(), e)
+        });
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every mock definition in a file that was written
+    /// by [MockServer::export_mocks](struct.MockServer.html#method.export_mocks). The returned
+    /// mocks are in the same order as in the file, so they are matched in the same order as
+    /// the exported mocks.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    ///
+    /// # Panics
+    /// Panics if the file cannot be read or is invalid.
+    #[cfg(feature = "yaml")]
+    pub fn import_mocks<P: AsRef<Path>>(&self, path: P) -> Vec<Mock> {
+        self.import_mocks_async(path).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every mock definition in a file that was written
+    /// by [MockServer::export_mocks](struct.MockServer.html#method.export_mocks). Please refer
+    /// to [MockServer::import_mocks](struct.MockServer.html#method.import_mocks) for more
+    /// information.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    #[cfg(feature = "yaml")]
+    pub async fn import_mocks_async<P: AsRef<Path>>(&self, path: P) -> Vec<Mock<'_>> {
+        let yaml_mocks = crate::common::yaml::read_recording_file(path.as_ref())
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        let mut mocks = Vec::with_capacity(yaml_mocks.len());
+        for yaml_mock in yaml_mocks {
+            let response = self
+                .server_adapter
+                .as_ref()
+                .unwrap()
+                .create_mock(&yaml_mock.definition)
+                .await
+                .unwrap_or_else(|e| panic!("Cannot create mock '{}': {}", yaml_mock.name, e));
+            mocks.push(Mock::for_request(
+                response.mock_id,
+                self,
+                &yaml_mock.definition.request,
+            ));
+        }
+
+        mocks
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for the requests that were recorded in a
+    /// [HAR](https://w3c.github.io/web-performance/specs/HAR/Overview.html) file (e.g. exported
+    /// from the developer tools of a browser), using the default [HarOptions](struct.HarOptions.html):
+    /// * Mocks match the method, the path and all query parameters of a recorded request.
+    /// * Requests with the same method, path and query parameters are combined into a single mock
+    /// that replays the recorded responses in the order in which they were recorded.
+    /// * Responses contain the recorded status, headers and body. Connection-specific headers
+    /// (e.g. `Transfer-Encoding`) as well as `Content-Length` and `Content-Encoding` are removed,
+    /// because HAR files contain the decoded body.
+    ///
+    /// The returned mocks are ordered by the first recorded request that they match.
+    ///
+    /// # Panics
+    /// Panics if the file cannot be read or is not a valid HAR file.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::start();
+    /// let mocks = server.load_har("session.har");
+    ///
+    /// isahc::get(server.url("/users")).unwrap();
+    ///
+    /// mocks[0].assert();
+    /// ```
+    pub fn load_har<P: AsRef<Path>>(&self, path: P) -> Vec<Mock> {
+        self.load_har_with_options(path, HarOptions::default())
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for the requests that were recorded in a HAR file.
+    /// Please refer to [MockServer::load_har](struct.MockServer.html#method.load_har) for
+    /// details.
+    pub async fn load_har_async<P: AsRef<Path>>(&self, path: P) -> Vec<Mock<'_>> {
+        self.load_har_with_options_async(path, HarOptions::default())
+            .await
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for the requests that were recorded in a HAR file.
+    /// The options control which request headers are matched, which query parameters are
+    /// ignored and whether identical requests replay their responses in sequence.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// use httpmock::{HarOptions, MockServer};
+    ///
+    /// let server = MockServer::start();
+    /// let mocks = server.load_har_with_options(
+    ///     "session.har",
+    ///     HarOptions::new().match_header("authorization").ignore_query_param("_"),
+    /// );
+    /// ```
+    pub fn load_har_with_options<P: AsRef<Path>>(&self, path: P, options: HarOptions) -> Vec<Mock> {
+        self.load_har_with_options_async(path, options).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for the requests that were recorded in a HAR file.
+    /// Please refer to
+    /// [MockServer::load_har_with_options](struct.MockServer.html#method.load_har_with_options)
+    /// for details.
+    pub async fn load_har_with_options_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: HarOptions,
+    ) -> Vec<Mock<'_>> {
+        let definitions = crate::common::har::read_har_file(path.as_ref(), &options)
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        let mut mocks = Vec::with_capacity(definitions.len());
+        for definition in definitions {
+            let response = self
+                .server_adapter
+                .as_ref()
+                .unwrap()
+                .create_mock(&definition)
+                .await
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "Cannot create mock from HAR file '{}': {}",
+                        path.as_ref().to_string_lossy(),
+                        e
+                    )
+                });
+            mocks.push(Mock::for_request(
+                response.mock_id,
+                self,
+                &definition.request,
+            ));
+        }
+
+        mocks
+    }
+
+    /// Writes all requests that the mock server received and the responses it sent to a
+    /// [HAR 1.2](https://w3c.github.io/web-performance/specs/HAR/Overview.html) file, which
+    /// can be opened in the developer tools of a browser or loaded with
+    /// [MockServer::load_har](struct.MockServer.html#method.load_har). The file contains matched,
+    /// unmatched and forwarded requests in the order in which they were received (up to the
+    /// last 100 requests, like the request history). Binary bodies are base64 encoded.
+    ///
+    /// Missing parent directories are created. An existing file is overwritten.
+    ///
+    /// # Panics
+    /// Panics if the traffic cannot be fetched from the mock server or if the file cannot be
+    /// written.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::start();
+    ///
+    /// // ... send requests to the server ...
+    ///
+    /// server.export_har("target/traffic.har");
+    /// ```
+    pub fn export_har<P: AsRef<Path>>(&self, path: P) {
+        self.export_har_async(path).join()
+    }
+
+    /// Writes all requests that the mock server received and the responses it sent to a HAR
+    /// file. Please refer to [MockServer::export_har](struct.MockServer.html#method.export_har)
+    /// for details.
+    pub async fn export_har_async<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        let har = self
+            .server_adapter
+            .as_ref()
+            .unwrap()
+            .export_har()
+            .await
+            .expect("Cannot fetch traffic from mock server");
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|e| {
+                panic!(
+                    "Cannot create directory '{}': {}",
+                    parent.to_string_lossy(),
+                    e
+                )
+            });
+        }
+        let content = serde_json::to_string_pretty(&har).expect("Cannot serialize HAR document");
+        std::fs::write(path, content).unwrap_or_else(|e| {
+            panic!("Cannot write HAR file '{}': {}", path.to_string_lossy(), e)
+        });
+    }
+
+    /// Returns statistics about the requests that this mock server handled since it was started
+    /// or last reset: the number of served and unmatched requests, the number of requests that
+    /// matched each mock, the number of responses by status code and the time the server took
+    /// to handle requests. Requests to the admin API are not counted.
+    ///
+    /// The statistics of a standalone server are also available in the Prometheus text format
+    /// at `GET /__httpmock__/metrics`.
+    ///
+    /// # Panics
+    /// Panics if the statistics cannot be fetched from the mock server.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::get;
+    ///
+    /// let server = MockServer::start();
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200);
+    /// });
+    ///
+    /// get(server.url("/hello")).unwrap();
+    /// get(server.url("/unknown")).unwrap();
+    ///
+    /// let stats = server.stats();
+    /// assert_eq!(stats.total_requests, 2);
+    /// assert_eq!(stats.unmatched_requests, 1);
+    /// assert_eq!(stats.mock_hits[&mock.id], 1);
+    /// assert_eq!(stats.status_counts[&404], 1);
+    /// ```
+    pub fn stats(&self) -> ServerStats {
+        self.stats_async().join()
+    }
+
+    /// Returns statistics about the requests that this mock server handled. Please refer to
+    /// [MockServer::stats](struct.MockServer.html#method.stats) for details.
+    pub async fn stats_async(&self) -> ServerStats {
+        self.server_adapter
+            .as_ref()
+            .unwrap()
+            .stats()
+            .await
+            .expect("Cannot fetch statistics from mock server")
+    }
+
+    /// Verifies that every mock on this server received at least one request. Mocks that were
+    /// loaded when a standalone server started (static mocks) are ignored.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/hello")).unwrap();
+    ///
+    /// server.verify_all();
+    /// ```
+    /// # Panics
+    /// Panics if a mock has not received any request. The message lists the method and path of
+    /// each such mock.
+    pub fn verify_all(&self) {
+        self.verify_all_async().join()
+    }
+
+    /// Verifies that every mock on this server received at least one request. Please refer to
+    /// [MockServer::verify_all](struct.MockServer.html#method.verify_all) for details.
+    pub async fn verify_all_async(&self) {
+        if let Some(message) = uncalled_mocks_message(self.server_adapter.as_ref().unwrap()).await {
+            panic!("{}", message);
+        }
+    }
+
+    /// Makes this server call [MockServer::verify_all](struct.MockServer.html#method.verify_all)
+    /// when it is dropped, unless the current thread is already panicking. This is what
+    /// `#[httpmock::test(verify_all)]` uses, so that the verification also covers servers that
+    /// are moved into the test function.
+    pub fn verify_all_on_drop(&mut self) {
+        self.verify_on_drop = true;
+    }
+
+    /// Returns a [VerificationGuard](struct.VerificationGuard.html) that verifies the requests
+    /// this server received when it is dropped: by default, every mock must have received a
+    /// request and every request must have matched a mock. The guard needs to be bound to a
+    /// variable (e.g. `_guard`, but not `_`) so that it lives until the end of the test.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    /// let _guard = server.verify_on_drop();
+    ///
+    /// server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/hello")).unwrap();
+    /// ```
+    pub fn verify_on_drop(&self) -> VerificationGuard {
+        VerificationGuard::new(self)
+    }
+
+    /// Sets a path prefix, such as `/api/v2`, that is prepended to the paths of all mocks that
+    /// are created afterwards, so that they do not need to repeat it. Path requirements (e.g.
+    /// [When::path](struct.When.html#method.path)) are matched against the rest of the request
+    /// path, and [MockServer::base_url](struct.MockServer.html#method.base_url) and
+    /// [MockServer::url](struct.MockServer.html#method.url) include the prefix. Mocks that use
+    /// [When::ignore_base_path](struct.When.html#method.ignore_base_path) match the full path.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let mut server = MockServer::start();
+    /// server.set_base_path("/api/v2");
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.method(GET).path("/users");
+    ///     then.status(200);
+    /// });
+    ///
+    /// let response = isahc::get(server.url("/users")).unwrap();
+    ///
+    /// mock.assert();
+    /// assert_eq!(server.url("/users"), format!("http://{}/api/v2/users", server.address()));
+    /// assert_eq!(response.status(), 200);
+    /// ```
+    pub fn set_base_path<S: Into<String>>(&mut self, path: S) {
+        self.base_path = normalize_base_path(path.into());
+    }
+
+    /// Serves the files of a directory for `GET` and `HEAD` requests below a path prefix, for
+    /// example to serve a directory of fixtures. The content type of a response is derived
+    /// from the file extension. Requests for files that do not exist are handled like any other
+    /// request that does not match a mock, and request paths that would escape the directory
+    /// (e.g. using `..`) are rejected with status code 403.
+    ///
+    /// Mocks always take precedence over served directories. Served requests are recorded in
+    /// the request history like requests that match a mock. Served directories are removed
+    /// when the server is reset.
+    ///
+    /// The path of the directory is resolved on the machine of the mock server, which matters
+    /// if it is a standalone server.
+    ///
+    /// * `path_prefix` - The path prefix of the served files (e.g. `/assets`).
+    /// * `dir` - The directory that contains the files.
+    ///
+    /// # Panics
+    /// Panics if the path prefix does not start with a slash or if the mock server rejects the
+    /// directory.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    /// server.serve_dir("/assets", "tests/resources/static");
+    ///
+    /// let mut response = isahc::get(server.url("/assets/css/style.css")).unwrap();
+    ///
+    /// assert_eq!(response.status(), 200);
+    /// assert_eq!(response.headers()["content-type"], "text/css; charset=utf-8");
+    /// assert_eq!(response.text().unwrap(), "body { color: #333; }\n");
+    /// ```
+    pub fn serve_dir<SP: Into<String>, P: AsRef<Path>>(&self, path_prefix: SP, dir: P) {
+        self.serve_dir_async(path_prefix, dir).join()
+    }
+
+    /// Serves the files of a directory for requests below a path prefix. Please refer to
+    /// [MockServer::serve_dir](struct.MockServer.html#method.serve_dir) for details.
+    pub async fn serve_dir_async<SP: Into<String>, P: AsRef<Path>>(&self, path_prefix: SP, dir: P) {
+        self.add_static_dir(path_prefix.into(), dir.as_ref(), None)
+            .await
+    }
+
+    /// Serves the files of a directory like
+    /// [MockServer::serve_dir](struct.MockServer.html#method.serve_dir), but additionally
+    /// serves an index file (e.g. `index.html`) for request paths that end with a slash.
+    pub fn serve_dir_with_index<SP: Into<String>, P: AsRef<Path>, SI: Into<String>>(
+        &self,
+        path_prefix: SP,
+        dir: P,
+        index_file: SI,
+    ) {
+        self.serve_dir_with_index_async(path_prefix, dir, index_file)
+            .join()
+    }
+
+    /// Serves the files of a directory along with an index file. Please refer to
+    /// [MockServer::serve_dir_with_index](struct.MockServer.html#method.serve_dir_with_index)
+    /// for details.
+    pub async fn serve_dir_with_index_async<SP: Into<String>, P: AsRef<Path>, SI: Into<String>>(
+        &self,
+        path_prefix: SP,
+        dir: P,
+        index_file: SI,
+    ) {
+        self.add_static_dir(path_prefix.into(), dir.as_ref(), Some(index_file.into()))
+            .await
+    }
+
+    async fn add_static_dir(&self, path_prefix: String, dir: &Path, index_file: Option<String>) {
+        let dir = StaticDirectory {
+            path_prefix,
+            root: dir.to_string_lossy().to_string(),
+            index_file,
+            namespace: None,
+        };
+        self.server_adapter
+            .as_ref()
+            .unwrap()
+            .serve_dir(&dir)
+            .await
+            .unwrap_or_else(|e| panic!("Cannot serve directory: {}", e))
+    }
+
+    /// Writes a shell script with a [curl](https://curl.se/) command for every mock of this
+    /// server, each sending an example request that matches the mock (see
+    /// [Mock::to_curl](struct.Mock.html#method.to_curl)). The commands are ordered by mock ID.
+    ///
+    /// Missing parent directories are created. An existing file is overwritten. On Unix, the
+    /// script is made executable.
+    ///
+    /// # Panics
+    /// Panics if the mocks cannot be fetched from the mock server or if the file cannot be
+    /// written.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::start();
+    ///
+    /// // ... create mocks ...
+    ///
+    /// server.export_curl_script("target/requests.sh");
+    /// ```
+    pub fn export_curl_script<P: AsRef<Path>>(&self, path: P) {
+        self.export_curl_script_async(path).join()
+    }
+
+    /// Writes a shell script with a curl command for every mock of this server. Please refer to
+    /// [MockServer::export_curl_script](struct.MockServer.html#method.export_curl_script) for
+    /// details.
+    pub async fn export_curl_script_async<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        let mut mocks = self
+            .server_adapter
+            .as_ref()
+            .unwrap()
+            .fetch_all_mocks()
+            .await
+            .expect("Cannot fetch mocks from mock server");
+        mocks.sort_by_key(|mock| mock.id);
+
+        let mut script = String::from("#!/bin/sh\n");
+        for mock in mocks {
+            let base_url = self.mock_base_url(&mock.definition.request);
+            script.push_str(&format!(
+                "\n# Mock {}\n{}\n",
+                mock.id,
+                crate::common::curl::mock_to_curl(&base_url, &mock.definition.request)
+            ));
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|e| {
+                panic!(
+                    "Cannot create directory '{}': {}",
+                    parent.to_string_lossy(),
+                    e
+                )
+            });
+        }
+        std::fs::write(path, script).unwrap_or_else(|e| {
+            panic!(
+                "Cannot write curl script '{}': {}",
+                path.to_string_lossy(),
+                e
+            )
+        });
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap_or_else(
+                |e| panic!("Cannot make '{}' executable: {}", path.to_string_lossy(), e),
+            );
+        }
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every request in a
+    /// [Postman](https://www.postman.com/) collection (format v2.0 or v2.1) that has at least one
+    /// saved example:
+    /// * Mocks match the method and the URL path of the request. Path variables (e.g. `:id`)
+    /// match any value of a path segment.
+    /// * Responses contain the status, headers and body of the first example. `Content-Length`
+    /// and `Content-Encoding` headers are removed, because examples contain the decoded body.
+    ///
+    /// Variables in request URLs (e.g. `{{baseUrl}}`) are resolved from the collection variables.
+    /// Use
+    /// [MockServer::load_postman_collection_with_variables](struct.MockServer.html#method.load_postman_collection_with_variables)
+    /// to provide environment variables. Requests without examples are skipped and listed in a
+    /// warning in the log.
+    ///
+    /// The returned mocks can be looked up by the name path of their request, which joins the
+    /// names of the enclosing folders and the request name with ` / ` (e.g. `Users / Get by id`).
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// # Panics
+    /// Panics if the file cannot be read or is not a valid Postman collection.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::start();
+    /// let mocks = server.load_postman_collection("tests/api.postman_collection.json");
+    ///
+    /// isahc::get(server.url("/users/1")).unwrap();
+    ///
+    /// mocks["Users / Get by id"].assert();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn load_postman_collection<P: AsRef<Path>>(&self, path: P) -> HashMap<String, Mock> {
+        self.load_postman_collection_with_variables(path, HashMap::new())
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every request with a saved example in a Postman
+    /// collection. Please refer to
+    /// [MockServer::load_postman_collection](struct.MockServer.html#method.load_postman_collection)
+    /// for details.
+    #[cfg(feature = "regex")]
+    pub async fn load_postman_collection_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> HashMap<String, Mock<'_>> {
+        self.load_postman_collection_with_variables_async(path, HashMap::new())
+            .await
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every request with a saved example in a Postman
+    /// collection. The provided variables (e.g. from a Postman environment) take precedence over
+    /// the collection variables when request URLs are resolved.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// use std::collections::HashMap;
+    ///
+    /// let server = httpmock::MockServer::start();
+    /// let mut variables = HashMap::new();
+    /// variables.insert("version".to_string(), "v2".to_string());
+    ///
+    /// let mocks = server.load_postman_collection_with_variables(
+    ///     "tests/api.postman_collection.json",
+    ///     variables,
+    /// );
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn load_postman_collection_with_variables<P: AsRef<Path>>(
+        &self,
+        path: P,
+        variables: HashMap<String, String>,
+    ) -> HashMap<String, Mock> {
+        self.load_postman_collection_with_variables_async(path, variables)
+            .join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every request with a saved example in a Postman
+    /// collection. Please refer to
+    /// [MockServer::load_postman_collection_with_variables](struct.MockServer.html#method.load_postman_collection_with_variables)
+    /// for details.
+    #[cfg(feature = "regex")]
+    pub async fn load_postman_collection_with_variables_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+        variables: HashMap<String, String>,
+    ) -> HashMap<String, Mock<'_>> {
+        let path = path.as_ref();
+        let collection = crate::common::postman::read_postman_collection(path, &variables)
+            .unwrap_or_else(|e| panic!("{}", e));
+        for warning in &collection.warnings {
+            log::warn!(
+                "Postman collection '{}': {}",
+                path.to_string_lossy(),
+                warning
+            );
+        }
+
+        let mut mocks = HashMap::with_capacity(collection.mocks.len());
+        for (name, definition) in collection.mocks {
+            let response = self
+                .server_adapter
+                .as_ref()
+                .unwrap()
+                .create_mock(&definition)
+                .await
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "Cannot create mock for request '{}' of Postman collection '{}': {}",
+                        name,
+                        path.to_string_lossy(),
+                        e
+                    )
+                });
+            mocks.insert(
+                name,
+                Mock::for_request(response.mock_id, self, &definition.request),
+            );
+        }
+
+        mocks
+    }
+
+    /// Loads a GraphQL schema from a file in the GraphQL schema definition language (SDL).
+    /// The data of all mocks that are created afterwards with
+    /// [Then::graphql_data](struct.Then.html#method.graphql_data) is validated
+    /// against the schema when the mock is created, so that mocks that do not fit the schema
+    /// anymore are detected early. Data is validated against the root type of the operation
+    /// in [When::graphql_query](struct.When.html#method.graphql_query) or the query type if no
+    /// query was set. Fields that are not defined in the schema, values of the wrong type and
+    /// null values of non-null fields are reported.
+    ///
+    /// This method requires the `graphql` Cargo feature.
+    ///
+    /// # Panics
+    /// Panics if the schema cannot be read or parsed.
+    ///
+    /// **Example**:
+    /// ```should_panic
+    /// use httpmock::prelude::*;
+    /// use serde_json::json;
+    ///
+    /// let server = MockServer::start();
+    /// server.load_graphql_schema("tests/resources/graphql/schema.graphql");
+    ///
+    /// // Panics, because "age" is an Int in the schema
+    /// server.mock(|when, then| {
+    ///     when.path("/graphql");
+    ///     then.graphql_data(json!({ "user": { "id": "1", "age": "30" } }));
+    /// });
+    /// ```
+    #[cfg(feature = "graphql")]
+    pub fn load_graphql_schema<P: AsRef<Path>>(&self, path: P) {
+        let schema = GraphQLSchema::from_file(path.as_ref()).unwrap_or_else(|e| panic!("{}", e));
+        *self.graphql_schema.lock().unwrap() = Some(Arc::new(schema));
+    }
+
+    #[cfg(feature = "graphql")]
+    fn validate_graphql_data(&self, spec: &GraphQLSpec) -> Result<(), String> {
+        let schema = self.graphql_schema.lock().unwrap().clone();
+        if let (Some(schema), Some(data)) = (schema, &spec.data) {
+            let operation_type = spec.operation_type.unwrap_or(OperationType::Query);
+            if let Err(e) = schema.validate(operation_type, data) {
+                return Err(format!("GraphQL data does not match the schema: {}", e));
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the mocks of this server as interactions to a
+    /// [Pact v3](https://github.com/pact-foundation/pact-specification/tree/version-3) contract
+    /// file between the provided consumer and provider:
+    /// * The first request that a mock received provides the example request of an interaction.
+    /// Mocks that did not receive any request are skipped.
+    /// * Matchers are converted into Pact matching rules where possible. For example, regex
+    /// matchers become `regex` rules and partial JSON body matchers become `type` rules.
+    /// * The configured response (or the first response of a sequence) is the expected response.
+    ///
+    /// If the file already exists, the interactions are merged into it and existing interactions
+    /// with the same description are replaced, so that several tests can contribute to the same
+    /// contract. Matchers that cannot be expressed in Pact are ignored and reported as warnings
+    /// in the log.
+    ///
+    /// # Panics
+    /// Panics if the mocks cannot be fetched from the mock server, if the existing file belongs to
+    /// another consumer or provider or if the file cannot be written.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    /// server.mock(|when, then| {
+    ///     when.method(GET).path_matches(Regex::new("^/users/\\d+$").unwrap());
+    ///     then.status(200).json_body(serde_json::json!({ "name": "Fred" }));
+    /// });
+    ///
+    /// isahc::get(server.url("/users/1")).unwrap();
+    ///
+    /// server.write_pact("web-app", "user-service", "target/pacts/web-app-user-service.json");
+    /// ```
+    pub fn write_pact<P: AsRef<Path>>(&self, consumer: &str, provider: &str, path: P) {
+        self.write_pact_async(consumer, provider, path).join()
+    }
+
+    /// Writes the mocks of this server as interactions to a Pact contract file. Please refer to
+    /// [MockServer::write_pact](struct.MockServer.html#method.write_pact) for details.
+    pub async fn write_pact_async<P: AsRef<Path>>(&self, consumer: &str, provider: &str, path: P) {
+        let path = path.as_ref();
+        let adapter = self.server_adapter.as_ref().unwrap();
+
+        let mut mocks = Vec::new();
+        for mock in adapter
+            .fetch_all_mocks()
+            .await
+            .expect("Cannot fetch mocks from mock server")
+        {
+            let requests = adapter
+                .fetch_mock_requests(mock.id)
+                .await
+                .expect("Cannot fetch mock requests from mock server");
+            mocks.push((mock, requests));
+        }
+
+        let mut warnings = Vec::new();
+        let interactions = crate::common::pact::to_interactions(&mocks, &mut warnings);
+        for warning in warnings {
+            log::warn!("Pact file '{}': {}", path.to_string_lossy(), warning);
+        }
+
+        let existing = match path.exists() {
+            true => Some(std::fs::read_to_string(path).unwrap_or_else(|e| {
+                panic!("Cannot read Pact file '{}': {}", path.to_string_lossy(), e)
+            })),
+            false => None,
+        };
+        let pact =
+            crate::common::pact::to_pact(existing.as_deref(), consumer, provider, interactions)
+                .unwrap_or_else(|e| {
+                    panic!("Cannot write Pact file '{}': {}", path.to_string_lossy(), e)
+                });
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|e| {
+                panic!(
+                    "Cannot create directory '{}': {}",
+                    parent.to_string_lossy(),
+                    e
+                )
+            });
+        }
+        let content = serde_json::to_string_pretty(&pact).expect("Cannot serialize Pact file");
+        std::fs::write(path, content).unwrap_or_else(|e| {
+            panic!("Cannot write Pact file '{}': {}", path.to_string_lossy(), e)
+        });
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every
+    /// [WireMock](https://wiremock.org/docs/stubbing/) stub mapping in a JSON file or in a
+    /// directory of JSON files (including subdirectories). If the directory contains a `mappings`
+    /// subdirectory, the mappings are read from there. Response bodies referenced by
+    /// `bodyFileName` are read from the `__files` directory next to the mappings directory.
+    ///
+    /// The following parts of a stub mapping are supported:
+    /// * `request`: `method`, `url`, `urlPath`, `urlPathPattern`, `queryParameters`, `headers`
+    /// and `cookies` (with `equalTo`, `matches` and `contains`, and query parameters and
+    /// headers also with `absent`), `basicAuthCredentials` as well as `bodyPatterns`
+    /// (`equalTo`, `equalToJson`, `matchesJsonPath`, `contains` and `matches`).
+    /// * `response`: `status`, `headers`, `body`, `jsonBody`, `base64Body`, `bodyFileName` and
+    /// `fixedDelayMilliseconds`.
+    /// * `priority`: mocks are created in the order of their priority, so that mappings with a
+    /// higher priority (i.e., a lower number) are matched first.
+    ///
+    /// All other parts of a mapping (e.g. scenarios or response templates) are ignored. A warning
+    /// naming the file and the ignored part is logged for each of them.
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// # Panics
+    /// Panics if a file cannot be read or is not a valid stub mapping file.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::start();
+    /// let mocks = server.load_wiremock_mappings("tests/wiremock/");
+    ///
+    /// isahc::get(server.url("/users/1")).unwrap();
+    ///
+    /// mocks[0].assert();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn load_wiremock_mappings<P: AsRef<Path>>(&self, path: P) -> Vec<Mock> {
+        self.load_wiremock_mappings_async(path).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every WireMock stub mapping in a file or
+    /// directory. Please refer to
+    /// [MockServer::load_wiremock_mappings](struct.MockServer.html#method.load_wiremock_mappings)
+    /// for details.
+    #[cfg(feature = "regex")]
+    pub async fn load_wiremock_mappings_async<P: AsRef<Path>>(&self, path: P) -> Vec<Mock<'_>> {
+        let files = crate::common::wiremock::read_wiremock_mappings(path.as_ref())
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        let mut mappings = Vec::new();
+        for file in files {
+            for warning in &file.warnings {
+                log::warn!(
+                    "WireMock mapping file '{}': {}",
+                    file.path.to_string_lossy(),
+                    warning
+                );
+            }
+            let path = file.path;
+            mappings.extend(
+                file.mappings
+                    .into_iter()
+                    .map(|(priority, definition)| (priority, path.clone(), definition)),
+            );
+        }
+        // The sort is stable, so that mappings with the same priority keep the order of the files.
+        mappings.sort_by_key(|(priority, _, _)| *priority);
+
+        let mut mocks = Vec::with_capacity(mappings.len());
+        for (_, path, definition) in mappings {
+            let response = self
+                .server_adapter
+                .as_ref()
+                .unwrap()
+                .create_mock(&definition)
+                .await
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "Cannot create mock from WireMock mapping file '{}': {}",
+                        path.to_string_lossy(),
+                        e
+                    )
+                });
+            mocks.push(Mock::for_request(
+                response.mock_id,
+                self,
+                &definition.request,
+            ));
+        }
+
+        mocks
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every operation of an OpenAPI 3 specification
+    /// (in YAML format or, if the file has a `json` extension, in JSON format):
+    /// * Path templates (e.g. `/users/{id}`) match any value for their path parameters. The
+    /// path of the first server URL (e.g. `/v1` for `https://example.com/v1`) is used as a prefix.
+    /// * Required query parameters, headers and cookies must be present in the request.
+    /// * If the operation requires a JSON request body, the body must be valid according to its
+    /// schema. If a request does not match because of a schema violation, the failing
+    /// validation is reported by [Mock::assert](struct.Mock.html#method.assert).
+    /// * The response is created from the documented response with the lowest status code. Its
+    /// body is taken from the first example or, if there is none, derived from the schema.
+    ///
+    /// The returned map contains a handle for each operation, keyed by its `operationId`
+    /// (or by its method and path template if the `operationId` is not set, e.g.
+    /// `GET /users/{id}`). Operations can be overridden by deleting their mock and creating a
+    /// new one.
+    ///
+    /// This method requires the `openapi` Cargo feature.
+    ///
+    /// # Panics
+    /// Panics if the file cannot be read or is not a valid OpenAPI specification.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::start();
+    /// let mocks = server.mocks_from_openapi("api.yaml");
+    ///
+    /// isahc::get(server.url("/users/1")).unwrap();
+    ///
+    /// mocks["getUser"].assert();
+    /// ```
+    #[cfg(feature = "openapi")]
+    pub fn mocks_from_openapi<P: AsRef<Path>>(&self, path: P) -> HashMap<String, Mock> {
+        self.mocks_from_openapi_async(path).join()
+    }
+
+    /// Creates a [Mock](struct.Mock.html) for every operation of an OpenAPI 3 specification.
+    /// Please refer to
+    /// [MockServer::mocks_from_openapi](struct.MockServer.html#method.mocks_from_openapi)
+    /// for details.
+    ///
+    /// This method requires the `openapi` Cargo feature.
+    #[cfg(feature = "openapi")]
+    pub async fn mocks_from_openapi_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> HashMap<String, Mock<'_>> {
+        let openapi_mocks = crate::common::openapi::read_openapi_file(path.as_ref())
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        let mut mocks = HashMap::with_capacity(openapi_mocks.len());
+        for openapi_mock in openapi_mocks {
+            let response = self
+                .server_adapter
+                .as_ref()
+                .unwrap()
+                .create_mock(&openapi_mock.definition)
+                .await
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "Cannot create mock for operation '{}': {}",
+                        openapi_mock.name, e
+                    )
+                });
+            mocks.insert(
+                openapi_mock.name,
+                Mock::for_request(response.mock_id, self, &openapi_mock.definition.request),
+            );
+        }
+
+        mocks
+    }
+
+    /// Starts a new `MockServer` asynchronously that records traffic: all requests that are
+    /// not sent to the admin API are forwarded to the upstream server at the provided base URL
+    /// (e.g. `https://api.github.com`) and answered with the upstream response. The requests
+    /// and responses are kept, so that they can be saved as mock definitions using
+    /// [MockServer::save_recording](struct.MockServer.html#method.save_recording) and replayed
+    /// later using [MockServer::playback](struct.MockServer.html#method.playback).
+    ///
+    /// Requests are forwarded with all headers except connection-specific ones, `Host` and
+    /// `Accept-Encoding`, so that responses are recorded without content encoding. If the
+    /// upstream server cannot be reached, the request is answered with `502 Bad Gateway`.
+    ///
+    /// # Panics
+    /// This method will panic if the upstream URL is invalid.
+    pub async fn start_recording_async<S: Into<String>>(upstream: S) -> Self {
+        Self::builder().record(upstream).start_async().await
+    }
+
+    /// Starts a new `MockServer` synchronously that records traffic. Please refer to
+    /// [MockServer::start_recording_async](struct.MockServer.html#method.start_recording_async)
+    /// for more information.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::start_recording("https://api.github.com");
+    ///
+    /// let response = isahc::get(server.url("/repos/alexliesenfeld/httpmock")).unwrap();
+    ///
+    /// assert_eq!(response.status(), 200);
+    /// ```
+    pub fn start_recording<S: Into<String>>(upstream: S) -> Self {
+        Self::start_recording_async(upstream).join()
+    }
+
+    /// Saves the traffic that was recorded by a mock server started with
+    /// [MockServer::start_recording](struct.MockServer.html#method.start_recording) as a YAML
+    /// file with one mock definition per request, using the default
+    /// [RecordingOptions](struct.RecordingOptions.html):
+    /// * Mocks match the method, the path, all query parameters and the body of the recorded
+    /// request. Only the first response is saved for requests that are matched by the same mock.
+    /// * Responses contain the recorded status, headers and body. The values of the
+    /// `Authorization`, `Proxy-Authorization`, `Cookie` and `Set-Cookie` headers are redacted.
+    ///
+    /// Missing parent directories are created. An existing file is overwritten.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    ///
+    /// # Panics
+    /// Panics if the mock server does not record traffic or if the file cannot be written.
+    #[cfg(feature = "yaml")]
+    pub fn save_recording<P: AsRef<Path>>(&self, path: P) {
+        self.save_recording_with_options(path, RecordingOptions::default())
+    }
+
+    /// Saves the recorded traffic as a YAML file. Please refer to
+    /// [MockServer::save_recording](struct.MockServer.html#method.save_recording) for details.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    #[cfg(feature = "yaml")]
+    pub async fn save_recording_async<P: AsRef<Path>>(&self, path: P) {
+        self.save_recording_with_options_async(path, RecordingOptions::default())
+            .await
+    }
+
+    /// Saves the recorded traffic as a YAML file. The options control which request headers
+    /// are matched as well as which secrets are redacted and which volatile response fields
+    /// are normalized before the file is written.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// use httpmock::{MockServer, RecordingOptions, Regex};
+    ///
+    /// let server = MockServer::start_recording("https://api.github.com");
+    ///
+    /// // ... send requests to the server ...
+    ///
+    /// server.save_recording_with_options(
+    ///     "tests/recordings/github.yaml",
+    ///     RecordingOptions::new()
+    ///         .redact_body(Regex::new(r#""token":\s*"([^"]*)""#).unwrap())
+    ///         .normalize_json_field("$.updated_at", "2020-01-01T00:00:00Z"),
+    /// );
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub fn save_recording_with_options<P: AsRef<Path>>(&self, path: P, options: RecordingOptions) {
+        self.save_recording_with_options_async(path, options).join()
+    }
+
+    /// Saves the recorded traffic as a YAML file. Please refer to
+    /// [MockServer::save_recording_with_options](struct.MockServer.html#method.save_recording_with_options)
+    /// for details.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    #[cfg(feature = "yaml")]
+    pub async fn save_recording_with_options_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: RecordingOptions,
+    ) {
+        let path = path.as_ref();
+        let exchanges = self
+            .server_adapter
+            .as_ref()
+            .unwrap()
+            .recordings()
+            .await
+            .expect("Cannot fetch recorded requests from mock server");
+        let content = crate::common::recording::to_recording_file(&exchanges, &options)
+            .unwrap_or_else(|e| panic!("Cannot save recording: {}", e));
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|e| {
+                panic!(
+                    "Cannot create directory '{}': {}",
+                    parent.to_string_lossy(),
+                    e
+                )
+            });
+        }
+        std::fs::write(path, content).unwrap_or_else(|e| {
+            panic!(
+                "Cannot write recording to '{}': {}",
+                path.to_string_lossy(),
+                e
+            )
+        });
+    }
+
+    /// Starts a new `MockServer` asynchronously and creates a mock for every mock definition
+    /// in a recording that was saved by
+    /// [MockServer::save_recording](struct.MockServer.html#method.save_recording), so that
+    /// the recorded responses are served without contacting the upstream server. The server
+    /// is taken from the server pool (see
+    /// [MockServer::start_async](struct.MockServer.html#method.start_async)).
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    ///
+    /// # Panics
+    /// Panics if the file cannot be read or is not a valid recording.
+    #[cfg(feature = "yaml")]
+    pub async fn playback_async<P: AsRef<Path>>(path: P) -> Self {
+        let yaml_mocks = crate::common::yaml::read_recording_file(path.as_ref())
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        let server = Self::start_async().await;
+        for yaml_mock in yaml_mocks {
+            server
+                .server_adapter
+                .as_ref()
+                .unwrap()
+                .create_mock(&yaml_mock.definition)
+                .await
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "Cannot create mock '{}' from recording: {}",
+                        yaml_mock.name, e
+                    )
+                });
+        }
+
+        server
+    }
+
+    /// Starts a new `MockServer` synchronously that replays a recording. Please refer to
+    /// [MockServer::playback_async](struct.MockServer.html#method.playback_async) for more
+    /// information.
+    ///
+    /// This method requires the `yaml` Cargo feature.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// let server = httpmock::MockServer::playback("tests/recordings/github.yaml");
+    ///
+    /// let response = isahc::get(server.url("/repos/alexliesenfeld/httpmock")).unwrap();
+    ///
+    /// assert_eq!(response.status(), 200);
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub fn playback<P: AsRef<Path>>(path: P) -> Self {
+        Self::playback_async(path).join()
+    }
+}
+
+/// Returns a message that lists all mocks that have not received any request, or `None` if
+/// every mock has been called.
+async fn uncalled_mocks_message(
+    adapter: &Arc<dyn MockServerAdapter + Send + Sync>,
+) -> Option<String> {
+    let mocks = adapter
+        .fetch_all_mocks()
+        .await
+        .expect("Cannot fetch mocks from mock server");
+    let uncalled: Vec<String> = mocks
+        .iter()
+        .filter(|mock| !mock.is_static && mock.call_counter.load(Ordering::SeqCst) == 0)
+        .map(|mock| {
+            format!(
+                "{} (ID {})",
+                describe_mock(&mock.definition.request),
+                mock.id
+            )
+        })
+        .collect();
+
+    match uncalled.is_empty() {
+        true => None,
+        false => Some(format!(
+            "The following mocks have not received any request:\n{}",
+            uncalled.join("\n")
+        )),
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        let adapter = self.server_adapter.take().unwrap();
+        // Verify before the mocks are deleted below, but fail only after the cleanup.
+        let uncalled_mocks = match self.verify_on_drop && !thread::panicking() {
+            true => uncalled_mocks_message(&adapter).join(),
+            false => None,
+        };
+
+        if let Some(pool) = &self.pool {
+            // Only local mock servers can be shut down. They are replaced by a new server to
+            // keep the pool at its size.
+            let adapter = match adapter.is_shut_down() {
+                true => LOCAL_SERVER_ADAPTER_GENERATOR(),
+                false => adapter,
+            };
+
+            // A paused server must accept connections again before it can be reused.
+            let _ = adapter.resume().join();
+            if let Some(request_log) = adapter.request_log() {
+                *request_log.write().unwrap() = RequestLogConfig::default();
+            }
+
+            // Release resources held by mocks and recorded requests while the server is idle.
+            // The server is reset again when it is taken from the pool, so errors are ignored.
+            let _ = adapter.delete_all_mocks().join();
+            let _ = adapter.delete_history().join();
+
+            pool.put(adapter).join();
+        } else if adapter.namespace().is_some() {
+            // Leave nothing behind on the shared remote server.
+            let _ = adapter.reset().join();
+        }
+
+        if let Some(message) = uncalled_mocks {
+            panic!("{}", message);
+        }
+    }
+}
+
+/// A builder that allows to configure a [MockServer](struct.MockServer.html) before it is
+/// started. Use [MockServer::builder](struct.MockServer.html#method.builder) to create one.
+pub struct MockServerBuilder {
+    #[cfg(feature = "https")]
+    tls_cert_pem: Option<PemSource>,
+    #[cfg(feature = "https")]
+    tls_key_pem: Option<PemSource>,
+    #[cfg(feature = "https")]
+    tls_alpn_protocols: Option<Vec<String>>,
+    #[cfg(feature = "https")]
+    tls_client_ca_pem: Option<PemSource>,
+    #[cfg(feature = "https")]
+    tls_client_auth_required: bool,
+    http2_prior_knowledge: bool,
+    shutdown_timeout: Option<Duration>,
+    drop_deadline: Option<Duration>,
+    max_body_bytes: Option<usize>,
+    #[cfg(feature = "decompression")]
+    decompress_request_bodies: bool,
+    keep_alive: bool,
+    idle_timeout: Option<Duration>,
+    introspection: bool,
+    max_connections: Option<usize>,
+    overload_behavior: OverloadBehavior,
+    bind_address: Option<IpAddr>,
+    dual_stack: bool,
+    listeners: Vec<(Option<String>, String)>,
+    proxy: bool,
+    recording_upstream: Option<String>,
+    fallback_upstream: Option<String>,
+    base_path: Option<String>,
+    warn_on_conflicts: bool,
+    #[cfg(unix)]
+    unix_socket: Option<PathBuf>,
+    admin_client: AdminClientConfig,
+}
+
+impl MockServerBuilder {
+    fn new() -> Self {
+        Self {
+            #[cfg(feature = "https")]
+            tls_cert_pem: None,
+            #[cfg(feature = "https")]
+            tls_key_pem: None,
+            #[cfg(feature = "https")]
+            tls_alpn_protocols: None,
+            #[cfg(feature = "https")]
+            tls_client_ca_pem: None,
+            #[cfg(feature = "https")]
+            tls_client_auth_required: false,
+            http2_prior_knowledge: false,
+            shutdown_timeout: None,
+            drop_deadline: None,
+            max_body_bytes: None,
+            #[cfg(feature = "decompression")]
+            decompress_request_bodies: false,
+            keep_alive: true,
+            idle_timeout: None,
+            introspection: true,
+            max_connections: None,
+            overload_behavior: OverloadBehavior::default(),
+            bind_address: None,
+            dual_stack: false,
+            listeners: Vec::new(),
+            proxy: false,
+            recording_upstream: None,
+            fallback_upstream: None,
+            base_path: None,
+            warn_on_conflicts: false,
+            #[cfg(unix)]
+            unix_socket: None,
+            admin_client: AdminClientConfig::default(),
+        }
+    }
+
+    /// Enables HTTP/2 for clients that connect without TLS but with prior knowledge (h2c).
+    /// HTTP/1 clients are still served. HTTPS mock servers serve HTTP/2 to clients that
+    /// negotiate it using ALPN, regardless of this setting (see
+    /// [MockServerBuilder::tls_alpn_protocols](struct.MockServerBuilder.html#method.tls_alpn_protocols)).
+    ///
+    /// * `enabled` - Whether HTTP/2 with prior knowledge is served.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::MockServer;
+    ///
+    /// let server = MockServer::builder().http2_prior_knowledge(true).start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hello").http_version("HTTP/1.1");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/hello")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn http2_prior_knowledge(mut self, enabled: bool) -> Self {
+        self.http2_prior_knowledge = enabled;
+        self
+    }
+
+    /// Sets the maximum time that the mock server waits for in-flight requests to complete when
+    /// it is shut down (see [MockServer::shutdown](struct.MockServer.html#method.shutdown)).
+    /// Connections that are still busy afterwards are closed. The default is 5 seconds.
+    ///
+    /// * `timeout` - The maximum time to wait.
+    pub fn shutdown_timeout<D: Into<Duration>>(mut self, timeout: D) -> Self {
+        self.shutdown_timeout = Some(timeout.into());
+        self
+    }
+
+    /// Sets the maximum time that the mock server waits for in-flight requests to complete when
+    /// the `MockServer` is dropped without being shut down explicitly, such as when a test
+    /// panics. Connections that are still busy afterwards are closed and the port is freed.
+    /// The default is 1 second.
+    ///
+    /// * `deadline` - The maximum time to wait.
+    pub fn drop_deadline<D: Into<Duration>>(mut self, deadline: D) -> Self {
+        self.drop_deadline = Some(deadline.into());
+        self
+    }
+
+    /// Sets the maximum size of request bodies in bytes. The default is 32 MiB.
+    ///
+    /// The limit only applies to requests whose body is required by a mock, i.e. requests
+    /// that match a mock with body requirements (or a custom matcher) apart from their body.
+    /// Requests with a larger body receive a `413 Payload Too Large` response and are not
+    /// matched against any mock. They are still recorded in the request history, but
+    /// without their body. The limit applies to bodies with a `Content-Length` header as well
+    /// as to chunked bodies, which are only read up to the limit.
+    ///
+    /// Bodies that no mock requires are not read at all if they exceed 64 KiB, so that mocks
+    /// which only match on the path or the headers can respond to large uploads without
+    /// buffering them. Such requests are recorded without their body.
+    ///
+    /// * `max_body_bytes` - The maximum number of body bytes.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::MockServer;
+    ///
+    /// let server = MockServer::builder().max_body_bytes(10).start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/upload").body_contains("bytes");
+    ///     then.status(200);
+    /// });
+    ///
+    /// let response = isahc::post(server.url("/upload"), "more than ten bytes").unwrap();
+    ///
+    /// assert_eq!(response.status(), 413);
+    /// mock.assert_hits(0);
+    /// ```
+    pub fn max_body_bytes(mut self, max_body_bytes: usize) -> Self {
+        self.max_body_bytes = Some(max_body_bytes);
+        self
+    }
+
+    /// Sets whether request bodies with a `gzip` or `deflate` content encoding are decompressed
+    /// before they are matched against mocks, so that body matchers can be used for clients
+    /// that compress their requests. Decompressed requests appear without the
+    /// `Content-Encoding` header in the request history as well. Bodies that cannot be
+    /// decompressed are matched as they are. The limit of
+    /// [MockServerBuilder::max_body_bytes](struct.MockServerBuilder.html#method.max_body_bytes)
+    /// applies to the decompressed body. Decompression is disabled by default.
+    ///
+    /// This method requires the `decompression` Cargo feature.
+    ///
+    /// * `enabled` - Whether request bodies are decompressed.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    /// use flate2::{write::GzEncoder, Compression};
+    /// use std::io::Write;
+    ///
+    /// let server = MockServer::builder().decompress_request_bodies(true).start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/users").json_body(serde_json::json!({ "name": "Fred" }));
+    ///     then.status(201);
+    /// });
+    ///
+    /// let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    /// encoder.write_all(br#"{"name":"Fred"}"#).unwrap();
+    ///
+    /// let response = Request::post(server.url("/users"))
+    ///     .header("Content-Encoding", "gzip")
+    ///     .body(encoder.finish().unwrap())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(response.status(), 201);
+    /// mock.assert();
+    /// ```
+    #[cfg(feature = "decompression")]
+    pub fn decompress_request_bodies(mut self, enabled: bool) -> Self {
+        self.decompress_request_bodies = enabled;
+        self
+    }
+
+    /// Sets whether HTTP/1 connections are kept alive after a response has been sent, so that
+    /// clients can reuse them for further requests. If disabled, the mock server sends a
+    /// `Connection: close` header with every response and closes the connection afterwards.
+    /// Keep-alive is enabled by default. Use
+    /// [Then::connection_close](struct.Then.html#method.connection_close) to
+    /// close connections only for the responses of a specific mock.
+    ///
+    /// * `enabled` - Whether connections are kept alive.
+    pub fn keep_alive(mut self, enabled: bool) -> Self {
+        self.keep_alive = enabled;
+        self
+    }
+
+    /// Sets a duration after which connections are closed if no data was sent or received on
+    /// them. This can be used to test how clients deal with pooled connections that were
+    /// dropped by the server. By default, idle connections are kept open.
+    ///
+    /// * `timeout` - The maximum time a connection may be idle.
+    pub fn idle_timeout<D: Into<Duration>>(mut self, timeout: D) -> Self {
+        self.idle_timeout = Some(timeout.into());
+        self
+    }
+
+    /// Limits the number of connections that the mock server serves at the same time. This can
+    /// be used to test how clients deal with a saturated server. What happens to connections
+    /// beyond the limit is set by
+    /// [MockServerBuilder::overload_behavior](struct.MockServerBuilder.html#method.overload_behavior).
+    /// By default, the number of connections is not limited.
+    ///
+    /// * `max_connections` - The maximum number of connections (at least 1).
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use httpmock::OverloadBehavior;
+    /// use std::net::TcpStream;
+    ///
+    /// let server = MockServer::builder()
+    ///     .max_connections(1)
+    ///     .overload_behavior(OverloadBehavior::Respond503)
+    ///     .start();
+    ///
+    /// // Occupy the only connection slot.
+    /// let _connection = TcpStream::connect(server.address()).unwrap();
+    ///
+    /// let response = isahc::get(server.url("/hello")).unwrap();
+    ///
+    /// assert_eq!(response.status(), 503);
+    /// assert_eq!(server.overload_rejections(), 1);
+    /// ```
+    pub fn max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = Some(max_connections);
+        self
+    }
+
+    /// Sets what happens to new connections while the maximum number of connections is
+    /// reached (see
+    /// [MockServerBuilder::max_connections](struct.MockServerBuilder.html#method.max_connections)).
+    /// By default, new connections are queued until another connection is closed.
+    ///
+    /// * `behavior` - The overload behavior.
+    pub fn overload_behavior(mut self, behavior: OverloadBehavior) -> Self {
+        self.overload_behavior = behavior;
+        self
+    }
+
+    /// Enables or disables the introspection endpoints of the admin API, which are enabled by
+    /// default. `GET /__httpmock__/mocks` returns all mocks along with their hit counts and
+    /// `GET /__httpmock__/history` returns the recorded requests as JSON (see the crate
+    /// documentation for all introspection endpoints). If disabled, such requests are matched
+    /// against the mocks like any other request.
+    ///
+    /// * `enabled` - Whether mocks and the request history can be listed.
+    pub fn introspection(mut self, enabled: bool) -> Self {
+        self.introspection = enabled;
+        self
+    }
+
+    /// Sets the IP address that the mock server listens on. The default is `127.0.0.1`. Use
+    /// `Ipv6Addr::LOCALHOST` (`::1`) to serve clients that can only use IPv6. IPv6 addresses
+    /// are enclosed in brackets by [MockServer::url](struct.MockServer.html#method.url), so
+    /// that they can be used in URLs.
+    ///
+    /// * `address` - The IP address to listen on.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::MockServer;
+    /// use std::net::Ipv6Addr;
+    ///
+    /// let server = MockServer::builder().bind_address(Ipv6Addr::LOCALHOST).start();
+    ///
+    /// assert_eq!(server.base_url(), format!("http://[::1]:{}", server.port()));
+    /// ```
+    pub fn bind_address<A: Into<IpAddr>>(mut self, address: A) -> Self {
+        self.bind_address = Some(address.into());
+        self
+    }
+
+    /// Lets the mock server accept IPv4 as well as IPv6 connections on the same port. Unless
+    /// set otherwise by
+    /// [MockServerBuilder::bind_address](struct.MockServerBuilder.html#method.bind_address),
+    /// the mock server listens on all IPv6 interfaces (`::`), because IPv4 clients cannot
+    /// connect to an IPv6 loopback address. The bind address must be an IPv6 address.
+    ///
+    /// * `enabled` - Whether IPv4 and IPv6 connections are accepted.
+    pub fn dual_stack(mut self, enabled: bool) -> Self {
+        self.dual_stack = enabled;
+        self
+    }
+
+    /// Lets the mock server listen on the given TCP address. This method can be called
+    /// several times to let a single mock server listen on several addresses that share the
+    /// same mocks and request history. The first address is the primary one that is returned
+    /// by [MockServer::address](struct.MockServer.html#method.address), all addresses are
+    /// returned by [MockServer::addresses](struct.MockServer.html#method.addresses).
+    ///
+    /// * `address` - The address to listen on, such as `127.0.0.1:0` for a random port.
+    ///
+    /// **Example**:
+    /// ```
+    /// let server = httpmock::MockServer::builder()
+    ///     .listen("127.0.0.1:0")
+    ///     .listen("127.0.0.1:0")
+    ///     .start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/hello");
+    ///     then.status(200);
+    /// });
+    ///
+    /// for address in server.addresses() {
+    ///     isahc::get(format!("http://{}/hello", address)).unwrap();
+    /// }
+    ///
+    /// mock.assert_hits(2);
+    /// ```
+    pub fn listen<S: Into<String>>(mut self, address: S) -> Self {
+        self.listeners.push((None, address.into()));
+        self
+    }
+
+    /// Lets the mock server listen on the given TCP address, like
+    /// [MockServerBuilder::listen](struct.MockServerBuilder.html#method.listen), and tags
+    /// requests that arrive on it with the given name. Use
+    /// [When::listener](struct.When.html#method.listener) to match requests by listener name.
+    ///
+    /// * `name` - The name of the listener.
+    /// * `address` - The address to listen on, such as `127.0.0.1:0` for a random port.
+    pub fn listen_named<N: Into<String>, S: Into<String>>(mut self, name: N, address: S) -> Self {
+        self.listeners.push((Some(name.into()), address.into()));
+        self
+    }
+
+    /// Lets the mock server act as an HTTP proxy. Please refer to
+    /// [MockServer::start_proxy_async](struct.MockServer.html#method.start_proxy_async) for
+    /// more information.
+    ///
+    /// * `enabled` - Whether the mock server acts as a proxy.
+    pub fn proxy(mut self, enabled: bool) -> Self {
+        self.proxy = enabled;
+        self
+    }
+
+    /// Lets the mock server record traffic by forwarding all requests to an upstream server.
+    /// Please refer to
+    /// [MockServer::start_recording_async](struct.MockServer.html#method.start_recording_async)
+    /// for more information.
+    ///
+    /// * `upstream` - The base URL of the upstream server, such as `https://api.github.com`.
+    pub fn record<S: Into<String>>(mut self, upstream: S) -> Self {
+        self.recording_upstream = Some(upstream.into());
+        self
+    }
+
+    /// Forwards all requests that do not match any mock to an upstream server, so that only
+    /// some routes of a real service need to be mocked. Forwarded requests keep their method,
+    /// path, query string, headers and body, except for connection-specific headers and `Host`,
+    /// which is set to the host of the upstream server. The upstream response is streamed back
+    /// to the client as it arrives.
+    ///
+    /// Forwarded requests are recorded in the request history with `forwarded` set to `true`
+    /// and the status of the upstream response. If the upstream server cannot be reached or
+    /// does not respond in time, the request is answered with `502 Bad Gateway` and the error
+    /// is described in the body.
+    ///
+    /// * `upstream` - The base URL of the upstream server, such as
+    /// `https://staging.example.com`. The path of a request is appended to its path.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::prelude::*;
+    ///
+    /// let upstream = MockServer::start();
+    /// upstream.mock(|when, then| {
+    ///     when.path("/users");
+    ///     then.status(200).body("from upstream");
+    /// });
+    ///
+    /// let server = MockServer::builder()
+    ///     .forward_unmatched_to(upstream.base_url())
+    ///     .start();
+    /// server.mock(|when, then| {
+    ///     when.path("/health");
+    ///     then.status(200).body("from mock");
+    /// });
+    ///
+    /// let mut response = isahc::get(server.url("/users")).unwrap();
+    ///
+    /// assert_eq!(response.text().unwrap(), "from upstream");
+    /// ```
+    pub fn forward_unmatched_to<S: Into<String>>(mut self, upstream: S) -> Self {
+        self.fallback_upstream = Some(upstream.into());
+        self
+    }
+
+    /// Sets a path prefix, such as `/api/v2`, for the paths of all mocks of the server. Please
+    /// refer to [MockServer::set_base_path](struct.MockServer.html#method.set_base_path) for
+    /// more information.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::builder().base_path("/api/v2").start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/users");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/users")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn base_path<S: Into<String>>(mut self, path: S) -> Self {
+        self.base_path = normalize_base_path(path.into());
+        self
+    }
+
+    /// Creates mocks whose request requirements contradict each other, such as two different
+    /// paths or a body that is not the expected JSON body, and logs a warning instead of
+    /// failing (see [MockServer::try_mock](struct.MockServer.html#method.try_mock)). Such mocks
+    /// may never match a request. Defaults to `false`.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::builder().warn_on_conflicts(true).start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/a").path("/b");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/b")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn warn_on_conflicts(mut self, enabled: bool) -> Self {
+        self.warn_on_conflicts = enabled;
+        self
+    }
+
+    /// Sets the maximum time that a request to the admin API of a remote mock server may take
+    /// (see [MockServerBuilder::connect](struct.MockServerBuilder.html#method.connect)). The
+    /// default is 30 seconds.
+    ///
+    /// * `timeout` - The request timeout.
+    pub fn admin_timeout<D: Into<Duration>>(mut self, timeout: D) -> Self {
+        self.admin_client.timeout = timeout.into();
+        self
+    }
+
+    /// Sets how often a request to the admin API of a remote mock server is repeated if its
+    /// connection could not be established or was reset (see
+    /// [MockServerBuilder::connect](struct.MockServerBuilder.html#method.connect)). All admin
+    /// API requests can be repeated safely, because requests that create a mock carry an
+    /// idempotency key, so that the remote server creates the mock only once. The default is
+    /// one retry.
+    ///
+    /// * `retries` - The number of retries.
+    pub fn admin_retries(mut self, retries: usize) -> Self {
+        self.admin_client.retries = retries;
+        self
+    }
+
+    /// Lets the mock server listen on a Unix domain socket at the given path instead of a TCP
+    /// port. Please refer to
+    /// [MockServer::start_unix_async](struct.MockServer.html#method.start_unix_async) for more
+    /// information.
+    ///
+    /// * `path` - The path of the socket file.
+    ///
+    /// > Note: This function is only available on Unix platforms.
+    #[cfg(unix)]
+    pub fn unix_socket<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.unix_socket = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the PEM encoded certificate chain that the mock server will present to clients.
+    /// The chain must start with the server certificate, followed by all intermediate
+    /// certificates. Setting a certificate enables HTTPS for the mock server and requires
+    /// a private key to be set as well (see
+    /// [MockServerBuilder::tls_key_pem](struct.MockServerBuilder.html#method.tls_key_pem)).
+    ///
+    /// * `cert_chain` - The PEM encoded certificate chain.
+    ///
+    /// > Note: This function is only available when the `https` feature is enabled.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::MockServer;
+    ///
+    /// let server = MockServer::builder()
+    ///     .tls_cert_pem(std::fs::read("tests/resources/certificates/server_chain.pem").unwrap())
+    ///     .tls_key_pem(std::fs::read("tests/resources/certificates/server_key.pem").unwrap())
+    ///     .start();
+    ///
+    /// assert!(server.base_url().starts_with("https://"));
+    /// ```
+    #[cfg(feature = "https")]
+    pub fn tls_cert_pem<B: AsRef<[u8]>>(mut self, cert_chain: B) -> Self {
+        self.tls_cert_pem = Some(PemSource::Bytes(cert_chain.as_ref().to_vec()));
+        self
+    }
+
+    /// Sets the path to a file that contains the PEM encoded certificate chain that the mock
+    /// server will present to clients. Relative paths are resolved against the current working
+    /// directory. Please refer to
+    /// [MockServerBuilder::tls_cert_pem](struct.MockServerBuilder.html#method.tls_cert_pem)
+    /// for more information.
+    ///
+    /// * `path` - The path to the PEM file.
+    ///
+    /// > Note: This function is only available when the `https` feature is enabled.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::MockServer;
+    ///
+    /// let server = MockServer::builder()
+    ///     .tls_cert_pem_file("tests/resources/certificates/server_chain.pem")
+    ///     .tls_key_pem_file("tests/resources/certificates/server_key.pem")
+    ///     .start();
+    ///
+    /// assert!(server.base_url().starts_with("https://"));
+    /// ```
+    #[cfg(feature = "https")]
+    pub fn tls_cert_pem_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.tls_cert_pem = Some(PemSource::File(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Sets the PEM encoded private key that belongs to the server certificate (see
+    /// [MockServerBuilder::tls_cert_pem](struct.MockServerBuilder.html#method.tls_cert_pem)).
+    /// PKCS#1, PKCS#8 and SEC1 encoded keys are supported.
+    ///
+    /// * `key` - The PEM encoded private key.
+    ///
+    /// > Note: This function is only available when the `https` feature is enabled.
+    #[cfg(feature = "https")]
+    pub fn tls_key_pem<B: AsRef<[u8]>>(mut self, key: B) -> Self {
+        self.tls_key_pem = Some(PemSource::Bytes(key.as_ref().to_vec()));
+        self
+    }
+
+    /// Sets the path to a file that contains the PEM encoded private key that belongs to the
+    /// server certificate. Relative paths are resolved against the current working directory.
+    ///
+    /// * `path` - The path to the PEM file.
+    ///
+    /// > Note: This function is only available when the `https` feature is enabled.
+    #[cfg(feature = "https")]
+    pub fn tls_key_pem_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.tls_key_pem = Some(PemSource::File(path.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Sets the protocols that the mock server offers during TLS application-layer protocol
+    /// negotiation (ALPN), in order of preference (e.g. `["http/1.1"]`). HTTP/2 is served to
+    /// clients that negotiated `h2`. By default, the mock server offers `["h2", "http/1.1"]`.
+    /// An empty list disables ALPN.
+    ///
+    /// * `protocols` - The ALPN protocol identifiers.
+    ///
+    /// > Note: This function is only available when the `https` feature is enabled.
+    #[cfg(feature = "https")]
+    pub fn tls_alpn_protocols<S: Into<String>, I: IntoIterator<Item = S>>(
+        mut self,
+        protocols: I,
+    ) -> Self {
+        self.tls_alpn_protocols = Some(protocols.into_iter().map(|p| p.into()).collect());
+        self
+    }
+
+    /// Requires clients to present a certificate that was issued by one of the given CA
+    /// certificates (mutual TLS). TLS handshakes of clients that present no certificate or
+    /// an untrusted one are rejected and requests sent by them are never recorded.
+    ///
+    /// The verified client certificate is available in `HttpMockRequest::client_certificate`
+    /// and can be matched with
+    /// [When::client_cert_cn](struct.When.html#method.client_cert_cn).
+    ///
+    /// * `ca_pem` - The PEM encoded CA certificates.
+    ///
+    /// > Note: This function is only available when the `https` feature is enabled.
+    ///
+    /// **Example**:
+    /// ```
+    /// use httpmock::MockServer;
+    ///
+    /// let server = MockServer::builder()
+    ///     .tls_cert_pem_file("tests/resources/certificates/server_chain.pem")
+    ///     .tls_key_pem_file("tests/resources/certificates/server_key.pem")
+    ///     .client_auth_required(std::fs::read("tests/resources/certificates/ca.pem").unwrap())
+    ///     .start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.client_cert_cn("my-service");
+    ///     then.status(200);
+    /// });
+    /// ```
+    #[cfg(feature = "https")]
+    pub fn client_auth_required<B: AsRef<[u8]>>(mut self, ca_pem: B) -> Self {
+        self.tls_client_ca_pem = Some(PemSource::Bytes(ca_pem.as_ref().to_vec()));
+        self.tls_client_auth_required = true;
+        self
+    }
+
+    /// Requests a client certificate during the TLS handshake, but also accepts clients
+    /// that do not present one. Certificates that are presented must be issued by one of the
+    /// given CA certificates. Please refer to
+    /// [MockServerBuilder::client_auth_required](struct.MockServerBuilder.html#method.client_auth_required)
+    /// for more information.
+    ///
+    /// * `ca_pem` - The PEM encoded CA certificates.
+    ///
+    /// > Note: This function is only available when the `https` feature is enabled.
+    #[cfg(feature = "https")]
+    pub fn client_auth_optional<B: AsRef<[u8]>>(mut self, ca_pem: B) -> Self {
+        self.tls_client_ca_pem = Some(PemSource::Bytes(ca_pem.as_ref().to_vec()));
+        self.tls_client_auth_required = false;
+        self
+    }
+
+    /// Starts the configured `MockServer` asynchronously.
+    ///
+    /// # Panics
+    /// This method will panic if the configuration is invalid (e.g., if a TLS certificate
+    /// cannot be parsed or does not match the private key).
+    pub async fn start_async(self) -> MockServer {
+        let base_path = self.base_path.clone();
+        let warn_on_conflicts = self.warn_on_conflicts;
+        let config = self
+            .build_config()
+            .unwrap_or_else(|e| panic!("Cannot start mock server: {}", e));
+        let mut server = MockServer::from(create_local_server_adapter(config), None).await;
+        server.base_path = base_path;
+        server.warn_on_conflicts = warn_on_conflicts;
+        server
+    }
+
+    /// Starts the configured `MockServer` synchronously.
+    ///
+    /// # Panics
+    /// This method will panic if the configuration is invalid (e.g., if a TLS certificate
+    /// cannot be parsed or does not match the private key).
+    pub fn start(self) -> MockServer {
+        self.start_async().join()
+    }
+
+    /// Starts the configured `MockServer` asynchronously without listening for network
+    /// connections. Please refer to
+    /// [MockServer::start_in_process_async](struct.MockServer.html#method.start_in_process_async)
+    /// for more information. Settings that only apply to network connections, such as TLS or
+    /// bind addresses, are ignored.
+    ///
+    /// # Panics
+    /// This method will panic if the configuration is invalid.
+    pub async fn start_in_process_async(self) -> MockServer {
+        let base_path = self.base_path.clone();
+        let warn_on_conflicts = self.warn_on_conflicts;
+        let config = self
+            .build_config()
+            .unwrap_or_else(|e| panic!("Cannot start mock server: {}", e));
+        let adapter = LocalMockServerAdapter::new(
+            SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            Arc::new(MockServerState::default()),
+        )
+        .with_config(Arc::new(config))
+        .with_in_process(true);
+        let mut server = MockServer::from(Arc::new(adapter), None).await;
+        server.base_path = base_path;
+        server.warn_on_conflicts = warn_on_conflicts;
+        server
+    }
+
+    /// Starts the configured `MockServer` synchronously without listening for network
+    /// connections. Please refer to
+    /// [MockServerBuilder::start_in_process_async](struct.MockServerBuilder.html#method.start_in_process_async)
+    /// for more information.
+    ///
+    /// # Panics
+    /// This method will panic if the configuration is invalid.
+    pub fn start_in_process(self) -> MockServer {
+        self.start_in_process_async().join()
+    }
+
+    /// Connects to a remote mock server that is running in standalone mode asynchronously.
+    /// Please refer to
+    /// [MockServerBuilder::connect](struct.MockServerBuilder.html#method.connect) for more
+    /// information.
+    pub async fn connect_async(self, address: &str) -> MockServer {
+        let addr = resolve_address(address);
+        let adapter = RemoteMockServerAdapter::with_config(addr, None, self.admin_client);
+        let mut server = MockServer::from(Arc::new(adapter), None).await;
+        server.base_path = self.base_path;
+        server.warn_on_conflicts = self.warn_on_conflicts;
+        server
+    }
+
+    /// Connects to a remote mock server that is running in standalone mode like
+    /// [MockServer::connect](struct.MockServer.html#method.connect), but with the admin API
+    /// client settings of this builder (see
+    /// [MockServerBuilder::admin_timeout](struct.MockServerBuilder.html#method.admin_timeout)
+    /// and [MockServerBuilder::admin_retries](struct.MockServerBuilder.html#method.admin_retries)).
+    /// Settings of the local server, such as TLS or listeners, are ignored.
+    ///
+    /// Each `MockServer` keeps its connections to the remote server alive, so that admin API
+    /// requests do not need a new connection each.
+    ///
+    /// **Example**:
+    /// ```no_run
+    /// use httpmock::MockServer;
+    /// use std::time::Duration;
+    ///
+    /// let server = MockServer::builder()
+    ///     .admin_timeout(Duration::from_secs(5))
+    ///     .admin_retries(3)
+    ///     .connect("127.0.0.1:5000");
+    /// ```
+    pub fn connect(self, address: &str) -> MockServer {
+        self.connect_async(address).join()
+    }
+
+    fn build_config(self) -> Result<ServerConfig, String> {
+        let mut config = ServerConfig {
+            http2_prior_knowledge: self.http2_prior_knowledge,
+            shutdown_timeout: self.shutdown_timeout,
+            drop_deadline: self.drop_deadline,
+            max_body_bytes: self.max_body_bytes,
+            #[cfg(feature = "decompression")]
+            decompress_request_bodies: self.decompress_request_bodies,
+            disable_keep_alive: !self.keep_alive,
+            idle_timeout: self.idle_timeout,
+            disable_introspection: !self.introspection,
+            max_connections: self.max_connections,
+            overload_behavior: self.overload_behavior,
+            bind_address: self.bind_address,
+            dual_stack: self.dual_stack,
+            proxy: self.proxy,
+            ..ServerConfig::default()
+        };
+
+        #[cfg(feature = "https")]
+        if self.proxy {
+            config.proxy_ca = Some(Arc::new(ProxyCa::generate()?));
+        }
+
+        if self.recording_upstream.is_some() && self.fallback_upstream.is_some() {
+            return Err("Recording cannot be combined with forwarding unmatched requests".into());
+        }
+        if let Some(upstream) = &self.recording_upstream {
+            config.recording_upstream = Some(Arc::new(Upstream::new(upstream)?));
+        }
+        if let Some(upstream) = &self.fallback_upstream {
+            config.fallback_upstream = Some(Arc::new(Upstream::new(upstream)?));
+        }
+
+        if self.max_connections == Some(0) {
+            return Err("The maximum number of connections must be greater than zero".into());
+        }
+
+        if self.dual_stack && matches!(self.bind_address, Some(IpAddr::V4(_))) {
+            return Err("Dual-stack binding requires an IPv6 bind address".into());
+        }
+
+        if !self.listeners.is_empty() {
+            #[cfg(unix)]
+            if self.unix_socket.is_some() {
+                return Err("Listen addresses cannot be combined with a Unix domain socket".into());
+            }
+            if self.bind_address.is_some() || self.dual_stack {
+                return Err("Listen addresses cannot be combined with a bind address".into());
+            }
+        }
+
+        for (name, address) in self.listeners {
+            let address = address
+                .to_socket_addrs()
+                .ok()
+                .and_then(|mut addrs| addrs.next())
+                .ok_or_else(|| format!("Invalid listen address: {}", address))?;
+            config.listeners.push(ListenerConfig { name, address });
+        }
+
+        #[cfg(unix)]
+        {
+            config.unix_socket = self.unix_socket;
+        }
+
+        #[cfg(feature = "https")]
+        match (self.tls_cert_pem, self.tls_key_pem) {
+            (None, None) if self.tls_client_ca_pem.is_some() => {
+                return Err("Client authentication requires a TLS certificate".into())
+            }
+            (None, None) => {}
+            (Some(cert), Some(key)) => {
+                let client_auth = match self.tls_client_ca_pem {
+                    Some(ca) => Some(ClientAuthConfig {
+                        ca_pem: ca.read()?,
+                        required: self.tls_client_auth_required,
+                    }),
+                    None => None,
+                };
+                let tls_config = build_tls_config(
+                    &cert.read()?,
+                    &key.read()?,
+                    &self
+                        .tls_alpn_protocols
+                        .unwrap_or_else(|| vec!["h2".into(), "http/1.1".into()]),
+                    client_auth.as_ref(),
+                )?;
+                config.tls = Some(Arc::new(tls_config));
+            }
+            (Some(_), None) => return Err("A TLS certificate requires a private key".into()),
+            (None, Some(_)) => return Err("A TLS private key requires a certificate".into()),
+        }
+
+        Ok(config)
+    }
+}
+
+/// Joins a path to a base URL. An empty path results in the base URL itself.
+fn join_url(base_url: String, path: String) -> String {
+    if path.is_empty() {
+        return base_url;
+    }
+
+    format!("{}/{}", base_url, path.trim_start_matches('/'))
+}
+
+/// Returns the base path with exactly one leading and no trailing slash, or `None` if the path
+/// is empty.
+fn normalize_base_path(path: String) -> Option<String> {
+    match path.trim_matches('/') {
+        "" => None,
+        path => Some(format!("/{}", path)),
+    }
+}
+
+/// Describes where PEM encoded data can be read from.
+#[cfg(feature = "https")]
+enum PemSource {
+    Bytes(Vec<u8>),
+    File(PathBuf),
+}
+
+#[cfg(feature = "https")]
+impl PemSource {
+    fn read(&self) -> Result<Vec<u8>, String> {
+        match self {
+            PemSource::Bytes(bytes) => Ok(bytes.clone()),
+            PemSource::File(path) => {
+                read_file(path).map_err(|e| format!("Cannot read file {:?}: {}", path, e))
+            }
+        }
+    }
+}
+
+const LOCAL_SERVER_ADAPTER_GENERATOR: fn() -> Arc<dyn MockServerAdapter + Send + Sync> =
+    || create_local_server_adapter(ServerConfig::default());
+
+fn create_local_server_adapter(config: ServerConfig) -> Arc<dyn MockServerAdapter + Send + Sync> {
+    let (addr_sender, addr_receiver) = tokio::sync::oneshot::channel::<Vec<SocketAddr>>();
+    let (shutdown_sender, shutdown_receiver) = tokio::sync::oneshot::channel::<Duration>();
+    let (stopped_sender, stopped_receiver) = tokio::sync::oneshot::channel::<()>();
+    let (pause_sender, pause_receiver) = tokio::sync::mpsc::unbounded_channel();
+    let state = Arc::new(MockServerState::default());
+    let server_state = state.clone();
+    #[cfg(feature = "https")]
+    let https = config.tls.is_some();
+    #[cfg(not(feature = "https"))]
+    let https = false;
+    #[cfg(unix)]
+    let socket_path = config.unix_socket.clone();
+    #[cfg(not(unix))]
+    let socket_path = None;
+    #[cfg(feature = "https")]
+    let proxy_ca_pem = config.proxy_ca.as_ref().map(|ca| ca.cert_pem());
+    #[cfg(not(feature = "https"))]
+    let proxy_ca_pem = None;
+
+    let listener_names: Vec<_> = config.listeners.iter().map(|l| l.name.clone()).collect();
+    let service_config = Arc::new(config.clone());
+
+    // The server stops all of its connections before it returns, so that it is stopped once
+    // the task has completed.
+    let server_task = SERVER_RUNTIME.spawn(async move {
+        let result = start_server(
+            config,
+            &server_state,
+            Some(addr_sender),
+            Some(shutdown_receiver),
+            Some(pause_receiver),
+        )
+        .await;
+        let _ = stopped_sender.send(());
+        result
+    });
+
+    let mut addrs = match addr_receiver.join() {
+        Ok(addrs) => addrs,
+        Err(_) => match server_task.join() {
+            Ok(Err(e)) => panic!("Cannot start mock server: {}", e),
+            _ => panic!("Cannot get server address"),
+        },
+    };
+
+    // Clients cannot connect to an unspecified address on all platforms, so the loopback
+    // address is used instead.
+    for addr in addrs.iter_mut() {
+        match addr.ip() {
+            IpAddr::V4(ip) if ip.is_unspecified() => addr.set_ip(Ipv4Addr::LOCALHOST.into()),
+            IpAddr::V6(ip) if ip.is_unspecified() => addr.set_ip(Ipv6Addr::LOCALHOST.into()),
+            _ => {}
+        }
+    }
+
+    let listeners = match listener_names.is_empty() {
+        true => Vec::new(),
+        false => listener_names
+            .into_iter()
+            .zip(addrs.iter().copied())
+            .collect(),
+    };
+
+    Arc::new(
+        LocalMockServerAdapter::new(addrs[0], state)
+            .with_https(https)
+            .with_socket_path(socket_path)
+            .with_proxy_ca_pem(proxy_ca_pem)
+            .with_config(service_config)
+            .with_listeners(listeners)
+            .with_server_handle(ServerHandle {
+                shutdown_sender,
+                stopped_receiver,
+                pause_sender,
+            }),
+    )
+}
+
+/// Resolves the address of a remote mock server, e.g. `127.0.0.1:5000`.
+fn resolve_address(address: &str) -> SocketAddr {
+    address
+        .to_socket_addrs()
+        .expect("Cannot parse address")
+        .find(|addr| addr.is_ipv4())
+        .expect("Not able to resolve the provided host name to an IPv4 address")
+}
+
+lazy_static! {
+    /// The runtime that all local mock servers run on, so that starting a server does not
+    /// require a new thread. It is separate from the runtime of the test (if any), so that
+    /// requests are served even while the test blocks its own runtime.
+    static ref SERVER_RUNTIME: tokio::runtime::Runtime = tokio::runtime::Builder::new_multi_thread()
+        .thread_name("httpmock-server")
+        .enable_all()
+        .build()
+        .expect("Cannot build the mock server runtime");
+    static ref LOCAL_SERVER_POOL_REF: Arc<Pool<Arc<dyn MockServerAdapter + Send + Sync>>> = {
+        let max_servers = read_env("HTTPMOCK_MAX_SERVERS", "25")
+            .parse::<usize>()
+            .expect("Cannot parse environment variable HTTPMOCK_MAX_SERVERS to an integer");
+        Arc::new(Pool::new(max_servers))
+    };
+    static ref REMOTE_SERVER_POOL_REF: Arc<Pool<Arc<dyn MockServerAdapter + Send + Sync>>> =
+        Arc::new(Pool::new(1));
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use async_object_pool::Pool;
+
+    use crate::api::server::{MockServer, LOCAL_SERVER_ADAPTER_GENERATOR};
+    use crate::common::util::Join;
+
+    /// This test ensures that a pooled server is reset when it is returned to the pool and not
+    /// only when it is taken from the pool again.
+    #[test]
+    fn pooled_server_reset_on_return_test() {
+        // Arrange
+        let pool = Arc::new(Pool::new(1));
+        let adapter = pool.take_or_create(LOCAL_SERVER_ADAPTER_GENERATOR).join();
+        let server = MockServer::from(adapter, Some(pool.clone())).join();
+        server.mock(|when, then| {
+            when.path("/pooled");
+            then.status(200);
+        });
+        isahc::get(server.url("/unmatched")).unwrap();
+
+        // Act
+        drop(server);
+
+        // Assert: The adapter is taken from the pool directly, because a MockServer would reset it.
+        let adapter = pool.take_or_create(LOCAL_SERVER_ADAPTER_GENERATOR).join();
+        assert!(adapter.fetch_all_mocks().join().unwrap().is_empty());
+        assert!(adapter
+            .fetch_unmatched_requests()
+            .join()
+            .unwrap()
+            .is_empty());
+    }
 }
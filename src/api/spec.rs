@@ -1,22 +1,72 @@
 use crate::common::data::{
-    MockMatcherFunction, MockServerHttpResponse, Pattern, RequestRequirements,
+    BodyLength, CustomMatcher, HeaderValues, HmacSignature, HttpMockRequest, JsonBodyIgnoringPaths,
+    JsonPathRequirement, JwtClaim, Matcher, MockServerHttpResponse, MultipartPart, NdjsonLine,
+    Pattern, QueryParamValues, QueryString, RequestRequirements,
 };
-use crate::common::util::{get_test_resource_file_path, read_file, update_cell};
-use crate::{Method, Regex};
+#[cfg(feature = "graphql")]
+use crate::common::graphql::{self, GraphQLError, GraphQLSpec};
+#[cfg(feature = "grpc")]
+use crate::common::grpc::{self, GrpcCode};
+use crate::common::json_path;
+#[cfg(feature = "json-schema")]
+use crate::common::json_schema;
+use crate::common::util::{
+    get_test_resource_file_path, path_template_to_regex, read_file, update_cell,
+};
+#[cfg(feature = "xml")]
+use crate::common::{
+    data::XPathRequirement,
+    soap::{self, SoapFaultCode, SoapVersion},
+    xml,
+};
+use crate::Method;
+#[cfg(feature = "regex")]
+use crate::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::cell::Cell;
+use std::net::SocketAddr;
+use std::ops::{Bound, RangeBounds};
 use std::path::Path;
 use std::rc::Rc;
 use std::str::FromStr;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 /// A type that allows the specification of HTTP request values.
 pub struct When {
     pub(crate) expectations: Rc<Cell<RequestRequirements>>,
+    /// Requirements with a single value that were set to different values, e.g. two paths.
+    pub(crate) conflicts: Rc<Cell<Vec<String>>>,
+    #[cfg(feature = "graphql")]
+    pub(crate) graphql: Rc<Cell<GraphQLSpec>>,
 }
 
 impl When {
+    /// Sets a requirement that has a single value. If the requirement was already set to
+    /// another value, the conflict is remembered, so that creating the mock fails instead of
+    /// silently matching only the last value.
+    fn set_single<T, F>(&self, requirement: &str, value: T, field: F)
+    where
+        T: PartialEq + std::fmt::Display,
+        F: FnOnce(&mut RequestRequirements) -> &mut Option<T>,
+    {
+        let mut conflict = None;
+        update_cell(&self.expectations, |e| {
+            let current = field(e);
+            if let Some(previous) = current.as_ref().filter(|previous| **previous != value) {
+                conflict = Some(format!(
+                    "{} equals '{}' and {} equals '{}'",
+                    requirement, previous, requirement, value
+                ));
+            }
+            *current = Some(value);
+        });
+        if let Some(conflict) = conflict {
+            update_cell(&self.conflicts, |c| c.push(conflict));
+        }
+    }
+
     /// Sets the mock server to respond to any incoming request.
     ///
     /// # Example
@@ -41,593 +91,2978 @@ impl When {
         self
     }
 
-    /// Sets the expected HTTP method.
+    /// Applies a function to this `When`, which allows to share request requirements between
+    /// mocks. Every mock has its own copy of the requirements, so the requirements that are added
+    /// to one mock never affect another mock that uses the same function.
     ///
-    /// * `method` - The HTTP method (a [Method](enum.Method.html) or a `String`).
+    /// * `f` - A function that adds request requirements.
     ///
     /// # Example
     /// ```
     /// use httpmock::prelude::*;
+    /// use httpmock::When;
+    /// use isahc::{prelude::*, Request};
     ///
     /// let server = MockServer::start();
     ///
-    /// let mock = server.mock(|when, then|{
-    ///     when.method(GET);
-    ///     then.status(200);
+    /// let items = |when: When| {
+    ///     when.method(GET)
+    ///         .path("/items")
+    ///         .header_exists("Authorization")
+    /// };
+    ///
+    /// let page1 = server.mock(|when, then| {
+    ///     when.and(items).query_param("page", "1");
+    ///     then.status(200).body("page 1");
+    /// });
+    /// let page2 = server.mock(|when, then| {
+    ///     when.and(items).query_param("page", "2");
+    ///     then.status(200).body("page 2");
     /// });
     ///
-    /// isahc::get(server.url("/")).unwrap();
+    /// let mut response = Request::get(server.url("/items?page=2"))
+    ///     .header("Authorization", "Bearer 123")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
     ///
-    /// mock.assert();
+    /// assert_eq!(response.text().unwrap(), "page 2");
+    /// page1.assert_hits(0);
+    /// page2.assert();
     /// ```
-    pub fn method<M: Into<Method>>(mut self, method: M) -> Self {
-        update_cell(&self.expectations, |e| {
-            e.method = Some(method.into().to_string())
-        });
-        self
+    pub fn and<F: FnOnce(When) -> When>(self, f: F) -> Self {
+        f(self)
     }
 
-    /// Sets the expected URL path.
-    /// * `path` - The URL path.
+    /// Requires the request to fulfill at least one of several alternative sets of
+    /// requirements, e.g. one of two paths, so that no duplicate mocks are needed. Each
+    /// alternative is specified by a function that receives an empty `When` (see also
+    /// [all_of](struct.When.html#method.all_of) to combine reusable sets of requirements). All
+    /// other requirements of the mock apply to every alternative.
+    ///
+    /// Alternatives may contain `any_of` themselves, but `any_of` can only be called once per
+    /// mock or alternative.
+    ///
+    /// * `alternatives` - The functions that specify the alternatives. Closures that do not
+    /// capture variables can be passed in an array, other closures need to be boxed.
     ///
     /// # Example
     /// ```
     /// use httpmock::prelude::*;
+    /// use httpmock::When;
     ///
     /// let server = MockServer::start();
     ///
-    /// let mock = server.mock(|when, then|{
-    ///     when.path("/test");
+    /// let m = server.mock(|when, then| {
+    ///     when.method(GET).any_of([
+    ///         |when: When| when.path("/v1/users"),
+    ///         |when: When| when.path("/v2/users").header("x-api-version", "2"),
+    ///     ]);
     ///     then.status(200);
     /// });
     ///
-    /// isahc::get(server.url("/test")).unwrap();
+    /// isahc::get(server.url("/v1/users")).unwrap();
+    /// isahc::get(server.url("/v2/users")).unwrap();
     ///
-    /// mock.assert();
+    /// m.assert_hits(1);
     /// ```
-    pub fn path<S: Into<String>>(mut self, path: S) -> Self {
+    pub fn any_of<F, I>(self, alternatives: I) -> Self
+    where
+        F: FnOnce(When) -> When,
+        I: IntoIterator<Item = F>,
+    {
+        let alternatives: Vec<RequestRequirements> = alternatives
+            .into_iter()
+            .map(|f| {
+                let alternative = When {
+                    expectations: Rc::new(Cell::new(RequestRequirements::new())),
+                    conflicts: self.conflicts.clone(),
+                    #[cfg(feature = "graphql")]
+                    graphql: self.graphql.clone(),
+                };
+                f(alternative).expectations.take()
+            })
+            .collect();
+        if alternatives.is_empty() {
+            panic!("At least one alternative must be given");
+        }
         update_cell(&self.expectations, |e| {
-            e.path = Some(path.into());
+            if e.any_of.is_some() {
+                panic!("any_of can only be called once per mock or alternative");
+            }
+            e.any_of = Some(alternatives);
         });
         self
     }
 
-    /// Sets an substring that the URL path needs to contain.
-    /// * `substring` - The substring to match against.
+    /// Requires the request to fulfill all of the given sets of requirements. This is the same
+    /// as passing each of them to [and](struct.When.html#method.and), but allows to combine
+    /// reusable sets of requirements in one call, e.g. inside of an alternative of
+    /// [any_of](struct.When.html#method.any_of).
+    ///
+    /// * `requirements` - The functions that specify the sets of requirements.
     ///
     /// # Example
     /// ```
     /// use httpmock::prelude::*;
+    /// use httpmock::When;
     ///
     /// let server = MockServer::start();
     ///
-    /// let mock = server.mock(|when, then|{
-    ///     when.path_contains("es");
+    /// let authenticated = |when: When| when.header_exists("authorization");
+    /// let json = |when: When| when.header("content-type", "application/json");
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/users").all_of([authenticated, json]);
     ///     then.status(200);
     /// });
     ///
-    /// isahc::get(server.url("/test")).unwrap();
+    /// isahc::get(server.url("/users")).unwrap();
     ///
-    /// mock.assert();
+    /// m.assert_hits(0);
     /// ```
-    pub fn path_contains<S: Into<String>>(mut self, substring: S) -> Self {
-        update_cell(&self.expectations, |e| {
-            if e.path_contains.is_none() {
-                e.path_contains = Some(Vec::new());
-            }
-            e.path_contains.as_mut().unwrap().push(substring.into());
-        });
-        self
+    pub fn all_of<F, I>(self, requirements: I) -> Self
+    where
+        F: FnOnce(When) -> When,
+        I: IntoIterator<Item = F>,
+    {
+        requirements.into_iter().fold(self, |when, f| f(when))
     }
 
-    /// Sets a regex that the URL path needs to match.
-    /// * `regex` - The regex to match against.
+    /// Sets the point in time from which on the mock matches requests. Requests that are
+    /// received earlier are treated as if the mock did not exist, e.g. to simulate an upstream
+    /// service that comes online in the middle of a test.
+    ///
+    /// * `time` - The point in time, according to the clock of the mock server.
     ///
     /// # Example
     /// ```
     /// use httpmock::prelude::*;
+    /// use std::time::{Duration, SystemTime};
     ///
     /// let server = MockServer::start();
     ///
-    /// let mock = server.mock(|when, then|{
-    ///     when.path_matches(Regex::new("le$").unwrap());
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/health")
+    ///         .active_from(SystemTime::now() + Duration::from_millis(200));
     ///     then.status(200);
     /// });
     ///
-    /// isahc::get(server.url("/example")).unwrap();
+    /// assert_eq!(isahc::get(server.url("/health")).unwrap().status(), 404);
     ///
+    /// std::thread::sleep(Duration::from_millis(200));
+    ///
+    /// assert_eq!(isahc::get(server.url("/health")).unwrap().status(), 200);
     /// mock.assert();
     /// ```
-    pub fn path_matches<R: Into<Regex>>(mut self, regex: R) -> Self {
-        update_cell(&self.expectations, |e| {
-            if e.path_matches.is_none() {
-                e.path_matches = Some(Vec::new());
-            }
-            e.path_matches
-                .as_mut()
-                .unwrap()
-                .push(Pattern::from_regex(regex.into()));
-        });
+    pub fn active_from(self, time: SystemTime) -> Self {
+        update_cell(&self.expectations, |e| e.active_from = Some(time));
         self
     }
 
-    /// Sets a query parameter that needs to be provided.
+    /// Sets the point in time from which on the mock does not match requests anymore, e.g. to
+    /// simulate an upstream service that goes offline in the middle of a test. The mock is
+    /// kept, so that its hits can still be verified.
     ///
-    /// Attention!: The request query keys and values are implicitly *allowed, but is not required*
-    /// to be urlencoded! The value you pass here, however, must be in plain text (i.e. not encoded)!
-    ///
-    /// * `name` - The query parameter name that will matched against.
-    /// * `value` - The value parameter name that will matched against.
+    /// * `time` - The point in time, according to the clock of the mock server.
     ///
+    /// # Example
     /// ```
-    /// // Arrange
-    /// use isahc::get;
     /// use httpmock::prelude::*;
+    /// use std::time::{Duration, SystemTime};
     ///
-    /// let _ = env_logger::try_init();
     /// let server = MockServer::start();
     ///
-    /// let m = server.mock(|when, then|{
-    ///     when.query_param("query", "Metallica");
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/health")
+    ///         .active_until(SystemTime::now() + Duration::from_secs(60));
     ///     then.status(200);
     /// });
     ///
-    /// // Act
-    /// get(server.url("/search?query=Metallica")).unwrap();
-    ///
-    /// // Assert
-    /// m.assert();
+    /// assert_eq!(isahc::get(server.url("/health")).unwrap().status(), 200);
+    /// mock.assert();
     /// ```
-    pub fn query_param<SK: Into<String>, SV: Into<String>>(mut self, name: SK, value: SV) -> Self {
-        update_cell(&self.expectations, |e| {
-            if e.query_param.is_none() {
-                e.query_param = Some(Vec::new());
-            }
-            e.query_param
-                .as_mut()
-                .unwrap()
-                .push((name.into(), value.into()));
-        });
+    pub fn active_until(self, time: SystemTime) -> Self {
+        update_cell(&self.expectations, |e| e.active_until = Some(time));
         self
     }
 
-    /// Sets a query parameter that needs to exist in an HTTP request.
-    ///
-    /// Attention!: The request query key is implicitly *allowed, but is not required* to be
-    /// urlencoded! The value you pass here, however, must be in plain text (i.e. not encoded)!
-    ///
-    /// * `name` - The query parameter name that will matched against.
+    /// Creates the mock disabled, so that it does not match any request until it is enabled
+    /// with [Mock::enable](struct.Mock.html#method.enable), e.g. from another thread.
     ///
+    /// # Example
     /// ```
-    /// // Arrange
-    /// use isahc::get;
     /// use httpmock::prelude::*;
     ///
-    /// let _ = env_logger::try_init();
     /// let server = MockServer::start();
     ///
-    /// let m = server.mock(|when, then| {
-    ///     when.query_param_exists("query");
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/health").disabled();
     ///     then.status(200);
     /// });
     ///
-    /// // Act
-    /// get(server.url("/search?query=Metallica")).unwrap();
+    /// assert_eq!(isahc::get(server.url("/health")).unwrap().status(), 404);
     ///
-    /// // Assert
-    /// m.assert();
+    /// mock.enable();
+    ///
+    /// assert_eq!(isahc::get(server.url("/health")).unwrap().status(), 200);
     /// ```
-    pub fn query_param_exists<S: Into<String>>(mut self, name: S) -> Self {
-        update_cell(&self.expectations, |e| {
-            if e.query_param_exists.is_none() {
-                e.query_param_exists = Some(Vec::new());
-            }
-            e.query_param_exists.as_mut().unwrap().push(name.into());
-        });
+    pub fn disabled(self) -> Self {
+        update_cell(&self.expectations, |e| e.disabled = true);
         self
     }
 
-    /// Sets a requirement for a tuple in an x-www-form-urlencoded request body.
-    /// Please refer to https://url.spec.whatwg.org/#application/x-www-form-urlencoded for more
-    /// information.
+    /// Sets the expected HTTP method. Methods are compared case-sensitively. A mock without any
+    /// method requirement matches requests of all methods (see also
+    /// [methods](struct.When.html#method.methods)).
+    ///
+    /// * `method` - The HTTP method (a [Method](enum.Method.html) or a `String`). Nonstandard
+    /// methods such as `PROPFIND` can be passed as strings or as `Method::Custom`.
+    ///
+    /// # Example
     /// ```
     /// use httpmock::prelude::*;
-    /// use isahc::{prelude::*, Request};
     ///
-    /// // Arrange
     /// let server = MockServer::start();
     ///
-    /// let m = server.mock(|when, then| {
-    ///    when.method(POST)
-    ///        .path("/example")
-    ///        .header("content-type", "application/x-www-form-urlencoded")
-    ///        .x_www_form_urlencoded_tuple("name", "Peter Griffin")
-    ///        .x_www_form_urlencoded_tuple("town", "Quahog");
-    ///    then.status(202);
+    /// let mock = server.mock(|when, then|{
+    ///     when.method(GET);
+    ///     then.status(200);
     /// });
     ///
-    /// let response = Request::post(server.url("/example"))
-    ///    .header("content-type", "application/x-www-form-urlencoded")
-    ///    .body("name=Peter%20Griffin&town=Quahog")
-    ///    .unwrap()
-    ///    .send()
-    ///    .unwrap();
+    /// isahc::get(server.url("/")).unwrap();
     ///
-    /// // Assert
-    /// m.assert();
-    /// assert_eq!(response.status(), 202);
+    /// mock.assert();
     /// ```
-    pub fn x_www_form_urlencoded_tuple<SK: Into<String>, SV: Into<String>>(
-        mut self,
-        key: SK,
-        value: SV,
-    ) -> Self {
-        update_cell(&self.expectations, |e| {
-            if e.x_www_form_urlencoded.is_none() {
-                e.x_www_form_urlencoded = Some(Vec::new());
-            }
-            e.x_www_form_urlencoded
-                .as_mut()
-                .unwrap()
-                .push((key.into(), value.into()));
-        });
+    pub fn method<M: Into<Method>>(mut self, method: M) -> Self {
+        self.set_single("method", method.into().to_string(), |e| &mut e.method);
         self
     }
 
-    /// Sets a requirement for a tuple key in an x-www-form-urlencoded request body.
-    /// Please refer to https://url.spec.whatwg.org/#application/x-www-form-urlencoded for more
-    /// information.
+    /// Sets the HTTP methods of which the request must use one, so that a single mock can
+    /// serve an endpoint that accepts several methods. A mock without any method requirement
+    /// matches requests of all methods.
+    ///
+    /// * `methods` - The accepted HTTP methods.
+    ///
+    /// # Panics
+    /// Panics if no method is given.
+    ///
+    /// # Example
     /// ```
     /// use httpmock::prelude::*;
-    /// use isahc::{prelude::*, Request};
     ///
-    /// // Arrange
     /// let server = MockServer::start();
     ///
-    /// let m = server.mock(|when, then| {
-    ///    when.method(POST)
-    ///        .path("/example")
-    ///        .header("content-type", "application/x-www-form-urlencoded")
-    ///        .x_www_form_urlencoded_key_exists("name")
-    ///        .x_www_form_urlencoded_key_exists("town");
-    ///    then.status(202);
+    /// let mock = server.mock(|when, then|{
+    ///     when.methods([GET, HEAD]).path("/resource");
+    ///     then.status(200);
     /// });
     ///
-    /// let response = Request::post(server.url("/example"))
-    ///    .header("content-type", "application/x-www-form-urlencoded")
-    ///    .body("name=Peter%20Griffin&town=Quahog")
-    ///    .unwrap()
-    ///    .send()
-    ///    .unwrap();
+    /// isahc::get(server.url("/resource")).unwrap();
+    /// isahc::head(server.url("/resource")).unwrap();
     ///
-    /// // Assert
-    /// m.assert();
-    /// assert_eq!(response.status(), 202);
+    /// mock.assert_hits(2);
     /// ```
-    pub fn x_www_form_urlencoded_key_exists<S: Into<String>>(mut self, key: S) -> Self {
+    pub fn methods<M: Into<Method>, I: IntoIterator<Item = M>>(mut self, methods: I) -> Self {
+        let methods: Vec<String> = methods.into_iter().map(|m| m.into().to_string()).collect();
+        assert!(!methods.is_empty(), "At least one method must be given");
         update_cell(&self.expectations, |e| {
-            if e.x_www_form_urlencoded_key_exists.is_none() {
-                e.x_www_form_urlencoded_key_exists = Some(Vec::new());
-            }
-            e.x_www_form_urlencoded_key_exists
-                .as_mut()
-                .unwrap()
-                .push(key.into());
+            e.methods = Some(methods);
         });
         self
     }
 
-    /// Sets the required HTTP request body content.
+    /// Sets the expected HTTP version of the request. HTTP versions are named as in HTTP/1
+    /// request lines, but without a minor version for HTTP/2 (e.g. `HTTP/1.1` or `HTTP/2`).
     ///
-    /// * `body` - The required HTTP request body.
+    /// * `version` - The HTTP version.
     ///
     /// # Example
     /// ```
     /// use httpmock::prelude::*;
-    /// use isahc::{prelude::*, Request};
     ///
     /// let server = MockServer::start();
     ///
     /// let mock = server.mock(|when, then|{
-    ///     when.body("The Great Gatsby");
+    ///     when.http_version("HTTP/1.1");
     ///     then.status(200);
     /// });
     ///
-    /// Request::post(&format!("http://{}/test", server.address()))
-    ///     .body("The Great Gatsby")
-    ///     .unwrap()
-    ///     .send()
-    ///     .unwrap();
+    /// isahc::get(server.url("/")).unwrap();
     ///
     /// mock.assert();
     /// ```
-    pub fn body<S: Into<String>>(mut self, body: S) -> Self {
+    pub fn http_version<S: Into<String>>(mut self, version: S) -> Self {
         update_cell(&self.expectations, |e| {
-            e.body = Some(body.into());
+            e.http_version = Some(version.into());
         });
         self
     }
 
-    /// Sets a [Regex](type.Regex.html) for the expected HTTP body.
+    /// Sets the expected host of the request (without the port), as given by the request URI
+    /// or the `Host` header. Host names are compared case-insensitively. This allows a single
+    /// mock server to serve different mocks for several host names that resolve to it (e.g.
+    /// via `/etc/hosts`). It is also useful for proxy mock servers (see
+    /// [MockServer::start_proxy](struct.MockServer.html#method.start_proxy)), which receive
+    /// requests for many different hosts.
     ///
-    /// * `regex` - The regex that the HTTP request body will matched against.
+    /// * `host` - The host name or IP address.
     ///
+    /// # Example
     /// ```
-    /// use isahc::{prelude::*, Request};
     /// use httpmock::prelude::*;
     ///
-    /// // Arrange
-    /// let _ = env_logger::try_init();
-    ///
     /// let server = MockServer::start();
     ///
-    /// let m = server.mock(|when, then|{
-    ///     when.method(POST)
-    ///         .path("/books")
-    ///         .body_matches(Regex::new("Fellowship").unwrap());
-    ///     then.status(201);
+    /// let mock = server.mock(|when, then|{
+    ///     when.host("127.0.0.1");
+    ///     then.status(200);
     /// });
     ///
-    /// // Act: Send the request
-    /// let response = Request::post(server.url("/books"))
-    ///     .body("The Fellowship of the Ring")
-    ///     .unwrap()
-    ///     .send()
-    ///     .unwrap();
+    /// isahc::get(server.url("/")).unwrap();
     ///
-    /// // Assert
-    /// m.assert();
-    /// assert_eq!(response.status(), 201);
+    /// mock.assert();
     /// ```
-    pub fn body_matches<R: Into<Regex>>(mut self, regex: R) -> Self {
+    pub fn host<S: Into<String>>(mut self, host: S) -> Self {
         update_cell(&self.expectations, |e| {
-            if e.body_matches.is_none() {
-                e.body_matches = Some(Vec::new());
-            }
-            e.body_matches
-                .as_mut()
-                .unwrap()
-                .push(Pattern::from_regex(regex.into()));
+            e.host = Some(host.into());
         });
         self
     }
 
-    /// Sets the expected HTTP body substring.
+    /// Sets the expected name of the listener that the request arrives on. Listeners are
+    /// named using
+    /// [MockServerBuilder::listen_named](struct.MockServerBuilder.html#method.listen_named).
     ///
-    /// * `substring` - The substring that will matched against.
+    /// * `name` - The name of the listener.
     ///
+    /// # Example
     /// ```
     /// use httpmock::prelude::*;
-    /// use isahc::{prelude::*, Request};
     ///
-    /// // Arrange
-    /// let _ = env_logger::try_init();
+    /// let server = MockServer::builder()
+    ///     .listen_named("api", "127.0.0.1:0")
+    ///     .listen_named("cdn", "127.0.0.1:0")
+    ///     .start();
     ///
-    /// let server = MockServer::start();
+    /// let mock = server.mock(|when, then|{
+    ///     when.listener("api");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.listener_url("api", "/")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn listener<S: Into<String>>(mut self, name: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.listener = Some(name.into());
+        });
+        self
+    }
+
+    /// Sets the address of the client that the request must be sent from. This allows to tell
+    /// several clients apart that send requests to the same mock server. Requests that arrive
+    /// on a Unix domain socket have no remote address and never match.
+    ///
+    /// * `addr` - The IP address and port of the client.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use std::io::{Read, Write};
+    /// use std::net::TcpStream;
+    ///
+    /// let server = MockServer::start();
+    /// let mut stream = TcpStream::connect(server.address()).unwrap();
+    /// let client_addr = stream.local_addr().unwrap();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.remote_addr(client_addr);
+    ///     then.status(200);
+    /// });
+    ///
+    /// stream
+    ///     .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+    ///     .unwrap();
+    /// stream.read_to_end(&mut Vec::new()).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn remote_addr(mut self, addr: SocketAddr) -> Self {
+        self.set_single("remote address", addr.to_string(), |e| &mut e.remote_addr);
+        self
+    }
+
+    /// Sets a regex that the address of the client must match. The address is formatted as
+    /// `ip:port`, e.g. `127.0.0.1:52300` or `[::1]:52300`.
+    ///
+    /// * `regex` - The regex that the remote address must match.
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.remote_addr_matches(Regex::new(r"^127\.0\.0\.1:").unwrap());
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn remote_addr_matches<R: Into<Regex>>(mut self, regex: R) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.remote_addr_matches
+                .get_or_insert_with(Vec::new)
+                .push(Pattern::from_regex(regex.into()));
+        });
+        self
+    }
+
+    /// Sets the expected URL path.
+    ///
+    /// The path is compared in the form that the client sent it in, i.e. still percent-encoded:
+    /// a request for `/a%20b` matches `path("/a%20b")`, but not `path("/a b")`.
+    ///
+    /// * `path` - The URL path.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/test");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/test")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn path<S: Into<String>>(mut self, path: S) -> Self {
+        self.set_single("path", path.into(), |e| &mut e.path);
+        self
+    }
+
+    /// Sets an substring that the URL path needs to contain.
+    /// * `substring` - The substring to match against.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path_contains("es");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/test")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn path_contains<S: Into<String>>(mut self, substring: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.path_contains.is_none() {
+                e.path_contains = Some(Vec::new());
+            }
+            e.path_contains.as_mut().unwrap().push(substring.into());
+        });
+        self
+    }
+
+    /// Sets a substring that the URL path must not contain. Paths are compared case-sensitively.
+    /// * `substring` - The substring that must not be part of the path.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path_prefix("/api/")
+    ///         .path_not_contains("/internal/");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/api/users")).unwrap();
+    /// isahc::get(server.url("/api/internal/users")).unwrap();
+    ///
+    /// mock.assert_hits(1);
+    /// ```
+    pub fn path_not_contains<S: Into<String>>(mut self, substring: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.path_not_contains
+                .get_or_insert_with(Vec::new)
+                .push(substring.into());
+        });
+        self
+    }
+
+    /// Sets a prefix that the URL path needs to start with. This is the simplest way to create
+    /// catch-all mocks for a part of an API. Paths are compared case-sensitively.
+    /// * `prefix` - The prefix to match against.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path_prefix("/api/v1/");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/api/v1/users")).unwrap();
+    /// isahc::get(server.url("/api/v2/users")).unwrap();
+    ///
+    /// mock.assert_hits(1);
+    /// ```
+    pub fn path_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.path_prefix
+                .get_or_insert_with(Vec::new)
+                .push(prefix.into());
+        });
+        self
+    }
+
+    /// Sets a suffix that the URL path needs to end with. Paths are compared case-sensitively.
+    /// * `suffix` - The suffix to match against.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path_suffix(".json");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/reports/2024.json")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn path_suffix<S: Into<String>>(mut self, suffix: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.path_suffix
+                .get_or_insert_with(Vec::new)
+                .push(suffix.into());
+        });
+        self
+    }
+
+    /// Sets a regex that the URL path needs to match.
+    /// * `regex` - The regex to match against.
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path_matches(Regex::new("le$").unwrap());
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/example")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn path_matches<R: Into<Regex>>(mut self, regex: R) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.path_matches.is_none() {
+                e.path_matches = Some(Vec::new());
+            }
+            e.path_matches
+                .as_mut()
+                .unwrap()
+                .push(Pattern::from_regex(regex.into()));
+        });
+        self
+    }
+
+    /// Sets a regex that the URL path must not match.
+    /// * `regex` - The regex that the path must not match.
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path_prefix("/users/")
+    ///         .path_not_matches(Regex::new("^/users/[0-9]+$").unwrap());
+    ///     then.status(400);
+    /// });
+    ///
+    /// isahc::get(server.url("/users/abc")).unwrap();
+    /// isahc::get(server.url("/users/42")).unwrap();
+    ///
+    /// mock.assert_hits(1);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn path_not_matches<R: Into<Regex>>(mut self, regex: R) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.path_not_matches
+                .get_or_insert_with(Vec::new)
+                .push(Pattern::from_regex(regex.into()));
+        });
+        self
+    }
+
+    /// Sets a path template that the URL path needs to match. Every `{name}` placeholder in the
+    /// template matches exactly one path segment. A template without placeholders is treated
+    /// like an exact path (see [When::path](struct.When.html#method.path)).
+    ///
+    /// * `template` - The path template, e.g. `/users/{id}`.
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path_template("/users/{id}/posts");
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/users/42/posts")).unwrap();
+    /// isahc::get(server.url("/users/42/comments")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn path_template<S: Into<String>>(self, template: S) -> Self {
+        let template = template.into();
+        match template.contains('{') {
+            true => self.path_matches(
+                Regex::new(&path_template_to_regex(&template))
+                    .expect("Cannot convert the path template to a regex"),
+            ),
+            false => self.path(template),
+        }
+    }
+
+    /// Matches all path requirements of this mock case-insensitively, including prefixes,
+    /// suffixes, substrings and regexes.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path_prefix("/api/users/").path_case_insensitive();
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/API/Users/42")).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn path_case_insensitive(mut self) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.path_case_insensitive = true;
+        });
+        self
+    }
+
+    /// Ignores a trailing slash of the request path, so that `/users/` matches a mock for
+    /// `/users`. A trailing slash of the expected path (see
+    /// [When::path](struct.When.html#method.path)) is ignored as well. All other path
+    /// requirements are matched against the request path without its trailing slash.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/users").path_ignore_trailing_slash();
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/users")).unwrap();
+    /// isahc::get(server.url("/users/")).unwrap();
+    ///
+    /// mock.assert_hits(2);
+    /// ```
+    pub fn path_ignore_trailing_slash(mut self) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.path_ignore_trailing_slash = true;
+        });
+        self
+    }
+
+    /// Matches the path requirements of this mock against the full request path, even if the
+    /// mock server has a base path (see
+    /// [MockServer::set_base_path](struct.MockServer.html#method.set_base_path)). This is
+    /// useful for endpoints outside of the base path, such as a health check at the root.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::builder().base_path("/api/v2").start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/health").ignore_base_path();
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(format!("http://{}/health", server.address())).unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn ignore_base_path(mut self) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.base_path = None;
+        });
+        self
+    }
+
+    /// Sets a query parameter that needs to be provided.
+    ///
+    /// Attention!: The request query keys and values are implicitly *allowed, but is not required*
+    /// to be urlencoded! The value you pass here, however, must be in plain text (i.e. not encoded)!
+    /// Request parameters are decoded before they are compared, so `%20` and `+` both match a
+    /// space (see [When::query_params_raw](#method.query_params_raw) to match the encoded form).
+    ///
+    /// * `name` - The query parameter name that will matched against.
+    /// * `value` - The value parameter name that will matched against.
+    ///
+    /// ```
+    /// // Arrange
+    /// use isahc::get;
+    /// use httpmock::prelude::*;
+    ///
+    /// let _ = env_logger::try_init();
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.query_param("query", "Metallica");
+    ///     then.status(200);
+    /// });
+    ///
+    /// // Act
+    /// get(server.url("/search?query=Metallica")).unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// ```
+    pub fn query_param<SK: Into<String>, SV: Into<String>>(mut self, name: SK, value: SV) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.query_param.is_none() {
+                e.query_param = Some(Vec::new());
+            }
+            e.query_param
+                .as_mut()
+                .unwrap()
+                .push((name.into(), value.into()));
+        });
+        self
+    }
+
+    /// Matches query parameters in the percent-encoded form that the client sent them in,
+    /// instead of decoding them first. This allows to verify how a client encodes its
+    /// parameters, e.g. whether it sends a space as `%20` or as `+`.
+    ///
+    /// This applies to the names and values of
+    /// [When::query_param](#method.query_param),
+    /// [When::query_param_exists](#method.query_param_exists),
+    /// [When::query_param_missing](#method.query_param_missing) and
+    /// [When::query_param_matches](#method.query_param_matches), which must then be given in
+    /// their encoded form. Paths are always compared in their encoded form.
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.query_params_raw().query_param("q", "hello%20world");
+    ///     then.status(200);
+    /// });
+    ///
+    /// let percent = isahc::get(server.url("/search?q=hello%20world")).unwrap();
+    /// let plus = isahc::get(server.url("/search?q=hello+world")).unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(percent.status(), 200);
+    /// assert_eq!(plus.status(), 404);
+    /// ```
+    pub fn query_params_raw(mut self) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.query_params_raw = true;
+        });
+        self
+    }
+
+    /// Sets a query parameter that needs to exist in an HTTP request.
+    ///
+    /// Attention!: The request query key is implicitly *allowed, but is not required* to be
+    /// urlencoded! The value you pass here, however, must be in plain text (i.e. not encoded)!
+    ///
+    /// * `name` - The query parameter name that will matched against.
+    ///
+    /// ```
+    /// // Arrange
+    /// use isahc::get;
+    /// use httpmock::prelude::*;
+    ///
+    /// let _ = env_logger::try_init();
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.query_param_exists("query");
+    ///     then.status(200);
+    /// });
+    ///
+    /// // Act
+    /// get(server.url("/search?query=Metallica")).unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// ```
+    pub fn query_param_exists<S: Into<String>>(mut self, name: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.query_param_exists.is_none() {
+                e.query_param_exists = Some(Vec::new());
+            }
+            e.query_param_exists.as_mut().unwrap().push(name.into());
+        });
+        self
+    }
+
+    /// Sets a query parameter that must not be present in an HTTP request, e.g. to verify that
+    /// a client does not send internal flags.
+    ///
+    /// * `name` - The query parameter name that will matched against.
+    ///
+    /// ```
+    /// // Arrange
+    /// use isahc::get;
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/search").query_param_missing("debug");
+    ///     then.status(200);
+    /// });
+    ///
+    /// // Act
+    /// let with_flag = get(server.url("/search?query=Metallica&debug=true")).unwrap();
+    /// let without_flag = get(server.url("/search?query=Metallica")).unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(with_flag.status(), 404);
+    /// assert_eq!(without_flag.status(), 200);
+    /// ```
+    pub fn query_param_missing<S: Into<String>>(mut self, name: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.query_param_missing.is_none() {
+                e.query_param_missing = Some(Vec::new());
+            }
+            e.query_param_missing.as_mut().unwrap().push(name.into());
+        });
+        self
+    }
+
+    /// Sets the values of a query parameter that is repeated in the query string, e.g.
+    /// `?id=1&id=2&id=3`. The request must contain exactly these values for the parameter, but
+    /// they may appear in any order.
+    ///
+    /// * `name` - The query parameter name that will matched against.
+    /// * `values` - The (decoded) values that the query parameter needs to have.
+    ///
+    /// ```
+    /// // Arrange
+    /// use isahc::get;
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/users").query_param_values("id", ["1", "2", "3"]);
+    ///     then.status(200);
+    /// });
+    ///
+    /// // Act
+    /// let all_ids = get(server.url("/users?id=3&id=1&id=2")).unwrap();
+    /// let some_ids = get(server.url("/users?id=1&id=2")).unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(all_ids.status(), 200);
+    /// assert_eq!(some_ids.status(), 404);
+    /// ```
+    pub fn query_param_values<S: Into<String>, I: IntoIterator<Item = V>, V: AsRef<str>>(
+        mut self,
+        name: S,
+        values: I,
+    ) -> Self {
+        let values: Vec<String> = values.into_iter().map(|v| v.as_ref().to_string()).collect();
+        if values.is_empty() {
+            panic!("At least one value must be given");
+        }
+        update_cell(&self.expectations, |e| {
+            if e.query_param_values.is_none() {
+                e.query_param_values = Some(Vec::new());
+            }
+            e.query_param_values
+                .as_mut()
+                .unwrap()
+                .push(QueryParamValues {
+                    name: name.into(),
+                    values,
+                });
+        });
+        self
+    }
+
+    /// Sets the complete query string of the request (e.g. `?page=1&tag=rust`). The request
+    /// must have exactly these query parameters, so unlike
+    /// [When::query_param](#method.query_param), any additional parameter makes the request
+    /// not match. The parameters may appear in any order. Use
+    /// [When::query_string_ordered](#method.query_string_ordered) if the order matters.
+    ///
+    /// * `query` - The URL encoded query string. A leading `?` is ignored. An empty string
+    ///   requires the request to have no query parameters at all.
+    ///
+    /// ```
+    /// // Arrange
+    /// use isahc::get;
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/search").query_string("?page=1&tag=rust");
+    ///     then.status(200);
+    /// });
+    ///
+    /// // Act
+    /// let reordered = get(server.url("/search?tag=rust&page=1")).unwrap();
+    /// let extra_param = get(server.url("/search?page=1&tag=rust&debug=true")).unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(reordered.status(), 200);
+    /// assert_eq!(extra_param.status(), 404);
+    /// ```
+    pub fn query_string<S: AsRef<str>>(mut self, query: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.query_string = Some(QueryString::parse(query.as_ref(), false));
+        });
+        self
+    }
+
+    /// Sets the complete query string of the request, like
+    /// [When::query_string](#method.query_string), but additionally requires the query
+    /// parameters to appear in the given order.
+    ///
+    /// * `query` - The URL encoded query string. A leading `?` is ignored.
+    ///
+    /// ```
+    /// // Arrange
+    /// use isahc::get;
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/search").query_string_ordered("page=1&tag=rust");
+    ///     then.status(200);
+    /// });
+    ///
+    /// // Act
+    /// let ordered = get(server.url("/search?page=1&tag=rust")).unwrap();
+    /// let reordered = get(server.url("/search?tag=rust&page=1")).unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(ordered.status(), 200);
+    /// assert_eq!(reordered.status(), 404);
+    /// ```
+    pub fn query_string_ordered<S: AsRef<str>>(mut self, query: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.query_string = Some(QueryString::parse(query.as_ref(), true));
+        });
+        self
+    }
+
+    /// Requires the nested query parameters of the request to contain the given object. Query
+    /// parameters with bracketed names, as used by PHP and Rails (e.g.
+    /// `filter[status]=open&filter[page]=2&tags[]=a&tags[]=b`), are combined into an object
+    /// like `{"filter": {"status": "open", "page": "2"}, "tags": ["a", "b"]}` first. Single
+    /// parameters can also be matched by their full name with
+    /// [When::query_param](#method.query_param), e.g. `query_param("filter[status]", "open")`.
+    ///
+    /// Query parameter values are always strings, so numbers and booleans in the object are
+    /// compared by their string representation. Like with
+    /// [When::json_body_partial](#method.json_body_partial), the request may contain additional
+    /// parameters. If this method is called more than once, all objects must be contained.
+    ///
+    /// * `object` - The JSON object that the query parameters must contain.
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use serde_json::json;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/issues")
+    ///         .query_object(json!({ "filter": { "status": "open", "page": 2 } }));
+    ///     then.status(200);
+    /// });
+    ///
+    /// let response = isahc::get(server.url(
+    ///     "/issues?filter%5Bstatus%5D=open&filter%5Bpage%5D=2&sort=created",
+    /// ))
+    /// .unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(response.status(), 200);
+    /// ```
+    pub fn query_object(self, object: Value) -> Self {
+        assert!(
+            object.is_object(),
+            "The query object must be a JSON object, but was {}",
+            object
+        );
+        update_cell(&self.expectations, |e| {
+            e.query_objects
+                .get_or_insert_with(Vec::new)
+                .push(stringify_json_values(object));
+        });
+        self
+    }
+
+    /// Sets a query parameter whose value needs to match a regular expression.
+    ///
+    /// * `name` - The query parameter name that will matched against.
+    /// * `regex` - The regular expression that the (decoded) value needs to match.
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.query_param_matches("page", Regex::new("^[0-9]+$").unwrap());
+    ///     then.status(200);
+    /// });
+    ///
+    /// isahc::get(server.url("/search?page=12")).unwrap();
+    ///
+    /// m.assert();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn query_param_matches<S: Into<String>, R: Into<Regex>>(
+        mut self,
+        name: S,
+        regex: R,
+    ) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.query_param_matches.is_none() {
+                e.query_param_matches = Some(Vec::new());
+            }
+            e.query_param_matches
+                .as_mut()
+                .unwrap()
+                .push((name.into(), Pattern::from_regex(regex.into())));
+        });
+        self
+    }
+
+    /// Sets a requirement for a tuple in an x-www-form-urlencoded request body, e.g. a field of
+    /// an HTML form or an OAuth token request. The body is decoded before it is matched, so the
+    /// order of the fields and how they are encoded (e.g. `+` or `%20` for spaces) do not
+    /// matter. Please refer to https://url.spec.whatwg.org/#application/x-www-form-urlencoded
+    /// for more information.
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // Arrange
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///    when.method(POST)
+    ///        .path("/example")
+    ///        .header("content-type", "application/x-www-form-urlencoded")
+    ///        .x_www_form_urlencoded_tuple("name", "Peter Griffin")
+    ///        .x_www_form_urlencoded_tuple("town", "Quahog");
+    ///    then.status(202);
+    /// });
+    ///
+    /// let response = Request::post(server.url("/example"))
+    ///    .header("content-type", "application/x-www-form-urlencoded")
+    ///    .body("name=Peter%20Griffin&town=Quahog")
+    ///    .unwrap()
+    ///    .send()
+    ///    .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 202);
+    /// ```
+    pub fn x_www_form_urlencoded_tuple<SK: Into<String>, SV: Into<String>>(
+        mut self,
+        key: SK,
+        value: SV,
+    ) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.x_www_form_urlencoded.is_none() {
+                e.x_www_form_urlencoded = Some(Vec::new());
+            }
+            e.x_www_form_urlencoded
+                .as_mut()
+                .unwrap()
+                .push((key.into(), value.into()));
+        });
+        self
+    }
+
+    /// Sets a requirement for a tuple key in an x-www-form-urlencoded request body. The key is
+    /// matched against the decoded body (see
+    /// [When::x_www_form_urlencoded_tuple](struct.When.html#method.x_www_form_urlencoded_tuple)).
+    /// Please refer to https://url.spec.whatwg.org/#application/x-www-form-urlencoded for more
+    /// information.
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // Arrange
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///    when.method(POST)
+    ///        .path("/example")
+    ///        .header("content-type", "application/x-www-form-urlencoded")
+    ///        .x_www_form_urlencoded_key_exists("name")
+    ///        .x_www_form_urlencoded_key_exists("town");
+    ///    then.status(202);
+    /// });
+    ///
+    /// let response = Request::post(server.url("/example"))
+    ///    .header("content-type", "application/x-www-form-urlencoded")
+    ///    .body("name=Peter%20Griffin&town=Quahog")
+    ///    .unwrap()
+    ///    .send()
+    ///    .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 202);
+    /// ```
+    pub fn x_www_form_urlencoded_key_exists<S: Into<String>>(mut self, key: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.x_www_form_urlencoded_key_exists.is_none() {
+                e.x_www_form_urlencoded_key_exists = Some(Vec::new());
+            }
+            e.x_www_form_urlencoded_key_exists
+                .as_mut()
+                .unwrap()
+                .push(key.into());
+        });
+        self
+    }
+
+    /// Requires a `multipart/form-data` request body (e.g. a file upload) to contain a part.
+    /// Passing a name only requires a part with this name to exist. A
+    /// [MultipartPart](struct.MultipartPart.html) can additionally require the filename,
+    /// content type and body of the part. The boundary of the body is taken from the
+    /// `Content-Type` header of the request.
+    ///
+    /// * `part` - The part name or a [MultipartPart](struct.MultipartPart.html).
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use httpmock::MultipartPart;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // Arrange
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///    when.method(POST)
+    ///        .path("/upload")
+    ///        .multipart_part("description")
+    ///        .multipart_part(
+    ///            MultipartPart::new("file")
+    ///                .filename("notes.txt")
+    ///                .content_type("text/plain")
+    ///                .body("Hello"),
+    ///        );
+    ///    then.status(201);
+    /// });
+    ///
+    /// let body = "--XYZ\r\n\
+    ///             Content-Disposition: form-data; name=\"description\"\r\n\r\n\
+    ///             My notes\r\n\
+    ///             --XYZ\r\n\
+    ///             Content-Disposition: form-data; name=\"file\"; filename=\"notes.txt\"\r\n\
+    ///             Content-Type: text/plain\r\n\r\n\
+    ///             Hello\r\n\
+    ///             --XYZ--\r\n";
+    ///
+    /// let response = Request::post(server.url("/upload"))
+    ///    .header("content-type", "multipart/form-data; boundary=XYZ")
+    ///    .body(body)
+    ///    .unwrap()
+    ///    .send()
+    ///    .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
+    /// ```
+    pub fn multipart_part<P: Into<MultipartPart>>(self, part: P) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.multipart_parts
+                .get_or_insert_with(Vec::new)
+                .push(part.into());
+        });
+        self
+    }
+
+    /// Sets the required HTTP request body content.
+    ///
+    /// * `body` - The required HTTP request body.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.body("The Great Gatsby");
+    ///     then.status(200);
+    /// });
+    ///
+    /// Request::post(&server.url("/test"))
+    ///     .body("The Great Gatsby")
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn body<S: Into<String>>(mut self, body: S) -> Self {
+        self.set_single("body", body.into(), |e| &mut e.body);
+        self
+    }
+
+    /// Sets the exact bytes of the required HTTP request body. Unlike
+    /// [body](struct.When.html#method.body), this matches binary payloads (e.g. protobuf
+    /// messages or images) byte for byte.
+    ///
+    /// * `body` - The required HTTP request body.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.body_bytes([0x89, b'P', b'N', b'G', 0xff]);
+    ///     then.status(200);
+    /// });
+    ///
+    /// Request::post(&server.url("/upload"))
+    ///     .body(vec![0x89, b'P', b'N', b'G', 0xff])
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn body_bytes(mut self, body: impl AsRef<[u8]>) -> Self {
+        let body = base64::encode(body);
+        self.set_single("body bytes", body, |e| &mut e.body_base64);
+        self
+    }
+
+    /// Sets the exact length in bytes that the HTTP request body must have, regardless of its
+    /// content.
+    ///
+    /// * `len` - The length of the body in bytes.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/upload").body_len(4);
+    ///     then.status(201);
+    /// });
+    ///
+    /// Request::post(&server.url("/upload"))
+    ///     .body("abcd")
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn body_len(self, len: usize) -> Self {
+        self.body_len_range(len..=len)
+    }
+
+    /// Sets the range that the length in bytes of the HTTP request body must be in, regardless
+    /// of its content, e.g. to verify how a client splits uploads into chunks.
+    ///
+    /// * `range` - The range of accepted lengths, e.g. `1..=4096` or `1024..`.
+    ///
+    /// # Panics
+    /// Panics if the range is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/upload").body_len_range(1..=4096);
+    ///     then.status(201);
+    /// });
+    ///
+    /// Request::post(&server.url("/upload"))
+    ///     .body(vec![0u8; 4096])
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn body_len_range<R: RangeBounds<usize>>(mut self, range: R) -> Self {
+        let min = match range.start_bound() {
+            Bound::Included(min) => Some(*min),
+            Bound::Excluded(min) => Some(min + 1),
+            Bound::Unbounded => None,
+        };
+        let max = match range.end_bound() {
+            Bound::Included(max) => Some(*max),
+            Bound::Excluded(max) => Some(
+                max.checked_sub(1)
+                    .expect("The body length range must not be empty"),
+            ),
+            Bound::Unbounded => None,
+        };
+        assert!(
+            min.unwrap_or(0) <= max.unwrap_or(usize::MAX),
+            "The body length range must not be empty"
+        );
+        self.set_single("body length", BodyLength { min, max }, |e| &mut e.body_len);
+        self
+    }
+
+    /// Requires the body of an HTTP/1.1 request to be sent with chunked transfer encoding
+    /// (`Transfer-Encoding: chunked`), as clients do that stream a body of unknown length
+    /// instead of buffering it. The chunks are combined before they are matched, so all other
+    /// body requirements apply to the complete body.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Body, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/upload").chunked_body().body("streamed");
+    ///     then.status(201);
+    /// });
+    ///
+    /// // A reader has no known length, so the body is sent in chunks.
+    /// Request::post(&server.url("/upload"))
+    ///     .body(Body::from_reader(std::io::Cursor::new("streamed")))
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn chunked_body(self) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.chunked_body = true;
+        });
+        self
+    }
+
+    /// Requires a header to hold the HMAC-SHA256 signature of the raw request body, computed with
+    /// the given secret, as webhook senders like GitHub or Shopify add it. The signature may be
+    /// hex encoded (in any case) or base64 encoded and may have a `sha256=` prefix (e.g.
+    /// `X-Hub-Signature-256: sha256=<hex>`). The mismatch report shows the expected signature.
+    ///
+    /// * `header_name` - The name of the header that holds the signature.
+    /// * `secret` - The secret that the signature is computed with.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/webhook")
+    ///         .hmac_sha256_signature("X-Hub-Signature-256", "Jefe");
+    ///     then.status(204);
+    /// });
+    ///
+    /// Request::post(&server.url("/webhook"))
+    ///     .header(
+    ///         "X-Hub-Signature-256",
+    ///         "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+    ///     )
+    ///     .body("what do ya want for nothing?")
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn hmac_sha256_signature<SH: Into<String>, SS: Into<String>>(
+        self,
+        header_name: SH,
+        secret: SS,
+    ) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.hmac_signatures
+                .get_or_insert_with(Vec::new)
+                .push(HmacSignature {
+                    header: header_name.into(),
+                    secret: secret.into(),
+                });
+        });
+        self
+    }
+
+    /// Sets a [Regex](type.Regex.html) for the expected HTTP body.
+    ///
+    /// * `regex` - The regex that the HTTP request body will matched against.
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// ```
+    /// use isahc::{prelude::*, Request};
+    /// use httpmock::prelude::*;
+    ///
+    /// // Arrange
+    /// let _ = env_logger::try_init();
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.method(POST)
+    ///         .path("/books")
+    ///         .body_matches(Regex::new("Fellowship").unwrap());
+    ///     then.status(201);
+    /// });
+    ///
+    /// // Act: Send the request
+    /// let response = Request::post(server.url("/books"))
+    ///     .body("The Fellowship of the Ring")
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn body_matches<R: Into<Regex>>(mut self, regex: R) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.body_matches.is_none() {
+                e.body_matches = Some(Vec::new());
+            }
+            e.body_matches
+                .as_mut()
+                .unwrap()
+                .push(Pattern::from_regex(regex.into()));
+        });
+        self
+    }
+
+    /// Sets a regex that the HTTP body must not match.
+    ///
+    /// * `regex` - The regex that the body must not match.
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // Arrange
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.method(POST)
+    ///         .path("/logs")
+    ///         .body_not_matches(Regex::new("password=").unwrap());
+    ///     then.status(201);
+    /// });
+    ///
+    /// // Act: Send the request
+    /// let response = Request::post(server.url("/logs"))
+    ///     .body("user=alice")
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn body_not_matches<R: Into<Regex>>(mut self, regex: R) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.body_not_matches
+                .get_or_insert_with(Vec::new)
+                .push(Pattern::from_regex(regex.into()));
+        });
+        self
+    }
+
+    /// Sets the expected HTTP body substring.
+    ///
+    /// * `substring` - The substring that will matched against.
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // Arrange
+    /// let _ = env_logger::try_init();
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.path("/books")
+    ///         .body_contains("Ring");
+    ///     then.status(201);
+    /// });
+    ///
+    /// // Act: Send the request
+    /// let response = Request::post(server.url("/books"))
+    ///     .body("The Fellowship of the Ring")
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
+    /// ```
+    pub fn body_contains<S: Into<String>>(mut self, substring: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.body_contains.is_none() {
+                e.body_contains = Some(Vec::new());
+            }
+            e.body_contains.as_mut().unwrap().push(substring.into());
+        });
+        self
+    }
+
+    /// Sets a substring that the HTTP body must not contain, e.g. a deprecated field that
+    /// clients should no longer send. Bodies are compared case-sensitively.
+    ///
+    /// * `substring` - The substring that must not be part of the body.
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // Arrange
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.path("/users")
+    ///         .body_not_contains("\"nickname\"");
+    ///     then.status(201);
+    /// });
+    ///
+    /// // Act: Send the request
+    /// let response = Request::post(server.url("/users"))
+    ///     .body(r#"{ "name": "Fred" }"#)
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
+    /// ```
+    pub fn body_not_contains<S: Into<String>>(mut self, substring: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.body_not_contains
+                .get_or_insert_with(Vec::new)
+                .push(substring.into());
+        });
+        self
+    }
+
+    /// Sets the expected JSON body. This method expects a [serde_json::Value](../serde_json/enum.Value.html)
+    /// that will be serialized/deserialized to/from a JSON string.
+    ///
+    /// Note that this method does not set the `content-type` header automatically, so you
+    /// need to provide one yourself!
+    ///
+    /// * `body` - The HTTP body object that will be serialized to JSON using serde.
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use serde_json::json;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // Arrange
+    /// let _ = env_logger::try_init();
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.path("/user")
+    ///         .header("content-type", "application/json")
+    ///         .json_body(json!({ "name": "Hans" }));
+    ///     then.status(201);
+    /// });
+    ///
+    /// // Act: Send the request and deserialize the response to JSON
+    /// let mut response = Request::post(&server.url("/user"))
+    ///     .header("content-type", "application/json")
+    ///     .body(json!({ "name": "Hans" }).to_string())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
+    /// ```
+    pub fn json_body<V: Into<serde_json::Value>>(mut self, value: V) -> Self {
+        self.set_single("JSON body", value.into(), |e| &mut e.json_body);
+        self
+    }
+
+    /// Sets the expected JSON body, ignoring the values at the given
+    /// [JSONPath](https://goessner.net/articles/JsonPath/) expressions. Use this for fields that
+    /// change with every request, such as timestamps or generated IDs. Ignored object members
+    /// may be missing from the request body.
+    ///
+    /// * `body` - The expected HTTP body object that will be serialized to JSON using serde.
+    /// * `paths` - The JSONPath expressions of the values to ignore (e.g. `$.timestamp`).
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use serde_json::json;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // Arrange
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.path("/events").json_body_ignoring_paths(
+    ///         json!({ "type": "login", "timestamp": 0 }),
+    ///         ["$.timestamp"],
+    ///     );
+    ///     then.status(201);
+    /// });
+    ///
+    /// // Act
+    /// let response = Request::post(&server.url("/events"))
+    ///     .body(json!({ "type": "login", "timestamp": 1700000000 }).to_string())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
+    /// ```
+    pub fn json_body_ignoring_paths<V, I, S>(self, body: V, paths: I) -> Self
+    where
+        V: Into<serde_json::Value>,
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let ignored_paths: Vec<String> = paths.into_iter().map(Into::into).collect();
+        for path in &ignored_paths {
+            if let Err(e) = json_path::check_path(path) {
+                panic!("{}", e);
+            }
+        }
+        let value = JsonBodyIgnoringPaths {
+            body: body.into(),
+            ignored_paths,
+        };
+        self.set_single("JSON body", value, |e| &mut e.json_body_ignoring_paths);
+        self
+    }
+
+    /// Sets the expected JSON body. This method expects a serializable serde object
+    /// that will be serialized/deserialized to/from a JSON string.
+    ///
+    /// Note that this method does not set the "content-type" header automatically, so you
+    /// need to provide one yourself!
+    ///
+    /// * `body` - The HTTP body object that will be serialized to JSON using serde.
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use serde_json::json;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// // This is a temporary type that we will use for this test
+    /// #[derive(serde::Serialize, serde::Deserialize)]
+    /// struct TestUser {
+    ///     name: String,
+    /// }
+    ///
+    /// // Arrange
+    /// let _ = env_logger::try_init();
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.path("/user")
+    ///         .header("content-type", "application/json")
+    ///         .json_body_obj(&TestUser {
+    ///             name: String::from("Fred"),
+    ///         });
+    ///     then.status(200);
+    /// });
+    ///
+    /// // Act: Send the request and deserialize the response to JSON
+    /// let mut response = Request::post(&server.url("/user"))
+    ///     .header("content-type", "application/json")
+    ///     .body(json!(&TestUser {
+    ///         name: "Fred".to_string()
+    ///     }).to_string())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(response.status(), 200);
+    /// ```
+    pub fn json_body_obj<'a, T>(self, body: &T) -> Self
+    where
+        T: Serialize + Deserialize<'a>,
+    {
+        let json_value = serde_json::to_value(body).expect("Cannot serialize json body to JSON");
+        self.json_body(json_value)
+    }
+
+    /// Sets the expected partial JSON body.
+    ///
+    /// **Attention: The partial string needs to be a valid JSON string. It must contain
+    /// the full object hierarchy from the original JSON object but can leave out irrelevant
+    /// attributes (see example).**
+    ///
+    /// Note that this method does not set the `content-type` header automatically, so you
+    /// need to provide one yourself!
+    ///
+    /// String format and attribute order are irrelevant.
+    ///
+    /// * `partial_body` - The HTTP body object that will be serialized to JSON using serde.
+    ///
+    /// ## Example
+    /// Suppose your application sends the following JSON request body:
+    /// ```json
+    /// {
+    ///     "parent_attribute" : "Some parent data goes here",
+    ///     "child" : {
+    ///         "target_attribute" : "Example",
+    ///         "other_attribute" : "Another value"
+    ///     }
+    /// }
+    /// ```
+    /// If we only want to verify that `target_attribute` has value `Example` without the need
+    /// to provide a full JSON object, we can use this method as follows:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mut mock = server.mock(|when, then|{
+    ///     when.json_body_partial(r#"
+    ///         {
+    ///             "child" : {
+    ///                 "target_attribute" : "Example"
+    ///             }
+    ///          }
+    ///     "#);
+    ///     then.status(200);
+    /// });
+    /// ```
+    /// Please note that the JSON partial contains the full object hierarchy, i.e. it needs to start
+    /// from the root! It leaves out irrelevant attributes, however (`parent_attribute`
+    /// and `child.other_attribute`).
+    pub fn json_body_partial<S: Into<String>>(mut self, partial: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.json_body_includes.is_none() {
+                e.json_body_includes = Some(Vec::new());
+            }
+            let value = Value::from_str(&partial.into())
+                .expect("cannot convert JSON string to serde value");
+            e.json_body_includes.as_mut().unwrap().push(value);
+        });
+        self
+    }
+
+    /// Like [When::json_body_partial](struct.When.html#method.json_body_partial), but the
+    /// request does not match if its JSON body contains fields that are not part of the
+    /// partial, e.g. for contract tests where new fields must not go unnoticed. If this method
+    /// is called more than once, the request body may contain the fields of all partials.
+    ///
+    /// Arrays may not have more elements than the array in the partial. The mismatch report
+    /// lists the JSONPaths of all unexpected fields.
+    ///
+    /// * `partial` - The JSON partial.
+    ///
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// // Arrange
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.json_body_partial_strict(r#"{ "user": { "name": "Fred" } }"#);
+    ///     then.status(200);
+    /// });
+    ///
+    /// // Act
+    /// let matching = isahc::post(server.url("/"), r#"{ "user": { "name": "Fred" } }"#).unwrap();
+    /// let extra = isahc::post(server.url("/"), r#"{ "user": { "name": "Fred", "age": 30 } }"#)
+    ///     .unwrap();
+    ///
+    /// // Assert
+    /// m.assert();
+    /// assert_eq!(matching.status(), 200);
+    /// assert_eq!(extra.status(), 404);
+    /// ```
+    pub fn json_body_partial_strict<S: Into<String>>(self, partial: S) -> Self {
+        let value =
+            Value::from_str(&partial.into()).expect("cannot convert JSON string to serde value");
+        update_cell(&self.expectations, |e| {
+            merge_json_fields(e.json_body_strict.get_or_insert(Value::Null), &value);
+            e.json_body_includes
+                .get_or_insert_with(Vec::new)
+                .push(value);
+        });
+        self
+    }
+
+    /// Requires the request body to be a JSON document that is valid according to a
+    /// [JSON Schema](https://json-schema.org/), so that existing API schemas can be used as
+    /// matchers. Every schema violation is reported when the mock is verified.
+    ///
+    /// Schemas are validated with the [jsonschema](https://docs.rs/jsonschema) crate according
+    /// to the draft that they declare with `$schema`, or draft-07 if they do not declare one.
+    /// Formats (e.g. `date-time` or `email`) are validated as well. References (`$ref`) must
+    /// point into the schema itself, since other documents are not loaded.
+    ///
+    /// * `schema` - The JSON schema. Panics if it is invalid or references another document.
+    ///
+    /// This method requires the `json-schema` Cargo feature.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use serde_json::json;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/users").json_body_schema(json!({
+    ///         "type": "object",
+    ///         "required": ["name"],
+    ///         "properties": {
+    ///             "name": { "type": "string", "minLength": 1 },
+    ///             "age": { "type": "integer", "minimum": 0 }
+    ///         }
+    ///     }));
+    ///     then.status(201);
+    /// });
+    ///
+    /// let valid = isahc::post(server.url("/users"), r#"{"name": "Fred", "age": 42}"#).unwrap();
+    /// let invalid = isahc::post(server.url("/users"), r#"{"age": -1}"#).unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(valid.status(), 201);
+    /// assert_eq!(invalid.status(), 404);
+    /// ```
+    #[cfg(feature = "json-schema")]
+    pub fn json_body_schema<V: Into<Value>>(self, schema: V) -> Self {
+        let schema = schema.into();
+        if let Err(e) = json_schema::check_schema(&schema) {
+            panic!("{}", e);
+        }
+        update_cell(&self.expectations, |e| {
+            e.json_body_schema = Some(schema);
+        });
+        self
+    }
+
+    /// Requires a JSON request body to contain a value at a JSONPath expression that equals the
+    /// expected value, so that a single nested field can be checked without a partial JSON
+    /// document (see [When::json_body_partial](#method.json_body_partial)). Strings are also
+    /// compared with the text of numbers and booleans, so that `"10115"` matches `10115`.
+    ///
+    /// Paths are evaluated according to [RFC 9535](https://www.rfc-editor.org/rfc/rfc9535),
+    /// including wildcards (`[*]`), slices (`[1:3]`), descendants (`$..zip`) and filters
+    /// (`$.users[?@.id == 1]`). A path that selects several values matches if any of them equals
+    /// the expected value.
+    ///
+    /// * `path` - The JSONPath expression. Panics if it is invalid.
+    /// * `value` - The expected value.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use serde_json::json;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/users")
+    ///         .json_body_path("$.user.addresses[0].zip", "10115")
+    ///         .json_body_path("$.user.tags[*]", json!("admin"));
+    ///     then.status(201);
+    /// });
+    ///
+    /// let response = isahc::post(
+    ///     server.url("/users"),
+    ///     r#"{"user": {"addresses": [{"zip": 10115}], "tags": ["dev", "admin"]}}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
+    /// ```
+    pub fn json_body_path<S: Into<String>, V: Into<Value>>(self, path: S, value: V) -> Self {
+        self.add_json_body_path(path.into(), Some(value.into()))
+    }
+
+    /// Requires a JSON request body to contain any value at a JSONPath expression (see
+    /// [When::json_body_path](#method.json_body_path) for the supported syntax).
+    ///
+    /// * `path` - The JSONPath expression. Panics if it is invalid.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/users").json_body_path_exists("$.user.email");
+    ///     then.status(201);
+    /// });
+    ///
+    /// let response = isahc::post(
+    ///     server.url("/users"),
+    ///     r#"{"user": {"email": "fred@example.com"}}"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
+    /// ```
+    pub fn json_body_path_exists<S: Into<String>>(self, path: S) -> Self {
+        self.add_json_body_path(path.into(), None)
+    }
+
+    fn add_json_body_path(self, path: String, value: Option<Value>) -> Self {
+        if let Err(e) = json_path::check_path(&path) {
+            panic!("{}", e);
+        }
+        update_cell(&self.expectations, |e| {
+            e.json_body_paths
+                .get_or_insert_with(Vec::new)
+                .push(JsonPathRequirement { path, value });
+        });
+        self
+    }
+
+    /// Requires a line of an NDJSON (newline-delimited JSON, also known as JSON Lines) request
+    /// body to be equal to a JSON value, e.g. to verify a single document of a bulk upload.
+    /// The body is split on newlines and each line is parsed as JSON. Blank lines (such as the
+    /// one after the final newline) are skipped, and bodies with a line that is not valid JSON
+    /// never match.
+    ///
+    /// * `index` - The index of the line, starting at 0.
+    /// * `value` - The JSON value that the line must be equal to.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use serde_json::json;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/_bulk")
+    ///         .ndjson_body_line(0, json!({ "index": { "_id": "1" } }))
+    ///         .ndjson_body_line(1, json!({ "name": "Fred" }));
+    ///     then.status(200);
+    /// });
+    ///
+    /// let response = isahc::post(
+    ///     server.url("/_bulk"),
+    ///     "{\"index\":{\"_id\":\"1\"}}\n{\"name\":\"Fred\"}\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(response.status(), 200);
+    /// ```
+    pub fn ndjson_body_line<V: Into<Value>>(self, index: usize, value: V) -> Self {
+        let line = NdjsonLine {
+            index,
+            value: value.into(),
+        };
+        update_cell(&self.expectations, |e| {
+            e.ndjson_lines.get_or_insert_with(Vec::new).push(line);
+        });
+        self
+    }
+
+    /// Requires an NDJSON (newline-delimited JSON) request body to have an exact number of
+    /// lines, not counting blank lines (see
+    /// [When::ndjson_body_line](#method.ndjson_body_line)).
+    ///
+    /// * `count` - The number of lines.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/events").ndjson_line_count(3);
+    ///     then.status(202);
+    /// });
+    ///
+    /// let response = isahc::post(server.url("/events"), "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n")
+    ///     .unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(response.status(), 202);
+    /// ```
+    pub fn ndjson_line_count(self, count: usize) -> Self {
+        self.set_single("NDJSON line count", count, |e| &mut e.ndjson_line_count);
+        self
+    }
+
+    /// Sets the expected HTTP header.
+    /// * `name` - The HTTP header name (header names are case-insensitive by RFC 2616).
+    /// * `value` - The header value.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.header("Authorization", "token 1234567890");
+    ///     then.status(200);
+    /// });
+    ///
+    /// Request::post(&server.url("/test"))
+    ///     .header("Authorization", "token 1234567890")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn header<SK: Into<String>, SV: Into<String>>(mut self, name: SK, value: SV) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.headers.is_none() {
+                e.headers = Some(Vec::new());
+            }
+            e.headers
+                .as_mut()
+                .unwrap()
+                .push((name.into(), value.into()));
+        });
+        self
+    }
+
+    /// Sets the expected HTTP header like [When::header](struct.When.html#method.header), but
+    /// takes the typed header names and values of the `http` crate, so that names such as
+    /// `http::header::CONTENT_TYPE` are checked at compile time. Requires the `http-interop`
+    /// feature.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use http::{header, HeaderValue};
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.typed_header(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    ///     then.status(200);
+    /// });
+    ///
+    /// Request::post(&server.url("/test"))
+    ///     .header("Content-Type", "application/json")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    /// # Panics
+    /// Panics if the header value is not valid UTF-8.
+    #[cfg(feature = "http-interop")]
+    pub fn typed_header<K: Into<http::HeaderName>, V: Into<http::HeaderValue>>(
+        self,
+        name: K,
+        value: V,
+    ) -> Self {
+        let (name, value) = header_strings(name.into(), value.into());
+        self.header(name, value)
+    }
+
+    /// Sets the requirement that the HTTP request needs to contain a specific header
+    /// (value is unchecked, refer to [Mock::expect_header](struct.Mock.html#method.expect_header)).
+    ///
+    /// * `name` - The HTTP header name (header names are case-insensitive by RFC 2616).
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.header_exists("Authorization");
+    ///     then.status(200);
+    /// });
+    ///
+    /// Request::post(&server.url("/test"))
+    ///     .header("Authorization", "token 1234567890")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn header_exists<S: Into<String>>(mut self, name: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.header_exists.is_none() {
+                e.header_exists = Some(Vec::new());
+            }
+            e.header_exists.as_mut().unwrap().push(name.into());
+        });
+        self
+    }
+
+    /// Sets a header that must not be present in an HTTP request, e.g. to verify that
+    /// credentials are removed before a request is passed on.
+    ///
+    /// * `name` - The HTTP header name (header names are case-insensitive by RFC 2616).
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/upstream").header_missing("X-Api-Key");
+    ///     then.status(200);
+    /// });
+    ///
+    /// let response = Request::get(&server.url("/upstream"))
+    ///     .header("X-Api-Key", "secret")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    /// assert_eq!(response.status(), 404);
+    ///
+    /// let response = isahc::get(&server.url("/upstream")).unwrap();
+    /// assert_eq!(response.status(), 200);
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn header_missing<S: Into<String>>(mut self, name: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.header_missing.is_none() {
+                e.header_missing = Some(Vec::new());
+            }
+            e.header_missing.as_mut().unwrap().push(name.into());
+        });
+        self
+    }
+
+    /// Sets the values that a header, which can occur several times in a request, must have.
+    /// The request must send exactly these values in the same order, e.g. the addresses of all
+    /// proxies in an `X-Forwarded-For` header. Since a list of values can be sent in one header
+    /// line or in several (see [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-5.3)),
+    /// comma-separated values are split.
+    ///
+    /// * `name` - The HTTP header name (header names are case-insensitive by RFC 2616).
+    /// * `values` - The expected header values.
+    ///
+    /// # Panics
+    /// Panics if no value is given.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/")
+    ///         .header_values("X-Forwarded-For", ["1.1.1.1", "2.2.2.2"]);
+    ///     then.status(200);
+    /// });
+    ///
+    /// Request::get(&server.url("/"))
+    ///     .header("X-Forwarded-For", "1.1.1.1")
+    ///     .header("X-Forwarded-For", "2.2.2.2")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn header_values<S: Into<String>, I: IntoIterator<Item = V>, V: AsRef<str>>(
+        mut self,
+        name: S,
+        values: I,
+    ) -> Self {
+        let values: Vec<String> = values.into_iter().map(|v| v.as_ref().to_string()).collect();
+        if values.is_empty() {
+            panic!("At least one value must be given");
+        }
+        update_cell(&self.expectations, |e| {
+            e.header_values
+                .get_or_insert_with(Vec::new)
+                .push(HeaderValues {
+                    name: name.into(),
+                    values,
+                });
+        });
+        self
+    }
+
+    /// Sets the credentials that an HTTP request must send with basic authentication (see
+    /// [RFC 7617](https://tools.ietf.org/html/rfc7617)). The `Authorization` header of the
+    /// request is decoded, so that the credentials do not need to be base64 encoded in tests.
+    ///
+    /// * `username` - The expected username.
+    /// * `password` - The expected password.
+    ///
+    /// # Panics
+    /// Panics if the username contains a colon, which basic authentication does not allow.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, auth::{Authentication, Credentials}, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/account").basic_auth("alice", "secret");
+    ///     then.status(200);
+    /// });
+    ///
+    /// Request::get(server.url("/account"))
+    ///     .authentication(Authentication::basic())
+    ///     .credentials(Credentials::new("alice", "secret"))
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn basic_auth<U: Into<String>, P: Into<String>>(
+        mut self,
+        username: U,
+        password: P,
+    ) -> Self {
+        let username = username.into();
+        assert!(
+            !username.contains(':'),
+            "The username '{}' of basic auth credentials must not contain a colon",
+            username
+        );
+        let credentials = format!("{}:{}", username, password.into());
+        self.set_single("basic auth credentials", credentials, |e| &mut e.basic_auth);
+        self
+    }
+
+    /// Sets the media type that the `Content-Type` header of an HTTP request must have. Unlike
+    /// [When::header](struct.When.html#method.header), the media type is compared
+    /// case-insensitively and parameters such as `charset` are ignored, unless they are part of
+    /// the expected value. Parameter names and `charset` values are case-insensitive.
+    ///
+    /// * `media_type` - The expected media type, e.g. `application/json` or
+    ///   `text/plain; charset=utf-8`.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/users").content_type("application/json");
+    ///     then.status(201);
+    /// });
+    ///
+    /// Request::post(server.url("/users"))
+    ///     .header("Content-Type", "Application/JSON; charset=UTF-8")
+    ///     .body("{}")
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn content_type<S: Into<String>>(mut self, media_type: S) -> Self {
+        let media_type = media_type.into();
+        assert!(
+            media_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .contains('/'),
+            "'{}' is not a valid media type",
+            media_type
+        );
+        self.set_single("content type", media_type, |e| &mut e.content_type);
+        self
+    }
+
+    /// Requires the `Accept` header of an HTTP request to accept the given media type. The
+    /// header is evaluated like in content negotiation: the most specific media range that
+    /// matches the media type (e.g. `application/json` before `application/*` before `*/*`)
+    /// must have a quality value (`q`) greater than zero. A request without an `Accept` header
+    /// accepts any media type. If called multiple times, all media types must be accepted.
+    ///
+    /// * `media_type` - The media type that must be accepted, e.g. `application/json`.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/users").accepts("application/json");
+    ///     then.status(200).json_body(serde_json::json!([]));
+    /// });
+    ///
+    /// Request::get(server.url("/users"))
+    ///     .header("Accept", "text/html, application/*;q=0.8, */*;q=0.1")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn accepts<S: Into<String>>(self, media_type: S) -> Self {
+        let media_type = media_type.into();
+        assert!(
+            media_type
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .contains('/'),
+            "'{}' is not a valid media type",
+            media_type
+        );
+        update_cell(&self.expectations, |e| {
+            e.accepts.get_or_insert_with(Vec::new).push(media_type);
+        });
+        self
+    }
+
+    /// Sets the token that an HTTP request must send with bearer authentication (i.e. in an
+    /// `Authorization: Bearer <token>` header).
+    ///
+    /// * `token` - The expected token.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/account").bearer_token("abc123");
+    ///     then.status(200);
+    /// });
+    ///
+    /// Request::get(server.url("/account"))
+    ///     .header("Authorization", "Bearer abc123")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn bearer_token<S: Into<String>>(mut self, token: S) -> Self {
+        self.set_single("bearer token", token.into(), |e| &mut e.bearer_token);
+        self
+    }
+
+    /// Sets a claim that the JWT bearer token of an HTTP request must contain, e.g. to verify
+    /// that a client sends the correct identity. The token is decoded without verifying its
+    /// signature. Claims that hold an array (such as `aud`) match if they contain the value.
+    ///
+    /// * `name` - The name of the claim (e.g. `sub`).
+    /// * `value` - The expected value of the claim.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.path("/account")
+    ///         .jwt_claim("sub", "alice")
+    ///         .jwt_claim("aud", "api")
+    ///         .jwt_claim("admin", true);
+    ///     then.status(200);
+    /// });
+    ///
+    /// // The claims of this token are {"sub":"alice","aud":["api","web"],"admin":true}
+    /// let token = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9.\
+    ///     eyJzdWIiOiJhbGljZSIsImF1ZCI6WyJhcGkiLCJ3ZWIiXSwiYWRtaW4iOnRydWV9.c2lnbmF0dXJl";
+    ///
+    /// Request::get(server.url("/account"))
+    ///     .header("Authorization", format!("Bearer {}", token))
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    pub fn jwt_claim<S: Into<String>, V: Into<Value>>(mut self, name: S, value: V) -> Self {
+        let claim = JwtClaim {
+            name: name.into(),
+            value: value.into(),
+        };
+        update_cell(&self.expectations, |e| {
+            e.jwt_claims.get_or_insert_with(Vec::new).push(claim);
+        });
+        self
+    }
+
+    /// Sets a header whose value needs to match a regular expression.
+    ///
+    /// * `name` - The HTTP header name (header names are case-insensitive by RFC 2616).
+    /// * `regex` - The regular expression that the header value needs to match.
+    ///
+    /// This method requires the `regex` Cargo feature, which is enabled by default.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.header_matches("Authorization", Regex::new("^Bearer .+$").unwrap());
+    ///     then.status(200);
+    /// });
+    ///
+    /// Request::post(&server.url("/test"))
+    ///     .header("Authorization", "Bearer 1234567890")
+    ///     .body(())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// mock.assert();
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn header_matches<S: Into<String>, R: Into<Regex>>(mut self, name: S, regex: R) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.header_matches.is_none() {
+                e.header_matches = Some(Vec::new());
+            }
+            e.header_matches
+                .as_mut()
+                .unwrap()
+                .push((name.into(), Pattern::from_regex(regex.into())));
+        });
+        self
+    }
+
+    /// Sets the cookie that needs to exist in the HTTP request.
+    /// Cookie parsing follows [RFC-6265](https://tools.ietf.org/html/rfc6265.html).
+    /// **Attention**: Cookie names are **case-sensitive**.
     ///
-    /// let m = server.mock(|when, then|{
-    ///     when.path("/books")
-    ///         .body_contains("Ring");
-    ///     then.status(201);
+    /// * `name` - The cookie name.
+    /// * `value` - The expected cookie value.
+    ///
+    /// > Note: This function is only available when the `cookies` feature is enabled.
+    /// > It is enabled by default.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then|{
+    ///     when.cookie("SESSIONID", "1234567890");
+    ///     then.status(200);
     /// });
     ///
-    /// // Act: Send the request
-    /// let response = Request::post(server.url("/books"))
-    ///     .body("The Fellowship of the Ring")
+    /// Request::post(&server.url("/test"))
+    ///     .header("Cookie", "TRACK=12345; SESSIONID=1234567890; CONSENT=1")
+    ///     .body(())
     ///     .unwrap()
     ///     .send()
     ///     .unwrap();
     ///
-    /// // Assert
-    /// m.assert();
-    /// assert_eq!(response.status(), 201);
+    /// mock.assert();
     /// ```
-    pub fn body_contains<S: Into<String>>(mut self, substring: S) -> Self {
+    pub fn cookie<SK: Into<String>, SV: Into<String>>(mut self, name: SK, value: SV) -> Self {
         update_cell(&self.expectations, |e| {
-            if e.body_contains.is_none() {
-                e.body_contains = Some(Vec::new());
+            if e.cookies.is_none() {
+                e.cookies = Some(Vec::new());
             }
-            e.body_contains.as_mut().unwrap().push(substring.into());
+            e.cookies
+                .as_mut()
+                .unwrap()
+                .push((name.into(), value.into()));
         });
         self
     }
 
-    /// Sets the expected JSON body. This method expects a [serde_json::Value](../serde_json/enum.Value.html)
-    /// that will be serialized/deserialized to/from a JSON string.
+    /// Sets the cookie that needs to exist in the HTTP request.
+    /// Cookie parsing follows [RFC-6265](https://tools.ietf.org/html/rfc6265.html).
+    /// **Attention**: Cookie names are **case-sensitive**.
     ///
-    /// Note that this method does not set the `content-type` header automatically, so you
-    /// need to provide one yourself!
+    /// * `name` - The cookie name
     ///
-    /// * `body` - The HTTP body object that will be serialized to JSON using serde.
+    /// > Note: This function is only available when the `cookies` feature is enabled.
+    /// > It is enabled by default.
     ///
+    /// # Example
     /// ```
     /// use httpmock::prelude::*;
-    /// use serde_json::json;
     /// use isahc::{prelude::*, Request};
     ///
-    /// // Arrange
-    /// let _ = env_logger::try_init();
     /// let server = MockServer::start();
     ///
-    /// let m = server.mock(|when, then|{
-    ///     when.path("/user")
-    ///         .header("content-type", "application/json")
-    ///         .json_body(json!({ "name": "Hans" }));
-    ///     then.status(201);
+    /// let mock = server.mock(|when, then|{
+    ///     when.cookie_exists("SESSIONID");
+    ///     then.status(200);
     /// });
     ///
-    /// // Act: Send the request and deserialize the response to JSON
-    /// let mut response = Request::post(&format!("http://{}/user", server.address()))
-    ///     .header("content-type", "application/json")
-    ///     .body(json!({ "name": "Hans" }).to_string())
+    /// Request::post(&server.url("/test"))
+    ///     .header("Cookie", "TRACK=12345; SESSIONID=1234567890; CONSENT=1")
+    ///     .body(())
     ///     .unwrap()
     ///     .send()
     ///     .unwrap();
     ///
+    /// mock.assert();
+    /// ```
+    pub fn cookie_exists<S: Into<String>>(mut self, name: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            if e.cookie_exists.is_none() {
+                e.cookie_exists = Some(Vec::new());
+            }
+            e.cookie_exists.as_mut().unwrap().push(name.into());
+        });
+        self
+    }
+
+    /// Sets the required common name (CN) of the certificate that the client presented during
+    /// the TLS handshake. Requests without a verified client certificate never match. Client
+    /// certificates are only verified by servers that have client authentication enabled (see
+    /// [MockServerBuilder::client_auth_required](struct.MockServerBuilder.html#method.client_auth_required)).
+    ///
+    /// * `common_name` - The required common name.
+    ///
+    /// > Note: This function is only available when the `https` feature is enabled.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::builder()
+    ///     .tls_cert_pem_file("tests/resources/certificates/server_chain.pem")
+    ///     .tls_key_pem_file("tests/resources/certificates/server_key.pem")
+    ///     .client_auth_required(std::fs::read("tests/resources/certificates/ca.pem").unwrap())
+    ///     .start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/test").client_cert_cn("my-service");
+    ///     then.status(200);
+    /// });
+    /// ```
+    #[cfg(feature = "https")]
+    pub fn client_cert_cn<S: Into<String>>(mut self, common_name: S) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.client_cert_cn = Some(common_name.into());
+        });
+        self
+    }
+    /// Sets a custom matcher for expected HTTP request. If this function returns true, the request
+    /// is considered a match and the mock server will respond to the request
+    /// (given all other criteria are also met). The function receives the full request, including
+    /// its method, path, query parameters, headers and body. It may capture variables of the
+    /// test, as long as they can be shared between threads. A function that panics does not
+    /// match the request. This method can be called multiple times, in which case all functions
+    /// must match.
+    ///
+    /// Custom matchers cannot be sent to a remote mock server.
+    /// * `matcher` - The matcher function.
+    ///
+    /// ## Example:
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// // Arrange
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///    when.matches(|req: &HttpMockRequest| {
+    ///         req.path.contains("es")
+    ///    });
+    ///    then.status(200);
+    /// });
+    ///
+    /// // Act: Send the HTTP request
+    /// let response = isahc::get(server.url("/test")).unwrap();
+    ///
     /// // Assert
     /// m.assert();
-    /// assert_eq!(response.status(), 201);
+    /// assert_eq!(response.status(), 200);
     /// ```
-    pub fn json_body<V: Into<serde_json::Value>>(mut self, value: V) -> Self {
+    pub fn matches<F>(mut self, matcher: F) -> Self
+    where
+        F: Fn(&HttpMockRequest) -> bool + Send + Sync + 'static,
+    {
         update_cell(&self.expectations, |e| {
-            e.json_body = Some(value.into());
+            if e.matchers.is_none() {
+                e.matchers = Some(Vec::new());
+            }
+            e.matchers.as_mut().unwrap().push(Arc::new(matcher));
         });
         self
     }
 
-    /// Sets the expected JSON body. This method expects a serializable serde object
-    /// that will be serialized/deserialized to/from a JSON string.
+    /// Adds a reusable [Matcher](trait.Matcher.html) that the request must
+    /// satisfy, e.g. one that a library provides to verify request signatures. The description
+    /// of the matcher is shown when a request does not match. This method can be called
+    /// multiple times, in which case all matchers must match.
     ///
-    /// Note that this method does not set the "content-type" header automatically, so you
-    /// need to provide one yourself!
+    /// Like functions passed to [When::matches](#method.matches), matchers cannot be sent to a
+    /// remote mock server.
     ///
-    /// * `body` - The HTTP body object that will be serialized to JSON using serde.
+    /// * `matcher` - The matcher.
     ///
+    /// ## Example:
     /// ```
     /// use httpmock::prelude::*;
-    /// use serde_json::json;
+    /// use httpmock::Matcher;
     /// use isahc::{prelude::*, Request};
     ///
-    /// // This is a temporary type that we will use for this test
-    /// #[derive(serde::Serialize, serde::Deserialize)]
-    /// struct TestUser {
-    ///     name: String,
+    /// struct TenantHeader(&'static str);
+    ///
+    /// impl Matcher for TenantHeader {
+    ///     fn matches(&self, req: &HttpMockRequest) -> bool {
+    ///         req.headers
+    ///             .iter()
+    ///             .flatten()
+    ///             .any(|(name, value)| name.eq_ignore_ascii_case("x-tenant") && value == self.0)
+    ///     }
+    ///
+    ///     fn describe(&self) -> String {
+    ///         format!("the tenant '{}'", self.0)
+    ///     }
     /// }
     ///
-    /// // Arrange
-    /// let _ = env_logger::try_init();
     /// let server = MockServer::start();
     ///
-    /// let m = server.mock(|when, then|{
-    ///     when.path("/user")
-    ///         .header("content-type", "application/json")
-    ///         .json_body_obj(&TestUser {
-    ///             name: String::from("Fred"),
-    ///         });
+    /// let m = server.mock(|when, then| {
+    ///     when.custom_matcher(Box::new(TenantHeader("acme")));
     ///     then.status(200);
     /// });
     ///
-    /// // Act: Send the request and deserialize the response to JSON
-    /// let mut response = Request::post(&format!("http://{}/user", server.address()))
-    ///     .header("content-type", "application/json")
-    ///     .body(json!(&TestUser {
-    ///         name: "Fred".to_string()
-    ///     }).to_string())
+    /// let response = Request::get(server.url("/orders"))
+    ///     .header("X-Tenant", "acme")
+    ///     .body(())
     ///     .unwrap()
     ///     .send()
     ///     .unwrap();
     ///
-    /// // Assert
     /// m.assert();
     /// assert_eq!(response.status(), 200);
     /// ```
-    pub fn json_body_obj<'a, T>(self, body: &T) -> Self
-    where
-        T: Serialize + Deserialize<'a>,
-    {
-        let json_value = serde_json::to_value(body).expect("Cannot serialize json body to JSON");
-        self.json_body(json_value)
+    pub fn custom_matcher(self, matcher: Box<dyn Matcher>) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.custom_matchers
+                .get_or_insert_with(Vec::new)
+                .push(CustomMatcher(Arc::from(matcher)));
+        });
+        self
     }
 
-    /// Sets the expected partial JSON body.
+    /// Sets the GraphQL document that is expected in the `query` field of the JSON body of a
+    /// `POST` request, as sent by GraphQL clients. Differences in whitespace, commas and comments
+    /// between the tokens of the document are ignored.
     ///
-    /// **Attention: The partial string needs to be a valid JSON string. It must contain
-    /// the full object hierarchy from the original JSON object but can leave out irrelevant
-    /// attributes (see example).**
+    /// The type of the operation in the document (query, mutation or subscription) determines
+    /// which root type of the schema the data of
+    /// [Then::graphql_data](struct.Then.html#method.graphql_data) is validated
+    /// against (see [MockServer::load_graphql_schema](struct.MockServer.html#method.load_graphql_schema)).
     ///
-    /// Note that this method does not set the `content-type` header automatically, so you
-    /// need to provide one yourself!
+    /// This method requires the `graphql` Cargo feature.
     ///
-    /// String format and attribute order are irrelevant.
+    /// # Panics
+    /// Panics if the document is not valid GraphQL.
     ///
-    /// * `partial_body` - The HTTP body object that will be serialized to JSON using serde.
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    /// use serde_json::json;
     ///
-    /// ## Example
-    /// Suppose your application sends the following JSON request body:
-    /// ```json
-    /// {
-    ///     "parent_attribute" : "Some parent data goes here",
-    ///     "child" : {
-    ///         "target_attribute" : "Example",
-    ///         "other_attribute" : "Another value"
-    ///     }
-    /// }
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/graphql")
+    ///         .graphql_query("query { user(id: 1) { name } }");
+    ///     then.graphql_data(json!({ "user": { "name": "Fred" } }));
+    /// });
+    ///
+    /// let mut response = Request::post(server.url("/graphql"))
+    ///     .body(json!({ "query": "query {\n  user(id: 1) {\n    name\n  }\n}" }).to_string())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(response.text().unwrap(), r#"{"data":{"user":{"name":"Fred"}}}"#);
     /// ```
-    /// If we only want to verify that `target_attribute` has value `Example` without the need
-    /// to provide a full JSON object, we can use this method as follows:
+    #[cfg(feature = "graphql")]
+    pub fn graphql_query<S: Into<String>>(mut self, query: S) -> Self {
+        let query = query.into();
+        let operation_type = graphql::operation_type(&query)
+            .unwrap_or_else(|e| panic!("Invalid GraphQL query: {}", e));
+        let regex = Regex::new(&graphql::query_body_regex(&query))
+            .expect("Cannot create regex for GraphQL query");
+
+        update_cell(&self.graphql, |g| {
+            g.operation_type = Some(operation_type);
+        });
+        self.method(Method::POST).body_matches(regex)
+    }
+
+    /// Sets the name of the GraphQL operation that a `POST` request must execute. The name is
+    /// taken from the `operationName` field of the JSON body or, if the field is not set, from
+    /// the only operation of the GraphQL document in the `query` field. Unlike
+    /// [When::graphql_query](#method.graphql_query), this does not depend on how the client
+    /// formats the document.
+    ///
+    /// This method requires the `graphql` Cargo feature.
+    ///
+    /// # Example
     /// ```
     /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    /// use serde_json::json;
     ///
     /// let server = MockServer::start();
     ///
-    /// let mut mock = server.mock(|when, then|{
-    ///     when.json_body_partial(r#"
-    ///         {
-    ///             "child" : {
-    ///                 "target_attribute" : "Example"
-    ///             }
-    ///          }
-    ///     "#);
-    ///     then.status(200);
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/graphql")
+    ///         .graphql_operation("GetUser")
+    ///         .graphql_variables(json!({ "id": "1" }));
+    ///     then.graphql_data(json!({ "user": { "name": "Fred" } }));
     /// });
+    ///
+    /// let response = Request::post(server.url("/graphql"))
+    ///     .body(json!({
+    ///         "query": "query GetUser($id: ID!) { user(id: $id) { name } }",
+    ///         "variables": { "id": "1" }
+    ///     }).to_string())
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(response.status(), 200);
     /// ```
-    /// Please note that the JSON partial contains the full object hierarchy, i.e. it needs to start
-    /// from the root! It leaves out irrelevant attributes, however (`parent_attribute`
-    /// and `child.other_attribute`).
-    pub fn json_body_partial<S: Into<String>>(mut self, partial: S) -> Self {
+    #[cfg(feature = "graphql")]
+    pub fn graphql_operation<S: Into<String>>(mut self, name: S) -> Self {
         update_cell(&self.expectations, |e| {
-            if e.json_body_includes.is_none() {
-                e.json_body_includes = Some(Vec::new());
-            }
-            let value = Value::from_str(&partial.into())
-                .expect("cannot convert JSON string to serde value");
-            e.json_body_includes.as_mut().unwrap().push(value);
+            e.graphql_operation = Some(name.into());
+        });
+        self.method(Method::POST)
+    }
+
+    /// Sets the value that the `variables` field of the JSON body of a GraphQL `POST` request
+    /// must be equal to. Requests without a `variables` field do not match.
+    ///
+    /// Please refer to [When::graphql_operation](#method.graphql_operation) for an example.
+    /// This method requires the `graphql` Cargo feature.
+    #[cfg(feature = "graphql")]
+    pub fn graphql_variables<V: Into<Value>>(mut self, variables: V) -> Self {
+        let variables = variables.into();
+        update_cell(&self.expectations, |e| {
+            e.graphql_variables = Some(variables);
+        });
+        self.method(Method::POST)
+    }
+
+    /// Sets the protobuf message that is expected in the gRPC frame of the request body. The
+    /// message matches if its protobuf encoding equals the encoding of the expected message.
+    /// Compressed messages never match.
+    ///
+    /// Please refer to [MockServer::mock_grpc](struct.MockServer.html#method.mock_grpc) for an
+    /// example. This method requires the `grpc` Cargo feature.
+    #[cfg(feature = "grpc")]
+    pub fn grpc_request<T: prost::Message>(mut self, expected: &T) -> Self {
+        update_cell(&self.expectations, |e| {
+            e.grpc_message = Some(base64::encode(expected.encode_to_vec()));
         });
         self
     }
 
-    /// Sets the expected HTTP header.
-    /// * `name` - The HTTP header name (header names are case-insensitive by RFC 2616).
-    /// * `value` - The header value.
+    /// Sets the protobuf message that the request body must be equal to, e.g. for REST APIs
+    /// that exchange protobuf messages instead of JSON. The messages are compared field by
+    /// field, so the body matches regardless of the order in which the client encoded the
+    /// fields. Unlike [When::grpc_request](#method.grpc_request), the body is
+    /// expected to be the plain message without a gRPC frame.
+    ///
+    /// This method requires the `proto` Cargo feature.
     ///
     /// # Example
     /// ```
     /// use httpmock::prelude::*;
     /// use isahc::{prelude::*, Request};
+    /// use prost::Message;
+    ///
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// struct CreateUser {
+    ///     #[prost(string, tag = "1")]
+    ///     name: String,
+    ///     #[prost(uint32, tag = "2")]
+    ///     age: u32,
+    /// }
     ///
     /// let server = MockServer::start();
+    /// let user = CreateUser { name: "Fred".into(), age: 30 };
     ///
-    /// let mock = server.mock(|when, then|{
-    ///     when.header("Authorization", "token 1234567890");
-    ///     then.status(200);
+    /// let m = server.mock(|when, then| {
+    ///     when.method(POST).path("/users").protobuf_body(&user);
+    ///     then.status(201);
     /// });
     ///
-    /// Request::post(&format!("http://{}/test", server.address()))
-    ///     .header("Authorization", "token 1234567890")
-    ///     .body(())
+    /// let response = Request::post(server.url("/users"))
+    ///     .header("content-type", "application/x-protobuf")
+    ///     .body(user.encode_to_vec())
     ///     .unwrap()
     ///     .send()
     ///     .unwrap();
     ///
-    /// mock.assert();
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
     /// ```
-    pub fn header<SK: Into<String>, SV: Into<String>>(mut self, name: SK, value: SV) -> Self {
+    #[cfg(feature = "proto")]
+    pub fn protobuf_body<T: prost::Message>(mut self, expected: &T) -> Self {
         update_cell(&self.expectations, |e| {
-            if e.headers.is_none() {
-                e.headers = Some(Vec::new());
-            }
-            e.headers
-                .as_mut()
-                .unwrap()
-                .push((name.into(), value.into()));
+            e.protobuf_body = Some(base64::encode(expected.encode_to_vec()));
         });
         self
     }
 
-    /// Sets the requirement that the HTTP request needs to contain a specific header
-    /// (value is unchecked, refer to [Mock::expect_header](struct.Mock.html#method.expect_header)).
+    /// Sets the XML document that the request body must be equal to. The documents are
+    /// compared semantically: elements and attributes must have the same names and namespaces,
+    /// but namespace prefixes, the order of attributes, whitespace around text as well as
+    /// comments do not matter.
     ///
-    /// * `name` - The HTTP header name (header names are case-insensitive by RFC 2616).
+    /// * `xml` - The expected XML document. Panics if it is not well-formed.
+    ///
+    /// This method requires the `xml` Cargo feature.
     ///
     /// # Example
     /// ```
@@ -636,159 +3071,293 @@ impl When {
     ///
     /// let server = MockServer::start();
     ///
-    /// let mock = server.mock(|when, then|{
-    ///     when.header_exists("Authorization");
-    ///     then.status(200);
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/orders")
+    ///         .xml_body(r#"<order id="42" status="open"><item>Pen</item></order>"#);
+    ///     then.status(201);
     /// });
     ///
-    /// Request::post(&format!("http://{}/test", server.address()))
-    ///     .header("Authorization", "token 1234567890")
-    ///     .body(())
+    /// let response = Request::post(server.url("/orders"))
+    ///     .header("Content-Type", "application/xml")
+    ///     .body(r#"<?xml version="1.0"?>
+    ///              <order status="open" id="42">
+    ///                <item> Pen </item>
+    ///              </order>"#)
     ///     .unwrap()
     ///     .send()
     ///     .unwrap();
     ///
-    /// mock.assert();
+    /// m.assert();
+    /// assert_eq!(response.status(), 201);
     /// ```
-    pub fn header_exists<S: Into<String>>(mut self, name: S) -> Self {
+    #[cfg(feature = "xml")]
+    pub fn xml_body<S: Into<String>>(self, xml: S) -> Self {
+        let xml = xml.into();
+        if let Err(e) = roxmltree::Document::parse(&xml) {
+            panic!("Invalid XML body: {}", e);
+        }
         update_cell(&self.expectations, |e| {
-            if e.header_exists.is_none() {
-                e.header_exists = Some(Vec::new());
-            }
-            e.header_exists.as_mut().unwrap().push(name.into());
+            e.xml_body = Some(xml);
         });
         self
     }
 
-    /// Sets the cookie that needs to exist in the HTTP request.
-    /// Cookie parsing follows [RFC-6265](https://tools.ietf.org/html/rfc6265.html).
-    /// **Attention**: Cookie names are **case-sensitive**.
+    /// Requires an XML request body to contain a node at an XPath expression, whose text
+    /// equals the expected value. Whitespace around the text is ignored.
     ///
-    /// * `name` - The cookie name.
-    /// * `value` - The expected cookie value.
+    /// Supported are absolute paths with child (`/`) and descendant (`//`) steps, `*`,
+    /// `text()`, attributes (`@name`) as the last step and the predicates `[n]`,
+    /// `[@name='value']`, `[name='value']` and `[text()='value']`. Namespace prefixes are
+    /// ignored, elements are compared by their local name, so that
+    /// `/soap:Envelope/soap:Body` and `/Envelope/Body` select the same element.
     ///
-    /// > Note: This function is only available when the `cookies` feature is enabled.
-    /// > It is enabled by default.
+    /// * `path` - The XPath expression. Panics if it is invalid.
+    /// * `value` - The expected text of the node.
+    ///
+    /// This method requires the `xml` Cargo feature.
     ///
     /// # Example
     /// ```
     /// use httpmock::prelude::*;
-    /// use isahc::{prelude::*, Request};
     ///
     /// let server = MockServer::start();
     ///
-    /// let mock = server.mock(|when, then|{
-    ///     when.cookie("SESSIONID", "1234567890");
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/orders")
+    ///         .xpath("/soap:Envelope/Body/Order/Id", "42")
+    ///         .xpath("//Order/@status", "open");
     ///     then.status(200);
     /// });
     ///
-    /// Request::post(&format!("http://{}/test", server.address()))
-    ///     .header("Cookie", "TRACK=12345; SESSIONID=1234567890; CONSENT=1")
-    ///     .body(())
-    ///     .unwrap()
-    ///     .send()
-    ///     .unwrap();
+    /// let response = isahc::post(
+    ///     server.url("/orders"),
+    ///     r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+    ///          <soap:Body><Order status="open"><Id>42</Id></Order></soap:Body>
+    ///        </soap:Envelope>"#,
+    /// )
+    /// .unwrap();
     ///
-    /// mock.assert();
+    /// m.assert();
+    /// assert_eq!(response.status(), 200);
     /// ```
-    pub fn cookie<SK: Into<String>, SV: Into<String>>(mut self, name: SK, value: SV) -> Self {
+    #[cfg(feature = "xml")]
+    pub fn xpath<SP: Into<String>, SV: Into<String>>(self, path: SP, value: SV) -> Self {
+        let path = path.into();
+        if let Err(e) = xml::check_path(&path) {
+            panic!("{}", e);
+        }
         update_cell(&self.expectations, |e| {
-            if e.cookies.is_none() {
-                e.cookies = Some(Vec::new());
-            }
-            e.cookies
-                .as_mut()
-                .unwrap()
-                .push((name.into(), value.into()));
+            e.body_xpaths
+                .get_or_insert_with(Vec::new)
+                .push(XPathRequirement {
+                    path,
+                    value: Some(value.into()),
+                });
         });
         self
     }
 
-    /// Sets the cookie that needs to exist in the HTTP request.
-    /// Cookie parsing follows [RFC-6265](https://tools.ietf.org/html/rfc6265.html).
-    /// **Attention**: Cookie names are **case-sensitive**.
+    /// Sets the action of a SOAP request. Requests must be sent with the `POST` method and
+    /// with the content type of the SOAP version. SOAP 1.1 requests need to send the action in
+    /// the `SOAPAction` header, SOAP 1.2 requests in the `action` parameter of the content type.
     ///
-    /// * `name` - The cookie name
+    /// * `version` - The SOAP version of the request.
+    /// * `action` - The expected action (e.g. `urn:GetUser`).
     ///
-    /// > Note: This function is only available when the `cookies` feature is enabled.
-    /// > It is enabled by default.
+    /// This method requires the `xml` Cargo feature.
     ///
     /// # Example
     /// ```
     /// use httpmock::prelude::*;
+    /// use httpmock::{SoapFaultCode, SoapVersion};
     /// use isahc::{prelude::*, Request};
     ///
     /// let server = MockServer::start();
     ///
-    /// let mock = server.mock(|when, then|{
-    ///     when.cookie_exists("SESSIONID");
-    ///     then.status(200);
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/users")
+    ///         .soap_action(SoapVersion::V1_1, "urn:GetUser")
+    ///         .soap_body_xpath("GetUser/Id", "42");
+    ///     then.soap_body(SoapVersion::V1_1, "<GetUserResponse><Name>Fred</Name></GetUserResponse>");
     /// });
     ///
-    /// Request::post(&format!("http://{}/test", server.address()))
-    ///     .header("Cookie", "TRACK=12345; SESSIONID=1234567890; CONSENT=1")
-    ///     .body(())
+    /// let mut response = Request::post(server.url("/users"))
+    ///     .header("Content-Type", "text/xml; charset=utf-8")
+    ///     .header("SOAPAction", "\"urn:GetUser\"")
+    ///     .body(r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+    ///                <soap:Body><GetUser><Id>42</Id></GetUser></soap:Body>
+    ///              </soap:Envelope>"#)
     ///     .unwrap()
     ///     .send()
     ///     .unwrap();
     ///
-    /// mock.assert();
+    /// m.assert();
+    /// assert_eq!(response.headers()["content-type"], "text/xml; charset=utf-8");
+    /// assert!(response.text().unwrap().contains("<Name>Fred</Name>"));
     /// ```
-    pub fn cookie_exists<S: Into<String>>(mut self, name: S) -> Self {
+    #[cfg(feature = "xml")]
+    pub fn soap_action<S: Into<String>>(self, version: SoapVersion, action: S) -> Self {
+        let action = regex::escape(&action.into());
+        let this = self.method(Method::POST);
+        match version {
+            SoapVersion::V1_1 => this
+                .header_matches("content-type", Regex::new(r"^(?i:text/xml)\b").unwrap())
+                .header_matches(
+                    "soapaction",
+                    Regex::new(&format!(r#"^"?{}"?$"#, action)).unwrap(),
+                ),
+            SoapVersion::V1_2 => this.header_matches(
+                "content-type",
+                Regex::new(&format!(
+                    r#"^(?i:application/soap\+xml)\b.*;\s*(?i:action)="?{}"?\s*(;|$)"#,
+                    action
+                ))
+                .unwrap(),
+            ),
+        }
+    }
+
+    /// Requires the body of a SOAP request to contain a node at an XPath expression, whose text
+    /// equals the expected value. The path is relative to the SOAP body (e.g. `GetUser/Id`).
+    /// Namespace prefixes are ignored, elements are compared by their local name.
+    ///
+    /// Supported are child (`/`) and descendant (`//`) steps, `*`, `text()`, attributes
+    /// (`@name`) as the last step and the predicates `[n]`, `[@name='value']`,
+    /// `[name='value']` and `[text()='value']`. Whitespace around the text is ignored.
+    ///
+    /// Please refer to [When::soap_action](#method.soap_action) for an
+    /// example. This method requires the `xml` Cargo feature.
+    #[cfg(feature = "xml")]
+    pub fn soap_body_xpath<SP: Into<String>, SV: Into<String>>(
+        mut self,
+        path: SP,
+        value: SV,
+    ) -> Self {
+        let path = path.into();
+        let path = match path.starts_with('/') {
+            true => format!("/Envelope/Body{}", path),
+            false => format!("/Envelope/Body/{}", path),
+        };
         update_cell(&self.expectations, |e| {
-            if e.cookie_exists.is_none() {
-                e.cookie_exists = Some(Vec::new());
-            }
-            e.cookie_exists.as_mut().unwrap().push(name.into());
+            e.body_xpaths
+                .get_or_insert_with(Vec::new)
+                .push(XPathRequirement {
+                    path: path.clone(),
+                    value: Some(value.into()),
+                });
         });
         self
     }
-    /// Sets a custom matcher for expected HTTP request. If this function returns true, the request
-    /// is considered a match and the mock server will respond to the request
-    /// (given all other criteria are also met).
-    /// * `matcher` - The matcher function.
+}
+
+/// Merges the fields of a JSON partial into the target, so that the target contains all fields
+/// of both. Arrays are merged element by element.
+fn merge_json_fields(target: &mut Value, partial: &Value) {
+    match (target, partial) {
+        (Value::Object(target), Value::Object(partial)) => {
+            for (key, value) in partial {
+                merge_json_fields(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (Value::Array(target), Value::Array(partial)) => {
+            for (idx, value) in partial.iter().enumerate() {
+                match target.get_mut(idx) {
+                    Some(target) => merge_json_fields(target, value),
+                    None => target.push(value.clone()),
+                }
+            }
+        }
+        (target, partial) => *target = partial.clone(),
+    }
+}
+
+/// Converts all numbers and booleans in a JSON value to strings, so that it can be compared with
+/// values parsed from a query string.
+fn stringify_json_values(value: Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, stringify_json_values(value)))
+                .collect(),
+        ),
+        Value::Array(values) => {
+            Value::Array(values.into_iter().map(stringify_json_values).collect())
+        }
+        Value::Number(number) => Value::String(number.to_string()),
+        Value::Bool(flag) => Value::String(flag.to_string()),
+        value => value,
+    }
+}
+
+#[cfg(feature = "http-interop")]
+fn header_strings(name: http::HeaderName, value: http::HeaderValue) -> (String, String) {
+    let value = value
+        .to_str()
+        .unwrap_or_else(|_| panic!("The value of header '{}' is not valid UTF-8", name));
+    (name.as_str().to_string(), value.to_string())
+}
+
+/// A value that can be used as the status code of a response (see
+/// [Then::status](struct.Then.html#method.status)). It is implemented for `u16` and, with the
+/// `http-interop` feature, for `http::StatusCode`.
+pub trait IntoStatusCode {
+    fn into_status_code(self) -> u16;
+}
+
+impl IntoStatusCode for u16 {
+    fn into_status_code(self) -> u16 {
+        self
+    }
+}
+
+#[cfg(feature = "http-interop")]
+impl IntoStatusCode for http::StatusCode {
+    fn into_status_code(self) -> u16 {
+        self.as_u16()
+    }
+}
+
+/// A type that allows the specification of HTTP response values.
+pub struct Then {
+    pub(crate) response_template: Rc<Cell<MockServerHttpResponse>>,
+    #[cfg(feature = "graphql")]
+    pub(crate) graphql: Rc<Cell<GraphQLSpec>>,
+}
+
+impl Then {
+    /// Applies a function to this `Then`, which allows to share response settings between mocks
+    /// (see [When::and](struct.When.html#method.and)).
     ///
-    /// ## Example:
+    /// * `f` - A function that configures the response.
+    ///
+    /// # Example
     /// ```
     /// use httpmock::prelude::*;
+    /// use httpmock::Then;
     ///
-    /// // Arrange
     /// let server = MockServer::start();
     ///
-    /// let m = server.mock(|when, then|{
-    ///    when.matches(|req: &HttpMockRequest| {
-    ///         req.path.contains("es")
-    ///    });
-    ///    then.status(200);
+    /// let json = |then: Then| then.header("content-type", "application/json");
+    ///
+    /// server.mock(|when, then| {
+    ///     when.path("/created");
+    ///     then.and(json).status(201).body("{}");
     /// });
     ///
-    /// // Act: Send the HTTP request
-    /// let response = isahc::get(server.url("/test")).unwrap();
+    /// let response = isahc::get(server.url("/created")).unwrap();
     ///
-    /// // Assert
-    /// m.assert();
-    /// assert_eq!(response.status(), 200);
+    /// assert_eq!(response.status(), 201);
+    /// assert_eq!(response.headers()["content-type"], "application/json");
     /// ```
-    pub fn matches(mut self, matcher: MockMatcherFunction) -> Self {
-        update_cell(&self.expectations, |e| {
-            if e.matchers.is_none() {
-                e.matchers = Some(Vec::new());
-            }
-            e.matchers.as_mut().unwrap().push(matcher);
-        });
-        self
+    pub fn and<F: FnOnce(Then) -> Then>(self, f: F) -> Self {
+        f(self)
     }
-}
-
-/// A type that allows the specification of HTTP response values.
-pub struct Then {
-    pub(crate) response_template: Rc<Cell<MockServerHttpResponse>>,
-}
 
-impl Then {
     /// Sets the HTTP response code that will be returned by the mock server.
     ///
-    /// * `status` - The status code.
+    /// * `status` - The status code (a `u16` or, with the `http-interop` feature, an
+    /// `http::StatusCode`).
     ///
     /// ## Example:
     /// ```
@@ -809,9 +3378,9 @@ impl Then {
     /// m.assert();
     /// assert_eq!(response.status(), 200);
     /// ```
-    pub fn status(mut self, status: u16) -> Self {
+    pub fn status<S: IntoStatusCode>(mut self, status: S) -> Self {
         update_cell(&self.response_template, |r| {
-            r.status = Some(status);
+            r.status = Some(status.into_status_code());
         });
         self
     }
@@ -1033,6 +3602,71 @@ impl Then {
         self
     }
 
+    /// Sets an HTTP header like [Then::header](struct.Then.html#method.header), but takes the
+    /// typed header names and values of the `http` crate. Requires the `http-interop` feature.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use http::{header, HeaderValue, StatusCode};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let mock = server.mock(|when, then| {
+    ///     when.path("/");
+    ///     then.status(StatusCode::CREATED)
+    ///         .typed_header(header::LOCATION, HeaderValue::from_static("/users/1"));
+    /// });
+    ///
+    /// let response = isahc::get(server.url("/")).unwrap();
+    ///
+    /// assert_eq!(response.status(), 201);
+    /// assert_eq!(response.headers()["location"], "/users/1");
+    /// ```
+    /// # Panics
+    /// Panics if the header value is not valid UTF-8.
+    #[cfg(feature = "http-interop")]
+    pub fn typed_header<K: Into<http::HeaderName>, V: Into<http::HeaderValue>>(
+        self,
+        name: K,
+        value: V,
+    ) -> Self {
+        let (name, value) = header_strings(name.into(), value.into());
+        self.header(name, value)
+    }
+
+    /// Sets the `Connection: close` header and lets the mock server close the connection after
+    /// the response has been sent. This can be used to test whether clients reconnect properly.
+    /// Only HTTP/1 connections are closed.
+    ///
+    /// ## Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then|{
+    ///     when.path("/");
+    ///     then.status(200).connection_close();
+    /// });
+    ///
+    /// let response = isahc::get(server.url("/")).unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(response.headers().get("connection").unwrap(), "close");
+    /// ```
+    pub fn connection_close(self) -> Self {
+        self.header("connection", "close")
+    }
+
+    /// Sets the `Connection: close` header and lets the mock server close the connection after
+    /// the response has been sent. Please refer to
+    /// [Then::connection_close](struct.Then.html#method.connection_close) for more information.
+    #[deprecated(note = "Please use Then::connection_close instead.")]
+    pub fn return_connection_close(self) -> Self {
+        self.connection_close()
+    }
+
     /// Sets a duration that will delay the mock server response.
     ///
     /// * `duration` - The delay.
@@ -1066,4 +3700,176 @@ impl Then {
         });
         self
     }
+
+    /// Responds with the provided GraphQL data, wrapped in the `{"data": ...}` envelope of a
+    /// GraphQL response. The `content-type` header is set to `application/json` unless it was
+    /// set before.
+    ///
+    /// If a GraphQL schema was loaded with
+    /// [MockServer::load_graphql_schema](struct.MockServer.html#method.load_graphql_schema),
+    /// the data is validated against the schema when the mock is created.
+    ///
+    /// This method requires the `graphql` Cargo feature.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use isahc::{prelude::*, Request};
+    /// use serde_json::json;
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// let m = server.mock(|when, then| {
+    ///     when.path("/graphql");
+    ///     then.graphql_data(json!({ "user": { "id": "1" } }));
+    /// });
+    ///
+    /// let mut response = Request::post(server.url("/graphql"))
+    ///     .body(r#"{"query": "{ user { id } }"}"#)
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// m.assert();
+    /// assert_eq!(response.text().unwrap(), r#"{"data":{"user":{"id":"1"}}}"#);
+    /// ```
+    #[cfg(feature = "graphql")]
+    pub fn graphql_data<V: Into<Value>>(mut self, data: V) -> Self {
+        let data = data.into();
+        update_cell(&self.graphql, |g| {
+            g.data = Some(data.clone());
+        });
+        self.graphql_response_field("data", data)
+    }
+
+    /// Responds with the provided errors in the `errors` field of a GraphQL response. The
+    /// errors can be combined with [Then::graphql_data](#method.graphql_data)
+    /// to mock a partial response.
+    ///
+    /// This method requires the `graphql` Cargo feature.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    /// use httpmock::GraphQLError;
+    /// use isahc::{prelude::*, Request};
+    /// use serde_json::{json, Value};
+    ///
+    /// let server = MockServer::start();
+    ///
+    /// server.mock(|when, then| {
+    ///     when.path("/graphql");
+    ///     then.graphql_data(json!({ "user": null }))
+    ///         .graphql_errors(vec![GraphQLError::new("User not found")
+    ///             .path("user")
+    ///             .extension("code", "NOT_FOUND")]);
+    /// });
+    ///
+    /// let mut response = Request::post(server.url("/graphql"))
+    ///     .body(r#"{"query": "{ user { id } }"}"#)
+    ///     .unwrap()
+    ///     .send()
+    ///     .unwrap();
+    ///
+    /// let body: Value = serde_json::from_str(&response.text().unwrap()).unwrap();
+    /// assert_eq!(body["errors"][0]["path"], json!(["user"]));
+    /// assert_eq!(body["errors"][0]["extensions"]["code"], "NOT_FOUND");
+    /// ```
+    #[cfg(feature = "graphql")]
+    pub fn graphql_errors<I: IntoIterator<Item = GraphQLError>>(self, errors: I) -> Self {
+        let errors: Vec<GraphQLError> = errors.into_iter().collect();
+        let errors = serde_json::to_value(errors).expect("Cannot serialize GraphQL errors");
+        self.graphql_response_field("errors", errors)
+    }
+
+    /// Sets the protobuf message that is sent in a gRPC frame as the response body.
+    ///
+    /// Please refer to [MockServer::mock_grpc](struct.MockServer.html#method.mock_grpc) for an
+    /// example. This method requires the `grpc` Cargo feature.
+    #[cfg(feature = "grpc")]
+    pub fn grpc_response<T: prost::Message>(mut self, message: &T) -> Self {
+        update_cell(&self.response_template, |r| {
+            r.body = Some(grpc::encode_frame(&message.encode_to_vec()));
+        });
+        self
+    }
+
+    /// Sets the status of the gRPC call, which is sent in the `grpc-status` and `grpc-message`
+    /// trailers. An empty message is not sent. Calls that fail with an error status usually do
+    /// not have a response message.
+    ///
+    /// Please refer to [MockServer::mock_grpc](struct.MockServer.html#method.mock_grpc) for an
+    /// example. This method requires the `grpc` Cargo feature.
+    #[cfg(feature = "grpc")]
+    pub fn grpc_status<S: Into<String>>(mut self, code: GrpcCode, message: S) -> Self {
+        let message = message.into();
+        update_cell(&self.response_template, |r| {
+            let trailers = r.trailers.get_or_insert_with(Vec::new);
+            trailers.retain(|(name, _)| name != "grpc-status" && name != "grpc-message");
+            trailers.push(("grpc-status".to_string(), (code as i32).to_string()));
+            if !message.is_empty() {
+                trailers.push((
+                    "grpc-message".to_string(),
+                    grpc::encode_status_message(&message),
+                ));
+            }
+        });
+        self
+    }
+
+    /// Responds with the provided XML content in the body of a SOAP envelope. Sets the status
+    /// code to 200 and the content type of the SOAP version.
+    ///
+    /// Please refer to [When::soap_action](struct.When.html#method.soap_action)
+    /// for an example. This method requires the `xml` Cargo feature.
+    #[cfg(feature = "xml")]
+    pub fn soap_body<S: AsRef<str>>(self, version: SoapVersion, content: S) -> Self {
+        self.status(200)
+            .header("content-type", version.content_type())
+            .body(soap::envelope(version, content.as_ref()))
+    }
+
+    /// Responds with a SOAP fault. SOAP 1.1 faults are sent with status code 500, SOAP 1.2
+    /// faults with status code 400 if the sender caused the fault and 500 otherwise.
+    ///
+    /// * `version` - The SOAP version of the response.
+    /// * `code` - The fault code. SOAP 1.1 names `Sender` and `Receiver` faults `Client` and
+    ///   `Server`.
+    /// * `reason` - The human readable reason of the fault.
+    ///
+    /// This method requires the `xml` Cargo feature.
+    #[cfg(feature = "xml")]
+    pub fn soap_fault<S: AsRef<str>>(
+        self,
+        version: SoapVersion,
+        code: SoapFaultCode,
+        reason: S,
+    ) -> Self {
+        self.status(code.status(version))
+            .header("content-type", version.content_type())
+            .body(soap::fault(version, code, reason.as_ref()))
+    }
+
+    /// Sets a top-level field of the GraphQL response body and keeps the other fields.
+    #[cfg(feature = "graphql")]
+    fn graphql_response_field(self, name: &str, value: Value) -> Self {
+        update_cell(&self.response_template, |r| {
+            let mut body: serde_json::Map<String, Value> = r
+                .body
+                .as_ref()
+                .and_then(|body| serde_json::from_slice(body).ok())
+                .unwrap_or_default();
+            body.insert(name.to_string(), value);
+            r.body = Some(Value::Object(body).to_string().into_bytes());
+
+            let headers = r.headers.get_or_insert_with(Vec::new);
+            if !headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            {
+                headers.push(("content-type".to_string(), "application/json".to_string()));
+            }
+        });
+        self
+    }
 }
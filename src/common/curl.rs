@@ -0,0 +1,560 @@
+//! Renders mocks and recorded requests as [curl](https://curl.se/) commands, so that requests
+//! can be reproduced without a Rust toolchain.
+
+use serde_json::Value;
+
+use crate::common::data::{body_preview, HttpMockRequest, RequestRequirements};
+use crate::common::util::format_rfc3339;
+
+/// Returns a curl command that sends an example request satisfying the provided requirements
+/// to the mock server at the provided base URL. Requirements that cannot be satisfied by a
+/// concrete value (e.g. regex matchers) are described in comments above the command and use
+/// placeholders in the command.
+pub(crate) fn mock_to_curl(base_url: &str, rr: &RequestRequirements) -> String {
+    let mut comments = Vec::new();
+    let mut headers: Vec<(String, String)> = Vec::new();
+
+    let path = match (&rr.path, &rr.path_contains) {
+        (Some(path), _) => path.clone(),
+        (None, Some(substrings)) if !substrings.is_empty() => {
+            let joined = substrings.concat();
+            match joined.starts_with('/') {
+                true => joined,
+                false => format!("/{}", joined),
+            }
+        }
+        _ => {
+            let prefix = rr
+                .path_prefix
+                .iter()
+                .flatten()
+                .next()
+                .map_or("", String::as_str);
+            let suffix = rr
+                .path_suffix
+                .iter()
+                .flatten()
+                .next()
+                .map_or("", String::as_str);
+            match prefix.starts_with('/') {
+                true => format!("{}{}", prefix, suffix),
+                false => format!("/{}{}", prefix, suffix),
+            }
+        }
+    };
+    for pattern in rr.path_matches.iter().flatten() {
+        comments.push(format!("the path must match the regex '{}'", pattern.regex));
+    }
+    for substring in rr.path_not_contains.iter().flatten() {
+        comments.push(format!("the path must not contain '{}'", substring));
+    }
+    for pattern in rr.path_not_matches.iter().flatten() {
+        comments.push(format!(
+            "the path must not match the regex '{}'",
+            pattern.regex
+        ));
+    }
+
+    let mut query: Vec<(String, String)> = rr.query_param.clone().unwrap_or_default();
+    for name in rr.query_param_exists.iter().flatten() {
+        if !query.iter().any(|(n, _)| n == name) {
+            query.push((name.clone(), placeholder()));
+        }
+    }
+    if let Some(query_string) = &rr.query_string {
+        query = query_string.params.clone();
+    }
+    for object in rr.query_objects.iter().flatten() {
+        for (name, value) in query_object_params("", object) {
+            if !query.iter().any(|(n, _)| *n == name) {
+                query.push((name, value));
+            }
+        }
+    }
+    for requirement in rr.query_param_values.iter().flatten() {
+        query.extend(
+            requirement
+                .values
+                .iter()
+                .map(|value| (requirement.name.clone(), value.clone())),
+        );
+    }
+    for (name, pattern) in rr.query_param_matches.iter().flatten() {
+        comments.push(format!(
+            "the query parameter '{}' must match the regex '{}'",
+            name, pattern.regex
+        ));
+        if !query.iter().any(|(n, _)| n == name) {
+            query.push((name.clone(), placeholder()));
+        }
+    }
+
+    headers.extend(rr.headers.iter().flatten().cloned());
+    for requirement in rr.header_values.iter().flatten() {
+        headers.extend(
+            requirement
+                .values
+                .iter()
+                .map(|value| (requirement.name.clone(), value.clone())),
+        );
+    }
+    for name in rr.header_exists.iter().flatten() {
+        if !has_header(&headers, name) {
+            headers.push((name.clone(), placeholder()));
+        }
+    }
+    for (name, pattern) in rr.header_matches.iter().flatten() {
+        comments.push(format!(
+            "the header '{}' must match the regex '{}'",
+            name, pattern.regex
+        ));
+        if !has_header(&headers, name) {
+            headers.push((name.clone(), placeholder()));
+        }
+    }
+    if let Some(content_type) = &rr.content_type {
+        if !has_header(&headers, "content-type") {
+            headers.push(("Content-Type".to_string(), content_type.clone()));
+        }
+    }
+    for signature in rr.hmac_signatures.iter().flatten() {
+        comments.push(format!("the header {} (sha256=<hex>)", signature));
+        if !has_header(&headers, &signature.header) {
+            headers.push((signature.header.clone(), placeholder()));
+        }
+    }
+    if let Some(media_types) = &rr.accepts {
+        if !has_header(&headers, "accept") {
+            headers.push(("Accept".to_string(), media_types.join(", ")));
+        }
+    }
+    if let Some(credentials) = &rr.basic_auth {
+        if !has_header(&headers, "authorization") {
+            headers.push((
+                "Authorization".to_string(),
+                format!("Basic {}", base64::encode(credentials)),
+            ));
+        }
+    }
+    for claim in rr.jwt_claims.iter().flatten() {
+        comments.push(format!(
+            "the bearer token must be a JWT with the claim {}",
+            claim
+        ));
+    }
+    if rr.bearer_token.is_some() || rr.jwt_claims.is_some() {
+        if !has_header(&headers, "authorization") {
+            let token = rr.bearer_token.clone().unwrap_or_else(placeholder);
+            headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+        }
+    }
+    if let Some(host) = &rr.host {
+        if !has_header(&headers, "host") {
+            headers.push(("Host".to_string(), host.clone()));
+        }
+    }
+
+    let mut cookies: Vec<String> = rr
+        .cookies
+        .iter()
+        .flatten()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    cookies.extend(
+        rr.cookie_exists
+            .iter()
+            .flatten()
+            .map(|name| format!("{}={}", name, placeholder())),
+    );
+
+    let mut body = None;
+    if let Some(text) = &rr.body {
+        body = Some(text.clone());
+    } else if let Some(bytes) = rr.body_base64.as_ref().and_then(|b| base64::decode(b).ok()) {
+        match String::from_utf8(bytes) {
+            Ok(text) => body = Some(text),
+            Err(e) => comments.push(format!(
+                "the body must equal the binary data {} (e.g. sent with --data-binary @file)",
+                body_preview(e.as_bytes())
+            )),
+        }
+    } else if let Some(xml) = &rr.xml_body {
+        body = Some(xml.clone());
+        if !has_header(&headers, "content-type") {
+            headers.push(("Content-Type".to_string(), "application/xml".to_string()));
+        }
+    } else if let Some(json) = &rr.json_body {
+        body = Some(json.to_string());
+    } else if let Some(json) = &rr.json_body_ignoring_paths {
+        body = Some(json.body.to_string());
+    } else if let Some(partials) = &rr.json_body_includes {
+        let mut merged = Value::Null;
+        for partial in partials {
+            merge_json(&mut merged, partial);
+        }
+        body = Some(merged.to_string());
+    } else if let Some(params) = &rr.x_www_form_urlencoded {
+        let mut serializer = form_urlencoded::Serializer::new(String::new());
+        serializer.extend_pairs(params);
+        for name in rr.x_www_form_urlencoded_key_exists.iter().flatten() {
+            serializer.append_pair(name, &placeholder());
+        }
+        body = Some(serializer.finish());
+        if !has_header(&headers, "content-type") {
+            headers.push((
+                "Content-Type".to_string(),
+                "application/x-www-form-urlencoded".to_string(),
+            ));
+        }
+    } else if let Some(substrings) = &rr.body_contains {
+        body = Some(substrings.concat());
+    }
+    if (rr.json_body.is_some()
+        || rr.json_body_ignoring_paths.is_some()
+        || rr.json_body_includes.is_some())
+        && rr.body.is_none()
+        && !has_header(&headers, "content-type")
+    {
+        headers.push(("Content-Type".to_string(), "application/json".to_string()));
+    }
+    for pattern in rr.body_matches.iter().flatten() {
+        comments.push(format!("the body must match the regex '{}'", pattern.regex));
+    }
+    for substring in rr.body_not_contains.iter().flatten() {
+        comments.push(format!("the body must not contain '{}'", substring));
+    }
+    for pattern in rr.body_not_matches.iter().flatten() {
+        comments.push(format!(
+            "the body must not match the regex '{}'",
+            pattern.regex
+        ));
+    }
+    if rr.chunked_body && !has_header(&headers, "transfer-encoding") {
+        headers.push(("Transfer-Encoding".to_string(), "chunked".to_string()));
+    }
+    if let Some(len) = &rr.body_len {
+        comments.push(format!("the body must have {}", len));
+    }
+    for requirement in rr.json_body_paths.iter().flatten() {
+        comments.push(format!(
+            "the JSON body must contain the JSON path '{}'",
+            requirement
+        ));
+    }
+    for line in rr.ndjson_lines.iter().flatten() {
+        comments.push(format!("the NDJSON body {}", line));
+    }
+    if let Some(count) = rr.ndjson_line_count {
+        comments.push(format!("the NDJSON body must have {} lines", count));
+    }
+    if let Some(json) = &rr.json_body_strict {
+        comments.push(format!(
+            "the JSON body must not contain fields other than in {}",
+            json
+        ));
+    }
+    if let Some(json) = &rr.json_body_ignoring_paths {
+        comments.push(format!(
+            "the values at {} may differ from the JSON body",
+            json.ignored_paths.join(", ")
+        ));
+    }
+    if rr.json_body_schema.is_some() {
+        comments.push("the JSON body must be valid according to the JSON schema".to_string());
+    }
+    for requirement in rr.body_xpaths.iter().flatten() {
+        comments.push(format!(
+            "the XML body must contain the XPath '{}'",
+            requirement
+        ));
+    }
+    for part in rr.multipart_parts.iter().flatten() {
+        comments.push(format!("the multipart body must contain the part {}", part));
+    }
+    if rr.grpc_message.is_some() {
+        comments.push("the body must be a gRPC frame with the expected message".to_string());
+    }
+    if rr.protobuf_body.is_some() {
+        comments.push("the body must be the expected protobuf message".to_string());
+    }
+    if let Some(operation) = &rr.graphql_operation {
+        comments.push(format!("the GraphQL operation must be '{}'", operation));
+    }
+    if let Some(variables) = &rr.graphql_variables {
+        comments.push(format!("the GraphQL variables must equal {}", variables));
+    }
+    if let Some(cn) = &rr.client_cert_cn {
+        comments.push(format!(
+            "a client certificate with the common name '{}' is required (--cert and --key)",
+            cn
+        ));
+    }
+    if let Some(methods) = &rr.methods {
+        comments.push(format!("the method must be one of {}", methods.join(", ")));
+    }
+    if let Some(version) = &rr.http_version {
+        comments.push(format!("the request must use {}", version));
+    }
+    if let Some(listener) = &rr.listener {
+        comments.push(format!(
+            "the request must be sent to the listener '{}'",
+            listener
+        ));
+    }
+    if let Some(remote_addr) = &rr.remote_addr {
+        comments.push(format!("the request must be sent from '{}'", remote_addr));
+    }
+    for pattern in rr.remote_addr_matches.iter().flatten() {
+        comments.push(format!(
+            "the address the request is sent from must match regex '{}'",
+            pattern.regex
+        ));
+    }
+    if rr.matchers.as_ref().map_or(false, |m| !m.is_empty()) {
+        comments.push("custom matcher functions must also be satisfied".to_string());
+    }
+    for matcher in rr.custom_matchers.iter().flatten() {
+        comments.push(format!("the custom matcher must be satisfied: {}", matcher));
+    }
+    if rr.disabled {
+        comments.push("the mock must be enabled first".to_string());
+    }
+    if let Some(time) = rr.active_from {
+        comments.push(format!(
+            "the request must be sent at or after {}",
+            format_rfc3339(time)
+        ));
+    }
+    if let Some(time) = rr.active_until {
+        comments.push(format!(
+            "the request must be sent before {}",
+            format_rfc3339(time)
+        ));
+    }
+    if let Some(alternatives) = &rr.any_of {
+        let alternatives: Vec<String> = alternatives
+            .iter()
+            .map(|alternative| alternative.summary().join(" and "))
+            .collect();
+        comments.push(format!(
+            "the request must also fulfill one of: ({})",
+            alternatives.join(") or (")
+        ));
+    }
+
+    let method = rr
+        .method
+        .as_deref()
+        .or_else(|| rr.methods.iter().flatten().next().map(String::as_str))
+        .unwrap_or("GET");
+    // Raw query parameters are already encoded as the client must send them.
+    let url = match rr.query_params_raw && !query.is_empty() {
+        true => {
+            let pairs: Vec<String> = query.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+            format!("{}?{}", to_url(base_url, &path, &[]), pairs.join("&"))
+        }
+        false => to_url(base_url, &path, &query),
+    };
+    let command = to_command(method, &url, &headers, &cookies, body.as_deref());
+
+    let mut script = String::new();
+    for comment in comments {
+        script.push_str(&format!("# {}\n", comment));
+    }
+    script.push_str(&command);
+    script
+}
+
+/// Returns a curl command that sends the recorded request again. The URL is derived from the
+/// `Host` header of the request. Binary bodies cannot be rendered and are omitted.
+pub(crate) fn request_to_curl(request: &HttpMockRequest) -> String {
+    let headers = request.headers.as_deref().unwrap_or_default();
+    let host = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("host"))
+        .map(|(_, value)| value.clone())
+        .or_else(|| request.host.clone())
+        .unwrap_or_else(|| "localhost".to_string());
+    let url = to_url(
+        &format!("http://{}", host),
+        &request.path,
+        request.query_params.as_deref().unwrap_or_default(),
+    );
+
+    // Curl sets the host and the content length itself.
+    let headers: Vec<(String, String)> = headers
+        .iter()
+        .filter(|(name, _)| {
+            !name.eq_ignore_ascii_case("host") && !name.eq_ignore_ascii_case("content-length")
+        })
+        .cloned()
+        .collect();
+
+    let mut script = String::new();
+    let body = request.body.as_deref().filter(|b| !b.is_empty());
+    let text = match body.map(std::str::from_utf8) {
+        Some(Ok(text)) => Some(text),
+        Some(Err(_)) => {
+            script.push_str(&format!(
+                "# the binary body of {} bytes was omitted\n",
+                body.unwrap().len()
+            ));
+            None
+        }
+        None => None,
+    };
+    if request.body_truncated {
+        script.push_str("# the body was too large and was not recorded\n");
+    }
+    script.push_str(&to_command(&request.method, &url, &headers, &[], text));
+    script
+}
+
+fn to_command(
+    method: &str,
+    url: &str,
+    headers: &[(String, String)],
+    cookies: &[String],
+    body: Option<&str>,
+) -> String {
+    let mut parts = vec![match method {
+        // Curl waits for a response body if HEAD is set with -X.
+        "HEAD" => format!("curl --head {}", quote(url)),
+        method => format!("curl -X {} {}", method, quote(url)),
+    }];
+    for (name, value) in headers {
+        parts.push(format!("-H {}", quote(&format!("{}: {}", name, value))));
+    }
+    if !cookies.is_empty() {
+        parts.push(format!("-b {}", quote(&cookies.join("; "))));
+    }
+    if let Some(body) = body {
+        parts.push(format!("--data-raw {}", quote(body)));
+    }
+    parts.join(" \\\n  ")
+}
+
+fn to_url(base_url: &str, path: &str, query: &[(String, String)]) -> String {
+    let mut url = format!("{}{}", base_url.trim_end_matches('/'), path);
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(
+            &form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(query)
+                .finish(),
+        );
+    }
+    url
+}
+
+/// Quotes a value for a POSIX shell.
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn placeholder() -> String {
+    "<value>".to_string()
+}
+
+fn has_header(headers: &[(String, String)], name: &str) -> bool {
+    headers.iter().any(|(n, _)| n.eq_ignore_ascii_case(name))
+}
+
+/// Merges the fields of a partial JSON object into the target, so that the result includes
+/// all partial objects.
+fn merge_json(target: &mut Value, partial: &Value) {
+    match (target, partial) {
+        (Value::Object(target), Value::Object(partial)) => {
+            for (key, value) in partial {
+                merge_json(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (target, partial) => *target = partial.clone(),
+    }
+}
+
+/// Flattens a query object into query parameters with bracketed names, e.g.
+/// `{"filter": {"tags": ["a"]}}` into `filter[tags][]=a`.
+fn query_object_params(prefix: &str, value: &Value) -> Vec<(String, String)> {
+    let nested = |key: &str| match prefix {
+        "" => key.to_string(),
+        _ => format!("{}[{}]", prefix, key),
+    };
+    match value {
+        Value::Object(fields) => fields
+            .iter()
+            .flat_map(|(key, value)| query_object_params(&nested(key), value))
+            .collect(),
+        Value::Array(values) => values
+            .iter()
+            .flat_map(|value| query_object_params(&nested(""), value))
+            .collect(),
+        Value::String(value) => vec![(prefix.to_string(), value.clone())],
+        Value::Null => vec![(prefix.to_string(), String::new())],
+        value => vec![(prefix.to_string(), value.to_string())],
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "regex")]
+    use regex::Regex;
+    use serde_json::json;
+
+    use crate::common::curl::{mock_to_curl, request_to_curl};
+    use crate::common::data::{HttpMockRequest, Pattern, RequestRequirements};
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn mock_to_curl_test() {
+        // Arrange
+        let mut rr = RequestRequirements::new()
+            .with_method("POST".to_string())
+            .with_path("/users".to_string());
+        rr.query_param = Some(vec![("tag".to_string(), "a b".to_string())]);
+        rr.header_matches = Some(vec![(
+            "authorization".to_string(),
+            Pattern::from_regex(Regex::new("^Bearer .+$").unwrap()),
+        )]);
+        rr.json_body_includes = Some(vec![
+            json!({ "user": { "name": "Fred" } }),
+            json!({ "user": { "age": 30 } }),
+        ]);
+
+        // Act
+        let curl = mock_to_curl("http://127.0.0.1:5000", &rr);
+
+        // Assert
+        assert_eq!(
+            curl,
+            "# the header 'authorization' must match the regex '^Bearer .+$'\n\
+             curl -X POST 'http://127.0.0.1:5000/users?tag=a+b' \\\n  \
+             -H 'authorization: <value>' \\\n  \
+             -H 'Content-Type: application/json' \\\n  \
+             --data-raw '{\"user\":{\"age\":30,\"name\":\"Fred\"}}'"
+        );
+    }
+
+    #[test]
+    fn request_to_curl_test() {
+        // Arrange
+        let request = HttpMockRequest::new("PUT".to_string(), "/notes/1".to_string())
+            .with_headers(vec![
+                ("host".to_string(), "localhost:5000".to_string()),
+                ("content-length".to_string(), "11".to_string()),
+                ("x-note".to_string(), "it's".to_string()),
+            ])
+            .with_body(b"it's a note".to_vec());
+
+        // Act
+        let curl = request_to_curl(&request);
+
+        // Assert
+        assert_eq!(
+            curl,
+            "curl -X PUT 'http://localhost:5000/notes/1' \\\n  \
+             -H 'x-note: it'\\''s' \\\n  \
+             --data-raw 'it'\\''s a note'"
+        );
+    }
+}
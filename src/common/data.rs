@@ -1,26 +1,95 @@
-extern crate serde_regex;
-
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::fmt;
 use std::fmt::Debug;
+use std::net::SocketAddr;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::Relaxed;
-use std::sync::{Arc, RwLock};
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 
+use assert_json_diff::{assert_json_matches_no_panic, CompareMode, Config};
+#[cfg(feature = "regex")]
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::common::json_path;
+use crate::common::util::format_rfc3339;
+
 /// A general abstraction of an HTTP request of `httpmock`.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct HttpMockRequest {
     pub path: String,
     pub method: String,
     pub headers: Option<Vec<(String, String)>>,
     pub query_params: Option<Vec<(String, String)>>,
+    /// The query string as it was sent, without the leading `?` and not percent-decoded.
+    #[serde(default)]
+    pub raw_query: Option<String>,
     pub body: Option<Vec<u8>>,
+    /// The HTTP version of the request (e.g. `HTTP/1.1` or `HTTP/2`).
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub client_certificate: Option<ClientCertificate>,
+    /// Whether the body was not read, either because it exceeded the maximum body size of the
+    /// mock server, or because no mock required it and it was too large to be recorded. The
+    /// body is not recorded in this case.
+    #[serde(default)]
+    pub body_truncated: bool,
+    /// The host that the request was sent to, as given by the request URI or the `Host`
+    /// header (without the port).
+    #[serde(default)]
+    pub host: Option<String>,
+    /// The name of the listener that the request arrived on, if the mock server listens on
+    /// several named addresses.
+    #[serde(default)]
+    pub listener: Option<String>,
+    /// The address of the client that sent the request. Not set for requests that arrived on a
+    /// Unix domain socket.
+    #[serde(default)]
+    pub remote_addr: Option<SocketAddr>,
+    /// Whether the request did not match any mock and was forwarded to an upstream server.
+    #[serde(default)]
+    pub forwarded: bool,
+    /// The status of the upstream response to a forwarded request. Not set if the request
+    /// could not be forwarded.
+    #[serde(default)]
+    pub upstream_status: Option<u16>,
+    /// The namespace that the request was sent to (see
+    /// [MockServer::connect_isolated](struct.MockServer.html#method.connect_isolated)). The
+    /// namespace prefix is not part of the path.
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// Converts a request that the mock server received into a request of the `http` crate, e.g. to
+/// replay it with another client. The URI contains the path and the query parameters. Requires
+/// the `http-interop` feature.
+#[cfg(feature = "http-interop")]
+impl std::convert::TryFrom<&HttpMockRequest> for http::Request<Vec<u8>> {
+    type Error = http::Error;
+
+    fn try_from(request: &HttpMockRequest) -> Result<Self, Self::Error> {
+        let version = match request.version.as_deref() {
+            Some("HTTP/0.9") => http::Version::HTTP_09,
+            Some("HTTP/1.0") => http::Version::HTTP_10,
+            Some("HTTP/2") => http::Version::HTTP_2,
+            Some("HTTP/3") => http::Version::HTTP_3,
+            _ => http::Version::HTTP_11,
+        };
+
+        let mut builder = http::Request::builder()
+            .method(request.method.as_str())
+            .uri(request.path_and_query())
+            .version(version);
+        for (name, value) in request.headers.iter().flatten() {
+            builder = builder.header(name, value);
+        }
+        builder.body(request.body.clone().unwrap_or_default())
+    }
 }
 
 impl HttpMockRequest {
@@ -30,7 +99,17 @@ impl HttpMockRequest {
             method,
             headers: None,
             query_params: None,
+            raw_query: None,
             body: None,
+            version: None,
+            client_certificate: None,
+            body_truncated: false,
+            host: None,
+            listener: None,
+            remote_addr: None,
+            forwarded: false,
+            upstream_status: None,
+            namespace: None,
         }
     }
 
@@ -48,6 +127,237 @@ impl HttpMockRequest {
         self.body = Some(arg);
         self
     }
+
+    pub fn with_version(mut self, arg: String) -> Self {
+        self.version = Some(arg);
+        self
+    }
+
+    pub fn with_client_certificate(mut self, arg: ClientCertificate) -> Self {
+        self.client_certificate = Some(arg);
+        self
+    }
+
+    pub fn with_host(mut self, arg: String) -> Self {
+        self.host = Some(arg);
+        self
+    }
+
+    pub fn with_listener(mut self, arg: String) -> Self {
+        self.listener = Some(arg);
+        self
+    }
+
+    pub fn with_namespace(mut self, arg: String) -> Self {
+        self.namespace = Some(arg);
+        self
+    }
+
+    pub fn with_raw_query(mut self, arg: String) -> Self {
+        self.raw_query = Some(arg);
+        self
+    }
+
+    pub fn with_remote_addr(mut self, arg: SocketAddr) -> Self {
+        self.remote_addr = Some(arg);
+        self
+    }
+
+    /// Returns a [curl](https://curl.se/) command that sends this request again, so that it
+    /// can be reproduced by hand. The URL is derived from the `Host` header of the request.
+    /// Binary bodies cannot be rendered and are omitted.
+    ///
+    /// # Example
+    /// ```
+    /// use httpmock::prelude::*;
+    ///
+    /// let request = HttpMockRequest::new("GET".to_string(), "/hello".to_string())
+    ///     .with_headers(vec![("host".to_string(), "localhost:5000".to_string())]);
+    ///
+    /// assert_eq!(request.to_curl(), "curl -X GET 'http://localhost:5000/hello'");
+    /// ```
+    pub fn to_curl(&self) -> String {
+        crate::common::curl::request_to_curl(self)
+    }
+
+    /// Returns the path and the URL encoded query parameters of the request.
+    pub(crate) fn path_and_query(&self) -> String {
+        match self.query_params.as_ref().filter(|p| !p.is_empty()) {
+            Some(query_params) => format!(
+                "{}?{}",
+                self.path,
+                form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(query_params)
+                    .finish()
+            ),
+            None => self.path.clone(),
+        }
+    }
+}
+
+/// The maximum number of characters of a body that is shown in summaries of requests and mocks.
+const BODY_PREVIEW_CHARS: usize = 200;
+
+/// The maximum number of bytes of a binary body that is shown in summaries.
+const BINARY_PREVIEW_BYTES: usize = 16;
+
+/// Describes a mock by its method and path for error messages and logs (e.g. `GET /search`).
+/// Mocks without a method or path requirement of their own are described by the ones of their
+/// alternatives (e.g. `GET /v1/users|/v2/users`).
+pub(crate) fn describe_mock(request: &RequestRequirements) -> String {
+    format!("{} {}", describe_method(request), describe_path(request))
+}
+
+fn describe_method(request: &RequestRequirements) -> String {
+    match (&request.method, &request.methods) {
+        (Some(method), _) => method.clone(),
+        (None, Some(methods)) if !methods.is_empty() => methods.join("|"),
+        _ => describe_alternatives(request, describe_method),
+    }
+}
+
+fn describe_path(request: &RequestRequirements) -> String {
+    let prefix = request.path_prefix.iter().flatten().next();
+    let suffix = request.path_suffix.iter().flatten().next();
+    match (&request.path, &request.path_contains, &request.path_matches) {
+        (Some(path), _, _) => path.clone(),
+        (None, _, _) if prefix.is_some() || suffix.is_some() => format!(
+            "{}*{}",
+            prefix.map_or("", String::as_str),
+            suffix.map_or("", String::as_str)
+        ),
+        (None, Some(substrings), _) if !substrings.is_empty() => {
+            format!("*{}*", substrings.join("*"))
+        }
+        (None, _, Some(patterns)) if !patterns.is_empty() => format!("~{}", patterns[0].regex),
+        _ => describe_alternatives(request, describe_path),
+    }
+}
+
+/// Joins the distinct descriptions of the alternatives of a mock, or returns `*` if any of them
+/// does not restrict the described value.
+fn describe_alternatives(
+    request: &RequestRequirements,
+    describe: fn(&RequestRequirements) -> String,
+) -> String {
+    let mut descriptions: Vec<String> = Vec::new();
+    for alternative in request.any_of.iter().flatten() {
+        let description = describe(alternative);
+        if description == "*" {
+            return description;
+        }
+        if !descriptions.contains(&description) {
+            descriptions.push(description);
+        }
+    }
+    match descriptions.is_empty() {
+        true => "*".to_string(),
+        false => descriptions.join("|"),
+    }
+}
+
+/// Returns a short, single-line preview of a body. Long text is truncated and binary bodies are
+/// shown as their length and a hex prefix.
+pub(crate) fn body_preview(body: &[u8]) -> String {
+    match std::str::from_utf8(body) {
+        Ok(text) if text.chars().count() <= BODY_PREVIEW_CHARS => format!("{:?}", text),
+        Ok(text) => format!(
+            "{:?}... ({} bytes)",
+            text.chars().take(BODY_PREVIEW_CHARS).collect::<String>(),
+            body.len()
+        ),
+        Err(_) => {
+            let prefix: Vec<String> = body
+                .iter()
+                .take(BINARY_PREVIEW_BYTES)
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            let ellipsis = match body.len() > BINARY_PREVIEW_BYTES {
+                true => " ...",
+                false => "",
+            };
+            format!(
+                "<{} bytes of binary data: {}{}>",
+                body.len(),
+                prefix.join(" "),
+                ellipsis
+            )
+        }
+    }
+}
+
+/// Prints the request line, one line per header and a preview of the body, e.g.:
+/// ```text
+/// POST /users?dry_run=true HTTP/1.1
+///   content-type: application/json
+///   body: "{\"name\":\"Fred\"}"
+/// ```
+impl fmt::Display for HttpMockRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.path_and_query())?;
+        if let Some(version) = &self.version {
+            write!(f, " {}", version)?;
+        }
+        for (name, value) in self.headers.iter().flatten() {
+            write!(f, "\n  {}: {}", name, value)?;
+        }
+        match (&self.body, self.body_truncated) {
+            (_, true) => write!(f, "\n  body: <truncated>"),
+            (Some(body), false) if !body.is_empty() => {
+                write!(f, "\n  body: {}", body_preview(body))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Prints the body as a preview (see the `Display` implementation).
+impl fmt::Debug for HttpMockRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpMockRequest")
+            .field("path", &self.path)
+            .field("method", &self.method)
+            .field("headers", &self.headers)
+            .field("query_params", &self.query_params)
+            .field("raw_query", &self.raw_query)
+            .field(
+                "body",
+                &self
+                    .body
+                    .as_ref()
+                    .map(|body| PreviewDebug(body_preview(body))),
+            )
+            .field("version", &self.version)
+            .field("client_certificate", &self.client_certificate)
+            .field("body_truncated", &self.body_truncated)
+            .field("host", &self.host)
+            .field("listener", &self.listener)
+            .field("remote_addr", &self.remote_addr)
+            .field("forwarded", &self.forwarded)
+            .field("upstream_status", &self.upstream_status)
+            .field("namespace", &self.namespace)
+            .finish()
+    }
+}
+
+/// Prints an already formatted value without quoting it again.
+struct PreviewDebug(String);
+
+impl fmt::Debug for PreviewDebug {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The verified certificate that a client presented during the TLS handshake.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClientCertificate {
+    /// The subject distinguished name (e.g. `CN=my-service, O=example`).
+    pub subject: String,
+    /// The common name (CN) of the subject, if present.
+    pub common_name: Option<String>,
+    /// The DER encoded certificate.
+    pub der: Vec<u8>,
 }
 
 /// A general abstraction of an HTTP response for all handlers.
@@ -58,6 +368,14 @@ pub struct MockServerHttpResponse {
     #[serde(default, with = "opt_vector_serde_base64")]
     pub body: Option<Vec<u8>>,
     pub delay: Option<Duration>,
+    /// Responses that are served one after another, one per matching request. The last response
+    /// is repeated once all responses have been served. All other fields are ignored if set.
+    #[serde(default)]
+    pub sequence: Option<Vec<MockServerHttpResponse>>,
+    /// Headers that are sent after the body (e.g. the status of a gRPC call). Trailers are only
+    /// sent over HTTP/2.
+    #[serde(default)]
+    pub trailers: Option<Vec<(String, String)>>,
 }
 
 impl MockServerHttpResponse {
@@ -67,6 +385,16 @@ impl MockServerHttpResponse {
             headers: None,
             body: None,
             delay: None,
+            sequence: None,
+            trailers: None,
+        }
+    }
+
+    /// Returns the response for the request with the provided index, starting at 0.
+    pub(crate) fn for_request(&self, index: usize) -> &MockServerHttpResponse {
+        match &self.sequence {
+            Some(sequence) if !sequence.is_empty() => &sequence[index.min(sequence.len() - 1)],
+            _ => self,
         }
     }
 }
@@ -131,18 +459,26 @@ impl fmt::Debug for MockServerHttpResponse {
                     .map(|x| String::from_utf8_lossy(x.as_ref()).to_string()),
             )
             .field("delay", &self.delay)
+            .field("sequence", &self.sequence)
             .finish()
     }
 }
 
 /// A general abstraction of an HTTP request for all handlers.
+///
+/// Without the `regex` feature, the pattern only holds the source of the regular expression,
+/// so that it serializes the same way, but it cannot be matched.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Pattern {
+    #[cfg(feature = "regex")]
     #[serde(with = "serde_regex")]
     pub regex: Regex,
+    #[cfg(not(feature = "regex"))]
+    pub regex: String,
 }
 
 impl Pattern {
+    #[cfg(feature = "regex")]
     pub fn from_regex(regex: Regex) -> Pattern {
         Pattern { regex }
     }
@@ -168,31 +504,514 @@ impl PartialEq for Pattern {
 
 impl Eq for Pattern {}
 
-pub type MockMatcherFunction = fn(&HttpMockRequest) -> bool;
+/// Requires a JSON request body to contain a value at a JSONPath expression (e.g.
+/// `$.user.addresses[0].zip`) that, if set, equals the expected value.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JsonPathRequirement {
+    pub path: String,
+    pub value: Option<Value>,
+}
+
+impl fmt::Display for JsonPathRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{} == {}", self.path, value),
+            None => write!(f, "{}", self.path),
+        }
+    }
+}
+
+/// Requires the JWT bearer token of a request to contain a claim (e.g. `sub`) with the expected
+/// value. The signature of the token is not verified.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JwtClaim {
+    pub name: String,
+    pub value: Value,
+}
+
+impl fmt::Display for JwtClaim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} == {}", self.name, self.value)
+    }
+}
+
+/// Requires the line at an index of an NDJSON (JSON Lines) body to be equal to the expected JSON
+/// value. Indexes start at 0 and blank lines are not counted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NdjsonLine {
+    pub index: usize,
+    pub value: Value,
+}
+
+impl fmt::Display for NdjsonLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} equals {}", self.index, self.value)
+    }
+}
+
+/// Requires a JSON body to be equal to the expected value, except for the values at the
+/// ignored JSONPath expressions (e.g. `$.timestamp`), which may have any value or be missing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JsonBodyIgnoringPaths {
+    pub body: Value,
+    pub ignored_paths: Vec<String>,
+}
+
+impl JsonBodyIgnoringPaths {
+    /// Returns a copy of a value without the values at the ignored paths.
+    pub(crate) fn without_ignored(&self, value: &Value) -> Value {
+        let mut value = value.clone();
+        for path in &self.ignored_paths {
+            // Paths are validated when the mock is created.
+            let _ = json_path::remove(&mut value, path);
+        }
+        value
+    }
+}
+
+impl fmt::Display for JsonBodyIgnoringPaths {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.body)
+    }
+}
+
+/// Requires a query parameter that can occur several times in a request (e.g. `?id=1&id=2`) to
+/// have exactly the expected values, in any order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QueryParamValues {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+impl QueryParamValues {
+    /// Returns the values in a canonical order, so that they can be compared regardless of the
+    /// order in which they were given.
+    pub(crate) fn sorted_values(&self) -> Vec<&str> {
+        let mut values: Vec<&str> = self.values.iter().map(String::as_str).collect();
+        values.sort_unstable();
+        values
+    }
+}
+
+impl fmt::Display for QueryParamValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self.values.iter().map(|v| format!("'{}'", v)).collect();
+        write!(f, "'{}' has the values {}", self.name, values.join(", "))
+    }
+}
+
+/// Requires the body of a request to have a length in bytes between the bounds, which are
+/// inclusive. A missing bound is unlimited.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BodyLength {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+}
+
+impl BodyLength {
+    /// Returns whether a body with the given length in bytes is within the bounds.
+    pub(crate) fn contains(&self, len: usize) -> bool {
+        self.min.map_or(true, |min| len >= min) && self.max.map_or(true, |max| len <= max)
+    }
+}
+
+impl fmt::Display for BodyLength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min == max => write!(f, "{} bytes", min),
+            (Some(min), Some(max)) => write!(f, "between {} and {} bytes", min, max),
+            (Some(min), None) => write!(f, "at least {} bytes", min),
+            (None, Some(max)) => write!(f, "at most {} bytes", max),
+            (None, None) => write!(f, "any number of bytes"),
+        }
+    }
+}
+
+/// Requires a header that can occur several times in a request (e.g. `X-Forwarded-For`) to have
+/// exactly the expected values, in the same order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HeaderValues {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+impl fmt::Display for HeaderValues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values: Vec<String> = self.values.iter().map(|v| format!("'{}'", v)).collect();
+        write!(f, "'{}' has the values {}", self.name, values.join(", "))
+    }
+}
+
+/// Requires a header to hold the HMAC-SHA256 signature of the raw request body, as webhook
+/// senders like GitHub add it (e.g. `X-Hub-Signature-256: sha256=<hex>`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HmacSignature {
+    pub header: String,
+    pub secret: String,
+}
+
+impl fmt::Display for HmacSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' holds the HMAC-SHA256 signature of the body",
+            self.header
+        )
+    }
+}
+
+/// Requires a request to have exactly the expected query parameters and no others, optionally
+/// in the same order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QueryString {
+    /// The decoded names and values of the query parameters.
+    pub params: Vec<(String, String)>,
+    pub ordered: bool,
+}
+
+impl QueryString {
+    /// Parses a URL encoded query string. A leading `?` is ignored.
+    pub(crate) fn parse(query: &str, ordered: bool) -> Self {
+        let query = query.strip_prefix('?').unwrap_or(query);
+        Self {
+            params: form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect(),
+            ordered,
+        }
+    }
+
+    /// Returns the URL encoded query string without a leading `?`.
+    pub(crate) fn encoded(&self) -> String {
+        form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(&self.params)
+            .finish()
+    }
+
+    /// Returns the parameters in a canonical order, unless their order matters.
+    pub(crate) fn comparable_params<'a>(
+        &self,
+        params: &'a [(String, String)],
+    ) -> Vec<&'a (String, String)> {
+        let mut params: Vec<&(String, String)> = params.iter().collect();
+        if !self.ordered {
+            params.sort_unstable();
+        }
+        params
+    }
+}
+
+impl fmt::Display for QueryString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}'", self.encoded())
+    }
+}
+
+/// Requires an XML request body to contain a node at an XPath expression (e.g.
+/// `/Envelope/Body/GetUser/Id`) whose text, if set, equals the expected value.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct XPathRequirement {
+    pub path: String,
+    pub value: Option<String>,
+}
+
+impl fmt::Display for XPathRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{} == {:?}", self.path, value),
+            None => write!(f, "{}", self.path),
+        }
+    }
+}
+
+/// Requires a `multipart/form-data` request body to contain a part with a name (e.g. a file
+/// upload field). The filename, content type and body of the part are only compared if they
+/// are set (see [When::multipart_part](struct.When.html#method.multipart_part)).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MultipartPart {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub body: Option<String>,
+}
+
+impl MultipartPart {
+    /// Creates a requirement for a part with the given name.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            filename: None,
+            content_type: None,
+            body: None,
+        }
+    }
+
+    /// Sets the filename that the part must have.
+    pub fn filename<S: Into<String>>(mut self, filename: S) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Sets the content type that the part must have. Parameters such as the charset are
+    /// compared as well.
+    pub fn content_type<S: Into<String>>(mut self, content_type: S) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    /// Sets the content that the body of the part must be equal to.
+    pub fn body<S: Into<String>>(mut self, body: S) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+}
+
+impl From<&str> for MultipartPart {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for MultipartPart {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl fmt::Display for MultipartPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}'", self.name)?;
+        if let Some(filename) = &self.filename {
+            write!(f, " with filename {:?}", filename)?;
+        }
+        if let Some(content_type) = &self.content_type {
+            write!(f, " with content type {:?}", content_type)?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, " with body {}", body_preview(body.as_bytes()))?;
+        }
+        Ok(())
+    }
+}
+
+/// A directory on the file system of the mock server whose files are served for `GET`
+/// requests below a path prefix.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StaticDirectory {
+    pub path_prefix: String,
+    pub root: String,
+    /// The file that is served for request paths that end with a slash (e.g. `index.html`).
+    pub index_file: Option<String>,
+    /// The namespace that requests must be sent to.
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+/// A user provided function that decides whether a request matches a mock
+/// (see [When::matches](../../struct.When.html#method.matches)).
+pub type MockMatcherFunction = Arc<dyn Fn(&HttpMockRequest) -> bool + Send + Sync>;
+
+/// A reusable request matcher, e.g. one that verifies a request signature or a tenant header,
+/// that can be added to mocks with
+/// [When::custom_matcher](../../struct.When.html#method.custom_matcher). Unlike a function
+/// passed to [When::matches](../../struct.When.html#method.matches), a matcher describes what
+/// it expects, which is shown when a request does not match.
+pub trait Matcher: Send + Sync {
+    /// Returns `true` if the request matches.
+    fn matches(&self, req: &HttpMockRequest) -> bool;
+
+    /// Describes what the matcher expects, e.g. `a valid HMAC signature in X-Signature`.
+    fn describe(&self) -> String;
+}
+
+/// A [Matcher] that was added to a mock.
+#[derive(Clone)]
+pub struct CustomMatcher(pub Arc<dyn Matcher>);
+
+impl fmt::Display for CustomMatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.describe())
+    }
+}
 
 /// A general abstraction of an HTTP request for all handlers.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct RequestRequirements {
     pub path: Option<String>,
     pub path_contains: Option<Vec<String>>,
+    /// The prefixes that the path must start with.
+    #[serde(default)]
+    pub path_prefix: Option<Vec<String>>,
+    /// The suffixes that the path must end with.
+    #[serde(default)]
+    pub path_suffix: Option<Vec<String>>,
     pub path_matches: Option<Vec<Pattern>>,
+    /// The substrings that the path must not contain.
+    #[serde(default)]
+    pub path_not_contains: Option<Vec<String>>,
+    /// The regexes that the path must not match.
+    #[serde(default)]
+    pub path_not_matches: Option<Vec<Pattern>>,
+    /// Whether the path requirements are matched case-insensitively.
+    #[serde(default)]
+    pub path_case_insensitive: bool,
+    /// Whether a trailing slash of the request path and of the expected path is ignored.
+    #[serde(default)]
+    pub path_ignore_trailing_slash: bool,
     pub method: Option<String>,
+    /// The methods of which the request must use one.
+    #[serde(default)]
+    pub methods: Option<Vec<String>>,
     pub headers: Option<Vec<(String, String)>>,
     pub header_exists: Option<Vec<String>>,
+    /// The names of the headers that must not be present in the request.
+    #[serde(default)]
+    pub header_missing: Option<Vec<String>>,
+    #[serde(default)]
+    pub header_matches: Option<Vec<(String, Pattern)>>,
+    /// The headers that must have exactly the given values, in the given order.
+    #[serde(default)]
+    pub header_values: Option<Vec<HeaderValues>>,
+    /// The media type of the `Content-Type` header. Parameters that are not part of this value
+    /// (e.g. `charset`) are ignored.
+    #[serde(default)]
+    pub content_type: Option<String>,
+    /// Media types that the `Accept` header must accept, considering wildcards and q-values.
+    #[serde(default)]
+    pub accepts: Option<Vec<String>>,
+    /// The basic authentication credentials of the request as `username:password`.
+    #[serde(default)]
+    pub basic_auth: Option<String>,
+    /// The token that the request must send with bearer authentication.
+    #[serde(default)]
+    pub bearer_token: Option<String>,
+    /// The claims that the JWT bearer token of the request must contain.
+    #[serde(default)]
+    pub jwt_claims: Option<Vec<JwtClaim>>,
     pub cookies: Option<Vec<(String, String)>>,
     pub cookie_exists: Option<Vec<String>>,
     pub body: Option<String>,
+    /// The exact bytes of the request body, base64 encoded. Unlike `body`, binary bodies are
+    /// matched byte for byte.
+    #[serde(default)]
+    pub body_base64: Option<String>,
     pub json_body: Option<Value>,
     pub json_body_includes: Option<Vec<Value>>,
+    /// A JSON value with all fields that the JSON body may contain. Requests whose JSON body
+    /// contains other fields do not match.
+    #[serde(default)]
+    pub json_body_strict: Option<Value>,
+    #[serde(default)]
+    pub json_body_schema: Option<Value>,
+    /// The JSON body that the request body must be equal to, except for the ignored paths.
+    #[serde(default)]
+    pub json_body_ignoring_paths: Option<JsonBodyIgnoringPaths>,
+    #[serde(default)]
+    pub json_body_paths: Option<Vec<JsonPathRequirement>>,
+    /// The JSON values that lines of an NDJSON body must be equal to.
+    #[serde(default)]
+    pub ndjson_lines: Option<Vec<NdjsonLine>>,
+    /// The number of lines that an NDJSON body must have.
+    #[serde(default)]
+    pub ndjson_line_count: Option<usize>,
+    #[serde(default)]
+    pub body_xpaths: Option<Vec<XPathRequirement>>,
+    /// An XML document that the request body must be semantically equal to.
+    #[serde(default)]
+    pub xml_body: Option<String>,
+    #[serde(default)]
+    pub multipart_parts: Option<Vec<MultipartPart>>,
     pub body_contains: Option<Vec<String>>,
     pub body_matches: Option<Vec<Pattern>>,
+    /// The substrings that the body must not contain.
+    #[serde(default)]
+    pub body_not_contains: Option<Vec<String>>,
+    /// The regexes that the body must not match.
+    #[serde(default)]
+    pub body_not_matches: Option<Vec<Pattern>>,
+    /// The bounds of the length of the body in bytes.
+    #[serde(default)]
+    pub body_len: Option<BodyLength>,
+    /// Whether the body must be sent with chunked transfer encoding.
+    #[serde(default)]
+    pub chunked_body: bool,
+    /// The headers that must hold the HMAC-SHA256 signature of the body.
+    #[serde(default)]
+    pub hmac_signatures: Option<Vec<HmacSignature>>,
     pub query_param_exists: Option<Vec<String>>,
     pub query_param: Option<Vec<(String, String)>>,
+    #[serde(default)]
+    pub query_param_matches: Option<Vec<(String, Pattern)>>,
+    /// The names of the query parameters that must not be present in the request.
+    #[serde(default)]
+    pub query_param_missing: Option<Vec<String>>,
+    /// The query parameters that must have exactly the given values, in any order.
+    #[serde(default)]
+    pub query_param_values: Option<Vec<QueryParamValues>>,
+    /// The complete set of query parameters of the request.
+    #[serde(default)]
+    pub query_string: Option<QueryString>,
+    /// Whether query parameters are matched in the percent-encoded form that they were sent in.
+    #[serde(default)]
+    pub query_params_raw: bool,
+    /// Objects that the nested query parameters of the request (e.g. `filter[status]=open`)
+    /// must contain. All values are strings.
+    #[serde(default)]
+    pub query_objects: Option<Vec<Value>>,
     pub x_www_form_urlencoded_key_exists: Option<Vec<String>>,
     pub x_www_form_urlencoded: Option<Vec<(String, String)>>,
+    pub client_cert_cn: Option<String>,
+    pub http_version: Option<String>,
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default)]
+    pub listener: Option<String>,
+    /// The address that the request must be sent from (e.g. `127.0.0.1:52300`).
+    #[serde(default)]
+    pub remote_addr: Option<String>,
+    /// The regexes that the address the request was sent from must match.
+    #[serde(default)]
+    pub remote_addr_matches: Option<Vec<Pattern>>,
+    /// The base64 encoded protobuf message that is expected in the gRPC frame of the request
+    /// body.
+    #[serde(default)]
+    pub grpc_message: Option<String>,
+    /// The base64 encoded protobuf message that the request body must be equal to, regardless
+    /// of the order in which the fields are encoded.
+    #[serde(default)]
+    pub protobuf_body: Option<String>,
+    /// The name of the GraphQL operation that a GraphQL request must execute.
+    #[serde(default)]
+    pub graphql_operation: Option<String>,
+    /// The value that the `variables` field of a GraphQL request must be equal to.
+    #[serde(default)]
+    pub graphql_variables: Option<Value>,
+    /// The namespace that requests must be sent to. Mocks without a namespace only match
+    /// requests that were not sent to a namespace.
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// The path prefix that request paths must start with. The path requirements are matched
+    /// against the rest of the path.
+    #[serde(default)]
+    pub base_path: Option<String>,
+    /// Alternative requirements of which the request must fulfill at least one, in addition to
+    /// all other requirements.
+    #[serde(default)]
+    pub any_of: Option<Vec<RequestRequirements>>,
+    /// The point in time from which on the mock matches requests.
+    #[serde(default)]
+    pub active_from: Option<SystemTime>,
+    /// The point in time from which on the mock does not match requests anymore.
+    #[serde(default)]
+    pub active_until: Option<SystemTime>,
+    /// Whether the mock does not match any request until it is enabled.
+    #[serde(default)]
+    pub disabled: bool,
 
     #[serde(skip_serializing, skip_deserializing)]
     pub matchers: Option<Vec<MockMatcherFunction>>,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub custom_matchers: Option<Vec<CustomMatcher>>,
 }
 
 impl Default for RequestRequirements {
@@ -202,26 +1021,733 @@ impl Default for RequestRequirements {
 }
 
 impl RequestRequirements {
+    /// Returns whether matching a request against these requirements requires its body.
+    /// Custom matcher functions receive the whole request, so they might require it as well.
+    pub(crate) fn requires_body(&self) -> bool {
+        self.body.is_some()
+            || self.body_base64.is_some()
+            || self.json_body.is_some()
+            || self.json_body_includes.is_some()
+            || self.json_body_strict.is_some()
+            || self.json_body_schema.is_some()
+            || self.json_body_paths.is_some()
+            || self.ndjson_lines.is_some()
+            || self.ndjson_line_count.is_some()
+            || self.json_body_ignoring_paths.is_some()
+            || self.body_xpaths.is_some()
+            || self.xml_body.is_some()
+            || self.multipart_parts.is_some()
+            || self.body_contains.is_some()
+            || self.body_matches.is_some()
+            || self.body_not_contains.is_some()
+            || self.body_not_matches.is_some()
+            || self.body_len.is_some()
+            || self.hmac_signatures.is_some()
+            || self.x_www_form_urlencoded_key_exists.is_some()
+            || self.x_www_form_urlencoded.is_some()
+            || self.grpc_message.is_some()
+            || self.protobuf_body.is_some()
+            || self.graphql_operation.is_some()
+            || self.graphql_variables.is_some()
+            || self.matchers.is_some()
+            || self.custom_matchers.is_some()
+            || self.any_of.iter().flatten().any(|a| a.requires_body())
+    }
+
+    /// Returns a one-line description of every requirement, e.g. `header 'accept' equals
+    /// 'text/html'`.
+    pub(crate) fn summary(&self) -> Vec<String> {
+        fn pairs(lines: &mut Vec<String>, kind: &str, pairs: &Option<Vec<(String, String)>>) {
+            for (name, value) in pairs.iter().flatten() {
+                lines.push(format!("{} '{}' equals '{}'", kind, name, value));
+            }
+        }
+        fn exist(lines: &mut Vec<String>, kind: &str, names: &Option<Vec<String>>) {
+            for name in names.iter().flatten() {
+                lines.push(format!("{} '{}' exists", kind, name));
+            }
+        }
+        fn patterns(
+            lines: &mut Vec<String>,
+            kind: &str,
+            patterns: &Option<Vec<(String, Pattern)>>,
+        ) {
+            for (name, pattern) in patterns.iter().flatten() {
+                lines.push(format!(
+                    "{} '{}' matches regex '{}'",
+                    kind, name, pattern.regex
+                ));
+            }
+        }
+
+        let mut lines = Vec::new();
+        if let Some(method) = &self.method {
+            lines.push(format!("method equals {}", method));
+        }
+        if let Some(methods) = &self.methods {
+            lines.push(format!("method is one of {}", methods.join(", ")));
+        }
+        if let Some(base_path) = &self.base_path {
+            lines.push(format!("path starts with base path '{}'", base_path));
+        }
+        if let Some(path) = &self.path {
+            lines.push(format!("path equals '{}'", path));
+        }
+        for prefix in self.path_prefix.iter().flatten() {
+            lines.push(format!("path starts with '{}'", prefix));
+        }
+        for suffix in self.path_suffix.iter().flatten() {
+            lines.push(format!("path ends with '{}'", suffix));
+        }
+        for substring in self.path_contains.iter().flatten() {
+            lines.push(format!("path contains '{}'", substring));
+        }
+        for pattern in self.path_matches.iter().flatten() {
+            lines.push(format!("path matches regex '{}'", pattern.regex));
+        }
+        for substring in self.path_not_contains.iter().flatten() {
+            lines.push(format!("path does not contain '{}'", substring));
+        }
+        for pattern in self.path_not_matches.iter().flatten() {
+            lines.push(format!("path does not match regex '{}'", pattern.regex));
+        }
+        if self.path_case_insensitive {
+            lines.push("path is compared case-insensitively".to_string());
+        }
+        if self.path_ignore_trailing_slash {
+            lines.push("trailing slash of the path is ignored".to_string());
+        }
+        if let Some(version) = &self.http_version {
+            lines.push(format!("HTTP version equals {}", version));
+        }
+        if let Some(host) = &self.host {
+            lines.push(format!("host equals '{}'", host));
+        }
+        if let Some(listener) = &self.listener {
+            lines.push(format!("listener equals '{}'", listener));
+        }
+        if let Some(remote_addr) = &self.remote_addr {
+            lines.push(format!("remote address equals '{}'", remote_addr));
+        }
+        for pattern in self.remote_addr_matches.iter().flatten() {
+            lines.push(format!("remote address matches regex '{}'", pattern.regex));
+        }
+
+        pairs(&mut lines, "query parameter", &self.query_param);
+        exist(&mut lines, "query parameter", &self.query_param_exists);
+        patterns(&mut lines, "query parameter", &self.query_param_matches);
+        for name in self.query_param_missing.iter().flatten() {
+            lines.push(format!("query parameter '{}' is missing", name));
+        }
+        for values in self.query_param_values.iter().flatten() {
+            lines.push(format!("query parameter {} in any order", values));
+        }
+        if let Some(query) = &self.query_string {
+            lines.push(match query.ordered {
+                true => format!("query string equals {}", query),
+                false => format!("query string equals {} in any order", query),
+            });
+        }
+        if self.query_params_raw {
+            lines.push("query parameters are compared without decoding".to_string());
+        }
+        for object in self.query_objects.iter().flatten() {
+            lines.push(format!("query parameters contain the object {}", object));
+        }
+        pairs(&mut lines, "header", &self.headers);
+        exist(&mut lines, "header", &self.header_exists);
+        patterns(&mut lines, "header", &self.header_matches);
+        if let Some(content_type) = &self.content_type {
+            lines.push(format!("content type is '{}'", content_type));
+        }
+        for media_type in self.accepts.iter().flatten() {
+            lines.push(format!("accepts media type '{}'", media_type));
+        }
+        if let Some(credentials) = &self.basic_auth {
+            lines.push(format!("basic auth credentials equal '{}'", credentials));
+        }
+        if let Some(token) = &self.bearer_token {
+            lines.push(format!("bearer token equals '{}'", token));
+        }
+        for claim in self.jwt_claims.iter().flatten() {
+            lines.push(format!("JWT claim {}", claim));
+        }
+        for name in self.header_missing.iter().flatten() {
+            lines.push(format!("header '{}' is missing", name));
+        }
+        for values in self.header_values.iter().flatten() {
+            lines.push(format!("header {} in this order", values));
+        }
+        pairs(&mut lines, "cookie", &self.cookies);
+        exist(&mut lines, "cookie", &self.cookie_exists);
+        pairs(&mut lines, "form field", &self.x_www_form_urlencoded);
+        exist(
+            &mut lines,
+            "form field",
+            &self.x_www_form_urlencoded_key_exists,
+        );
+
+        if let Some(body) = &self.body {
+            lines.push(format!("body equals {}", body_preview(body.as_bytes())));
+        }
+        if let Some(body) = &self.body_base64 {
+            let bytes = base64::decode(body).unwrap_or_default();
+            lines.push(format!("body bytes equal {}", body_preview(&bytes)));
+        }
+        if let Some(len) = &self.body_len {
+            lines.push(format!("body length is {}", len));
+        }
+        if self.chunked_body {
+            lines.push("body is sent with chunked transfer encoding".to_string());
+        }
+        for signature in self.hmac_signatures.iter().flatten() {
+            lines.push(format!("header {}", signature));
+        }
+        for substring in self.body_contains.iter().flatten() {
+            lines.push(format!(
+                "body contains {}",
+                body_preview(substring.as_bytes())
+            ));
+        }
+        for pattern in self.body_matches.iter().flatten() {
+            lines.push(format!("body matches regex '{}'", pattern.regex));
+        }
+        for substring in self.body_not_contains.iter().flatten() {
+            lines.push(format!(
+                "body does not contain {}",
+                body_preview(substring.as_bytes())
+            ));
+        }
+        for pattern in self.body_not_matches.iter().flatten() {
+            lines.push(format!("body does not match regex '{}'", pattern.regex));
+        }
+        if let Some(json) = &self.json_body {
+            lines.push(format!("JSON body equals {}", json));
+        }
+        for json in self.json_body_includes.iter().flatten() {
+            lines.push(format!("JSON body includes {}", json));
+        }
+        if let Some(json) = &self.json_body_strict {
+            lines.push(format!("JSON body has no fields other than in {}", json));
+        }
+        if self.json_body_schema.is_some() {
+            lines.push("JSON body matches a JSON schema".to_string());
+        }
+        if let Some(requirement) = &self.json_body_ignoring_paths {
+            lines.push(format!(
+                "JSON body equals {} ignoring {}",
+                requirement,
+                requirement.ignored_paths.join(", ")
+            ));
+        }
+        for requirement in self.json_body_paths.iter().flatten() {
+            lines.push(format!("JSON body contains path {}", requirement));
+        }
+        for line in self.ndjson_lines.iter().flatten() {
+            lines.push(format!("NDJSON body {}", line));
+        }
+        if let Some(count) = self.ndjson_line_count {
+            lines.push(format!("NDJSON body has {} lines", count));
+        }
+        for requirement in self.body_xpaths.iter().flatten() {
+            lines.push(format!("XML body contains XPath {}", requirement));
+        }
+        if let Some(xml) = &self.xml_body {
+            lines.push(format!("XML body equals {}", body_preview(xml.as_bytes())));
+        }
+        for part in self.multipart_parts.iter().flatten() {
+            lines.push(format!("multipart body contains part {}", part));
+        }
+        if let Some(common_name) = &self.client_cert_cn {
+            lines.push(format!("client certificate CN equals '{}'", common_name));
+        }
+        if self.grpc_message.is_some() {
+            lines.push("gRPC message equals the expected message".to_string());
+        }
+        if self.protobuf_body.is_some() {
+            lines.push("protobuf body equals the expected message".to_string());
+        }
+        if let Some(operation) = &self.graphql_operation {
+            lines.push(format!("GraphQL operation equals '{}'", operation));
+        }
+        if let Some(variables) = &self.graphql_variables {
+            lines.push(format!("GraphQL variables equal {}", variables));
+        }
+        if let Some(matchers) = self.matchers.as_ref().filter(|m| !m.is_empty()) {
+            lines.push(format!("{} custom matcher function(s)", matchers.len()));
+        }
+        for matcher in self.custom_matchers.iter().flatten() {
+            lines.push(format!("custom matcher: {}", matcher));
+        }
+        if let Some(alternatives) = &self.any_of {
+            let alternatives: Vec<String> = alternatives
+                .iter()
+                .map(|alternative| match alternative.summary() {
+                    lines if lines.is_empty() => "(any request)".to_string(),
+                    lines => format!("({})", lines.join(" and ")),
+                })
+                .collect();
+            lines.push(format!("any of {}", alternatives.join(" or ")));
+        }
+        if self.disabled {
+            lines.push("mock is disabled".to_string());
+        }
+        if let Some(time) = self.active_from {
+            lines.push(format!("mock is active from {}", format_rfc3339(time)));
+        }
+        if let Some(time) = self.active_until {
+            lines.push(format!("mock is active until {}", format_rfc3339(time)));
+        }
+        lines
+    }
+
+    /// Returns whether the mock matches requests at the given point in time, i.e. whether it is
+    /// enabled and the time is within its activation window.
+    pub(crate) fn is_active(&self, now: SystemTime) -> bool {
+        !self.disabled
+            && self.active_from.map_or(true, |from| now >= from)
+            && self.active_until.map_or(true, |until| now < until)
+    }
+
+    /// Returns a description of every pair of requirements that no request can fulfill at the
+    /// same time, e.g. `path equals '/a' and path contains '/b'`. Exact paths and bodies are
+    /// compared case-insensitively by the mock server, so they only conflict with another
+    /// requirement if no letter case of them fulfills it.
+    pub(crate) fn conflicts(&self) -> Vec<String> {
+        fn contains(value: &str, substring: &str) -> bool {
+            value.to_lowercase().contains(&substring.to_lowercase())
+        }
+        #[cfg(feature = "regex")]
+        fn is_match(value: &str, pattern: &Pattern) -> bool {
+            regex::RegexBuilder::new(pattern.regex.as_str())
+                .case_insensitive(true)
+                .build()
+                .map_or(true, |regex| regex.is_match(value))
+        }
+        #[cfg(not(feature = "regex"))]
+        fn is_match(_value: &str, _pattern: &Pattern) -> bool {
+            true
+        }
+        fn lowercase_json(value: &str) -> Option<Value> {
+            serde_json::from_str(&value.to_lowercase()).ok()
+        }
+        fn includes(actual: &Value, expected: &Value) -> bool {
+            let config = Config::new(CompareMode::Inclusive);
+            assert_json_matches_no_panic(actual, expected, config).is_ok()
+        }
+
+        let mut conflicts = Vec::new();
+
+        if let (Some(method), Some(methods)) = (&self.method, &self.methods) {
+            if !methods.contains(method) {
+                conflicts.push(format!(
+                    "method equals {} and method is one of {}",
+                    method,
+                    methods.join(", ")
+                ));
+            }
+        }
+
+        // Path requirements are compared like the mock server compares them.
+        let paths = self.with_normalized_paths();
+        if let Some(path) = &paths.path {
+            for prefix in paths.path_prefix.iter().flatten() {
+                if !path.starts_with(prefix.as_str()) {
+                    conflicts.push(format!(
+                        "path equals '{}' and path starts with '{}'",
+                        path, prefix
+                    ));
+                }
+            }
+            for suffix in paths.path_suffix.iter().flatten() {
+                if !path.ends_with(suffix.as_str()) {
+                    conflicts.push(format!(
+                        "path equals '{}' and path ends with '{}'",
+                        path, suffix
+                    ));
+                }
+            }
+            for substring in paths.path_contains.iter().flatten() {
+                if !contains(path, substring) {
+                    conflicts.push(format!(
+                        "path equals '{}' and path contains '{}'",
+                        path, substring
+                    ));
+                }
+            }
+            for pattern in paths.path_matches.iter().flatten() {
+                if !is_match(path, pattern) {
+                    conflicts.push(format!(
+                        "path equals '{}' and path matches regex '{}'",
+                        path, pattern.regex
+                    ));
+                }
+            }
+        }
+
+        if let Some(body) = &self.body {
+            let preview = body_preview(body.as_bytes());
+            if let Some(len) = self.body_len.as_ref().filter(|l| !l.contains(body.len())) {
+                conflicts.push(format!(
+                    "body equals {} and body length is {}",
+                    preview, len
+                ));
+            }
+            for substring in self.body_contains.iter().flatten() {
+                if !contains(body, substring) {
+                    conflicts.push(format!(
+                        "body equals {} and body contains {}",
+                        preview,
+                        body_preview(substring.as_bytes())
+                    ));
+                }
+            }
+            for pattern in self.body_matches.iter().flatten() {
+                if !is_match(body, pattern) {
+                    conflicts.push(format!(
+                        "body equals {} and body matches regex '{}'",
+                        preview, pattern.regex
+                    ));
+                }
+            }
+
+            let json = lowercase_json(body);
+            if let Some(expected) = &self.json_body {
+                if json.is_none() || json != lowercase_json(&expected.to_string()) {
+                    conflicts.push(format!(
+                        "body equals {} and JSON body equals {}",
+                        preview, expected
+                    ));
+                }
+            }
+            for expected in self.json_body_includes.iter().flatten() {
+                let included = match (&json, lowercase_json(&expected.to_string())) {
+                    (Some(json), Some(expected)) => includes(json, &expected),
+                    _ => false,
+                };
+                if !included {
+                    conflicts.push(format!(
+                        "body equals {} and JSON body includes {}",
+                        preview, expected
+                    ));
+                }
+            }
+        }
+
+        // Substrings are matched case-sensitively, so a required substring that contains an
+        // excluded one always conflicts with it.
+        let required_path_parts = [
+            ("path contains", &self.path_contains),
+            ("path starts with", &self.path_prefix),
+            ("path ends with", &self.path_suffix),
+        ];
+        for excluded in self.path_not_contains.iter().flatten() {
+            for (requirement, values) in required_path_parts.iter() {
+                for value in values.iter().flatten() {
+                    if value.contains(excluded.as_str()) {
+                        conflicts.push(format!(
+                            "{} '{}' and path does not contain '{}'",
+                            requirement, value, excluded
+                        ));
+                    }
+                }
+            }
+        }
+        for excluded in self.body_not_contains.iter().flatten() {
+            for substring in self.body_contains.iter().flatten() {
+                if substring.contains(excluded.as_str()) {
+                    conflicts.push(format!(
+                        "body contains {} and body does not contain {}",
+                        body_preview(substring.as_bytes()),
+                        body_preview(excluded.as_bytes())
+                    ));
+                }
+            }
+        }
+
+        for (i, line) in self.ndjson_lines.iter().flatten().enumerate() {
+            if let Some(count) = self.ndjson_line_count.filter(|count| line.index >= *count) {
+                conflicts.push(format!(
+                    "NDJSON body has {} lines and NDJSON body {}",
+                    count, line
+                ));
+            }
+            for other in self.ndjson_lines.iter().flatten().skip(i + 1) {
+                if other.index == line.index && other.value != line.value {
+                    conflicts.push(format!("NDJSON body {} and {}", line, other));
+                }
+            }
+        }
+
+        if let Some(json) = &self.json_body {
+            for expected in self.json_body_includes.iter().flatten() {
+                if !includes(json, expected) {
+                    conflicts.push(format!(
+                        "JSON body equals {} and JSON body includes {}",
+                        json, expected
+                    ));
+                }
+            }
+        }
+
+        for name in self.query_param_missing.iter().flatten() {
+            let required = self
+                .query_param
+                .iter()
+                .flatten()
+                .map(|(n, _)| n)
+                .chain(self.query_param_exists.iter().flatten())
+                .chain(self.query_param_matches.iter().flatten().map(|(n, _)| n))
+                .chain(self.query_param_values.iter().flatten().map(|v| &v.name));
+            if required.into_iter().any(|n| n == name) {
+                conflicts.push(format!(
+                    "query parameter '{}' exists and query parameter '{}' is missing",
+                    name, name
+                ));
+            }
+        }
+
+        let query_param_values: Vec<&QueryParamValues> =
+            self.query_param_values.iter().flatten().collect();
+        for (idx, values) in query_param_values.iter().enumerate() {
+            for (name, value) in self.query_param.iter().flatten() {
+                if *name == values.name && !values.values.contains(value) {
+                    conflicts.push(format!(
+                        "query parameter '{}' equals '{}' and query parameter {}",
+                        name, value, values
+                    ));
+                }
+            }
+            for other in &query_param_values[idx + 1..] {
+                if other.name == values.name && other.sorted_values() != values.sorted_values() {
+                    conflicts.push(format!(
+                        "query parameter {} and query parameter {}",
+                        values, other
+                    ));
+                }
+            }
+        }
+
+        if let Some(query) = &self.query_string {
+            let has_param = |name: &str| query.params.iter().any(|(n, _)| n == name);
+            for (name, value) in self.query_param.iter().flatten() {
+                if !query.params.contains(&(name.clone(), value.clone())) {
+                    conflicts.push(format!(
+                        "query parameter '{}' equals '{}' and query string equals {}",
+                        name, value, query
+                    ));
+                }
+            }
+            for name in self.query_param_exists.iter().flatten() {
+                if !has_param(name) {
+                    conflicts.push(format!(
+                        "query parameter '{}' exists and query string equals {}",
+                        name, query
+                    ));
+                }
+            }
+            for name in self.query_param_missing.iter().flatten() {
+                if has_param(name) {
+                    conflicts.push(format!(
+                        "query parameter '{}' is missing and query string equals {}",
+                        name, query
+                    ));
+                }
+            }
+        }
+
+        for name in self.header_missing.iter().flatten() {
+            let required = self
+                .headers
+                .iter()
+                .flatten()
+                .map(|(n, _)| n)
+                .chain(self.header_exists.iter().flatten())
+                .chain(self.header_matches.iter().flatten().map(|(n, _)| n))
+                .chain(self.header_values.iter().flatten().map(|v| &v.name))
+                .chain(self.hmac_signatures.iter().flatten().map(|s| &s.header));
+            if required.into_iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                conflicts.push(format!(
+                    "header '{}' exists and header '{}' is missing",
+                    name, name
+                ));
+            }
+            if let Some(content_type) = &self.content_type {
+                if name.eq_ignore_ascii_case("content-type") {
+                    conflicts.push(format!(
+                        "content type is '{}' and header '{}' is missing",
+                        content_type, name
+                    ));
+                }
+            }
+            if let Some(credentials) = &self.basic_auth {
+                if name.eq_ignore_ascii_case("authorization") {
+                    conflicts.push(format!(
+                        "basic auth credentials equal '{}' and header '{}' is missing",
+                        credentials, name
+                    ));
+                }
+            }
+        }
+
+        if let Some(credentials) = &self.basic_auth {
+            if self.bearer_token.is_some() || self.jwt_claims.is_some() {
+                conflicts.push(format!(
+                    "basic auth credentials equal '{}' and a bearer token is required",
+                    credentials
+                ));
+            }
+        }
+
+        if let (Some(from), Some(until)) = (self.active_from, self.active_until) {
+            if from >= until {
+                conflicts.push(format!(
+                    "mock is active from {} and mock is active until {}",
+                    format_rfc3339(from),
+                    format_rfc3339(until)
+                ));
+            }
+        }
+
+        conflicts
+    }
+
+    /// Normalizes a request path as requested by these requirements, i.e. removes a trailing
+    /// slash (except from the root path) and converts it to lowercase.
+    pub(crate) fn normalize_path(&self, path: &str) -> String {
+        let path = match self.path_ignore_trailing_slash && path.len() > 1 {
+            true => path.strip_suffix('/').unwrap_or(path),
+            false => path,
+        };
+        match self.path_case_insensitive {
+            true => path.to_lowercase(),
+            false => path.to_string(),
+        }
+    }
+
+    /// Returns these requirements with path requirements that can be matched against paths
+    /// normalized by [normalize_path](#method.normalize_path). The requirements are only copied
+    /// if paths need to be normalized.
+    pub(crate) fn with_normalized_paths(&self) -> Cow<'_, RequestRequirements> {
+        if !self.path_case_insensitive && !self.path_ignore_trailing_slash {
+            return Cow::Borrowed(self);
+        }
+        let mut normalized = self.clone();
+        normalized.normalize_path_requirements(self);
+        Cow::Owned(normalized)
+    }
+
+    /// Normalizes the path requirements of these requirements and their alternatives with the
+    /// path options of `options`.
+    fn normalize_path_requirements(&mut self, options: &RequestRequirements) {
+        let lowercase = |values: &mut Option<Vec<String>>| {
+            if options.path_case_insensitive {
+                for value in values.iter_mut().flatten() {
+                    *value = value.to_lowercase();
+                }
+            }
+        };
+        self.path = self
+            .path
+            .as_deref()
+            .map(|path| options.normalize_path(path));
+        lowercase(&mut self.path_contains);
+        lowercase(&mut self.path_prefix);
+        lowercase(&mut self.path_suffix);
+        lowercase(&mut self.path_not_contains);
+        #[cfg(feature = "regex")]
+        if options.path_case_insensitive {
+            let case_insensitive = |patterns: &mut Option<Vec<Pattern>>| {
+                for pattern in patterns.iter_mut().flatten() {
+                    if let Ok(regex) = regex::RegexBuilder::new(pattern.regex.as_str())
+                        .case_insensitive(true)
+                        .build()
+                    {
+                        pattern.regex = regex;
+                    }
+                }
+            };
+            case_insensitive(&mut self.path_matches);
+            case_insensitive(&mut self.path_not_matches);
+        }
+        for alternative in self.any_of.iter_mut().flatten() {
+            alternative.normalize_path_requirements(options);
+        }
+    }
+
     pub fn new() -> Self {
         Self {
             path: None,
             path_contains: None,
+            path_prefix: None,
+            path_suffix: None,
             path_matches: None,
+            path_not_contains: None,
+            path_not_matches: None,
+            path_case_insensitive: false,
+            path_ignore_trailing_slash: false,
             method: None,
+            methods: None,
             headers: None,
             header_exists: None,
+            header_missing: None,
+            header_values: None,
+            content_type: None,
+            accepts: None,
+            basic_auth: None,
+            bearer_token: None,
+            jwt_claims: None,
+            header_matches: None,
             cookies: None,
             cookie_exists: None,
             body: None,
             json_body: None,
             json_body_includes: None,
+            json_body_strict: None,
+            json_body_schema: None,
+            json_body_ignoring_paths: None,
+            json_body_paths: None,
+            ndjson_lines: None,
+            ndjson_line_count: None,
+            body_xpaths: None,
+            xml_body: None,
+            multipart_parts: None,
+            body_base64: None,
             body_contains: None,
             body_matches: None,
+            body_not_contains: None,
+            body_not_matches: None,
+            body_len: None,
+            chunked_body: false,
+            hmac_signatures: None,
             query_param_exists: None,
             query_param: None,
+            query_param_matches: None,
+            query_param_missing: None,
+            query_param_values: None,
+            query_string: None,
+            query_params_raw: false,
+            query_objects: None,
             x_www_form_urlencoded: None,
             x_www_form_urlencoded_key_exists: None,
+            client_cert_cn: None,
+            http_version: None,
+            host: None,
+            listener: None,
+            remote_addr: None,
+            remote_addr_matches: None,
+            grpc_message: None,
+            protobuf_body: None,
+            graphql_operation: None,
+            graphql_variables: None,
+            namespace: None,
+            base_path: None,
+            any_of: None,
+            active_from: None,
+            active_until: None,
+            disabled: false,
             matchers: None,
+            custom_matchers: None,
         }
     }
 
@@ -235,11 +1761,26 @@ impl RequestRequirements {
         self
     }
 
+    pub fn with_methods(mut self, arg: Vec<String>) -> Self {
+        self.methods = Some(arg);
+        self
+    }
+
+    pub fn with_any_of(mut self, arg: Vec<RequestRequirements>) -> Self {
+        self.any_of = Some(arg);
+        self
+    }
+
     pub fn with_body(mut self, arg: String) -> Self {
         self.body = Some(arg);
         self
     }
 
+    pub fn with_body_base64(mut self, arg: String) -> Self {
+        self.body_base64 = Some(arg);
+        self
+    }
+
     pub fn with_json_body(mut self, arg: Value) -> Self {
         self.json_body = Some(arg);
         self
@@ -250,11 +1791,31 @@ impl RequestRequirements {
         self
     }
 
+    pub fn with_path_prefix(mut self, arg: Vec<String>) -> Self {
+        self.path_prefix = Some(arg);
+        self
+    }
+
+    pub fn with_path_suffix(mut self, arg: Vec<String>) -> Self {
+        self.path_suffix = Some(arg);
+        self
+    }
+
     pub fn with_path_matches(mut self, arg: Vec<Pattern>) -> Self {
         self.path_matches = Some(arg);
         self
     }
 
+    pub fn with_path_not_contains(mut self, arg: Vec<String>) -> Self {
+        self.path_not_contains = Some(arg);
+        self
+    }
+
+    pub fn with_path_not_matches(mut self, arg: Vec<Pattern>) -> Self {
+        self.path_not_matches = Some(arg);
+        self
+    }
+
     pub fn with_headers(mut self, arg: Vec<(String, String)>) -> Self {
         self.headers = Some(arg);
         self
@@ -265,6 +1826,31 @@ impl RequestRequirements {
         self
     }
 
+    pub fn with_header_missing(mut self, arg: Vec<String>) -> Self {
+        self.header_missing = Some(arg);
+        self
+    }
+
+    pub fn with_basic_auth(mut self, arg: String) -> Self {
+        self.basic_auth = Some(arg);
+        self
+    }
+
+    pub fn with_content_type(mut self, arg: String) -> Self {
+        self.content_type = Some(arg);
+        self
+    }
+
+    pub fn with_bearer_token(mut self, arg: String) -> Self {
+        self.bearer_token = Some(arg);
+        self
+    }
+
+    pub fn with_jwt_claims(mut self, arg: Vec<JwtClaim>) -> Self {
+        self.jwt_claims = Some(arg);
+        self
+    }
+
     pub fn with_cookies(mut self, arg: Vec<(String, String)>) -> Self {
         self.cookies = Some(arg);
         self
@@ -280,6 +1866,21 @@ impl RequestRequirements {
         self
     }
 
+    pub fn with_json_body_strict(mut self, arg: Value) -> Self {
+        self.json_body_strict = Some(arg);
+        self
+    }
+
+    pub fn with_json_body_ignoring_paths(mut self, arg: JsonBodyIgnoringPaths) -> Self {
+        self.json_body_ignoring_paths = Some(arg);
+        self
+    }
+
+    pub fn with_json_body_schema(mut self, arg: Value) -> Self {
+        self.json_body_schema = Some(arg);
+        self
+    }
+
     pub fn with_body_contains(mut self, arg: Vec<String>) -> Self {
         self.body_contains = Some(arg);
         self
@@ -290,6 +1891,16 @@ impl RequestRequirements {
         self
     }
 
+    pub fn with_body_not_contains(mut self, arg: Vec<String>) -> Self {
+        self.body_not_contains = Some(arg);
+        self
+    }
+
+    pub fn with_body_not_matches(mut self, arg: Vec<Pattern>) -> Self {
+        self.body_not_matches = Some(arg);
+        self
+    }
+
     pub fn with_query_param_exists(mut self, arg: Vec<String>) -> Self {
         self.query_param_exists = Some(arg);
         self
@@ -299,6 +1910,31 @@ impl RequestRequirements {
         self.query_param = Some(arg);
         self
     }
+
+    pub fn with_query_param_missing(mut self, arg: Vec<String>) -> Self {
+        self.query_param_missing = Some(arg);
+        self
+    }
+
+    pub fn with_query_param_values(mut self, arg: Vec<QueryParamValues>) -> Self {
+        self.query_param_values = Some(arg);
+        self
+    }
+
+    pub fn with_query_string(mut self, arg: QueryString) -> Self {
+        self.query_string = Some(arg);
+        self
+    }
+
+    pub fn with_graphql_operation(mut self, arg: String) -> Self {
+        self.graphql_operation = Some(arg);
+        self
+    }
+
+    pub fn with_graphql_variables(mut self, arg: Value) -> Self {
+        self.graphql_variables = Some(arg);
+        self
+    }
 }
 
 /// A Request that is made to set a new mock.
@@ -328,25 +1964,121 @@ impl MockRef {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+/// A request that was forwarded to an upstream server along with the upstream response.
+#[derive(Clone)]
+pub(crate) struct RecordedExchange {
+    pub request: Arc<HttpMockRequest>,
+    pub response: MockServerHttpResponse,
+}
+
+/// A request that the mock server received along with the response that it sent, which is kept
+/// to export the traffic as a HAR file.
+pub(crate) struct TrafficEntry {
+    pub started: SystemTime,
+    pub request: Arc<HttpMockRequest>,
+    /// The response, which is only complete once a streamed response body has been sent.
+    pub response: Mutex<TrafficResponse>,
+}
+
+#[derive(Default)]
+pub(crate) struct TrafficResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// The time from receiving the request until the response was ready to be sent.
+    pub wait: Duration,
+    /// The time that it took to send a streamed response body.
+    pub receive: Duration,
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct ActiveMock {
     pub id: usize,
-    pub call_counter: usize,
+    /// The number of matching requests. This is an atomic so that it can be incremented while
+    /// the mocks are only locked for reading.
+    pub call_counter: AtomicUsize,
     pub definition: MockDefinition,
     pub is_static: bool,
+    /// The request requirements of the definition with normalized path requirements (see
+    /// [RequestRequirements::with_normalized_paths]), if the mock requires paths to be
+    /// normalized. They are created once, so that they are not copied for every request.
+    #[serde(skip)]
+    normalized_request: Option<RequestRequirements>,
 }
 
 impl ActiveMock {
     pub fn new(id: usize, mock_definition: MockDefinition, is_static: bool) -> Self {
+        let normalized_request = match mock_definition.request.with_normalized_paths() {
+            Cow::Owned(normalized) => Some(normalized),
+            Cow::Borrowed(_) => None,
+        };
         ActiveMock {
             id,
             definition: mock_definition,
-            call_counter: 0,
+            call_counter: AtomicUsize::new(0),
             is_static,
+            normalized_request,
+        }
+    }
+
+    /// Returns the request requirements that requests are matched against, i.e. the request
+    /// requirements of the definition with normalized path requirements.
+    pub(crate) fn matched_request(&self) -> &RequestRequirements {
+        self.normalized_request
+            .as_ref()
+            .unwrap_or(&self.definition.request)
+    }
+}
+
+impl Clone for ActiveMock {
+    fn clone(&self) -> Self {
+        ActiveMock {
+            id: self.id,
+            definition: self.definition.clone(),
+            call_counter: AtomicUsize::new(self.call_counter.load(Relaxed)),
+            is_static: self.is_static,
+            normalized_request: self.normalized_request.clone(),
         }
     }
 }
 
+/// Statistics about the requests that a mock server handled since it was started or last
+/// reset. Please refer to [MockServer::stats](struct.MockServer.html#method.stats).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ServerStats {
+    /// The number of requests that were served, excluding admin API requests.
+    pub total_requests: u64,
+    /// The number of requests that did not match any mock.
+    pub unmatched_requests: u64,
+    /// The number of request body bytes that were read into memory, excluding admin API
+    /// requests. Bodies are only read completely if a mock or the server configuration
+    /// requires it.
+    #[serde(default)]
+    pub buffered_body_bytes: u64,
+    /// The number of requests that matched each mock, by mock ID.
+    pub mock_hits: BTreeMap<usize, u64>,
+    /// The number of responses that were sent, by status code.
+    pub status_counts: BTreeMap<u16, u64>,
+    /// The time it took to handle requests.
+    pub latency: LatencyStats,
+}
+
+/// The time it took the mock server to handle requests, from receiving the request to sending
+/// the response header. Configured response delays are not included. Percentiles are
+/// approximated with an error of less than 20%.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LatencyStats {
+    /// The number of requests that were measured.
+    pub count: u64,
+    /// The sum of all latencies.
+    pub total: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ClosestMatch {
     pub request: HttpMockRequest,
@@ -408,18 +2140,70 @@ pub struct Mismatch {
     pub title: String,
     pub reason: Option<Reason>,
     pub diff: Option<DiffResult>,
+    /// The stage in which the requirement was checked that the request did not meet.
+    #[serde(default)]
+    pub stage: Option<MatchStage>,
+}
+
+/// The stages in which the requirements of a mock are checked, from the cheapest to the most
+/// expensive one. Matching a request against a mock stops at the first requirement that the
+/// request does not meet, so that e.g. the body of a request is not parsed for mocks that
+/// require another path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MatchStage {
+    Method,
+    Path,
+    Query,
+    /// The headers, including cookies, and the properties of the connection (HTTP version,
+    /// host, listener, remote address and client certificate).
+    Headers,
+    Body,
+}
+
+impl fmt::Display for MatchStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MatchStage::Method => "method",
+            MatchStage::Path => "path",
+            MatchStage::Query => "query",
+            MatchStage::Headers => "headers",
+            MatchStage::Body => "body",
+        };
+        f.write_str(name)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use std::collections::BTreeMap;
 
+    #[cfg(feature = "regex")]
     use regex::Regex;
     use serde_json::json;
 
-    use crate::common::data::{Pattern, RequestRequirements};
+    use crate::common::data::{
+        body_preview, HttpMockRequest, Pattern, QueryParamValues, QueryString, RequestRequirements,
+    };
+
+    /// Patterns are serialized as the plain source of the regex, so that the format does not
+    /// depend on whether the `regex` feature is enabled.
+    #[cfg(feature = "regex")]
+    #[test]
+    fn pattern_serialization_test() {
+        // Arrange
+        let pattern = Pattern::from_regex(Regex::new(r"^/users/\d+$").unwrap());
+
+        // Act
+        let value = serde_json::to_value(&pattern).unwrap();
+        let parsed: Pattern = serde_json::from_value(value.clone()).unwrap();
+
+        // Assert
+        assert_eq!(value, json!({ "regex": r"^/users/\d+$" }));
+        assert_eq!(parsed, pattern);
+    }
 
     /// This test makes sure that adding the matching rules to a mock fills the struct as expected.
+    #[cfg(feature = "regex")]
     #[test]
     fn fill_mock_requirements() {
         // Arrange
@@ -494,4 +2278,181 @@ mod test {
             &with_header_exists.clone()
         );
     }
+
+    #[test]
+    fn body_preview_test() {
+        assert_eq!(body_preview(b"{\"id\":1}"), r#""{\"id\":1}""#);
+        assert_eq!(
+            body_preview("a".repeat(201).as_bytes()),
+            format!("{:?}... (201 bytes)", "a".repeat(200))
+        );
+        assert_eq!(
+            body_preview(&[0x89, 0x50, 0x4e, 0x47, 0xff]),
+            "<5 bytes of binary data: 89 50 4e 47 ff>"
+        );
+        assert_eq!(
+            body_preview(&[0xff; 17]),
+            format!(
+                "<17 bytes of binary data: {} ...>",
+                vec!["ff"; 16].join(" ")
+            )
+        );
+    }
+
+    #[test]
+    fn request_display_test() {
+        // Arrange
+        let request = HttpMockRequest::new("POST".to_string(), "/users".to_string())
+            .with_query_params(vec![("dry run".to_string(), "true".to_string())])
+            .with_headers(vec![(
+                "content-type".to_string(),
+                "application/json".to_string(),
+            )])
+            .with_body(b"{\"name\":\"Fred\"}".to_vec())
+            .with_version("HTTP/1.1".to_string());
+
+        // Act
+        let output = request.to_string();
+
+        // Assert
+        assert_eq!(
+            output,
+            "POST /users?dry+run=true HTTP/1.1\n  \
+             content-type: application/json\n  \
+             body: \"{\\\"name\\\":\\\"Fred\\\"}\""
+        );
+        assert!(format!("{:?}", request).contains(r#"body: Some("{\"name\":\"Fred\"}")"#));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn requirements_summary_test() {
+        // Arrange
+        let mut requirements = RequestRequirements::new()
+            .with_method("GET".to_string())
+            .with_path("/search".to_string());
+        requirements.query_param = Some(vec![("q".to_string(), "rust".to_string())]);
+        requirements.query_string = Some(QueryString::parse("q=rust", false));
+        requirements.header_exists = Some(vec!["authorization".to_string()]);
+        requirements.path_matches = Some(vec![Pattern::from_regex(Regex::new("^/s").unwrap())]);
+        requirements.path_not_contains = Some(vec!["v1".to_string()]);
+        requirements.json_body = Some(json!({ "id": 1 }));
+
+        // Act
+        let summary = requirements.summary();
+
+        // Assert
+        assert_eq!(
+            summary,
+            vec![
+                "method equals GET",
+                "path equals '/search'",
+                "path matches regex '^/s'",
+                "path does not contain 'v1'",
+                "query parameter 'q' equals 'rust'",
+                "query string equals 'q=rust' in any order",
+                "header 'authorization' exists",
+                "JSON body equals {\"id\":1}",
+            ]
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn requirements_conflicts_test() {
+        // Arrange
+        let mut conflicting = RequestRequirements::new()
+            .with_path("/users".to_string())
+            .with_method("POST".to_string())
+            .with_methods(vec!["GET".to_string(), "HEAD".to_string()]);
+        conflicting.path_contains = Some(vec!["orders".to_string()]);
+        conflicting.path_prefix = Some(vec!["/api".to_string()]);
+        conflicting.path_suffix = Some(vec!["/users".to_string()]);
+        conflicting.path_not_contains = Some(vec!["user".to_string()]);
+        conflicting.body = Some("{}".to_string());
+        conflicting.body_contains = Some(vec!["\"legacy\": true".to_string()]);
+        conflicting.body_not_contains = Some(vec!["legacy".to_string()]);
+        conflicting.json_body = Some(json!({ "id": 1 }));
+        conflicting.json_body_includes = Some(vec![json!({ "id": 2 })]);
+        conflicting.query_param_exists = Some(vec!["debug".to_string()]);
+        conflicting.query_param_missing = Some(vec!["debug".to_string()]);
+        conflicting.query_param = Some(vec![("id".to_string(), "3".to_string())]);
+        conflicting.query_param_values = Some(vec![
+            QueryParamValues {
+                name: "id".to_string(),
+                values: vec!["1".to_string(), "2".to_string()],
+            },
+            QueryParamValues {
+                name: "id".to_string(),
+                values: vec!["2".to_string()],
+            },
+        ]);
+        conflicting.query_string = Some(QueryString::parse("?id=1&id=2&debug", false));
+        conflicting.headers = Some(vec![("X-Api-Key".to_string(), "secret".to_string())]);
+        conflicting.header_missing =
+            Some(vec!["x-api-key".to_string(), "Content-Type".to_string()]);
+        conflicting.content_type = Some("application/json".to_string());
+        conflicting.basic_auth = Some("alice:secret".to_string());
+        conflicting.bearer_token = Some("abc123".to_string());
+
+        let mut consistent = RequestRequirements::new()
+            .with_path("/Users/1".to_string())
+            .with_method("GET".to_string())
+            .with_methods(vec!["GET".to_string(), "HEAD".to_string()]);
+        consistent.path_contains = Some(vec!["users".to_string()]);
+        consistent.path_prefix = Some(vec!["/Users/".to_string()]);
+        consistent.path_suffix = Some(vec!["1".to_string()]);
+        consistent.path_matches = Some(vec![Pattern::from_regex(
+            Regex::new("^/users/[0-9]+$").unwrap(),
+        )]);
+        consistent.path_not_contains = Some(vec!["orders".to_string()]);
+        consistent.body = Some(r#"{ "id": 1, "name": "Fred" }"#.to_string());
+        consistent.body_not_contains = Some(vec!["legacy".to_string()]);
+        consistent.json_body = Some(json!({ "id": 1, "name": "Fred" }));
+        consistent.json_body_includes = Some(vec![json!({ "name": "Fred" })]);
+        consistent.query_param_exists = Some(vec!["page".to_string()]);
+        consistent.query_param_missing = Some(vec!["debug".to_string()]);
+        consistent.query_param = Some(vec![("id".to_string(), "2".to_string())]);
+        consistent.query_param_values = Some(vec![
+            QueryParamValues {
+                name: "id".to_string(),
+                values: vec!["1".to_string(), "2".to_string()],
+            },
+            QueryParamValues {
+                name: "id".to_string(),
+                values: vec!["2".to_string(), "1".to_string()],
+            },
+        ]);
+        consistent.query_string = Some(QueryString::parse("page=1&id=2&id=1", true));
+        consistent.header_missing = Some(vec!["x-api-key".to_string()]);
+
+        // Act
+        let conflicts = conflicting.conflicts();
+
+        // Assert
+        assert_eq!(
+            conflicts,
+            vec![
+                "method equals POST and method is one of GET, HEAD",
+                "path equals '/users' and path starts with '/api'",
+                "path equals '/users' and path contains 'orders'",
+                "body equals \"{}\" and body contains \"\\\"legacy\\\": true\"",
+                "body equals \"{}\" and JSON body equals {\"id\":1}",
+                "body equals \"{}\" and JSON body includes {\"id\":2}",
+                "path ends with '/users' and path does not contain 'user'",
+                "body contains \"\\\"legacy\\\": true\" and body does not contain \"legacy\"",
+                "JSON body equals {\"id\":1} and JSON body includes {\"id\":2}",
+                "query parameter 'debug' exists and query parameter 'debug' is missing",
+                "query parameter 'id' equals '3' and query parameter 'id' has the values '1', '2'",
+                "query parameter 'id' has the values '1', '2' and query parameter 'id' has the values '2'",
+                "query parameter 'id' equals '3' and query parameter 'id' has the values '2'",
+                "query parameter 'id' equals '3' and query string equals 'id=1&id=2&debug='",
+                "query parameter 'debug' is missing and query string equals 'id=1&id=2&debug='",
+                "header 'x-api-key' exists and header 'x-api-key' is missing",
+                "content type is 'application/json' and header 'Content-Type' is missing",
+                "basic auth credentials equal 'alice:secret' and a bearer token is required",
+            ]
+        );
+        assert!(consistent.conflicts().is_empty());
+    }
 }
@@ -0,0 +1,597 @@
+//! Support for mocking GraphQL APIs: validating response data against a schema in the
+//! GraphQL schema definition language (SDL) and building spec-compliant response bodies.
+
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::path::Path;
+
+use async_graphql_parser::types::{
+    BaseType, FieldDefinition, OperationType, Type, TypeKind, TypeSystemDefinition,
+};
+use async_graphql_parser::Positioned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::common::util::read_file;
+
+/// An error object of a GraphQL response as defined in the
+/// [GraphQL specification](https://spec.graphql.org/October2021/#sec-Errors). Please refer to
+/// [Then::graphql_errors](struct.Then.html#method.graphql_errors).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GraphQLError {
+    message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<GraphQLErrorLocation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    path: Option<Vec<Value>>,
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    extensions: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct GraphQLErrorLocation {
+    line: usize,
+    column: usize,
+}
+
+impl GraphQLError {
+    /// Creates an error with the provided message.
+    pub fn new<S: Into<String>>(message: S) -> Self {
+        Self {
+            message: message.into(),
+            locations: Vec::new(),
+            path: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Sets the path of the response field that the error belongs to. Segments are separated
+    /// by dots and numeric segments are list indices (e.g. `user.friends.0.name`).
+    pub fn path<S: Into<String>>(mut self, path: S) -> Self {
+        let segments = path
+            .into()
+            .split('.')
+            .map(|segment| match segment.parse::<u64>() {
+                Ok(index) => Value::from(index),
+                Err(_) => Value::from(segment),
+            })
+            .collect();
+        self.path = Some(segments);
+        self
+    }
+
+    /// Adds a location in the GraphQL document that the error belongs to. Lines and columns
+    /// start at 1.
+    pub fn location(mut self, line: usize, column: usize) -> Self {
+        self.locations.push(GraphQLErrorLocation { line, column });
+        self
+    }
+
+    /// Adds an entry to the `extensions` of the error (e.g. an error code).
+    pub fn extension<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.extensions.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// The GraphQL data of a mock that is validated when the mock is created.
+#[derive(Default)]
+pub(crate) struct GraphQLSpec {
+    /// The type of the operation that the mock matches. Defaults to a query.
+    pub operation_type: Option<OperationType>,
+    pub data: Option<Value>,
+}
+
+/// The types of a GraphQL schema that are relevant for validating response data.
+pub(crate) struct GraphQLSchema {
+    types: HashMap<String, SchemaType>,
+    query: String,
+    mutation: String,
+    subscription: String,
+}
+
+enum SchemaType {
+    Scalar,
+    Enum(HashSet<String>),
+    Object(HashMap<String, Type>),
+    Interface(HashMap<String, Type>),
+    Union(Vec<String>),
+    InputObject,
+}
+
+impl GraphQLSchema {
+    /// Reads a schema from an SDL file.
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let content = read_file(path).map_err(|e| {
+            format!(
+                "Cannot read GraphQL schema '{}': {}",
+                path.to_string_lossy(),
+                e
+            )
+        })?;
+        let sdl = String::from_utf8(content)
+            .map_err(|e| format!("Invalid GraphQL schema '{}': {}", path.to_string_lossy(), e))?;
+        Self::parse(&sdl)
+            .map_err(|e| format!("Invalid GraphQL schema '{}': {}", path.to_string_lossy(), e))
+    }
+
+    /// Parses a schema in the GraphQL schema definition language. Type extensions are merged
+    /// into their types.
+    pub fn parse(sdl: &str) -> Result<Self, String> {
+        let document = async_graphql_parser::parse_schema(sdl).map_err(|e| e.to_string())?;
+
+        let mut schema = GraphQLSchema {
+            types: HashMap::new(),
+            query: "Query".to_string(),
+            mutation: "Mutation".to_string(),
+            subscription: "Subscription".to_string(),
+        };
+        for definition in document.definitions {
+            match definition {
+                TypeSystemDefinition::Schema(definition) => {
+                    let definition = definition.node;
+                    if let Some(name) = definition.query {
+                        schema.query = name.node.to_string();
+                    }
+                    if let Some(name) = definition.mutation {
+                        schema.mutation = name.node.to_string();
+                    }
+                    if let Some(name) = definition.subscription {
+                        schema.subscription = name.node.to_string();
+                    }
+                }
+                TypeSystemDefinition::Type(definition) => {
+                    let definition = definition.node;
+                    let name = definition.name.node.to_string();
+                    let schema_type = match definition.kind {
+                        TypeKind::Scalar => SchemaType::Scalar,
+                        TypeKind::Enum(t) => SchemaType::Enum(
+                            t.values
+                                .into_iter()
+                                .map(|v| v.node.value.node.to_string())
+                                .collect(),
+                        ),
+                        TypeKind::Object(t) => SchemaType::Object(to_fields(t.fields)),
+                        TypeKind::Interface(t) => SchemaType::Interface(to_fields(t.fields)),
+                        TypeKind::Union(t) => SchemaType::Union(
+                            t.members.into_iter().map(|m| m.node.to_string()).collect(),
+                        ),
+                        TypeKind::InputObject(_) => SchemaType::InputObject,
+                    };
+                    match (schema.types.get_mut(&name), schema_type) {
+                        (Some(SchemaType::Object(fields)), SchemaType::Object(extension))
+                        | (Some(SchemaType::Interface(fields)), SchemaType::Interface(extension)) => {
+                            fields.extend(extension)
+                        }
+                        (Some(SchemaType::Enum(values)), SchemaType::Enum(extension)) => {
+                            values.extend(extension)
+                        }
+                        (Some(SchemaType::Union(members)), SchemaType::Union(extension)) => {
+                            members.extend(extension)
+                        }
+                        (_, schema_type) => {
+                            schema.types.insert(name, schema_type);
+                        }
+                    }
+                }
+                TypeSystemDefinition::Directive(_) => {}
+            }
+        }
+
+        if !schema.types.contains_key(&schema.query) {
+            return Err(format!("the query type '{}' is not defined", schema.query));
+        }
+        Ok(schema)
+    }
+
+    /// Checks that the data of a response to an operation of the provided type fits the types
+    /// of the schema. Fields that are missing in the data are not reported, because a response
+    /// only contains the fields that were requested.
+    pub fn validate(&self, operation_type: OperationType, data: &Value) -> Result<(), String> {
+        let root = match operation_type {
+            OperationType::Query => &self.query,
+            OperationType::Mutation => &self.mutation,
+            OperationType::Subscription => &self.subscription,
+        };
+        if !self.types.contains_key(root) {
+            return Err(format!("the schema does not define the type '{}'", root));
+        }
+        match data {
+            // The data is null if an error occurred before execution.
+            Value::Null => Ok(()),
+            data => self.validate_named(root, data, "data"),
+        }
+    }
+
+    fn validate_type(&self, ty: &Type, value: &Value, path: &str) -> Result<(), String> {
+        if value.is_null() {
+            return match ty.nullable {
+                true => Ok(()),
+                false => Err(format!("field '{}' is not nullable, but is null", path)),
+            };
+        }
+        match &ty.base {
+            BaseType::List(item_type) => match value {
+                Value::Array(items) => items.iter().enumerate().try_for_each(|(index, item)| {
+                    self.validate_type(item_type, item, &format!("{}[{}]", path, index))
+                }),
+                other => Err(mismatch(path, &ty.to_string(), other)),
+            },
+            BaseType::Named(name) => self.validate_named(name, value, path),
+        }
+    }
+
+    fn validate_named(&self, name: &str, value: &Value, path: &str) -> Result<(), String> {
+        let valid = match name {
+            "Int" => value.as_i64().map_or(false, |n| i32::try_from(n).is_ok()),
+            "Float" => value.is_number(),
+            "String" => value.is_string(),
+            "Boolean" => value.is_boolean(),
+            "ID" => value.is_string() || value.is_i64() || value.is_u64(),
+            _ => match self.types.get(name) {
+                None => return Err(format!("the schema does not define the type '{}'", name)),
+                // Custom scalars can have any representation.
+                Some(SchemaType::Scalar) => true,
+                Some(SchemaType::Enum(values)) => {
+                    value.as_str().map_or(false, |v| values.contains(v))
+                }
+                Some(SchemaType::Object(fields)) => {
+                    return self.validate_object(name, fields, value, path)
+                }
+                Some(SchemaType::Interface(fields)) => {
+                    return match concrete_type_name(value) {
+                        Some(concrete) if concrete != name => {
+                            self.validate_named(concrete, value, path)
+                        }
+                        _ => self.validate_object(name, fields, value, path),
+                    }
+                }
+                Some(SchemaType::Union(members)) => {
+                    return self.validate_union(name, members, value, path)
+                }
+                Some(SchemaType::InputObject) => {
+                    return Err(format!(
+                        "field '{}' has the input type '{}', which cannot be returned",
+                        path, name
+                    ))
+                }
+            },
+        };
+
+        match valid {
+            true => Ok(()),
+            false => Err(mismatch(path, name, value)),
+        }
+    }
+
+    fn validate_object(
+        &self,
+        name: &str,
+        fields: &HashMap<String, Type>,
+        value: &Value,
+        path: &str,
+    ) -> Result<(), String> {
+        let object = match value {
+            Value::Object(object) => object,
+            other => return Err(mismatch(path, name, other)),
+        };
+        for (key, field_value) in object {
+            let field_path = format!("{}.{}", path, key);
+            if key == "__typename" {
+                continue;
+            }
+            match fields.get(key) {
+                Some(field_type) => self.validate_type(field_type, field_value, &field_path)?,
+                None => {
+                    return Err(format!(
+                        "field '{}' is not defined on type '{}'",
+                        field_path, name
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_union(
+        &self,
+        name: &str,
+        members: &[String],
+        value: &Value,
+        path: &str,
+    ) -> Result<(), String> {
+        match concrete_type_name(value) {
+            Some(concrete) if members.iter().any(|m| m == concrete) => {
+                self.validate_named(concrete, value, path)
+            }
+            Some(concrete) => Err(format!(
+                "field '{}' has the type '{}', which is not a member of the union '{}'",
+                path, concrete, name
+            )),
+            // Without a type name, the value needs to fit any member type.
+            None => match members
+                .iter()
+                .any(|member| self.validate_named(member, value, path).is_ok())
+            {
+                true => Ok(()),
+                false => Err(mismatch(path, name, value)),
+            },
+        }
+    }
+}
+
+fn to_fields(fields: Vec<Positioned<FieldDefinition>>) -> HashMap<String, Type> {
+    fields
+        .into_iter()
+        .map(|field| (field.node.name.node.to_string(), field.node.ty.node))
+        .collect()
+}
+
+/// Returns the type of the operations in a GraphQL document. If the document contains
+/// operations of different types, the type of the query operations is returned.
+pub(crate) fn operation_type(query: &str) -> Result<OperationType, String> {
+    let document = async_graphql_parser::parse_query(query).map_err(|e| e.to_string())?;
+    let mut types = document
+        .operations
+        .iter()
+        .map(|(_, operation)| operation.node.ty);
+    let first = types.next().unwrap_or(OperationType::Query);
+    match types.all(|ty| ty == first) {
+        true => Ok(first),
+        false => Ok(OperationType::Query),
+    }
+}
+
+/// Returns the name of the operation that a GraphQL request body executes: the
+/// `operationName` field if it is set, otherwise the name of the only operation of the
+/// document in the `query` field.
+pub(crate) fn operation_name(body: &Value) -> Option<String> {
+    if let Some(name) = body.get("operationName").and_then(|name| name.as_str()) {
+        return Some(name.to_string());
+    }
+
+    let query = body.get("query")?.as_str()?;
+    let document = async_graphql_parser::parse_query(query).ok()?;
+    let mut operations = document.operations.iter();
+    match (operations.next(), operations.next()) {
+        (Some((Some(name), _)), None) => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns a regex that matches a JSON request body whose `query` field contains the provided
+/// GraphQL document, ignoring differences in whitespace, commas and comments between tokens.
+pub(crate) fn query_body_regex(query: &str) -> String {
+    const SEPARATOR: &str = r"(?:\s|,|\\[nrt])*";
+    let tokens: Vec<String> = tokenize(query)
+        .iter()
+        .map(|token| {
+            let json = serde_json::to_string(token).unwrap();
+            regex::escape(&json[1..json.len() - 1])
+        })
+        .collect();
+    format!(
+        r#""query"\s*:\s*"{sep}{tokens}{sep}""#,
+        sep = SEPARATOR,
+        tokens = tokens.join(SEPARATOR)
+    )
+}
+
+/// Splits a GraphQL document into its lexical tokens, dropping whitespace, commas and comments.
+fn tokenize(query: &str) -> Vec<String> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() || c == ',' || c == '\u{feff}' {
+            i += 1;
+        } else if c == '#' {
+            while i < chars.len() && chars[i] != '\n' && chars[i] != '\r' {
+                i += 1;
+            }
+        } else if c == '"' {
+            let start = i;
+            let block = chars[i..].starts_with(&['"', '"', '"']);
+            i += if block { 3 } else { 1 };
+            while i < chars.len() {
+                if chars[i] == '\\' {
+                    i += 2;
+                } else if block && chars[i..].starts_with(&['"', '"', '"']) {
+                    i += 3;
+                    break;
+                } else if !block && chars[i] == '"' {
+                    i += 1;
+                    break;
+                } else {
+                    i += 1;
+                }
+            }
+            tokens.push(chars[start..i.min(chars.len())].iter().collect());
+        } else if chars[i..].starts_with(&['.', '.', '.']) {
+            tokens.push("...".to_string());
+            i += 3;
+        } else if c.is_alphanumeric() || c == '_' || c == '-' {
+            let start = i;
+            i += 1;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric()
+                    || chars[i] == '_'
+                    || (chars[i] == '.' && !chars[i..].starts_with(&['.', '.', '.']))
+                    || ((chars[i] == '+' || chars[i] == '-') && matches!(chars[i - 1], 'e' | 'E')))
+            {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Returns the value of the `__typename` field of an object.
+fn concrete_type_name(value: &Value) -> Option<&str> {
+    value.get("__typename").and_then(|name| name.as_str())
+}
+
+fn mismatch(path: &str, expected: &str, found: &Value) -> String {
+    format!(
+        "field '{}' is expected to be of type '{}', but is {}",
+        path, expected, found
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use async_graphql_parser::types::OperationType;
+    use regex::Regex;
+    use serde_json::json;
+
+    use crate::common::graphql::{
+        operation_name, operation_type, query_body_regex, GraphQLError, GraphQLSchema,
+    };
+
+    const SCHEMA: &str = r#"
+        type Query {
+            user(id: ID!): User
+            search(text: String!): [SearchResult!]!
+        }
+        type Mutation {
+            deleteUser(id: ID!): Boolean!
+        }
+        interface Node { id: ID! }
+        type User implements Node {
+            id: ID!
+            name: String!
+            age: Int
+            role: Role!
+            friends: [User!]
+        }
+        type Post implements Node { id: ID!, title: String! }
+        union SearchResult = User | Post
+        enum Role { ADMIN, MEMBER }
+    "#;
+
+    #[test]
+    fn validate_test() {
+        let schema = GraphQLSchema::parse(SCHEMA).unwrap();
+        let validate = |data| schema.validate(OperationType::Query, &data);
+
+        assert_eq!(
+            validate(json!({
+                "user": { "id": "1", "name": "Fred", "role": "ADMIN", "friends": [{ "id": 2 }] },
+                "search": [{ "__typename": "Post", "title": "Hello" }]
+            })),
+            Ok(())
+        );
+        assert_eq!(
+            validate(json!({ "user": { "friends": [{ "age": "30" }] } })),
+            Err(
+                "field 'data.user.friends[0].age' is expected to be of type 'Int', but is \"30\""
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            validate(json!({ "user": { "nickname": "Freddy" } })),
+            Err("field 'data.user.nickname' is not defined on type 'User'".to_string())
+        );
+        assert_eq!(
+            validate(json!({ "user": { "role": "GUEST" } })),
+            Err(
+                "field 'data.user.role' is expected to be of type 'Role', but is \"GUEST\""
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            validate(json!({ "search": null })),
+            Err("field 'data.search' is not nullable, but is null".to_string())
+        );
+        assert_eq!(
+            validate(json!({ "search": [{ "__typename": "Comment" }] })),
+            Err("field 'data.search[0]' has the type 'Comment', which is not a member of the union 'SearchResult'".to_string())
+        );
+        assert_eq!(
+            validate(json!({ "deleteUser": true })),
+            Err("field 'data.deleteUser' is not defined on type 'Query'".to_string())
+        );
+        assert_eq!(
+            schema.validate(OperationType::Mutation, &json!({ "deleteUser": true })),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn operation_type_test() {
+        assert_eq!(
+            operation_type("{ user(id: 1) { name } }"),
+            Ok(OperationType::Query)
+        );
+        assert_eq!(
+            operation_type("mutation Delete { deleteUser(id: 1) }"),
+            Ok(OperationType::Mutation)
+        );
+        assert!(operation_type("query {").is_err());
+    }
+
+    #[test]
+    fn operation_name_test() {
+        let query = "query GetUser { user(id: 1) { name } }";
+        let queries = "query GetUser { user(id: 1) { name } } query GetUsers { users { name } }";
+
+        assert_eq!(
+            operation_name(&json!({ "query": query })),
+            Some("GetUser".to_string())
+        );
+        assert_eq!(
+            operation_name(&json!({ "query": queries, "operationName": "GetUsers" })),
+            Some("GetUsers".to_string())
+        );
+        assert_eq!(operation_name(&json!({ "query": queries })), None);
+        assert_eq!(
+            operation_name(&json!({ "query": "{ users { name } }" })),
+            None
+        );
+        assert_eq!(operation_name(&json!({ "query": "query {" })), None);
+    }
+
+    #[test]
+    fn query_body_regex_test() {
+        // Arrange
+        let regex = Regex::new(&query_body_regex(
+            "query GetUser($id: ID!) {\n  user(id: $id) { name, ...Fields }\n}",
+        ))
+        .unwrap();
+
+        // Assert
+        assert!(regex.is_match(
+            &json!({ "query": "query GetUser($id:ID!){user(id:$id){name ...Fields}}" }).to_string()
+        ));
+        assert!(regex.is_match(
+            &json!({ "query": "query GetUser($id: ID!) {\n\tuser(id: $id) {\n name\n ...Fields } }" })
+                .to_string()
+        ));
+        assert!(!regex.is_match(
+            &json!({ "query": "query GetUser($id: ID!) { user(id: $id) { id } }" }).to_string()
+        ));
+    }
+
+    #[test]
+    fn graphql_error_test() {
+        let error = GraphQLError::new("User not found")
+            .path("user.friends.0")
+            .location(2, 3)
+            .extension("code", "NOT_FOUND");
+
+        assert_eq!(
+            serde_json::to_value(error).unwrap(),
+            json!({
+                "message": "User not found",
+                "locations": [{ "line": 2, "column": 3 }],
+                "path": ["user", "friends", 0],
+                "extensions": { "code": "NOT_FOUND" }
+            })
+        );
+    }
+}
@@ -0,0 +1,90 @@
+//! Support for mocking unary gRPC calls. Messages are sent in length-prefixed frames: a
+//! compression flag byte, followed by the message length as a big-endian `u32` and the
+//! protobuf encoded message.
+
+/// The length of the prefix of a gRPC frame.
+const FRAME_PREFIX_LEN: usize = 5;
+
+/// Wraps a protobuf encoded message in an uncompressed gRPC frame.
+pub(crate) fn encode_frame(message: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(FRAME_PREFIX_LEN + message.len());
+    frame.push(0);
+    frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    frame.extend_from_slice(message);
+    frame
+}
+
+/// Returns the message of the first gRPC frame in a request body. Returns `None` if the body
+/// does not start with a complete frame or if the message is compressed.
+pub(crate) fn decode_frame(body: &[u8]) -> Option<&[u8]> {
+    if body.len() < FRAME_PREFIX_LEN || body[0] != 0 {
+        return None;
+    }
+    let mut len = [0; 4];
+    len.copy_from_slice(&body[1..FRAME_PREFIX_LEN]);
+    body.get(FRAME_PREFIX_LEN..FRAME_PREFIX_LEN + u32::from_be_bytes(len) as usize)
+}
+
+/// The status codes of gRPC calls as defined in the
+/// [gRPC documentation](https://grpc.github.io/grpc/core/md_doc_statuscodes.html).
+#[cfg(feature = "grpc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrpcCode {
+    Ok = 0,
+    Cancelled = 1,
+    Unknown = 2,
+    InvalidArgument = 3,
+    DeadlineExceeded = 4,
+    NotFound = 5,
+    AlreadyExists = 6,
+    PermissionDenied = 7,
+    ResourceExhausted = 8,
+    FailedPrecondition = 9,
+    Aborted = 10,
+    OutOfRange = 11,
+    Unimplemented = 12,
+    Internal = 13,
+    Unavailable = 14,
+    DataLoss = 15,
+    Unauthenticated = 16,
+}
+
+/// Percent-encodes a status message for the `grpc-message` trailer.
+#[cfg(feature = "grpc")]
+pub(crate) fn encode_status_message(message: &str) -> String {
+    let mut encoded = String::with_capacity(message.len());
+    for byte in message.bytes() {
+        match byte {
+            b' '..=b'~' if byte != b'%' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::grpc::{decode_frame, encode_frame};
+
+    #[test]
+    fn frame_test() {
+        let frame = encode_frame(b"\x0a\x03Fred");
+
+        assert_eq!(frame, b"\x00\x00\x00\x00\x06\x0a\x03Fred".to_vec());
+        assert_eq!(decode_frame(&frame), Some(&b"\x0a\x03Fred"[..]));
+        assert_eq!(decode_frame(&frame[..6]), None);
+        assert_eq!(decode_frame(b"\x01\x00\x00\x00\x00"), None);
+        assert_eq!(decode_frame(b"\x00\x00\x00\x00\x00"), Some(&b""[..]));
+    }
+
+    #[cfg(feature = "grpc")]
+    #[test]
+    fn encode_status_message_test() {
+        use crate::common::grpc::encode_status_message;
+
+        assert_eq!(
+            encode_status_message("try again: 100% ünavailable"),
+            "try again: 100%25 %C3%BCnavailable"
+        );
+    }
+}
@@ -0,0 +1,552 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::common::data::{
+    HttpMockRequest, MockDefinition, MockServerHttpResponse, RequestRequirements, TrafficEntry,
+    TrafficResponse,
+};
+use crate::common::util::{format_rfc3339, read_file};
+
+/// Response headers that only apply to the connection that a response was recorded on or that
+/// do not describe the recorded (already decoded) body. They are not replayed.
+pub(crate) const SKIPPED_RESPONSE_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "content-length",
+    "content-encoding",
+];
+
+/// Options that control how the entries of a HAR file are turned into mocks
+/// (see [MockServer::load_har_with_options](struct.MockServer.html#method.load_har_with_options)).
+///
+/// By default, mocks match the method, the path and all query parameters of a recorded request
+/// and recorded requests with the same method, path and query parameters are combined into a
+/// single mock that replays the recorded responses in order.
+#[derive(Debug, Clone)]
+pub struct HarOptions {
+    match_headers: Vec<String>,
+    ignored_query_params: Vec<String>,
+    sequences: bool,
+}
+
+impl HarOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self {
+            match_headers: Vec::new(),
+            ignored_query_params: Vec::new(),
+            sequences: true,
+        }
+    }
+
+    /// Lets the mocks match the recorded value of a request header (case-insensitive name).
+    /// Mocks for recorded requests that did not send the header do not match it.
+    pub fn match_header<S: Into<String>>(mut self, name: S) -> Self {
+        self.match_headers.push(name.into().to_lowercase());
+        self
+    }
+
+    /// Ignores a query parameter (e.g. a cache buster such as `_`) when matching requests and
+    /// when comparing recorded requests with each other.
+    pub fn ignore_query_param<S: Into<String>>(mut self, name: S) -> Self {
+        self.ignored_query_params.push(name.into());
+        self
+    }
+
+    /// Sets whether recorded requests with the same method, path, query parameters and matched
+    /// headers are combined into a single mock that replays the recorded responses in order (the last response is repeated once all
+    /// responses have been served). If disabled, a mock is created for every entry, so that only
+    /// the first recorded response of identical requests is ever served. Defaults to `true`.
+    pub fn sequences(mut self, enabled: bool) -> Self {
+        self.sequences = enabled;
+        self
+    }
+}
+
+impl Default for HarOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Deserialize)]
+struct Har {
+    log: HarLog,
+}
+
+#[derive(Deserialize)]
+struct HarLog {
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Deserialize)]
+struct HarEntry {
+    request: HarRequest,
+    response: HarResponse,
+}
+
+#[derive(Deserialize)]
+struct HarRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<HarNameValuePair>,
+}
+
+#[derive(Deserialize)]
+struct HarResponse {
+    status: u16,
+    #[serde(default)]
+    headers: Vec<HarNameValuePair>,
+    content: Option<HarContent>,
+}
+
+#[derive(Deserialize)]
+struct HarContent {
+    text: Option<String>,
+    encoding: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct HarNameValuePair {
+    name: String,
+    value: String,
+}
+
+/// Reads a HAR file and creates a mock definition for every distinct recorded request (or for
+/// every entry if sequences are disabled), in the order in which the requests were recorded.
+pub(crate) fn read_har_file(
+    path: &Path,
+    options: &HarOptions,
+) -> Result<Vec<MockDefinition>, String> {
+    let content = read_file(path)
+        .map_err(|e| format!("Cannot read HAR file '{}': {}", path.to_string_lossy(), e))?;
+    let har: Har = serde_json::from_slice(&content)
+        .map_err(|e| format!("Invalid HAR file '{}': {}", path.to_string_lossy(), e))?;
+
+    to_mock_definitions(har.log.entries, options)
+        .map_err(|e| format!("Invalid HAR file '{}': {}", path.to_string_lossy(), e))
+}
+
+fn to_mock_definitions(
+    entries: Vec<HarEntry>,
+    options: &HarOptions,
+) -> Result<Vec<MockDefinition>, String> {
+    let mut definitions: Vec<(RequestRequirements, Vec<MockServerHttpResponse>)> = Vec::new();
+    for (idx, entry) in entries.into_iter().enumerate() {
+        let request = to_request_requirements(&entry.request, options)
+            .map_err(|e| format!("entry {}: {}", idx, e))?;
+        let response = to_response(&entry.response).map_err(|e| format!("entry {}: {}", idx, e))?;
+
+        let existing = match options.sequences {
+            true => definitions
+                .iter_mut()
+                .find(|(r, _)| same_request(r, &request)),
+            false => None,
+        };
+        match existing {
+            Some((_, responses)) => responses.push(response),
+            None => definitions.push((request, vec![response])),
+        }
+    }
+
+    Ok(definitions
+        .into_iter()
+        .map(|(request, mut responses)| {
+            let response = match responses.len() {
+                1 => responses.remove(0),
+                _ => {
+                    let mut response = MockServerHttpResponse::new();
+                    response.sequence = Some(responses);
+                    response
+                }
+            };
+            MockDefinition::new(request, response)
+        })
+        .collect())
+}
+
+fn to_request_requirements(
+    request: &HarRequest,
+    options: &HarOptions,
+) -> Result<RequestRequirements, String> {
+    let url =
+        Url::parse(&request.url).map_err(|e| format!("invalid URL '{}': {}", request.url, e))?;
+
+    let mut requirements = RequestRequirements::new();
+    requirements.method = Some(request.method.to_uppercase());
+    requirements.path = Some(url.path().to_string());
+
+    let mut query_params: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(name, _)| !options.ignored_query_params.iter().any(|i| i == name))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    if !query_params.is_empty() {
+        query_params.sort();
+        requirements.query_param = Some(query_params);
+    }
+
+    let mut headers: Vec<(String, String)> = request
+        .headers
+        .iter()
+        .filter(|h| options.match_headers.contains(&h.name.to_lowercase()))
+        .map(|h| (h.name.to_lowercase(), h.value.clone()))
+        .collect();
+    if !headers.is_empty() {
+        headers.sort();
+        requirements.headers = Some(headers);
+    }
+
+    Ok(requirements)
+}
+
+/// Returns true if both requirements were created from the same request key (method, path,
+/// significant query parameters and matched headers).
+fn same_request(a: &RequestRequirements, b: &RequestRequirements) -> bool {
+    a.method == b.method
+        && a.path == b.path
+        && a.query_param == b.query_param
+        && a.headers == b.headers
+}
+
+fn to_response(response: &HarResponse) -> Result<MockServerHttpResponse, String> {
+    let mut mock_response = MockServerHttpResponse::new();
+    mock_response.status = Some(response.status);
+
+    let headers: Vec<(String, String)> = response
+        .headers
+        .iter()
+        .filter(|h| !h.name.starts_with(':'))
+        .filter(|h| !SKIPPED_RESPONSE_HEADERS.contains(&h.name.to_lowercase().as_str()))
+        .map(|h| (h.name.clone(), h.value.clone()))
+        .collect();
+    if !headers.is_empty() {
+        mock_response.headers = Some(headers);
+    }
+
+    if let Some(content) = &response.content {
+        mock_response.body = match (&content.text, content.encoding.as_deref()) {
+            (None, _) => None,
+            (Some(text), Some("base64")) => Some(
+                base64::decode(text).map_err(|e| format!("invalid base64 response body: {}", e))?,
+            ),
+            (Some(_), Some(encoding)) => {
+                return Err(format!("unsupported response body encoding '{}'", encoding))
+            }
+            (Some(text), None) => Some(text.clone().into_bytes()),
+        };
+    }
+
+    Ok(mock_response)
+}
+
+/// Creates a HAR 1.2 document with an entry for every request in the traffic log.
+pub(crate) fn to_har(traffic: &[Arc<TrafficEntry>], https: bool) -> Value {
+    let entries: Vec<Value> = traffic
+        .iter()
+        .map(|entry| {
+            let response = entry.response.lock().unwrap();
+            let timings = json!({
+                "send": 0,
+                "wait": milliseconds(response.wait),
+                "receive": milliseconds(response.receive),
+            });
+            json!({
+                "startedDateTime": format_rfc3339(entry.started),
+                "time": milliseconds(response.wait + response.receive),
+                "request": to_har_request(&entry.request, https),
+                "response": to_har_response(&response, http_version(&entry.request)),
+                "cache": {},
+                "timings": timings,
+            })
+        })
+        .collect();
+
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "httpmock", "version": env!("CARGO_PKG_VERSION") },
+            "entries": entries,
+        }
+    })
+}
+
+fn to_har_request(request: &HttpMockRequest, https: bool) -> Value {
+    let headers = request.headers.as_deref().unwrap_or_default();
+    let query_params = request.query_params.as_deref().unwrap_or_default();
+
+    let host = find_header(headers, "host")
+        .map(|host| host.to_string())
+        .or_else(|| request.host.clone())
+        .unwrap_or_else(|| "localhost".to_string());
+    let mut url = format!(
+        "{}://{}{}",
+        if https { "https" } else { "http" },
+        host,
+        request.path
+    );
+    if !query_params.is_empty() {
+        url.push('?');
+        url.push_str(
+            &form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(query_params)
+                .finish(),
+        );
+    }
+
+    let cookies: Vec<Value> = headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("cookie"))
+        .flat_map(|(_, value)| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect();
+
+    let body = request.body.as_deref().unwrap_or_default();
+    let mut har_request = json!({
+        "method": request.method,
+        "url": url,
+        "httpVersion": http_version(request),
+        "cookies": cookies,
+        "headers": to_name_value_list(headers),
+        "queryString": to_name_value_list(query_params),
+        "headersSize": -1,
+        "bodySize": if request.body_truncated { -1 } else { body.len() as i64 },
+    });
+    if !body.is_empty() {
+        let mut post_data = json!({
+            "mimeType": find_header(headers, "content-type").unwrap_or_default(),
+            "params": [],
+        });
+        match std::str::from_utf8(body) {
+            Ok(text) => post_data["text"] = json!(text),
+            Err(_) => {
+                post_data["text"] = json!(base64::encode(body));
+                post_data["_encoding"] = json!("base64");
+            }
+        }
+        har_request["postData"] = post_data;
+    }
+
+    har_request
+}
+
+fn to_har_response(response: &TrafficResponse, http_version: &str) -> Value {
+    let cookies: Vec<Value> = response
+        .headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("set-cookie"))
+        .filter_map(|(_, value)| value.split(';').next()?.trim().split_once('='))
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect();
+
+    let mut content = json!({
+        "size": response.body.len(),
+        "mimeType": find_header(&response.headers, "content-type").unwrap_or_default(),
+    });
+    if !response.body.is_empty() {
+        match std::str::from_utf8(&response.body) {
+            Ok(text) => content["text"] = json!(text),
+            Err(_) => {
+                content["text"] = json!(base64::encode(&response.body));
+                content["encoding"] = json!("base64");
+            }
+        }
+    }
+
+    json!({
+        "status": response.status,
+        "statusText": hyper::StatusCode::from_u16(response.status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or_default(),
+        "httpVersion": http_version,
+        "cookies": cookies,
+        "headers": to_name_value_list(&response.headers),
+        "content": content,
+        "redirectURL": find_header(&response.headers, "location").unwrap_or_default(),
+        "headersSize": -1,
+        "bodySize": response.body.len(),
+    })
+}
+
+fn http_version(request: &HttpMockRequest) -> &str {
+    request.version.as_deref().unwrap_or("HTTP/1.1")
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+fn to_name_value_list(pairs: &[(String, String)]) -> Vec<Value> {
+    pairs
+        .iter()
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use serde_json::json;
+
+    use crate::common::data::{HttpMockRequest, TrafficEntry, TrafficResponse};
+    use crate::common::har::{to_har, to_mock_definitions, Har, HarOptions};
+
+    fn entries() -> Har {
+        serde_json::from_value(json!({
+            "log": {
+                "entries": [
+                    {
+                        "request": {
+                            "method": "GET",
+                            "url": "https://example.com/users?page=1&_=123",
+                            "headers": [{ "name": "Accept", "value": "application/json" }]
+                        },
+                        "response": {
+                            "status": 200,
+                            "headers": [
+                                { "name": "Content-Type", "value": "application/json" },
+                                { "name": "Transfer-Encoding", "value": "chunked" }
+                            ],
+                            "content": { "text": "[]" }
+                        }
+                    },
+                    {
+                        "request": { "method": "GET", "url": "https://example.com/users?_=456&page=1" },
+                        "response": {
+                            "status": 200,
+                            "content": { "text": "WzFd", "encoding": "base64" }
+                        }
+                    }
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn har_sequence_test() {
+        // Act
+        let definitions = to_mock_definitions(
+            entries().log.entries,
+            &HarOptions::new().ignore_query_param("_"),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(definitions.len(), 1);
+        let request = &definitions[0].request;
+        assert_eq!(request.path, Some("/users".to_string()));
+        assert_eq!(
+            request.query_param,
+            Some(vec![("page".to_string(), "1".to_string())])
+        );
+        let sequence = definitions[0].response.sequence.as_ref().unwrap();
+        assert_eq!(sequence.len(), 2);
+        assert_eq!(
+            sequence[0].headers,
+            Some(vec![(
+                "Content-Type".to_string(),
+                "application/json".to_string()
+            )])
+        );
+        assert_eq!(sequence[0].body, Some(b"[]".to_vec()));
+        assert_eq!(sequence[1].body, Some(b"[1]".to_vec()));
+    }
+
+    #[test]
+    fn har_options_test() {
+        // Act
+        let with_cache_buster =
+            to_mock_definitions(entries().log.entries, &HarOptions::new()).unwrap();
+        let without_sequences = to_mock_definitions(
+            entries().log.entries,
+            &HarOptions::new()
+                .ignore_query_param("_")
+                .match_header("accept")
+                .sequences(false),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(with_cache_buster.len(), 2);
+        assert_eq!(without_sequences.len(), 2);
+        assert_eq!(
+            without_sequences[0].request.headers,
+            Some(vec![("accept".to_string(), "application/json".to_string())])
+        );
+        assert!(without_sequences[0].response.sequence.is_none());
+    }
+
+    #[test]
+    fn to_har_test() {
+        // Arrange
+        let request = HttpMockRequest::new("POST".to_string(), "/upload".to_string())
+            .with_headers(vec![
+                ("host".to_string(), "127.0.0.1:5000".to_string()),
+                ("cookie".to_string(), "a=1; b=2".to_string()),
+            ])
+            .with_query_params(vec![("name".to_string(), "a b".to_string())])
+            .with_body(vec![0xff, 0x00]);
+        let traffic = vec![Arc::new(TrafficEntry {
+            started: UNIX_EPOCH + Duration::from_millis(1500),
+            request: Arc::new(request),
+            response: Mutex::new(TrafficResponse {
+                status: 201,
+                headers: vec![("content-type".to_string(), "text/plain".to_string())],
+                body: b"done".to_vec(),
+                wait: Duration::from_millis(20),
+                receive: Duration::from_millis(5),
+            }),
+        })];
+
+        // Act
+        let har = to_har(&traffic, false);
+
+        // Assert
+        let entry = &har["log"]["entries"][0];
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(entry["startedDateTime"], "1970-01-01T00:00:01.500Z");
+        assert_eq!(entry["time"], 25.0);
+        assert_eq!(
+            entry["timings"],
+            json!({ "send": 0, "wait": 20.0, "receive": 5.0 })
+        );
+        assert_eq!(
+            entry["request"]["url"],
+            "http://127.0.0.1:5000/upload?name=a+b"
+        );
+        assert_eq!(
+            entry["request"]["cookies"],
+            json!([{ "name": "a", "value": "1" }, { "name": "b", "value": "2" }])
+        );
+        assert_eq!(entry["request"]["postData"]["text"], "/wA=");
+        assert_eq!(entry["response"]["statusText"], "Created");
+        assert_eq!(
+            entry["response"]["content"],
+            json!({ "size": 4, "mimeType": "text/plain", "text": "done" })
+        );
+    }
+}
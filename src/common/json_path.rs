@@ -0,0 +1,202 @@
+//! JSONPath expressions (RFC 9535) that address values in a JSON document, evaluated by
+//! [serde_json_path](https://docs.rs/serde_json_path).
+
+use serde_json::Value;
+use serde_json_path::{JsonPath, PathElement};
+
+/// Returns all values that the path selects from the provided value. Returns an error if the
+/// path is invalid.
+pub(crate) fn select<'a>(value: &'a Value, path: &str) -> Result<Vec<&'a Value>, String> {
+    Ok(parse(path)?.query(value).all())
+}
+
+/// Returns an error if the path is invalid.
+pub(crate) fn check_path(path: &str) -> Result<(), String> {
+    parse(path).map(|_| ())
+}
+
+/// Replaces all values that the path selects in the provided value with the replacement and
+/// returns the number of replaced values. Returns an error if the path is invalid.
+pub(crate) fn replace(value: &mut Value, path: &str, replacement: &Value) -> Result<usize, String> {
+    let mut replaced = 0;
+    for (pointer, _) in locate(value, path)? {
+        if let Some(selected) = value.pointer_mut(&pointer) {
+            *selected = replacement.clone();
+            replaced += 1;
+        }
+    }
+    Ok(replaced)
+}
+
+/// Removes all values that the path selects from the provided value. Object members are
+/// removed, while array elements are replaced with `null`, so that the positions of the other
+/// elements do not change. Returns an error if the path is invalid.
+pub(crate) fn remove(value: &mut Value, path: &str) -> Result<(), String> {
+    for (pointer, member) in locate(value, path)? {
+        match member {
+            Some(name) => {
+                let parent = &pointer[..pointer.rfind('/').unwrap_or(0)];
+                if let Some(Value::Object(object)) = value.pointer_mut(parent) {
+                    object.remove(&name);
+                }
+            }
+            None => {
+                if let Some(selected) = value.pointer_mut(&pointer) {
+                    *selected = Value::Null;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns true if a selected value equals the expected value. Strings are also compared with
+/// the text representation of numbers and booleans, so that `"1"` matches `1`.
+pub(crate) fn value_matches(selected: &Value, expected: &Value) -> bool {
+    match (selected, expected) {
+        (Value::Number(_), Value::String(s)) | (Value::Bool(_), Value::String(s)) => {
+            &selected.to_string() == s
+        }
+        _ => selected == expected,
+    }
+}
+
+fn parse(path: &str) -> Result<JsonPath, String> {
+    JsonPath::parse(path).map_err(|e| format!("Invalid JSONPath '{}': {}", path, e))
+}
+
+/// Returns the JSON pointers of all values that the path selects from the provided value,
+/// along with their member names if they are members of an object, so that the values can be
+/// modified after the query.
+fn locate(value: &Value, path: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    Ok(parse(path)?
+        .query_located(value)
+        .iter()
+        .map(|node| {
+            let location = node.location();
+            let member = match location.last() {
+                Some(PathElement::Name(name)) => Some(name.to_string()),
+                _ => None,
+            };
+            (location.to_json_pointer(), member)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::common::json_path::{remove, replace, select, value_matches};
+
+    #[test]
+    fn select_test() {
+        let value = json!({
+            "user": {
+                "addresses": [{ "zip": "10115" }, { "zip": 80331 }],
+                "first name": "Fred"
+            }
+        });
+
+        assert_eq!(
+            select(&value, "$.user.addresses[0].zip").unwrap(),
+            vec![&json!("10115")]
+        );
+        assert_eq!(
+            select(&value, "$['user']['first name']").unwrap(),
+            vec![&json!("Fred")]
+        );
+        assert_eq!(
+            select(&value, "$.user.addresses[*].zip").unwrap(),
+            vec![&json!("10115"), &json!(80331)]
+        );
+        assert_eq!(
+            select(&value, "$.user.addresses[-1].zip").unwrap(),
+            vec![&json!(80331)]
+        );
+        assert!(select(&value, "$.user.missing").unwrap().is_empty());
+        assert_eq!(select(&value, "$").unwrap(), vec![&value]);
+    }
+
+    #[test]
+    fn select_filter_and_descendants_test() {
+        let value = json!({
+            "users": [
+                { "id": 1, "name": "Fred", "address": { "zip": "10115" } },
+                { "id": 2, "name": "Wilma", "address": { "zip": "80331" } }
+            ]
+        });
+
+        assert_eq!(
+            select(&value, "$.users[?@.id == 2].name").unwrap(),
+            vec![&json!("Wilma")]
+        );
+        assert_eq!(
+            select(&value, "$..zip").unwrap(),
+            vec![&json!("10115"), &json!("80331")]
+        );
+        assert_eq!(select(&value, "$.users[1:].id").unwrap(), vec![&json!(2)]);
+    }
+
+    #[test]
+    fn select_invalid_path_test() {
+        let value = json!({});
+
+        assert!(select(&value, "user.name").is_err());
+        assert!(select(&value, "$.users[0").is_err());
+        assert!(select(&value, "$.users[?(@.id == 1)").is_err());
+    }
+
+    #[test]
+    fn replace_test() {
+        // Arrange
+        let mut value = json!({
+            "id": "8f2a",
+            "items": [{ "updated": 1 }, { "updated": 2 }]
+        });
+
+        // Act
+        let replaced_id = replace(&mut value, "$.id", &json!("ID")).unwrap();
+        let replaced_items = replace(&mut value, "$.items[*].updated", &json!(0)).unwrap();
+        let replaced_missing = replace(&mut value, "$.missing", &json!(0)).unwrap();
+
+        // Assert
+        assert_eq!((replaced_id, replaced_items, replaced_missing), (1, 2, 0));
+        assert_eq!(
+            value,
+            json!({ "id": "ID", "items": [{ "updated": 0 }, { "updated": 0 }] })
+        );
+    }
+
+    #[test]
+    fn remove_test() {
+        // Arrange
+        let mut value = json!({
+            "id": "8f2a",
+            "items": [{ "id": 1, "updated": 1 }, { "id": 2, "updated": 2 }],
+            "tags": ["a", "b"]
+        });
+
+        // Act
+        remove(&mut value, "$.id").unwrap();
+        remove(&mut value, "$.items[*].updated").unwrap();
+        remove(&mut value, "$.tags[0]").unwrap();
+        remove(&mut value, "$.missing.path").unwrap();
+        remove(&mut value, "$..unknown").unwrap();
+
+        // Assert
+        assert_eq!(
+            value,
+            json!({ "items": [{ "id": 1 }, { "id": 2 }], "tags": [null, "b"] })
+        );
+        assert!(remove(&mut value, "items").is_err());
+    }
+
+    #[test]
+    fn value_matches_test() {
+        assert!(value_matches(&json!(80331), &json!("80331")));
+        assert!(value_matches(&json!(true), &json!("true")));
+        assert!(value_matches(&json!({"a": 1}), &json!({"a": 1})));
+        assert!(!value_matches(&json!("80331"), &json!(80331)));
+    }
+}
@@ -0,0 +1,121 @@
+//! Validation of JSON documents against a [JSON Schema](https://json-schema.org/) with the
+//! [jsonschema](https://docs.rs/jsonschema) crate. Schemas that do not declare their dialect with
+//! `$schema` are validated according to draft-07. Formats (e.g. `date-time` or `email`) are
+//! validated as well, and references to other documents are not resolved.
+
+use jsonschema::paths::LocationSegment;
+use jsonschema::{Draft, Validator};
+use serde_json::Value;
+
+/// Validates a value against a schema. Returns a description of every violation, each
+/// starting with the JSON path of the offending value (e.g. `$.user.name: ...`). An invalid
+/// schema is reported as a single violation.
+pub(crate) fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    match validator(schema) {
+        Ok(validator) => validator
+            .iter_errors(value)
+            .map(|e| {
+                let mut path = "$".to_string();
+                for segment in e.instance_path() {
+                    match segment {
+                        LocationSegment::Property(name) => path.push_str(&format!(".{}", name)),
+                        LocationSegment::Index(idx) => path.push_str(&format!("[{}]", idx)),
+                    }
+                }
+                format!("{}: {}", path, e)
+            })
+            .collect(),
+        Err(e) => vec![e],
+    }
+}
+
+/// Returns an error if the schema is invalid or references another document.
+pub(crate) fn check_schema(schema: &Value) -> Result<(), String> {
+    validator(schema).map(|_| ())
+}
+
+fn validator(schema: &Value) -> Result<Validator, String> {
+    let options = jsonschema::options().should_validate_formats(true);
+    let options = match schema.get("$schema") {
+        Some(_) => options,
+        None => options.with_draft(Draft::Draft7),
+    };
+    options
+        .build(schema)
+        .map_err(|e| format!("Invalid JSON schema: {}", e))
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::common::json_schema::{check_schema, validate};
+
+    #[test]
+    fn validate_test() {
+        // Arrange
+        let schema = json!({
+            "definitions": {
+                "tag": { "enum": ["a", "b"] }
+            },
+            "type": "object",
+            "required": ["name", "tags"],
+            "additionalProperties": false,
+            "properties": {
+                "name": { "type": "string", "minLength": 2 },
+                "age": { "type": "integer", "minimum": 0 },
+                "email": { "type": "string", "format": "email" },
+                "tags": { "type": "array", "items": { "$ref": "#/definitions/tag" } }
+            }
+        });
+
+        // Act
+        let valid = validate(
+            &schema,
+            &json!({"name": "Fred", "age": 3, "email": "fred@example.com", "tags": ["a"]}),
+        );
+        let invalid = validate(
+            &schema,
+            &json!({"name": "F", "age": -1, "email": "fred", "tags": ["c"]}),
+        );
+
+        // Assert
+        assert!(valid.is_empty(), "{:?}", valid);
+        assert_eq!(invalid.len(), 4, "{:?}", invalid);
+        for path in ["$.name: ", "$.age: ", "$.email: ", "$.tags[0]: "] {
+            assert!(
+                invalid.iter().any(|e| e.starts_with(path)),
+                "{:?} does not contain a violation at {}",
+                invalid,
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn validate_declared_dialect_test() {
+        // Arrange
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "array",
+            "prefixItems": [{ "type": "number" }],
+            "items": false
+        });
+
+        // Act
+        let valid = validate(&schema, &json!([1.5]));
+        let invalid = validate(&schema, &json!([1.5, 2]));
+
+        // Assert
+        assert!(valid.is_empty(), "{:?}", valid);
+        assert_eq!(invalid.len(), 1, "{:?}", invalid);
+    }
+
+    #[test]
+    fn check_schema_test() {
+        assert!(check_schema(&json!({ "type": "object" })).is_ok());
+        assert!(check_schema(&json!(true)).is_ok());
+        assert!(check_schema(&json!({ "type": "unknown" })).is_err());
+        assert!(check_schema(&json!({ "$ref": "https://example.com/user.json" })).is_err());
+    }
+}
@@ -1,2 +1,27 @@
+pub(crate) mod curl;
 pub mod data;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+pub mod grpc;
+pub mod har;
+pub(crate) mod json_path;
+#[cfg(feature = "json-schema")]
+pub(crate) mod json_schema;
+pub(crate) mod multipart;
+#[cfg(feature = "openapi")]
+pub(crate) mod openapi;
+pub(crate) mod pact;
+#[cfg(feature = "regex")]
+pub(crate) mod postman;
+pub(crate) mod protobuf;
+#[cfg(feature = "yaml")]
+pub(crate) mod recording;
+#[cfg(feature = "xml")]
+pub(crate) mod soap;
 pub mod util;
+#[cfg(feature = "regex")]
+pub(crate) mod wiremock;
+#[cfg(feature = "xml")]
+pub(crate) mod xml;
+#[cfg(feature = "yaml")]
+pub(crate) mod yaml;
@@ -0,0 +1,185 @@
+//! Parses `multipart/form-data` request bodies (see
+//! [RFC 7578](https://tools.ietf.org/html/rfc7578)), so that mocks can match the parts of file
+//! uploads.
+
+use std::fmt;
+
+/// A part of a `multipart/form-data` body.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Part {
+    /// The `name` parameter of the `Content-Disposition` header of the part.
+    pub name: Option<String>,
+    /// The `filename` parameter of the `Content-Disposition` header of the part.
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// The parts of a `multipart/form-data` body.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MultipartBody {
+    pub parts: Vec<Part>,
+}
+
+impl fmt::Display for MultipartBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .parts
+            .iter()
+            .map(|part| {
+                let mut text = format!("'{}'", part.name.as_deref().unwrap_or_default());
+                if let Some(filename) = &part.filename {
+                    text.push_str(&format!(" (filename {:?})", filename));
+                }
+                text
+            })
+            .collect();
+        write!(f, "multipart body with the parts {}", parts.join(", "))
+    }
+}
+
+/// Returns the boundary parameter of a `multipart/form-data` content type, or `None` if the
+/// content type is a different one.
+pub(crate) fn boundary(content_type: &str) -> Option<&str> {
+    let mut params = content_type.split(';');
+    let media_type = params.next()?.trim();
+    if !media_type.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim().trim_matches('"'))
+        .filter(|value| !value.is_empty())
+}
+
+/// Parses a `multipart/form-data` body with the given boundary. Returns `None` if the body is
+/// not delimited by the boundary or if it is not terminated by the closing delimiter.
+pub(crate) fn parse(body: &[u8], boundary: &str) -> Option<MultipartBody> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut rest = &body[find(body, &delimiter)? + delimiter.len()..];
+    let mut parts = Vec::new();
+
+    loop {
+        if rest.starts_with(b"--") {
+            return Some(MultipartBody { parts });
+        }
+        // The rest of the delimiter line (usually only the line break) is ignored.
+        rest = &rest[find(rest, b"\r\n")? + 2..];
+
+        let end = find(rest, &[b"\r\n", delimiter.as_slice()].concat())?;
+        parts.push(parse_part(&rest[..end])?);
+        rest = &rest[end + 2 + delimiter.len()..];
+    }
+}
+
+/// Parses the headers and the body of a part.
+fn parse_part(part: &[u8]) -> Option<Part> {
+    let (headers, body) = match part.starts_with(b"\r\n") {
+        true => (&part[..0], &part[2..]),
+        false => {
+            let end = find(part, b"\r\n\r\n")?;
+            (&part[..end], &part[end + 4..])
+        }
+    };
+
+    let mut result = Part {
+        name: None,
+        filename: None,
+        content_type: None,
+        body: body.to_vec(),
+    };
+    for line in String::from_utf8_lossy(headers).split("\r\n") {
+        let (name, value) = match line.split_once(':') {
+            Some(header) => header,
+            None => continue,
+        };
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("content-type") {
+            result.content_type = Some(value.trim().to_string());
+        } else if name.eq_ignore_ascii_case("content-disposition") {
+            for param in value.split(';').skip(1) {
+                if let Some((name, value)) = param.split_once('=') {
+                    let value = Some(value.trim().trim_matches('"').to_string());
+                    match name.trim().to_ascii_lowercase().as_str() {
+                        "name" => result.name = value,
+                        "filename" => result.filename = value,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Returns the position of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::multipart::{boundary, parse, Part};
+
+    #[test]
+    fn boundary_test() {
+        assert_eq!(
+            boundary("multipart/form-data; boundary=\"abc 123\""),
+            Some("abc 123")
+        );
+        assert_eq!(
+            boundary("Multipart/Form-Data;charset=utf-8;Boundary=xyz"),
+            Some("xyz")
+        );
+        assert_eq!(boundary("multipart/mixed; boundary=xyz"), None);
+        assert_eq!(boundary("multipart/form-data"), None);
+    }
+
+    #[test]
+    fn parse_test() {
+        // Arrange
+        let body = "preamble\r\n--XYZ\r\n\
+                    Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+                    Holiday\r\n\
+                    --XYZ\r\n\
+                    Content-Disposition: form-data; name=\"photo\"; filename=\"beach.png\"\r\n\
+                    Content-Type: image/png\r\n\r\n\
+                    \u{1}\r\n\u{2}\r\n\
+                    --XYZ--\r\n";
+
+        // Act
+        let parsed = parse(body.as_bytes(), "XYZ").unwrap();
+
+        // Assert
+        assert_eq!(
+            parsed.parts,
+            vec![
+                Part {
+                    name: Some("title".to_string()),
+                    filename: None,
+                    content_type: None,
+                    body: b"Holiday".to_vec(),
+                },
+                Part {
+                    name: Some("photo".to_string()),
+                    filename: Some("beach.png".to_string()),
+                    content_type: Some("image/png".to_string()),
+                    body: b"\x01\r\n\x02".to_vec(),
+                },
+            ]
+        );
+        assert_eq!(
+            parsed.to_string(),
+            "multipart body with the parts 'title', 'photo' (filename \"beach.png\")"
+        );
+    }
+
+    #[test]
+    fn parse_invalid_body_test() {
+        assert_eq!(parse(b"--XYZ\r\n\r\nunterminated", "XYZ"), None);
+        assert_eq!(parse(b"no delimiter", "XYZ"), None);
+    }
+}
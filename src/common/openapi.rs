@@ -0,0 +1,582 @@
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+
+use crate::common::data::{MockDefinition, MockServerHttpResponse, Pattern, RequestRequirements};
+use crate::common::util::{path_template_to_regex, read_file};
+use crate::Regex;
+
+/// The HTTP methods that can be described by an OpenAPI path item.
+const OPERATION_METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// The maximum depth up to which references (`$ref`) are resolved, so that recursive schemas
+/// do not lead to endless recursion.
+const MAX_REFERENCE_DEPTH: usize = 16;
+
+/// The JSON Schema dialect of the schemas in OpenAPI 3.1 specifications.
+const OPENAPI_3_1_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// A mock definition that was created for an operation of an OpenAPI specification.
+pub(crate) struct OpenApiMock {
+    /// The `operationId` of the operation or, if not set, its method and path (e.g.
+    /// `GET /users/{id}`).
+    pub name: String,
+    pub definition: MockDefinition,
+}
+
+/// Reads an OpenAPI 3 specification in YAML or JSON format (depending on the file extension)
+/// and creates a mock definition for every operation.
+pub(crate) fn read_openapi_file(path: &Path) -> Result<Vec<OpenApiMock>, String> {
+    let content = read_file(path).map_err(|e| {
+        format!(
+            "Cannot read OpenAPI file '{}': {}",
+            path.to_string_lossy(),
+            e
+        )
+    })?;
+    let spec: Value = match path.extension() {
+        Some(ext) if ext == "json" => serde_json::from_slice(&content).map_err(|e| e.to_string()),
+        _ => serde_yaml::from_slice(&content).map_err(|e| e.to_string()),
+    }
+    .map_err(|e| format!("Invalid OpenAPI file '{}': {}", path.to_string_lossy(), e))?;
+
+    to_mock_definitions(&spec)
+        .map_err(|e| format!("Invalid OpenAPI file '{}': {}", path.to_string_lossy(), e))
+}
+
+fn to_mock_definitions(spec: &Value) -> Result<Vec<OpenApiMock>, String> {
+    let spec = resolve_references(spec, spec, 0)?;
+    let paths = spec
+        .get("paths")
+        .and_then(|p| p.as_object())
+        .ok_or("the specification does not contain any paths")?;
+    let base_path = base_path(&spec);
+    let version = spec
+        .get("openapi")
+        .and_then(|v| v.as_str())
+        .unwrap_or("3.0");
+
+    let mut mocks: Vec<OpenApiMock> = Vec::new();
+    for (path_template, path_item) in paths {
+        let shared_parameters = parameters(path_item);
+        for method in OPERATION_METHODS {
+            let operation = match path_item.get(*method) {
+                Some(operation) => operation,
+                None => continue,
+            };
+
+            let name = operation
+                .get("operationId")
+                .and_then(|id| id.as_str())
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| format!("{} {}", method.to_uppercase(), path_template));
+            if mocks.iter().any(|m| m.name == name) {
+                return Err(format!("operationId '{}' is used more than once", name));
+            }
+
+            let mut all_parameters = shared_parameters.clone();
+            all_parameters.extend(parameters(operation));
+
+            let request = to_request_requirements(
+                &format!("{}{}", base_path, path_template),
+                method,
+                &all_parameters,
+                operation.get("requestBody"),
+                version,
+            )
+            .map_err(|e| format!("{}: {}", name, e))?;
+
+            mocks.push(OpenApiMock {
+                name,
+                definition: MockDefinition {
+                    request,
+                    response: to_response(operation.get("responses")),
+                },
+            });
+        }
+    }
+
+    Ok(mocks)
+}
+
+/// Replaces all local references (`{"$ref": "#/components/..."}`) by the referenced value.
+fn resolve_references(value: &Value, spec: &Value, depth: usize) -> Result<Value, String> {
+    match value {
+        Value::Object(object) => {
+            if let Some(reference) = object.get("$ref").and_then(|r| r.as_str()) {
+                if depth >= MAX_REFERENCE_DEPTH {
+                    return Ok(Value::Object(Map::new()));
+                }
+                let target = reference
+                    .strip_prefix('#')
+                    .and_then(|pointer| spec.pointer(pointer))
+                    .ok_or_else(|| format!("cannot resolve reference '{}'", reference))?;
+                return resolve_references(target, spec, depth + 1);
+            }
+
+            object
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), resolve_references(v, spec, depth)?)))
+                .collect::<Result<Map<String, Value>, String>>()
+                .map(Value::Object)
+        }
+        Value::Array(values) => values
+            .iter()
+            .map(|v| resolve_references(v, spec, depth))
+            .collect::<Result<Vec<Value>, String>>()
+            .map(Value::Array),
+        other => Ok(other.clone()),
+    }
+}
+
+/// Returns the path of the first server URL without a trailing slash (e.g. `/v1` for
+/// `https://example.com/v1/`).
+fn base_path(spec: &Value) -> String {
+    let url = match spec.pointer("/servers/0/url").and_then(|u| u.as_str()) {
+        Some(url) => url,
+        None => return String::new(),
+    };
+
+    let path = match url.find("://") {
+        Some(idx) => {
+            let after_scheme = &url[idx + 3..];
+            after_scheme.find('/').map_or("", |i| &after_scheme[i..])
+        }
+        None => url,
+    };
+
+    path.trim_end_matches('/').to_string()
+}
+
+fn parameters(item: &Value) -> Vec<Value> {
+    item.get("parameters")
+        .and_then(|p| p.as_array())
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn to_request_requirements(
+    path_template: &str,
+    method: &str,
+    parameters: &[Value],
+    request_body: Option<&Value>,
+    version: &str,
+) -> Result<RequestRequirements, String> {
+    let mut requirements = RequestRequirements::new();
+    requirements.method = Some(method.to_uppercase());
+
+    match path_template.contains('{') {
+        true => {
+            let regex = Regex::new(&path_template_to_regex(path_template))
+                .map_err(|e| format!("cannot convert path to a regex: {}", e))?;
+            requirements.path_matches = Some(vec![Pattern::from_regex(regex)]);
+        }
+        false => requirements.path = Some(path_template.to_string()),
+    }
+
+    for parameter in parameters {
+        if parameter.get("required") != Some(&Value::Bool(true)) {
+            continue;
+        }
+        let name = match parameter.get("name").and_then(|n| n.as_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        let target = match parameter.get("in").and_then(|i| i.as_str()) {
+            Some("query") => &mut requirements.query_param_exists,
+            Some("header") => &mut requirements.header_exists,
+            Some("cookie") => &mut requirements.cookie_exists,
+            _ => continue,
+        };
+        target.get_or_insert_with(Vec::new).push(name);
+    }
+
+    if let Some(request_body) = request_body {
+        if request_body.get("required") == Some(&Value::Bool(true)) {
+            requirements.json_body_schema = request_body
+                .get("content")
+                .and_then(|c| c.as_object())
+                .and_then(|c| c.iter().find(|(media_type, _)| media_type.contains("json")))
+                .and_then(|(_, media_type)| media_type.get("schema"))
+                .map(|schema| to_json_schema(schema, version));
+        }
+    }
+
+    Ok(requirements)
+}
+
+/// Creates a response from the first documented response of an operation.
+fn to_response(responses: Option<&Value>) -> MockServerHttpResponse {
+    let mut response = MockServerHttpResponse::new();
+
+    let responses = responses.and_then(|r| r.as_object());
+    let first_documented = responses
+        .and_then(|r| {
+            r.iter()
+                .filter_map(|(code, documented)| to_status(code).map(|status| (status, documented)))
+                .min_by_key(|(status, _)| *status)
+        })
+        .or_else(|| responses.and_then(|r| r.get("default")).map(|d| (200, d)));
+    let (status, documented) = match first_documented {
+        Some(first) => first,
+        None => {
+            response.status = Some(200);
+            return response;
+        }
+    };
+    response.status = Some(status);
+
+    let content = documented.get("content").and_then(|c| c.as_object());
+    let (media_type, content) = match content.and_then(|c| {
+        c.iter()
+            .find(|(media_type, _)| media_type.contains("json"))
+            .or_else(|| c.iter().next())
+    }) {
+        Some(found) => found,
+        None => return response,
+    };
+
+    let example = content
+        .get("example")
+        .or_else(|| {
+            content
+                .get("examples")
+                .and_then(|e| e.as_object())
+                .and_then(|e| e.values().next())
+                .and_then(|e| e.get("value"))
+        })
+        .cloned()
+        .or_else(|| content.get("schema").map(example));
+
+    if let Some(example) = example {
+        response.body = Some(match (&example, media_type.contains("json")) {
+            (Value::String(text), false) => text.clone().into_bytes(),
+            _ => example.to_string().into_bytes(),
+        });
+        response.headers = Some(vec![("content-type".to_string(), media_type.clone())]);
+    }
+
+    response
+}
+
+/// Converts a schema of an OpenAPI specification into a JSON schema. The schemas of OpenAPI 3.1
+/// are JSON schemas of draft 2020-12, which is declared with `$schema`. The schemas of OpenAPI
+/// 3.0 are converted into draft-07 schemas (see [to_draft_07](fn.to_draft_07.html)).
+fn to_json_schema(schema: &Value, version: &str) -> Value {
+    if version.starts_with("3.0") {
+        return to_draft_07(schema);
+    }
+    match schema {
+        Value::Object(object) if !object.contains_key("$schema") => {
+            let mut object = object.clone();
+            object.insert("$schema".to_string(), OPENAPI_3_1_SCHEMA_DIALECT.into());
+            Value::Object(object)
+        }
+        _ => schema.clone(),
+    }
+}
+
+/// Converts an OpenAPI 3.0 schema into a draft-07 JSON schema: `nullable` schemas also allow
+/// `null` and the boolean `exclusiveMinimum` and `exclusiveMaximum` are replaced by the numeric
+/// form. All other keywords (e.g. `example`) are copied as they are.
+fn to_draft_07(schema: &Value) -> Value {
+    let object = match schema {
+        Value::Object(object) => object,
+        _ => return schema.clone(),
+    };
+
+    let mut converted: Map<String, Value> = object
+        .iter()
+        .map(|(key, value)| {
+            let value = match (key.as_str(), value) {
+                ("properties" | "patternProperties" | "definitions", Value::Object(schemas)) => {
+                    Value::Object(
+                        schemas
+                            .iter()
+                            .map(|(name, schema)| (name.clone(), to_draft_07(schema)))
+                            .collect(),
+                    )
+                }
+                ("allOf" | "anyOf" | "oneOf" | "items", Value::Array(schemas)) => {
+                    Value::Array(schemas.iter().map(to_draft_07).collect())
+                }
+                ("items" | "additionalItems" | "additionalProperties" | "not", _) => {
+                    to_draft_07(value)
+                }
+                _ => value.clone(),
+            };
+            (key.clone(), value)
+        })
+        .collect();
+
+    for (exclusive, inclusive) in [
+        ("exclusiveMinimum", "minimum"),
+        ("exclusiveMaximum", "maximum"),
+    ] {
+        if let Some(Value::Bool(is_exclusive)) = converted.get(exclusive).cloned() {
+            converted.remove(exclusive);
+            if is_exclusive {
+                if let Some(bound) = converted.remove(inclusive) {
+                    converted.insert(exclusive.to_string(), bound);
+                }
+            }
+        }
+    }
+
+    match converted.remove("nullable") {
+        Some(Value::Bool(true)) => {
+            json!({ "anyOf": [Value::Object(converted), { "type": "null" }] })
+        }
+        _ => Value::Object(converted),
+    }
+}
+
+/// Creates a value that is valid according to the schema. Values from `example`, `default`,
+/// `const` and `enum` are preferred over generated ones.
+pub(crate) fn example(schema: &Value) -> Value {
+    let schema = match schema.as_object() {
+        Some(schema) => schema,
+        None => return Value::Null,
+    };
+
+    if let Some(value) = schema
+        .get("example")
+        .or_else(|| schema.get("default"))
+        .or_else(|| schema.get("const"))
+    {
+        return value.clone();
+    }
+    if let Some(Value::Array(values)) = schema.get("enum") {
+        if let Some(value) = values.first() {
+            return value.clone();
+        }
+    }
+    if let Some(Value::Array(schemas)) = schema.get("allOf") {
+        let mut merged = Map::new();
+        for sub_schema in schemas {
+            if let Value::Object(object) = example(sub_schema) {
+                merged.extend(object);
+            }
+        }
+        return Value::Object(merged);
+    }
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(schemas)) = schema.get(keyword) {
+            if let Some(sub_schema) = schemas.first() {
+                return example(sub_schema);
+            }
+        }
+    }
+
+    let schema_type = match schema.get("type") {
+        Some(Value::String(t)) => t.as_str(),
+        Some(Value::Array(ts)) => ts.iter().filter_map(|t| t.as_str()).next().unwrap_or(""),
+        _ if schema.contains_key("properties") => "object",
+        _ if schema.contains_key("items") => "array",
+        _ => "",
+    };
+
+    match schema_type {
+        "object" => Value::Object(
+            schema
+                .get("properties")
+                .and_then(|p| p.as_object())
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .map(|(name, property_schema)| (name.clone(), example(property_schema)))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+        "array" => match schema.get("items") {
+            Some(item_schema) => Value::Array(vec![example(item_schema)]),
+            None => Value::Array(Vec::new()),
+        },
+        "string" => Value::String(
+            match schema.get("format").and_then(|f| f.as_str()) {
+                Some("date-time") => "1970-01-01T00:00:00Z",
+                Some("date") => "1970-01-01",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("email") => "user@example.com",
+                Some("uri") => "https://example.com",
+                _ => "string",
+            }
+            .to_string(),
+        ),
+        "integer" => schema
+            .get("minimum")
+            .and_then(|m| m.as_f64())
+            .map_or(Value::from(0), |m| Value::from(m.ceil() as i64)),
+        "number" => schema
+            .get("minimum")
+            .cloned()
+            .unwrap_or_else(|| Value::from(0.0)),
+        "boolean" => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+/// Converts a response code of an OpenAPI specification into a status code. Ranges (e.g. `2XX`)
+/// are mapped to the first code they cover.
+fn to_status(code: &str) -> Option<u16> {
+    match code.strip_suffix("XX") {
+        Some(class) => class.parse::<u16>().ok().map(|c| c * 100),
+        None => code.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::common::json_schema::validate;
+    use crate::common::openapi::{
+        example, to_draft_07, to_json_schema, to_mock_definitions, to_status,
+    };
+
+    #[test]
+    fn to_status_test() {
+        assert_eq!(to_status("201"), Some(201));
+        assert_eq!(to_status("4XX"), Some(400));
+        assert_eq!(to_status("default"), None);
+        assert_eq!(to_status("x-extension"), None);
+    }
+
+    #[test]
+    fn to_mock_definitions_test() {
+        // Arrange
+        let spec = json!({
+            "openapi": "3.0.0",
+            "servers": [{ "url": "https://api.example.com/v1/" }],
+            "paths": {
+                "/users/{id}": {
+                    "parameters": [{ "name": "id", "in": "path", "required": true }],
+                    "get": {
+                        "operationId": "getUser",
+                        "parameters": [{ "name": "x-api-key", "in": "header", "required": true }],
+                        "responses": {
+                            "404": { "description": "Not found" },
+                            "200": {
+                                "description": "A user",
+                                "content": {
+                                    "application/json": {
+                                        "schema": { "$ref": "#/components/schemas/User" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "User": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string", "example": "Fred" } }
+                    }
+                }
+            }
+        });
+
+        // Act
+        let mocks = to_mock_definitions(&spec).unwrap();
+
+        // Assert
+        assert_eq!(mocks.len(), 1);
+        assert_eq!(mocks[0].name, "getUser");
+        let request = &mocks[0].definition.request;
+        assert_eq!(request.method, Some("GET".to_string()));
+        assert_eq!(
+            request.path_matches.as_ref().unwrap()[0].regex.as_str(),
+            "^/v1/users/[^/]+$"
+        );
+        assert_eq!(request.header_exists, Some(vec!["x-api-key".to_string()]));
+        let response = &mocks[0].definition.response;
+        assert_eq!(response.status, Some(200));
+        assert_eq!(response.body, Some(br#"{"name":"Fred"}"#.to_vec()));
+    }
+
+    #[test]
+    fn to_draft_07_test() {
+        // Arrange
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "nickname": { "type": "string", "nullable": true },
+                "example": { "type": "string", "nullable": true },
+                "price": { "type": "number", "minimum": 0, "exclusiveMinimum": true },
+                "age": { "type": "integer", "maximum": 150, "exclusiveMaximum": false }
+            },
+            "example": { "nickname": null, "nullable": true }
+        });
+
+        // Act
+        let converted = to_draft_07(&schema);
+
+        // Assert
+        assert_eq!(
+            converted,
+            json!({
+                "type": "object",
+                "properties": {
+                    "nickname": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                    "example": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                    "price": { "type": "number", "exclusiveMinimum": 0 },
+                    "age": { "type": "integer", "maximum": 150 }
+                },
+                "example": { "nickname": null, "nullable": true }
+            })
+        );
+        assert!(validate(&converted, &json!({ "nickname": null, "price": 1 })).is_empty());
+        assert_eq!(validate(&converted, &json!({ "price": 0 })).len(), 1);
+    }
+
+    #[test]
+    fn to_json_schema_test() {
+        let schema = json!({ "type": "string", "nullable": true });
+
+        assert_eq!(
+            to_json_schema(&schema, "3.0.3"),
+            json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] })
+        );
+        assert_eq!(
+            to_json_schema(&json!({ "type": ["string", "null"] }), "3.1.0"),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": ["string", "null"]
+            })
+        );
+    }
+
+    #[test]
+    fn example_is_valid_test() {
+        // Arrange
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "minimum": 1 },
+                "created": { "type": "string", "format": "date-time" },
+                "status": { "enum": ["active", "inactive"] },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            }
+        });
+
+        // Act
+        let value = example(&schema);
+
+        // Assert
+        assert_eq!(
+            value,
+            json!({
+                "id": 1,
+                "created": "1970-01-01T00:00:00Z",
+                "status": "active",
+                "tags": ["string"]
+            })
+        );
+        assert!(validate(&schema, &value).is_empty());
+    }
+}
@@ -0,0 +1,581 @@
+//! Converts mocks and the requests that they received into
+//! [Pact v3](https://github.com/pact-foundation/pact-specification/tree/version-3) interactions.
+//! Matchers are converted into Pact matching rules where possible. Matchers without a Pact
+//! equivalent are ignored and reported as warnings.
+
+use std::collections::HashSet;
+
+use serde_json::{json, Map, Value};
+
+use crate::common::data::{ActiveMock, HttpMockRequest, RequestRequirements};
+use crate::common::util::escape_regex;
+
+/// Creates a Pact interaction for every mock that received at least one request. The first
+/// request that a mock received provides the example values of the interaction, the matchers
+/// of the mock provide the matching rules and the configured response (or the first response
+/// of a sequence) the expected response. Mocks are expected in the order of their IDs.
+pub(crate) fn to_interactions(
+    mocks: &[(ActiveMock, Vec<HttpMockRequest>)],
+    warnings: &mut Vec<String>,
+) -> Vec<Value> {
+    let mut descriptions = HashSet::new();
+    let mut interactions = Vec::new();
+
+    for (mock, requests) in mocks {
+        let example = match requests.first() {
+            Some(example) => example,
+            None => {
+                warnings.push(format!(
+                    "mock {} did not receive any requests and was not exported",
+                    mock.id
+                ));
+                continue;
+            }
+        };
+
+        let mut mock_warnings = Vec::new();
+        let mut interaction = to_interaction(mock, example, &mut mock_warnings);
+        warnings.extend(
+            mock_warnings
+                .into_iter()
+                .map(|w| format!("mock {}: {}", mock.id, w)),
+        );
+
+        // Pact identifies interactions by their description, so it must be unique.
+        let base_description = interaction["description"].as_str().unwrap().to_string();
+        let mut description = base_description.clone();
+        let mut n = 1;
+        while !descriptions.insert(description.clone()) {
+            n += 1;
+            description = format!("{} ({})", base_description, n);
+        }
+        interaction["description"] = json!(description);
+
+        interactions.push(interaction);
+    }
+
+    interactions
+}
+
+/// Creates a Pact file for the provided interactions. If the content of an existing Pact file
+/// is provided, the interactions are merged into it: existing interactions with the same
+/// description are replaced and all other existing interactions are kept.
+pub(crate) fn to_pact(
+    existing: Option<&str>,
+    consumer: &str,
+    provider: &str,
+    interactions: Vec<Value>,
+) -> Result<Value, String> {
+    let mut merged = Vec::new();
+    if let Some(existing) = existing {
+        let existing: Value = serde_json::from_str(existing)
+            .map_err(|e| format!("The existing Pact file is not valid JSON: {}", e))?;
+        let existing_consumer = existing["consumer"]["name"].as_str().unwrap_or_default();
+        let existing_provider = existing["provider"]["name"].as_str().unwrap_or_default();
+        if existing_consumer != consumer || existing_provider != provider {
+            return Err(format!(
+                "The existing Pact file belongs to consumer '{}' and provider '{}'",
+                existing_consumer, existing_provider
+            ));
+        }
+
+        let new_descriptions: HashSet<&Value> =
+            interactions.iter().map(|i| &i["description"]).collect();
+        if let Some(existing_interactions) = existing["interactions"].as_array() {
+            merged.extend(
+                existing_interactions
+                    .iter()
+                    .filter(|i| !new_descriptions.contains(&i["description"]))
+                    .cloned(),
+            );
+        }
+    }
+    merged.extend(interactions);
+
+    Ok(json!({
+        "consumer": { "name": consumer },
+        "provider": { "name": provider },
+        "interactions": merged,
+        "metadata": {
+            "pactSpecification": { "version": "3.0.0" },
+            "httpmock": { "version": env!("CARGO_PKG_VERSION") },
+        }
+    }))
+}
+
+fn to_interaction(
+    mock: &ActiveMock,
+    example: &HttpMockRequest,
+    warnings: &mut Vec<String>,
+) -> Value {
+    let request = to_pact_request(&mock.definition.request, example, warnings);
+
+    let mut response = &mock.definition.response;
+    if let Some(sequence) = &response.sequence {
+        if sequence.len() > 1 {
+            warnings.push(
+                "the mock responds with a sequence, only the first response was exported"
+                    .to_string(),
+            );
+        }
+        response = response.for_request(0);
+    }
+
+    let status = response.status.unwrap_or(200);
+    let headers = response.headers.as_deref().unwrap_or_default();
+    let mut pact_response = json!({ "status": status, "headers": to_header_map(headers) });
+    if let Some(body) = response.body.as_deref().filter(|b| !b.is_empty()) {
+        // Bodies set with `Then::json_body` do not have a content type.
+        let json = find_value(headers, "content-type").map_or(true, |v| v.contains("json"));
+        if let Some(body) = to_pact_body(body, json, "response", warnings) {
+            pact_response["body"] = body;
+        }
+    }
+
+    json!({
+        "description": format!("{} {} returns {}", example.method, example.path, status),
+        "providerStates": [],
+        "request": request,
+        "response": pact_response,
+    })
+}
+
+fn to_pact_request(
+    rr: &RequestRequirements,
+    example: &HttpMockRequest,
+    warnings: &mut Vec<String>,
+) -> Value {
+    let example_headers = example.headers.as_deref().unwrap_or_default();
+    let mut header_rules = Map::new();
+    let mut header_names = Vec::new();
+
+    for (name, _) in rr.headers.iter().flatten() {
+        header_names.push(name.to_lowercase());
+    }
+    for name in rr.header_exists.iter().flatten() {
+        header_names.push(name.to_lowercase());
+        add_rule(&mut header_rules, name, json!({ "match": "type" }));
+    }
+    for (name, pattern) in rr.header_matches.iter().flatten() {
+        header_names.push(name.to_lowercase());
+        add_rule(
+            &mut header_rules,
+            name,
+            regex_rule(&full_match(pattern.regex.as_str())),
+        );
+    }
+
+    if rr.basic_auth.is_some() || rr.bearer_token.is_some() || rr.jwt_claims.is_some() {
+        header_names.push("authorization".to_string());
+    }
+
+    // Content types match regardless of their parameters and casing.
+    if let Some(content_type) = &rr.content_type {
+        header_names.push("content-type".to_string());
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        let regex = format!(r"(?i){}(\s*;.*)?", escape_regex(media_type));
+        add_rule(&mut header_rules, "Content-Type", regex_rule(&regex));
+    }
+
+    // The cookie header usually holds other cookies too, so cookies are matched by regex.
+    for (name, value) in rr.cookies.iter().flatten() {
+        header_names.push("cookie".to_string());
+        let regex = format!(
+            r"(.*;\s*)?{}={}(;.*)?",
+            escape_regex(name),
+            escape_regex(value)
+        );
+        add_rule(&mut header_rules, "Cookie", regex_rule(&regex));
+    }
+    for name in rr.cookie_exists.iter().flatten() {
+        header_names.push("cookie".to_string());
+        let regex = format!(r"(.*;\s*)?{}=.*", escape_regex(name));
+        add_rule(&mut header_rules, "Cookie", regex_rule(&regex));
+    }
+
+    let mut path_rules = Vec::new();
+    for pattern in rr.path_matches.iter().flatten() {
+        path_rules.push(regex_rule(&full_match(pattern.regex.as_str())));
+    }
+    for substring in rr.path_contains.iter().flatten() {
+        path_rules.push(regex_rule(&format!(".*{}.*", escape_regex(substring))));
+    }
+    for prefix in rr.path_prefix.iter().flatten() {
+        path_rules.push(regex_rule(&format!("{}.*", escape_regex(prefix))));
+    }
+    for suffix in rr.path_suffix.iter().flatten() {
+        path_rules.push(regex_rule(&format!(".*{}", escape_regex(suffix))));
+    }
+
+    let mut query = Map::new();
+    for (name, value) in example.query_params.iter().flatten() {
+        let values = query.entry(name.clone()).or_insert_with(|| json!([]));
+        values.as_array_mut().unwrap().push(json!(value));
+    }
+    let mut query_rules = Map::new();
+    for name in rr.query_param_exists.iter().flatten() {
+        add_rule(&mut query_rules, name, json!({ "match": "type" }));
+    }
+    for (name, pattern) in rr.query_param_matches.iter().flatten() {
+        add_rule(
+            &mut query_rules,
+            name,
+            regex_rule(&full_match(pattern.regex.as_str())),
+        );
+    }
+
+    let json = rr.json_body.is_some()
+        || rr.json_body_ignoring_paths.is_some()
+        || rr.json_body_includes.is_some()
+        || rr.json_body_paths.is_some()
+        || rr.json_body_schema.is_some();
+    let mut body_rules = Map::new();
+    if rr.json_body_includes.is_some() {
+        add_rule(&mut body_rules, "$", json!({ "match": "type" }));
+    }
+    for path in rr
+        .json_body_ignoring_paths
+        .iter()
+        .flat_map(|json| json.ignored_paths.iter())
+    {
+        add_rule(&mut body_rules, path, json!({ "match": "type" }));
+    }
+    if rr.json_body_schema.is_some() {
+        warnings.push("the JSON schema matcher was exported as type matching".to_string());
+        add_rule(&mut body_rules, "$", json!({ "match": "type" }));
+    }
+    for requirement in rr.json_body_paths.iter().flatten() {
+        let rule = match requirement.value {
+            Some(_) => json!({ "match": "equality" }),
+            None => json!({ "match": "type" }),
+        };
+        add_rule(&mut body_rules, &requirement.path, rule);
+    }
+    for substring in rr.body_contains.iter().flatten() {
+        let regex = format!("(?s).*{}.*", escape_regex(substring));
+        add_rule(&mut body_rules, "$", regex_rule(&regex));
+    }
+    for pattern in rr.body_matches.iter().flatten() {
+        add_rule(
+            &mut body_rules,
+            "$",
+            regex_rule(&full_match(pattern.regex.as_str())),
+        );
+    }
+    if rr.x_www_form_urlencoded.is_some() || rr.x_www_form_urlencoded_key_exists.is_some() {
+        warnings.push(
+            "form parameter matchers are not supported, the body was exported as it was received"
+                .to_string(),
+        );
+    }
+
+    let body = example
+        .body
+        .as_deref()
+        .filter(|b| !b.is_empty() && (json || has_body_requirements(rr)))
+        .and_then(|b| to_pact_body(b, json, "request", warnings));
+    if body.is_some() {
+        header_names.push("content-type".to_string());
+    }
+
+    for (unsupported, is_set) in [
+        ("client_cert_cn", rr.client_cert_cn.is_some()),
+        ("http_version", rr.http_version.is_some()),
+        ("host", rr.host.is_some()),
+        ("listener", rr.listener.is_some()),
+        ("remote_addr", rr.remote_addr.is_some()),
+        ("remote_addr_matches", rr.remote_addr_matches.is_some()),
+        ("body_xpaths", rr.body_xpaths.is_some()),
+        ("xml_body", rr.xml_body.is_some()),
+        ("query_param_missing", rr.query_param_missing.is_some()),
+        ("query_objects", rr.query_objects.is_some()),
+        ("query_params_raw", rr.query_params_raw),
+        ("header_missing", rr.header_missing.is_some()),
+        ("header_values", rr.header_values.is_some()),
+        ("accepts", rr.accepts.is_some()),
+        ("multipart_parts", rr.multipart_parts.is_some()),
+        ("path_not_contains", rr.path_not_contains.is_some()),
+        ("path_not_matches", rr.path_not_matches.is_some()),
+        ("body_not_contains", rr.body_not_contains.is_some()),
+        ("body_not_matches", rr.body_not_matches.is_some()),
+        ("body_len", rr.body_len.is_some()),
+        ("ndjson_lines", rr.ndjson_lines.is_some()),
+        ("ndjson_line_count", rr.ndjson_line_count.is_some()),
+        ("chunked_body", rr.chunked_body),
+        ("hmac_signatures", rr.hmac_signatures.is_some()),
+        ("protobuf_body", rr.protobuf_body.is_some()),
+        ("graphql_operation", rr.graphql_operation.is_some()),
+        ("graphql_variables", rr.graphql_variables.is_some()),
+        ("any_of", rr.any_of.is_some()),
+    ] {
+        if is_set {
+            warnings.push(format!(
+                "the '{}' matcher is not supported and was ignored",
+                unsupported
+            ));
+        }
+    }
+    if rr.active_from.is_some() || rr.active_until.is_some() {
+        warnings
+            .push("the activation window of the mock is not supported and was ignored".to_string());
+    }
+    if rr.matchers.as_ref().map_or(false, |m| !m.is_empty()) {
+        warnings.push("custom matcher functions are not supported and were ignored".to_string());
+    }
+    for matcher in rr.custom_matchers.iter().flatten() {
+        warnings.push(format!(
+            "the custom matcher '{}' is not supported and was ignored",
+            matcher
+        ));
+    }
+
+    let headers: Vec<(String, String)> = example_headers
+        .iter()
+        .filter(|(name, _)| header_names.contains(&name.to_lowercase()))
+        .cloned()
+        .collect();
+    let mut headers = to_header_map(&headers);
+    if json && body.is_some() && find_value(example_headers, "content-type").is_none() {
+        headers.insert("Content-Type".to_string(), json!("application/json"));
+    }
+
+    let mut pact_request = json!({
+        "method": example.method.to_uppercase(),
+        "path": example.path,
+    });
+    if !query.is_empty() {
+        pact_request["query"] = Value::Object(query);
+    }
+    if !headers.is_empty() {
+        pact_request["headers"] = Value::Object(headers);
+    }
+    if let Some(body) = body {
+        pact_request["body"] = body;
+    }
+
+    let mut rules = Map::new();
+    if !path_rules.is_empty() {
+        rules.insert(
+            "path".to_string(),
+            json!({ "matchers": path_rules, "combine": "AND" }),
+        );
+    }
+    for (category, category_rules) in [
+        ("query", query_rules),
+        ("header", header_rules),
+        ("body", body_rules),
+    ] {
+        if !category_rules.is_empty() {
+            rules.insert(category.to_string(), Value::Object(category_rules));
+        }
+    }
+    if !rules.is_empty() {
+        pact_request["matchingRules"] = Value::Object(rules);
+    }
+
+    pact_request
+}
+
+fn has_body_requirements(rr: &RequestRequirements) -> bool {
+    rr.body.is_some()
+        || rr.body_base64.is_some()
+        || rr.body_contains.is_some()
+        || rr.body_matches.is_some()
+        || rr.x_www_form_urlencoded.is_some()
+        || rr.x_www_form_urlencoded_key_exists.is_some()
+}
+
+/// Converts a body into a JSON value if requested and possible, or else into a string. Pact
+/// cannot represent binary bodies, so these are omitted.
+fn to_pact_body(body: &[u8], json: bool, kind: &str, warnings: &mut Vec<String>) -> Option<Value> {
+    if json {
+        if let Ok(value) = serde_json::from_slice(body) {
+            return Some(value);
+        }
+    }
+    match std::str::from_utf8(body) {
+        Ok(text) => Some(json!(text)),
+        Err(_) => {
+            warnings.push(format!("the {} body is binary and was not exported", kind));
+            None
+        }
+    }
+}
+
+/// Pact regexes must match the whole value, while httpmock regexes may match any part of it.
+fn full_match(regex: &str) -> String {
+    let prefix = if regex.starts_with('^') { "" } else { ".*" };
+    let suffix = if regex.ends_with('$') && !regex.ends_with("\\$") {
+        ""
+    } else {
+        ".*"
+    };
+    format!("{}(?:{}){}", prefix, regex, suffix)
+}
+
+fn regex_rule(regex: &str) -> Value {
+    json!({ "match": "regex", "regex": regex })
+}
+
+fn add_rule(rules: &mut Map<String, Value>, key: &str, rule: Value) {
+    let entry = rules
+        .entry(key.to_string())
+        .or_insert_with(|| json!({ "matchers": [], "combine": "AND" }));
+    entry["matchers"].as_array_mut().unwrap().push(rule);
+}
+
+fn to_header_map(headers: &[(String, String)]) -> Map<String, Value> {
+    let mut map: Map<String, Value> = Map::new();
+    for (name, value) in headers {
+        match map.get_mut(name) {
+            Some(Value::String(existing)) => {
+                existing.push_str(", ");
+                existing.push_str(value);
+            }
+            _ => {
+                map.insert(name.clone(), json!(value));
+            }
+        }
+    }
+    map
+}
+
+fn find_value<'a>(pairs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    pairs
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "regex")]
+    use regex::Regex;
+    use serde_json::json;
+
+    use crate::common::data::{
+        ActiveMock, HttpMockRequest, MockDefinition, MockServerHttpResponse, Pattern,
+        RequestRequirements,
+    };
+    use crate::common::pact::{full_match, to_interactions, to_pact};
+
+    fn mock(id: usize, request: RequestRequirements, body: &str) -> ActiveMock {
+        let mut response = MockServerHttpResponse::new();
+        response.status = Some(201);
+        response.headers = Some(vec![(
+            "content-type".to_string(),
+            "application/json".to_string(),
+        )]);
+        response.body = Some(body.as_bytes().to_vec());
+        ActiveMock::new(id, MockDefinition::new(request, response), false)
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn to_interactions_test() {
+        // Arrange
+        let mut request = RequestRequirements::new()
+            .with_method("POST".to_string())
+            .with_path("/users".to_string());
+        request.path_matches = Some(vec![Pattern::from_regex(Regex::new("^/users").unwrap())]);
+        request.header_exists = Some(vec!["Authorization".to_string()]);
+        request.json_body_includes = Some(vec![json!({ "name": "Fred" })]);
+        request.client_cert_cn = Some("client".to_string());
+
+        let example = HttpMockRequest::new("POST".to_string(), "/users".to_string())
+            .with_headers(vec![
+                ("authorization".to_string(), "Bearer 1".to_string()),
+                ("user-agent".to_string(), "test".to_string()),
+            ])
+            .with_query_params(vec![("page".to_string(), "1".to_string())])
+            .with_body(br#"{"name":"Fred","age":30}"#.to_vec());
+        let mocks = vec![
+            (mock(1, request, r#"{"id":1}"#), vec![example]),
+            (mock(2, RequestRequirements::new(), "{}"), vec![]),
+        ];
+        let mut warnings = Vec::new();
+
+        // Act
+        let interactions = to_interactions(&mocks, &mut warnings);
+
+        // Assert
+        assert_eq!(
+            interactions,
+            vec![json!({
+                "description": "POST /users returns 201",
+                "providerStates": [],
+                "request": {
+                    "method": "POST",
+                    "path": "/users",
+                    "query": { "page": ["1"] },
+                    "headers": {
+                        "authorization": "Bearer 1",
+                        "Content-Type": "application/json"
+                    },
+                    "body": { "name": "Fred", "age": 30 },
+                    "matchingRules": {
+                        "path": {
+                            "matchers": [{ "match": "regex", "regex": "(?:^/users).*" }],
+                            "combine": "AND"
+                        },
+                        "header": {
+                            "Authorization": { "matchers": [{ "match": "type" }], "combine": "AND" }
+                        },
+                        "body": {
+                            "$": { "matchers": [{ "match": "type" }], "combine": "AND" }
+                        }
+                    }
+                },
+                "response": {
+                    "status": 201,
+                    "headers": { "content-type": "application/json" },
+                    "body": { "id": 1 }
+                }
+            })]
+        );
+        assert_eq!(
+            warnings,
+            vec![
+                "mock 1: the 'client_cert_cn' matcher is not supported and was ignored",
+                "mock 2 did not receive any requests and was not exported"
+            ]
+        );
+    }
+
+    #[test]
+    fn to_pact_merge_test() {
+        // Arrange
+        let existing = json!({
+            "consumer": { "name": "web" },
+            "provider": { "name": "api" },
+            "interactions": [
+                { "description": "GET /a returns 200" },
+                { "description": "GET /b returns 200", "old": true }
+            ]
+        })
+        .to_string();
+        let interactions = vec![json!({ "description": "GET /b returns 200" })];
+
+        // Act
+        let pact = to_pact(Some(&existing), "web", "api", interactions.clone()).unwrap();
+        let other_provider = to_pact(Some(&existing), "web", "billing", interactions);
+
+        // Assert
+        assert_eq!(
+            pact["interactions"],
+            json!([
+                { "description": "GET /a returns 200" },
+                { "description": "GET /b returns 200" }
+            ])
+        );
+        assert_eq!(pact["metadata"]["pactSpecification"]["version"], "3.0.0");
+        assert!(other_provider.is_err());
+    }
+
+    #[test]
+    fn full_match_test() {
+        assert_eq!(full_match("^/users/\\d+$"), "(?:^/users/\\d+$)");
+        assert_eq!(full_match("users"), ".*(?:users).*");
+        assert_eq!(full_match("price\\$"), ".*(?:price\\$).*");
+    }
+}
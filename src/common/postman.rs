@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::common::data::{MockDefinition, MockServerHttpResponse, Pattern, RequestRequirements};
+use crate::common::har::SKIPPED_RESPONSE_HEADERS;
+use crate::common::util::read_file;
+use crate::Regex;
+
+/// The separator between the folder names and the request name in the name path of a request.
+const NAME_PATH_SEPARATOR: &str = " / ";
+
+/// A Postman collection that was converted into mock definitions.
+pub(crate) struct PostmanCollection {
+    /// The mock definitions together with the name path of their request (e.g.
+    /// `Users / Get by id`), in the order of the collection.
+    pub mocks: Vec<(String, MockDefinition)>,
+    /// Descriptions of all requests that were skipped.
+    pub warnings: Vec<String>,
+}
+
+/// Reads a Postman collection (format v2.0 or v2.1) and creates a mock definition for every
+/// request with at least one saved example. Variables in request URLs (`{{name}}`) are resolved
+/// from the provided variables first and from the collection variables second.
+pub(crate) fn read_postman_collection(
+    path: &Path,
+    variables: &HashMap<String, String>,
+) -> Result<PostmanCollection, String> {
+    let error = |message: String| {
+        format!(
+            "Invalid Postman collection '{}': {}",
+            path.to_string_lossy(),
+            message
+        )
+    };
+
+    let content = read_file(path).map_err(|e| {
+        format!(
+            "Cannot read Postman collection '{}': {}",
+            path.to_string_lossy(),
+            e
+        )
+    })?;
+    let collection: Collection =
+        serde_json::from_slice(&content).map_err(|e| error(e.to_string()))?;
+
+    let mut resolved_variables: HashMap<String, String> = collection
+        .variable
+        .iter()
+        .filter(|v| !v.disabled)
+        .filter_map(|v| Some((v.key.clone()?, variable_value(v.value.as_ref()?))))
+        .collect();
+    resolved_variables.extend(variables.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+    let mut converted = PostmanCollection {
+        mocks: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let mut without_examples = Vec::new();
+    collect_mocks(
+        &collection.item,
+        "",
+        &resolved_variables,
+        &mut converted,
+        &mut without_examples,
+    )
+    .map_err(error)?;
+
+    if !without_examples.is_empty() {
+        converted.warnings.push(format!(
+            "requests without saved examples were skipped: {}",
+            without_examples.join(", ")
+        ));
+    }
+
+    Ok(converted)
+}
+
+fn collect_mocks(
+    items: &[Item],
+    parent: &str,
+    variables: &HashMap<String, String>,
+    collection: &mut PostmanCollection,
+    without_examples: &mut Vec<String>,
+) -> Result<(), String> {
+    for item in items {
+        let name = item.name.as_deref().unwrap_or_default();
+        let name_path = match parent.is_empty() {
+            true => name.to_string(),
+            false => format!("{}{}{}", parent, NAME_PATH_SEPARATOR, name),
+        };
+
+        if let Some(children) = &item.item {
+            collect_mocks(
+                children,
+                &name_path,
+                variables,
+                collection,
+                without_examples,
+            )?;
+            continue;
+        }
+
+        let request = match &item.request {
+            Some(request) => request,
+            None => continue,
+        };
+        let example = match item.response.first() {
+            Some(example) => example,
+            None => {
+                without_examples.push(format!("'{}'", name_path));
+                continue;
+            }
+        };
+        if collection.mocks.iter().any(|(n, _)| n == &name_path) {
+            collection.warnings.push(format!(
+                "request '{}' was skipped, because another request has the same name",
+                name_path
+            ));
+            continue;
+        }
+
+        let requirements = to_request_requirements(request, variables)
+            .map_err(|e| format!("request '{}': {}", name_path, e))?;
+        let response = to_response(example);
+        collection
+            .mocks
+            .push((name_path, MockDefinition::new(requirements, response)));
+    }
+
+    Ok(())
+}
+
+fn to_request_requirements(
+    request: &Request,
+    variables: &HashMap<String, String>,
+) -> Result<RequestRequirements, String> {
+    let (method, url) = match request {
+        Request::Url(url) => (None, Some(PostmanUrl::Raw(url.clone()))),
+        Request::Object { method, url } => (method.as_deref(), url.clone()),
+    };
+
+    let path = match url {
+        Some(PostmanUrl::Object {
+            path: Some(segments),
+            ..
+        }) => format!("/{}", to_path_segments(&segments).join("/")),
+        Some(PostmanUrl::Object { raw: Some(raw), .. }) | Some(PostmanUrl::Raw(raw)) => {
+            raw_path(&resolve_variables(&raw, variables))
+        }
+        _ => return Err("the request has no URL".to_string()),
+    };
+    let path = resolve_variables(&path, variables);
+
+    let mut requirements = RequestRequirements::new();
+    requirements.method = Some(method.unwrap_or("GET").to_uppercase());
+
+    // Path variables (`:id`) and unresolved variables match any value of a path segment.
+    let is_variable = |segment: &str| segment.starts_with(':') || segment.contains("{{");
+    if path.split('/').any(is_variable) {
+        let regex = path
+            .split('/')
+            .map(|segment| match is_variable(segment) {
+                true => "[^/]+".to_string(),
+                false => regex::escape(segment),
+            })
+            .collect::<Vec<String>>()
+            .join("/");
+        let regex = Regex::new(&format!("^{}$", regex))
+            .map_err(|e| format!("cannot create path pattern: {}", e))?;
+        requirements.path_matches = Some(vec![Pattern::from_regex(regex)]);
+    } else {
+        requirements.path = Some(path);
+    }
+
+    Ok(requirements)
+}
+
+fn to_response(example: &Example) -> MockServerHttpResponse {
+    let mut response = MockServerHttpResponse::new();
+    response.status = Some(example.code.unwrap_or(200));
+
+    let headers: Vec<(String, String)> = example
+        .header
+        .iter()
+        .flatten()
+        .filter(|h| !h.disabled)
+        .filter(|h| !SKIPPED_RESPONSE_HEADERS.contains(&h.key.to_lowercase().as_str()))
+        .map(|h| (h.key.clone(), h.value.clone()))
+        .collect();
+    if !headers.is_empty() {
+        response.headers = Some(headers);
+    }
+
+    response.body = example
+        .body
+        .as_ref()
+        .filter(|b| !b.is_empty())
+        .map(|b| b.clone().into_bytes());
+
+    response
+}
+
+/// Returns the path of a raw URL, which may lack the scheme or start with an unresolved
+/// variable (e.g. `{{baseUrl}}/users`).
+fn raw_path(raw: &str) -> String {
+    let without_query = raw
+        .split(|c| c == '?' || c == '#')
+        .next()
+        .unwrap_or_default();
+    let without_scheme = match without_query.find("://") {
+        Some(idx) => &without_query[idx + 3..],
+        None => without_query,
+    };
+    match without_scheme.find('/') {
+        Some(idx) => without_scheme[idx..].to_string(),
+        None => "/".to_string(),
+    }
+}
+
+fn to_path_segments(segments: &[Value]) -> Vec<String> {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Value::String(s) => s.clone(),
+            // Path segments can also be objects like `{"type": "string", "value": "users"}`.
+            other => other
+                .get("value")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        })
+        .collect()
+}
+
+/// Replaces all variables (`{{name}}`) with their value. Unknown variables are kept.
+fn resolve_variables(text: &str, variables: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+        result.push_str(&rest[..start]);
+        match variables.get(rest[start + 2..end].trim()) {
+            Some(value) => result.push_str(value),
+            None => result.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+fn variable_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+struct Collection {
+    #[serde(default)]
+    item: Vec<Item>,
+    #[serde(default)]
+    variable: Vec<Variable>,
+}
+
+#[derive(Deserialize)]
+struct Item {
+    name: Option<String>,
+    /// The child items if this item is a folder.
+    item: Option<Vec<Item>>,
+    request: Option<Request>,
+    #[serde(default)]
+    response: Vec<Example>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Request {
+    Url(String),
+    Object {
+        method: Option<String>,
+        url: Option<PostmanUrl>,
+    },
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+enum PostmanUrl {
+    Raw(String),
+    Object {
+        raw: Option<String>,
+        path: Option<Vec<Value>>,
+    },
+}
+
+#[derive(Deserialize)]
+struct Example {
+    code: Option<u16>,
+    header: Option<Vec<Header>>,
+    body: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    key: String,
+    value: String,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(Deserialize)]
+struct Variable {
+    key: Option<String>,
+    value: Option<Value>,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::common::postman::{raw_path, resolve_variables};
+
+    #[test]
+    fn resolve_variables_test() {
+        let variables: HashMap<String, String> = vec![("version".to_string(), "v2".to_string())]
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            resolve_variables("/api/{{version}}/users/{{ version }}", &variables),
+            "/api/v2/users/v2"
+        );
+        assert_eq!(
+            resolve_variables("/{{unknown}}/users", &variables),
+            "/{{unknown}}/users"
+        );
+        assert_eq!(resolve_variables("/users/{{", &variables), "/users/{{");
+    }
+
+    #[test]
+    fn raw_path_test() {
+        assert_eq!(raw_path("https://example.com/users?page=1"), "/users");
+        assert_eq!(raw_path("{{baseUrl}}/users/:id"), "/users/:id");
+        assert_eq!(raw_path("example.com"), "/");
+    }
+}
@@ -0,0 +1,177 @@
+//! Compares protobuf encoded messages without knowing their schema. Messages are compared on
+//! the level of the [wire format](https://protobuf.dev/programming-guides/encoding/), so that
+//! encoders may write fields in any order.
+
+use std::convert::TryFrom;
+
+/// A field of a protobuf encoded message.
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    number: u64,
+    wire_type: u8,
+    /// The encoded value without the length prefix of length-delimited fields.
+    value: Vec<u8>,
+}
+
+/// Returns the canonical encoding of a protobuf message: its fields ordered by field number
+/// (keeping the order of repeated fields) with the fields of embedded messages ordered the
+/// same way. Two encodings of the same message have the same canonical encoding. Returns
+/// `None` if the data is not a valid protobuf message.
+///
+/// Without a schema, length-delimited fields cannot be told apart from embedded messages.
+/// They are treated as embedded messages whenever they can be parsed as one.
+pub(crate) fn canonical_form(message: &[u8]) -> Option<Vec<u8>> {
+    let mut encoded = Vec::with_capacity(message.len());
+    for field in canonical_fields(message)? {
+        encode_varint(&mut encoded, (field.number << 3) | field.wire_type as u64);
+        if field.wire_type == 2 {
+            encode_varint(&mut encoded, field.value.len() as u64);
+        }
+        encoded.extend_from_slice(&field.value);
+    }
+    Some(encoded)
+}
+
+/// Returns the numbers of the top-level fields whose values differ between two messages.
+/// Returns `None` if one of them is not a valid protobuf message.
+pub(crate) fn differing_fields(expected: &[u8], actual: &[u8]) -> Option<Vec<u64>> {
+    let expected = canonical_fields(expected)?;
+    let actual = canonical_fields(actual)?;
+    let values = |fields: &[Field], number: u64| -> Vec<Field> {
+        fields
+            .iter()
+            .filter(|f| f.number == number)
+            .cloned()
+            .collect()
+    };
+
+    let mut numbers: Vec<u64> = expected
+        .iter()
+        .chain(actual.iter())
+        .map(|f| f.number)
+        .collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers.retain(|number| values(&expected, *number) != values(&actual, *number));
+    Some(numbers)
+}
+
+/// Parses a message into its fields in canonical order (see [canonical_form]).
+fn canonical_fields(message: &[u8]) -> Option<Vec<Field>> {
+    let mut fields = parse(message)?;
+    for field in fields.iter_mut().filter(|f| f.wire_type == 2) {
+        if let Some(embedded) = canonical_form(&field.value).filter(|e| !e.is_empty()) {
+            field.value = embedded;
+        }
+    }
+    // The sort is stable, so that repeated fields keep their order.
+    fields.sort_by_key(|f| f.number);
+    Some(fields)
+}
+
+/// Splits a message into its fields. Groups (wire types 3 and 4) are deprecated and not
+/// supported.
+fn parse(mut message: &[u8]) -> Option<Vec<Field>> {
+    let mut fields = Vec::new();
+    while !message.is_empty() {
+        let key = decode_varint(&mut message)?;
+        let (number, wire_type) = (key >> 3, (key & 0x7) as u8);
+        if number == 0 {
+            return None;
+        }
+        let len = match wire_type {
+            0 => {
+                let start = message;
+                decode_varint(&mut message)?;
+                let len = start.len() - message.len();
+                message = start;
+                len
+            }
+            1 => 8,
+            2 => usize::try_from(decode_varint(&mut message)?).ok()?,
+            5 => 4,
+            _ => return None,
+        };
+        if message.len() < len {
+            return None;
+        }
+        let (value, rest) = message.split_at(len);
+        fields.push(Field {
+            number,
+            wire_type,
+            value: value.to_vec(),
+        });
+        message = rest;
+    }
+    Some(fields)
+}
+
+fn decode_varint(data: &mut &[u8]) -> Option<u64> {
+    let mut value = 0u64;
+    for (i, byte) in data.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            *data = &data[i + 1..];
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::protobuf::{canonical_form, differing_fields};
+
+    #[test]
+    fn canonical_form_test() {
+        // Arrange: field 1 = 150, field 2 = "hi", field 3 = { field 1 = 1, field 2 = 2 }
+        let ordered = [
+            0x08, 0x96, 0x01, 0x12, 0x02, b'h', b'i', 0x1a, 0x04, 0x08, 0x01, 0x10, 0x02,
+        ];
+        let reordered = [
+            0x1a, 0x04, 0x10, 0x02, 0x08, 0x01, 0x12, 0x02, b'h', b'i', 0x08, 0x96, 0x01,
+        ];
+
+        // Act
+        let canonical = canonical_form(&ordered);
+
+        // Assert
+        assert_eq!(canonical, Some(ordered.to_vec()));
+        assert_eq!(canonical_form(&reordered), canonical);
+        assert_eq!(canonical_form(&[0x12, 0x05, b'h']), None);
+        assert_eq!(canonical_form(&[0x08, 0x96]), None);
+    }
+
+    #[test]
+    fn canonical_form_keeps_repeated_field_order_test() {
+        let first = [0x08, 0x01, 0x10, 0x05, 0x08, 0x02];
+        let second = [0x08, 0x02, 0x10, 0x05, 0x08, 0x01];
+
+        assert_eq!(
+            canonical_form(&first),
+            Some(vec![0x08, 0x01, 0x08, 0x02, 0x10, 0x05])
+        );
+        assert_ne!(canonical_form(&first), canonical_form(&second));
+    }
+
+    #[test]
+    fn differing_fields_test() {
+        assert_eq!(
+            differing_fields(&[0x08, 0x01, 0x10, 0x02], &[0x10, 0x02, 0x08, 0x01]),
+            Some(vec![])
+        );
+        assert_eq!(
+            differing_fields(&[0x08, 0x01, 0x10, 0x02], &[0x08, 0x03, 0x18, 0x02]),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(differing_fields(&[0x08, 0x01], &[0xff]), None);
+    }
+}
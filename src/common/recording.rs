@@ -0,0 +1,411 @@
+use std::ops::Range;
+use std::str::FromStr;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::common::data::{HttpMockRequest, MockServerHttpResponse, RecordedExchange};
+use crate::common::json_path;
+use crate::common::yaml::{
+    YAMLHTTPResponse, YAMLMockDefinition, YAMLPattern, YAMLRequestRequirements,
+};
+use crate::standalone::NameValuePair;
+use crate::Method;
+
+/// The value that redacted header values and body parts are replaced with.
+const REDACTED: &str = "REDACTED";
+
+/// Options that control how recorded traffic is saved (see
+/// [MockServer::save_recording_with_options](struct.MockServer.html#method.save_recording_with_options)).
+///
+/// By default, the saved mocks match the method, the path, all query parameters and the body of
+/// a recorded request and the values of the `Authorization`, `Proxy-Authorization`, `Cookie` and
+/// `Set-Cookie` headers are redacted.
+#[derive(Debug, Clone)]
+pub struct RecordingOptions {
+    match_headers: Vec<String>,
+    redacted_headers: Vec<String>,
+    body_redactions: Vec<Regex>,
+    normalized_json_fields: Vec<(String, Value)>,
+}
+
+impl RecordingOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self {
+            match_headers: Vec::new(),
+            redacted_headers: [
+                "authorization",
+                "proxy-authorization",
+                "cookie",
+                "set-cookie",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            body_redactions: Vec::new(),
+            normalized_json_fields: Vec::new(),
+        }
+    }
+
+    /// Lets the saved mocks match the recorded value of a request header (case-insensitive
+    /// name). If the header is redacted, the mocks only require the header to be present.
+    pub fn match_header<S: Into<String>>(mut self, name: S) -> Self {
+        self.match_headers.push(name.into().to_lowercase());
+        self
+    }
+
+    /// Replaces the value of a header (case-insensitive name) with `REDACTED` in the saved
+    /// responses and in matched request headers.
+    pub fn redact_header<S: Into<String>>(mut self, name: S) -> Self {
+        self.redacted_headers.push(name.into().to_lowercase());
+        self
+    }
+
+    /// Replaces all matches of the regular expression in request and response bodies with
+    /// `REDACTED`. If the expression contains capture groups, only the captured parts are
+    /// replaced (e.g. `"token":\s*"([^"]*)"` only replaces the value of the token). Mocks for
+    /// requests whose body contains redacted parts match any value in their place.
+    pub fn redact_body<R: Into<Regex>>(mut self, regex: R) -> Self {
+        self.body_redactions.push(regex.into());
+        self
+    }
+
+    /// Sets all values that a JSONPath expression (e.g. `$.items[*].updated_at`) selects in
+    /// JSON response bodies to the provided value, so that volatile fields such as timestamps
+    /// or generated IDs do not change every time the traffic is recorded.
+    pub fn normalize_json_field<S: Into<String>, V: Into<Value>>(
+        mut self,
+        path: S,
+        value: V,
+    ) -> Self {
+        self.normalized_json_fields
+            .push((path.into(), value.into()));
+        self
+    }
+}
+
+impl Default for RecordingOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Converts the recorded exchanges into a YAML file with one mock definition per document.
+/// Only the first exchange of requests that would be matched by the same mock is saved.
+pub(crate) fn to_recording_file(
+    exchanges: &[RecordedExchange],
+    options: &RecordingOptions,
+) -> Result<String, String> {
+    let mut documents: Vec<(serde_yaml::Value, serde_yaml::Value)> = Vec::new();
+    for exchange in exchanges {
+        let definition = YAMLMockDefinition {
+            name: None,
+            when: to_request_requirements(&exchange.request, options),
+            then: to_response(&exchange.response, options)?,
+        };
+
+        let when = to_yaml_value(&definition.when)?;
+        if documents.iter().any(|(other, _)| other == &when) {
+            continue;
+        }
+        documents.push((when, to_yaml_value(&definition)?));
+    }
+
+    documents
+        .iter()
+        .map(|(_, document)| serde_yaml::to_string(document).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<String>, String>>()
+        .map(|documents| documents.join("---\n"))
+}
+
+/// Serializes a value into YAML and leaves out all fields that are not set.
+fn to_yaml_value<T: serde::Serialize>(value: &T) -> Result<serde_yaml::Value, String> {
+    let mut value = serde_yaml::to_value(value).map_err(|e| e.to_string())?;
+    if let serde_yaml::Value::Mapping(mapping) = &mut value {
+        mapping.retain(|_, v| !v.is_null());
+        for (_, v) in mapping.iter_mut() {
+            if let serde_yaml::Value::Mapping(nested) = v {
+                nested.retain(|_, v| !v.is_null());
+            }
+        }
+    }
+    Ok(value)
+}
+
+fn to_request_requirements(
+    request: &HttpMockRequest,
+    options: &RecordingOptions,
+) -> YAMLRequestRequirements {
+    let mut requirements = YAMLRequestRequirements {
+        path: Some(request.path.clone()),
+        method: Method::from_str(&request.method).ok(),
+        ..YAMLRequestRequirements::default()
+    };
+
+    if let Some(query_params) = &request.query_params {
+        if !query_params.is_empty() {
+            requirements.query_param = Some(to_name_value_pairs(query_params));
+        }
+    }
+
+    let mut headers = Vec::new();
+    let mut header_exists = Vec::new();
+    for (name, value) in request.headers.iter().flatten() {
+        let name = name.to_lowercase();
+        if !options.match_headers.contains(&name) {
+            continue;
+        }
+        match options.redacted_headers.contains(&name) {
+            true => header_exists.push(name),
+            false => headers.push((name, value.clone())),
+        }
+    }
+    if !headers.is_empty() {
+        requirements.header = Some(to_name_value_pairs(&headers));
+    }
+    if !header_exists.is_empty() {
+        requirements.header_exists = Some(header_exists);
+    }
+
+    let body = match request.body.as_deref() {
+        Some(body) if !body.is_empty() => body,
+        _ => return requirements,
+    };
+    let body = match std::str::from_utf8(body) {
+        Ok(body) => body,
+        Err(_) => {
+            log::warn!(
+                "The binary body of the recorded request {} {} is not matched",
+                request.method,
+                request.path
+            );
+            return requirements;
+        }
+    };
+
+    let redactions = redaction_ranges(body, &options.body_redactions);
+    if !redactions.is_empty() {
+        requirements.body_matches = Some(vec![YAMLPattern(redacted_body_regex(body, &redactions))]);
+    } else if let Ok(json_body) = serde_json::from_str::<Value>(body) {
+        requirements.json_body = Some(json_body);
+    } else {
+        requirements.body = Some(body.to_string());
+    }
+
+    requirements
+}
+
+fn to_response(
+    response: &MockServerHttpResponse,
+    options: &RecordingOptions,
+) -> Result<YAMLHTTPResponse, String> {
+    let mut yaml_response = YAMLHTTPResponse {
+        status: response.status,
+        ..YAMLHTTPResponse::default()
+    };
+
+    if let Some(headers) = &response.headers {
+        let headers: Vec<(String, String)> = headers
+            .iter()
+            .map(
+                |(name, value)| match options.redacted_headers.contains(&name.to_lowercase()) {
+                    true => (name.clone(), REDACTED.to_string()),
+                    false => (name.clone(), value.clone()),
+                },
+            )
+            .collect();
+        yaml_response.header = Some(to_name_value_pairs(&headers));
+    }
+
+    let body = match &response.body {
+        Some(body) if !body.is_empty() => body,
+        _ => return Ok(yaml_response),
+    };
+    let mut body = match String::from_utf8(body.clone()) {
+        Ok(body) => body,
+        Err(_) => {
+            yaml_response.base64_body = Some(base64::encode(body));
+            return Ok(yaml_response);
+        }
+    };
+
+    if !options.normalized_json_fields.is_empty() {
+        if let Ok(mut json_body) = serde_json::from_str::<Value>(&body) {
+            for (path, value) in &options.normalized_json_fields {
+                json_path::replace(&mut json_body, path, value)?;
+            }
+            body = json_body.to_string();
+        }
+    }
+
+    let redactions = redaction_ranges(&body, &options.body_redactions);
+    if !redactions.is_empty() {
+        body = redact(&body, &redactions);
+    }
+
+    match serde_json::from_str::<Value>(&body) {
+        Ok(json_body) if json_body.is_object() || json_body.is_array() => {
+            yaml_response.json_body = Some(json_body)
+        }
+        _ => yaml_response.body = Some(body),
+    }
+
+    Ok(yaml_response)
+}
+
+fn to_name_value_pairs(pairs: &[(String, String)]) -> Vec<NameValuePair> {
+    pairs
+        .iter()
+        .map(|(name, value)| NameValuePair {
+            name: name.clone(),
+            value: value.clone(),
+        })
+        .collect()
+}
+
+/// Returns the sorted, non-overlapping ranges of the text that are redacted by the provided
+/// regular expressions.
+fn redaction_ranges(text: &str, redactions: &[Regex]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for regex in redactions {
+        for captures in regex.captures_iter(text) {
+            match captures.len() {
+                1 => ranges.extend(captures.get(0).map(|m| m.range())),
+                _ => ranges.extend(captures.iter().skip(1).flatten().map(|m| m.range())),
+            }
+        }
+    }
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+fn redact(text: &str, ranges: &[Range<usize>]) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut position = 0;
+    for range in ranges {
+        redacted.push_str(&text[position..range.start]);
+        redacted.push_str(REDACTED);
+        position = range.end;
+    }
+    redacted.push_str(&text[position..]);
+    redacted
+}
+
+/// Creates a regular expression that matches the text with any value in place of the
+/// redacted ranges.
+fn redacted_body_regex(text: &str, ranges: &[Range<usize>]) -> Regex {
+    let mut regex = String::from("(?s)^");
+    let mut position = 0;
+    for range in ranges {
+        regex.push_str(&regex::escape(&text[position..range.start]));
+        regex.push_str(".*?");
+        position = range.end;
+    }
+    regex.push_str(&regex::escape(&text[position..]));
+    regex.push('$');
+    Regex::new(&regex).expect("escaped text is a valid regular expression")
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use regex::Regex;
+    use serde_json::json;
+
+    use crate::common::data::{HttpMockRequest, MockServerHttpResponse, RecordedExchange};
+    use crate::common::recording::{to_recording_file, RecordingOptions};
+
+    fn exchange(body: &str, response_body: &str) -> RecordedExchange {
+        let request = HttpMockRequest::new("POST".to_string(), "/login".to_string())
+            .with_headers(vec![
+                ("Authorization".to_string(), "Bearer secret".to_string()),
+                ("Accept".to_string(), "application/json".to_string()),
+            ])
+            .with_query_params(vec![("v".to_string(), "2".to_string())])
+            .with_body(body.as_bytes().to_vec());
+
+        let mut response = MockServerHttpResponse::new();
+        response.status = Some(200);
+        response.headers = Some(vec![("Set-Cookie".to_string(), "session=abc".to_string())]);
+        response.body = Some(response_body.as_bytes().to_vec());
+
+        RecordedExchange {
+            request: Arc::new(request),
+            response,
+        }
+    }
+
+    #[test]
+    fn to_recording_file_test() {
+        // Arrange
+        let exchanges = vec![
+            exchange(r#"{"user":"fred"}"#, r#"{"id":"8f2a","user":"fred"}"#),
+            exchange(r#"{"user":"fred"}"#, r#"{"id":"91bc","user":"fred"}"#),
+        ];
+        let options = RecordingOptions::new()
+            .match_header("accept")
+            .match_header("authorization")
+            .normalize_json_field("$.id", "ID");
+
+        // Act
+        let file = to_recording_file(&exchanges, &options).unwrap();
+
+        // Assert
+        let document: serde_yaml::Value = serde_yaml::from_str(&file).unwrap();
+        let expected: serde_yaml::Value = serde_yaml::from_value(
+            serde_yaml::to_value(json!({
+                "when": {
+                    "path": "/login",
+                    "method": "POST",
+                    "header": [{ "name": "accept", "value": "application/json" }],
+                    "header_exists": ["authorization"],
+                    "json_body": { "user": "fred" },
+                    "query_param": [{ "name": "v", "value": "2" }]
+                },
+                "then": {
+                    "status": 200,
+                    "header": [{ "name": "Set-Cookie", "value": "REDACTED" }],
+                    "json_body": { "id": "ID", "user": "fred" }
+                }
+            }))
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(document, expected);
+        assert!(!file.contains("secret"), "{}", file);
+    }
+
+    #[test]
+    fn redact_body_test() {
+        // Arrange
+        let exchanges = vec![exchange(
+            "grant_type=password&password=hunter2",
+            "access_token=abc&expires=3600",
+        )];
+        let options = RecordingOptions::new()
+            .redact_body(Regex::new("password=([^&]*)").unwrap())
+            .redact_body(Regex::new("access_token=[^&]*").unwrap());
+
+        // Act
+        let file = to_recording_file(&exchanges, &options).unwrap();
+
+        // Assert
+        assert!(!file.contains("hunter2"), "{}", file);
+        assert!(file.contains("body: REDACTED&expires=3600"), "{}", file);
+        let document: serde_yaml::Value = serde_yaml::from_str(&file).unwrap();
+        let pattern = document["when"]["body_matches"][0].as_str().unwrap();
+        let regex = Regex::new(pattern).unwrap();
+        assert!(regex.is_match("grant_type=password&password=other"));
+        assert!(!regex.is_match("grant_type=client_credentials&password=other"));
+    }
+}
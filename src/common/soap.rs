@@ -0,0 +1,147 @@
+//! Support for mocking SOAP services. Messages are wrapped in the envelope of the SOAP version
+//! that a service uses, since SOAP 1.1 and SOAP 1.2 differ in their envelope namespace, their
+//! content type and the structure of faults.
+
+/// The SOAP version of the messages that a mock expects and returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoapVersion {
+    /// SOAP 1.1: messages use the `text/xml` content type and the action is sent in the
+    /// `SOAPAction` header.
+    V1_1,
+    /// SOAP 1.2: messages use the `application/soap+xml` content type and the action is sent
+    /// in its `action` parameter.
+    V1_2,
+}
+
+impl SoapVersion {
+    pub(crate) fn content_type(&self) -> &'static str {
+        match self {
+            SoapVersion::V1_1 => "text/xml; charset=utf-8",
+            SoapVersion::V1_2 => "application/soap+xml; charset=utf-8",
+        }
+    }
+
+    pub(crate) fn namespace(&self) -> &'static str {
+        match self {
+            SoapVersion::V1_1 => "http://schemas.xmlsoap.org/soap/envelope/",
+            SoapVersion::V1_2 => "http://www.w3.org/2003/05/soap-envelope",
+        }
+    }
+}
+
+/// The codes of SOAP faults. SOAP 1.1 names `Sender` and `Receiver` faults `Client` and
+/// `Server`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoapFaultCode {
+    VersionMismatch,
+    MustUnderstand,
+    Sender,
+    Receiver,
+}
+
+impl SoapFaultCode {
+    fn name(&self, version: SoapVersion) -> &'static str {
+        match (self, version) {
+            (SoapFaultCode::VersionMismatch, _) => "VersionMismatch",
+            (SoapFaultCode::MustUnderstand, _) => "MustUnderstand",
+            (SoapFaultCode::Sender, SoapVersion::V1_1) => "Client",
+            (SoapFaultCode::Sender, SoapVersion::V1_2) => "Sender",
+            (SoapFaultCode::Receiver, SoapVersion::V1_1) => "Server",
+            (SoapFaultCode::Receiver, SoapVersion::V1_2) => "Receiver",
+        }
+    }
+
+    /// Returns the HTTP status code of a response that contains a fault with this code. SOAP 1.1
+    /// always uses 500, SOAP 1.2 uses 400 for faults that were caused by the sender.
+    pub(crate) fn status(&self, version: SoapVersion) -> u16 {
+        match (self, version) {
+            (SoapFaultCode::Sender, SoapVersion::V1_2) => 400,
+            _ => 500,
+        }
+    }
+}
+
+/// Wraps the XML content of a SOAP body in an envelope.
+pub(crate) fn envelope(version: SoapVersion, content: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+         <soap:Envelope xmlns:soap=\"{}\"><soap:Body>{}</soap:Body></soap:Envelope>",
+        version.namespace(),
+        content
+    )
+}
+
+/// Returns an envelope that contains a fault with the provided code and reason.
+pub(crate) fn fault(version: SoapVersion, code: SoapFaultCode, reason: &str) -> String {
+    let fault = match version {
+        SoapVersion::V1_1 => format!(
+            "<soap:Fault><faultcode>soap:{}</faultcode><faultstring>{}</faultstring></soap:Fault>",
+            code.name(version),
+            escape(reason)
+        ),
+        SoapVersion::V1_2 => format!(
+            "<soap:Fault><soap:Code><soap:Value>soap:{}</soap:Value></soap:Code>\
+             <soap:Reason><soap:Text xml:lang=\"en\">{}</soap:Text></soap:Reason></soap:Fault>",
+            code.name(version),
+            escape(reason)
+        ),
+    };
+    envelope(version, &fault)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::soap::{envelope, fault, SoapFaultCode, SoapVersion};
+    use crate::common::xml::select;
+
+    #[test]
+    fn envelope_test() {
+        let envelope = envelope(
+            SoapVersion::V1_2,
+            "<GetUserResponse><Name>Fred</Name></GetUserResponse>",
+        );
+
+        assert!(envelope.contains("xmlns:soap=\"http://www.w3.org/2003/05/soap-envelope\""));
+        assert_eq!(
+            select(&envelope, "/Envelope/Body/GetUserResponse/Name").unwrap(),
+            vec!["Fred"]
+        );
+    }
+
+    #[test]
+    fn fault_test() {
+        // Arrange
+        let reason = "Unknown user <42> & more";
+
+        // Act
+        let v1_1 = fault(SoapVersion::V1_1, SoapFaultCode::Sender, reason);
+        let v1_2 = fault(SoapVersion::V1_2, SoapFaultCode::Sender, reason);
+
+        // Assert
+        assert_eq!(
+            select(&v1_1, "/Envelope/Body/Fault/faultcode").unwrap(),
+            vec!["soap:Client"]
+        );
+        assert_eq!(
+            select(&v1_1, "/Envelope/Body/Fault/faultstring").unwrap(),
+            vec![reason]
+        );
+        assert_eq!(
+            select(&v1_2, "/Envelope/Body/Fault/Code/Value").unwrap(),
+            vec!["soap:Sender"]
+        );
+        assert_eq!(
+            select(&v1_2, "/Envelope/Body/Fault/Reason/Text").unwrap(),
+            vec![reason]
+        );
+        assert_eq!(SoapFaultCode::Sender.status(SoapVersion::V1_1), 500);
+        assert_eq!(SoapFaultCode::Sender.status(SoapVersion::V1_2), 400);
+        assert_eq!(SoapFaultCode::Receiver.status(SoapVersion::V1_2), 500);
+    }
+}
@@ -2,6 +2,7 @@ use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{
     env,
     future::Future,
@@ -41,6 +42,21 @@ where
     result
 }
 
+/// Generates an ID that is unique across processes and machines with high probability, e.g.
+/// for namespaces.
+pub(crate) fn unique_id() -> String {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    // Every RandomState is seeded with different random keys.
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(std::process::id());
+    if let Ok(elapsed) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+    format!("{:016x}", hasher.finish())
+}
+
 // ===============================================================================================
 // Environment
 // ===============================================================================================
@@ -86,6 +102,19 @@ impl<F: Future> Join for F {
     }
 }
 
+/// Panics if a blocking method of the mock API is called from within a tokio runtime. Blocking
+/// parks the thread until the mock server answers, which stalls the executor of async tests or
+/// deadlocks it if the mock server requests are driven by the same runtime.
+pub(crate) fn assert_not_in_runtime(method: &str, async_method: &str) {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        panic!(
+            "{} must not be called from within an async runtime because it blocks the \
+             current thread. Please use {}(..).await instead.",
+            method, async_method
+        );
+    }
+}
+
 // ===============================================================================================
 // Files
 // ===============================================================================================
@@ -118,9 +147,92 @@ pub(crate) fn read_file<P: AsRef<Path>>(absolute_resource_path: P) -> Result<Vec
     Ok(buffer)
 }
 
+// ===============================================================================================
+// Time
+// ===============================================================================================
+/// Formats a point in time as an RFC 3339 timestamp in UTC with millisecond precision
+/// (e.g. `2022-03-01T10:00:00.000Z`).
+pub(crate) fn format_rfc3339(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Converts days since the epoch into a civil date (see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Escapes all characters of a text that have a special meaning in a regular expression, like
+/// `regex::escape`, which is not available without the `regex` feature.
+pub(crate) fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Converts a path template such as `/users/{id}` into a regular expression that matches
+/// any value for the path parameters.
+pub(crate) fn path_template_to_regex(path_template: &str) -> String {
+    let mut regex = String::from("^");
+    let mut rest = path_template;
+    while let Some(start) = rest.find('{') {
+        regex.push_str(&escape_regex(&rest[..start]));
+        regex.push_str("[^/]+");
+        rest = match rest[start..].find('}') {
+            Some(end) => &rest[start + end + 1..],
+            None => "",
+        };
+    }
+    regex.push_str(&escape_regex(rest));
+    regex.push('$');
+    regex
+}
+
 #[cfg(test)]
 mod test {
-    use crate::common::util::{with_retry, Join};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::common::util::{
+        escape_regex, format_rfc3339, path_template_to_regex, with_retry, Join,
+    };
+
+    #[test]
+    fn format_rfc3339_test() {
+        assert_eq!(format_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_rfc3339(UNIX_EPOCH + Duration::from_millis(951_827_696_789)),
+            "2000-02-29T12:34:56.789Z"
+        );
+        assert_eq!(
+            format_rfc3339(UNIX_EPOCH + Duration::from_secs(1_798_761_599)),
+            "2026-12-31T23:59:59.000Z"
+        );
+    }
 
     #[test]
     fn with_retry_error_test() {
@@ -132,4 +244,20 @@ mod test {
         assert_eq!(result.is_err(), true);
         assert_eq!(result.err().unwrap(), "test error")
     }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn escape_regex_test() {
+        let text = r"a\b.c+d*e?f(g)h|i[j]k{l}m^n$o#p&q-r~s t/u";
+        assert_eq!(escape_regex(text), regex::escape(text));
+    }
+
+    #[test]
+    fn path_template_to_regex_test() {
+        assert_eq!(path_template_to_regex("/users"), "^/users$");
+        assert_eq!(
+            path_template_to_regex("/users/{id}/posts/{post.id}"),
+            "^/users/[^/]+/posts/[^/]+$"
+        );
+    }
 }
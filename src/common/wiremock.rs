@@ -0,0 +1,713 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::{Map, Value};
+
+use crate::common::data::{
+    JsonPathRequirement, MockDefinition, MockServerHttpResponse, Pattern, QueryParamValues,
+    RequestRequirements,
+};
+use crate::common::util::read_file;
+use crate::Regex;
+
+/// The priority of WireMock stub mappings that do not specify one.
+const DEFAULT_PRIORITY: i64 = 5;
+
+/// A WireMock stub mapping file that was converted into mock definitions.
+pub(crate) struct WireMockFile {
+    pub path: PathBuf,
+    /// The mock definitions of the file together with their WireMock priority.
+    pub mappings: Vec<(i64, MockDefinition)>,
+    /// Descriptions of all parts of the file that are not supported and have been ignored.
+    pub warnings: Vec<String>,
+}
+
+/// Reads WireMock stub mappings from a single file or from all JSON files in a directory
+/// (including subdirectories). If the directory contains a `mappings` subdirectory (i.e., it is
+/// the root directory of a WireMock installation), the mappings are read from there. Response
+/// body files (`bodyFileName`) are looked up in the `__files` directory next to the mappings
+/// directory, also if only a subdirectory of it is loaded.
+pub(crate) fn read_wiremock_mappings(path: &Path) -> Result<Vec<WireMockFile>, String> {
+    let (mappings_path, files_dir) = match path.join("mappings").is_dir() {
+        true => (path.join("mappings"), path.join("__files")),
+        false => {
+            let mappings_dir = match path.is_dir() {
+                true => path,
+                false => path.parent().unwrap_or_else(|| Path::new("")),
+            };
+            // Mappings can be loaded from a subdirectory of the WireMock mappings directory.
+            let root_dir = mappings_dir
+                .ancestors()
+                .find(|dir| dir.file_name().map_or(false, |name| name == "mappings"))
+                .unwrap_or(mappings_dir)
+                .parent()
+                .unwrap_or_else(|| Path::new(""));
+            let files_dir = root_dir.join("__files");
+            (path.to_path_buf(), files_dir)
+        }
+    };
+
+    let mut paths = Vec::new();
+    collect_mapping_files(&mappings_path, &mut paths)?;
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| read_wiremock_file(&path, &files_dir))
+        .collect()
+}
+
+fn collect_mapping_files(path: &Path, paths: &mut Vec<PathBuf>) -> Result<(), String> {
+    if !path.is_dir() {
+        paths.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let entries = std::fs::read_dir(path).map_err(|e| {
+        format!(
+            "Cannot read WireMock mappings directory '{}': {}",
+            path.to_string_lossy(),
+            e
+        )
+    })?;
+    for entry in entries {
+        let entry_path = entry.map_err(|e| e.to_string())?.path();
+        if entry_path.is_dir() {
+            collect_mapping_files(&entry_path, paths)?;
+        } else if entry_path.extension().map_or(false, |ext| ext == "json") {
+            paths.push(entry_path);
+        }
+    }
+
+    Ok(())
+}
+
+fn read_wiremock_file(path: &Path, files_dir: &Path) -> Result<WireMockFile, String> {
+    let error = |message: String| {
+        format!(
+            "Invalid WireMock mapping file '{}': {}",
+            path.to_string_lossy(),
+            message
+        )
+    };
+
+    let content = read_file(path).map_err(|e| error(e))?;
+    let value: Value = serde_json::from_slice(&content).map_err(|e| error(e.to_string()))?;
+
+    let mappings = match value.get("mappings") {
+        Some(Value::Array(mappings)) => mappings.iter().collect(),
+        Some(_) => return Err(error("'mappings' must be an array".to_string())),
+        None => vec![&value],
+    };
+
+    let mut file = WireMockFile {
+        path: path.to_path_buf(),
+        mappings: Vec::new(),
+        warnings: Vec::new(),
+    };
+    for (idx, mapping) in mappings.into_iter().enumerate() {
+        let label = match mapping.get("name").and_then(|n| n.as_str()) {
+            Some(name) => format!("mapping '{}'", name),
+            None => format!("mapping {}", idx),
+        };
+        let mut warnings = Vec::new();
+        let converted = to_mock_definition(mapping, files_dir, &mut warnings)
+            .map_err(|e| error(format!("{}: {}", label, e)))?;
+        file.mappings.push(converted);
+        file.warnings
+            .extend(warnings.into_iter().map(|w| format!("{}: {}", label, w)));
+    }
+
+    Ok(file)
+}
+
+fn to_mock_definition(
+    mapping: &Value,
+    files_dir: &Path,
+    warnings: &mut Vec<String>,
+) -> Result<(i64, MockDefinition), String> {
+    let mapping = mapping.as_object().ok_or("a mapping must be an object")?;
+
+    let mut priority = DEFAULT_PRIORITY;
+    let mut request = RequestRequirements::new();
+    let mut response = MockServerHttpResponse::new();
+    response.status = Some(200);
+
+    for (key, value) in mapping {
+        match key.as_str() {
+            "request" => request = to_request_requirements(value, warnings)?,
+            "response" => response = to_response(value, files_dir, warnings)?,
+            "priority" => priority = value.as_i64().ok_or("'priority' must be a number")?,
+            "id" | "uuid" | "name" | "persistent" | "metadata" => {}
+            other => warnings.push(format!("'{}' is not supported and was ignored", other)),
+        }
+    }
+
+    Ok((priority, MockDefinition::new(request, response)))
+}
+
+fn to_request_requirements(
+    request: &Value,
+    warnings: &mut Vec<String>,
+) -> Result<RequestRequirements, String> {
+    let request = request.as_object().ok_or("'request' must be an object")?;
+    let mut requirements = RequestRequirements::new();
+
+    for (key, value) in request {
+        match key.as_str() {
+            "method" => {
+                let method = as_str(value, "request.method")?;
+                if method != "ANY" {
+                    requirements.method = Some(method.to_uppercase());
+                }
+            }
+            "url" => {
+                let url = as_str(value, "request.url")?;
+                let (path, query) = url.split_at(url.find('?').unwrap_or(url.len()));
+                requirements.path = Some(path.to_string());
+                let query_params: Vec<(String, String)> =
+                    form_urlencoded::parse(query.trim_start_matches('?').as_bytes())
+                        .into_owned()
+                        .collect();
+                if !query_params.is_empty() {
+                    requirements.query_param = Some(query_params);
+                }
+            }
+            "urlPath" => requirements.path = Some(as_str(value, "request.urlPath")?.to_string()),
+            "urlPathPattern" => {
+                let pattern = to_full_match_regex(as_str(value, "request.urlPathPattern")?)?;
+                requirements.path_matches = Some(vec![Pattern::from_regex(pattern)]);
+            }
+            "queryParameters" | "headers" | "cookies" => {
+                let matchers = value
+                    .as_object()
+                    .ok_or_else(|| format!("'request.{}' must be an object", key))?;
+                for (name, matcher) in matchers {
+                    let field = format!("request.{}.{}", key, name);
+                    if matcher.get("absent") == Some(&Value::Bool(true)) {
+                        let missing = match key.as_str() {
+                            "queryParameters" => &mut requirements.query_param_missing,
+                            "headers" => &mut requirements.header_missing,
+                            _ => {
+                                warnings.push(format!(
+                                    "'{}' uses an unsupported matcher and was ignored",
+                                    field
+                                ));
+                                continue;
+                            }
+                        };
+                        missing.get_or_insert_with(Vec::new).push(name.clone());
+                        continue;
+                    }
+                    if let (Some(values), "queryParameters") =
+                        (matcher.get("hasExactly"), key.as_str())
+                    {
+                        match to_exact_values(values) {
+                            Some(values) => requirements
+                                .query_param_values
+                                .get_or_insert_with(Vec::new)
+                                .push(QueryParamValues {
+                                    name: name.clone(),
+                                    values,
+                                }),
+                            None => warnings.push(format!(
+                                "'{}' uses an unsupported matcher and was ignored",
+                                field
+                            )),
+                        }
+                        continue;
+                    }
+                    let matcher = match to_value_matcher(matcher, &field)? {
+                        Some(matcher) => matcher,
+                        None => {
+                            warnings.push(format!(
+                                "'{}' uses an unsupported matcher and was ignored",
+                                field
+                            ));
+                            continue;
+                        }
+                    };
+                    let (exact, regex) = match key.as_str() {
+                        "queryParameters" => (
+                            &mut requirements.query_param,
+                            Some(&mut requirements.query_param_matches),
+                        ),
+                        "headers" => (
+                            &mut requirements.headers,
+                            Some(&mut requirements.header_matches),
+                        ),
+                        _ => (&mut requirements.cookies, None),
+                    };
+                    match (matcher, regex) {
+                        (ValueMatcher::EqualTo(expected), _) => exact
+                            .get_or_insert_with(Vec::new)
+                            .push((name.clone(), expected)),
+                        (ValueMatcher::Matches(regex), Some(regex_requirements)) => {
+                            regex_requirements
+                                .get_or_insert_with(Vec::new)
+                                .push((name.clone(), Pattern::from_regex(regex)))
+                        }
+                        (ValueMatcher::Matches(_), None) => warnings.push(format!(
+                            "'{}' only supports 'equalTo' and was ignored",
+                            field
+                        )),
+                    }
+                }
+            }
+            "bodyPatterns" => {
+                let patterns = value
+                    .as_array()
+                    .ok_or("'request.bodyPatterns' must be an array")?;
+                for pattern in patterns {
+                    add_body_pattern(&mut requirements, pattern, warnings)?;
+                }
+            }
+            "basicAuthCredentials" => {
+                let username = value.get("username").and_then(|u| u.as_str()).unwrap_or("");
+                let password = value.get("password").and_then(|p| p.as_str()).unwrap_or("");
+                requirements.basic_auth = Some(format!("{}:{}", username, password));
+            }
+            other => warnings.push(format!(
+                "'request.{}' is not supported and was ignored",
+                other
+            )),
+        }
+    }
+
+    Ok(requirements)
+}
+
+/// A matcher for a single value, such as a header or a query parameter.
+enum ValueMatcher {
+    EqualTo(String),
+    Matches(Regex),
+}
+
+/// Converts a WireMock value matcher (e.g. `{"equalTo": "abc"}`). Returns `None` if the matcher
+/// is not supported.
+fn to_value_matcher(matcher: &Value, field: &str) -> Result<Option<ValueMatcher>, String> {
+    let matcher = match matcher.as_object() {
+        Some(matcher) => matcher,
+        None => return Ok(None),
+    };
+    let case_insensitive = matcher.get("caseInsensitive") == Some(&Value::Bool(true));
+    if matcher
+        .keys()
+        .any(|k| !["equalTo", "matches", "contains", "caseInsensitive"].contains(&k.as_str()))
+    {
+        return Ok(None);
+    }
+
+    if let Some(expected) = matcher.get("equalTo") {
+        let expected = as_str(expected, field)?;
+        return Ok(Some(match case_insensitive {
+            true => ValueMatcher::Matches(to_regex(&format!("(?i)^{}$", regex::escape(expected)))?),
+            false => ValueMatcher::EqualTo(expected.to_string()),
+        }));
+    }
+    if let Some(pattern) = matcher.get("matches") {
+        return Ok(Some(ValueMatcher::Matches(to_full_match_regex(as_str(
+            pattern, field,
+        )?)?)));
+    }
+    if let Some(substring) = matcher.get("contains") {
+        return Ok(Some(ValueMatcher::Matches(to_regex(&regex::escape(
+            as_str(substring, field)?,
+        ))?)));
+    }
+
+    Ok(None)
+}
+
+/// Converts the value matchers of a `hasExactly` matcher (e.g. `[{"equalTo": "1"}]`). Returns
+/// `None` if any of the matchers is not a plain `equalTo` matcher.
+fn to_exact_values(matchers: &Value) -> Option<Vec<String>> {
+    matchers
+        .as_array()?
+        .iter()
+        .map(|matcher| match matcher.as_object() {
+            Some(matcher) if matcher.len() == 1 => {
+                matcher.get("equalTo")?.as_str().map(str::to_string)
+            }
+            _ => None,
+        })
+        .collect::<Option<Vec<String>>>()
+        .filter(|values| !values.is_empty())
+}
+
+fn add_body_pattern(
+    requirements: &mut RequestRequirements,
+    pattern: &Value,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    let pattern = pattern
+        .as_object()
+        .ok_or("'request.bodyPatterns' must only contain objects")?;
+
+    if let Some(expected) = pattern.get("equalToJson") {
+        let expected = match expected {
+            Value::String(json) => serde_json::from_str(json).map_err(|e| {
+                format!(
+                    "'request.bodyPatterns.equalToJson' is not valid JSON: {}",
+                    e
+                )
+            })?,
+            other => other.clone(),
+        };
+        if pattern.get("ignoreArrayOrder") == Some(&Value::Bool(true)) {
+            warnings.push(
+                "'request.bodyPatterns.ignoreArrayOrder' is not supported and was ignored"
+                    .to_string(),
+            );
+        }
+        match pattern.get("ignoreExtraElements") == Some(&Value::Bool(true)) {
+            true => requirements
+                .json_body_includes
+                .get_or_insert_with(Vec::new)
+                .push(expected),
+            false => requirements.json_body = Some(expected),
+        }
+    } else if let Some(expression) = pattern.get("matchesJsonPath") {
+        let requirement = match expression {
+            Value::String(path) => JsonPathRequirement {
+                path: path.clone(),
+                value: None,
+            },
+            Value::Object(object) => {
+                let path = object
+                    .get("expression")
+                    .and_then(|e| e.as_str())
+                    .ok_or("'request.bodyPatterns.matchesJsonPath' requires an 'expression'")?;
+                match object.get("equalTo") {
+                    Some(expected) if object.len() == 2 => JsonPathRequirement {
+                        path: path.to_string(),
+                        value: Some(expected.clone()),
+                    },
+                    _ => {
+                        warnings.push(format!(
+                            "'request.bodyPatterns.matchesJsonPath' for '{}' only supports 'equalTo' and was ignored",
+                            path
+                        ));
+                        return Ok(());
+                    }
+                }
+            }
+            _ => return Err("'request.bodyPatterns.matchesJsonPath' must be a string".to_string()),
+        };
+        if let Err(e) = crate::common::json_path::select(&Value::Null, &requirement.path) {
+            warnings.push(format!("{} (the matcher was ignored)", e));
+            return Ok(());
+        }
+        requirements
+            .json_body_paths
+            .get_or_insert_with(Vec::new)
+            .push(requirement);
+    } else if let Some(substring) = pattern.get("contains") {
+        requirements
+            .body_contains
+            .get_or_insert_with(Vec::new)
+            .push(as_str(substring, "request.bodyPatterns.contains")?.to_string());
+    } else if let Some(expected) = pattern.get("equalTo") {
+        requirements.body = Some(as_str(expected, "request.bodyPatterns.equalTo")?.to_string());
+    } else if let Some(regex) = pattern.get("matches") {
+        let regex = to_full_match_regex(as_str(regex, "request.bodyPatterns.matches")?)?;
+        requirements
+            .body_matches
+            .get_or_insert_with(Vec::new)
+            .push(Pattern::from_regex(regex));
+    } else if let Some(substring) = pattern.get("doesNotContain") {
+        requirements
+            .body_not_contains
+            .get_or_insert_with(Vec::new)
+            .push(as_str(substring, "request.bodyPatterns.doesNotContain")?.to_string());
+    } else if let Some(regex) = pattern.get("doesNotMatch") {
+        let regex = to_full_match_regex(as_str(regex, "request.bodyPatterns.doesNotMatch")?)?;
+        requirements
+            .body_not_matches
+            .get_or_insert_with(Vec::new)
+            .push(Pattern::from_regex(regex));
+    } else {
+        let names: Vec<&str> = pattern.keys().map(|k| k.as_str()).collect();
+        warnings.push(format!(
+            "body pattern '{}' is not supported and was ignored",
+            names.join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+fn to_response(
+    response: &Value,
+    files_dir: &Path,
+    warnings: &mut Vec<String>,
+) -> Result<MockServerHttpResponse, String> {
+    let response = response.as_object().ok_or("'response' must be an object")?;
+    let mut mock_response = MockServerHttpResponse::new();
+    mock_response.status = Some(200);
+
+    let mut bodies = Vec::new();
+    for (key, value) in response {
+        match key.as_str() {
+            "status" => {
+                mock_response.status =
+                    Some(value.as_u64().ok_or("'response.status' must be a number")? as u16)
+            }
+            "headers" => mock_response.headers = Some(to_response_headers(value)?),
+            "body" => bodies.push(as_str(value, "response.body")?.as_bytes().to_vec()),
+            "jsonBody" => bodies.push(value.to_string().into_bytes()),
+            "base64Body" => bodies.push(
+                base64::decode(as_str(value, "response.base64Body")?)
+                    .map_err(|e| format!("'response.base64Body' is not valid base64: {}", e))?,
+            ),
+            "bodyFileName" => {
+                let path = files_dir.join(as_str(value, "response.bodyFileName")?);
+                bodies.push(read_file(&path).map_err(|e| {
+                    format!(
+                        "'response.bodyFileName': cannot read '{}': {}",
+                        path.to_string_lossy(),
+                        e
+                    )
+                })?);
+            }
+            "fixedDelayMilliseconds" => {
+                mock_response.delay =
+                    Some(Duration::from_millis(value.as_u64().ok_or(
+                        "'response.fixedDelayMilliseconds' must be a number",
+                    )?))
+            }
+            other => warnings.push(format!(
+                "'response.{}' is not supported and was ignored",
+                other
+            )),
+        }
+    }
+
+    if bodies.len() > 1 {
+        return Err(
+            "only one of 'body', 'jsonBody', 'base64Body' and 'bodyFileName' can be specified"
+                .to_string(),
+        );
+    }
+    mock_response.body = bodies.pop();
+
+    Ok(mock_response)
+}
+
+/// Converts response headers. WireMock allows a list of values for headers that are sent
+/// multiple times.
+fn to_response_headers(headers: &Value) -> Result<Vec<(String, String)>, String> {
+    let headers: &Map<String, Value> = headers
+        .as_object()
+        .ok_or("'response.headers' must be an object")?;
+
+    let mut result = Vec::new();
+    for (name, value) in headers {
+        match value {
+            Value::Array(values) => {
+                for value in values {
+                    result.push((name.clone(), as_str(value, "response.headers")?.to_string()));
+                }
+            }
+            value => result.push((name.clone(), as_str(value, "response.headers")?.to_string())),
+        }
+    }
+
+    Ok(result)
+}
+
+fn as_str<'a>(value: &'a Value, field: &str) -> Result<&'a str, String> {
+    value
+        .as_str()
+        .ok_or_else(|| format!("'{}' must be a string", field))
+}
+
+fn to_regex(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e))
+}
+
+/// WireMock patterns need to match the entire value.
+fn to_full_match_regex(pattern: &str) -> Result<Regex, String> {
+    to_regex(&format!("^(?:{})$", pattern))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use crate::common::data::{JsonPathRequirement, QueryParamValues};
+    use crate::common::wiremock::to_mock_definition;
+
+    #[test]
+    fn to_mock_definition_test() {
+        // Arrange
+        let mapping = json!({
+            "priority": 1,
+            "request": {
+                "method": "POST",
+                "urlPathPattern": "/users/[0-9]+",
+                "queryParameters": {
+                    "dryRun": { "equalTo": "true" },
+                    "page": { "matches": "[0-9]+" }
+                },
+                "headers": { "Content-Type": { "contains": "json" } },
+                "basicAuthCredentials": { "username": "alice", "password": "secret" },
+                "bodyPatterns": [
+                    { "equalToJson": "{\"name\": \"Fred\"}", "ignoreExtraElements": true },
+                    { "matchesJsonPath": { "expression": "$.address.zip", "equalTo": "10115" } },
+                    { "doesNotContain": "nickname" },
+                    { "doesNotMatch": ".*password.*" }
+                ]
+            },
+            "response": {
+                "status": 201,
+                "headers": { "Set-Cookie": ["a=1", "b=2"] },
+                "jsonBody": { "id": 1 },
+                "fixedDelayMilliseconds": 10
+            }
+        });
+
+        // Act
+        let mut warnings = Vec::new();
+        let (priority, definition) =
+            to_mock_definition(&mapping, Path::new(""), &mut warnings).unwrap();
+
+        // Assert
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        assert_eq!(priority, 1);
+        let request = &definition.request;
+        assert_eq!(request.method, Some("POST".to_string()));
+        assert_eq!(
+            request.path_matches.as_ref().unwrap()[0].regex.as_str(),
+            "^(?:/users/[0-9]+)$"
+        );
+        assert_eq!(
+            request.query_param,
+            Some(vec![("dryRun".to_string(), "true".to_string())])
+        );
+        assert_eq!(request.query_param_matches.as_ref().unwrap().len(), 1);
+        assert_eq!(
+            request.header_matches.as_ref().unwrap()[0].0,
+            "Content-Type"
+        );
+        assert_eq!(request.basic_auth, Some("alice:secret".to_string()));
+        assert_eq!(
+            request.json_body_includes,
+            Some(vec![json!({"name": "Fred"})])
+        );
+        assert_eq!(
+            request.json_body_paths,
+            Some(vec![JsonPathRequirement {
+                path: "$.address.zip".to_string(),
+                value: Some(json!("10115")),
+            }])
+        );
+        assert_eq!(
+            request.body_not_contains,
+            Some(vec!["nickname".to_string()])
+        );
+        assert_eq!(
+            request.body_not_matches.as_ref().unwrap()[0].regex.as_str(),
+            "^(?:.*password.*)$"
+        );
+        let response = &definition.response;
+        assert_eq!(response.status, Some(201));
+        assert_eq!(response.headers.as_ref().unwrap().len(), 2);
+        assert_eq!(response.body, Some(br#"{"id":1}"#.to_vec()));
+        assert_eq!(response.delay.unwrap().as_millis(), 10);
+    }
+
+    #[test]
+    fn absent_values_test() {
+        // Arrange
+        let mapping = json!({
+            "request": {
+                "urlPath": "/search",
+                "queryParameters": { "debug": { "absent": true } },
+                "headers": { "X-Api-Key": { "absent": true } }
+            },
+            "response": { "status": 200 }
+        });
+
+        // Act
+        let mut warnings = Vec::new();
+        let (_, definition) = to_mock_definition(&mapping, Path::new(""), &mut warnings).unwrap();
+
+        // Assert
+        assert!(warnings.is_empty());
+        assert_eq!(
+            definition.request.query_param_missing,
+            Some(vec!["debug".to_string()])
+        );
+        assert_eq!(
+            definition.request.header_missing,
+            Some(vec!["X-Api-Key".to_string()])
+        );
+    }
+
+    #[test]
+    fn has_exactly_query_parameter_test() {
+        // Arrange
+        let mapping = json!({
+            "request": {
+                "urlPath": "/users",
+                "queryParameters": {
+                    "id": { "hasExactly": [{ "equalTo": "1" }, { "equalTo": "2" }] },
+                    "tag": { "hasExactly": [{ "matches": "a.*" }] }
+                }
+            },
+            "response": { "status": 200 }
+        });
+
+        // Act
+        let mut warnings = Vec::new();
+        let (_, definition) = to_mock_definition(&mapping, Path::new(""), &mut warnings).unwrap();
+
+        // Assert
+        assert_eq!(
+            definition.request.query_param_values,
+            Some(vec![QueryParamValues {
+                name: "id".to_string(),
+                values: vec!["1".to_string(), "2".to_string()],
+            }])
+        );
+        assert_eq!(
+            warnings,
+            vec!["'request.queryParameters.tag' uses an unsupported matcher and was ignored"]
+        );
+    }
+
+    #[test]
+    fn unsupported_features_warning_test() {
+        // Arrange
+        let mapping = json!({
+            "scenarioName": "checkout",
+            "request": {
+                "method": "ANY",
+                "url": "/search?q=rust",
+                "headers": { "X-Trace": { "doesNotMatch": "^1" } },
+                "bodyPatterns": [{ "equalToXml": "<a/>" }]
+            },
+            "response": { "status": 200, "transformers": ["response-template"] }
+        });
+
+        // Act
+        let mut warnings = Vec::new();
+        let (_, definition) = to_mock_definition(&mapping, Path::new(""), &mut warnings).unwrap();
+
+        // Assert
+        assert_eq!(definition.request.method, None);
+        assert_eq!(definition.request.path, Some("/search".to_string()));
+        assert_eq!(
+            warnings,
+            vec![
+                "body pattern 'equalToXml' is not supported and was ignored",
+                "'request.headers.X-Trace' uses an unsupported matcher and was ignored",
+                "'response.transformers' is not supported and was ignored",
+                "'scenarioName' is not supported and was ignored",
+            ]
+        );
+    }
+}
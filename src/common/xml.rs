@@ -0,0 +1,503 @@
+//! An evaluator for the subset of XPath 1.0 that is needed to address values in an XML document:
+//! absolute location paths with child (`/`) and descendant (`//`) steps, element names, the
+//! wildcard `*`, `text()` and attributes (`@name`) as the last step, as well as the predicates
+//! `[n]`, `[@name='value']`, `[name='value']` and `[text()='value']`.
+//!
+//! Namespace prefixes are ignored and elements are compared by their local name, so that
+//! `/soap:Envelope/soap:Body` and `/Envelope/Body` select the same element regardless of the
+//! prefixes that a document declares.
+//!
+//! Besides, XML documents can be compared semantically (see [compare]).
+
+use roxmltree::{Document, Node, NodeId};
+
+/// Returns the string values of all nodes that the path selects in the provided XML document.
+/// Returns an error if the document is not well-formed or if the path is invalid or uses
+/// unsupported syntax.
+pub(crate) fn select(document: &str, path: &str) -> Result<Vec<String>, String> {
+    let steps = parse(path)?;
+    let document = Document::parse(document).map_err(|e| format!("invalid XML: {}", e))?;
+
+    let mut nodes = vec![document.root()];
+    for step in &steps {
+        let context: Vec<Node> = match step.descendants {
+            true => unique(nodes.iter().flat_map(|node| node.descendants())),
+            false => nodes,
+        };
+
+        if let NodeTest::Attribute(name) = &step.test {
+            return Ok(context
+                .iter()
+                .flat_map(|node| node.attributes())
+                .filter(|attribute| name == "*" || attribute.name() == local_name(name))
+                .map(|attribute| attribute.value().to_string())
+                .collect());
+        }
+
+        nodes = unique(context.iter().flat_map(|node| step.apply(*node)));
+    }
+
+    Ok(nodes.iter().map(|node| string_value(*node)).collect())
+}
+
+/// Returns true if the path selects a node whose string value equals the expected value.
+/// Whitespace around values is ignored. Without an expected value, the path only needs to
+/// select any node.
+pub(crate) fn value_matches(document: &str, path: &str, expected: Option<&str>) -> bool {
+    match (select(document, path), expected) {
+        (Ok(values), Some(expected)) => values.iter().any(|v| v.trim() == expected.trim()),
+        (Ok(values), None) => !values.is_empty(),
+        (Err(_), _) => false,
+    }
+}
+
+/// Returns an error if the path is invalid or uses unsupported syntax.
+pub(crate) fn check_path(path: &str) -> Result<(), String> {
+    parse(path).map(|_| ())
+}
+
+/// Compares two XML documents semantically: elements and attributes must have the same names
+/// and namespaces (but not necessarily the same prefixes), attributes may be in any order,
+/// whitespace around text is ignored, and comments and processing instructions are skipped.
+/// Returns a description of the first difference if the documents are not equal.
+pub(crate) fn compare(expected: &str, actual: &str) -> Result<(), String> {
+    let expected = Document::parse(expected).map_err(|e| format!("invalid expected XML: {}", e))?;
+    let actual = Document::parse(actual).map_err(|e| format!("invalid XML: {}", e))?;
+    compare_elements(expected.root_element(), actual.root_element(), "")
+}
+
+/// The content of an element that is relevant for a semantic comparison.
+enum Content<'a, 'input> {
+    Element(Node<'a, 'input>),
+    Text(&'a str),
+}
+
+fn compare_elements(expected: Node, actual: Node, parent_path: &str) -> Result<(), String> {
+    let path = format!("{}/{}", parent_path, expected.tag_name().name());
+    if expected.tag_name() != actual.tag_name() {
+        return Err(format!(
+            "{}: expected element {} but got {}",
+            path,
+            expanded_name(expected),
+            expanded_name(actual)
+        ));
+    }
+
+    let expected_attributes = attributes(expected);
+    let actual_attributes = attributes(actual);
+    if expected_attributes != actual_attributes {
+        return Err(format!(
+            "{}: expected attributes [{}] but got [{}]",
+            path,
+            expected_attributes.join(", "),
+            actual_attributes.join(", ")
+        ));
+    }
+
+    let expected_content = content(expected);
+    let actual_content = content(actual);
+    for (expected, actual) in expected_content.iter().zip(actual_content.iter()) {
+        match (expected, actual) {
+            (Content::Element(e), Content::Element(a)) => compare_elements(*e, *a, &path)?,
+            (Content::Text(e), Content::Text(a)) if e == a => {}
+            (e, a) => {
+                return Err(format!(
+                    "{}: expected {} but got {}",
+                    path,
+                    describe(e),
+                    describe(a)
+                ))
+            }
+        }
+    }
+    match expected_content.len().cmp(&actual_content.len()) {
+        std::cmp::Ordering::Equal => Ok(()),
+        std::cmp::Ordering::Greater => Err(format!(
+            "{}: missing {}",
+            path,
+            describe(&expected_content[actual_content.len()])
+        )),
+        std::cmp::Ordering::Less => Err(format!(
+            "{}: unexpected {}",
+            path,
+            describe(&actual_content[expected_content.len()])
+        )),
+    }
+}
+
+/// Returns the child elements and the non-blank text of an element.
+fn content<'a, 'input>(node: Node<'a, 'input>) -> Vec<Content<'a, 'input>> {
+    node.children()
+        .filter_map(|child| match child.is_element() {
+            true => Some(Content::Element(child)),
+            false if child.is_text() => child
+                .text()
+                .map(|text| text.trim())
+                .filter(|text| !text.is_empty())
+                .map(Content::Text),
+            false => None,
+        })
+        .collect()
+}
+
+/// Returns the attributes of an element as sorted `name="value"` strings.
+fn attributes(node: Node) -> Vec<String> {
+    let mut attributes: Vec<String> = node
+        .attributes()
+        .map(|attribute| match attribute.namespace() {
+            Some(namespace) => format!(
+                "{{{}}}{}={:?}",
+                namespace,
+                attribute.name(),
+                attribute.value()
+            ),
+            None => format!("{}={:?}", attribute.name(), attribute.value()),
+        })
+        .collect();
+    attributes.sort();
+    attributes
+}
+
+fn expanded_name(node: Node) -> String {
+    match node.tag_name().namespace() {
+        Some(namespace) => format!("<{}> in namespace '{}'", node.tag_name().name(), namespace),
+        None => format!("<{}>", node.tag_name().name()),
+    }
+}
+
+fn describe(content: &Content) -> String {
+    match content {
+        Content::Element(node) => format!("element {}", expanded_name(*node)),
+        Content::Text(text) => format!("text {:?}", text),
+    }
+}
+
+struct Step {
+    descendants: bool,
+    test: NodeTest,
+    predicates: Vec<Predicate>,
+}
+
+enum NodeTest {
+    Element(String),
+    Text,
+    Attribute(String),
+}
+
+enum Predicate {
+    Position(usize),
+    Attribute(String, String),
+    Child(String, String),
+    Text(String),
+}
+
+impl Step {
+    /// Returns the children of a node that pass the node test and all predicates.
+    fn apply<'a, 'input>(&self, node: Node<'a, 'input>) -> Vec<Node<'a, 'input>> {
+        let mut selected: Vec<Node> = node
+            .children()
+            .filter(|child| match &self.test {
+                NodeTest::Element(name) => {
+                    child.is_element() && (name == "*" || child.tag_name().name() == name)
+                }
+                NodeTest::Text => child.is_text(),
+                NodeTest::Attribute(_) => false,
+            })
+            .collect();
+
+        for predicate in &self.predicates {
+            selected = match predicate {
+                Predicate::Position(position) => {
+                    selected.get(position - 1).copied().into_iter().collect()
+                }
+                Predicate::Attribute(name, value) => selected
+                    .into_iter()
+                    .filter(|n| n.attribute(name.as_str()) == Some(value.as_str()))
+                    .collect(),
+                Predicate::Child(name, value) => selected
+                    .into_iter()
+                    .filter(|n| {
+                        n.children().any(|c| {
+                            c.is_element()
+                                && c.tag_name().name() == name
+                                && &string_value(c) == value
+                        })
+                    })
+                    .collect(),
+                Predicate::Text(value) => selected
+                    .into_iter()
+                    .filter(|n| &string_value(*n) == value)
+                    .collect(),
+            };
+        }
+
+        selected
+    }
+}
+
+/// Returns the nodes in their original order without duplicates, which occur if descendant
+/// steps select nested elements.
+fn unique<'a, 'input, I: Iterator<Item = Node<'a, 'input>>>(nodes: I) -> Vec<Node<'a, 'input>> {
+    let mut seen: Vec<NodeId> = Vec::new();
+    let mut unique = Vec::new();
+    for node in nodes {
+        if !seen.contains(&node.id()) {
+            seen.push(node.id());
+            unique.push(node);
+        }
+    }
+    unique
+}
+
+/// Returns the concatenated text of a node and all its descendants.
+fn string_value(node: Node) -> String {
+    node.descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn parse(path: &str) -> Result<Vec<Step>, String> {
+    let invalid = |reason: &str| format!("invalid XPath '{}': {}", path, reason);
+    if !path.starts_with('/') {
+        return Err(invalid("only absolute paths are supported"));
+    }
+
+    let mut steps = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        let descendants = rest.starts_with("//");
+        rest = match descendants {
+            true => &rest[2..],
+            false => rest
+                .strip_prefix('/')
+                .ok_or_else(|| invalid("expected '/'"))?,
+        };
+
+        let end = step_end(rest).ok_or_else(|| invalid("unterminated predicate"))?;
+        let step = parse_step(&rest[..end], descendants).map_err(|e| invalid(&e))?;
+        if let Some(NodeTest::Attribute(_)) = steps.last().map(|s: &Step| &s.test) {
+            return Err(invalid("attributes can only be selected by the last step"));
+        }
+        steps.push(step);
+        rest = &rest[end..];
+    }
+
+    if steps.is_empty() {
+        return Err(invalid("the path does not contain any step"));
+    }
+    Ok(steps)
+}
+
+/// Returns the length of the step at the start of the path, which ends at the next slash that
+/// is not part of a predicate.
+fn step_end(path: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (idx, c) in path.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'') | (None, '"') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => depth -= 1,
+            (None, '/') if depth == 0 => return Some(idx),
+            _ => {}
+        }
+    }
+    match depth == 0 && quote.is_none() {
+        true => Some(path.len()),
+        false => None,
+    }
+}
+
+fn parse_step(step: &str, descendants: bool) -> Result<Step, String> {
+    let (test, mut predicates) = match step.find('[') {
+        Some(idx) => (&step[..idx], &step[idx..]),
+        None => (step, ""),
+    };
+
+    let test = match test.trim() {
+        "" => return Err("empty step".to_string()),
+        "text()" => NodeTest::Text,
+        test if test.starts_with('@') => NodeTest::Attribute(test[1..].to_string()),
+        test if is_name(test) => NodeTest::Element(local_name(test).to_string()),
+        test => return Err(format!("unsupported step '{}'", test)),
+    };
+
+    let mut parsed = Vec::new();
+    while !predicates.is_empty() {
+        let end = predicates
+            .find(']')
+            .filter(|_| predicates.starts_with('['))
+            .ok_or_else(|| format!("invalid predicates '{}'", predicates))?;
+        // Literals may contain closing brackets, so the predicate ends at the first closing
+        // bracket after the literal.
+        let end = match predicates[..end].matches(|c| c == '\'' || c == '"').count() % 2 {
+            0 => end,
+            _ => predicates[end + 1..]
+                .find(']')
+                .map(|idx| end + 1 + idx)
+                .ok_or_else(|| format!("invalid predicates '{}'", predicates))?,
+        };
+        parsed.push(parse_predicate(predicates[1..end].trim())?);
+        predicates = &predicates[end + 1..];
+    }
+
+    Ok(Step {
+        descendants,
+        test,
+        predicates: parsed,
+    })
+}
+
+fn parse_predicate(predicate: &str) -> Result<Predicate, String> {
+    if let Ok(position) = predicate.parse::<usize>() {
+        return match position {
+            0 => Err("positions start at 1".to_string()),
+            position => Ok(Predicate::Position(position)),
+        };
+    }
+
+    let (name, literal) = predicate
+        .split_once('=')
+        .ok_or_else(|| format!("unsupported predicate '{}'", predicate))?;
+    let literal = literal.trim();
+    let value = match (literal.chars().next(), literal.chars().last()) {
+        (Some(q), Some(l)) if (q == '\'' || q == '"') && l == q && literal.len() > 1 => {
+            literal[1..literal.len() - 1].to_string()
+        }
+        _ => return Err(format!("expected a quoted literal in '{}'", predicate)),
+    };
+
+    match name.trim() {
+        "text()" | "." => Ok(Predicate::Text(value)),
+        name if name.starts_with('@') => Ok(Predicate::Attribute(
+            local_name(&name[1..]).to_string(),
+            value,
+        )),
+        name if is_name(name) => Ok(Predicate::Child(local_name(name).to_string(), value)),
+        name => Err(format!("unsupported predicate '{}'", name)),
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    name == "*"
+        || (!name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ':'))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::xml::{compare, select, value_matches};
+
+    const ORDER: &str = r#"<?xml version="1.0"?>
+        <soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+          <soap:Body>
+            <m:Order xmlns:m="urn:orders" status="open">
+              <Id>42</Id>
+              <Item sku="a-1"><Name>Pen</Name></Item>
+              <Item sku="b-2"><Name>Paper [A4]</Name></Item>
+            </m:Order>
+          </soap:Body>
+        </soap:Envelope>"#;
+
+    #[test]
+    fn select_test() {
+        let select = |path: &str| select(ORDER, path).unwrap();
+
+        assert_eq!(select("/Envelope/Body/Order/Id"), vec!["42"]);
+        assert_eq!(
+            select("/soap:Envelope/soap:Body/m:Order/Id/text()"),
+            vec!["42"]
+        );
+        assert_eq!(select("//Item/Name"), vec!["Pen", "Paper [A4]"]);
+        assert_eq!(select("//Item[2]/@sku"), vec!["b-2"]);
+        assert_eq!(select("//Item[@sku='a-1']/Name"), vec!["Pen"]);
+        assert_eq!(select("//Item[Name='Paper [A4]']/@sku"), vec!["b-2"]);
+        assert_eq!(select("/*/*/*/@status"), vec!["open"]);
+        assert!(select("//Missing").is_empty());
+    }
+
+    #[test]
+    fn value_matches_test() {
+        assert!(value_matches(ORDER, "//Order/Id", Some("42")));
+        assert!(value_matches(ORDER, "//Order/Id", Some(" 42 ")));
+        assert!(!value_matches(ORDER, "//Order/Id", Some("43")));
+        assert!(value_matches(ORDER, "//Order/Id", None));
+        assert!(!value_matches(ORDER, "//Order/Missing", None));
+        assert!(!value_matches("not xml", "//Order/Id", None));
+    }
+
+    #[test]
+    fn invalid_path_test() {
+        let err = |path: &str| select(ORDER, path).err().unwrap();
+
+        assert_eq!(
+            err("Envelope"),
+            "invalid XPath 'Envelope': only absolute paths are supported"
+        );
+        assert_eq!(
+            err("//Item[1"),
+            "invalid XPath '//Item[1': unterminated predicate"
+        );
+        assert_eq!(
+            err("//@sku/Name"),
+            "invalid XPath '//@sku/Name': attributes can only be selected by the last step"
+        );
+        assert_eq!(
+            err("//Item[contains(Name, 'P')]"),
+            "invalid XPath '//Item[contains(Name, 'P')]': unsupported predicate 'contains(Name, 'P')'"
+        );
+    }
+
+    #[test]
+    fn compare_test() {
+        // Arrange
+        let expected = r#"<Order xmlns="urn:orders" id="1" status="open">
+              <Item sku="a-1">Pen</Item>
+              <!-- a comment -->
+            </Order>"#;
+        let equal = r#"<?xml version="1.0"?><o:Order xmlns:o="urn:orders" status="open" id="1"><o:Item sku="a-1"> Pen </o:Item></o:Order>"#;
+
+        // Act
+        let compare = |actual: &str| compare(expected, actual).err();
+
+        // Assert
+        assert_eq!(compare(equal), None);
+        assert_eq!(
+            compare(r#"<Order status="open" id="1"><Item sku="a-1">Pen</Item></Order>"#),
+            Some(
+                "/Order: expected element <Order> in namespace 'urn:orders' but got <Order>"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            compare(r#"<Order xmlns="urn:orders" id="2" status="open"><Item sku="a-1">Pen</Item></Order>"#),
+            Some(r#"/Order: expected attributes [id="1", status="open"] but got [id="2", status="open"]"#.to_string())
+        );
+        assert_eq!(
+            compare(
+                r#"<Order xmlns="urn:orders" id="1" status="open"><Item sku="a-1">Paper</Item></Order>"#
+            ),
+            Some(r#"/Order/Item: expected text "Pen" but got text "Paper""#.to_string())
+        );
+        assert_eq!(
+            compare(r#"<Order xmlns="urn:orders" id="1" status="open"/>"#),
+            Some("/Order: missing element <Item> in namespace 'urn:orders'".to_string())
+        );
+        assert_eq!(
+            compare(
+                r#"<Order xmlns="urn:orders" id="1" status="open"><Item sku="a-1">Pen</Item><Item/></Order>"#
+            ),
+            Some("/Order: unexpected element <Item> in namespace 'urn:orders'".to_string())
+        );
+        assert!(compare("not xml").unwrap().starts_with("invalid XML: "));
+    }
+}
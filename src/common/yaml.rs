@@ -0,0 +1,1040 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use regex::Regex;
+use serde::de::{Deserializer, Error};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::common::data::{
+    ActiveMock, BodyLength, HeaderValues, HmacSignature, JsonBodyIgnoringPaths,
+    JsonPathRequirement, JwtClaim, MockDefinition, MockServerHttpResponse, MultipartPart,
+    NdjsonLine, Pattern, QueryParamValues, QueryString, RequestRequirements, XPathRequirement,
+};
+use crate::common::util::read_file;
+use crate::standalone::NameValuePair;
+use crate::Method;
+
+/// A regular expression that is validated while the mock file is parsed, so that an invalid
+/// expression is reported together with its location in the file.
+#[derive(Debug)]
+pub(crate) struct YAMLPattern(pub Regex);
+
+impl Serialize for YAMLPattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for YAMLPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Regex::from_str(&value)
+            .map(YAMLPattern)
+            .map_err(|e| D::Error::custom(format!("invalid regex '{}': {}", value, e)))
+    }
+}
+
+/// Credentials that a request must send with basic authentication.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct YAMLBasicAuth {
+    pub username: String,
+    pub password: String,
+}
+
+/// A regular expression that a named value (e.g. a header) must match.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct YAMLNamePattern {
+    pub name: String,
+    pub value: YAMLPattern,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct YAMLRequestRequirements {
+    pub path: Option<String>,
+    pub path_contains: Option<Vec<String>>,
+    pub path_prefix: Option<Vec<String>>,
+    pub path_suffix: Option<Vec<String>>,
+    pub path_matches: Option<Vec<YAMLPattern>>,
+    pub path_not_contains: Option<Vec<String>>,
+    pub path_not_matches: Option<Vec<YAMLPattern>>,
+    pub path_case_insensitive: Option<bool>,
+    pub path_ignore_trailing_slash: Option<bool>,
+    pub method: Option<Method>,
+    pub methods: Option<Vec<Method>>,
+    pub http_version: Option<String>,
+    pub host: Option<String>,
+    pub listener: Option<String>,
+    pub remote_addr: Option<String>,
+    pub remote_addr_matches: Option<Vec<YAMLPattern>>,
+    pub header: Option<Vec<NameValuePair>>,
+    pub header_exists: Option<Vec<String>>,
+    pub header_matches: Option<Vec<YAMLNamePattern>>,
+    pub header_missing: Option<Vec<String>>,
+    pub header_values: Option<Vec<HeaderValues>>,
+    pub content_type: Option<String>,
+    pub accepts: Option<Vec<String>>,
+    pub basic_auth: Option<YAMLBasicAuth>,
+    pub bearer_token: Option<String>,
+    pub jwt_claim: Option<Vec<JwtClaim>>,
+    pub cookie: Option<Vec<NameValuePair>>,
+    pub cookie_exists: Option<Vec<String>>,
+    pub body: Option<String>,
+    pub json_body: Option<Value>,
+    pub json_body_ignoring_paths: Option<JsonBodyIgnoringPaths>,
+    pub json_body_partial: Option<Vec<Value>>,
+    pub json_body_strict: Option<Value>,
+    pub json_body_schema: Option<Value>,
+    pub json_body_path: Option<Vec<JsonPathRequirement>>,
+    pub ndjson_line: Option<Vec<NdjsonLine>>,
+    pub ndjson_line_count: Option<usize>,
+    pub body_xpath: Option<Vec<XPathRequirement>>,
+    pub xml_body: Option<String>,
+    pub multipart_part: Option<Vec<MultipartPart>>,
+    pub body_contains: Option<Vec<String>>,
+    pub body_matches: Option<Vec<YAMLPattern>>,
+    pub body_not_contains: Option<Vec<String>>,
+    pub body_not_matches: Option<Vec<YAMLPattern>>,
+    pub body_len: Option<BodyLength>,
+    pub chunked_body: Option<bool>,
+    pub hmac_signature: Option<Vec<HmacSignature>>,
+    pub query_param_exists: Option<Vec<String>>,
+    pub query_param: Option<Vec<NameValuePair>>,
+    pub query_param_matches: Option<Vec<YAMLNamePattern>>,
+    pub query_param_missing: Option<Vec<String>>,
+    pub query_param_values: Option<Vec<QueryParamValues>>,
+    /// The complete URL encoded query string of the request.
+    pub query_string: Option<String>,
+    /// Whether the parameters of `query_string` must appear in the given order.
+    pub query_string_ordered: Option<bool>,
+    /// Whether query parameters are matched in their percent-encoded form.
+    pub query_params_raw: Option<bool>,
+    pub query_object: Option<Vec<Value>>,
+    pub x_www_form_urlencoded_key_exists: Option<Vec<String>>,
+    pub x_www_form_urlencoded_tuple: Option<Vec<NameValuePair>>,
+    pub client_cert_cn: Option<String>,
+    /// The base64 encoded protobuf message of a gRPC request.
+    pub grpc_message: Option<String>,
+    /// The base64 encoded protobuf message that the body must be equal to.
+    pub protobuf_body: Option<String>,
+    pub graphql_operation: Option<String>,
+    pub graphql_variables: Option<Value>,
+    pub body_base64: Option<String>,
+    /// Alternative requirements of which the request must fulfill at least one.
+    pub any_of: Option<Vec<YAMLRequestRequirements>>,
+    pub active_from: Option<SystemTime>,
+    pub active_until: Option<SystemTime>,
+    pub disabled: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct YAMLHTTPResponse {
+    pub status: Option<u16>,
+    pub header: Option<Vec<NameValuePair>>,
+    pub body: Option<String>,
+    /// A file whose content is used as the response body. Relative paths are resolved against
+    /// the directory of the mock file.
+    pub body_file: Option<PathBuf>,
+    pub json_body: Option<Value>,
+    /// A Base64 encoded response body, which is used for binary bodies in recordings.
+    pub base64_body: Option<String>,
+    pub delay: Option<u64>,
+    pub trailer: Option<Vec<NameValuePair>>,
+    /// Responses that are served one after another. All other fields are ignored if set.
+    pub sequence: Option<Vec<YAMLHTTPResponse>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct YAMLMockDefinition {
+    /// The name the mock can be looked up by. Defaults to the file name without extension.
+    pub name: Option<String>,
+    pub when: YAMLRequestRequirements,
+    pub then: YAMLHTTPResponse,
+}
+
+/// A mock definition that was loaded from a YAML or JSON file.
+pub(crate) struct YAMLMock {
+    pub name: String,
+    pub path: PathBuf,
+    pub definition: MockDefinition,
+}
+
+/// An error that occurred while loading a mock file.
+#[derive(Debug)]
+pub(crate) struct YAMLMockError {
+    path: PathBuf,
+    message: String,
+}
+
+impl YAMLMockError {
+    fn new<S: Into<String>>(path: &Path, message: S) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for YAMLMockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid mock file '{}': {}",
+            self.path.to_string_lossy(),
+            self.message
+        )
+    }
+}
+
+impl From<YAMLMockError> for String {
+    fn from(error: YAMLMockError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Loads all YAML and JSON mock files from the provided directory, in alphabetical order.
+/// Mock names must be unique across all files.
+pub(crate) fn read_mock_dir(path: &Path) -> Result<Vec<YAMLMock>, YAMLMockError> {
+    let entries = std::fs::read_dir(path)
+        .map_err(|e| YAMLMockError::new(path, format!("cannot list files in directory: {}", e)))?;
+
+    let mut file_paths = Vec::new();
+    for entry in entries {
+        let file_path = entry
+            .map_err(|e| YAMLMockError::new(path, format!("cannot read directory entry: {}", e)))?
+            .path();
+        if is_mock_file(&file_path) {
+            file_paths.push(file_path);
+        }
+    }
+    file_paths.sort();
+
+    let mut mocks: Vec<YAMLMock> = Vec::with_capacity(file_paths.len());
+    for file_path in file_paths {
+        let mock = read_mock_file(&file_path)?;
+        if let Some(other) = mocks.iter().find(|m| m.name == mock.name) {
+            return Err(YAMLMockError::new(
+                &file_path,
+                format!(
+                    "name: mock name '{}' is already used by '{}'",
+                    mock.name,
+                    other.path.to_string_lossy()
+                ),
+            ));
+        }
+        mocks.push(mock);
+    }
+
+    Ok(mocks)
+}
+
+/// Returns true if the path has an extension of a supported mock file format (`yaml`, `yml` or
+/// `json`).
+pub(crate) fn is_mock_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("yaml") | Some("yml") | Some("json")
+    )
+}
+
+/// Loads a single mock file. Files with a `json` extension are parsed as JSON, all other files
+/// as YAML.
+pub(crate) fn read_mock_file(path: &Path) -> Result<YAMLMock, YAMLMockError> {
+    log::info!("Loading mock file from '{}'", path.to_string_lossy());
+
+    let content = read_file_to_string(path)?;
+    let yaml_definition: YAMLMockDefinition = match path.extension() {
+        Some(ext) if ext == "json" => serde_json::from_str(&content).map_err(|e| e.to_string()),
+        _ => serde_yaml::from_str(&content).map_err(|e| e.to_string()),
+    }
+    .map_err(|message| YAMLMockError::new(path, message))?;
+
+    let name = match &yaml_definition.name {
+        Some(name) => name.clone(),
+        None => file_stem(path)?,
+    };
+
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let definition = map_to_mock_definition(yaml_definition, base_dir)
+        .map_err(|message| YAMLMockError::new(path, message))?;
+
+    Ok(YAMLMock {
+        name,
+        path: path.to_path_buf(),
+        definition,
+    })
+}
+
+/// Loads a YAML file that contains any number of mock definitions separated by `---`, such as
+/// a recording. Mocks without a name are named after the file and their position in the file
+/// (e.g. `github-2` for the second mock in `github.yaml`).
+pub(crate) fn read_recording_file(path: &Path) -> Result<Vec<YAMLMock>, YAMLMockError> {
+    log::info!("Loading recording from '{}'", path.to_string_lossy());
+
+    let content = read_file_to_string(path)?;
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let stem = file_stem(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut mocks = Vec::new();
+    for (idx, document) in serde_yaml::Deserializer::from_str(&content).enumerate() {
+        let yaml_definition = YAMLMockDefinition::deserialize(document)
+            .map_err(|e| YAMLMockError::new(path, e.to_string()))?;
+        let name = match &yaml_definition.name {
+            Some(name) => name.clone(),
+            None => format!("{}-{}", stem, idx + 1),
+        };
+        let definition = map_to_mock_definition(yaml_definition, base_dir).map_err(|message| {
+            YAMLMockError::new(path, format!("mock {}: {}", idx + 1, message))
+        })?;
+        mocks.push(YAMLMock {
+            name,
+            path: path.to_path_buf(),
+            definition,
+        });
+    }
+
+    Ok(mocks)
+}
+
+fn read_file_to_string(path: &Path) -> Result<String, YAMLMockError> {
+    let content = read_file(path)
+        .map_err(|e| YAMLMockError::new(path, format!("cannot read file: {}", e)))?;
+    String::from_utf8(content)
+        .map_err(|e| YAMLMockError::new(path, format!("file is not valid UTF-8: {}", e)))
+}
+
+fn file_stem(path: &Path) -> Result<String, YAMLMockError> {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .ok_or_else(|| YAMLMockError::new(path, "cannot derive a mock name from the file"))
+}
+
+fn map_to_mock_definition(
+    yaml_definition: YAMLMockDefinition,
+    base_dir: &Path,
+) -> Result<MockDefinition, String> {
+    Ok(MockDefinition {
+        request: map_to_request_requirements(yaml_definition.when),
+        response: map_to_response(yaml_definition.then, base_dir)?,
+    })
+}
+
+fn map_to_request_requirements(when: YAMLRequestRequirements) -> RequestRequirements {
+    let query_string_ordered = when.query_string_ordered.unwrap_or(false);
+    RequestRequirements {
+        path: when.path,
+        path_contains: when.path_contains,
+        path_prefix: when.path_prefix,
+        path_suffix: when.path_suffix,
+        path_matches: to_pattern_vec(when.path_matches),
+        path_not_contains: when.path_not_contains,
+        path_not_matches: to_pattern_vec(when.path_not_matches),
+        path_case_insensitive: when.path_case_insensitive.unwrap_or(false),
+        path_ignore_trailing_slash: when.path_ignore_trailing_slash.unwrap_or(false),
+        method: when.method.map(|m| m.to_string()),
+        methods: when
+            .methods
+            .map(|methods| methods.iter().map(|m| m.to_string()).collect()),
+        http_version: when.http_version,
+        host: when.host,
+        listener: when.listener,
+        remote_addr: when.remote_addr,
+        remote_addr_matches: to_pattern_vec(when.remote_addr_matches),
+        headers: to_pair_vec(when.header),
+        header_exists: when.header_exists,
+        header_matches: to_name_pattern_vec(when.header_matches),
+        header_missing: when.header_missing,
+        header_values: when.header_values,
+        content_type: when.content_type,
+        accepts: when.accepts,
+        basic_auth: when
+            .basic_auth
+            .map(|auth| format!("{}:{}", auth.username, auth.password)),
+        bearer_token: when.bearer_token,
+        jwt_claims: when.jwt_claim,
+        cookies: to_pair_vec(when.cookie),
+        cookie_exists: when.cookie_exists,
+        body: when.body,
+        json_body: when.json_body,
+        json_body_ignoring_paths: when.json_body_ignoring_paths,
+        json_body_includes: when.json_body_partial,
+        json_body_strict: when.json_body_strict,
+        json_body_schema: when.json_body_schema,
+        json_body_paths: when.json_body_path,
+        ndjson_lines: when.ndjson_line,
+        ndjson_line_count: when.ndjson_line_count,
+        body_xpaths: when.body_xpath,
+        xml_body: when.xml_body,
+        multipart_parts: when.multipart_part,
+        body_contains: when.body_contains,
+        body_matches: to_pattern_vec(when.body_matches),
+        body_not_contains: when.body_not_contains,
+        body_not_matches: to_pattern_vec(when.body_not_matches),
+        body_len: when.body_len,
+        chunked_body: when.chunked_body.unwrap_or(false),
+        hmac_signatures: when.hmac_signature,
+        query_param_exists: when.query_param_exists,
+        query_param: to_pair_vec(when.query_param),
+        query_param_matches: to_name_pattern_vec(when.query_param_matches),
+        query_param_missing: when.query_param_missing,
+        query_param_values: when.query_param_values,
+        query_string: when
+            .query_string
+            .map(|q| QueryString::parse(&q, query_string_ordered)),
+        query_objects: when.query_object,
+        query_params_raw: when.query_params_raw.unwrap_or(false),
+        x_www_form_urlencoded: to_pair_vec(when.x_www_form_urlencoded_tuple),
+        x_www_form_urlencoded_key_exists: when.x_www_form_urlencoded_key_exists,
+        client_cert_cn: when.client_cert_cn,
+        grpc_message: when.grpc_message,
+        protobuf_body: when.protobuf_body,
+        graphql_operation: when.graphql_operation,
+        graphql_variables: when.graphql_variables,
+        body_base64: when.body_base64,
+        any_of: when.any_of.map(|alternatives| {
+            alternatives
+                .into_iter()
+                .map(map_to_request_requirements)
+                .collect()
+        }),
+        active_from: when.active_from,
+        active_until: when.active_until,
+        disabled: when.disabled.unwrap_or(false),
+        ..RequestRequirements::new()
+    }
+}
+
+fn map_to_response(
+    then: YAMLHTTPResponse,
+    base_dir: &Path,
+) -> Result<MockServerHttpResponse, String> {
+    let sequence = match then.sequence {
+        Some(sequence) => Some(
+            sequence
+                .into_iter()
+                .map(|response| map_to_response(response, base_dir))
+                .collect::<Result<Vec<_>, String>>()?,
+        ),
+        None => None,
+    };
+
+    Ok(MockServerHttpResponse {
+        status: then.status,
+        headers: to_pair_vec(then.header),
+        body: to_response_body(
+            then.body,
+            then.body_file,
+            then.json_body,
+            then.base64_body,
+            base_dir,
+        )?,
+        delay: then.delay.map(Duration::from_millis),
+        sequence,
+        trailers: to_pair_vec(then.trailer),
+    })
+}
+
+/// Converts mocks into a YAML file with one mock definition per document, which can be loaded
+/// again with [read_recording_file]. Returns an error that lists all mocks that cannot be
+/// expressed in YAML, such as mocks with custom matcher functions.
+pub(crate) fn to_mocks_file(mocks: &[ActiveMock]) -> Result<String, String> {
+    let mut documents = Vec::with_capacity(mocks.len());
+    let mut errors = Vec::new();
+    for mock in mocks {
+        match to_yaml_mock_definition(&mock.definition) {
+            Ok(definition) => documents.push(definition),
+            Err(e) => errors.push(format!("mock {}: {}", mock.id, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!(
+            "The following mocks cannot be serialized:\n{}",
+            errors.join("\n")
+        ));
+    }
+
+    documents
+        .iter()
+        .map(|definition| {
+            let mut document = serde_yaml::to_value(definition).map_err(|e| e.to_string())?;
+            retain_set_fields(&mut document);
+            if let Some(when) = document.get_mut("when") {
+                retain_set_request_fields(when);
+            }
+            if let Some(then) = document.get_mut("then") {
+                retain_set_fields(then);
+                if let Some(serde_yaml::Value::Sequence(sequence)) = then.get_mut("sequence") {
+                    sequence.iter_mut().for_each(retain_set_fields);
+                }
+            }
+            serde_yaml::to_string(&document).map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<String>, String>>()
+        .map(|documents| documents.join("---\n"))
+}
+
+/// Removes the request requirements that are not set, including the ones of alternatives.
+fn retain_set_request_fields(when: &mut serde_yaml::Value) {
+    retain_set_fields(when);
+    if let Some(serde_yaml::Value::Sequence(alternatives)) = when.get_mut("any_of") {
+        alternatives.iter_mut().for_each(retain_set_request_fields);
+    }
+}
+
+/// Removes the fields of a mapping that are not set. Values inside of JSON documents are kept.
+fn retain_set_fields(value: &mut serde_yaml::Value) {
+    if let serde_yaml::Value::Mapping(mapping) = value {
+        mapping.retain(|_, v| !v.is_null());
+    }
+}
+
+fn to_yaml_mock_definition(definition: &MockDefinition) -> Result<YAMLMockDefinition, String> {
+    Ok(YAMLMockDefinition {
+        name: None,
+        when: to_yaml_request_requirements(&definition.request)?,
+        then: to_yaml_response(&definition.response),
+    })
+}
+
+fn to_yaml_request_requirements(
+    request: &RequestRequirements,
+) -> Result<YAMLRequestRequirements, String> {
+    if request.matchers.as_ref().map_or(false, |m| !m.is_empty()) {
+        return Err("custom matcher functions cannot be serialized".to_string());
+    }
+    if let Some(matcher) = request.custom_matchers.iter().flatten().next() {
+        return Err(format!(
+            "the custom matcher '{}' cannot be serialized",
+            matcher
+        ));
+    }
+    let to_method = |method: &String| {
+        Method::from_str(method)
+            .map_err(|_| format!("the method '{}' cannot be serialized", method))
+    };
+    let method = request.method.as_ref().map(to_method).transpose()?;
+    let methods = match &request.methods {
+        Some(methods) => Some(methods.iter().map(to_method).collect::<Result<_, _>>()?),
+        None => None,
+    };
+
+    let any_of = match &request.any_of {
+        Some(alternatives) => Some(
+            alternatives
+                .iter()
+                .map(to_yaml_request_requirements)
+                .collect::<Result<_, _>>()?,
+        ),
+        None => None,
+    };
+
+    Ok(YAMLRequestRequirements {
+        path: request.path.clone(),
+        path_contains: request.path_contains.clone(),
+        path_prefix: request.path_prefix.clone(),
+        path_suffix: request.path_suffix.clone(),
+        path_matches: from_pattern_vec(&request.path_matches),
+        path_not_contains: request.path_not_contains.clone(),
+        path_not_matches: from_pattern_vec(&request.path_not_matches),
+        path_case_insensitive: Some(true).filter(|_| request.path_case_insensitive),
+        path_ignore_trailing_slash: Some(true).filter(|_| request.path_ignore_trailing_slash),
+        method,
+        methods,
+        http_version: request.http_version.clone(),
+        host: request.host.clone(),
+        listener: request.listener.clone(),
+        remote_addr: request.remote_addr.clone(),
+        remote_addr_matches: from_pattern_vec(&request.remote_addr_matches),
+        header: from_pair_vec(&request.headers),
+        header_exists: request.header_exists.clone(),
+        header_matches: from_name_pattern_vec(&request.header_matches),
+        header_missing: request.header_missing.clone(),
+        header_values: request.header_values.clone(),
+        content_type: request.content_type.clone(),
+        accepts: request.accepts.clone(),
+        basic_auth: request.basic_auth.as_ref().map(|credentials| {
+            let (username, password) = credentials.split_once(':').unwrap_or((credentials, ""));
+            YAMLBasicAuth {
+                username: username.to_string(),
+                password: password.to_string(),
+            }
+        }),
+        bearer_token: request.bearer_token.clone(),
+        jwt_claim: request.jwt_claims.clone(),
+        cookie: from_pair_vec(&request.cookies),
+        cookie_exists: request.cookie_exists.clone(),
+        body: request.body.clone(),
+        json_body: request.json_body.clone(),
+        json_body_ignoring_paths: request.json_body_ignoring_paths.clone(),
+        json_body_partial: request.json_body_includes.clone(),
+        json_body_strict: request.json_body_strict.clone(),
+        json_body_schema: request.json_body_schema.clone(),
+        json_body_path: request.json_body_paths.clone(),
+        ndjson_line: request.ndjson_lines.clone(),
+        ndjson_line_count: request.ndjson_line_count,
+        body_xpath: request.body_xpaths.clone(),
+        xml_body: request.xml_body.clone(),
+        multipart_part: request.multipart_parts.clone(),
+        body_contains: request.body_contains.clone(),
+        body_matches: from_pattern_vec(&request.body_matches),
+        body_not_contains: request.body_not_contains.clone(),
+        body_not_matches: from_pattern_vec(&request.body_not_matches),
+        body_len: request.body_len.clone(),
+        chunked_body: Some(true).filter(|_| request.chunked_body),
+        hmac_signature: request.hmac_signatures.clone(),
+        query_param_exists: request.query_param_exists.clone(),
+        query_param: from_pair_vec(&request.query_param),
+        query_param_matches: from_name_pattern_vec(&request.query_param_matches),
+        query_param_missing: request.query_param_missing.clone(),
+        query_param_values: request.query_param_values.clone(),
+        query_string: request.query_string.as_ref().map(QueryString::encoded),
+        query_string_ordered: request
+            .query_string
+            .as_ref()
+            .filter(|q| q.ordered)
+            .map(|_| true),
+        query_object: request.query_objects.clone(),
+        query_params_raw: Some(true).filter(|_| request.query_params_raw),
+        x_www_form_urlencoded_key_exists: request.x_www_form_urlencoded_key_exists.clone(),
+        x_www_form_urlencoded_tuple: from_pair_vec(&request.x_www_form_urlencoded),
+        client_cert_cn: request.client_cert_cn.clone(),
+        grpc_message: request.grpc_message.clone(),
+        protobuf_body: request.protobuf_body.clone(),
+        graphql_operation: request.graphql_operation.clone(),
+        graphql_variables: request.graphql_variables.clone(),
+        body_base64: request.body_base64.clone(),
+        any_of,
+        active_from: request.active_from,
+        active_until: request.active_until,
+        disabled: Some(true).filter(|_| request.disabled),
+    })
+}
+
+fn to_yaml_response(response: &MockServerHttpResponse) -> YAMLHTTPResponse {
+    let mut yaml_response = YAMLHTTPResponse {
+        status: response.status,
+        header: from_pair_vec(&response.headers),
+        delay: response.delay.map(|delay| delay.as_millis() as u64),
+        trailer: from_pair_vec(&response.trailers),
+        sequence: response
+            .sequence
+            .as_ref()
+            .map(|sequence| sequence.iter().map(to_yaml_response).collect()),
+        ..YAMLHTTPResponse::default()
+    };
+    match response
+        .body
+        .as_ref()
+        .map(|body| String::from_utf8(body.clone()))
+    {
+        Some(Ok(body)) => yaml_response.body = Some(body),
+        Some(Err(e)) => yaml_response.base64_body = Some(base64::encode(e.as_bytes())),
+        None => {}
+    }
+    yaml_response
+}
+
+fn to_response_body(
+    body: Option<String>,
+    body_file: Option<PathBuf>,
+    json_body: Option<Value>,
+    base64_body: Option<String>,
+    base_dir: &Path,
+) -> Result<Option<Vec<u8>>, String> {
+    match (body, body_file, json_body, base64_body) {
+        (None, None, None, None) => Ok(None),
+        (Some(body), None, None, None) => Ok(Some(body.into_bytes())),
+        // Mock files written for earlier versions may specify both, in which case 'body' has
+        // always taken precedence.
+        (Some(body), None, Some(_), None) => {
+            log::warn!("then: both 'body' and 'json_body' are specified, 'json_body' is ignored");
+            Ok(Some(body.into_bytes()))
+        }
+        (None, None, Some(json_body), None) => Ok(Some(json_body.to_string().into_bytes())),
+        (None, None, None, Some(base64_body)) => base64::decode(base64_body)
+            .map(Some)
+            .map_err(|e| format!("then.base64_body: invalid Base64: {}", e)),
+        (None, Some(body_file), None, None) => {
+            let path = base_dir.join(&body_file);
+            read_file(&path).map(Some).map_err(|e| {
+                format!(
+                    "then.body_file: cannot read '{}': {}",
+                    path.to_string_lossy(),
+                    e
+                )
+            })
+        }
+        _ => Err(
+            "then: only one of 'body', 'body_file', 'json_body' and 'base64_body' can be \
+                  specified"
+                .to_string(),
+        ),
+    }
+}
+
+fn to_pattern_vec(vec: Option<Vec<YAMLPattern>>) -> Option<Vec<Pattern>> {
+    vec.map(|vec| vec.into_iter().map(|p| Pattern::from_regex(p.0)).collect())
+}
+
+fn to_pair_vec(kvp: Option<Vec<NameValuePair>>) -> Option<Vec<(String, String)>> {
+    kvp.map(|vec| vec.into_iter().map(|nvp| (nvp.name, nvp.value)).collect())
+}
+
+fn to_name_pattern_vec(vec: Option<Vec<YAMLNamePattern>>) -> Option<Vec<(String, Pattern)>> {
+    vec.map(|vec| {
+        vec.into_iter()
+            .map(|np| (np.name, Pattern::from_regex(np.value.0)))
+            .collect()
+    })
+}
+
+fn from_pattern_vec(vec: &Option<Vec<Pattern>>) -> Option<Vec<YAMLPattern>> {
+    vec.as_ref()
+        .map(|vec| vec.iter().map(|p| YAMLPattern(p.regex.clone())).collect())
+}
+
+fn from_pair_vec(vec: &Option<Vec<(String, String)>>) -> Option<Vec<NameValuePair>> {
+    vec.as_ref().map(|vec| {
+        vec.iter()
+            .map(|(name, value)| NameValuePair {
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    })
+}
+
+fn from_name_pattern_vec(vec: &Option<Vec<(String, Pattern)>>) -> Option<Vec<YAMLNamePattern>> {
+    vec.as_ref().map(|vec| {
+        vec.iter()
+            .map(|(name, p)| YAMLNamePattern {
+                name: name.clone(),
+                value: YAMLPattern(p.regex.clone()),
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::common::data::{
+        ActiveMock, BodyLength, HeaderValues, HmacSignature, JsonBodyIgnoringPaths,
+        JsonPathRequirement, JwtClaim, MockDefinition, MockServerHttpResponse, MultipartPart,
+        NdjsonLine, Pattern, QueryParamValues, QueryString, RequestRequirements,
+    };
+    use crate::common::yaml::{read_mock_dir, read_mock_file, read_recording_file, to_mocks_file};
+    use regex::Regex;
+    use serde_json::json;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("httpmock-yaml-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn read_example_mock_file_test() {
+        let path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/static_yaml_mock.yaml");
+
+        let mock = read_mock_file(&path).unwrap();
+
+        assert_eq!(mock.name, "static_yaml_mock");
+        assert_eq!(mock.definition.request.path, Some("/hello".to_string()));
+        assert_eq!(mock.definition.response.status, Some(200));
+        assert_eq!(
+            mock.definition.response.body,
+            Some(b"this is an example body string".to_vec())
+        );
+    }
+
+    #[test]
+    fn unknown_field_error_test() {
+        let dir = temp_dir("unknown-field");
+        let path = dir.join("broken.yaml");
+        std::fs::write(
+            &path,
+            "when:\n  path: /hello\n  pathh: /typo\nthen:\n  status: 200\n",
+        )
+        .unwrap();
+
+        let err = read_mock_file(&path).err().unwrap().to_string();
+
+        assert!(err.contains("broken.yaml"), "{}", err);
+        assert!(err.contains("unknown field `pathh`"), "{}", err);
+        assert!(err.contains("line 3"), "{}", err);
+    }
+
+    #[test]
+    fn invalid_regex_error_test() {
+        let dir = temp_dir("invalid-regex");
+        let path = dir.join("regex.yaml");
+        std::fs::write(
+            &path,
+            "when:\n  path_matches:\n    - '(unclosed'\nthen:\n  status: 200\n",
+        )
+        .unwrap();
+
+        let err = read_mock_file(&path).err().unwrap().to_string();
+
+        assert!(err.contains("regex.yaml"), "{}", err);
+        assert!(err.contains("when.path_matches"), "{}", err);
+        assert!(err.contains("invalid regex"), "{}", err);
+    }
+
+    #[test]
+    fn conflicting_body_error_test() {
+        let dir = temp_dir("conflicting-body");
+        let path = dir.join("body.yaml");
+        std::fs::write(
+            &path,
+            "when:\n  path: /\nthen:\n  body: a\n  base64_body: AAE=\n",
+        )
+        .unwrap();
+
+        let err = read_mock_file(&path).err().unwrap().to_string();
+
+        assert!(err.contains("body.yaml"), "{}", err);
+        assert!(err.contains("only one of"), "{}", err);
+    }
+
+    #[test]
+    fn duplicate_name_error_test() {
+        let dir = temp_dir("duplicate-name");
+        std::fs::write(dir.join("a.yaml"), "name: same\nwhen: {}\nthen: {}\n").unwrap();
+        std::fs::write(dir.join("b.yml"), "name: same\nwhen: {}\nthen: {}\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let err = read_mock_dir(&dir).err().unwrap().to_string();
+
+        assert!(err.contains("b.yml"), "{}", err);
+        assert!(err.contains("'same' is already used"), "{}", err);
+    }
+
+    #[test]
+    fn read_recording_file_test() {
+        let dir = temp_dir("recording");
+        let path = dir.join("github.yaml");
+        std::fs::write(
+            &path,
+            "when:\n  path: /a\nthen:\n  status: 200\n---\nname: b\nwhen:\n  path: /b\nthen:\n  \
+             base64_body: AAE=\n",
+        )
+        .unwrap();
+
+        let mocks = read_recording_file(&path).unwrap();
+
+        assert_eq!(mocks.len(), 2);
+        assert_eq!(mocks[0].name, "github-1");
+        assert_eq!(mocks[1].name, "b");
+        assert_eq!(mocks[1].definition.response.body, Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn json_mock_file_error_test() {
+        let dir = temp_dir("json");
+        let path = dir.join("mock.json");
+        std::fs::write(
+            &path,
+            "{\n  \"when\": {},\n  \"then\": { \"stauts\": 200 }\n}",
+        )
+        .unwrap();
+
+        let err = read_mock_file(&path).err().unwrap().to_string();
+
+        assert!(err.contains("mock.json"), "{}", err);
+        assert!(err.contains("unknown field `stauts`"), "{}", err);
+        assert!(err.contains("line 3"), "{}", err);
+    }
+
+    #[test]
+    fn mocks_file_round_trip_test() {
+        // Arrange
+        let pattern = |regex: &str| Pattern::from_regex(Regex::new(regex).unwrap());
+        let pair = |name: &str, value: &str| (name.to_string(), value.to_string());
+        let mut request = RequestRequirements::new()
+            .with_method("POST".to_string())
+            .with_path("/users".to_string())
+            .with_path_contains(vec!["users".to_string()])
+            .with_path_prefix(vec!["/us".to_string()])
+            .with_path_suffix(vec!["ers".to_string()])
+            .with_path_matches(vec![pattern("^/users$")])
+            .with_path_not_contains(vec!["admin".to_string()])
+            .with_path_not_matches(vec![pattern("^/internal")])
+            .with_headers(vec![pair("accept", "application/json")])
+            .with_header_exists(vec!["authorization".to_string()])
+            .with_cookies(vec![pair("session", "1")])
+            .with_cookie_exists(vec!["tracking".to_string()])
+            .with_json_body(json!({ "name": "Fred", "nickname": null }))
+            .with_json_body_includes(vec![json!({ "name": "Fred" })])
+            .with_json_body_strict(json!({ "name": "Fred", "nickname": null }))
+            .with_json_body_schema(json!({ "type": "object" }))
+            .with_body_contains(vec!["Fred".to_string()])
+            .with_body_matches(vec![pattern("Fr.d")])
+            .with_body_not_contains(vec!["nickname".to_string()])
+            .with_body_not_matches(vec![pattern("pass(word)?")])
+            .with_query_param_exists(vec!["debug".to_string()])
+            .with_query_param(vec![pair("tag", "a b")]);
+        request.header_matches = Some(vec![("x-id".to_string(), pattern("^[0-9]+$"))]);
+        request.query_param_matches = Some(vec![("page".to_string(), pattern("^[0-9]$"))]);
+        request.query_param_missing = Some(vec!["internal".to_string()]);
+        request.path_case_insensitive = true;
+        request.path_ignore_trailing_slash = true;
+        request.chunked_body = true;
+        request.query_params_raw = true;
+        request.json_body_ignoring_paths = Some(JsonBodyIgnoringPaths {
+            body: json!({ "name": "Fred", "id": 1 }),
+            ignored_paths: vec!["$.id".to_string()],
+        });
+        request.query_string = Some(QueryString::parse("id=1&id=2&tag=a+b", true));
+        request.query_objects = Some(vec![json!({ "filter": { "status": "open" } })]);
+        request.query_param_values = Some(vec![QueryParamValues {
+            name: "id".to_string(),
+            values: vec!["1".to_string(), "2".to_string()],
+        }]);
+        request.header_missing = Some(vec!["x-api-key".to_string()]);
+        request.body_len = Some(BodyLength {
+            min: Some(1),
+            max: None,
+        });
+        request.hmac_signatures = Some(vec![HmacSignature {
+            header: "X-Hub-Signature-256".to_string(),
+            secret: "s3cret".to_string(),
+        }]);
+        request.header_values = Some(vec![HeaderValues {
+            name: "x-forwarded-for".to_string(),
+            values: vec!["1.1.1.1".to_string(), "2.2.2.2".to_string()],
+        }]);
+        request.methods = Some(vec!["POST".to_string(), "PUT".to_string()]);
+        request.content_type = Some("application/json; charset=utf-8".to_string());
+        request.accepts = Some(vec!["application/json".to_string()]);
+        request.basic_auth = Some("alice:s3cr:et".to_string());
+        request.bearer_token = Some("abc123".to_string());
+        request.jwt_claims = Some(vec![JwtClaim {
+            name: "roles".to_string(),
+            value: json!(["admin"]),
+        }]);
+        request.json_body_paths = Some(vec![JsonPathRequirement {
+            path: "$.name".to_string(),
+            value: Some(json!("Fred")),
+        }]);
+        request.ndjson_lines = Some(vec![NdjsonLine {
+            index: 1,
+            value: json!({ "name": "Fred" }),
+        }]);
+        request.ndjson_line_count = Some(2);
+        request.multipart_parts = Some(vec![MultipartPart::new("avatar")
+            .filename("avatar.png")
+            .content_type("image/png")]);
+        request.xml_body = Some("<order><id>1</id></order>".to_string());
+        request.x_www_form_urlencoded = Some(vec![pair("a", "b")]);
+        request.x_www_form_urlencoded_key_exists = Some(vec!["c".to_string()]);
+        request.client_cert_cn = Some("client".to_string());
+        request.http_version = Some("HTTP/2".to_string());
+        request.host = Some("example.com".to_string());
+        request.listener = Some("api".to_string());
+        request.remote_addr = Some("127.0.0.1:52300".to_string());
+        request.remote_addr_matches = Some(vec![pattern("^127\\.")]);
+        request.grpc_message = Some("CgNGcmVk".to_string());
+        request.protobuf_body = Some("CgRGcmVkEB4=".to_string());
+        request.graphql_operation = Some("GetUser".to_string());
+        request.graphql_variables = Some(json!({ "id": "1" }));
+        request.body_base64 = Some("iVBORw==".to_string());
+        request.any_of = Some(vec![
+            RequestRequirements::new().with_path("/v1/users".to_string()),
+            RequestRequirements::new()
+                .with_path_prefix(vec!["/v2/".to_string()])
+                .with_any_of(vec![
+                    RequestRequirements::new().with_headers(vec![pair("x-api-version", "2")])
+                ]),
+        ]);
+        request.active_from = Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+        request.active_until = Some(UNIX_EPOCH + Duration::from_secs(1_800_000_000));
+        request.disabled = true;
+
+        let mut response = MockServerHttpResponse::new();
+        response.status = Some(201);
+        response.headers = Some(vec![pair("content-type", "text/plain")]);
+        response.body = Some(b"created".to_vec());
+        response.delay = Some(Duration::from_millis(10));
+        response.trailers = Some(vec![pair("grpc-status", "0")]);
+        let mut binary = MockServerHttpResponse::new();
+        binary.body = Some(vec![0, 159, 146, 150]);
+        response.sequence = Some(vec![binary, MockServerHttpResponse::new()]);
+
+        let definition = MockDefinition { request, response };
+        let mocks = vec![
+            ActiveMock::new(1, definition.clone(), false),
+            ActiveMock::new(
+                2,
+                MockDefinition {
+                    request: RequestRequirements::new(),
+                    response: MockServerHttpResponse::new(),
+                },
+                true,
+            ),
+        ];
+
+        // Act
+        let content = to_mocks_file(&mocks).unwrap();
+        let path = temp_dir("round-trip").join("mocks.yaml");
+        std::fs::write(&path, &content).unwrap();
+        let restored = read_recording_file(&path).unwrap();
+
+        // Assert
+        assert_eq!(restored.len(), 2);
+        assert_eq!(
+            serde_json::to_value(&restored[0].definition).unwrap(),
+            serde_json::to_value(&definition).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&restored[1].definition.request).unwrap(),
+            serde_json::to_value(&RequestRequirements::new()).unwrap()
+        );
+    }
+
+    #[test]
+    fn mocks_file_error_test() {
+        // Arrange
+        let mut request = RequestRequirements::new();
+        request.matchers = Some(vec![Arc::new(|_| true)]);
+        let closure_mock = MockDefinition {
+            request,
+            response: MockServerHttpResponse::new(),
+        };
+        let custom_method_mock = MockDefinition {
+            request: RequestRequirements::new().with_method("NOT A METHOD".to_string()),
+            response: MockServerHttpResponse::new(),
+        };
+        let mocks = vec![
+            ActiveMock::new(3, closure_mock, false),
+            ActiveMock::new(4, custom_method_mock, false),
+        ];
+
+        // Act
+        let err = to_mocks_file(&mocks).err().unwrap();
+
+        // Assert
+        assert_eq!(
+            err,
+            "The following mocks cannot be serialized:\n\
+             mock 3: custom matcher functions cannot be serialized\n\
+             mock 4: the method 'NOT A METHOD' cannot be serialized"
+        );
+    }
+}
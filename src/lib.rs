@@ -69,6 +69,13 @@
 //! asynchronous counterpart to [MockServer::start](struct.MockServer.html#method.start). You can
 //! find similar methods throughout the entire library.
 //!
+//! The blocking methods that manage mocks ([MockServer::mock](struct.MockServer.html#method.mock),
+//! [MockServer::reset](struct.MockServer.html#method.reset),
+//! [Mock::hits](struct.Mock.html#method.hits), [Mock::assert](struct.Mock.html#method.assert),
+//! [Mock::delete](struct.Mock.html#method.delete) and their variants) panic when they are called
+//! from within a tokio runtime, because they would block the executor of the test. Please use
+//! their `async` variants in asynchronous tests instead.
+//!
 //! # Parallelism
 //! To balance execution speed and resource consumption, [MockServer](struct.MockServer.html)s
 //! are kept in a server pool internally. This allows to run tests in parallel without overwhelming
@@ -80,6 +87,60 @@
 //! [MockServer](struct.MockServer.html)s are never recreated but recycled/reset.
 //! The pool is filled on demand up to a maximum number of 25 servers.
 //! You can override this number by using the environment variable `HTTPMOCK_MAX_SERVERS`.
+//! If a test requires a dedicated server that is not managed by the pool, it can use
+//! [MockServer::builder](struct.MockServer.html#method.builder) to start one.
+//!
+//! # HTTPS
+//! When the `https` feature is enabled, a [MockServer](struct.MockServer.html) can serve HTTPS
+//! using a certificate chain and private key of your choice. Such servers are configured by using
+//! [MockServer::builder](struct.MockServer.html#method.builder) and are not taken from the
+//! server pool:
+//!
+//! ```
+//! # #[cfg(feature = "https")]
+//! # {
+//! let server = httpmock::MockServer::builder()
+//!     .tls_cert_pem_file("tests/resources/certificates/server_chain.pem")
+//!     .tls_key_pem_file("tests/resources/certificates/server_key.pem")
+//!     .tls_alpn_protocols(["http/1.1"])
+//!     .start();
+//!
+//! assert!(server.url("/hello").starts_with("https://"));
+//! # }
+//! ```
+//!
+//! # Runtime
+//! Mock servers that are started by a test run on a [tokio](https://tokio.rs) runtime in
+//! background threads, which all mock servers of the test process share, so that starting a
+//! server only costs binding its socket. Synchronous tests therefore do not need an async runtime, and the
+//! runtime of an asynchronous test (e.g. `#[tokio::test]` or `#[async_std::test]`) is never
+//! used to serve requests. `tokio` is a required dependency nevertheless: the server is built on
+//! [hyper](https://hyper.rs), so there is no build without an async runtime.
+//!
+//! # Test Macro
+//! With the `macros` Cargo feature, the [test](attr.test.html) attribute turns a function that
+//! receives a [MockServer](struct.MockServer.html) into a test, so that tests do not need to
+//! start a server themselves. Async tests additionally need a runtime attribute, such as
+//! `#[tokio::test]`, below `#[httpmock::test]`. With `#[httpmock::test(verify_all)]`, a test
+//! fails if any of its mocks did not receive a request (see
+//! [MockServer::verify_all](struct.MockServer.html#method.verify_all)):
+//!
+//! ```
+//! # #[cfg(feature = "macros")]
+//! # mod example {
+//! use httpmock::prelude::*;
+//!
+//! #[httpmock::test(verify_all)]
+//! fn hello_test(server: MockServer) {
+//!     server.mock(|when, then| {
+//!         when.path("/hello");
+//!         then.status(200);
+//!     });
+//!
+//!     isahc::get(server.url("/hello")).unwrap();
+//! }
+//! # }
+//! ```
 //!
 //! # Debugging
 //! `httpmock` logs against the [log](https://crates.io/crates/log) crate. This allows you to
@@ -96,6 +157,35 @@
 //! *Hint*: If you use the `env_logger` backend, you need to set the `RUST_LOG` environment variable to
 //! `httpmock=debug`.
 //!
+//! [MockServer::log_requests](struct.MockServer.html#method.log_requests) logs every request
+//! that a mock server receives in a readable or a JSON format, along with the mock that it
+//! matched and the response status. Secret headers can be redacted with
+//! [MockServer::redact_headers](struct.MockServer.html#method.redact_headers).
+//!
+//! With the `tracing` Cargo feature, the mock server is instrumented with
+//! [tracing](https://crates.io/crates/tracing) instead: every request is handled in an
+//! `httpmock.request` span (INFO level) that records the `method`, `path`, matched `mock_id`,
+//! response `status` and `duration_ms`. Matcher evaluations are emitted as TRACE events and the
+//! creation and deletion of mocks as DEBUG events. Without a tracing subscriber, these events are
+//! still written to the `log` crate.
+//!
+//! To reproduce requests without a Rust toolchain, [Mock::to_curl](struct.Mock.html#method.to_curl)
+//! renders a [curl](https://curl.se/) command for an example request that matches a mock and
+//! `HttpMockRequest::to_curl` renders one for a request that the mock server received.
+//! [MockServer::export_curl_script](struct.MockServer.html#method.export_curl_script) writes a
+//! shell script with a command for every mock.
+//!
+//! Regex matchers (e.g. [When::path_matches](struct.When.html#method.path_matches) or
+//! [When::path_template](struct.When.html#method.path_template)), the `Regex` type, the
+//! `mock!` macro and the WireMock and Postman loaders require the `regex` Cargo feature, which is
+//! enabled by default. Without it (`default-features = false`), the `regex` crate is not
+//! compiled. Mocks are sent to a standalone server in the same format either way, but a mock
+//! server that was built without the feature rejects mocks with regex matchers.
+//!
+//! [When::json_body_schema](struct.When.html#method.json_body_schema) requires the
+//! `json-schema` Cargo feature, which validates request bodies with the
+//! [jsonschema](https://docs.rs/jsonschema) crate. The standalone server enables it.
+//!
 //! # API Alternatives
 //! This library provides two functionally interchangeable DSL APIs that allow you to create
 //! mocks on the server. You can choose the one you like best or use both side-by-side. For a
@@ -123,6 +213,22 @@
 //! Note that `when` and `then` are variables. This allows you to rename them to something you
 //! like better (such as `expect`/`respond_with`).
 //!
+//! Request expectations and response settings are separate types, so each side only offers the
+//! methods that make sense for it. Setting a response value on the request side does not
+//! compile:
+//! ```compile_fail
+//! let server = httpmock::MockServer::start();
+//!
+//! server.mock(|when, then| {
+//!     when.path("/search").status(204);
+//!     then.header("X-Trace", "1");
+//! });
+//! ```
+//!
+//! [MockServer::mock_with](struct.MockServer.html#method.mock_with) is an alias of
+//! [MockServer::mock](struct.MockServer.html#method.mock) for code that prefers to name this
+//! style explicitly.
+//!
 //! Relevant elements for this API are [MockServer::mock](struct.MockServer.html#method.mock), [When](struct.When.html) and [Then](struct.Then.html).
 //!
 //! # Examples
@@ -177,16 +283,30 @@
 //! it becomes free again.
 //! This is in contrast to tests that use a local mock server.
 //!
+//! [MockServer::connect_isolated](struct.MockServer.html#method.connect_isolated) lifts this
+//! restriction by giving every `MockServer` its own namespace on the remote server, so that
+//! concurrent tests or CI jobs cannot see each other's mocks and requests.
+//! [MockServer::start_or_connect](struct.MockServer.html#method.start_or_connect) connects
+//! this way if the `HTTPMOCK_HOST` environment variable is set and starts a local mock server
+//! otherwise, so the same tests run locally and against a shared standalone server in CI.
+//!
+//! Admin API requests to a remote server reuse keep-alive connections. Requests time out after
+//! 30 seconds and are repeated once if their connection is reset. Both can be adjusted for
+//! flaky networks with [MockServerBuilder::admin_timeout](struct.MockServerBuilder.html#method.admin_timeout)
+//! and [MockServerBuilder::admin_retries](struct.MockServerBuilder.html#method.admin_retries)
+//! before connecting with [MockServerBuilder::connect](struct.MockServerBuilder.html#method.connect).
+//!
 //! ## Limitations of the Standalone Mode
 //! At this time, it is not possible to use custom request matchers in combination with standalone
-//! mock servers (see [When::matches](struct.When.html#method.matches) or
+//! mock servers (see [When::matches](struct.When.html#method.matches),
+//! [When::custom_matcher](struct.When.html#method.custom_matcher) or
 //! [Mock::expect_match](struct.Mock.html#method.expect_match)).
 //!
 //! ## Standalone Mode with YAML Mock Definition Files
-//! The standalone server can also be used to read mock definitions from YAML files on startup once
-//! and serve the mocked endpoints until the server is shut down again. These `static` mocks
-//! cannot be deleted at runtime (even by Rust-based tests that use the mock server) and exist
-//! for the entire uptime of the mock server.
+//! The standalone server can also be used to read mock definitions from YAML (or JSON) files on
+//! startup once and serve the mocked endpoints until the server is shut down again. These
+//! `static` mocks cannot be deleted at runtime (even by Rust-based tests that use the mock server)
+//! and exist for the entire uptime of the mock server.
 //!
 //! The definition files follow the standard `httpmock` API that you would also use in regular
 //! Rust tests. Please find an example mock definition file in the `httpmock` Github repository
@@ -199,6 +319,224 @@
 //! * If you build `httpmock` from source and use the binary, then you can pass the path to
 //! the directory containing all your mock specification files using the `--static-mock-dir`
 //! parameter. Example: `httpmock --expose --static-mock-dir=/mocks`.
+//! Single files can be loaded with the `--static-mock` parameter, which can be repeated.
+//!
+//! Files with a `json` extension are read as JSON documents with the same structure as the YAML
+//! files. With the `--watch` parameter, the server checks the mock files for changes while it is
+//! running. The mocks of a file that was added, changed or deleted are replaced at once, so that
+//! a request never sees a partially reloaded file. Invalid files are reported in the log and the
+//! previously loaded mocks are kept.
+//!
+//! Mock files are validated when they are loaded. Unknown fields, invalid regular expressions and
+//! conflicting response bodies are reported with the file name, the offending field and, where
+//! possible, the line number. Besides `body`, a response can use `json_body` or `body_file` (a
+//! path relative to the mock file) as its body. For compatibility with older mock files, `body`
+//! takes precedence if both `body` and `json_body` are given, which is logged as a warning.
+//!
+//! The same files can also be loaded into a mock server from Rust tests with
+//! [MockServer::load_mocks_from_dir](struct.MockServer.html#method.load_mocks_from_dir), which
+//! requires the `yaml` Cargo feature. Each file can have a `name` field (defaulting to the file
+//! name without extension) that the returned mock handles are keyed by.
+//!
+//! [MockServer::export_mocks](struct.MockServer.html#method.export_mocks) writes all mocks of a
+//! running server into a single file in this format (one mock per YAML document), which
+//! [MockServer::import_mocks](struct.MockServer.html#method.import_mocks) restores onto another
+//! server. Exported files also use the fields `header_matches` and `query_param_matches` (lists of
+//! `name` and `value` regex pairs), `query_object` (a list of JSON objects), `query_params_raw` (a
+//! boolean), `path_case_insensitive` and `path_ignore_trailing_slash` (booleans), `remote_addr` and
+//! `remote_addr_matches` (a list of regexes), `header_values` (lists of `name` and `values`),
+//! `body_len` (an optional `min` and `max`), `chunked_body` (a boolean), `hmac_signature` (lists of
+//! `header` and `secret`), `content_type`, `accepts` (a list of media types),
+//! `json_body_ignoring_paths` (a `body` and a list of `ignored_paths`), `json_body_strict` (a JSON
+//! value with all allowed fields), `json_body_schema`, `json_body_path` (lists of `path` and
+//! optional `value`), `ndjson_line` (lists of `index` and `value`), `ndjson_line_count`,
+//! `body_xpath` (lists of `path` and `value`), `grpc_message`, `protobuf_body` (a base64 encoded
+//! message), `graphql_operation`, `graphql_variables`, `any_of` (a list of alternative request
+//! requirements), `active_from` and `active_until` (`secs_since_epoch` and `nanos_since_epoch`),
+//! `disabled` (a boolean), `then.trailer` and `then.sequence` (a list of responses). Disabled
+//! static mocks can be enabled with `PUT /__httpmock__/mocks/<id>/enabled`.
+//!
+//! ## Mocks from OpenAPI Specifications
+//! [MockServer::mocks_from_openapi](struct.MockServer.html#method.mocks_from_openapi) creates a
+//! mock for every operation of an OpenAPI 3 specification (YAML or JSON) and requires the
+//! `openapi` Cargo feature. The mocks are keyed by `operationId` (or method and path, such as
+//! `DELETE /pets/{petId}`) and match the method, the path (with path parameters matching any
+//! value) and all required query parameters, headers and cookies. They respond with the lowest
+//! documented status code and its example body (or a body generated from the response schema).
+//! Required JSON request bodies are validated against their schema, so that
+//! [Mock::assert](struct.Mock.html#method.assert) reports every schema violation of the
+//! closest request.
+//!
+//! ## Mocks from Recorded Traffic
+//! [MockServer::load_har](struct.MockServer.html#method.load_har) creates mocks from a
+//! [HAR](https://w3c.github.io/web-performance/specs/HAR/Overview.html) file, as exported by
+//! browser developer tools and many HTTP proxies. Each recorded request is replayed with its
+//! recorded response. Identical requests respond with their recorded responses in order, so
+//! that a recorded session can be replayed against a new client build.
+//! [HarOptions](struct.HarOptions.html) controls which headers are matched and which query
+//! parameters (e.g. cache busters) are ignored.
+//!
+//! [MockServer::export_har](struct.MockServer.html#method.export_har) does the opposite and
+//! writes all requests that a mock server received and the responses it sent to a HAR file,
+//! including timings and unmatched or forwarded requests, so that a failing test can be
+//! inspected in the network panel of a browser.
+//!
+//! ## WireMock Stub Mappings
+//! [MockServer::load_wiremock_mappings](struct.MockServer.html#method.load_wiremock_mappings)
+//! creates mocks from [WireMock](https://wiremock.org/) stub mapping files, so that existing
+//! stubs can be reused in Rust tests. The common subset of request matchers and response
+//! settings is supported. Unsupported parts of a mapping (e.g. scenarios or response templates)
+//! are ignored and reported as warnings in the log, naming the file and the ignored part.
+//!
+//! ## Postman Collections
+//! [MockServer::load_postman_collection](struct.MockServer.html#method.load_postman_collection)
+//! creates a mock for every request of a [Postman](https://www.postman.com/) collection that has
+//! a saved example. The mock matches the method and URL path of the request and responds with the
+//! first example. Collection variables (or variables provided by the caller) are resolved in
+//! request URLs. The returned mocks can be looked up by the folder and request names (e.g.
+//! `Users / Get by id`).
+//!
+//! ## GraphQL
+//! With the `graphql` Cargo feature,
+//! [When::graphql_query](struct.When.html#method.graphql_query) matches the GraphQL document of a
+//! request (ignoring formatting), and
+//! [Then::graphql_data](struct.Then.html#method.graphql_data) and
+//! [Then::graphql_errors](struct.Then.html#method.graphql_errors) build
+//! spec-compliant response bodies. After a schema has been loaded with
+//! [MockServer::load_graphql_schema](struct.MockServer.html#method.load_graphql_schema), creating
+//! a mock whose data does not fit the schema panics with the path of the mismatching field.
+//!
+//! ## gRPC
+//! With the `grpc` Cargo feature,
+//! [MockServer::mock_grpc](struct.MockServer.html#method.mock_grpc) mocks unary gRPC methods.
+//! Request messages are matched with
+//! [When::grpc_request](struct.When.html#method.grpc_request), response messages
+//! and error statuses (e.g. `UNAVAILABLE` to test retries) are set with
+//! [Then::grpc_response](struct.Then.html#method.grpc_response) and
+//! [Then::grpc_status](struct.Then.html#method.grpc_status). Messages are
+//! [prost](https://docs.rs/prost) messages. gRPC clients require a server that accepts HTTP/2 with
+//! prior knowledge. Streaming calls are not supported.
+//!
+//! With the `proto` Cargo feature,
+//! [When::protobuf_body](struct.When.html#method.protobuf_body) matches request
+//! bodies that are plain protobuf messages, as sent by REST APIs that use protobuf instead of
+//! JSON.
+//!
+//! ## SOAP
+//! With the `xml` Cargo feature,
+//! [When::soap_action](struct.When.html#method.soap_action) matches the action of
+//! a SOAP request and
+//! [When::soap_body_xpath](struct.When.html#method.soap_body_xpath) matches values
+//! in its body with XPath expressions. [Then::soap_body](struct.Then.html#method.soap_body)
+//! and [Then::soap_fault](struct.Then.html#method.soap_fault) wrap responses in an
+//! envelope. The SOAP version (1.1 or 1.2) is always set explicitly, because it determines the
+//! content type, the envelope namespace and how the action is sent. The standalone server
+//! enables the `xml` feature.
+//!
+//! ## Compressed request bodies
+//! With the `decompression` Cargo feature,
+//! [MockServerBuilder::decompress_request_bodies](struct.MockServerBuilder.html#method.decompress_request_bodies)
+//! lets the mock server decompress request bodies with a `gzip` or `deflate` content encoding
+//! before they are matched, so that all body matchers work for clients that compress their
+//! requests.
+//!
+//! ## Record and Playback
+//! [MockServer::start_recording](struct.MockServer.html#method.start_recording) starts a mock
+//! server that forwards all requests to a real upstream server and records the responses.
+//! [MockServer::save_recording](struct.MockServer.html#method.save_recording) saves the
+//! recorded traffic as a YAML file of mock definitions (separated by `---`), and
+//! [MockServer::playback](struct.MockServer.html#method.playback) later serves these mocks
+//! without touching the network. Saving and playback require the `yaml` Cargo feature.
+//! [RecordingOptions](struct.RecordingOptions.html) redact secrets (such as `Authorization`
+//! headers or tokens in bodies) and normalize volatile JSON response fields before the
+//! recording is written.
+//!
+//! ## Forwarding Unmatched Requests
+//! A mock server that is started with
+//! [MockServerBuilder::forward_unmatched_to](struct.MockServerBuilder.html#method.forward_unmatched_to)
+//! serves matching requests from its mocks and forwards all other requests to a real upstream
+//! server, so that only some routes of a service need to be mocked. Forwarded requests appear in
+//! the request history with `forwarded` set to `true` and the `upstream_status`. Upstream errors
+//! are answered with `502 Bad Gateway`.
+//!
+//! ## Pact Contracts
+//! [MockServer::write_pact](struct.MockServer.html#method.write_pact) writes the mocks of a
+//! server and the requests they received as interactions to a
+//! [Pact](https://docs.pact.io/) v3 contract file, so that the expectations of a consumer test
+//! can be verified against the real provider. Matchers are converted into Pact matching rules
+//! where possible (e.g. regex matchers into `regex` rules and partial JSON body matchers into
+//! `type` rules). Matchers without a Pact equivalent are reported as warnings in the log.
+//! Several tests can write to the same file, their interactions are merged.
+//!
+//! ## Standalone Server Options
+//! The `httpmock` binary is built with the `standalone` Cargo feature
+//! (`cargo install httpmock --features standalone`). Besides the options above, it supports:
+//! * `--read-only`: disables the admin API, so that only static mocks are served. Servers in this
+//! mode cannot be used with [MockServer::connect](struct.MockServer.html#method.connect).
+//! * `--disable-introspection`: disables the admin API endpoints `GET /__httpmock__/mocks` and
+//! `GET /__httpmock__/history`, which list all mocks and the recorded requests as JSON. They are
+//! available by default, even in read-only mode.
+//! * `--log-level`: the verbosity of the log output (`off`, `error`, `warn`, `info`, `debug` or
+//! `trace`). Every received request is logged at level `info` unless `--disable-access-log` is set.
+//! * `--tls-cert-file` and `--tls-key-file`: serves HTTPS using the provided PEM encoded
+//! certificate chain and private key (requires the `https` Cargo feature).
+//!
+//! Each option can also be set by an environment variable (run `httpmock --help` for details).
+//!
+//! ## Admin REST API
+//! Every mock server (standalone or not) can be administrated over HTTP, so that test suites
+//! written in other languages can use it as well.
+//! [MockServer::connect](struct.MockServer.html#method.connect) uses the same API. Mock servers
+//! that run in the test process are managed with direct in-memory calls instead, which is about
+//! two orders of magnitude faster (see `benches/mock_management.rs`). All endpoints are located
+//! under the path prefix `/__httpmock__` and exchange JSON documents:
+//!
+//! * `POST /mocks`: creates a mock. Responds with `201` and `{"mock_id": <id>}`.
+//! * `GET /mocks`: lists all mocks, including their hit counts (`call_counter`).
+//! * `DELETE /mocks`: deletes all mocks that were not loaded from files and all served
+//! directories.
+//! * `GET /mocks/<id>`: fetches a mock, including its hit count (`call_counter`).
+//! * `PUT /mocks/<id>`: replaces the definition of a mock and resets its hit count.
+//! * `DELETE /mocks/<id>`: deletes a mock.
+//! * `GET /mocks/<id>/requests`: lists the recorded requests that match a mock.
+//! * `PUT /mocks/<id>/enabled`: enables (`true`) or disables (`false`) a mock without resetting
+//! its hit count. Responds with `202`.
+//! * `GET /history`: lists all recorded requests, starting with the oldest one.
+//! * `GET /history/unmatched`: lists the recorded requests that do not match any mock.
+//! * `GET /history/har`: exports all recorded requests and responses as a HAR 1.2 document.
+//! * `DELETE /history`: deletes all recorded requests and resets the request statistics.
+//! * `GET /stats`: returns statistics about the served requests as JSON (see
+//! [MockServer::stats](struct.MockServer.html#method.stats)).
+//! * `GET /metrics`: returns the same statistics in the
+//! [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+//! * `POST /verify`: finds the recorded request that is closest to the provided request
+//! requirements and lists the differences. Responds with `404` if no request was recorded.
+//! * `POST /static_dirs`: serves the files of a directory on the server for requests that do
+//! not match any mock (e.g. `{"path_prefix": "/assets", "root": "fixtures/assets",
+//! "index_file": "index.html"}`, see
+//! [MockServer::serve_dir](struct.MockServer.html#method.serve_dir)). Responds with `201`.
+//! * `POST /reset`: deletes all mocks that were not loaded from files, all served directories
+//! and all recorded requests.
+//! * `GET /ping`: responds with `200` if the server is running.
+//!
+//! Mocks are described by a `request` object holding the request requirements (e.g.
+//! `{"method": "GET", "path": "/hello"}`) and a `response` object (e.g.
+//! `{"status": 200, "body": "aGk="}` with a base64 encoded body), which is the JSON
+//! representation of the [When](struct.When.html) and [Then](struct.Then.html) settings.
+//! Unknown mock IDs are answered with `404` and invalid requests with `500` and a JSON object
+//! holding an error `message`.
+//!
+//! Requests to paths starting with `/__httpmock_ns__/<namespace>` are sent to a namespace. The
+//! prefix is removed before matching, and such requests only match mocks whose request
+//! requirements contain the same `namespace`. `GET /mocks`, `DELETE /mocks`, `GET /history`,
+//! `GET /history/unmatched`, `DELETE /history` and `POST /reset` accept a `namespace` query parameter that limits them to
+//! the mocks and requests of a namespace.
+//!
+//! Read-only servers only provide `GET /ping` and the introspection endpoints `GET /mocks`,
+//! `GET /mocks/<id>/requests`, `GET /history`, `GET /history/unmatched`, `GET /history/har`,
+//! `GET /stats` and `GET /metrics`. The introspection endpoints can be disabled with
+//! `--disable-introspection` or
+//! [MockServerBuilder::introspection](struct.MockServerBuilder.html#method.introspection).
 //!
 //! # License
 //! `httpmock` is free software: you can redistribute it and/or modify it under the terms
@@ -223,25 +561,55 @@ use std::thread;
 use async_object_pool::Pool;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tokio::task::LocalSet;
 use tokio::time::Duration;
 
 use api::MockServerAdapter;
 use common::util::Join;
 
+#[cfg(feature = "regex")]
+pub use api::Regex;
+pub use api::{
+    IntoStatusCode, Method, Mock, MockError, MockExt, MockServer, MockServerBuilder, Then,
+    VerificationGuard, When,
+};
 use api::{LocalMockServerAdapter, RemoteMockServerAdapter};
-pub use api::{Method, Mock, MockExt, MockServer, Regex, Then, When};
+pub use common::data::{ClientCertificate, LatencyStats, Matcher, MultipartPart, ServerStats};
+#[cfg(feature = "graphql")]
+pub use common::graphql::GraphQLError;
+#[cfg(feature = "grpc")]
+pub use common::grpc::GrpcCode;
+pub use common::har::HarOptions;
+#[cfg(feature = "yaml")]
+pub use common::recording::RecordingOptions;
+#[cfg(feature = "xml")]
+pub use common::soap::{SoapFaultCode, SoapVersion};
+pub use server::request_log::{LogFormat, LogLevel};
 use server::{start_server, MockServerState};
+pub use server::{MockService, OverloadBehavior};
+
+#[cfg(feature = "macros")]
+pub use httpmock_macros::test;
+
+#[doc(hidden)]
+pub mod __private {
+    // Used by the mock! macro.
+    pub use serde_json;
+}
 
 mod api;
 mod common;
+#[cfg(feature = "regex")]
+mod macros;
 mod server;
 pub mod standalone;
 
 pub mod prelude {
+    #[cfg(feature = "regex")]
+    #[doc(no_inline)]
+    pub use crate::Regex;
     #[doc(no_inline)]
     pub use crate::{
-        api::MockServer, common::data::HttpMockRequest, Method::DELETE, Method::GET,
-        Method::OPTIONS, Method::POST, Method::PUT, Regex,
+        api::MockServer, common::data::HttpMockRequest, Method::DELETE, Method::GET, Method::HEAD,
+        Method::OPTIONS, Method::PATCH, Method::POST, Method::PUT,
     };
 }
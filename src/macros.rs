@@ -0,0 +1,105 @@
+/// Creates a mock with a compact, declarative syntax and returns the [Mock](struct.Mock.html).
+///
+/// The mock is defined by the server, the method, the path (a path template, see
+/// [When::path_template](struct.When.html#method.path_template)), optional request
+/// requirements in braces and the response after `=>`:
+///
+/// ```
+/// use httpmock::prelude::*;
+/// use httpmock::mock;
+/// use isahc::{prelude::*, Request};
+///
+/// let server = MockServer::start();
+///
+/// let mock = mock!(server, PUT "/users/{id}" {
+///     query: { "notify": "true" },
+///     headers: { "Authorization": "Bearer 123" },
+///     json_partial: { "name": "Fred" },
+/// } => {
+///     status: 200,
+///     headers: { "X-Api-Version": "2" },
+///     json: { "id": 1, "name": "Fred" },
+/// });
+///
+/// let mut response = Request::put(server.url("/users/1?notify=true"))
+///     .header("Authorization", "Bearer 123")
+///     .body(r#"{ "name": "Fred", "age": 42 }"#)
+///     .unwrap()
+///     .send()
+///     .unwrap();
+///
+/// mock.assert();
+/// assert_eq!(response.headers()["X-Api-Version"], "2");
+/// assert_eq!(response.text().unwrap(), r#"{"id":1,"name":"Fred"}"#);
+/// ```
+///
+/// The method is any method name, such as `GET` or `PROPFIND` (see
+/// [Method](enum.Method.html)). The following keys are supported:
+///
+/// * Request: `query` and `headers` (maps of names to values), `json` (the exact JSON body),
+/// `json_partial` (a part of the JSON body, see
+/// [When::json_body_partial](struct.When.html#method.json_body_partial)) and `body`.
+/// * Response: `status`, `headers`, `json`, `body` and `delay` (a `Duration`).
+///
+/// JSON values use the syntax of the [serde_json::json](https://docs.rs/serde_json) macro and
+/// must be a single token tree, so expressions need to be wrapped in parentheses. Unknown keys
+/// are compile errors.
+///
+/// The macro requires the `regex` Cargo feature, which is enabled by default.
+#[macro_export]
+macro_rules! mock {
+    // Request requirements
+    (@when $when:expr;) => { $when; };
+    (@when $when:expr; query: { $($name:literal : $value:expr),* $(,)? } $(, $($rest:tt)*)?) => {
+        $crate::mock!(@when $when $(.query_param($name, $value))*; $($($rest)*)?)
+    };
+    (@when $when:expr; headers: { $($name:literal : $value:expr),* $(,)? } $(, $($rest:tt)*)?) => {
+        $crate::mock!(@when $when $(.header($name, $value))*; $($($rest)*)?)
+    };
+    (@when $when:expr; json: $json:tt $(, $($rest:tt)*)?) => {
+        $crate::mock!(@when $when.json_body($crate::__private::serde_json::json!($json)); $($($rest)*)?)
+    };
+    (@when $when:expr; json_partial: $json:tt $(, $($rest:tt)*)?) => {
+        $crate::mock!(@when $when.json_body_partial($crate::__private::serde_json::json!($json).to_string()); $($($rest)*)?)
+    };
+    (@when $when:expr; body: $body:expr $(, $($rest:tt)*)?) => {
+        $crate::mock!(@when $when.body($body); $($($rest)*)?)
+    };
+    (@when $when:expr; $key:ident $($rest:tt)*) => {
+        compile_error!(concat!(
+            "invalid request key `", stringify!($key),
+            "`, expected one of `query: { .. }`, `headers: { .. }`, `json`, `json_partial` or `body`"
+        ));
+    };
+
+    // Response
+    (@then $then:expr;) => { $then; };
+    (@then $then:expr; status: $status:expr $(, $($rest:tt)*)?) => {
+        $crate::mock!(@then $then.status($status); $($($rest)*)?)
+    };
+    (@then $then:expr; headers: { $($name:literal : $value:expr),* $(,)? } $(, $($rest:tt)*)?) => {
+        $crate::mock!(@then $then $(.header($name, $value))*; $($($rest)*)?)
+    };
+    (@then $then:expr; json: $json:tt $(, $($rest:tt)*)?) => {
+        $crate::mock!(@then $then.json_body($crate::__private::serde_json::json!($json)); $($($rest)*)?)
+    };
+    (@then $then:expr; body: $body:expr $(, $($rest:tt)*)?) => {
+        $crate::mock!(@then $then.body($body); $($($rest)*)?)
+    };
+    (@then $then:expr; delay: $delay:expr $(, $($rest:tt)*)?) => {
+        $crate::mock!(@then $then.delay($delay); $($($rest)*)?)
+    };
+    (@then $then:expr; $key:ident $($rest:tt)*) => {
+        compile_error!(concat!(
+            "invalid response key `", stringify!($key),
+            "`, expected one of `status`, `headers: { .. }`, `json`, `body` or `delay`"
+        ));
+    };
+
+    ($server:expr, $method:ident $path:tt $({ $($req:tt)* })? => { $($res:tt)* }) => {
+        $server.mock(|when, then| {
+            $crate::mock!(@when when.method(stringify!($method)).path_template($path); $($($req)*)?);
+            $crate::mock!(@then then; $($res)*);
+        })
+    };
+}
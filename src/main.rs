@@ -1,5 +1,5 @@
 use clap::Parser;
-use httpmock::standalone::start_standalone_server;
+use httpmock::standalone::{start_standalone_server_with_config, StandaloneServerConfig};
 use std::env;
 use std::path::PathBuf;
 
@@ -14,8 +14,21 @@ struct ExecutionParameters {
     pub port: u16,
     #[clap(short, long, env = "HTTPMOCK_EXPOSE")]
     pub expose: bool,
-    #[clap(short, long, env = "HTTPMOCK_MOCK_FILES_DIR")]
+    #[clap(
+        short,
+        long,
+        env = "HTTPMOCK_MOCK_FILES_DIR",
+        aliases = ["mock-files", "static-mock-dir"]
+    )]
     pub mock_files_dir: Option<PathBuf>,
+    #[clap(
+        long = "static-mock",
+        env = "HTTPMOCK_STATIC_MOCKS",
+        value_delimiter = ','
+    )]
+    pub static_mocks: Vec<PathBuf>,
+    #[clap(short, long, env = "HTTPMOCK_WATCH")]
+    pub watch: bool,
     #[clap(short, long, env = "HTTPMOCK_DISABLE_ACCESS_LOG")]
     pub disable_access_log: bool,
     #[clap(
@@ -25,14 +38,33 @@ struct ExecutionParameters {
         default_value = "100"
     )]
     pub request_history_limit: usize,
+    #[clap(
+        long,
+        env = "HTTPMOCK_LOG_LEVEL",
+        default_value = "info",
+        value_parser = ["off", "error", "warn", "info", "debug", "trace"]
+    )]
+    pub log_level: String,
+    #[clap(long, env = "HTTPMOCK_READ_ONLY")]
+    pub read_only: bool,
+    #[clap(long, env = "HTTPMOCK_DISABLE_INTROSPECTION")]
+    pub disable_introspection: bool,
+    #[cfg(feature = "https")]
+    #[clap(long, env = "HTTPMOCK_TLS_CERT_FILE", requires = "tls_key_file")]
+    pub tls_cert_file: Option<PathBuf>,
+    #[cfg(feature = "https")]
+    #[clap(long, env = "HTTPMOCK_TLS_KEY_FILE", requires = "tls_cert_file")]
+    pub tls_key_file: Option<PathBuf>,
 }
 
 #[tokio::main]
 async fn main() {
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("httpmock=info"));
-
     let params: ExecutionParameters = ExecutionParameters::parse();
 
+    env_logger::init_from_env(
+        env_logger::Env::default().default_filter_or(format!("httpmock={}", params.log_level)),
+    );
+
     log::info!("██╗  ██╗████████╗████████╗██████╗ ███╗   ███╗ ██████╗  ██████╗██╗  ██╗");
     log::info!("██║  ██║╚══██╔══╝╚══██╔══╝██╔══██╗████╗ ████║██╔═══██╗██╔════╝██║ ██╔╝");
     log::info!("███████║   ██║      ██║   ██████╔╝██╔████╔██║██║   ██║██║     █████╔╝");
@@ -48,13 +80,21 @@ async fn main() {
 
     log::info!("{:?}", params);
 
-    start_standalone_server(
-        params.port,
-        params.expose,
-        params.mock_files_dir,
-        !params.disable_access_log,
-        params.request_history_limit,
-    )
+    start_standalone_server_with_config(StandaloneServerConfig {
+        port: params.port,
+        expose: params.expose,
+        static_mock_dir_path: params.mock_files_dir,
+        static_mock_files: params.static_mocks,
+        watch_mock_files: params.watch,
+        print_access_log: !params.disable_access_log,
+        history_limit: params.request_history_limit,
+        read_only: params.read_only,
+        disable_introspection: params.disable_introspection,
+        #[cfg(feature = "https")]
+        tls_cert_file: params.tls_cert_file,
+        #[cfg(feature = "https")]
+        tls_key_file: params.tls_key_file,
+    })
     .await
     .expect("an error occurred during mock server execution");
 }
@@ -0,0 +1,138 @@
+use std::io::Read;
+
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+
+use crate::server::ServerRequestHeader;
+
+/// Decompresses a request body that was sent with a `gzip` or `deflate` content encoding, so
+/// that mocks match the decompressed body. The `Content-Encoding` header is removed and the
+/// `Content-Length` header is updated, so that the request looks as if it was sent without
+/// compression. Bodies with any other encoding and bodies that cannot be decompressed are
+/// returned unchanged. Returns `None` if the decompressed body is larger than `max_body_bytes`.
+pub(crate) fn decode_body(
+    header: &mut ServerRequestHeader,
+    body: Vec<u8>,
+    max_body_bytes: usize,
+) -> Option<Vec<u8>> {
+    let encoding = header
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-encoding"))
+        .map(|(_, value)| value.trim().to_ascii_lowercase());
+    let decoded = match encoding.as_deref() {
+        Some("gzip") | Some("x-gzip") => {
+            read_limited(GzDecoder::new(body.as_slice()), max_body_bytes)
+        }
+        // Some clients send raw deflate data instead of the zlib format that RFC 9110 requires.
+        Some("deflate") => read_limited(ZlibDecoder::new(body.as_slice()), max_body_bytes)
+            .or_else(|| read_limited(DeflateDecoder::new(body.as_slice()), max_body_bytes)),
+        _ => return Some(body),
+    };
+
+    let decoded = match decoded {
+        None => return Some(body),
+        Some(decoded) if decoded.len() > max_body_bytes => return None,
+        Some(decoded) => decoded,
+    };
+    header
+        .headers
+        .retain(|(name, _)| !name.eq_ignore_ascii_case("content-encoding"));
+    for (name, value) in header.headers.iter_mut() {
+        if name.eq_ignore_ascii_case("content-length") {
+            *value = decoded.len().to_string();
+        }
+    }
+    Some(decoded)
+}
+
+/// Reads at most one byte more than `max_bytes`, so that a body that decompresses to a huge
+/// size is not read completely. Returns `None` if the data is not valid.
+fn read_limited<R: Read>(reader: R, max_bytes: usize) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    reader
+        .take(max_bytes as u64 + 1)
+        .read_to_end(&mut decoded)
+        .ok()?;
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+    use flate2::Compression;
+
+    use crate::server::decompression::decode_body;
+    use crate::server::ServerRequestHeader;
+
+    fn header(encoding: &str, length: usize) -> ServerRequestHeader {
+        ServerRequestHeader {
+            method: "POST".to_string(),
+            path: "/".to_string(),
+            query: String::new(),
+            version: "HTTP/1.1".to_string(),
+            host: None,
+            headers: vec![
+                ("content-encoding".to_string(), encoding.to_string()),
+                ("content-length".to_string(), length.to_string()),
+            ],
+            client_certificate: None,
+            listener: None,
+            remote_addr: None,
+            body_skipped: false,
+        }
+    }
+
+    #[test]
+    fn decode_body_test() {
+        // Arrange
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(b"{\"name\":\"Fred\"}").unwrap();
+        let gzip = gzip.finish().unwrap();
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(b"zlib").unwrap();
+        let zlib = zlib.finish().unwrap();
+        let mut deflate = DeflateEncoder::new(Vec::new(), Compression::default());
+        deflate.write_all(b"raw deflate").unwrap();
+        let deflate = deflate.finish().unwrap();
+
+        // Act
+        let mut gzip_header = header("GZIP", gzip.len());
+        let gzip_body = decode_body(&mut gzip_header, gzip, 1024);
+        let mut zlib_header = header("deflate", zlib.len());
+        let zlib_body = decode_body(&mut zlib_header, zlib, 1024);
+        let mut deflate_header = header("deflate", deflate.len());
+        let deflate_body = decode_body(&mut deflate_header, deflate, 1024);
+
+        // Assert
+        assert_eq!(gzip_body, Some(b"{\"name\":\"Fred\"}".to_vec()));
+        assert_eq!(
+            gzip_header.headers,
+            vec![("content-length".to_string(), "15".to_string())]
+        );
+        assert_eq!(zlib_body, Some(b"zlib".to_vec()));
+        assert_eq!(deflate_body, Some(b"raw deflate".to_vec()));
+    }
+
+    #[test]
+    fn decode_body_unchanged_or_too_large_test() {
+        // Arrange
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(&[b'a'; 1000]).unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        // Act
+        let mut invalid_header = header("gzip", 3);
+        let invalid_body = decode_body(&mut invalid_header, b"abc".to_vec(), 1024);
+        let mut brotli_header = header("br", 3);
+        let brotli_body = decode_body(&mut brotli_header, b"abc".to_vec(), 1024);
+        let too_large = decode_body(&mut header("gzip", gzip.len()), gzip, 999);
+
+        // Assert
+        assert_eq!(invalid_body, Some(b"abc".to_vec()));
+        assert_eq!(invalid_header.headers.len(), 2);
+        assert_eq!(brotli_body, Some(b"abc".to_vec()));
+        assert_eq!(too_large, None);
+    }
+}
@@ -0,0 +1,185 @@
+//! Forwards requests that the mock server received to an upstream server.
+
+use std::time::Duration;
+
+use futures_util::AsyncReadExt;
+use hyper::Body;
+use isahc::config::{Configurable, RedirectPolicy};
+use isahc::http::Response;
+use isahc::{AsyncBody, AsyncReadResponseExt, HttpClient, Request};
+use url::Url;
+
+use crate::common::data::MockServerHttpResponse;
+use crate::server::{ServerRequestHeader, ServerResponse};
+
+/// Headers that only apply to a single connection and are therefore not forwarded.
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Request headers that are never forwarded in addition to the hop-by-hop headers. The host
+/// is derived from the upstream URL and the content length from the forwarded body.
+const SKIPPED_REQUEST_HEADERS: &[&str] = &["host", "content-length"];
+
+/// The maximum time to wait for a connection to the upstream server.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The size of the chunks that streamed response bodies are read in.
+const STREAM_CHUNK_SIZE: usize = 16 * 1024;
+
+/// An upstream server that requests are forwarded to.
+pub(crate) struct Upstream {
+    base_url: String,
+    client: HttpClient,
+}
+
+impl Upstream {
+    /// Creates an upstream for the provided base URL (e.g. `https://api.github.com`). The path
+    /// of a forwarded request is appended to the path of the base URL.
+    pub fn new(base_url: &str) -> Result<Self, String> {
+        let url = Url::parse(base_url)
+            .map_err(|e| format!("Invalid upstream URL '{}': {}", base_url, e))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(format!(
+                "Invalid upstream URL '{}': only http and https are supported",
+                base_url
+            ));
+        }
+
+        let client = HttpClient::builder()
+            .automatic_decompression(false)
+            .redirect_policy(RedirectPolicy::None)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Cannot create HTTP client for upstream: {}", e))?;
+
+        Ok(Self {
+            base_url: url.as_str().trim_end_matches('/').to_string(),
+            client,
+        })
+    }
+
+    /// Sends the request to the upstream server and returns its complete response. The
+    /// response body is requested without content encoding, so that it can be recorded.
+    pub async fn forward(
+        &self,
+        req: &ServerRequestHeader,
+        body: Vec<u8>,
+    ) -> Result<MockServerHttpResponse, String> {
+        let (uri, mut response) = self.send(req, body, &["accept-encoding"]).await?;
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| format!("Cannot read response from '{}': {}", uri, e))?;
+
+        let mut mock_response = MockServerHttpResponse::new();
+        mock_response.status = Some(response.status().as_u16());
+        let headers = response_headers(&response, &["content-length"]);
+        if !headers.is_empty() {
+            mock_response.headers = Some(headers);
+        }
+        if !body.is_empty() {
+            mock_response.body = Some(body);
+        }
+        Ok(mock_response)
+    }
+
+    /// Sends the request to the upstream server and returns a response whose body is streamed
+    /// from the upstream server as it arrives. Bodies are passed on as they are, including their
+    /// content encoding.
+    pub async fn forward_streaming(
+        &self,
+        req: &ServerRequestHeader,
+        body: Vec<u8>,
+    ) -> Result<ServerResponse, String> {
+        let (_, response) = self.send(req, body, &[]).await?;
+
+        let mut server_response = ServerResponse::new(
+            response.status().as_u16(),
+            response_headers(&response, &[]),
+            Vec::new(),
+        );
+        server_response.body_stream = Some(to_body_stream(response.into_body()));
+        Ok(server_response)
+    }
+
+    async fn send(
+        &self,
+        req: &ServerRequestHeader,
+        body: Vec<u8>,
+        skipped_headers: &[&str],
+    ) -> Result<(String, Response<AsyncBody>), String> {
+        let mut uri = format!("{}{}", self.base_url, req.path);
+        if !req.query.is_empty() {
+            uri.push('?');
+            uri.push_str(&req.query);
+        }
+
+        let mut builder = Request::builder().method(req.method.as_str()).uri(&uri);
+        for (name, value) in &req.headers {
+            let lowercase_name = name.to_lowercase();
+            if !HOP_BY_HOP_HEADERS.contains(&lowercase_name.as_str())
+                && !SKIPPED_REQUEST_HEADERS.contains(&lowercase_name.as_str())
+                && !skipped_headers.contains(&lowercase_name.as_str())
+            {
+                builder = builder.header(name.as_str(), value.as_str());
+            }
+        }
+        let request = builder
+            .body(body)
+            .map_err(|e| format!("Cannot create request to '{}': {}", uri, e))?;
+
+        let response = self
+            .client
+            .send_async(request)
+            .await
+            .map_err(|e| format!("Cannot send request to '{}': {}", uri, e))?;
+        Ok((uri, response))
+    }
+}
+
+/// Returns the headers of an upstream response except hop-by-hop headers and the provided
+/// headers.
+fn response_headers<B>(response: &Response<B>, skipped_headers: &[&str]) -> Vec<(String, String)> {
+    response
+        .headers()
+        .iter()
+        .filter(|(name, _)| {
+            !HOP_BY_HOP_HEADERS.contains(&name.as_str())
+                && !skipped_headers.contains(&name.as_str())
+        })
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Converts an upstream response body into a body that is sent to the client chunk by chunk,
+/// as soon as each chunk has been received.
+fn to_body_stream(body: AsyncBody) -> Body {
+    let chunks = futures_util::stream::unfold(Some(body), |body| async move {
+        let mut body = body?;
+        let mut chunk = vec![0; STREAM_CHUNK_SIZE];
+        match body.read(&mut chunk).await {
+            Ok(0) => None,
+            Ok(len) => {
+                chunk.truncate(len);
+                Some((Ok(chunk), Some(body)))
+            }
+            // The stream ends after an error, which aborts the response.
+            Err(e) => Some((Err(e), None)),
+        }
+    });
+    Body::wrap_stream(chunks)
+}
@@ -1,68 +1,1118 @@
-use assert_json_diff::{assert_json_matches_no_panic, CompareMode, Config};
+use std::panic::{self, AssertUnwindSafe};
+
+use hmac::{Hmac, Mac};
 use serde_json::Value;
+use sha2::Sha256;
+
+use crate::common::data::{
+    body_preview, BodyLength, CustomMatcher, HeaderValues, HmacSignature, HttpMockRequest,
+    JsonBodyIgnoringPaths, JsonPathRequirement, JwtClaim, MockMatcherFunction, MultipartPart,
+    NdjsonLine, QueryParamValues, QueryString,
+};
+#[cfg(feature = "json-schema")]
+use crate::common::json_schema;
+use crate::common::multipart::{MultipartBody, Part};
+#[cfg(feature = "xml")]
+use crate::common::{data::XPathRequirement, xml};
+use crate::common::{json_path, protobuf};
+use crate::server::matchers::distance_for;
+#[cfg(feature = "regex")]
+use crate::Regex;
+
+pub trait ValueComparator<S, T> {
+    fn matches(&self, mock_value: &S, req_value: &T) -> bool;
+    fn name(&self) -> &str;
+    fn distance(&self, mock_value: &Option<&S>, req_value: &Option<&T>) -> usize;
+
+    /// Describes why the values do not match, if the comparator can tell more than that.
+    fn explain(&self, _mock_value: &S, _req_value: &T) -> Option<String> {
+        None
+    }
+}
+
+// ************************************************************************************************
+// JSONExactMatchComparator
+// ************************************************************************************************
+pub struct JSONExactMatchComparator {}
+
+impl JSONExactMatchComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<Value, Value> for JSONExactMatchComparator {
+    fn matches(&self, mock_value: &Value, req_value: &Value) -> bool {
+        req_value == mock_value
+    }
+
+    fn name(&self) -> &str {
+        "equals"
+    }
+
+    fn distance(&self, mock_value: &Option<&Value>, req_value: &Option<&Value>) -> usize {
+        distance_for(mock_value, req_value)
+    }
+}
+
+// ************************************************************************************************
+// JSONIgnoringPathsComparator
+// ************************************************************************************************
+pub struct JSONIgnoringPathsComparator {}
+
+impl JSONIgnoringPathsComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<JsonBodyIgnoringPaths, Value> for JSONIgnoringPathsComparator {
+    fn matches(&self, mock_value: &JsonBodyIgnoringPaths, req_value: &Value) -> bool {
+        mock_value.without_ignored(&mock_value.body) == mock_value.without_ignored(req_value)
+    }
+
+    fn name(&self) -> &str {
+        "equals"
+    }
+
+    fn distance(
+        &self,
+        mock_value: &Option<&JsonBodyIgnoringPaths>,
+        req_value: &Option<&Value>,
+    ) -> usize {
+        let expected = mock_value.map(|m| m.without_ignored(&m.body));
+        let actual = match (mock_value, req_value) {
+            (Some(m), Some(r)) => Some(m.without_ignored(r)),
+            (_, r) => r.cloned(),
+        };
+        distance_for(&expected.as_ref(), &actual.as_ref())
+    }
+
+    fn explain(&self, mock_value: &JsonBodyIgnoringPaths, _req_value: &Value) -> Option<String> {
+        Some(format!(
+            "the values at {} are ignored",
+            mock_value.ignored_paths.join(", ")
+        ))
+    }
+}
+
+// ************************************************************************************************
+// JSONExactMatchComparator
+// ************************************************************************************************
+pub struct JSONContainsMatchComparator {}
+
+impl JSONContainsMatchComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<Value, Value> for JSONContainsMatchComparator {
+    fn matches(&self, mock_value: &Value, req_value: &Value) -> bool {
+        json_includes(req_value, mock_value)
+    }
+
+    fn name(&self) -> &str {
+        "contains"
+    }
+
+    fn distance(&self, mock_value: &Option<&Value>, req_value: &Option<&Value>) -> usize {
+        distance_for(mock_value, req_value)
+    }
+}
+
+// ************************************************************************************************
+// JSONNoExtraFieldsComparator
+// ************************************************************************************************
+pub struct JSONNoExtraFieldsComparator {}
+
+impl JSONNoExtraFieldsComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<Value, Value> for JSONNoExtraFieldsComparator {
+    fn matches(&self, mock_value: &Value, req_value: &Value) -> bool {
+        extra_json_fields(req_value, mock_value, "$").is_empty()
+    }
+
+    fn name(&self) -> &str {
+        "has no fields other than"
+    }
+
+    fn distance(&self, mock_value: &Option<&Value>, req_value: &Option<&Value>) -> usize {
+        match (mock_value, req_value) {
+            (Some(mock_value), Some(req_value)) => {
+                extra_json_fields(req_value, mock_value, "$").len()
+            }
+            _ => distance_for(mock_value, req_value),
+        }
+    }
+
+    fn explain(&self, mock_value: &Value, req_value: &Value) -> Option<String> {
+        Some(format!(
+            "unexpected fields {}",
+            extra_json_fields(req_value, mock_value, "$").join(", ")
+        ))
+    }
+}
+
+/// Returns the JSONPaths of the object members and array elements of `actual` that have no
+/// counterpart in `allowed`. Values of different types are not compared, since the other JSON
+/// matchers report them.
+fn extra_json_fields(actual: &Value, allowed: &Value, path: &str) -> Vec<String> {
+    match (actual, allowed) {
+        (Value::Object(actual), Value::Object(allowed)) => actual
+            .iter()
+            .flat_map(|(key, a)| {
+                let path = format!("{}.{}", path, key);
+                match allowed.get(key) {
+                    Some(e) => extra_json_fields(a, e, &path),
+                    None => vec![path],
+                }
+            })
+            .collect(),
+        (Value::Array(actual), Value::Array(allowed)) => actual
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, a)| {
+                let path = format!("{}[{}]", path, idx);
+                match allowed.get(idx) {
+                    Some(e) => extra_json_fields(a, e, &path),
+                    None => vec![path],
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns whether `actual` contains `expected`, i.e. whether every field of an expected object
+/// and every element of an expected array is contained in the value at the same position.
+/// Matches like `assert_json_diff` in inclusive mode, without describing the differences.
+fn json_includes(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(key, e)| actual.get(key).map_or(false, |a| json_includes(a, e))),
+        (Value::Array(actual), Value::Array(expected)) => expected
+            .iter()
+            .enumerate()
+            .all(|(idx, e)| actual.get(idx).map_or(false, |a| json_includes(a, e))),
+        _ => actual == expected,
+    }
+}
+
+// ************************************************************************************************
+// JSONSchemaMatchComparator
+// ************************************************************************************************
+#[cfg(feature = "json-schema")]
+pub struct JSONSchemaMatchComparator {}
+
+#[cfg(feature = "json-schema")]
+impl JSONSchemaMatchComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl ValueComparator<Value, Value> for JSONSchemaMatchComparator {
+    fn matches(&self, mock_value: &Value, req_value: &Value) -> bool {
+        json_schema::validate(mock_value, req_value).is_empty()
+    }
+
+    fn name(&self) -> &str {
+        "matches schema"
+    }
+
+    fn distance(&self, mock_value: &Option<&Value>, req_value: &Option<&Value>) -> usize {
+        match (mock_value, req_value) {
+            (Some(schema), Some(value)) => json_schema::validate(schema, value).len(),
+            (Some(_), None) => 1,
+            _ => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &Value, req_value: &Value) -> Option<String> {
+        Some(json_schema::validate(mock_value, req_value).join(", "))
+    }
+}
+
+// ************************************************************************************************
+// JSONPathMatchComparator
+// ************************************************************************************************
+pub struct JSONPathMatchComparator {}
+
+impl JSONPathMatchComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<JsonPathRequirement, Value> for JSONPathMatchComparator {
+    fn matches(&self, mock_value: &JsonPathRequirement, req_value: &Value) -> bool {
+        match json_path::select(req_value, &mock_value.path) {
+            Ok(selected) => match &mock_value.value {
+                Some(expected) => selected
+                    .into_iter()
+                    .any(|v| json_path::value_matches(v, expected)),
+                None => !selected.is_empty(),
+            },
+            Err(_) => false,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "contains path"
+    }
+
+    fn distance(
+        &self,
+        mock_value: &Option<&JsonPathRequirement>,
+        req_value: &Option<&Value>,
+    ) -> usize {
+        match (mock_value, req_value) {
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            (Some(_), _) => 1,
+            _ => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &JsonPathRequirement, req_value: &Value) -> Option<String> {
+        match json_path::select(req_value, &mock_value.path) {
+            Err(e) => Some(e),
+            Ok(selected) if selected.is_empty() => {
+                Some(format!("{}: no such value", mock_value.path))
+            }
+            Ok(selected) => Some(format!(
+                "{}: expected {} but got {}",
+                mock_value.path,
+                mock_value.value.as_ref().unwrap_or(&Value::Null),
+                selected
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+// ************************************************************************************************
+// XPathMatchComparator
+// ************************************************************************************************
+#[cfg(feature = "xml")]
+pub struct XPathMatchComparator {}
+
+#[cfg(feature = "xml")]
+impl XPathMatchComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "xml")]
+impl ValueComparator<XPathRequirement, String> for XPathMatchComparator {
+    fn matches(&self, mock_value: &XPathRequirement, req_value: &String) -> bool {
+        xml::value_matches(req_value, &mock_value.path, mock_value.value.as_deref())
+    }
+
+    fn name(&self) -> &str {
+        "contains XPath"
+    }
+
+    fn distance(
+        &self,
+        mock_value: &Option<&XPathRequirement>,
+        req_value: &Option<&String>,
+    ) -> usize {
+        match (mock_value, req_value) {
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            (Some(_), _) => 1,
+            _ => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &XPathRequirement, req_value: &String) -> Option<String> {
+        match xml::select(req_value, &mock_value.path) {
+            Err(e) => Some(e),
+            Ok(selected) if selected.is_empty() => {
+                Some(format!("{}: no such node", mock_value.path))
+            }
+            Ok(selected) => Some(format!(
+                "{}: expected {:?} but got {}",
+                mock_value.path,
+                mock_value.value.as_deref().unwrap_or_default(),
+                selected
+                    .iter()
+                    .map(|v| format!("{:?}", v.trim()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+// ************************************************************************************************
+// XMLEqualComparator
+// ************************************************************************************************
+#[cfg(feature = "xml")]
+pub struct XMLEqualComparator {}
+
+#[cfg(feature = "xml")]
+impl XMLEqualComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "xml")]
+impl ValueComparator<String, String> for XMLEqualComparator {
+    fn matches(&self, mock_value: &String, req_value: &String) -> bool {
+        xml::compare(mock_value, req_value).is_ok()
+    }
+
+    fn name(&self) -> &str {
+        "equals XML"
+    }
+
+    fn distance(&self, mock_value: &Option<&String>, req_value: &Option<&String>) -> usize {
+        match (mock_value, req_value) {
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            (Some(mv), Some(rv)) => distance_for(&Some(mv), &Some(rv)).max(1),
+            (Some(_), None) => 1,
+            _ => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &String, req_value: &String) -> Option<String> {
+        xml::compare(mock_value, req_value).err()
+    }
+}
+
+// ************************************************************************************************
+// MultipartPartComparator
+// ************************************************************************************************
+pub struct MultipartPartComparator {}
+
+impl MultipartPartComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns the properties of a part that do not match the requirement.
+    fn mismatched_properties(mock_value: &MultipartPart, part: &Part) -> Vec<String> {
+        let mut mismatched = Vec::new();
+        if mock_value.filename.is_some() && mock_value.filename != part.filename {
+            mismatched.push(format!(
+                "filename {:?} but got {:?}",
+                mock_value.filename.as_deref().unwrap_or_default(),
+                part.filename.as_deref().unwrap_or_default()
+            ));
+        }
+        if mock_value.content_type.is_some() && mock_value.content_type != part.content_type {
+            mismatched.push(format!(
+                "content type {:?} but got {:?}",
+                mock_value.content_type.as_deref().unwrap_or_default(),
+                part.content_type.as_deref().unwrap_or_default()
+            ));
+        }
+        if let Some(body) = &mock_value.body {
+            if body.as_bytes() != part.body.as_slice() {
+                mismatched.push(format!(
+                    "body {} but got {}",
+                    body_preview(body.as_bytes()),
+                    body_preview(&part.body)
+                ));
+            }
+        }
+        mismatched
+    }
+}
+
+impl ValueComparator<MultipartPart, MultipartBody> for MultipartPartComparator {
+    fn matches(&self, mock_value: &MultipartPart, req_value: &MultipartBody) -> bool {
+        req_value.parts.iter().any(|part| {
+            part.name.as_ref() == Some(&mock_value.name)
+                && Self::mismatched_properties(mock_value, part).is_empty()
+        })
+    }
+
+    fn name(&self) -> &str {
+        "contains part"
+    }
+
+    fn distance(
+        &self,
+        mock_value: &Option<&MultipartPart>,
+        req_value: &Option<&MultipartBody>,
+    ) -> usize {
+        match (mock_value, req_value) {
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            (Some(_), _) => 1,
+            _ => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &MultipartPart, req_value: &MultipartBody) -> Option<String> {
+        let part = req_value
+            .parts
+            .iter()
+            .find(|part| part.name.as_ref() == Some(&mock_value.name));
+        match part {
+            None => Some(format!("no part with name '{}'", mock_value.name)),
+            Some(part) => Some(format!(
+                "part '{}': expected {}",
+                mock_value.name,
+                Self::mismatched_properties(mock_value, part).join(", expected ")
+            )),
+        }
+    }
+}
+
+// ************************************************************************************************
+// BinaryExactMatchComparator
+// ************************************************************************************************
+/// Compares base64 encoded binary values. Mismatches are explained by the first byte that
+/// differs, since binary values are not readable as text.
+pub struct BinaryExactMatchComparator {}
+
+impl BinaryExactMatchComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<String, String> for BinaryExactMatchComparator {
+    fn matches(&self, mock_value: &String, req_value: &String) -> bool {
+        mock_value == req_value
+    }
+
+    fn name(&self) -> &str {
+        "equals"
+    }
+
+    fn distance(&self, mock_value: &Option<&String>, req_value: &Option<&String>) -> usize {
+        match (mock_value, req_value) {
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            (Some(_), _) => 1,
+            _ => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &String, req_value: &String) -> Option<String> {
+        let expected = base64::decode(mock_value).ok()?;
+        let actual = base64::decode(req_value).ok()?;
+        let offset = expected
+            .iter()
+            .zip(actual.iter())
+            .position(|(e, a)| e != a)
+            .unwrap_or_else(|| expected.len().min(actual.len()));
+        let byte = |bytes: &[u8]| {
+            bytes
+                .get(offset)
+                .map_or("the end of the body".to_string(), |b| {
+                    format!("0x{:02x}", b)
+                })
+        };
+        Some(format!(
+            "expected {} bytes but got {} bytes, which differ at offset {} (expected {} but got {})",
+            expected.len(),
+            actual.len(),
+            offset,
+            byte(&expected),
+            byte(&actual)
+        ))
+    }
+}
+
+// ************************************************************************************************
+// ProtobufMessageComparator
+// ************************************************************************************************
+/// Compares base64 encoded protobuf messages on the wire format level, so that messages match
+/// regardless of the order in which their fields are encoded.
+pub struct ProtobufMessageComparator {}
+
+impl ProtobufMessageComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<String, String> for ProtobufMessageComparator {
+    fn matches(&self, mock_value: &String, req_value: &String) -> bool {
+        let canonical_form = |value: &String| {
+            base64::decode(value)
+                .ok()
+                .and_then(|message| protobuf::canonical_form(&message))
+        };
+        match (canonical_form(mock_value), canonical_form(req_value)) {
+            (Some(expected), Some(actual)) => expected == actual,
+            _ => false,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "equals"
+    }
+
+    fn distance(&self, mock_value: &Option<&String>, req_value: &Option<&String>) -> usize {
+        match (mock_value, req_value) {
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            (Some(_), _) => 1,
+            _ => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &String, req_value: &String) -> Option<String> {
+        let expected = base64::decode(mock_value).ok()?;
+        let actual = base64::decode(req_value).ok()?;
+        Some(match protobuf::differing_fields(&expected, &actual) {
+            None => "the body is not a valid protobuf message".to_string(),
+            Some(numbers) => {
+                let numbers: Vec<String> = numbers.iter().map(|n| n.to_string()).collect();
+                format!("the fields {} differ", numbers.join(", "))
+            }
+        })
+    }
+}
+
+// ************************************************************************************************
+// JwtClaimComparator
+// ************************************************************************************************
+pub struct JwtClaimComparator {}
+
+impl JwtClaimComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<JwtClaim, Value> for JwtClaimComparator {
+    /// A claim matches if it equals the expected value. Claims that hold an array (e.g. `aud`)
+    /// also match if the array contains the expected value.
+    fn matches(&self, mock_value: &JwtClaim, req_value: &Value) -> bool {
+        match req_value.get(&mock_value.name) {
+            None => false,
+            Some(Value::Array(values)) if !mock_value.value.is_array() => {
+                values.contains(&mock_value.value)
+            }
+            Some(value) => value == &mock_value.value,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "contains claim"
+    }
+
+    fn distance(&self, mock_value: &Option<&JwtClaim>, req_value: &Option<&Value>) -> usize {
+        match (mock_value, req_value) {
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            (Some(_), _) => 1,
+            _ => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &JwtClaim, req_value: &Value) -> Option<String> {
+        match req_value.get(&mock_value.name) {
+            None => Some(format!("no claim '{}'", mock_value.name)),
+            Some(value) => Some(format!(
+                "claim '{}': expected {} but got {}",
+                mock_value.name, mock_value.value, value
+            )),
+        }
+    }
+}
+
+// ************************************************************************************************
+// NdjsonLineComparator
+// ************************************************************************************************
+pub struct NdjsonLineComparator {}
+
+impl NdjsonLineComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<NdjsonLine, Value> for NdjsonLineComparator {
+    fn matches(&self, mock_value: &NdjsonLine, req_value: &Value) -> bool {
+        req_value.get(mock_value.index) == Some(&mock_value.value)
+    }
+
+    fn name(&self) -> &str {
+        "contains line"
+    }
+
+    fn distance(&self, mock_value: &Option<&NdjsonLine>, req_value: &Option<&Value>) -> usize {
+        match (mock_value, req_value) {
+            (Some(mv), Some(rv)) => distance_for(&Some(&mv.value), &rv.get(mv.index)),
+            (Some(_), None) => 1,
+            (None, _) => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &NdjsonLine, req_value: &Value) -> Option<String> {
+        let lines = req_value.as_array().map_or(0, Vec::len);
+        Some(match req_value.get(mock_value.index) {
+            None => format!("expected {} but the body has {} lines", mock_value, lines),
+            Some(value) => format!(
+                "line {}: expected {} but got {}",
+                mock_value.index, mock_value.value, value
+            ),
+        })
+    }
+}
+
+// ************************************************************************************************
+// NdjsonLineCountComparator
+// ************************************************************************************************
+pub struct NdjsonLineCountComparator {}
+
+impl NdjsonLineCountComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<usize, Value> for NdjsonLineCountComparator {
+    fn matches(&self, mock_value: &usize, req_value: &Value) -> bool {
+        req_value.as_array().map(Vec::len) == Some(*mock_value)
+    }
+
+    fn name(&self) -> &str {
+        "has a line count of"
+    }
+
+    fn distance(&self, mock_value: &Option<&usize>, req_value: &Option<&Value>) -> usize {
+        match (mock_value, req_value.and_then(|v| v.as_array())) {
+            (Some(count), Some(lines)) => (**count).max(lines.len()) - (**count).min(lines.len()),
+            (Some(_), None) => 1,
+            (None, _) => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &usize, req_value: &Value) -> Option<String> {
+        Some(format!(
+            "expected {} lines but got {}",
+            mock_value,
+            req_value.as_array().map_or(0, Vec::len)
+        ))
+    }
+}
+
+// ************************************************************************************************
+// BodyLengthComparator
+// ************************************************************************************************
+pub struct BodyLengthComparator {}
+
+impl BodyLengthComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<BodyLength, HttpMockRequest> for BodyLengthComparator {
+    /// Bodies that were not recorded (e.g. because they exceeded the maximum body size) never
+    /// match, since their length is unknown.
+    fn matches(&self, mock_value: &BodyLength, req_value: &HttpMockRequest) -> bool {
+        req_value
+            .body
+            .as_ref()
+            .map_or(false, |body| mock_value.contains(body.len()))
+    }
+
+    fn name(&self) -> &str {
+        "has a length of"
+    }
+
+    fn distance(
+        &self,
+        mock_value: &Option<&BodyLength>,
+        req_value: &Option<&HttpMockRequest>,
+    ) -> usize {
+        let (mock_value, len) = match (mock_value, req_value.and_then(|r| r.body.as_ref())) {
+            (Some(mock_value), Some(body)) => (mock_value, body.len()),
+            (None, _) => return 0,
+            (Some(_), None) => return 1,
+        };
+        match (mock_value.min, mock_value.max) {
+            (Some(min), _) if len < min => min - len,
+            (_, Some(max)) if len > max => len - max,
+            _ => 0,
+        }
+    }
+
+    fn explain(&self, mock_value: &BodyLength, req_value: &HttpMockRequest) -> Option<String> {
+        Some(match &req_value.body {
+            None => format!(
+                "expected a body of {} but the body was not recorded",
+                mock_value
+            ),
+            Some(body) => format!(
+                "expected a body of {} but got {} bytes",
+                mock_value,
+                body.len()
+            ),
+        })
+    }
+}
+
+// ************************************************************************************************
+// HeaderValuesComparator
+// ************************************************************************************************
+pub struct HeaderValuesComparator {}
+
+impl HeaderValuesComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns the values of all headers of the request with the given name in the order in
+    /// which they were sent. Comma-separated values are split.
+    fn request_values<'a>(name: &str, req: &'a HttpMockRequest) -> Vec<&'a str> {
+        req.headers
+            .iter()
+            .flatten()
+            .filter(|(n, _)| n.eq_ignore_ascii_case(name))
+            .flat_map(|(_, v)| v.split(','))
+            .map(str::trim)
+            .collect()
+    }
+}
+
+impl ValueComparator<HeaderValues, HttpMockRequest> for HeaderValuesComparator {
+    fn matches(&self, mock_value: &HeaderValues, req_value: &HttpMockRequest) -> bool {
+        Self::request_values(&mock_value.name, req_value) == mock_value.values
+    }
+
+    fn name(&self) -> &str {
+        "has the values"
+    }
+
+    fn distance(
+        &self,
+        mock_value: &Option<&HeaderValues>,
+        req_value: &Option<&HttpMockRequest>,
+    ) -> usize {
+        let mock_value = match mock_value {
+            None => return 0,
+            Some(v) => v,
+        };
+        let expected = mock_value.values.join(", ");
+        let actual = req_value
+            .map(|req| Self::request_values(&mock_value.name, req).join(", "))
+            .unwrap_or_default();
+        levenshtein::levenshtein(&expected, &actual)
+    }
+
+    fn explain(&self, mock_value: &HeaderValues, req_value: &HttpMockRequest) -> Option<String> {
+        let expected: Vec<String> = mock_value
+            .values
+            .iter()
+            .map(|v| format!("'{}'", v))
+            .collect();
+        let actual: Vec<String> = Self::request_values(&mock_value.name, req_value)
+            .iter()
+            .map(|v| format!("'{}'", v))
+            .collect();
+        Some(match actual.is_empty() {
+            true => format!(
+                "expected '{}' to have the values {} but it is missing",
+                mock_value.name,
+                expected.join(", ")
+            ),
+            false => format!(
+                "expected '{}' to have the values {} in this order but got {}",
+                mock_value.name,
+                expected.join(", "),
+                actual.join(", ")
+            ),
+        })
+    }
+}
+
+// ************************************************************************************************
+// QueryParamValuesComparator
+// ************************************************************************************************
+pub struct QueryParamValuesComparator {}
+
+impl QueryParamValuesComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns the values of a query parameter of the request in a canonical order.
+    fn request_values<'a>(name: &str, req: &'a HttpMockRequest) -> Vec<&'a str> {
+        let mut values: Vec<&str> = req
+            .query_params
+            .iter()
+            .flatten()
+            .filter(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+            .collect();
+        values.sort_unstable();
+        values
+    }
+}
+
+impl ValueComparator<QueryParamValues, HttpMockRequest> for QueryParamValuesComparator {
+    fn matches(&self, mock_value: &QueryParamValues, req_value: &HttpMockRequest) -> bool {
+        Self::request_values(&mock_value.name, req_value) == mock_value.sorted_values()
+    }
+
+    fn name(&self) -> &str {
+        "has the values"
+    }
 
-use crate::common::data::{HttpMockRequest, MockMatcherFunction};
-use crate::server::matchers::distance_for;
-use crate::Regex;
+    fn distance(
+        &self,
+        mock_value: &Option<&QueryParamValues>,
+        req_value: &Option<&HttpMockRequest>,
+    ) -> usize {
+        let mock_value = match mock_value {
+            None => return 0,
+            Some(v) => v,
+        };
+        let expected = mock_value.sorted_values().join("&");
+        let actual = req_value
+            .map(|req| Self::request_values(&mock_value.name, req).join("&"))
+            .unwrap_or_default();
+        levenshtein::levenshtein(&expected, &actual)
+    }
 
-pub trait ValueComparator<S, T> {
-    fn matches(&self, mock_value: &S, req_value: &T) -> bool;
-    fn name(&self) -> &str;
-    fn distance(&self, mock_value: &Option<&S>, req_value: &Option<&T>) -> usize;
+    fn explain(
+        &self,
+        mock_value: &QueryParamValues,
+        req_value: &HttpMockRequest,
+    ) -> Option<String> {
+        let expected: Vec<String> = mock_value
+            .values
+            .iter()
+            .map(|v| format!("'{}'", v))
+            .collect();
+        let actual: Vec<String> = Self::request_values(&mock_value.name, req_value)
+            .iter()
+            .map(|v| format!("'{}'", v))
+            .collect();
+        Some(match actual.is_empty() {
+            true => format!(
+                "expected '{}' to have the values {} but it is missing",
+                mock_value.name,
+                expected.join(", ")
+            ),
+            false => format!(
+                "expected '{}' to have the values {} but got {}",
+                mock_value.name,
+                expected.join(", "),
+                actual.join(", ")
+            ),
+        })
+    }
 }
 
 // ************************************************************************************************
-// JSONExactMatchComparator
+// QueryStringComparator
 // ************************************************************************************************
-pub struct JSONExactMatchComparator {}
+pub struct QueryStringComparator {}
 
-impl JSONExactMatchComparator {
+impl QueryStringComparator {
     pub fn new() -> Self {
         Self {}
     }
+
+    fn encode(params: &[&(String, String)]) -> String {
+        form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params.iter().map(|(name, value)| (name, value)))
+            .finish()
+    }
 }
 
-impl ValueComparator<Value, Value> for JSONExactMatchComparator {
-    fn matches(&self, mock_value: &Value, req_value: &Value) -> bool {
-        let config = Config::new(CompareMode::Strict);
-        assert_json_matches_no_panic(req_value, mock_value, config).is_ok()
+impl ValueComparator<QueryString, HttpMockRequest> for QueryStringComparator {
+    fn matches(&self, mock_value: &QueryString, req_value: &HttpMockRequest) -> bool {
+        let actual = req_value.query_params.as_deref().unwrap_or_default();
+        mock_value.comparable_params(&mock_value.params) == mock_value.comparable_params(actual)
     }
 
     fn name(&self) -> &str {
         "equals"
     }
 
-    fn distance(&self, mock_value: &Option<&Value>, req_value: &Option<&Value>) -> usize {
-        distance_for(mock_value, req_value)
+    fn distance(
+        &self,
+        mock_value: &Option<&QueryString>,
+        req_value: &Option<&HttpMockRequest>,
+    ) -> usize {
+        let mock_value = match mock_value {
+            None => return 0,
+            Some(v) => v,
+        };
+        let actual = req_value
+            .and_then(|req| req.query_params.as_deref())
+            .unwrap_or_default();
+        levenshtein::levenshtein(
+            &Self::encode(&mock_value.comparable_params(&mock_value.params)),
+            &Self::encode(&mock_value.comparable_params(actual)),
+        )
+    }
+
+    fn explain(&self, mock_value: &QueryString, req_value: &HttpMockRequest) -> Option<String> {
+        let actual: Vec<&(String, String)> = req_value.query_params.iter().flatten().collect();
+        let mut missing: Vec<&(String, String)> = mock_value.params.iter().collect();
+        let mut unexpected = Vec::new();
+        for param in &actual {
+            match missing.iter().position(|p| p == param) {
+                Some(idx) => {
+                    missing.remove(idx);
+                }
+                None => unexpected.push(*param),
+            }
+        }
+
+        let mut details = Vec::new();
+        if !unexpected.is_empty() {
+            details.push(format!("unexpected '{}'", Self::encode(&unexpected)));
+        }
+        if !missing.is_empty() {
+            details.push(format!("missing '{}'", Self::encode(&missing)));
+        }
+        if details.is_empty() {
+            details.push("the parameters are in a different order".to_string());
+        }
+        Some(format!(
+            "expected {} but got '{}' ({})",
+            mock_value,
+            Self::encode(&actual),
+            details.join(", ")
+        ))
     }
 }
 
 // ************************************************************************************************
-// JSONExactMatchComparator
+// MediaTypeComparator
 // ************************************************************************************************
-pub struct JSONContainsMatchComparator {}
+pub struct MediaTypeComparator {}
 
-impl JSONContainsMatchComparator {
+impl MediaTypeComparator {
     pub fn new() -> Self {
         Self {}
     }
 }
 
-impl ValueComparator<Value, Value> for JSONContainsMatchComparator {
-    fn matches(&self, mock_value: &Value, req_value: &Value) -> bool {
-        let config = Config::new(CompareMode::Inclusive);
-        assert_json_matches_no_panic(req_value, mock_value, config).is_ok()
+impl ValueComparator<String, String> for MediaTypeComparator {
+    fn matches(&self, mock_value: &String, req_value: &String) -> bool {
+        let (expected_type, expected_params) = parse_media_type(mock_value);
+        let (actual_type, actual_params) = parse_media_type(req_value);
+        expected_type == actual_type
+            && expected_params.iter().all(|(name, value)| {
+                actual_params.iter().any(|(n, v)| {
+                    n == name
+                        && match name.as_str() {
+                            "charset" => v.eq_ignore_ascii_case(value),
+                            _ => v == value,
+                        }
+                })
+            })
     }
 
     fn name(&self) -> &str {
-        "contains"
+        "has media type"
     }
 
-    fn distance(&self, mock_value: &Option<&Value>, req_value: &Option<&Value>) -> usize {
+    fn distance(&self, mock_value: &Option<&String>, req_value: &Option<&String>) -> usize {
         distance_for(mock_value, req_value)
     }
 }
 
+/// Splits a `Content-Type` header value into the lowercase media type and its parameters
+/// (with lowercase names and unquoted values).
+fn parse_media_type(value: &str) -> (String, Vec<(String, String)>) {
+    let mut parts = value.split(';');
+    let media_type = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(name, value)| {
+            (
+                name.trim().to_ascii_lowercase(),
+                value.trim().trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    (media_type, params)
+}
+
+// ************************************************************************************************
+// AcceptComparator
+// ************************************************************************************************
+pub struct AcceptComparator {}
+
+impl AcceptComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<String, String> for AcceptComparator {
+    fn matches(&self, mock_value: &String, req_value: &String) -> bool {
+        accept_quality(mock_value, req_value) > 0.0
+    }
+
+    fn name(&self) -> &str {
+        "accepts"
+    }
+
+    fn distance(&self, mock_value: &Option<&String>, req_value: &Option<&String>) -> usize {
+        match (mock_value, req_value) {
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            _ => distance_for(mock_value, req_value),
+        }
+    }
+}
+
+/// Returns the quality value that an `Accept` header value assigns to a media type. The most
+/// specific media range that matches the media type decides (RFC 9110, section 12.5.1):
+/// a range with parameters takes precedence over `type/subtype`, which takes precedence over
+/// `type/*` and `*/*`. Media types that no range matches have a quality of 0.
+fn accept_quality(media_type: &str, accept: &str) -> f32 {
+    let (media_type, params) = parse_media_type(media_type);
+    let (main_type, sub_type) = media_type.split_once('/').unwrap_or((&media_type, ""));
+
+    let mut best: Option<(usize, f32)> = None;
+    for range in accept.split(',').filter(|range| !range.trim().is_empty()) {
+        let (range_type, mut range_params) = parse_media_type(range);
+        let quality = match range_params.iter().position(|(name, _)| name == "q") {
+            // Parameters after the weight are accept extensions and not part of the range.
+            Some(index) => {
+                let quality = range_params[index].1.parse().unwrap_or(1.0);
+                range_params.truncate(index);
+                quality
+            }
+            None => 1.0,
+        };
+        let (range_main, range_sub) = range_type.split_once('/').unwrap_or((&range_type, ""));
+
+        let specificity = match (range_main, range_sub) {
+            ("*", "*") => 1,
+            (m, "*") if m == main_type => 2,
+            (m, s) if m == main_type && s == sub_type => {
+                if !range_params.iter().all(|p| params.contains(p)) {
+                    continue;
+                }
+                3 + range_params.len()
+            }
+            _ => continue,
+        };
+
+        if best.map_or(true, |(s, _)| specificity > s) {
+            best = Some((specificity, quality));
+        }
+    }
+
+    best.map_or(0.0, |(_, quality)| quality)
+}
+
 // ************************************************************************************************
 // StringExactMatchComparator
 // ************************************************************************************************
@@ -80,6 +1130,10 @@ impl ValueComparator<String, String> for StringExactMatchComparator {
     fn matches(&self, mock_value: &String, req_value: &String) -> bool {
         match self.case_sensitive {
             true => mock_value.eq(req_value),
+            // Header names are ASCII, so they are compared without allocating lowercase copies.
+            false if mock_value.is_ascii() && req_value.is_ascii() => {
+                mock_value.eq_ignore_ascii_case(req_value)
+            }
             false => mock_value.to_lowercase().eq(&req_value.to_lowercase()),
         }
     }
@@ -121,17 +1175,66 @@ impl ValueComparator<String, String> for StringContainsMatchComparator {
     }
 }
 
+// ************************************************************************************************
+// StringPrefixMatchComparator
+// ************************************************************************************************
+pub struct StringPrefixMatchComparator {}
+
+impl StringPrefixMatchComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<String, String> for StringPrefixMatchComparator {
+    fn matches(&self, mock_value: &String, req_value: &String) -> bool {
+        req_value.starts_with(mock_value.as_str())
+    }
+    fn name(&self) -> &str {
+        "starts with"
+    }
+    fn distance(&self, mock_value: &Option<&String>, req_value: &Option<&String>) -> usize {
+        distance_for(mock_value, req_value)
+    }
+}
+
+// ************************************************************************************************
+// StringSuffixMatchComparator
+// ************************************************************************************************
+pub struct StringSuffixMatchComparator {}
+
+impl StringSuffixMatchComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<String, String> for StringSuffixMatchComparator {
+    fn matches(&self, mock_value: &String, req_value: &String) -> bool {
+        req_value.ends_with(mock_value.as_str())
+    }
+    fn name(&self) -> &str {
+        "ends with"
+    }
+    fn distance(&self, mock_value: &Option<&String>, req_value: &Option<&String>) -> usize {
+        distance_for(mock_value, req_value)
+    }
+}
+
 // ************************************************************************************************
 // StringExactMatchComparator
 // ************************************************************************************************
+#[cfg(feature = "regex")]
 pub struct StringRegexMatchComparator {}
 
+#[cfg(feature = "regex")]
 impl StringRegexMatchComparator {
     pub fn new() -> Self {
         Self {}
     }
 }
 
+#[cfg(feature = "regex")]
 impl ValueComparator<Regex, String> for StringRegexMatchComparator {
     fn matches(&self, mock_value: &Regex, req_value: &String) -> bool {
         mock_value.is_match(req_value)
@@ -146,6 +1249,45 @@ impl ValueComparator<Regex, String> for StringRegexMatchComparator {
     }
 }
 
+// ************************************************************************************************
+// NegatedComparator
+// ************************************************************************************************
+/// Matches the values that the wrapped comparator does not match.
+pub struct NegatedComparator<S, T> {
+    name: &'static str,
+    comparator: Box<dyn ValueComparator<S, T> + Send + Sync>,
+}
+
+impl<S, T> NegatedComparator<S, T> {
+    pub fn new<C>(name: &'static str, comparator: C) -> Self
+    where
+        C: ValueComparator<S, T> + Send + Sync + 'static,
+    {
+        Self {
+            name,
+            comparator: Box::new(comparator),
+        }
+    }
+}
+
+impl<S, T> ValueComparator<S, T> for NegatedComparator<S, T> {
+    fn matches(&self, mock_value: &S, req_value: &T) -> bool {
+        !self.comparator.matches(mock_value, req_value)
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn distance(&self, mock_value: &Option<&S>, req_value: &Option<&T>) -> usize {
+        match (mock_value, req_value) {
+            (None, _) => 0,
+            (Some(mock_value), Some(req_value)) if self.matches(mock_value, req_value) => 0,
+            _ => 1,
+        }
+    }
+}
+
 // ************************************************************************************************
 // AnyValueComparator
 // ************************************************************************************************
@@ -182,7 +1324,8 @@ impl FunctionMatchesRequestComparator {
 
 impl ValueComparator<MockMatcherFunction, HttpMockRequest> for FunctionMatchesRequestComparator {
     fn matches(&self, mock_value: &MockMatcherFunction, req_value: &HttpMockRequest) -> bool {
-        (*mock_value)(req_value)
+        // A panicking function must not take down the request handler of the mock server.
+        panic::catch_unwind(AssertUnwindSafe(|| mock_value(req_value))).unwrap_or(false)
     }
 
     fn name(&self) -> &str {
@@ -209,15 +1352,222 @@ impl ValueComparator<MockMatcherFunction, HttpMockRequest> for FunctionMatchesRe
     }
 }
 
+// ************************************************************************************************
+// ChunkedBodyComparator
+// ************************************************************************************************
+pub struct ChunkedBodyComparator {}
+
+impl ChunkedBodyComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns the value of the `Transfer-Encoding` header if chunked is its last coding.
+    fn chunked_encoding<'a>(&self, req_value: &'a HttpMockRequest) -> Option<&'a String> {
+        req_value
+            .headers
+            .iter()
+            .flatten()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("transfer-encoding"))
+            .map(|(_, value)| value)
+            .last()
+            .filter(|value| {
+                value.rsplit(',').next().map_or(false, |coding| {
+                    coding.trim().eq_ignore_ascii_case("chunked")
+                })
+            })
+    }
+}
+
+impl ValueComparator<bool, HttpMockRequest> for ChunkedBodyComparator {
+    fn matches(&self, mock_value: &bool, req_value: &HttpMockRequest) -> bool {
+        self.chunked_encoding(req_value).is_some() == *mock_value
+    }
+
+    fn name(&self) -> &str {
+        "is chunked"
+    }
+
+    fn distance(&self, mock_value: &Option<&bool>, req_value: &Option<&HttpMockRequest>) -> usize {
+        match (mock_value, req_value) {
+            (None, _) => 0,
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            _ => 1,
+        }
+    }
+
+    fn explain(&self, _mock_value: &bool, req_value: &HttpMockRequest) -> Option<String> {
+        let content_length = req_value
+            .headers
+            .iter()
+            .flatten()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .map(|(_, value)| value);
+        Some(match content_length {
+            Some(len) => format!(
+                "expected a chunked body but the request has a Content-Length of {} bytes",
+                len
+            ),
+            None => "expected a chunked body but the request is not chunked".to_string(),
+        })
+    }
+}
+
+// ************************************************************************************************
+// HmacSignatureComparator
+// ************************************************************************************************
+pub struct HmacSignatureComparator {}
+
+impl HmacSignatureComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn mac(&self, mock_value: &HmacSignature, req_value: &HttpMockRequest) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(mock_value.secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(req_value.body.as_deref().unwrap_or_default());
+        mac
+    }
+
+    /// Decodes a signature that is hex encoded (in any case) or base64 encoded and may have a
+    /// `sha256=` prefix.
+    fn decode_signature(&self, value: &str) -> Option<Vec<u8>> {
+        let value = value.trim();
+        let signature = match value.get(..7) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("sha256=") => &value[7..],
+            _ => value,
+        };
+        if signature.len() == 64 && signature.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return (0..signature.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&signature[i..i + 2], 16).ok())
+                .collect();
+        }
+        base64::decode(signature).ok()
+    }
+
+    fn header_values<'a>(
+        &self,
+        mock_value: &HmacSignature,
+        req_value: &'a HttpMockRequest,
+    ) -> Vec<&'a String> {
+        req_value
+            .headers
+            .iter()
+            .flatten()
+            .filter(|(name, _)| name.eq_ignore_ascii_case(&mock_value.header))
+            .map(|(_, value)| value)
+            .collect()
+    }
+}
+
+impl ValueComparator<HmacSignature, HttpMockRequest> for HmacSignatureComparator {
+    /// The signature may be hex encoded (in any case) or base64 encoded and may have a
+    /// `sha256=` prefix.
+    fn matches(&self, mock_value: &HmacSignature, req_value: &HttpMockRequest) -> bool {
+        let mac = self.mac(mock_value, req_value);
+        self.header_values(mock_value, req_value)
+            .into_iter()
+            .filter_map(|value| self.decode_signature(value))
+            .any(|signature| mac.clone().verify_slice(&signature).is_ok())
+    }
+
+    fn name(&self) -> &str {
+        "has a valid signature in"
+    }
+
+    fn distance(
+        &self,
+        mock_value: &Option<&HmacSignature>,
+        req_value: &Option<&HttpMockRequest>,
+    ) -> usize {
+        match (mock_value, req_value) {
+            (None, _) => 0,
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            _ => 1,
+        }
+    }
+
+    fn explain(&self, mock_value: &HmacSignature, req_value: &HttpMockRequest) -> Option<String> {
+        let expected: String = self
+            .mac(mock_value, req_value)
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let values: Vec<String> = self
+            .header_values(mock_value, req_value)
+            .into_iter()
+            .map(|v| format!("'{}'", v))
+            .collect();
+        Some(match values.is_empty() {
+            true => format!(
+                "expected the header '{}' to be 'sha256={}' but it is missing",
+                mock_value.header, expected
+            ),
+            false => format!(
+                "expected the header '{}' to be 'sha256={}' but got {}",
+                mock_value.header,
+                expected,
+                values.join(", ")
+            ),
+        })
+    }
+}
+
+// ************************************************************************************************
+// CustomMatcherComparator
+// ************************************************************************************************
+pub struct CustomMatcherComparator {}
+
+impl CustomMatcherComparator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueComparator<CustomMatcher, HttpMockRequest> for CustomMatcherComparator {
+    fn matches(&self, mock_value: &CustomMatcher, req_value: &HttpMockRequest) -> bool {
+        // A panicking matcher must not take down the request handler of the mock server.
+        panic::catch_unwind(AssertUnwindSafe(|| mock_value.0.matches(req_value))).unwrap_or(false)
+    }
+
+    fn name(&self) -> &str {
+        "matches"
+    }
+
+    fn distance(
+        &self,
+        mock_value: &Option<&CustomMatcher>,
+        req_value: &Option<&HttpMockRequest>,
+    ) -> usize {
+        match (mock_value, req_value) {
+            (None, _) => 0,
+            (Some(mv), Some(rv)) if self.matches(mv, rv) => 0,
+            _ => 1,
+        }
+    }
+
+    fn explain(&self, mock_value: &CustomMatcher, _req_value: &HttpMockRequest) -> Option<String> {
+        Some(format!("expected {}", mock_value))
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use serde_json::json;
+    use assert_json_diff::{assert_json_matches_no_panic, CompareMode, Config};
+    use serde_json::{json, Value};
 
+    #[cfg(feature = "regex")]
+    use crate::server::matchers::comparators::StringRegexMatchComparator;
     use crate::server::matchers::comparators::{
         AnyValueComparator, JSONContainsMatchComparator, JSONExactMatchComparator,
-        StringContainsMatchComparator, StringExactMatchComparator, StringRegexMatchComparator,
-        ValueComparator,
+        NegatedComparator, StringContainsMatchComparator, StringExactMatchComparator,
+        StringPrefixMatchComparator, StringSuffixMatchComparator, ValueComparator,
     };
+    #[cfg(feature = "regex")]
     use crate::Regex;
 
     fn run_test<S, T>(
@@ -287,6 +1637,42 @@ mod test {
         );
     }
 
+    #[test]
+    fn json_comparators_match_like_assert_json_diff() {
+        // Arrange
+        let cases = vec![
+            (json!({"a": 1}), json!({"a": 1, "b": 2})),
+            (json!({"a": 1}), json!({"a": 1.0})),
+            (json!({"a": [1, 2]}), json!({"a": [1, 2, 3]})),
+            (json!({"a": [2]}), json!({"a": [1, 2]})),
+            (json!([{"a": 1}]), json!([{"a": 1, "b": 2}, 3])),
+            (json!({"a": {}}), json!({"a": {"b": null}})),
+            (json!({"a": null}), json!({})),
+            (json!({"a": "1"}), json!({"a": 1})),
+            (json!([]), json!({})),
+            (json!("text"), json!("text")),
+        ];
+
+        for (mock_value, req_value) in cases {
+            for (mode, comparator) in [
+                (
+                    CompareMode::Inclusive,
+                    &JSONContainsMatchComparator::new() as &dyn ValueComparator<Value, Value>,
+                ),
+                (CompareMode::Strict, &JSONExactMatchComparator::new()),
+            ] {
+                // Act
+                let result = comparator.matches(&mock_value, &req_value);
+
+                // Assert
+                let expected =
+                    assert_json_matches_no_panic(&req_value, &mock_value, Config::new(mode))
+                        .is_ok();
+                assert_eq!(result, expected, "{} {}", mock_value, req_value);
+            }
+        }
+    }
+
     #[test]
     fn string_exact_comparator_match() {
         run_test(
@@ -359,6 +1745,79 @@ mod test {
         );
     }
 
+    #[test]
+    fn string_prefix_comparator_match() {
+        run_test(
+            &StringPrefixMatchComparator::new(),
+            &"/api/v1/".to_string(),
+            &"/api/v1/users".to_string(),
+            true,
+            5, // compute distance even if values match!
+            "starts with",
+        );
+    }
+
+    #[test]
+    fn string_prefix_comparator_no_match() {
+        run_test(
+            &StringPrefixMatchComparator::new(),
+            &"/api/v1/".to_string(),
+            &"/v1/api/".to_string(),
+            false,
+            6, // compute distance even if values match!
+            "starts with",
+        );
+    }
+
+    #[test]
+    fn string_suffix_comparator_match() {
+        run_test(
+            &StringSuffixMatchComparator::new(),
+            &".json".to_string(),
+            &"/data.json".to_string(),
+            true,
+            5, // compute distance even if values match!
+            "ends with",
+        );
+    }
+
+    #[test]
+    fn string_suffix_comparator_no_match() {
+        run_test(
+            &StringSuffixMatchComparator::new(),
+            &".json".to_string(),
+            &"/data.JSON".to_string(),
+            false,
+            9, // compute distance even if values match!
+            "ends with",
+        );
+    }
+
+    #[test]
+    fn negated_comparator_match() {
+        run_test(
+            &NegatedComparator::new("does not contain", StringContainsMatchComparator::new(true)),
+            &"nickname".to_string(),
+            &"{\"name\":\"Fred\"}".to_string(),
+            true,
+            0,
+            "does not contain",
+        );
+    }
+
+    #[test]
+    fn negated_comparator_no_match() {
+        run_test(
+            &NegatedComparator::new("does not contain", StringContainsMatchComparator::new(true)),
+            &"nickname".to_string(),
+            &"{\"nickname\":\"F\"}".to_string(),
+            false,
+            1,
+            "does not contain",
+        );
+    }
+
+    #[cfg(feature = "regex")]
     #[test]
     fn regex_comparator_match() {
         run_test(
@@ -371,6 +1830,7 @@ mod test {
         );
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn regex_comparator_no_match() {
         run_test(
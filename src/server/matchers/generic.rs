@@ -4,12 +4,12 @@ use std::net::ToSocketAddrs;
 
 use serde_json::Value;
 
-use crate::common::data::{HttpMockRequest, Mismatch, Reason, RequestRequirements, Tokenizer};
+use crate::common::data::{MatchStage, Mismatch, Reason, RequestRequirements, Tokenizer};
 use crate::server::matchers::comparators::ValueComparator;
 use crate::server::matchers::sources::{MultiValueSource, ValueRefSource};
-use crate::server::matchers::targets::{MultiValueTarget, ValueRefTarget, ValueTarget};
+use crate::server::matchers::targets::{MultiValueTarget, ValueTarget};
 use crate::server::matchers::transformers::Transformer;
-use crate::server::matchers::{diff_str, Matcher};
+use crate::server::matchers::{diff_str, Matcher, MatchingRequest};
 
 // ************************************************************************************************
 // SingleValueMatcher
@@ -27,6 +27,7 @@ where
     pub with_reason: bool,
     pub diff_with: Option<Tokenizer>,
     pub weight: usize,
+    pub stage: MatchStage,
 }
 
 impl<S, T> SingleValueMatcher<S, T>
@@ -36,7 +37,7 @@ where
 {
     fn find_unmatched<'a>(
         &self,
-        req_value: &Option<T>,
+        req_value: &Option<&T>,
         mock_values: &Option<Vec<&'a S>>,
     ) -> Vec<&'a S> {
         let mock_values = match mock_values {
@@ -59,32 +60,60 @@ where
     S: Display,
     T: Display,
 {
-    fn matches(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> bool {
-        let req_value = self.target.parse_from_request(req);
-        let mock_value = self.source.parse_from_mock(mock);
-        self.find_unmatched(&req_value, &mock_value).is_empty()
+    fn entity_name(&self) -> &'static str {
+        self.entity_name
     }
 
-    fn distance(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> usize {
-        let req_value = self.target.parse_from_request(req);
+    fn stage(&self) -> MatchStage {
+        self.stage
+    }
+
+    fn matches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> bool {
+        // The request is only parsed if the mock has a requirement for it.
+        let mock_values = match self.source.parse_from_mock(mock) {
+            None => return true,
+            Some(mock_values) => mock_values,
+        };
+        match self.target.parse_from_request(req) {
+            None => mock_values.is_empty(),
+            Some(req_value) => mock_values
+                .iter()
+                .all(|mock_value| self.comparator.matches(mock_value, req_value)),
+        }
+    }
+
+    fn distance(&self, req: &MatchingRequest, mock: &RequestRequirements) -> usize {
         let mock_values = self.source.parse_from_mock(mock);
+        if mock_values.is_none() {
+            return 0;
+        }
+        let req_value = self.target.parse_from_request(req);
         self.find_unmatched(&req_value, &mock_values)
             .into_iter()
-            .map(|s| self.comparator.distance(&Some(s), &req_value.as_ref()))
+            .map(|s| self.comparator.distance(&Some(s), &req_value))
             .map(|d| d * self.weight)
             .sum()
     }
 
-    fn mismatches(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> Vec<Mismatch> {
-        let req_value = self.target.parse_from_request(req);
+    fn mismatches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> Vec<Mismatch> {
         let mock_value = self.source.parse_from_mock(mock);
+        if mock_value.is_none() {
+            return Vec::new();
+        }
+        let req_value = self.target.parse_from_request(req);
         self.find_unmatched(&req_value, &mock_value)
             .into_iter()
             .map(|mock_value| {
+                let explanation = req_value.and_then(|rv| self.comparator.explain(mock_value, rv));
                 let mock_value = mock_value.to_string();
-                let req_value = req_value.as_ref().unwrap().to_string();
+                let req_value = req_value.map_or(String::new(), |v| v.to_string());
                 Mismatch {
-                    title: format!("The {} does not match", self.entity_name),
+                    title: match explanation {
+                        Some(explanation) => {
+                            format!("The {} does not match: {}", self.entity_name, explanation)
+                        }
+                        None => format!("The {} does not match", self.entity_name),
+                    },
                     reason: match self.with_reason {
                         true => Some(Reason {
                             expected: mock_value.to_owned(),
@@ -95,12 +124,98 @@ where
                         false => None,
                     },
                     diff: self.diff_with.map(|t| diff_str(&mock_value, &req_value, t)),
+                    stage: Some(self.stage),
                 }
             })
             .collect()
     }
 }
 
+// ************************************************************************************************
+// OneOfValueMatcher
+// ************************************************************************************************
+/// Matches requests whose value matches at least one of the values of the mock, e.g. one of
+/// the methods that a mock accepts.
+pub(crate) struct OneOfValueMatcher<S, T>
+where
+    S: Display,
+    T: Display,
+{
+    pub entity_name: &'static str,
+    pub source: Box<dyn ValueRefSource<S> + Send + Sync>,
+    pub target: Box<dyn ValueTarget<T> + Send + Sync>,
+    pub comparator: Box<dyn ValueComparator<S, T> + Send + Sync>,
+    pub weight: usize,
+    pub stage: MatchStage,
+}
+
+impl<S, T> Matcher for OneOfValueMatcher<S, T>
+where
+    S: Display,
+    T: Display,
+{
+    fn entity_name(&self) -> &'static str {
+        self.entity_name
+    }
+
+    fn stage(&self) -> MatchStage {
+        self.stage
+    }
+
+    fn matches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> bool {
+        let mock_values = match self.source.parse_from_mock(mock) {
+            None => return true,
+            Some(mock_values) => mock_values,
+        };
+        match self.target.parse_from_request(req) {
+            None => mock_values.is_empty(),
+            Some(req_value) => mock_values
+                .iter()
+                .any(|mock_value| self.comparator.matches(mock_value, req_value)),
+        }
+    }
+
+    fn distance(&self, req: &MatchingRequest, mock: &RequestRequirements) -> usize {
+        if self.matches(req, mock) {
+            return 0;
+        }
+        let req_value = self.target.parse_from_request(req);
+        self.source
+            .parse_from_mock(mock)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|mock_value| self.comparator.distance(&Some(mock_value), &req_value))
+            .min()
+            .unwrap_or(0)
+            * self.weight
+    }
+
+    fn mismatches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> Vec<Mismatch> {
+        if self.matches(req, mock) {
+            return Vec::new();
+        }
+        let mock_values: Vec<String> = self
+            .source
+            .parse_from_mock(mock)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|mock_value| mock_value.to_string())
+            .collect();
+        let req_value = self.target.parse_from_request(req);
+        vec![Mismatch {
+            title: format!("The {} does not match", self.entity_name),
+            reason: Some(Reason {
+                expected: mock_values.join(" or "),
+                actual: req_value.map_or(String::new(), |v| v.to_string()),
+                comparison: self.comparator.name().into(),
+                best_match: false,
+            }),
+            diff: None,
+            stage: Some(self.stage),
+        }]
+    }
+}
+
 // ************************************************************************************************
 // MultiValueMatcher
 // ************************************************************************************************
@@ -121,6 +236,7 @@ where
     pub with_reason: bool,
     pub diff_with: Option<Tokenizer>,
     pub weight: usize,
+    pub stage: MatchStage,
 }
 
 impl<SK, SV, TK, TV> MultiValueMatcher<SK, SV, TK, TV>
@@ -137,23 +253,23 @@ where
     ) -> Vec<&'a (&'a SK, Option<&'a SV>)> {
         mock_values
             .into_iter()
-            .filter(|(sk, sv)| {
-                req_values
-                    .iter()
-                    .find(|(tk, tv)| {
-                        let key_matches = self.key_comparator.matches(sk, &tk);
-                        let value_matches = match (sv, tv) {
-                            (Some(_), None) => false, // Mock required a value but none was present
-                            (Some(sv), Some(tv)) => self.value_comparator.matches(sv, &tv),
-                            _ => true,
-                        };
-                        key_matches && value_matches
-                    })
-                    .is_none()
-            })
+            .filter(|mock_value| !self.is_matched(mock_value, req_values))
             .collect()
     }
 
+    /// Returns whether the request contains a key/value pair that matches the one of the mock.
+    fn is_matched(&self, mock_value: &(&SK, Option<&SV>), req_values: &[(TK, Option<TV>)]) -> bool {
+        let (sk, sv) = mock_value;
+        req_values.iter().any(|(tk, tv)| {
+            self.key_comparator.matches(sk, tk)
+                && match (sv, tv) {
+                    (Some(_), None) => false, // Mock required a value but none was present
+                    (Some(sv), Some(tv)) => self.value_comparator.matches(sv, tv),
+                    _ => true,
+                }
+        })
+    }
+
     fn find_best_match<'a>(
         &self,
         sk: &SK,
@@ -190,18 +306,37 @@ where
     TK: Display,
     TV: Display,
 {
-    fn matches(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> bool {
-        let req_values = self.target.parse_from_request(req).unwrap_or(Vec::new());
+    fn entity_name(&self) -> &'static str {
+        self.entity_name
+    }
+
+    fn stage(&self) -> MatchStage {
+        self.stage
+    }
+
+    fn matches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> bool {
+        // The request is only parsed if the mock has a requirement for it.
         let mock_values = self.source.parse_from_mock(mock).unwrap_or(Vec::new());
-        self.find_unmatched(&req_values, &mock_values).is_empty()
+        if mock_values.is_empty() {
+            return true;
+        }
+        let empty = Vec::new();
+        let req_values = self.target.parse_from_request(req).unwrap_or(&empty);
+        mock_values
+            .iter()
+            .all(|mock_value| self.is_matched(mock_value, req_values))
     }
 
-    fn distance(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> usize {
-        let req_values = self.target.parse_from_request(req).unwrap_or(Vec::new());
+    fn distance(&self, req: &MatchingRequest, mock: &RequestRequirements) -> usize {
         let mock_values = self.source.parse_from_mock(mock).unwrap_or(Vec::new());
-        self.find_unmatched(&req_values, &mock_values)
+        if mock_values.is_empty() {
+            return 0;
+        }
+        let empty = Vec::new();
+        let req_values = self.target.parse_from_request(req).unwrap_or(&empty);
+        self.find_unmatched(req_values, &mock_values)
             .into_iter()
-            .map(|(k, v)| (k, v, self.find_best_match(&k, v, &req_values)))
+            .map(|(k, v)| (k, v, self.find_best_match(&k, v, req_values)))
             .map(|(k, v, best_match)| match best_match {
                 None => {
                     self.key_comparator.distance(&Some(k), &None)
@@ -216,12 +351,16 @@ where
             .sum()
     }
 
-    fn mismatches(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> Vec<Mismatch> {
-        let req_values = self.target.parse_from_request(req).unwrap_or(Vec::new());
+    fn mismatches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> Vec<Mismatch> {
         let mock_values = self.source.parse_from_mock(mock).unwrap_or(Vec::new());
-        self.find_unmatched(&req_values, &mock_values)
+        if mock_values.is_empty() {
+            return Vec::new();
+        }
+        let empty = Vec::new();
+        let req_values = self.target.parse_from_request(req).unwrap_or(&empty);
+        self.find_unmatched(req_values, &mock_values)
             .into_iter()
-            .map(|(k, v)| (k, v, self.find_best_match(&k, v, &req_values)))
+            .map(|(k, v)| (k, v, self.find_best_match(&k, v, req_values)))
             .map(|(k, v, best_match)| Mismatch {
                 title: match v {
                     None => format!("Expected {} with name '{}' to be present in the request but it wasn't.", self.entity_name, &k),
@@ -242,6 +381,97 @@ where
                     }
                 }),
                 diff: None,
+                stage: Some(self.stage),
+            })
+            .collect()
+    }
+}
+
+// ************************************************************************************************
+// MissingValueMatcher
+// ************************************************************************************************
+/// Matches requests that contain none of the keys of the mock, e.g. none of the query
+/// parameters that a mock requires to be missing.
+pub(crate) struct MissingValueMatcher<SK, TK, TV>
+where
+    SK: Display,
+    TK: Display,
+    TV: Display,
+{
+    pub entity_name: &'static str,
+    pub source: Box<dyn ValueRefSource<SK> + Send + Sync>,
+    pub target: Box<dyn MultiValueTarget<TK, TV> + Send + Sync>,
+    pub key_comparator: Box<dyn ValueComparator<SK, TK> + Send + Sync>,
+    pub weight: usize,
+    pub stage: MatchStage,
+}
+
+impl<SK, TK, TV> MissingValueMatcher<SK, TK, TV>
+where
+    SK: Display,
+    TK: Display,
+    TV: Display,
+{
+    /// Returns the keys of the mock that are present in the request.
+    fn find_present<'a>(&self, req: &MatchingRequest, mock_values: Vec<&'a SK>) -> Vec<&'a SK> {
+        let req_values = match self.target.parse_from_request(req) {
+            None => return Vec::new(),
+            Some(req_values) => req_values,
+        };
+        mock_values
+            .into_iter()
+            .filter(|sk| {
+                req_values
+                    .iter()
+                    .any(|(tk, _)| self.key_comparator.matches(sk, tk))
+            })
+            .collect()
+    }
+}
+
+impl<SK, TK, TV> Matcher for MissingValueMatcher<SK, TK, TV>
+where
+    SK: Display,
+    TK: Display,
+    TV: Display,
+{
+    fn entity_name(&self) -> &'static str {
+        self.entity_name
+    }
+
+    fn stage(&self) -> MatchStage {
+        self.stage
+    }
+
+    fn matches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> bool {
+        match self.source.parse_from_mock(mock) {
+            None => true,
+            Some(mock_values) => self.find_present(req, mock_values).is_empty(),
+        }
+    }
+
+    fn distance(&self, req: &MatchingRequest, mock: &RequestRequirements) -> usize {
+        match self.source.parse_from_mock(mock) {
+            None => 0,
+            Some(mock_values) => self.find_present(req, mock_values).len() * self.weight,
+        }
+    }
+
+    fn mismatches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> Vec<Mismatch> {
+        let mock_values = match self.source.parse_from_mock(mock) {
+            None => return Vec::new(),
+            Some(mock_values) => mock_values,
+        };
+        self.find_present(req, mock_values)
+            .into_iter()
+            .map(|k| Mismatch {
+                title: format!(
+                    "Expected {} with name '{}' to be missing in the request but it was present.",
+                    self.entity_name, k
+                ),
+                reason: None,
+                diff: None,
+                stage: Some(self.stage),
             })
             .collect()
     }
@@ -253,10 +483,11 @@ where
 pub(crate) struct FunctionValueMatcher<S, T> {
     pub entity_name: &'static str,
     pub source: Box<dyn ValueRefSource<S> + Send + Sync>,
-    pub target: Box<dyn ValueRefTarget<T> + Send + Sync>,
+    pub target: Box<dyn ValueTarget<T> + Send + Sync>,
     pub comparator: Box<dyn ValueComparator<S, T> + Send + Sync>,
     pub transformer: Option<Box<dyn Transformer<T, T> + Send + Sync>>,
     pub weight: usize,
+    pub stage: MatchStage,
 }
 
 impl<S, T> FunctionValueMatcher<S, T> {
@@ -289,21 +520,38 @@ impl<S, T> FunctionValueMatcher<S, T> {
 }
 
 impl<S, T> Matcher for FunctionValueMatcher<S, T> {
-    fn matches(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> bool {
-        let req_value = self.target.parse_from_request(req);
+    fn entity_name(&self) -> &'static str {
+        self.entity_name
+    }
+
+    fn stage(&self) -> MatchStage {
+        self.stage
+    }
+
+    fn matches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> bool {
         let mock_values = self.source.parse_from_mock(mock);
+        if mock_values.is_none() {
+            return true;
+        }
+        let req_value = self.target.parse_from_request(req);
         self.get_unmatched(&req_value, &mock_values).is_empty()
     }
 
-    fn distance(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> usize {
-        let req_value = self.target.parse_from_request(req);
+    fn distance(&self, req: &MatchingRequest, mock: &RequestRequirements) -> usize {
         let mock_values = self.source.parse_from_mock(mock);
+        if mock_values.is_none() {
+            return 0;
+        }
+        let req_value = self.target.parse_from_request(req);
         self.get_unmatched(&req_value, &mock_values).len() * self.weight
     }
 
-    fn mismatches(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> Vec<Mismatch> {
-        let req_value = self.target.parse_from_request(req);
+    fn mismatches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> Vec<Mismatch> {
         let mock_value = self.source.parse_from_mock(mock);
+        if mock_value.is_none() {
+            return Vec::new();
+        }
+        let req_value = self.target.parse_from_request(req);
         self.get_unmatched(&req_value, &mock_value)
             .into_iter()
             .map(|idx| Mismatch {
@@ -314,6 +562,7 @@ impl<S, T> Matcher for FunctionValueMatcher<S, T> {
                 ),
                 reason: None,
                 diff: None,
+                stage: Some(self.stage),
             })
             .collect()
     }
@@ -1,14 +1,15 @@
+use std::cell::OnceCell;
 use std::collections::BTreeMap;
 use std::fmt::Display;
 
-#[cfg(feature = "cookies")]
-use basic_cookies::Cookie;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use similar::{ChangeTag, TextDiff};
 
 use crate::common::data::{
-    Diff, DiffResult, HttpMockRequest, Mismatch, RequestRequirements, Tokenizer,
+    Diff, DiffResult, HttpMockRequest, MatchStage, Mismatch, RequestRequirements, Tokenizer,
 };
+use crate::common::multipart::MultipartBody;
 
 pub(crate) mod comparators;
 pub(crate) mod generic;
@@ -38,33 +39,148 @@ pub(crate) fn diff_str(base: &str, edit: &str, tokenizer: Tokenizer) -> DiffResu
 }
 
 pub trait Matcher {
-    fn matches(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> bool;
-    fn distance(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> usize;
-    fn mismatches(&self, req: &HttpMockRequest, mock: &RequestRequirements) -> Vec<Mismatch>;
+    /// The name of the part of the request that this matcher checks (e.g. `path`).
+    fn entity_name(&self) -> &'static str;
+    /// The stage in which this matcher is evaluated. Matchers are evaluated in the order of
+    /// their stages.
+    fn stage(&self) -> MatchStage;
+    fn matches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> bool;
+    fn distance(&self, req: &MatchingRequest, mock: &RequestRequirements) -> usize;
+    fn mismatches(&self, req: &MatchingRequest, mock: &RequestRequirements) -> Vec<Mismatch>;
+}
+
+/// A request that is matched against mocks. The parts of the request that matchers compare
+/// (e.g. the JSON body or the cookies) are parsed when they are first needed and then shared
+/// by all mocks, so that matching a request against many mocks does not parse it again for
+/// every mock.
+pub(crate) struct ParsedRequest<'a> {
+    pub req: &'a HttpMockRequest,
+    pub body: OnceCell<Option<String>>,
+    pub body_base64: OnceCell<Option<String>>,
+    pub json_body: OnceCell<Option<Value>>,
+    pub ndjson_body: OnceCell<Option<Value>>,
+    pub headers: OnceCell<Option<Vec<(String, Option<String>)>>>,
+    pub basic_auth: OnceCell<Option<String>>,
+    pub bearer_token: OnceCell<Option<String>>,
+    pub jwt_claims: OnceCell<Option<Value>>,
+    pub query_params: OnceCell<Option<Vec<(String, Option<String>)>>>,
+    pub raw_query_params: OnceCell<Option<Vec<(String, Option<String>)>>>,
+    pub query_object: OnceCell<Value>,
+    pub cookies: OnceCell<Option<Vec<(String, Option<String>)>>>,
+    pub form_body: OnceCell<Option<Vec<(String, Option<String>)>>>,
+    pub grpc_message: OnceCell<Option<String>>,
+    #[cfg(feature = "graphql")]
+    pub graphql_operation: OnceCell<Option<String>>,
+    pub remote_addr: OnceCell<Option<String>>,
+    pub accept: OnceCell<String>,
+    pub multipart_body: OnceCell<Option<MultipartBody>>,
+}
+
+impl<'a> ParsedRequest<'a> {
+    pub fn new(req: &'a HttpMockRequest) -> Self {
+        Self {
+            req,
+            body: OnceCell::new(),
+            body_base64: OnceCell::new(),
+            json_body: OnceCell::new(),
+            ndjson_body: OnceCell::new(),
+            headers: OnceCell::new(),
+            basic_auth: OnceCell::new(),
+            bearer_token: OnceCell::new(),
+            jwt_claims: OnceCell::new(),
+            query_params: OnceCell::new(),
+            raw_query_params: OnceCell::new(),
+            query_object: OnceCell::new(),
+            cookies: OnceCell::new(),
+            form_body: OnceCell::new(),
+            grpc_message: OnceCell::new(),
+            #[cfg(feature = "graphql")]
+            graphql_operation: OnceCell::new(),
+            remote_addr: OnceCell::new(),
+            accept: OnceCell::new(),
+            multipart_body: OnceCell::new(),
+        }
+    }
+}
+
+/// A parsed request as it is seen by the matchers of a single mock, i.e. with the base path of
+/// the mock removed from the request path and the path normalized as requested by the mock.
+pub struct MatchingRequest<'a> {
+    parsed: &'a ParsedRequest<'a>,
+    relative_path: Option<String>,
+    relative_request: OnceCell<HttpMockRequest>,
+    raw_query: bool,
+}
+
+impl<'a> MatchingRequest<'a> {
+    pub(crate) fn new(parsed: &'a ParsedRequest<'a>, relative_path: Option<String>) -> Self {
+        Self {
+            parsed,
+            relative_path,
+            relative_request: OnceCell::new(),
+            raw_query: false,
+        }
+    }
+
+    /// Matches query parameters in the percent-encoded form that they were sent in.
+    pub(crate) fn with_raw_query(mut self, raw_query: bool) -> Self {
+        self.raw_query = raw_query;
+        self
+    }
+
+    /// Whether query parameters are matched without decoding them.
+    pub(crate) fn raw_query(&self) -> bool {
+        self.raw_query
+    }
+
+    pub(crate) fn parsed(&self) -> &ParsedRequest<'a> {
+        self.parsed
+    }
+
+    /// The request path without the base path of the mock, normalized as requested by the mock.
+    pub(crate) fn path(&self) -> &String {
+        self.relative_path.as_ref().unwrap_or(&self.parsed.req.path)
+    }
+
+    /// The request with the path as it is seen by the mock. The request is only copied if the
+    /// mock has a base path or normalizes paths.
+    pub(crate) fn request(&self) -> &HttpMockRequest {
+        match &self.relative_path {
+            None => self.parsed.req,
+            Some(path) => self.relative_request.get_or_init(|| {
+                let mut relative = self.parsed.req.clone();
+                relative.path = path.clone();
+                relative
+            }),
+        }
+    }
 }
 
 // *************************************************************************************************
 // Helper functions
 // *************************************************************************************************
+/// Parses the cookies of all `Cookie` headers of a request (HTTP/2 clients may send one header
+/// per cookie). Parsing is lenient about whitespace, empty pairs and quoted values, so that
+/// cookies match regardless of how a client formats the header. Pairs without a `=` are
+/// skipped.
 #[cfg(feature = "cookies")]
-pub(crate) fn parse_cookies(req: &HttpMockRequest) -> Result<Vec<(String, String)>, String> {
-    let parsing_result = req.headers.as_ref().map_or(None, |request_headers| {
-        request_headers
-            .iter()
-            .find(|(k, _)| k.to_lowercase().eq("cookie"))
-            .map(|(k, v)| Cookie::parse(v))
-    });
-
-    match parsing_result {
-        None => Ok(Vec::new()),
-        Some(res) => match res {
-            Err(err) => Err(err.to_string()),
-            Ok(vec) => Ok(vec
-                .into_iter()
-                .map(|c| (c.get_name().to_owned(), c.get_value().to_owned()))
-                .collect()),
-        },
-    }
+pub(crate) fn parse_cookies(req: &HttpMockRequest) -> Vec<(String, String)> {
+    req.headers
+        .iter()
+        .flatten()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("cookie"))
+        .flat_map(|(_, value)| value.split(';'))
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(name, value)| {
+            let value = value.trim();
+            let value = match value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+                true => &value[1..value.len() - 1],
+                false => value,
+            };
+            (name.trim().to_string(), value.to_string())
+        })
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
 }
 
 pub(crate) fn distance_for<T, U>(expected: &Option<&T>, actual: &Option<&U>) -> usize
@@ -76,3 +192,35 @@ where
     let actual = actual.map_or(String::new(), |x| x.to_string());
     levenshtein::levenshtein(&expected, &actual)
 }
+
+#[cfg(all(test, feature = "cookies"))]
+mod test {
+    use crate::common::data::HttpMockRequest;
+    use crate::server::matchers::parse_cookies;
+
+    #[test]
+    fn parse_cookies_test() {
+        // Arrange
+        let req = HttpMockRequest::new("GET".to_string(), "/".to_string()).with_headers(vec![
+            (
+                "Cookie".to_string(),
+                " a=1 ;b=\"two\";; HttpOnly;".to_string(),
+            ),
+            ("content-type".to_string(), "text/plain".to_string()),
+            ("cookie".to_string(), "c = x=y".to_string()),
+        ]);
+
+        // Act
+        let cookies = parse_cookies(&req);
+
+        // Assert
+        assert_eq!(
+            cookies,
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "two".to_string()),
+                ("c".to_string(), "x=y".to_string()),
+            ]
+        );
+    }
+}
@@ -2,7 +2,14 @@ use std::collections::BTreeMap;
 
 use serde_json::Value;
 
-use crate::common::data::{MockMatcherFunction, RequestRequirements};
+#[cfg(feature = "xml")]
+use crate::common::data::XPathRequirement;
+use crate::common::data::{
+    BodyLength, CustomMatcher, HeaderValues, HmacSignature, JsonBodyIgnoringPaths,
+    JsonPathRequirement, JwtClaim, MockMatcherFunction, MultipartPart, NdjsonLine,
+    QueryParamValues, QueryString, RequestRequirements,
+};
+#[cfg(feature = "regex")]
 use crate::Regex;
 
 pub(crate) trait ValueRefSource<T> {
@@ -52,6 +59,143 @@ impl ValueRefSource<String> for StringBodyContainsSource {
     }
 }
 
+// ************************************************************************************************
+// StringBodyNotContainsSource
+// ************************************************************************************************
+pub(crate) struct StringBodyNotContainsSource {}
+
+impl StringBodyNotContainsSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for StringBodyNotContainsSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.body_not_contains.as_ref().map(|v| v.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// JSONBodySchemaSource
+// ************************************************************************************************
+#[cfg(feature = "json-schema")]
+pub(crate) struct JSONBodySchemaSource {}
+
+#[cfg(feature = "json-schema")]
+impl JSONBodySchemaSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl ValueRefSource<Value> for JSONBodySchemaSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Value>> {
+        mock.json_body_schema.as_ref().map(|s| vec![s])
+    }
+}
+
+// ************************************************************************************************
+// JSONBodyIgnoringPathsSource
+// ************************************************************************************************
+pub(crate) struct JSONBodyIgnoringPathsSource {}
+
+impl JSONBodyIgnoringPathsSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<JsonBodyIgnoringPaths> for JSONBodyIgnoringPathsSource {
+    fn parse_from_mock<'a>(
+        &self,
+        mock: &'a RequestRequirements,
+    ) -> Option<Vec<&'a JsonBodyIgnoringPaths>> {
+        mock.json_body_ignoring_paths.as_ref().map(|v| vec![v])
+    }
+}
+
+// ************************************************************************************************
+// JSONBodyPathSource
+// ************************************************************************************************
+pub(crate) struct JSONBodyPathSource {}
+
+impl JSONBodyPathSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<JsonPathRequirement> for JSONBodyPathSource {
+    fn parse_from_mock<'a>(
+        &self,
+        mock: &'a RequestRequirements,
+    ) -> Option<Vec<&'a JsonPathRequirement>> {
+        mock.json_body_paths.as_ref().map(|v| v.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// MultipartPartSource
+// ************************************************************************************************
+pub(crate) struct MultipartPartSource {}
+
+impl MultipartPartSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<MultipartPart> for MultipartPartSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a MultipartPart>> {
+        mock.multipart_parts.as_ref().map(|v| v.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// XPathSource
+// ************************************************************************************************
+#[cfg(feature = "xml")]
+pub(crate) struct XPathSource {}
+
+#[cfg(feature = "xml")]
+impl XPathSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "xml")]
+impl ValueRefSource<XPathRequirement> for XPathSource {
+    fn parse_from_mock<'a>(
+        &self,
+        mock: &'a RequestRequirements,
+    ) -> Option<Vec<&'a XPathRequirement>> {
+        mock.body_xpaths.as_ref().map(|v| v.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// XMLBodySource
+// ************************************************************************************************
+#[cfg(feature = "xml")]
+pub(crate) struct XMLBodySource {}
+
+#[cfg(feature = "xml")]
+impl XMLBodySource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "xml")]
+impl ValueRefSource<String> for XMLBodySource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.xml_body.as_ref().map(|b| vec![b])
+    }
+}
+
 // ************************************************************************************************
 // BodyRegexSource
 // ************************************************************************************************
@@ -70,112 +214,473 @@ impl ValueRefSource<Value> for JSONBodySource {
 }
 
 // ************************************************************************************************
-// PartialJSONBodySource
+// PartialJSONBodySource
+// ************************************************************************************************
+pub(crate) struct PartialJSONBodySource {}
+
+impl PartialJSONBodySource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<Value> for PartialJSONBodySource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Value>> {
+        mock.json_body_includes
+            .as_ref()
+            .map(|b| b.into_iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// StrictJSONBodySource
+// ************************************************************************************************
+pub(crate) struct StrictJSONBodySource {}
+
+impl StrictJSONBodySource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<Value> for StrictJSONBodySource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Value>> {
+        mock.json_body_strict.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// BodyRegexSource
+// ************************************************************************************************
+#[cfg(feature = "regex")]
+pub(crate) struct BodyRegexSource {}
+
+#[cfg(feature = "regex")]
+impl BodyRegexSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "regex")]
+impl ValueRefSource<Regex> for BodyRegexSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Regex>> {
+        mock.body_matches
+            .as_ref()
+            .map(|b| b.iter().map(|p| &p.regex).collect())
+    }
+}
+
+// ************************************************************************************************
+// BodyNotRegexSource
+// ************************************************************************************************
+#[cfg(feature = "regex")]
+pub(crate) struct BodyNotRegexSource {}
+
+#[cfg(feature = "regex")]
+impl BodyNotRegexSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "regex")]
+impl ValueRefSource<Regex> for BodyNotRegexSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Regex>> {
+        mock.body_not_matches
+            .as_ref()
+            .map(|b| b.iter().map(|p| &p.regex).collect())
+    }
+}
+
+// ************************************************************************************************
+// MethodSource
+// ************************************************************************************************
+pub(crate) struct MethodSource {}
+
+impl MethodSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for MethodSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.method.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// BasicAuthSource
+// ************************************************************************************************
+pub(crate) struct BasicAuthSource {}
+
+impl BasicAuthSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for BasicAuthSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.basic_auth.as_ref().map(|c| vec![c])
+    }
+}
+
+// ************************************************************************************************
+// BinaryBodySource
+// ************************************************************************************************
+pub(crate) struct BinaryBodySource {}
+
+impl BinaryBodySource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for BinaryBodySource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.body_base64.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// ContentTypeSource
+// ************************************************************************************************
+pub(crate) struct ContentTypeSource {}
+
+impl ContentTypeSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for ContentTypeSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.content_type.as_ref().map(|t| vec![t])
+    }
+}
+
+// ************************************************************************************************
+// QueryObjectSource
+// ************************************************************************************************
+pub(crate) struct QueryObjectSource {}
+
+impl QueryObjectSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<Value> for QueryObjectSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Value>> {
+        mock.query_objects.as_ref().map(|v| v.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// AcceptSource
+// ************************************************************************************************
+pub(crate) struct AcceptSource {}
+
+impl AcceptSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for AcceptSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.accepts.as_ref().map(|v| v.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// BearerTokenSource
+// ************************************************************************************************
+pub(crate) struct BearerTokenSource {}
+
+impl BearerTokenSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for BearerTokenSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.bearer_token.as_ref().map(|t| vec![t])
+    }
+}
+
+// ************************************************************************************************
+// JwtClaimSource
+// ************************************************************************************************
+pub(crate) struct JwtClaimSource {}
+
+impl JwtClaimSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<JwtClaim> for JwtClaimSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a JwtClaim>> {
+        mock.jwt_claims.as_ref().map(|c| c.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// MethodsSource
+// ************************************************************************************************
+pub(crate) struct MethodsSource {}
+
+impl MethodsSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for MethodsSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.methods.as_ref().map(|m| m.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// HttpVersionSource
+// ************************************************************************************************
+pub(crate) struct HttpVersionSource {}
+
+impl HttpVersionSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for HttpVersionSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.http_version.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// HostSource
+// ************************************************************************************************
+pub(crate) struct HostSource {}
+
+impl HostSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for HostSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.host.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// ListenerSource
+// ************************************************************************************************
+pub(crate) struct ListenerSource {}
+
+impl ListenerSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for ListenerSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.listener.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// RemoteAddrSource
+// ************************************************************************************************
+pub(crate) struct RemoteAddrSource {}
+
+impl RemoteAddrSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for RemoteAddrSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.remote_addr.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// ClientCertCommonNameSource
+// ************************************************************************************************
+pub(crate) struct ClientCertCommonNameSource {}
+
+impl ClientCertCommonNameSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for ClientCertCommonNameSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.client_cert_cn.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// StringPathSource
+// ************************************************************************************************
+pub(crate) struct StringPathSource {}
+
+impl StringPathSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for StringPathSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.path.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// StringPathContainsSource
+// ************************************************************************************************
+pub(crate) struct PathContainsSubstringSource {}
+
+impl PathContainsSubstringSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for PathContainsSubstringSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.path_contains
+            .as_ref()
+            .map(|b| b.into_iter().map(|v| v).collect())
+    }
+}
+
+// ************************************************************************************************
+// PathNotContainsSubstringSource
 // ************************************************************************************************
-pub(crate) struct PartialJSONBodySource {}
+pub(crate) struct PathNotContainsSubstringSource {}
 
-impl PartialJSONBodySource {
+impl PathNotContainsSubstringSource {
     pub fn new() -> Self {
         Self {}
     }
 }
 
-impl ValueRefSource<Value> for PartialJSONBodySource {
-    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Value>> {
-        mock.json_body_includes
-            .as_ref()
-            .map(|b| b.into_iter().collect())
+impl ValueRefSource<String> for PathNotContainsSubstringSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.path_not_contains.as_ref().map(|v| v.iter().collect())
     }
 }
 
 // ************************************************************************************************
-// BodyRegexSource
+// PathPrefixSource
 // ************************************************************************************************
-pub(crate) struct BodyRegexSource {}
+pub(crate) struct PathPrefixSource {}
 
-impl BodyRegexSource {
+impl PathPrefixSource {
     pub fn new() -> Self {
         Self {}
     }
 }
 
-impl ValueRefSource<Regex> for BodyRegexSource {
-    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Regex>> {
-        mock.body_matches
-            .as_ref()
-            .map(|b| b.iter().map(|p| &p.regex).collect())
+impl ValueRefSource<String> for PathPrefixSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.path_prefix.as_ref().map(|p| p.iter().collect())
     }
 }
 
 // ************************************************************************************************
-// MethodSource
+// PathSuffixSource
 // ************************************************************************************************
-pub(crate) struct MethodSource {}
+pub(crate) struct PathSuffixSource {}
 
-impl MethodSource {
+impl PathSuffixSource {
     pub fn new() -> Self {
         Self {}
     }
 }
 
-impl ValueRefSource<String> for MethodSource {
+impl ValueRefSource<String> for PathSuffixSource {
     fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
-        mock.method.as_ref().map(|b| vec![b])
+        mock.path_suffix.as_ref().map(|s| s.iter().collect())
     }
 }
 
 // ************************************************************************************************
-// StringPathSource
+// PathRegexSource
 // ************************************************************************************************
-pub(crate) struct StringPathSource {}
+#[cfg(feature = "regex")]
+pub(crate) struct PathRegexSource {}
 
-impl StringPathSource {
+#[cfg(feature = "regex")]
+impl PathRegexSource {
     pub fn new() -> Self {
         Self {}
     }
 }
 
-impl ValueRefSource<String> for StringPathSource {
-    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
-        mock.path.as_ref().map(|b| vec![b])
+#[cfg(feature = "regex")]
+impl ValueRefSource<Regex> for PathRegexSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Regex>> {
+        mock.path_matches
+            .as_ref()
+            .map(|b| b.into_iter().map(|v| &v.regex).collect())
     }
 }
 
 // ************************************************************************************************
-// StringPathContainsSource
+// PathNotRegexSource
 // ************************************************************************************************
-pub(crate) struct PathContainsSubstringSource {}
+#[cfg(feature = "regex")]
+pub(crate) struct PathNotRegexSource {}
 
-impl PathContainsSubstringSource {
+#[cfg(feature = "regex")]
+impl PathNotRegexSource {
     pub fn new() -> Self {
         Self {}
     }
 }
 
-impl ValueRefSource<String> for PathContainsSubstringSource {
-    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
-        mock.path_contains
+#[cfg(feature = "regex")]
+impl ValueRefSource<Regex> for PathNotRegexSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Regex>> {
+        mock.path_not_matches
             .as_ref()
-            .map(|b| b.into_iter().map(|v| v).collect())
+            .map(|b| b.iter().map(|v| &v.regex).collect())
     }
 }
 
 // ************************************************************************************************
-// PathRegexSource
+// RemoteAddrRegexSource
 // ************************************************************************************************
-pub(crate) struct PathRegexSource {}
+#[cfg(feature = "regex")]
+pub(crate) struct RemoteAddrRegexSource {}
 
-impl PathRegexSource {
+#[cfg(feature = "regex")]
+impl RemoteAddrRegexSource {
     pub fn new() -> Self {
         Self {}
     }
 }
 
-impl ValueRefSource<Regex> for PathRegexSource {
+#[cfg(feature = "regex")]
+impl ValueRefSource<Regex> for RemoteAddrRegexSource {
     fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Regex>> {
-        mock.path_matches
+        mock.remote_addr_matches
             .as_ref()
-            .map(|b| b.into_iter().map(|v| &v.regex).collect())
+            .map(|b| b.iter().map(|v| &v.regex).collect())
     }
 }
 
@@ -245,6 +750,31 @@ impl MultiValueSource<String, String> for HeaderSource {
     }
 }
 
+// ************************************************************************************************
+// HeaderRegexSource
+// ************************************************************************************************
+#[cfg(feature = "regex")]
+pub(crate) struct HeaderRegexSource {}
+
+#[cfg(feature = "regex")]
+impl HeaderRegexSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "regex")]
+impl MultiValueSource<String, Regex> for HeaderRegexSource {
+    fn parse_from_mock<'a>(
+        &self,
+        mock: &'a RequestRequirements,
+    ) -> Option<Vec<(&'a String, Option<&'a Regex>)>> {
+        mock.header_matches
+            .as_ref()
+            .map(|v| v.into_iter().map(|(k, v)| (k, Some(&v.regex))).collect())
+    }
+}
+
 // ************************************************************************************************
 // ContainsCookieSource
 // ************************************************************************************************
@@ -289,6 +819,31 @@ impl MultiValueSource<String, String> for QueryParameterSource {
     }
 }
 
+// ************************************************************************************************
+// QueryParameterRegexSource
+// ************************************************************************************************
+#[cfg(feature = "regex")]
+pub(crate) struct QueryParameterRegexSource {}
+
+#[cfg(feature = "regex")]
+impl QueryParameterRegexSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "regex")]
+impl MultiValueSource<String, Regex> for QueryParameterRegexSource {
+    fn parse_from_mock<'a>(
+        &self,
+        mock: &'a RequestRequirements,
+    ) -> Option<Vec<(&'a String, Option<&'a Regex>)>> {
+        mock.query_param_matches
+            .as_ref()
+            .map(|v| v.into_iter().map(|(k, v)| (k, Some(&v.regex))).collect())
+    }
+}
+
 // ************************************************************************************************
 // ContainsQueryParameterSource
 // ************************************************************************************************
@@ -311,6 +866,130 @@ impl MultiValueSource<String, String> for ContainsQueryParameterSource {
     }
 }
 
+// ************************************************************************************************
+// MissingHeaderSource
+// ************************************************************************************************
+pub(crate) struct MissingHeaderSource {}
+
+impl MissingHeaderSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for MissingHeaderSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.header_missing.as_ref().map(|v| v.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// MissingQueryParameterSource
+// ************************************************************************************************
+pub(crate) struct MissingQueryParameterSource {}
+
+impl MissingQueryParameterSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for MissingQueryParameterSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.query_param_missing
+            .as_ref()
+            .map(|v| v.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// NdjsonLineSource
+// ************************************************************************************************
+pub(crate) struct NdjsonLineSource {}
+
+impl NdjsonLineSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<NdjsonLine> for NdjsonLineSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a NdjsonLine>> {
+        mock.ndjson_lines.as_ref().map(|l| l.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// NdjsonLineCountSource
+// ************************************************************************************************
+pub(crate) struct NdjsonLineCountSource {}
+
+impl NdjsonLineCountSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<usize> for NdjsonLineCountSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a usize>> {
+        mock.ndjson_line_count.as_ref().map(|c| vec![c])
+    }
+}
+
+// ************************************************************************************************
+// BodyLengthSource
+// ************************************************************************************************
+pub(crate) struct BodyLengthSource {}
+
+impl BodyLengthSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<BodyLength> for BodyLengthSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a BodyLength>> {
+        mock.body_len.as_ref().map(|l| vec![l])
+    }
+}
+
+// ************************************************************************************************
+// HeaderValuesSource
+// ************************************************************************************************
+pub(crate) struct HeaderValuesSource {}
+
+impl HeaderValuesSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<HeaderValues> for HeaderValuesSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a HeaderValues>> {
+        mock.header_values.as_ref().map(|v| v.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// QueryParameterValuesSource
+// ************************************************************************************************
+pub(crate) struct QueryParameterValuesSource {}
+
+impl QueryParameterValuesSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<QueryParamValues> for QueryParameterValuesSource {
+    fn parse_from_mock<'a>(
+        &self,
+        mock: &'a RequestRequirements,
+    ) -> Option<Vec<&'a QueryParamValues>> {
+        mock.query_param_values.as_ref().map(|v| v.iter().collect())
+    }
+}
+
 // ************************************************************************************************
 // QueryParameterSource
 // ************************************************************************************************
@@ -376,3 +1055,142 @@ impl ValueRefSource<MockMatcherFunction> for FunctionSource {
             .map(|b| b.iter().map(|f| f).collect())
     }
 }
+
+// ************************************************************************************************
+// ChunkedBodySource
+// ************************************************************************************************
+pub(crate) struct ChunkedBodySource {}
+
+impl ChunkedBodySource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<bool> for ChunkedBodySource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a bool>> {
+        mock.chunked_body.then(|| vec![&mock.chunked_body])
+    }
+}
+
+// ************************************************************************************************
+// HmacSignatureSource
+// ************************************************************************************************
+pub(crate) struct HmacSignatureSource {}
+
+impl HmacSignatureSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<HmacSignature> for HmacSignatureSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a HmacSignature>> {
+        mock.hmac_signatures.as_ref().map(|s| s.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// CustomMatcherSource
+// ************************************************************************************************
+pub(crate) struct CustomMatcherSource {}
+
+impl CustomMatcherSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<CustomMatcher> for CustomMatcherSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a CustomMatcher>> {
+        mock.custom_matchers.as_ref().map(|m| m.iter().collect())
+    }
+}
+
+// ************************************************************************************************
+// GrpcMessageSource
+// ************************************************************************************************
+pub(crate) struct GrpcMessageSource {}
+
+impl GrpcMessageSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for GrpcMessageSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.grpc_message.as_ref().map(|m| vec![m])
+    }
+}
+
+// ************************************************************************************************
+// QueryStringSource
+// ************************************************************************************************
+pub(crate) struct QueryStringSource {}
+
+impl QueryStringSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<QueryString> for QueryStringSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a QueryString>> {
+        mock.query_string.as_ref().map(|q| vec![q])
+    }
+}
+
+// ************************************************************************************************
+// ProtobufBodySource
+// ************************************************************************************************
+pub(crate) struct ProtobufBodySource {}
+
+impl ProtobufBodySource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<String> for ProtobufBodySource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.protobuf_body.as_ref().map(|b| vec![b])
+    }
+}
+
+// ************************************************************************************************
+// GraphQLOperationSource
+// ************************************************************************************************
+#[cfg(feature = "graphql")]
+pub(crate) struct GraphQLOperationSource {}
+
+#[cfg(feature = "graphql")]
+impl GraphQLOperationSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "graphql")]
+impl ValueRefSource<String> for GraphQLOperationSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a String>> {
+        mock.graphql_operation.as_ref().map(|o| vec![o])
+    }
+}
+
+// ************************************************************************************************
+// GraphQLVariablesSource
+// ************************************************************************************************
+pub(crate) struct GraphQLVariablesSource {}
+
+impl GraphQLVariablesSource {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueRefSource<Value> for GraphQLVariablesSource {
+    fn parse_from_mock<'a>(&self, mock: &'a RequestRequirements) -> Option<Vec<&'a Value>> {
+        mock.graphql_variables.as_ref().map(|v| vec![v])
+    }
+}
@@ -1,21 +1,19 @@
-use std::cell::RefCell;
-use std::collections::BTreeMap;
-
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 use crate::common::data::HttpMockRequest;
+#[cfg(feature = "graphql")]
+use crate::common::graphql;
+use crate::common::multipart::MultipartBody;
+use crate::common::{grpc, multipart};
 use crate::server::matchers;
+use crate::server::matchers::MatchingRequest;
 
 pub(crate) trait ValueTarget<T> {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<T>;
-}
-
-pub(crate) trait ValueRefTarget<T> {
-    fn parse_from_request<'a>(&self, req: &'a HttpMockRequest) -> Option<&'a T>;
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a T>;
 }
 
 pub(crate) trait MultiValueTarget<T, U> {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<Vec<(T, Option<U>)>>;
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a Vec<(T, Option<U>)>>;
 }
 
 // *************************************************************************************
@@ -30,10 +28,40 @@ impl StringBodyTarget {
 }
 
 impl ValueTarget<String> for StringBodyTarget {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<String> {
-        req.body
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        let parsed = req.parsed();
+        parsed
+            .body
+            .get_or_init(|| {
+                parsed
+                    .req
+                    .body
+                    .as_ref()
+                    .map(|b| String::from_utf8_lossy(b).to_string())
+            })
+            .as_ref()
+    }
+}
+
+// *************************************************************************************
+// BinaryBodyTarget
+// *************************************************************************************
+pub(crate) struct BinaryBodyTarget {}
+
+impl BinaryBodyTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for BinaryBodyTarget {
+    /// Returns the base64 encoded bytes of the body.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        let parsed = req.parsed();
+        parsed
+            .body_base64
+            .get_or_init(|| parsed.req.body.as_ref().map(base64::encode))
             .as_ref()
-            .map(|b| String::from_utf8_lossy(b).to_string()) // FIXME: Avoid copying here. Create a "ValueRefTarget".
     }
 }
 
@@ -49,19 +77,58 @@ impl JSONBodyTarget {
 }
 
 impl ValueTarget<Value> for JSONBodyTarget {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<Value> {
-        let body = req.body.as_ref();
-        if body.is_none() {
-            return None;
-        }
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a Value> {
+        let parsed = req.parsed();
+        parsed
+            .json_body
+            .get_or_init(|| {
+                let body = parsed.req.body.as_ref()?;
+                match serde_json::from_slice(body) {
+                    Err(e) => {
+                        log::trace!("Cannot parse json value: {}", e);
+                        None
+                    }
+                    Ok(v) => Some(v),
+                }
+            })
+            .as_ref()
+    }
+}
 
-        match serde_json::from_slice(body.unwrap()) {
-            Err(e) => {
-                log::trace!("Cannot parse json value: {}", e);
-                None
-            }
-            Ok(v) => Some(v),
-        }
+// *************************************************************************************
+// NdjsonBodyTarget
+// *************************************************************************************
+pub(crate) struct NdjsonBodyTarget {}
+
+impl NdjsonBodyTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<Value> for NdjsonBodyTarget {
+    /// Returns the lines of an NDJSON (JSON Lines) body as a JSON array. Blank lines (e.g. after
+    /// the final newline) are skipped. Bodies with a line that is not valid JSON have no value.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a Value> {
+        let parsed = req.parsed();
+        parsed
+            .ndjson_body
+            .get_or_init(|| {
+                let body = parsed.req.body.as_ref()?;
+                let lines = body
+                    .split(|b| *b == b'\n')
+                    .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+                    .map(serde_json::from_slice)
+                    .collect::<Result<Vec<Value>, _>>();
+                match lines {
+                    Err(e) => {
+                        log::trace!("Cannot parse NDJSON line: {}", e);
+                        None
+                    }
+                    Ok(lines) => Some(Value::Array(lines)),
+                }
+            })
+            .as_ref()
     }
 }
 
@@ -80,19 +147,18 @@ impl CookieTarget {
 
 #[cfg(feature = "cookies")]
 impl MultiValueTarget<String, String> for CookieTarget {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<Vec<(String, Option<String>)>> {
-        let req_cookies = match matchers::parse_cookies(req) {
-            Ok(v) => v,
-            Err(err) => {
-                log::info!(
-                "Cannot parse cookies. Cookie matching will not work for this request. Error: {}",
-                err
-            );
-                return None;
-            }
-        };
-
-        Some(req_cookies.into_iter().map(|(k, v)| (k, Some(v))).collect())
+    fn parse_from_request<'a>(
+        &self,
+        req: &'a MatchingRequest,
+    ) -> Option<&'a Vec<(String, Option<String>)>> {
+        let parsed = req.parsed();
+        parsed
+            .cookies
+            .get_or_init(|| {
+                let req_cookies = matchers::parse_cookies(parsed.req);
+                Some(req_cookies.into_iter().map(|(k, v)| (k, Some(v))).collect())
+            })
+            .as_ref()
     }
 }
 
@@ -108,13 +174,22 @@ impl HeaderTarget {
 }
 
 impl MultiValueTarget<String, String> for HeaderTarget {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<Vec<(String, Option<String>)>> {
-        req.headers.as_ref().map(|headers| {
-            headers
-                .into_iter()
-                .map(|(k, v)| (k.to_string(), Some(v.to_string())))
-                .collect()
-        })
+    fn parse_from_request<'a>(
+        &self,
+        req: &'a MatchingRequest,
+    ) -> Option<&'a Vec<(String, Option<String>)>> {
+        let parsed = req.parsed();
+        parsed
+            .headers
+            .get_or_init(|| {
+                parsed.req.headers.as_ref().map(|headers| {
+                    headers
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), Some(v.to_string())))
+                        .collect()
+                })
+            })
+            .as_ref()
     }
 }
 
@@ -130,13 +205,130 @@ impl QueryParameterTarget {
 }
 
 impl MultiValueTarget<String, String> for QueryParameterTarget {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<Vec<(String, Option<String>)>> {
-        req.query_params.as_ref().map(|headers| {
-            headers
-                .into_iter()
-                .map(|(k, v)| (k.to_string(), Some(v.to_string())))
-                .collect()
-        })
+    fn parse_from_request<'a>(
+        &self,
+        req: &'a MatchingRequest,
+    ) -> Option<&'a Vec<(String, Option<String>)>> {
+        let parsed = req.parsed();
+        if req.raw_query() {
+            return parsed
+                .raw_query_params
+                .get_or_init(|| {
+                    let query = parsed.req.raw_query.as_ref()?;
+                    Some(
+                        query
+                            .split('&')
+                            .filter(|pair| !pair.is_empty())
+                            .map(|pair| match pair.split_once('=') {
+                                Some((name, value)) => (name.to_string(), Some(value.to_string())),
+                                None => (pair.to_string(), Some(String::new())),
+                            })
+                            .collect(),
+                    )
+                })
+                .as_ref();
+        }
+        parsed
+            .query_params
+            .get_or_init(|| {
+                parsed.req.query_params.as_ref().map(|params| {
+                    params
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), Some(v.to_string())))
+                        .collect()
+                })
+            })
+            .as_ref()
+    }
+}
+
+// *************************************************************************************
+// QueryObjectTarget
+// *************************************************************************************
+pub(crate) struct QueryObjectTarget {}
+
+impl QueryObjectTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<Value> for QueryObjectTarget {
+    /// Returns the query parameters as a JSON object, with bracketed parameter names
+    /// (e.g. `filter[status]` or `tags[]`) turned into nested objects and arrays.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a Value> {
+        let parsed = req.parsed();
+        Some(parsed.query_object.get_or_init(|| {
+            let mut object = Value::Object(Map::new());
+            for (name, value) in parsed.req.query_params.iter().flatten() {
+                let segments = query_param_name_segments(name);
+                insert_query_value(&mut object, &segments, value);
+            }
+            object
+        }))
+    }
+}
+
+/// Splits a query parameter name like `filter[tags][]` into its segments (`filter`, `tags`
+/// and an empty segment that stands for an array element). Names that are not properly
+/// bracketed are returned as a single segment.
+fn query_param_name_segments(name: &str) -> Vec<&str> {
+    let (root, mut rest) = match name.find('[') {
+        Some(idx) if idx > 0 => name.split_at(idx),
+        _ => return vec![name],
+    };
+    let mut segments = vec![root];
+    while !rest.is_empty() {
+        match rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            Some((segment, remainder)) => {
+                segments.push(segment);
+                rest = remainder;
+            }
+            None => return vec![name],
+        }
+    }
+    segments
+}
+
+/// Inserts a query parameter value into a nested JSON value at the path given by the
+/// segments of the parameter name. Later values replace earlier ones, except for array
+/// elements (empty segments), which are appended.
+fn insert_query_value(target: &mut Value, segments: &[&str], value: &str) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            *target = Value::String(value.to_string());
+            return;
+        }
+    };
+
+    if segment.is_empty() {
+        if !target.is_array() {
+            *target = Value::Array(Vec::new());
+        }
+        let values = target.as_array_mut().unwrap();
+        // Objects in arrays (e.g. `items[][id]=1&items[][name]=a`) are filled until a field
+        // repeats, like Rails does it.
+        let reuse_last = match (rest.first(), values.last()) {
+            (Some(field), Some(Value::Object(last))) => {
+                !field.is_empty() && !last.contains_key(*field)
+            }
+            _ => false,
+        };
+        if !reuse_last {
+            values.push(Value::Null);
+        }
+        insert_query_value(values.last_mut().unwrap(), rest, value);
+    } else {
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        let entry = target
+            .as_object_mut()
+            .unwrap()
+            .entry(segment.to_string())
+            .or_insert(Value::Null);
+        insert_query_value(entry, rest, value);
     }
 }
 
@@ -152,8 +344,8 @@ impl PathTarget {
 }
 
 impl ValueTarget<String> for PathTarget {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<String> {
-        Some(req.path.to_string()) // FIXME: Avoid copying here. Create a "ValueRefTarget".
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        Some(req.path())
     }
 }
 
@@ -169,8 +361,102 @@ impl MethodTarget {
 }
 
 impl ValueTarget<String> for MethodTarget {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<String> {
-        Some(req.method.to_string()) // FIXME: Avoid copying here. Create a "ValueRefTarget".
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        Some(&req.parsed().req.method)
+    }
+}
+
+// *************************************************************************************
+// HttpVersionTarget
+// *************************************************************************************
+pub(crate) struct HttpVersionTarget {}
+
+impl HttpVersionTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for HttpVersionTarget {
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        req.parsed().req.version.as_ref()
+    }
+}
+
+// *************************************************************************************
+// HostTarget
+// *************************************************************************************
+pub(crate) struct HostTarget {}
+
+impl HostTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for HostTarget {
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        req.parsed().req.host.as_ref()
+    }
+}
+
+// *************************************************************************************
+// ListenerTarget
+// *************************************************************************************
+pub(crate) struct ListenerTarget {}
+
+impl ListenerTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for ListenerTarget {
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        req.parsed().req.listener.as_ref()
+    }
+}
+
+// *************************************************************************************
+// RemoteAddrTarget
+// *************************************************************************************
+pub(crate) struct RemoteAddrTarget {}
+
+impl RemoteAddrTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for RemoteAddrTarget {
+    /// Returns the address of the client as `ip:port`, e.g. `127.0.0.1:52300` or `[::1]:52300`.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        let parsed = req.parsed();
+        parsed
+            .remote_addr
+            .get_or_init(|| parsed.req.remote_addr.map(|addr| addr.to_string()))
+            .as_ref()
+    }
+}
+
+// *************************************************************************************
+// ClientCertCommonNameTarget
+// *************************************************************************************
+pub(crate) struct ClientCertCommonNameTarget {}
+
+impl ClientCertCommonNameTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for ClientCertCommonNameTarget {
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        req.parsed()
+            .req
+            .client_certificate
+            .as_ref()
+            .and_then(|c| c.common_name.as_ref())
     }
 }
 
@@ -185,9 +471,9 @@ impl FullRequestTarget {
     }
 }
 
-impl ValueRefTarget<HttpMockRequest> for FullRequestTarget {
-    fn parse_from_request<'a>(&self, req: &'a HttpMockRequest) -> Option<&'a HttpMockRequest> {
-        Some(req)
+impl ValueTarget<HttpMockRequest> for FullRequestTarget {
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a HttpMockRequest> {
+        Some(req.request())
     }
 }
 
@@ -203,12 +489,275 @@ impl XWWWFormUrlEncodedBodyTarget {
 }
 
 impl MultiValueTarget<String, String> for XWWWFormUrlEncodedBodyTarget {
-    fn parse_from_request(&self, req: &HttpMockRequest) -> Option<Vec<(String, Option<String>)>> {
-        req.body.as_ref().map(|body| {
-            form_urlencoded::parse(body)
-                .into_owned()
-                .map(|(k, v)| (k, Some(v)))
-                .collect()
-        })
+    fn parse_from_request<'a>(
+        &self,
+        req: &'a MatchingRequest,
+    ) -> Option<&'a Vec<(String, Option<String>)>> {
+        let parsed = req.parsed();
+        parsed
+            .form_body
+            .get_or_init(|| {
+                parsed.req.body.as_ref().map(|body| {
+                    form_urlencoded::parse(body)
+                        .into_owned()
+                        .map(|(k, v)| (k, Some(v)))
+                        .collect()
+                })
+            })
+            .as_ref()
+    }
+}
+
+// *************************************************************************************
+// ContentTypeTarget
+// *************************************************************************************
+pub(crate) struct ContentTypeTarget {}
+
+impl ContentTypeTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for ContentTypeTarget {
+    /// Returns the value of the `Content-Type` header.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        req.parsed()
+            .req
+            .headers
+            .iter()
+            .flatten()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value)
+    }
+}
+
+// *************************************************************************************
+// AcceptTarget
+// *************************************************************************************
+pub(crate) struct AcceptTarget {}
+
+impl AcceptTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for AcceptTarget {
+    /// Returns the values of all `Accept` headers as one comma separated list. A request without
+    /// an `Accept` header accepts any media type, so it is treated like `*/*`.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        let parsed = req.parsed();
+        Some(parsed.accept.get_or_init(|| {
+            let values: Vec<&str> = parsed
+                .req
+                .headers
+                .iter()
+                .flatten()
+                .filter(|(name, _)| name.eq_ignore_ascii_case("accept"))
+                .map(|(_, value)| value.as_str())
+                .collect();
+            if values.is_empty() {
+                "*/*".to_string()
+            } else {
+                values.join(", ")
+            }
+        }))
+    }
+}
+
+// *************************************************************************************
+// BasicAuthTarget
+// *************************************************************************************
+pub(crate) struct BasicAuthTarget {}
+
+impl BasicAuthTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for BasicAuthTarget {
+    /// Returns the decoded credentials (`username:password`) of an `Authorization` header that
+    /// uses the `Basic` scheme.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        let parsed = req.parsed();
+        parsed
+            .basic_auth
+            .get_or_init(|| {
+                let credentials = authorization_credentials(parsed.req, "basic")?;
+                String::from_utf8(base64::decode(credentials).ok()?).ok()
+            })
+            .as_ref()
+    }
+}
+
+// *************************************************************************************
+// BearerTokenTarget
+// *************************************************************************************
+pub(crate) struct BearerTokenTarget {}
+
+impl BearerTokenTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for BearerTokenTarget {
+    /// Returns the token of an `Authorization` header that uses the `Bearer` scheme.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        let parsed = req.parsed();
+        parsed
+            .bearer_token
+            .get_or_init(|| authorization_credentials(parsed.req, "bearer").map(String::from))
+            .as_ref()
+    }
+}
+
+// *************************************************************************************
+// JwtClaimsTarget
+// *************************************************************************************
+pub(crate) struct JwtClaimsTarget {}
+
+impl JwtClaimsTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<Value> for JwtClaimsTarget {
+    /// Returns the claims of the bearer token if it is a JWT. The signature is not verified.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a Value> {
+        let parsed = req.parsed();
+        parsed
+            .jwt_claims
+            .get_or_init(|| {
+                let token = BearerTokenTarget::new().parse_from_request(req)?;
+                let payload = token.split('.').nth(1)?.trim_end_matches('=');
+                let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD).ok()?;
+                serde_json::from_slice::<Value>(&payload)
+                    .ok()
+                    .filter(Value::is_object)
+            })
+            .as_ref()
+    }
+}
+
+/// Returns the credentials of the `Authorization` header of a request if it uses the given
+/// authentication scheme (schemes are case-insensitive).
+fn authorization_credentials<'a>(req: &'a HttpMockRequest, scheme: &str) -> Option<&'a str> {
+    let (name, credentials) = req
+        .headers
+        .iter()
+        .flatten()
+        .find(|(name, _)| name.eq_ignore_ascii_case("authorization"))?
+        .1
+        .trim()
+        .split_once(' ')?;
+    match name.eq_ignore_ascii_case(scheme) {
+        true => Some(credentials.trim()),
+        false => None,
+    }
+}
+
+// *************************************************************************************
+// GrpcMessageTarget
+// *************************************************************************************
+pub(crate) struct GrpcMessageTarget {}
+
+impl GrpcMessageTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<String> for GrpcMessageTarget {
+    /// Returns the base64 encoded protobuf message of the first gRPC frame in the body.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        let parsed = req.parsed();
+        parsed
+            .grpc_message
+            .get_or_init(|| {
+                let body = parsed.req.body.as_ref()?;
+                grpc::decode_frame(body).map(base64::encode)
+            })
+            .as_ref()
+    }
+}
+
+// *************************************************************************************
+// GraphQLOperationTarget
+// *************************************************************************************
+#[cfg(feature = "graphql")]
+pub(crate) struct GraphQLOperationTarget {}
+
+#[cfg(feature = "graphql")]
+impl GraphQLOperationTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "graphql")]
+impl ValueTarget<String> for GraphQLOperationTarget {
+    /// Returns the name of the GraphQL operation that the JSON body executes.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a String> {
+        let body = JSONBodyTarget::new().parse_from_request(req);
+        req.parsed()
+            .graphql_operation
+            .get_or_init(|| graphql::operation_name(body?))
+            .as_ref()
+    }
+}
+
+// *************************************************************************************
+// GraphQLVariablesTarget
+// *************************************************************************************
+pub(crate) struct GraphQLVariablesTarget {}
+
+impl GraphQLVariablesTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<Value> for GraphQLVariablesTarget {
+    /// Returns the `variables` field of the JSON body.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a Value> {
+        JSONBodyTarget::new()
+            .parse_from_request(req)?
+            .get("variables")
+    }
+}
+
+// *************************************************************************************
+// MultipartBodyTarget
+// *************************************************************************************
+pub(crate) struct MultipartBodyTarget {}
+
+impl MultipartBodyTarget {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ValueTarget<MultipartBody> for MultipartBodyTarget {
+    /// Returns the parts of a `multipart/form-data` body. The boundary is taken from the
+    /// `Content-Type` header of the request.
+    fn parse_from_request<'a>(&self, req: &'a MatchingRequest) -> Option<&'a MultipartBody> {
+        let parsed = req.parsed();
+        parsed
+            .multipart_body
+            .get_or_init(|| {
+                let (_, content_type) = parsed
+                    .req
+                    .headers
+                    .iter()
+                    .flatten()
+                    .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))?;
+                let boundary = multipart::boundary(content_type)?;
+                multipart::parse(parsed.req.body.as_ref()?, boundary)
+            })
+            .as_ref()
     }
 }
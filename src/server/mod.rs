@@ -1,56 +1,120 @@
 #![allow(clippy::trivial_regex)]
 
 use std::borrow::Borrow;
-use std::collections::BTreeMap;
-use std::net::SocketAddr;
+use std::collections::{BTreeMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering::Relaxed;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 
-use hyper::body::Buf;
+use futures_util::future::{BoxFuture, Either};
+use futures_util::pin_mut;
+use hyper::body::HttpBody;
 use hyper::header::HeaderValue;
 use hyper::http::header::HeaderName;
-use hyper::service::{make_service_fn, service_fn};
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
 use hyper::{
     Body, HeaderMap, Request as HyperRequest, Response as HyperResponse, Result as HyperResult,
-    Server, StatusCode,
+    StatusCode, Version,
 };
-use regex::internal::Input;
-use regex::Regex;
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tokio::sync::{mpsc, watch, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
+#[cfg(feature = "https")]
+use tokio_rustls::TlsAcceptor;
 
 use matchers::generic::SingleValueMatcher;
 use matchers::targets::{JSONBodyTarget, StringBodyTarget};
 
-use crate::common::data::{ActiveMock, HttpMockRequest, Tokenizer};
+use crate::common::data::{
+    ActiveMock, ClientCertificate, HttpMockRequest, MatchStage, RecordedExchange, StaticDirectory,
+    Tokenizer, TrafficEntry,
+};
+#[cfg(feature = "json-schema")]
+use crate::server::matchers::comparators::JSONSchemaMatchComparator;
+#[cfg(feature = "regex")]
+use crate::server::matchers::comparators::StringRegexMatchComparator;
 use crate::server::matchers::comparators::{
-    AnyValueComparator, FunctionMatchesRequestComparator, JSONContainsMatchComparator,
-    JSONExactMatchComparator, StringContainsMatchComparator, StringExactMatchComparator,
-    StringRegexMatchComparator,
+    AcceptComparator, AnyValueComparator, BinaryExactMatchComparator, BodyLengthComparator,
+    ChunkedBodyComparator, CustomMatcherComparator, FunctionMatchesRequestComparator,
+    HeaderValuesComparator, HmacSignatureComparator, JSONContainsMatchComparator,
+    JSONExactMatchComparator, JSONIgnoringPathsComparator, JSONNoExtraFieldsComparator,
+    JSONPathMatchComparator, JwtClaimComparator, MediaTypeComparator, MultipartPartComparator,
+    NdjsonLineComparator, NdjsonLineCountComparator, NegatedComparator, ProtobufMessageComparator,
+    QueryParamValuesComparator, QueryStringComparator, StringContainsMatchComparator,
+    StringExactMatchComparator, StringPrefixMatchComparator, StringSuffixMatchComparator,
+};
+#[cfg(feature = "xml")]
+use crate::server::matchers::comparators::{XMLEqualComparator, XPathMatchComparator};
+use crate::server::matchers::generic::{
+    FunctionValueMatcher, MissingValueMatcher, MultiValueMatcher, OneOfValueMatcher,
+};
+#[cfg(feature = "graphql")]
+use crate::server::matchers::sources::GraphQLOperationSource;
+#[cfg(feature = "json-schema")]
+use crate::server::matchers::sources::JSONBodySchemaSource;
+use crate::server::matchers::sources::{
+    AcceptSource, BasicAuthSource, BearerTokenSource, BinaryBodySource, BodyLengthSource,
+    ChunkedBodySource, ClientCertCommonNameSource, ContainsCookieSource, ContainsHeaderSource,
+    ContainsQueryParameterSource, ContainsXWWWFormUrlencodedKeySource, ContentTypeSource,
+    CookieSource, CustomMatcherSource, FunctionSource, GraphQLVariablesSource, GrpcMessageSource,
+    HeaderSource, HeaderValuesSource, HmacSignatureSource, HostSource, HttpVersionSource,
+    JSONBodyIgnoringPathsSource, JSONBodyPathSource, JSONBodySource, JwtClaimSource,
+    ListenerSource, MethodSource, MethodsSource, MissingHeaderSource, MissingQueryParameterSource,
+    MultipartPartSource, NdjsonLineCountSource, NdjsonLineSource, PartialJSONBodySource,
+    PathContainsSubstringSource, PathNotContainsSubstringSource, PathPrefixSource,
+    PathSuffixSource, ProtobufBodySource, QueryObjectSource, QueryParameterSource,
+    QueryParameterValuesSource, QueryStringSource, RemoteAddrSource, StrictJSONBodySource,
+    StringBodyContainsSource, StringBodyNotContainsSource, StringBodySource, StringPathSource,
+    XWWWFormUrlencodedSource,
 };
-use crate::server::matchers::generic::{FunctionValueMatcher, MultiValueMatcher};
+#[cfg(feature = "regex")]
 use crate::server::matchers::sources::{
-    BodyRegexSource, ContainsCookieSource, ContainsHeaderSource, ContainsQueryParameterSource,
-    ContainsXWWWFormUrlencodedKeySource, CookieSource, FunctionSource, HeaderSource,
-    JSONBodySource, MethodSource, PartialJSONBodySource, PathContainsSubstringSource,
-    PathRegexSource, QueryParameterSource, StringBodyContainsSource, StringBodySource,
-    StringPathSource, XWWWFormUrlencodedSource,
+    BodyNotRegexSource, BodyRegexSource, HeaderRegexSource, PathNotRegexSource, PathRegexSource,
+    QueryParameterRegexSource, RemoteAddrRegexSource,
 };
+#[cfg(feature = "xml")]
+use crate::server::matchers::sources::{XMLBodySource, XPathSource};
 #[cfg(feature = "cookies")]
 use crate::server::matchers::targets::CookieTarget;
+#[cfg(feature = "graphql")]
+use crate::server::matchers::targets::GraphQLOperationTarget;
 use crate::server::matchers::targets::{
-    FullRequestTarget, HeaderTarget, MethodTarget, PathTarget, QueryParameterTarget,
-    XWWWFormUrlEncodedBodyTarget,
+    AcceptTarget, BasicAuthTarget, BearerTokenTarget, BinaryBodyTarget, ClientCertCommonNameTarget,
+    ContentTypeTarget, FullRequestTarget, GraphQLVariablesTarget, GrpcMessageTarget, HeaderTarget,
+    HostTarget, HttpVersionTarget, JwtClaimsTarget, ListenerTarget, MethodTarget,
+    MultipartBodyTarget, NdjsonBodyTarget, PathTarget, QueryObjectTarget, QueryParameterTarget,
+    RemoteAddrTarget, XWWWFormUrlEncodedBodyTarget,
 };
 use crate::server::matchers::Matcher;
+use crate::server::request_log::RequestLogConfig;
+use crate::server::stats::StatsCounters;
 use crate::server::web::routes;
 use futures_util::task::Spawn;
 use std::future::Future;
 use std::iter::Map;
-use std::time::Instant;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
+#[cfg(feature = "decompression")]
+mod decompression;
+pub(crate) mod forward;
 mod matchers;
+pub(crate) mod request_log;
+mod static_files;
+pub(crate) mod stats;
 
+#[cfg(feature = "https")]
+pub(crate) mod tls;
 mod util;
 pub(crate) mod web;
 
@@ -58,8 +122,25 @@ pub(crate) mod web;
 pub struct MockServerState {
     id_counter: AtomicUsize,
     history_limit: usize,
-    pub mocks: Mutex<BTreeMap<usize, ActiveMock>>,
+    pub mocks: RwLock<BTreeMap<usize, ActiveMock>>,
     pub history: Mutex<Vec<Arc<HttpMockRequest>>>,
+    /// The number of connections that were refused and requests that were answered with
+    /// `503 Service Unavailable` because the connection limit was reached.
+    pub overload_rejections: AtomicUsize,
+    /// The requests that were forwarded to the upstream server along with its responses, if
+    /// the mock server records traffic.
+    pub recordings: Mutex<Vec<RecordedExchange>>,
+    /// The requests in the request history along with the responses that were sent.
+    pub traffic: Mutex<Vec<Arc<TrafficEntry>>>,
+    /// Counters for the served requests.
+    pub(crate) stats: StatsCounters,
+    /// The directories whose files are served for requests that do not match any mock.
+    pub static_dirs: RwLock<Vec<StaticDirectory>>,
+    /// The settings of the request log.
+    pub(crate) request_log: RwLock<RequestLogConfig>,
+    /// The idempotency keys of the last mocks that were added through the admin API, along
+    /// with the IDs of the mocks.
+    pub(crate) idempotency_keys: Mutex<VecDeque<(String, usize)>>,
     pub matchers: Vec<Box<dyn Matcher + Sync + Send>>,
 }
 
@@ -70,11 +151,41 @@ impl MockServerState {
 
     pub fn new(history_limit: usize) -> Self {
         MockServerState {
-            mocks: Mutex::new(BTreeMap::new()),
+            mocks: RwLock::new(BTreeMap::new()),
             history_limit,
             history: Mutex::new(Vec::new()),
+            overload_rejections: AtomicUsize::new(0),
+            recordings: Mutex::new(Vec::new()),
+            traffic: Mutex::new(Vec::new()),
+            stats: StatsCounters::new(),
+            static_dirs: RwLock::new(Vec::new()),
+            request_log: RwLock::new(RequestLogConfig::default()),
+            idempotency_keys: Mutex::new(VecDeque::new()),
             id_counter: AtomicUsize::new(0),
+            // Matchers are evaluated in this order and matching stops at the first one that
+            // fails, so they are ordered by their stage from the cheapest to the most expensive.
             matchers: vec![
+                // method exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "method",
+                    comparator: Box::new(StringExactMatchComparator::new(true)),
+                    source: Box::new(MethodSource::new()),
+                    target: Box::new(MethodTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 3,
+                    stage: MatchStage::Method,
+                }),
+                // methods
+                Box::new(OneOfValueMatcher {
+                    entity_name: "method",
+                    comparator: Box::new(StringExactMatchComparator::new(true)),
+                    source: Box::new(MethodsSource::new()),
+                    target: Box::new(MethodTarget::new()),
+                    weight: 3,
+                    stage: MatchStage::Method,
+                }),
                 // path exact
                 Box::new(SingleValueMatcher {
                     entity_name: "path",
@@ -85,6 +196,7 @@ impl MockServerState {
                     with_reason: true,
                     diff_with: None,
                     weight: 10,
+                    stage: MatchStage::Path,
                 }),
                 // path contains
                 Box::new(SingleValueMatcher {
@@ -96,8 +208,34 @@ impl MockServerState {
                     with_reason: true,
                     diff_with: None,
                     weight: 10,
+                    stage: MatchStage::Path,
+                }),
+                // path prefix
+                Box::new(SingleValueMatcher {
+                    entity_name: "path",
+                    comparator: Box::new(StringPrefixMatchComparator::new()),
+                    source: Box::new(PathPrefixSource::new()),
+                    target: Box::new(PathTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 10,
+                    stage: MatchStage::Path,
+                }),
+                // path suffix
+                Box::new(SingleValueMatcher {
+                    entity_name: "path",
+                    comparator: Box::new(StringSuffixMatchComparator::new()),
+                    source: Box::new(PathSuffixSource::new()),
+                    target: Box::new(PathTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 10,
+                    stage: MatchStage::Path,
                 }),
                 // path matches regex
+                #[cfg(feature = "regex")]
                 Box::new(SingleValueMatcher {
                     entity_name: "path",
                     comparator: Box::new(StringRegexMatchComparator::new()),
@@ -107,17 +245,38 @@ impl MockServerState {
                     with_reason: true,
                     diff_with: None,
                     weight: 10,
+                    stage: MatchStage::Path,
                 }),
-                // method exact
+                // path does not contain
                 Box::new(SingleValueMatcher {
-                    entity_name: "method",
-                    comparator: Box::new(StringExactMatchComparator::new(false)),
-                    source: Box::new(MethodSource::new()),
-                    target: Box::new(MethodTarget::new()),
+                    entity_name: "path",
+                    comparator: Box::new(NegatedComparator::new(
+                        "does not contain",
+                        StringContainsMatchComparator::new(true),
+                    )),
+                    source: Box::new(PathNotContainsSubstringSource::new()),
+                    target: Box::new(PathTarget::new()),
                     transformer: None,
                     with_reason: true,
                     diff_with: None,
-                    weight: 3,
+                    weight: 10,
+                    stage: MatchStage::Path,
+                }),
+                // path does not match regex
+                #[cfg(feature = "regex")]
+                Box::new(SingleValueMatcher {
+                    entity_name: "path",
+                    comparator: Box::new(NegatedComparator::new(
+                        "does not match regex",
+                        StringRegexMatchComparator::new(),
+                    )),
+                    source: Box::new(PathNotRegexSource::new()),
+                    target: Box::new(PathTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 10,
+                    stage: MatchStage::Path,
                 }),
                 // Query Param exact
                 Box::new(MultiValueMatcher {
@@ -131,47 +290,154 @@ impl MockServerState {
                     with_reason: true,
                     diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Query,
                 }),
-                // Query Param exists
+                // Query Param regex
+                #[cfg(feature = "regex")]
                 Box::new(MultiValueMatcher {
                     entity_name: "query parameter",
                     key_comparator: Box::new(StringExactMatchComparator::new(true)),
-                    value_comparator: Box::new(AnyValueComparator::new()),
+                    value_comparator: Box::new(StringRegexMatchComparator::new()),
                     key_transformer: None,
                     value_transformer: None,
-                    source: Box::new(ContainsQueryParameterSource::new()),
+                    source: Box::new(QueryParameterRegexSource::new()),
                     target: Box::new(QueryParameterTarget::new()),
                     with_reason: true,
                     diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Query,
                 }),
-                // Cookie exact
-                #[cfg(feature = "cookies")]
+                // Query Param exists
                 Box::new(MultiValueMatcher {
-                    entity_name: "cookie",
+                    entity_name: "query parameter",
                     key_comparator: Box::new(StringExactMatchComparator::new(true)),
-                    value_comparator: Box::new(StringExactMatchComparator::new(true)),
+                    value_comparator: Box::new(AnyValueComparator::new()),
                     key_transformer: None,
                     value_transformer: None,
-                    source: Box::new(CookieSource::new()),
-                    target: Box::new(CookieTarget::new()),
+                    source: Box::new(ContainsQueryParameterSource::new()),
+                    target: Box::new(QueryParameterTarget::new()),
                     with_reason: true,
                     diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Query,
                 }),
-                // Cookie exists
-                #[cfg(feature = "cookies")]
-                Box::new(MultiValueMatcher {
-                    entity_name: "cookie",
+                // Query Param missing
+                Box::new(MissingValueMatcher {
+                    entity_name: "query parameter",
                     key_comparator: Box::new(StringExactMatchComparator::new(true)),
-                    value_comparator: Box::new(AnyValueComparator::new()),
-                    key_transformer: None,
-                    value_transformer: None,
-                    source: Box::new(ContainsCookieSource::new()),
-                    target: Box::new(CookieTarget::new()),
+                    source: Box::new(MissingQueryParameterSource::new()),
+                    target: Box::new(QueryParameterTarget::new()),
+                    weight: 1,
+                    stage: MatchStage::Query,
+                }),
+                // Query string exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "query string",
+                    comparator: Box::new(QueryStringComparator::new()),
+                    source: Box::new(QueryStringSource::new()),
+                    target: Box::new(FullRequestTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Query,
+                }),
+                // Query object
+                Box::new(SingleValueMatcher {
+                    entity_name: "query object",
+                    comparator: Box::new(JSONContainsMatchComparator::new()),
+                    source: Box::new(QueryObjectSource::new()),
+                    target: Box::new(QueryObjectTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: Some(Tokenizer::Line),
+                    weight: 1,
+                    stage: MatchStage::Query,
+                }),
+                // Query Param values
+                Box::new(SingleValueMatcher {
+                    entity_name: "query parameter",
+                    comparator: Box::new(QueryParamValuesComparator::new()),
+                    source: Box::new(QueryParameterValuesSource::new()),
+                    target: Box::new(FullRequestTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Query,
+                }),
+                // HTTP version exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "HTTP version",
+                    comparator: Box::new(StringExactMatchComparator::new(false)),
+                    source: Box::new(HttpVersionSource::new()),
+                    target: Box::new(HttpVersionTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 3,
+                    stage: MatchStage::Headers,
+                }),
+                // host exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "host",
+                    comparator: Box::new(StringExactMatchComparator::new(false)),
+                    source: Box::new(HostSource::new()),
+                    target: Box::new(HostTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 3,
+                    stage: MatchStage::Headers,
+                }),
+                // listener exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "listener",
+                    comparator: Box::new(StringExactMatchComparator::new(true)),
+                    source: Box::new(ListenerSource::new()),
+                    target: Box::new(ListenerTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 3,
+                    stage: MatchStage::Headers,
+                }),
+                // remote address exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "remote address",
+                    comparator: Box::new(StringExactMatchComparator::new(true)),
+                    source: Box::new(RemoteAddrSource::new()),
+                    target: Box::new(RemoteAddrTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 3,
+                    stage: MatchStage::Headers,
+                }),
+                // remote address matches regex
+                #[cfg(feature = "regex")]
+                Box::new(SingleValueMatcher {
+                    entity_name: "remote address",
+                    comparator: Box::new(StringRegexMatchComparator::new()),
+                    source: Box::new(RemoteAddrRegexSource::new()),
+                    target: Box::new(RemoteAddrTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 3,
+                    stage: MatchStage::Headers,
+                }),
+                // client certificate common name exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "client certificate common name",
+                    comparator: Box::new(StringExactMatchComparator::new(true)),
+                    source: Box::new(ClientCertCommonNameSource::new()),
+                    target: Box::new(ClientCertCommonNameTarget::new()),
+                    transformer: None,
                     with_reason: true,
                     diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
                 }),
                 // Header exact
                 Box::new(MultiValueMatcher {
@@ -185,6 +451,22 @@ impl MockServerState {
                     with_reason: true,
                     diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
+                }),
+                // Header regex
+                #[cfg(feature = "regex")]
+                Box::new(MultiValueMatcher {
+                    entity_name: "header",
+                    key_comparator: Box::new(StringExactMatchComparator::new(false)),
+                    value_comparator: Box::new(StringRegexMatchComparator::new()),
+                    key_transformer: None,
+                    value_transformer: None,
+                    source: Box::new(HeaderRegexSource::new()),
+                    target: Box::new(HeaderTarget::new()),
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Headers,
                 }),
                 // Header exists
                 Box::new(MultiValueMatcher {
@@ -198,117 +480,494 @@ impl MockServerState {
                     with_reason: true,
                     diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
                 }),
-                // Box::new(CustomFunctionMatcher::new(1.0)),
-                // string body exact
+                // Header missing
+                Box::new(MissingValueMatcher {
+                    entity_name: "header",
+                    key_comparator: Box::new(StringExactMatchComparator::new(false)),
+                    source: Box::new(MissingHeaderSource::new()),
+                    target: Box::new(HeaderTarget::new()),
+                    weight: 1,
+                    stage: MatchStage::Headers,
+                }),
+                // Header values
                 Box::new(SingleValueMatcher {
-                    entity_name: "body",
-                    comparator: Box::new(StringExactMatchComparator::new(false)),
-                    source: Box::new(StringBodySource::new()),
-                    target: Box::new(StringBodyTarget::new()),
+                    entity_name: "header",
+                    comparator: Box::new(HeaderValuesComparator::new()),
+                    source: Box::new(HeaderValuesSource::new()),
+                    target: Box::new(FullRequestTarget::new()),
                     transformer: None,
                     with_reason: false,
-                    diff_with: Some(Tokenizer::Line),
+                    diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
                 }),
-                // string body contains
+                // Content type
                 Box::new(SingleValueMatcher {
-                    entity_name: "body",
-                    comparator: Box::new(StringContainsMatchComparator::new(true)),
-                    source: Box::new(StringBodyContainsSource::new()),
-                    target: Box::new(StringBodyTarget::new()),
+                    entity_name: "content type",
+                    comparator: Box::new(MediaTypeComparator::new()),
+                    source: Box::new(ContentTypeSource::new()),
+                    target: Box::new(ContentTypeTarget::new()),
                     transformer: None,
-                    with_reason: false,
-                    diff_with: Some(Tokenizer::Line),
+                    with_reason: true,
+                    diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
                 }),
-                // string body regex
+                // Chunked transfer encoding
                 Box::new(SingleValueMatcher {
-                    entity_name: "body",
-                    comparator: Box::new(StringRegexMatchComparator::new()),
-                    source: Box::new(BodyRegexSource::new()),
-                    target: Box::new(StringBodyTarget::new()),
+                    entity_name: "transfer encoding",
+                    comparator: Box::new(ChunkedBodyComparator::new()),
+                    source: Box::new(ChunkedBodySource::new()),
+                    target: Box::new(FullRequestTarget::new()),
                     transformer: None,
                     with_reason: false,
-                    diff_with: Some(Tokenizer::Line),
+                    diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
                 }),
-                // JSON body contains
+                // Accept header
                 Box::new(SingleValueMatcher {
-                    entity_name: "body",
-                    comparator: Box::new(JSONContainsMatchComparator::new()),
-                    source: Box::new(PartialJSONBodySource::new()),
-                    target: Box::new(JSONBodyTarget::new()),
+                    entity_name: "accepted media type",
+                    comparator: Box::new(AcceptComparator::new()),
+                    source: Box::new(AcceptSource::new()),
+                    target: Box::new(AcceptTarget::new()),
                     transformer: None,
-                    with_reason: false,
-                    diff_with: Some(Tokenizer::Line),
+                    with_reason: true,
+                    diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
                 }),
-                // JSON body exact
+                // Basic auth credentials
                 Box::new(SingleValueMatcher {
-                    entity_name: "body",
-                    comparator: Box::new(JSONExactMatchComparator::new()),
-                    source: Box::new(JSONBodySource::new()),
-                    target: Box::new(JSONBodyTarget::new()),
+                    entity_name: "basic authentication",
+                    comparator: Box::new(StringExactMatchComparator::new(true)),
+                    source: Box::new(BasicAuthSource::new()),
+                    target: Box::new(BasicAuthTarget::new()),
                     transformer: None,
                     with_reason: true,
-                    diff_with: Some(Tokenizer::Line),
+                    diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
                 }),
-                // Query Param exact
+                // Bearer token
+                Box::new(SingleValueMatcher {
+                    entity_name: "bearer token",
+                    comparator: Box::new(StringExactMatchComparator::new(true)),
+                    source: Box::new(BearerTokenSource::new()),
+                    target: Box::new(BearerTokenTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Headers,
+                }),
+                // JWT claims
+                Box::new(SingleValueMatcher {
+                    entity_name: "JWT",
+                    comparator: Box::new(JwtClaimComparator::new()),
+                    source: Box::new(JwtClaimSource::new()),
+                    target: Box::new(JwtClaimsTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Headers,
+                }),
+                // Cookie exact
+                #[cfg(feature = "cookies")]
                 Box::new(MultiValueMatcher {
-                    entity_name: "x-www-form-urlencoded body tuple",
+                    entity_name: "cookie",
                     key_comparator: Box::new(StringExactMatchComparator::new(true)),
                     value_comparator: Box::new(StringExactMatchComparator::new(true)),
                     key_transformer: None,
                     value_transformer: None,
-                    source: Box::new(XWWWFormUrlencodedSource::new()),
-                    target: Box::new(XWWWFormUrlEncodedBodyTarget::new()),
+                    source: Box::new(CookieSource::new()),
+                    target: Box::new(CookieTarget::new()),
                     with_reason: true,
                     diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
                 }),
-                // Query Param exists
+                // Cookie exists
+                #[cfg(feature = "cookies")]
                 Box::new(MultiValueMatcher {
-                    entity_name: "x-www-form-urlencoded body tuple",
+                    entity_name: "cookie",
                     key_comparator: Box::new(StringExactMatchComparator::new(true)),
                     value_comparator: Box::new(AnyValueComparator::new()),
                     key_transformer: None,
                     value_transformer: None,
-                    source: Box::new(ContainsXWWWFormUrlencodedKeySource::new()),
-                    target: Box::new(XWWWFormUrlEncodedBodyTarget::new()),
+                    source: Box::new(ContainsCookieSource::new()),
+                    target: Box::new(CookieTarget::new()),
                     with_reason: true,
                     diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Headers,
                 }),
-                // User provided matcher function
-                Box::new(FunctionValueMatcher {
-                    entity_name: "user provided matcher function",
-                    comparator: Box::new(FunctionMatchesRequestComparator::new()),
-                    source: Box::new(FunctionSource::new()),
+                // string body exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(StringExactMatchComparator::new(false)),
+                    source: Box::new(StringBodySource::new()),
+                    target: Box::new(StringBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: Some(Tokenizer::Line),
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // binary body exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(BinaryExactMatchComparator::new()),
+                    source: Box::new(BinaryBodySource::new()),
+                    target: Box::new(BinaryBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // body length
+                Box::new(SingleValueMatcher {
+                    entity_name: "body length",
+                    comparator: Box::new(BodyLengthComparator::new()),
+                    source: Box::new(BodyLengthSource::new()),
                     target: Box::new(FullRequestTarget::new()),
                     transformer: None,
+                    with_reason: false,
+                    diff_with: None,
                     weight: 1,
+                    stage: MatchStage::Body,
                 }),
-            ],
-        }
-    }
-}
-
-impl Default for MockServerState {
-    fn default() -> Self {
-        MockServerState::new(usize::MAX)
+                // HMAC signature
+                Box::new(SingleValueMatcher {
+                    entity_name: "HMAC signature",
+                    comparator: Box::new(HmacSignatureComparator::new()),
+                    source: Box::new(HmacSignatureSource::new()),
+                    target: Box::new(FullRequestTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // string body contains
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(StringContainsMatchComparator::new(true)),
+                    source: Box::new(StringBodyContainsSource::new()),
+                    target: Box::new(StringBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: Some(Tokenizer::Line),
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // string body regex
+                #[cfg(feature = "regex")]
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(StringRegexMatchComparator::new()),
+                    source: Box::new(BodyRegexSource::new()),
+                    target: Box::new(StringBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: Some(Tokenizer::Line),
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // string body does not contain
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(NegatedComparator::new(
+                        "does not contain",
+                        StringContainsMatchComparator::new(true),
+                    )),
+                    source: Box::new(StringBodyNotContainsSource::new()),
+                    target: Box::new(StringBodyTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // string body does not match regex
+                #[cfg(feature = "regex")]
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(NegatedComparator::new(
+                        "does not match regex",
+                        StringRegexMatchComparator::new(),
+                    )),
+                    source: Box::new(BodyNotRegexSource::new()),
+                    target: Box::new(StringBodyTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // JSON body contains
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(JSONContainsMatchComparator::new()),
+                    source: Box::new(PartialJSONBodySource::new()),
+                    target: Box::new(JSONBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: Some(Tokenizer::Line),
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // JSON body without extra fields
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(JSONNoExtraFieldsComparator::new()),
+                    source: Box::new(StrictJSONBodySource::new()),
+                    target: Box::new(JSONBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // JSON body exact
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(JSONExactMatchComparator::new()),
+                    source: Box::new(JSONBodySource::new()),
+                    target: Box::new(JSONBodyTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: Some(Tokenizer::Line),
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // JSON body ignoring paths
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(JSONIgnoringPathsComparator::new()),
+                    source: Box::new(JSONBodyIgnoringPathsSource::new()),
+                    target: Box::new(JSONBodyTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: Some(Tokenizer::Line),
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // JSON body schema
+                #[cfg(feature = "json-schema")]
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(JSONSchemaMatchComparator::new()),
+                    source: Box::new(JSONBodySchemaSource::new()),
+                    target: Box::new(JSONBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // JSON body path
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(JSONPathMatchComparator::new()),
+                    source: Box::new(JSONBodyPathSource::new()),
+                    target: Box::new(JSONBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // NDJSON body lines
+                Box::new(SingleValueMatcher {
+                    entity_name: "NDJSON body",
+                    comparator: Box::new(NdjsonLineComparator::new()),
+                    source: Box::new(NdjsonLineSource::new()),
+                    target: Box::new(NdjsonBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // NDJSON body line count
+                Box::new(SingleValueMatcher {
+                    entity_name: "NDJSON body",
+                    comparator: Box::new(NdjsonLineCountComparator::new()),
+                    source: Box::new(NdjsonLineCountSource::new()),
+                    target: Box::new(NdjsonBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // XML body XPath
+                #[cfg(feature = "xml")]
+                Box::new(SingleValueMatcher {
+                    entity_name: "body",
+                    comparator: Box::new(XPathMatchComparator::new()),
+                    source: Box::new(XPathSource::new()),
+                    target: Box::new(StringBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // XML body exact
+                #[cfg(feature = "xml")]
+                Box::new(SingleValueMatcher {
+                    entity_name: "XML body",
+                    comparator: Box::new(XMLEqualComparator::new()),
+                    source: Box::new(XMLBodySource::new()),
+                    target: Box::new(StringBodyTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: Some(Tokenizer::Line),
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // x-www-form-urlencoded body tuple exact
+                Box::new(MultiValueMatcher {
+                    entity_name: "x-www-form-urlencoded body tuple",
+                    key_comparator: Box::new(StringExactMatchComparator::new(true)),
+                    value_comparator: Box::new(StringExactMatchComparator::new(true)),
+                    key_transformer: None,
+                    value_transformer: None,
+                    source: Box::new(XWWWFormUrlencodedSource::new()),
+                    target: Box::new(XWWWFormUrlEncodedBodyTarget::new()),
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // x-www-form-urlencoded body key exists
+                Box::new(MultiValueMatcher {
+                    entity_name: "x-www-form-urlencoded body tuple",
+                    key_comparator: Box::new(StringExactMatchComparator::new(true)),
+                    value_comparator: Box::new(AnyValueComparator::new()),
+                    key_transformer: None,
+                    value_transformer: None,
+                    source: Box::new(ContainsXWWWFormUrlencodedKeySource::new()),
+                    target: Box::new(XWWWFormUrlEncodedBodyTarget::new()),
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // Multipart body part
+                Box::new(SingleValueMatcher {
+                    entity_name: "multipart body",
+                    comparator: Box::new(MultipartPartComparator::new()),
+                    source: Box::new(MultipartPartSource::new()),
+                    target: Box::new(MultipartBodyTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // gRPC request message
+                Box::new(SingleValueMatcher {
+                    entity_name: "gRPC message",
+                    comparator: Box::new(StringExactMatchComparator::new(true)),
+                    source: Box::new(GrpcMessageSource::new()),
+                    target: Box::new(GrpcMessageTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // protobuf body
+                Box::new(SingleValueMatcher {
+                    entity_name: "protobuf body",
+                    comparator: Box::new(ProtobufMessageComparator::new()),
+                    source: Box::new(ProtobufBodySource::new()),
+                    target: Box::new(BinaryBodyTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // GraphQL operation
+                #[cfg(feature = "graphql")]
+                Box::new(SingleValueMatcher {
+                    entity_name: "GraphQL operation",
+                    comparator: Box::new(StringExactMatchComparator::new(true)),
+                    source: Box::new(GraphQLOperationSource::new()),
+                    target: Box::new(GraphQLOperationTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // GraphQL variables
+                Box::new(SingleValueMatcher {
+                    entity_name: "GraphQL variables",
+                    comparator: Box::new(JSONExactMatchComparator::new()),
+                    source: Box::new(GraphQLVariablesSource::new()),
+                    target: Box::new(GraphQLVariablesTarget::new()),
+                    transformer: None,
+                    with_reason: true,
+                    diff_with: Some(Tokenizer::Line),
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // User provided matcher function
+                Box::new(FunctionValueMatcher {
+                    entity_name: "user provided matcher function",
+                    comparator: Box::new(FunctionMatchesRequestComparator::new()),
+                    source: Box::new(FunctionSource::new()),
+                    target: Box::new(FullRequestTarget::new()),
+                    transformer: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+                // Custom matchers
+                Box::new(SingleValueMatcher {
+                    entity_name: "custom matcher",
+                    comparator: Box::new(CustomMatcherComparator::new()),
+                    source: Box::new(CustomMatcherSource::new()),
+                    target: Box::new(FullRequestTarget::new()),
+                    transformer: None,
+                    with_reason: false,
+                    diff_with: None,
+                    weight: 1,
+                    stage: MatchStage::Body,
+                }),
+            ],
+        }
     }
 }
 
-type GenericError = Box<dyn std::error::Error + Send + Sync>;
+impl Default for MockServerState {
+    fn default() -> Self {
+        MockServerState::new(usize::MAX)
+    }
+}
 
 #[derive(Default, Debug)]
 pub(crate) struct ServerRequestHeader {
     pub method: String,
     pub path: String,
     pub query: String,
+    pub version: String,
+    pub host: Option<String>,
     pub headers: Vec<(String, String)>,
+    pub client_certificate: Option<ClientCertificate>,
+    pub listener: Option<String>,
+    pub remote_addr: Option<SocketAddr>,
+    /// Whether the body was not read, because no mock required it (see
+    /// [RECORDED_BODY_BYTES](constant.RECORDED_BODY_BYTES.html)).
+    pub body_skipped: bool,
 }
 
 impl ServerRequestHeader {
@@ -323,191 +982,1128 @@ impl ServerRequestHeader {
         let query = req.uri().query().unwrap_or("").to_string();
         let headers = headers.unwrap();
 
-        let server_request = ServerRequestHeader::new(method, path, query, headers);
+        let mut server_request = ServerRequestHeader::new(method, path, query, headers);
+        server_request.version = http_version_name(req.version()).to_string();
+        server_request.host = request_host(req);
+        if let Some(conn_info) = req.extensions().get::<ConnectionInfo>() {
+            server_request.client_certificate = conn_info.client_certificate.clone();
+            server_request.listener = conn_info.listener.clone();
+            server_request.remote_addr = conn_info.remote_addr;
+        }
+
+        Ok(server_request)
+    }
+
+    pub fn new(
+        method: String,
+        path: String,
+        query: String,
+        headers: Vec<(String, String)>,
+    ) -> Self {
+        Self {
+            method,
+            path,
+            query,
+            version: http_version_name(Version::HTTP_11).to_string(),
+            host: None,
+            headers,
+            client_certificate: None,
+            listener: None,
+            remote_addr: None,
+            body_skipped: false,
+        }
+    }
+}
+
+/// Returns the host that a request was sent to. Requests to proxies and HTTP/2 requests carry
+/// the host in the request URI, all others in the `Host` header. The trailing dot of fully
+/// qualified host names (e.g. `api.example.com.`) is removed.
+fn request_host(req: &HyperRequest<Body>) -> Option<String> {
+    let host = match req.uri().host() {
+        Some(host) => host.to_string(),
+        None => {
+            let host = req.headers().get(hyper::header::HOST)?.to_str().ok()?;
+            let authority = hyper::http::uri::Authority::from_str(host).ok()?;
+            authority.host().to_string()
+        }
+    };
+    Some(host.strip_suffix('.').map(str::to_string).unwrap_or(host))
+}
+
+/// Returns the name of an HTTP version as it appears in HTTP/1 request lines (e.g. `HTTP/1.1`).
+/// HTTP/2 and HTTP/3 are named without a minor version.
+fn http_version_name(version: Version) -> &'static str {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_2 => "HTTP/2",
+        Version::HTTP_3 => "HTTP/3",
+        _ => "HTTP/1.1",
+    }
+}
+
+#[derive(Default, Debug)]
+pub(crate) struct ServerResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// A body that is streamed to the client instead of `body`, if set.
+    pub body_stream: Option<Body>,
+    /// Headers that are sent after the body.
+    pub trailers: Vec<(String, String)>,
+}
+
+impl ServerResponse {
+    pub fn new(status: u16, headers: Vec<(String, String)>, body: Vec<u8>) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+            body_stream: None,
+            trailers: Vec::new(),
+        }
+    }
+}
+
+/// Extracts all headers from the URI of the given request.
+fn extract_headers(header_map: &HeaderMap) -> Result<Vec<(String, String)>, String> {
+    let mut headers = Vec::new();
+    for (hn, hv) in header_map {
+        let hn = hn.as_str().to_string();
+        let hv = hv.to_str();
+        if let Err(e) = hv {
+            return Err(format!("error parsing headers: {}", e));
+        }
+        headers.push((hn, hv.unwrap().to_string()));
+    }
+    Ok(headers)
+}
+
+async fn access_log_middleware<F, T>(
+    req: HyperRequest<Body>,
+    state: Arc<MockServerState>,
+    print_access_log: bool,
+    next: F,
+) -> HyperResult<HyperResponse<Body>>
+where
+    F: FnOnce(HyperRequest<Body>, Arc<MockServerState>) -> T,
+    T: Future<Output = HyperResult<HyperResponse<Body>>>,
+{
+    let time_request_received = Instant::now();
+
+    let request_method = req.method().to_string();
+    let request_uri = req.uri().to_string();
+    let is_admin_request = is_admin_path(req.uri().path());
+    let request_http_version = format!("{:?}", &req.version());
+
+    let result = next(req, state).await;
+
+    if print_access_log && !is_admin_request {
+        if let Ok(response) = &result {
+            log::info!(
+                "\"{} {} {:?}\" {} {}",
+                request_method,
+                request_uri,
+                request_http_version,
+                response.status().as_u16(),
+                time_request_received.elapsed().as_millis()
+            );
+        }
+    };
+
+    return result;
+}
+
+async fn handle_server_request(
+    req: HyperRequest<Body>,
+    state: Arc<MockServerState>,
+    config: Arc<ServerConfig>,
+) -> HyperResult<HyperResponse<Body>> {
+    #[cfg(feature = "tracing")]
+    {
+        use tracing::Instrument;
+
+        let span = tracing::info_span!(
+            "httpmock.request",
+            method = %req.method(),
+            path = req.uri().path(),
+            mock_id = tracing::field::Empty,
+            status = tracing::field::Empty,
+            duration_ms = tracing::field::Empty,
+        );
+        let started = Instant::now();
+        let response = serve_request(req, state, config)
+            .instrument(span.clone())
+            .await;
+        if let Ok(response) = &response {
+            span.record("status", response.status().as_u16());
+        }
+        span.record("duration_ms", started.elapsed().as_secs_f64() * 1000.0);
+        return response;
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    serve_request(req, state, config).await
+}
+
+async fn serve_request(
+    req: HyperRequest<Body>,
+    state: Arc<MockServerState>,
+    config: Arc<ServerConfig>,
+) -> HyperResult<HyperResponse<Body>> {
+    let version = req.version();
+    let request_header = ServerRequestHeader::from(&req);
+
+    if let Err(e) = request_header {
+        return Ok(error_response(format!("Cannot parse request: {}", e)));
+    }
+    let mut request_header = request_header.unwrap();
+
+    // Admin API requests are sent by httpmock itself and are therefore not limited.
+    let is_admin_request = is_admin_path(&request_header.path) && !config.read_only;
+    let max_body_bytes = match is_admin_request {
+        true => usize::MAX,
+        false => config.max_body_bytes.unwrap_or(DEFAULT_MAX_BODY_BYTES),
+    };
+
+    // The body is only read completely if a mock requires it or if it is forwarded, so that
+    // large uploads are not buffered when mocks only match on the path or the headers.
+    let needs_body = is_admin_request
+        || config.recording_upstream.is_some()
+        || config.fallback_upstream.is_some()
+        || routes::needs_body(state.borrow(), &request_header);
+    let read_limit = match needs_body {
+        true => max_body_bytes,
+        false => max_body_bytes.min(RECORDED_BODY_BYTES),
+    };
+
+    let stats = match is_admin_request {
+        true => None,
+        false => Some(&state.stats),
+    };
+    let mut body = req.into_body();
+    let routing_result = match read_body(&mut body, read_limit, stats).await {
+        #[cfg(feature = "decompression")]
+        Ok(body) if config.decompress_request_bodies && !is_admin_request => {
+            match decompression::decode_body(&mut request_header, body, max_body_bytes) {
+                Some(body) => route_request(state.borrow(), &request_header, body, &config).await,
+                None => routes::payload_too_large(state.borrow(), &request_header, max_body_bytes),
+            }
+        }
+        Ok(body) => route_request(state.borrow(), &request_header, body, &config).await,
+        Err(ReadBodyError::TooLarge) if !needs_body => {
+            request_header.body_skipped = true;
+            discard_body(body);
+            route_request(state.borrow(), &request_header, Vec::new(), &config).await
+        }
+        Err(ReadBodyError::TooLarge) => {
+            routes::payload_too_large(state.borrow(), &request_header, max_body_bytes)
+        }
+        Err(ReadBodyError::Hyper(e)) => {
+            return Ok(error_response(format!("Cannot read request body: {}", e)))
+        }
+    };
+    if let Err(e) = routing_result {
+        return Ok(error_response(format!("Request handler error: {}", e)));
+    }
+
+    let response = map_response(routing_result.unwrap());
+    if let Err(e) = response {
+        return Ok(error_response(format!("Cannot build response: {}", e)));
+    }
+    let mut response = response.unwrap();
+
+    // Tell HTTP/1 clients that the connection will be closed after this response.
+    if config.disable_keep_alive && version < Version::HTTP_2 {
+        response
+            .headers_mut()
+            .insert(hyper::header::CONNECTION, HeaderValue::from_static("close"));
+    }
+
+    Ok(response)
+}
+
+/// Serves requests of a local mock server in-process, without a network connection. Requests
+/// are handled exactly like requests that are received over the network, so mocks are matched,
+/// counted and recorded in the request history as usual.
+///
+/// `MockService` implements [hyper::service::Service](https://docs.rs/hyper/0.14/hyper/service/trait.Service.html),
+/// which is the same trait as `tower::Service`, so it can be used wherever such a service is
+/// accepted. Use [MockService::handle](struct.MockService.html#method.handle) to send a
+/// single request.
+#[derive(Clone)]
+pub struct MockService {
+    state: Arc<MockServerState>,
+    config: Arc<ServerConfig>,
+}
+
+impl MockService {
+    pub(crate) fn new(state: Arc<MockServerState>, config: Arc<ServerConfig>) -> Self {
+        Self { state, config }
+    }
+
+    /// Handles a request and returns the response of the matching mock.
+    pub async fn handle(&self, req: HyperRequest<Body>) -> HyperResponse<Body> {
+        // The request handler reports all errors as error responses.
+        handle_server_request(req, self.state.clone(), self.config.clone())
+            .await
+            .expect("Cannot handle request")
+    }
+}
+
+impl hyper::service::Service<HyperRequest<Body>> for MockService {
+    type Response = HyperResponse<Body>;
+    type Error = hyper::Error;
+    type Future = BoxFuture<'static, HyperResult<HyperResponse<Body>>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<HyperResult<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: HyperRequest<Body>) -> Self::Future {
+        Box::pin(handle_server_request(
+            req,
+            self.state.clone(),
+            self.config.clone(),
+        ))
+    }
+}
+
+/// The reason why a request body could not be read.
+enum ReadBodyError {
+    TooLarge,
+    Hyper(hyper::Error),
+}
+
+/// Reads a request body, but fails as soon as it turns out to be larger than `max_body_bytes`.
+/// Bodies with a `Content-Length` header that exceeds the limit are not read at all. The bytes
+/// that are read into memory are counted in `stats`, if provided.
+async fn read_body(
+    body: &mut Body,
+    max_body_bytes: usize,
+    stats: Option<&StatsCounters>,
+) -> Result<Vec<u8>, ReadBodyError> {
+    if body.size_hint().lower() > max_body_bytes as u64 {
+        return Err(ReadBodyError::TooLarge);
+    }
+
+    let mut buf = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(ReadBodyError::Hyper)?;
+        if buf.len() + chunk.len() > max_body_bytes {
+            return Err(ReadBodyError::TooLarge);
+        }
+        buf.extend_from_slice(&chunk);
+        if let Some(stats) = stats {
+            stats.record_buffered_body(chunk.len());
+        }
+    }
+
+    Ok(buf)
+}
+
+/// Reads the rest of a body that is not required in the background without keeping it, so that
+/// clients can finish sending it and the connection can be reused after the response.
+fn discard_body(mut body: Body) {
+    // Requests to an in-process server might be handled outside of a tokio runtime.
+    if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+        runtime.spawn(async move { while let Some(Ok(_)) = body.data().await {} });
+    }
+}
+
+/// Returns whether the path belongs to the admin API.
+fn is_admin_path(path: &str) -> bool {
+    path.starts_with(&format!("{}/", BASE_PATH))
+}
+
+#[cfg(not(target_os = "windows"))]
+async fn shutdown_signal() {
+    let mut hangup_stream = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        .expect("Cannot install SIGINT signal handler");
+    let mut sigint_stream =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())
+            .expect("Cannot install SIGINT signal handler");
+    let mut sigterm_stream =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Cannot install SIGINT signal handler");
+
+    tokio::select! {
+        val = hangup_stream.recv() => log::trace!("Received SIGINT"),
+        val = sigint_stream.recv() => log::trace!("Received SIGINT"),
+        val = sigterm_stream.recv() => log::trace!("Received SIGTERM"),
+    }
+}
+
+#[cfg(target_os = "windows")]
+async fn shutdown_signal() {
+    tokio::signal::ctrl_c()
+        .await
+        .expect("Cannot install CTRL+C signal handler");
+}
+
+/// Holds the settings that a mock server instance is started with.
+#[derive(Default, Clone)]
+pub(crate) struct ServerConfig {
+    pub port: u16,
+    pub expose: bool,
+    pub print_access_log: bool,
+    #[cfg(feature = "https")]
+    pub tls: Option<Arc<tokio_rustls::rustls::ServerConfig>>,
+    /// Whether HTTP/2 is served to clients that connect without TLS but with prior knowledge.
+    pub http2_prior_knowledge: bool,
+    /// If set, the server listens on a Unix domain socket at this path instead of a TCP port.
+    #[cfg(unix)]
+    pub unix_socket: Option<PathBuf>,
+    /// The maximum time to wait for in-flight requests to complete when the server shuts down.
+    /// Defaults to [DEFAULT_SHUTDOWN_TIMEOUT](constant.DEFAULT_SHUTDOWN_TIMEOUT.html).
+    pub shutdown_timeout: Option<Duration>,
+    /// The maximum time to wait for in-flight requests to complete when a `MockServer` that is
+    /// not pooled is dropped. Defaults to [DEFAULT_DROP_DEADLINE](constant.DEFAULT_DROP_DEADLINE.html).
+    pub drop_deadline: Option<Duration>,
+    /// Whether the admin API is disabled, so that only previously added mocks are served.
+    pub read_only: bool,
+    /// The maximum size of request bodies that a mock requires in bytes. Defaults to
+    /// [DEFAULT_MAX_BODY_BYTES](constant.DEFAULT_MAX_BODY_BYTES.html).
+    pub max_body_bytes: Option<usize>,
+    /// Whether the admin API endpoints that list all mocks and the request history are
+    /// disabled, so that such requests are matched against the mocks instead.
+    pub disable_introspection: bool,
+    /// Whether `gzip` and `deflate` encoded request bodies are decompressed before they are
+    /// matched against mocks.
+    #[cfg(feature = "decompression")]
+    pub decompress_request_bodies: bool,
+    /// Whether HTTP/1 connections are closed after every response instead of being kept alive.
+    pub disable_keep_alive: bool,
+    /// If set, connections without any activity for this duration are closed.
+    pub idle_timeout: Option<Duration>,
+    /// The IP address to listen on. Defaults to `127.0.0.1`, or to `0.0.0.0` if `expose` is set.
+    pub bind_address: Option<IpAddr>,
+    /// Whether the server listens on an IPv6 address (`::` by default) that also accepts
+    /// IPv4 connections.
+    pub dual_stack: bool,
+    /// The maximum number of connections that are served at the same time.
+    pub max_connections: Option<usize>,
+    /// What happens to connections beyond `max_connections`.
+    pub overload_behavior: OverloadBehavior,
+    /// The TCP addresses to listen on instead of a single address that is derived from
+    /// `port`, `bind_address` and `expose`. The first listener is the primary one.
+    pub listeners: Vec<ListenerConfig>,
+    /// Whether the server acts as an HTTP proxy that accepts `CONNECT` requests.
+    pub proxy: bool,
+    /// The certificate authority that issues certificates for hosts that proxy clients
+    /// connect to using `CONNECT`.
+    #[cfg(feature = "https")]
+    pub proxy_ca: Option<Arc<tls::ProxyCa>>,
+    /// If set, all requests that are not sent to the admin API are forwarded to this upstream
+    /// server and recorded along with the upstream responses.
+    pub recording_upstream: Option<Arc<forward::Upstream>>,
+    /// If set, requests that do not match any mock are forwarded to this upstream server.
+    pub fallback_upstream: Option<Arc<forward::Upstream>>,
+}
+
+/// Describes what a mock server does with new connections while the maximum number of
+/// connections is reached (see
+/// [MockServerBuilder::max_connections](struct.MockServerBuilder.html#method.max_connections)).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverloadBehavior {
+    /// New connections are not accepted until another connection is closed, so they wait in
+    /// the listen backlog of the operating system.
+    #[default]
+    Queue,
+    /// New connections are accepted and closed right away.
+    Reject,
+    /// New connections are accepted, but all requests on them are answered with
+    /// `503 Service Unavailable` and the connection is closed afterwards.
+    Respond503,
+}
+
+/// Describes an additional TCP address that a mock server listens on.
+#[derive(Clone)]
+pub(crate) struct ListenerConfig {
+    /// The name that requests are tagged with to tell listeners apart.
+    pub name: Option<String>,
+    pub address: SocketAddr,
+}
+
+/// The default maximum time to wait for in-flight requests to complete on shutdown.
+pub(crate) const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The default maximum time to wait for in-flight requests to complete when a `MockServer` is
+/// dropped. It is kept short, since a test that panics mid-request should fail quickly.
+pub(crate) const DEFAULT_DROP_DEADLINE: Duration = Duration::from_secs(1);
+
+/// The default maximum size of request bodies in bytes.
+pub(crate) const DEFAULT_MAX_BODY_BYTES: usize = 32 * 1024 * 1024;
+
+/// The maximum size of a request body that is read although no mock requires it, so that the
+/// request history contains the bodies of typical requests. Larger bodies are not read at all.
+pub(crate) const RECORDED_BODY_BYTES: usize = 64 * 1024;
+
+/// A listener that accepts client connections on any of the supported transports.
+enum Listener {
+    /// A TCP listener along with its name, if any.
+    Tcp(TcpListener, Option<String>),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+/// Starts a new instance of an HTTP mock server. You should never need to use this function
+/// directly. Use it if you absolutely need to manage the low-level details of how the mock
+/// server operates.
+///
+/// The server runs until a termination signal is received or, if provided, the
+/// `shutdown_receiver` resolves. The value received is the maximum time to wait for in-flight
+/// requests to complete before their connections are closed.
+pub(crate) async fn start_server(
+    config: ServerConfig,
+    state: &Arc<MockServerState>,
+    socket_addr_sender: Option<tokio::sync::oneshot::Sender<Vec<SocketAddr>>>,
+    shutdown_receiver: Option<tokio::sync::oneshot::Receiver<Duration>>,
+    pause_receiver: Option<mpsc::UnboundedReceiver<PauseRequest>>,
+) -> Result<(), String> {
+    let (mut listeners, addrs): (Vec<_>, Vec<_>) = bind(&config).await?.into_iter().unzip();
+
+    // A paused server binds to the same ports again when it is resumed.
+    let mut resume_config = config.clone();
+    resume_config.port = addrs[0].port();
+    for (listener, addr) in resume_config.listeners.iter_mut().zip(&addrs) {
+        listener.address = *addr;
+    }
+
+    if let Some(socket_addr_sender) = socket_addr_sender {
+        if let Err(e) = socket_addr_sender.send(addrs) {
+            return Err(format!(
+                "Cannot send socket information to the test thread: {:?}",
+                e
+            ));
+        }
+    }
+
+    // Accept connections until a shutdown signal is received. The signal tells how long
+    // in-flight requests may take to complete.
+    let shutdown_timeout = config.shutdown_timeout.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT);
+    let shutdown = async move {
+        match shutdown_receiver {
+            Some(receiver) => tokio::select! {
+                timeout = receiver => timeout.unwrap_or(Duration::ZERO),
+                _ = shutdown_signal() => shutdown_timeout,
+            },
+            None => {
+                shutdown_signal().await;
+                shutdown_timeout
+            }
+        }
+    };
+    pin_mut!(shutdown);
+    let timeout;
+
+    let (draining_sender, draining_receiver) = watch::channel(false);
+    let mut connections = JoinSet::new();
+    let connection_limit = config.max_connections.map(|n| Arc::new(Semaphore::new(n)));
+
+    // The sender is kept so that servers without pause support never see a closed channel.
+    let (_pause_sender, unused_pause_receiver) = mpsc::unbounded_channel();
+    let mut pause_receiver = pause_receiver.unwrap_or(unused_pause_receiver);
+    let mut resume_at: Option<tokio::time::Instant> = None;
+
+    loop {
+        tokio::select! {
+            accepted = accept_any(&listeners, state, &config, &draining_receiver, connection_limit.as_ref()) => match accepted {
+                Ok(connection) => {
+                    connections.spawn(connection);
+                }
+                Err(e) => log::warn!("Cannot accept connection: {}", e),
+            },
+            // Reap connections that were closed in the meantime.
+            Some(_) = connections.join_next(), if !connections.is_empty() => {},
+            Some(request) = pause_receiver.recv() => {
+                let result = match request.command {
+                    PauseCommand::Pause { resume_after } => {
+                        if !listeners.is_empty() {
+                            close_listeners(&mut listeners, &config);
+                            connections.shutdown().await;
+                        }
+                        resume_at = resume_after.map(|d| tokio::time::Instant::now() + d);
+                        Ok(())
+                    }
+                    PauseCommand::Resume => {
+                        resume_at = None;
+                        resume_listeners(&mut listeners, &resume_config).await
+                    }
+                };
+                let _ = request.result_sender.send(result);
+            },
+            _ = tokio::time::sleep_until(resume_at.unwrap_or_else(tokio::time::Instant::now)), if resume_at.is_some() => {
+                resume_at = None;
+                if let Err(e) = resume_listeners(&mut listeners, &resume_config).await {
+                    log::warn!("Cannot resume paused mock server: {}", e);
+                }
+            },
+            t = &mut shutdown => {
+                timeout = t;
+                break;
+            },
+        };
+    }
+
+    // Stop accepting new connections and free the port before draining in-flight requests.
+    close_listeners(&mut listeners, &config);
+
+    let _ = draining_sender.send(true);
+    let drained = tokio::time::timeout(timeout, async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+
+    if drained.is_err() {
+        log::debug!(
+            "Aborting {} connection(s) that did not complete within {:?}",
+            connections.len(),
+            timeout
+        );
+        connections.shutdown().await;
+    }
+
+    Ok(())
+}
+
+/// Tells a running server to stop or to start accepting connections again.
+pub(crate) enum PauseCommand {
+    /// Closes the listener and all open connections, so that clients cannot connect until
+    /// the server is resumed, optionally automatically after the given duration.
+    Pause { resume_after: Option<Duration> },
+    /// Binds the listener to the same address again.
+    Resume,
+}
+
+/// A [PauseCommand](enum.PauseCommand.html) along with a channel that receives its result.
+pub(crate) struct PauseRequest {
+    pub command: PauseCommand,
+    pub result_sender: tokio::sync::oneshot::Sender<Result<(), String>>,
+}
+
+/// Closes all listeners and removes the Unix domain socket file, if any.
+#[allow(unused_variables)]
+fn close_listeners(listeners: &mut Vec<Listener>, config: &ServerConfig) {
+    if listeners.is_empty() {
+        return;
+    }
+    listeners.clear();
+
+    #[cfg(unix)]
+    if let Some(path) = &config.unix_socket {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Cannot remove Unix domain socket file {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Binds the listeners of a paused server again. Does nothing if the server is not paused.
+async fn resume_listeners(
+    listeners: &mut Vec<Listener>,
+    config: &ServerConfig,
+) -> Result<(), String> {
+    if listeners.is_empty() {
+        *listeners = bind(config).await?.into_iter().map(|(l, _)| l).collect();
+    }
+    Ok(())
+}
+
+/// Creates all listeners that are described by the server configuration, in the order of
+/// [ServerConfig::listeners](struct.ServerConfig.html#structfield.listeners). The name of
+/// each listener is attached to the connections it accepts.
+async fn bind(config: &ServerConfig) -> Result<Vec<(Listener, SocketAddr)>, String> {
+    if config.listeners.is_empty() {
+        return Ok(vec![bind_default(config).await?]);
+    }
+
+    let mut bound = Vec::with_capacity(config.listeners.len());
+    for listener_config in &config.listeners {
+        let listener = match TcpListener::bind(listener_config.address).await {
+            Ok(listener) => listener,
+            Err(e) => return Err(format!("Cannot bind to {}: {}", listener_config.address, e)),
+        };
+
+        let addr = match listener.local_addr() {
+            Ok(addr) => addr,
+            Err(e) => return Err(format!("Cannot read local socket address: {}", e)),
+        };
+
+        log::info!("Listening on {}", addr);
+        bound.push((Listener::Tcp(listener, listener_config.name.clone()), addr));
+    }
+
+    Ok(bound)
+}
+
+/// Creates the single listener that is used if no listeners are configured explicitly. For Unix domain
+/// sockets, the returned socket address is unspecified (`0.0.0.0:0`).
+async fn bind_default(config: &ServerConfig) -> Result<(Listener, SocketAddr), String> {
+    #[cfg(unix)]
+    if let Some(path) = &config.unix_socket {
+        remove_stale_socket_file(path)?;
+        let listener = match UnixListener::bind(path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                return Err(format!(
+                    "Cannot bind to Unix domain socket {:?}: {}",
+                    path, e
+                ))
+            }
+        };
+
+        log::info!("Listening on {:?}", path);
+        return Ok((
+            Listener::Unix(listener),
+            SocketAddr::from(([0, 0, 0, 0], 0)),
+        ));
+    }
+
+    let ip = match (config.bind_address, config.expose, config.dual_stack) {
+        (Some(ip), _, _) => ip,
+        // IPv4 clients can only connect to IPv6 sockets that listen on all interfaces.
+        (None, _, true) => IpAddr::from(Ipv6Addr::UNSPECIFIED),
+        (None, true, false) => IpAddr::from(Ipv4Addr::UNSPECIFIED),
+        (None, false, false) => IpAddr::from(Ipv4Addr::LOCALHOST),
+    };
+    let addr = SocketAddr::new(ip, config.port);
+
+    let listener = match config.dual_stack {
+        true => bind_dual_stack(addr),
+        false => TcpListener::bind(addr).await,
+    };
+    let listener = match listener {
+        Ok(listener) => listener,
+        Err(e) => return Err(format!("Cannot bind to {}: {}", addr, e)),
+    };
+
+    let addr = match listener.local_addr() {
+        Ok(addr) => addr,
+        Err(e) => return Err(format!("Cannot read local socket address: {}", e)),
+    };
 
-        Ok(server_request)
-    }
+    log::info!("Listening on {}", addr);
+    Ok((Listener::Tcp(listener, None), addr))
+}
 
-    pub fn new(
-        method: String,
-        path: String,
-        query: String,
-        headers: Vec<(String, String)>,
-    ) -> Self {
-        Self {
-            method,
-            path,
-            query,
-            headers,
-        }
+/// Creates a listener on an IPv6 address that also accepts IPv4 connections.
+fn bind_dual_stack(addr: SocketAddr) -> std::io::Result<TcpListener> {
+    if !addr.is_ipv6() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "dual-stack listeners require an IPv6 address",
+        ));
     }
+
+    let socket = Socket::new(Domain::IPV6, Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_only_v6(false)?;
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+
+    TcpListener::from_std(socket.into())
 }
 
-#[derive(Default, Debug)]
-pub(crate) struct ServerResponse {
-    pub status: u16,
-    pub headers: Vec<(String, String)>,
-    pub body: Vec<u8>,
+/// Removes a socket file that was left behind by a previous server at the same path.
+/// Files that are not sockets are left untouched, so that binding fails for them.
+#[cfg(unix)]
+fn remove_stale_socket_file(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)
+            .map_err(|e| format!("Cannot remove stale Unix domain socket {:?}: {}", path, e)),
+        _ => Ok(()),
+    }
 }
 
-impl ServerResponse {
-    pub fn new(status: u16, headers: Vec<(String, String)>, body: Vec<u8>) -> Self {
-        Self {
-            status,
-            headers,
-            body,
+/// Accepts a single client connection on any of the listeners and returns a future that
+/// serves it. Never completes if there are no listeners.
+async fn accept_any(
+    listeners: &[Listener],
+    state: &Arc<MockServerState>,
+    config: &ServerConfig,
+    draining: &watch::Receiver<bool>,
+    connection_limit: Option<&Arc<Semaphore>>,
+) -> std::io::Result<BoxFuture<'static, ()>> {
+    match listeners {
+        [] => std::future::pending().await,
+        [listener] => accept(listener, state, config, draining, connection_limit).await,
+        listeners => {
+            let accepts = listeners.iter().map(|listener| {
+                Box::pin(accept(listener, state, config, draining, connection_limit))
+            });
+            futures_util::future::select_all(accepts).await.0
         }
     }
 }
 
-/// Extracts all headers from the URI of the given request.
-fn extract_headers(header_map: &HeaderMap) -> Result<Vec<(String, String)>, String> {
-    let mut headers = Vec::new();
-    for (hn, hv) in header_map {
-        let hn = hn.as_str().to_string();
-        let hv = hv.to_str();
-        if let Err(e) = hv {
-            return Err(format!("error parsing headers: {}", e));
+/// Accepts a single client connection and returns a future that serves it.
+async fn accept(
+    listener: &Listener,
+    state: &Arc<MockServerState>,
+    config: &ServerConfig,
+    draining: &watch::Receiver<bool>,
+    connection_limit: Option<&Arc<Semaphore>>,
+) -> std::io::Result<BoxFuture<'static, ()>> {
+    // Queued connections wait in the listen backlog until a connection slot becomes free.
+    let permit = match (connection_limit, config.overload_behavior) {
+        (Some(limit), OverloadBehavior::Queue) => Some(
+            limit
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("Connection limit semaphore was closed"),
+        ),
+        _ => None,
+    };
+
+    let state = state.clone();
+    let config = config.clone();
+    let draining = draining.clone();
+
+    match listener {
+        Listener::Tcp(listener, name) => {
+            let (stream, remote_addr) = listener.accept().await?;
+            let conn_info = ConnectionInfo {
+                listener: name.clone(),
+                remote_addr: Some(remote_addr),
+                ..ConnectionInfo::default()
+            };
+            Ok(limit_connection(
+                stream,
+                state,
+                config,
+                conn_info,
+                draining,
+                connection_limit,
+                permit,
+            ))
+        }
+        #[cfg(unix)]
+        Listener::Unix(listener) => {
+            let (stream, _) = listener.accept().await?;
+            Ok(limit_connection(
+                stream,
+                state,
+                config,
+                ConnectionInfo::default(),
+                draining,
+                connection_limit,
+                permit,
+            ))
         }
-        headers.push((hn, hv.unwrap().to_string()));
     }
-    Ok(headers)
 }
 
-async fn access_log_middleware<T>(
-    req: HyperRequest<Body>,
+/// Returns a future that serves an accepted connection while it occupies a connection slot.
+/// If all slots are occupied, the connection is closed right away or all of its requests
+/// are answered with `503 Service Unavailable`, depending on the overload behavior.
+fn limit_connection<I>(
+    stream: I,
     state: Arc<MockServerState>,
-    print_access_log: bool,
-    next: fn(req: HyperRequest<Body>, state: Arc<MockServerState>) -> T,
-) -> HyperResult<HyperResponse<Body>>
+    config: ServerConfig,
+    mut conn_info: ConnectionInfo,
+    draining: watch::Receiver<bool>,
+    connection_limit: Option<&Arc<Semaphore>>,
+    permit: Option<OwnedSemaphorePermit>,
+) -> BoxFuture<'static, ()>
 where
-    T: Future<Output = HyperResult<HyperResponse<Body>>>,
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
 {
-    let time_request_received = Instant::now();
+    let permit = match (permit, connection_limit) {
+        (None, Some(limit)) => match limit.clone().try_acquire_owned() {
+            Ok(permit) => Some(permit),
+            Err(_) if config.overload_behavior == OverloadBehavior::Reject => {
+                log::debug!("Refusing connection because the connection limit is reached");
+                state.overload_rejections.fetch_add(1, Relaxed);
+                return Box::pin(async {});
+            }
+            Err(_) => {
+                conn_info.overloaded = true;
+                None
+            }
+        },
+        (permit, _) => permit,
+    };
 
-    let request_method = req.method().to_string();
-    let request_uri = req.uri().to_string();
-    let request_http_version = format!("{:?}", &req.version());
+    Box::pin(async move {
+        serve_connection(stream, state, config, conn_info, draining).await;
+        drop(permit);
+    })
+}
 
-    let result = next(req, state).await;
+/// Information about a client connection that is attached to every request received on it.
+#[derive(Default, Clone)]
+pub(crate) struct ConnectionInfo {
+    pub client_certificate: Option<ClientCertificate>,
+    /// The name of the listener that accepted the connection, if any.
+    pub listener: Option<String>,
+    /// The address of the client. Not set for Unix domain socket connections.
+    pub remote_addr: Option<SocketAddr>,
+    /// Whether the connection was accepted although the connection limit was reached, so
+    /// that all requests on it are answered with `503 Service Unavailable`.
+    pub overloaded: bool,
+}
 
-    if print_access_log && !request_uri.starts_with(&format!("{}/", BASE_PATH)) {
-        if let Ok(response) = &result {
-            log::info!(
-                "\"{} {} {:?}\" {} {}",
-                request_method,
-                request_uri,
-                request_http_version,
-                response.status().as_u16(),
-                time_request_received.elapsed().as_millis()
+/// Serves all HTTP requests that arrive on a single client connection.
+async fn serve_connection<I>(
+    stream: I,
+    state: Arc<MockServerState>,
+    config: ServerConfig,
+    #[allow(unused_mut)] mut conn_info: ConnectionInfo,
+    draining: watch::Receiver<bool>,
+) where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    #[cfg(feature = "https")]
+    if let Some(tls) = &config.tls {
+        let stream = match TlsAcceptor::from(tls.clone()).accept(stream).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::debug!("TLS handshake failed: {}", e);
+                return;
+            }
+        };
+
+        conn_info.client_certificate = match stream.get_ref().1.peer_certificates() {
+            Some(certs) if !certs.is_empty() => match tls::parse_client_certificate(&certs[0]) {
+                Ok(cert) => Some(cert),
+                Err(e) => {
+                    log::debug!("{}", e);
+                    return;
+                }
+            },
+            _ => None,
+        };
+
+        // HTTP/2 is only served to clients that negotiated it using ALPN.
+        let mut http = Http::new();
+        match stream.get_ref().1.alpn_protocol() {
+            Some(b"h2") => http.http2_only(true),
+            _ => http.http1_only(true),
+        };
+
+        return serve_http(stream, http, state, &config, conn_info, draining).await;
+    }
+
+    // Without TLS, HTTP/2 is only served to clients with prior knowledge (h2c) if enabled.
+    // The protocol is then detected by the connection preface.
+    let mut http = Http::new();
+    if !config.http2_prior_knowledge {
+        http.http1_only(true);
+    }
+
+    serve_http(stream, http, state, &config, conn_info, draining).await
+}
+
+async fn serve_http<I>(
+    io: I,
+    mut http: Http,
+    state: Arc<MockServerState>,
+    config: &ServerConfig,
+    conn_info: ConnectionInfo,
+    mut draining: watch::Receiver<bool>,
+) where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    http.http1_keep_alive(!config.disable_keep_alive && !conn_info.overloaded);
+    let idle_timeout = config.idle_timeout;
+
+    let config = Arc::new(config.clone());
+    let tunnel_draining = draining.clone();
+    let service = service_fn(move |mut req: HyperRequest<Body>| {
+        req.extensions_mut().insert(conn_info.clone());
+        let config = config.clone();
+
+        if conn_info.overloaded {
+            state.overload_rejections.fetch_add(1, Relaxed);
+            let mut response = status_response(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "The connection limit of the mock server is reached",
             );
+            if req.version() < Version::HTTP_2 {
+                response
+                    .headers_mut()
+                    .insert(hyper::header::CONNECTION, HeaderValue::from_static("close"));
+            }
+            return Either::Left(Either::Left(futures_util::future::ready(Ok(response))));
+        }
+
+        if config.proxy && req.method() == hyper::Method::CONNECT {
+            return Either::Left(Either::Right(handle_connect(
+                req,
+                state.clone(),
+                config,
+                tunnel_draining.clone(),
+            )));
         }
+
+        Either::Right(access_log_middleware(
+            req,
+            state.clone(),
+            config.print_access_log,
+            move |req, state| handle_server_request(req, state, config),
+        ))
+    });
+
+    let io = ActivityTrackingIo::new(io);
+    let last_activity = io.last_activity.clone();
+    let connection = http.serve_connection(io, service).with_upgrades();
+    pin_mut!(connection);
+
+    // When the server shuts down or the connection has been idle for too long, in-flight
+    // requests are completed but the connection is closed afterwards.
+    let mut closing = false;
+    let result = loop {
+        let idle_deadline = *last_activity.lock().unwrap() + idle_timeout.unwrap_or_default();
+
+        tokio::select! {
+            served = &mut connection => break served,
+            _ = draining.changed(), if !closing => {
+                connection.as_mut().graceful_shutdown();
+                closing = true;
+            }
+            _ = tokio::time::sleep_until(idle_deadline), if idle_timeout.is_some() && !closing => {
+                if *last_activity.lock().unwrap() <= idle_deadline - idle_timeout.unwrap() {
+                    log::debug!("Closing idle connection");
+                    connection.as_mut().graceful_shutdown();
+                    closing = true;
+                }
+            }
+        };
     };
 
-    return result;
+    if let Err(e) = result {
+        log::debug!("Error while serving HTTP connection: {}", e);
+    }
 }
 
-async fn handle_server_request(
+/// Accepts a `CONNECT` request of a proxy client and serves the requests that are sent through
+/// the tunnel afterwards. TLS is terminated using a certificate for the target host that is
+/// issued by the proxy certificate authority.
+async fn handle_connect(
     req: HyperRequest<Body>,
     state: Arc<MockServerState>,
+    config: Arc<ServerConfig>,
+    draining: watch::Receiver<bool>,
 ) -> HyperResult<HyperResponse<Body>> {
-    let request_header = ServerRequestHeader::from(&req);
+    #[cfg(feature = "https")]
+    if let Some(ca) = &config.proxy_ca {
+        let host = match req.uri().host() {
+            Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+            None => {
+                return Ok(status_response(
+                    StatusCode::BAD_REQUEST,
+                    "CONNECT requests require a target host",
+                ))
+            }
+        };
 
-    if let Err(e) = request_header {
-        return Ok(error_response(format!("Cannot parse request: {}", e)));
-    }
+        // Requests that are sent through the tunnel belong to the listener and the client of
+        // the proxy.
+        let proxy_conn_info = req.extensions().get::<ConnectionInfo>();
+        let conn_info = ConnectionInfo {
+            listener: proxy_conn_info.and_then(|c| c.listener.clone()),
+            remote_addr: proxy_conn_info.and_then(|c| c.remote_addr),
+            ..ConnectionInfo::default()
+        };
 
-    let body = hyper::body::to_bytes(req.into_body()).await;
-    if let Err(e) = body {
-        return Ok(error_response(format!("Cannot read request body: {}", e)));
-    }
+        let mut tunnel_config = ServerConfig::clone(&config);
+        tunnel_config.tls = match ca.server_config(host) {
+            Ok(tls) => Some(tls),
+            Err(e) => return Ok(error_response(e)),
+        };
 
-    let routing_result = route_request(
-        state.borrow(),
-        &request_header.unwrap(),
-        body.unwrap().to_vec(),
-    )
-    .await;
-    if let Err(e) = routing_result {
-        return Ok(error_response(format!("Request handler error: {}", e)));
-    }
+        log::debug!("Opening tunnel to {}", req.uri());
+        tokio::spawn(async move {
+            match hyper::upgrade::on(req).await {
+                Ok(upgraded) => {
+                    serve_tunnel(upgraded, state, tunnel_config, conn_info, draining).await
+                }
+                Err(e) => log::debug!("Cannot open tunnel: {}", e),
+            }
+        });
 
-    let response = map_response(routing_result.unwrap());
-    if let Err(e) = response {
-        return Ok(error_response(format!("Cannot build response: {}", e)));
+        return Ok(HyperResponse::new(Body::empty()));
     }
 
-    Ok(response.unwrap())
+    Ok(status_response(
+        StatusCode::NOT_IMPLEMENTED,
+        "CONNECT requests are only supported if the https feature is enabled",
+    ))
 }
 
-#[cfg(not(target_os = "windows"))]
-async fn shutdown_signal() {
-    let mut hangup_stream = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
-        .expect("Cannot install SIGINT signal handler");
-    let mut sigint_stream =
-        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::interrupt())
-            .expect("Cannot install SIGINT signal handler");
-    let mut sigterm_stream =
-        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
-            .expect("Cannot install SIGINT signal handler");
-
-    tokio::select! {
-        val = hangup_stream.recv() => log::trace!("Received SIGINT"),
-        val = sigint_stream.recv() => log::trace!("Received SIGINT"),
-        val = sigterm_stream.recv() => log::trace!("Received SIGTERM"),
-    }
+/// Serves the requests that are sent through a proxy tunnel. The future is boxed because it
+/// is spawned from within the connection it serves requests of.
+#[cfg(feature = "https")]
+fn serve_tunnel(
+    upgraded: hyper::upgrade::Upgraded,
+    state: Arc<MockServerState>,
+    config: ServerConfig,
+    conn_info: ConnectionInfo,
+    draining: watch::Receiver<bool>,
+) -> BoxFuture<'static, ()> {
+    Box::pin(serve_connection(
+        upgraded, state, config, conn_info, draining,
+    ))
 }
 
-#[cfg(target_os = "windows")]
-async fn shutdown_signal() {
-    tokio::signal::ctrl_c()
-        .await
-        .expect("Cannot install CTRL+C signal handler");
+/// Wraps a client connection to keep track of when data was last read or written.
+struct ActivityTrackingIo<I> {
+    io: I,
+    last_activity: Arc<Mutex<tokio::time::Instant>>,
 }
 
-/// Starts a new instance of an HTTP mock server. You should never need to use this function
-/// directly. Use it if you absolutely need to manage the low-level details of how the mock
-/// server operates.
-pub(crate) async fn start_server(
-    port: u16,
-    expose: bool,
-    state: &Arc<MockServerState>,
-    socket_addr_sender: Option<tokio::sync::oneshot::Sender<SocketAddr>>,
-    print_access_log: bool,
-) -> Result<(), String> {
-    let host = if expose { "0.0.0.0" } else { "127.0.0.1" };
-
-    let state = state.clone();
-    let new_service = make_service_fn(move |_| {
-        let state = state.clone();
-        async move {
-            Ok::<_, GenericError>(service_fn(move |req: HyperRequest<Body>| {
-                let state = state.clone();
-                access_log_middleware(req, state, print_access_log, handle_server_request)
-            }))
+impl<I> ActivityTrackingIo<I> {
+    fn new(io: I) -> Self {
+        Self {
+            io,
+            last_activity: Arc::new(Mutex::new(tokio::time::Instant::now())),
         }
-    });
+    }
 
-    let server = Server::bind(&format!("{}:{}", host, port).parse().unwrap()).serve(new_service);
-    let addr = server.local_addr();
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap() = tokio::time::Instant::now();
+    }
+}
 
-    // And now add a graceful shutdown signal...
-    let graceful = server.with_graceful_shutdown(shutdown_signal());
-    if let Some(socket_addr_sender) = socket_addr_sender {
-        if let Err(e) = socket_addr_sender.send(addr) {
-            return Err(format!(
-                "Cannot send socket information to the test thread: {:?}",
-                e
-            ));
+impl<I: AsyncRead + Unpin> AsyncRead for ActivityTrackingIo<I> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let result = Pin::new(&mut self.io).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = result {
+            self.touch();
         }
+        result
     }
+}
 
-    log::info!("Listening on {}", addr);
-    if let Err(e) = graceful.await {
-        return Err(format!("Err: {}", e));
+impl<I: AsyncWrite + Unpin> AsyncWrite for ActivityTrackingIo<I> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let result = Pin::new(&mut self.io).poll_write(cx, buf);
+        if let Poll::Ready(Ok(_)) = result {
+            self.touch();
+        }
+        result
     }
 
-    Ok(())
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
 }
 
 /// Maps a server response to a hyper response.
@@ -535,7 +2131,15 @@ fn map_response(route_response: ServerResponse) -> Result<HyperResponse<Body>, S
         builder = builder.header(name.unwrap(), value.unwrap());
     }
 
-    let result = builder.body(Body::from(route_response.body));
+    let body = match route_response.body_stream {
+        Some(body_stream) => body_stream,
+        None => Body::from(route_response.body),
+    };
+    let body = match route_response.trailers.is_empty() {
+        true => body,
+        false => with_trailers(body, to_header_map(route_response.trailers)?),
+    };
+    let result = builder.body(body);
     if let Err(e) = result {
         return Err(format!("Cannot create HTTP response: {}", e));
     }
@@ -543,11 +2147,47 @@ fn map_response(route_response: ServerResponse) -> Result<HyperResponse<Body>, S
     Ok(result.unwrap())
 }
 
-/// Routes a request to the appropriate route handler.
+/// Returns a body that sends the trailers after all data of the provided body was sent.
+fn with_trailers(mut body: Body, trailers: HeaderMap) -> Body {
+    let (mut sender, body_with_trailers) = Body::channel();
+    tokio::spawn(async move {
+        while let Some(chunk) = body.data().await {
+            match chunk {
+                Ok(chunk) => {
+                    if sender.send_data(chunk).await.is_err() {
+                        return;
+                    }
+                }
+                Err(_) => return sender.abort(),
+            }
+        }
+        if let Err(e) = sender.send_trailers(trailers).await {
+            log::debug!("Cannot send trailers: {}", e);
+        }
+    });
+    body_with_trailers
+}
+
+fn to_header_map(headers: Vec<(String, String)>) -> Result<HeaderMap, String> {
+    let mut header_map = HeaderMap::new();
+    for (key, value) in headers {
+        let name = HeaderName::from_str(&key)
+            .map_err(|e| format!("Cannot create header from name: {}", e))?;
+        let value = HeaderValue::from_str(&value)
+            .map_err(|e| format!("Cannot create header from value: {}", e))?;
+        header_map.append(name, value);
+    }
+    Ok(header_map)
+}
+
+/// Routes a request to the appropriate route handler. If the server is read-only, all admin
+/// API requests except pings and introspection requests are served like any other request.
+/// Introspection requests are served like any other request if introspection is disabled.
 async fn route_request(
     state: &MockServerState,
     request_header: &ServerRequestHeader,
     body: Vec<u8>,
+    config: &ServerConfig,
 ) -> Result<ServerResponse, String> {
     log::trace!("Routing incoming request: {:?}", request_header);
 
@@ -557,10 +2197,51 @@ async fn route_request(
         }
     }
 
+    // Admin API requests can be limited to the mocks and requests of a namespace.
+    let namespace = namespace_param(&request_header.query);
+    let namespace = namespace.as_deref();
+
+    if !config.disable_introspection && request_header.method == "GET" {
+        if MOCKS_PATH.is_match(&request_header.path) {
+            return routes::read_all(state, namespace);
+        }
+        if HISTORY_PATH.is_match(&request_header.path) {
+            return routes::read_history(state, namespace);
+        }
+        if UNMATCHED_PATH.is_match(&request_header.path) {
+            return routes::read_unmatched(state, namespace);
+        }
+        if HAR_PATH.is_match(&request_header.path) {
+            return routes::export_har(state, is_https(config));
+        }
+        if STATS_PATH.is_match(&request_header.path) {
+            return routes::read_stats(state);
+        }
+        if METRICS_PATH.is_match(&request_header.path) {
+            return routes::metrics(state);
+        }
+        if MOCK_REQUESTS_PATH.is_match(&request_header.path) {
+            let id = get_path_param(&MOCK_REQUESTS_PATH, 1, &request_header.path)
+                .map_err(|e| format!("Cannot parse id from path: {}", e))?;
+            return routes::read_mock_requests(state, id);
+        }
+    }
+
+    if config.read_only {
+        return serve_or_record(state, request_header, body, config).await;
+    }
+
     if MOCKS_PATH.is_match(&request_header.path) {
         match request_header.method.as_str() {
-            "POST" => return routes::add(state, body),
-            "DELETE" => return routes::delete_all_mocks(state),
+            "POST" => {
+                let idempotency_key = request_header
+                    .headers
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(IDEMPOTENCY_KEY_HEADER))
+                    .map(|(_, value)| value.as_str());
+                return routes::add(state, body, idempotency_key);
+            }
+            "DELETE" => return routes::delete_all_mocks(state, namespace),
             _ => {}
         }
     }
@@ -574,11 +2255,28 @@ async fn route_request(
 
         match request_header.method.as_str() {
             "GET" => return routes::read_one(state, id),
+            "PUT" => return routes::update(state, id, body),
             "DELETE" => return routes::delete_one(state, id),
             _ => {}
         }
     }
 
+    if MOCK_ENABLED_PATH.is_match(&request_header.path) {
+        let id = get_path_param(&MOCK_ENABLED_PATH, 1, &request_header.path)
+            .map_err(|e| format!("Cannot parse id from path: {}", e))?;
+        match request_header.method.as_str() {
+            "PUT" => return routes::set_enabled(state, id, body),
+            _ => {}
+        }
+    }
+
+    if STATIC_DIRS_PATH.is_match(&request_header.path) {
+        match request_header.method.as_str() {
+            "POST" => return routes::add_static_dir(state, body),
+            _ => {}
+        }
+    }
+
     if VERIFY_PATH.is_match(&request_header.path) {
         match request_header.method.as_str() {
             "POST" => return routes::verify(state, body),
@@ -588,17 +2286,57 @@ async fn route_request(
 
     if HISTORY_PATH.is_match(&request_header.path) {
         match request_header.method.as_str() {
-            "DELETE" => return routes::delete_history(state),
+            "DELETE" => return routes::delete_history(state, namespace),
             _ => {}
         }
     }
 
-    routes::serve(state, request_header, body).await
+    if RESET_PATH.is_match(&request_header.path) {
+        match request_header.method.as_str() {
+            "POST" => return routes::reset(state, namespace),
+            _ => {}
+        }
+    }
+
+    serve_or_record(state, request_header, body, config).await
+}
+
+/// Returns true if the mock server accepts HTTPS connections.
+fn is_https(config: &ServerConfig) -> bool {
+    #[cfg(feature = "https")]
+    return config.tls.is_some();
+    #[cfg(not(feature = "https"))]
+    return false;
+}
+
+/// Serves a request that is not sent to the admin API, either from the mocks or, if the mock
+/// server records traffic, by forwarding it to the upstream server. Unmatched requests are
+/// forwarded to the fallback upstream server, if any.
+async fn serve_or_record(
+    state: &MockServerState,
+    request_header: &ServerRequestHeader,
+    body: Vec<u8>,
+    config: &ServerConfig,
+) -> Result<ServerResponse, String> {
+    match &config.recording_upstream {
+        Some(upstream) => routes::record(state, request_header, body, upstream).await,
+        None => {
+            let fallback_upstream = config.fallback_upstream.as_deref();
+            routes::serve(state, request_header, body, fallback_upstream).await
+        }
+    }
+}
+
+/// Returns the value of the `namespace` query parameter of an admin API request.
+fn namespace_param(query: &str) -> Option<String> {
+    form_urlencoded::parse(query.as_bytes())
+        .find(|(name, _)| name == "namespace")
+        .map(|(_, value)| value.into_owned())
 }
 
 /// Get request path parameters.
-fn get_path_param(regex: &Regex, idx: usize, path: &str) -> Result<usize, String> {
-    let cap = regex.captures(path);
+fn get_path_param(route: &RoutePath, idx: usize, path: &str) -> Result<usize, String> {
+    let cap = route.captures(path);
     if cap.is_none() {
         return Err(format!(
             "Error capturing parameter from request path: {}",
@@ -614,7 +2352,7 @@ fn get_path_param(regex: &Regex, idx: usize, path: &str) -> Result<usize, String
             path
         ));
     }
-    let id = id.unwrap().as_str();
+    let id = id.unwrap();
 
     let id = id.parse::<usize>();
     if let Err(e) = id {
@@ -625,6 +2363,14 @@ fn get_path_param(regex: &Regex, idx: usize, path: &str) -> Result<usize, String
     Ok(id)
 }
 
+/// Creates a plain text response with the given status code.
+fn status_response(status: StatusCode, body: &'static str) -> HyperResponse<Body> {
+    HyperResponse::builder()
+        .status(status)
+        .body(Body::from(body))
+        .expect("Cannot build response")
+}
+
 /// Creates a default error response.
 fn error_response(body: String) -> HyperResponse<Body> {
     HyperResponse::builder()
@@ -635,12 +2381,63 @@ fn error_response(body: String) -> HyperResponse<Body> {
 
 static BASE_PATH: &'static str = "/__httpmock__";
 
+/// The path prefix of requests that are sent to a namespace. The name of the namespace is the
+/// next path segment.
+/// The header that carries the idempotency key of an admin API request that adds a mock, so
+/// that a repeated request does not add the mock twice.
+pub(crate) static IDEMPOTENCY_KEY_HEADER: &'static str = "x-httpmock-idempotency-key";
+pub(crate) static NAMESPACE_BASE_PATH: &'static str = "/__httpmock_ns__";
+
+/// The path of an admin API route below the base path, in which every `{id}` segment stands
+/// for a numeric path parameter. Routes are matched segment by segment, so that the mock server
+/// does not depend on the `regex` feature.
+struct RoutePath(String);
+
+impl RoutePath {
+    fn new(path: &str) -> Self {
+        RoutePath(format!("{}{}", BASE_PATH, path))
+    }
+
+    fn is_match(&self, path: &str) -> bool {
+        self.captures(path).is_some()
+    }
+
+    /// Returns the full path followed by the values of all path parameters, like the capture
+    /// groups of a regex, or `None` if the path does not match the route.
+    fn captures<'a>(&self, path: &'a str) -> Option<Vec<&'a str>> {
+        let mut captures = vec![path];
+        let mut segments = path.split('/');
+        for expected in self.0.split('/') {
+            let segment = segments.next()?;
+            match expected {
+                "{id}" if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) => {
+                    captures.push(segment)
+                }
+                _ if expected == segment => {}
+                _ => return None,
+            }
+        }
+        match segments.next() {
+            Some(_) => None,
+            None => Some(captures),
+        }
+    }
+}
+
 lazy_static! {
-    static ref PING_PATH: Regex = Regex::new(&format!(r"^{}/ping$", BASE_PATH)).unwrap();
-    static ref MOCKS_PATH: Regex = Regex::new(&format!(r"^{}/mocks$", BASE_PATH)).unwrap();
-    static ref MOCK_PATH: Regex = Regex::new(&format!(r"^{}/mocks/([0-9]+)$", BASE_PATH)).unwrap();
-    static ref HISTORY_PATH: Regex = Regex::new(&format!(r"^{}/history$", BASE_PATH)).unwrap();
-    static ref VERIFY_PATH: Regex = Regex::new(&format!(r"^{}/verify$", BASE_PATH)).unwrap();
+    static ref PING_PATH: RoutePath = RoutePath::new("/ping");
+    static ref MOCKS_PATH: RoutePath = RoutePath::new("/mocks");
+    static ref MOCK_PATH: RoutePath = RoutePath::new("/mocks/{id}");
+    static ref MOCK_REQUESTS_PATH: RoutePath = RoutePath::new("/mocks/{id}/requests");
+    static ref MOCK_ENABLED_PATH: RoutePath = RoutePath::new("/mocks/{id}/enabled");
+    static ref HISTORY_PATH: RoutePath = RoutePath::new("/history");
+    static ref UNMATCHED_PATH: RoutePath = RoutePath::new("/history/unmatched");
+    static ref HAR_PATH: RoutePath = RoutePath::new("/history/har");
+    static ref STATS_PATH: RoutePath = RoutePath::new("/stats");
+    static ref METRICS_PATH: RoutePath = RoutePath::new("/metrics");
+    static ref RESET_PATH: RoutePath = RoutePath::new("/reset");
+    static ref VERIFY_PATH: RoutePath = RoutePath::new("/verify");
+    static ref STATIC_DIRS_PATH: RoutePath = RoutePath::new("/static_dirs");
 }
 
 #[cfg(test)]
@@ -650,10 +2447,10 @@ mod test {
     use futures_util::TryStreamExt;
 
     use crate::server::{
-        error_response, get_path_param, map_response, ServerResponse, HISTORY_PATH, MOCKS_PATH,
+        error_response, get_path_param, map_response, route_request, routes, MockServerState,
+        RoutePath, ServerConfig, ServerRequestHeader, ServerResponse, HISTORY_PATH, MOCKS_PATH,
         MOCK_PATH, PING_PATH, VERIFY_PATH,
     };
-    use crate::Regex;
     use hyper::body::Bytes;
     use hyper::Error;
 
@@ -687,7 +2484,6 @@ mod test {
         assert_eq!(VERIFY_PATH.is_match("test/verify/1295473892374"), false);
 
         assert_eq!(HISTORY_PATH.is_match("/__httpmock__/history"), true);
-        println!("{:?}", HISTORY_PATH.as_str());
 
         assert_eq!(
             HISTORY_PATH.is_match("/__httpmock__/history/1295473892374"),
@@ -717,6 +2513,99 @@ mod test {
         assert_eq!(String::from_utf8(body).unwrap(), "test".to_string())
     }
 
+    /// Makes sure the admin API cannot be used to add mocks to a read-only server.
+    #[test]
+    fn route_request_read_only_test() {
+        // Arrange
+        let state = MockServerState::default();
+        let header = ServerRequestHeader::new(
+            "POST".to_string(),
+            "/__httpmock__/mocks".to_string(),
+            String::new(),
+            Vec::new(),
+        );
+        let body = br#"{"request":{},"response":{}}"#.to_vec();
+        let read_only_config = ServerConfig {
+            read_only: true,
+            ..ServerConfig::default()
+        };
+
+        // Act
+        let read_only = async_std::task::block_on(route_request(
+            &state,
+            &header,
+            body.clone(),
+            &read_only_config,
+        ));
+        let writable = async_std::task::block_on(route_request(
+            &state,
+            &header,
+            body,
+            &ServerConfig::default(),
+        ));
+
+        // Assert
+        assert_eq!(read_only.unwrap().status, 404);
+        assert_eq!(writable.unwrap().status, 201);
+        assert_eq!(state.mocks.read().unwrap().len(), 1);
+    }
+
+    /// Makes sure mocks can only be listed if introspection is enabled.
+    #[test]
+    fn route_request_introspection_test() {
+        // Arrange
+        let state = MockServerState::default();
+        let header = ServerRequestHeader::new(
+            "GET".to_string(),
+            "/__httpmock__/mocks".to_string(),
+            String::new(),
+            Vec::new(),
+        );
+        let disabled_config = ServerConfig {
+            disable_introspection: true,
+            ..ServerConfig::default()
+        };
+
+        // Act
+        let enabled = async_std::task::block_on(route_request(
+            &state,
+            &header,
+            Vec::new(),
+            &ServerConfig::default(),
+        ));
+        let disabled =
+            async_std::task::block_on(route_request(&state, &header, Vec::new(), &disabled_config));
+
+        // Assert
+        let enabled = enabled.unwrap();
+        assert_eq!(enabled.status, 200);
+        assert_eq!(enabled.body, b"[]".to_vec());
+        assert_eq!(disabled.unwrap().status, 404);
+    }
+
+    /// Makes sure requests with a too large body are recorded without their body.
+    #[test]
+    fn payload_too_large_test() {
+        // Arrange
+        let state = MockServerState::default();
+        let header = ServerRequestHeader::new(
+            "POST".to_string(),
+            "/upload".to_string(),
+            String::new(),
+            Vec::new(),
+        );
+
+        // Act
+        let response = routes::payload_too_large(&state, &header, 10).unwrap();
+
+        // Assert
+        assert_eq!(response.status, 413);
+        let history = state.history.lock().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].body, None);
+        assert!(history[0].body_truncated);
+    }
+
     /// Makes sure an error is return if there is a header parsing error
     #[test]
     fn response_header_key_parsing_error_test() {
@@ -728,6 +2617,8 @@ mod test {
             body: Vec::new(),
             status: 500,
             headers,
+            body_stream: None,
+            trailers: Vec::new(),
         };
 
         // Act
@@ -747,10 +2638,10 @@ mod test {
     #[test]
     fn get_path_param_regex_error_test() {
         // Arrange
-        let re = Regex::new(r"^/__httpmock__/mocks/([0-9]+)$").unwrap();
+        let route = RoutePath::new("/mocks/{id}");
 
         // Act
-        let result = get_path_param(&re, 0, "");
+        let result = get_path_param(&route, 0, "");
 
         // Assert
         assert_eq!(result.is_err(), true);
@@ -766,10 +2657,10 @@ mod test {
     #[test]
     fn get_path_param_index_error_test() {
         // Arrange
-        let re = Regex::new(r"^/__httpmock__/mocks/([0-9]+)$").unwrap();
+        let route = RoutePath::new("/mocks/{id}");
 
         // Act
-        let result = get_path_param(&re, 5, "/__httpmock__/mocks/5");
+        let result = get_path_param(&route, 5, "/__httpmock__/mocks/5");
 
         // Assert
         assert_eq!(result.is_err(), true);
@@ -782,10 +2673,10 @@ mod test {
     #[test]
     fn get_path_param_number_error_test() {
         // Arrange
-        let re = Regex::new(r"^/__httpmock__/mocks/([0-9]+)$").unwrap();
+        let route = RoutePath::new("/mocks/{id}");
 
         // Act
-        let result = get_path_param(&re, 0, "/__httpmock__/mocks/9999999999999999999999999");
+        let result = get_path_param(&route, 0, "/__httpmock__/mocks/9999999999999999999999999");
 
         // Assert
         assert_eq!(result.is_err(), true);
@@ -0,0 +1,265 @@
+//! Logs the requests that the mock server received along with the mock that they matched and
+//! the response status (see
+//! [MockServer::log_requests](../struct.MockServer.html#method.log_requests)).
+
+use serde_json::{Map, Value};
+
+use crate::common::data::HttpMockRequest;
+
+/// The default maximum number of body bytes that are written to the request log.
+pub(crate) const DEFAULT_LOG_BODY_BYTES: usize = 4096;
+
+/// The value that is logged instead of the value of a redacted header.
+const REDACTED: &str = "[REDACTED]";
+
+/// The level of the log records that the request log is written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => log::Level::Error,
+            LogLevel::Warn => log::Level::Warn,
+            LogLevel::Info => log::Level::Info,
+            LogLevel::Debug => log::Level::Debug,
+            LogLevel::Trace => log::Level::Trace,
+        }
+    }
+}
+
+/// The format of the request log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// A human-readable, multi-line record per request. JSON bodies are pretty-printed.
+    Pretty,
+    /// A single-line JSON object per request, e.g. for structured log collectors.
+    Json,
+}
+
+/// The settings of the request log of a mock server. Requests are not logged by default.
+#[derive(Debug, Clone)]
+pub(crate) struct RequestLogConfig {
+    pub enabled: Option<(LogLevel, LogFormat)>,
+    /// The lowercase names of the headers whose values are not logged.
+    pub redact_headers: Vec<String>,
+    pub max_body_bytes: usize,
+}
+
+impl Default for RequestLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: None,
+            redact_headers: Vec::new(),
+            max_body_bytes: DEFAULT_LOG_BODY_BYTES,
+        }
+    }
+}
+
+/// Logs a request along with the mock that it matched (its ID and description) and the status
+/// of the response, if the request log is enabled.
+pub(crate) fn log_request(
+    config: &RequestLogConfig,
+    req: &HttpMockRequest,
+    mock: Option<(usize, String)>,
+    status: u16,
+) {
+    if let Some((level, format)) = config.enabled {
+        let level = log::Level::from(level);
+        if log::log_enabled!(level) {
+            log::log!(
+                level,
+                "{}",
+                format_request(config, format, req, mock, status)
+            );
+        }
+    }
+}
+
+fn format_request(
+    config: &RequestLogConfig,
+    format: LogFormat,
+    req: &HttpMockRequest,
+    mock: Option<(usize, String)>,
+    status: u16,
+) -> String {
+    let headers: Vec<(&str, &str)> = req
+        .headers
+        .iter()
+        .flatten()
+        .map(
+            |(name, value)| match config.redact_headers.contains(&name.to_lowercase()) {
+                true => (name.as_str(), REDACTED),
+                false => (name.as_str(), value.as_str()),
+            },
+        )
+        .collect();
+    let body = req.body.as_deref().filter(|body| !body.is_empty());
+    let is_json = req.headers.iter().flatten().any(|(name, value)| {
+        name.eq_ignore_ascii_case("content-type") && value.to_lowercase().contains("json")
+    });
+
+    match format {
+        LogFormat::Pretty => {
+            let matched = match &mock {
+                Some((id, description)) => format!("matched mock {} ({})", id, description),
+                None => "did not match any mock".to_string(),
+            };
+            let mut lines = vec![format!(
+                "{} {} {}, response status {}",
+                req.method,
+                req.path_and_query(),
+                matched,
+                status
+            )];
+            for (name, value) in headers {
+                lines.push(format!("  {}: {}", name, value));
+            }
+            if let Some(body) = body {
+                lines.push(String::new());
+                let body = format_body(body, is_json, config.max_body_bytes);
+                lines.extend(body.lines().map(|line| format!("  {}", line)));
+            }
+            lines.join("\n")
+        }
+        LogFormat::Json => {
+            let mut header_map = Map::new();
+            for (name, value) in headers {
+                let value = match header_map.get(name).and_then(|v| v.as_str()) {
+                    Some(previous) => format!("{}, {}", previous, value),
+                    None => value.to_string(),
+                };
+                header_map.insert(name.to_string(), Value::String(value));
+            }
+            let body = body.map(
+                |body| match is_json && body.len() <= config.max_body_bytes {
+                    true => serde_json::from_slice(body)
+                        .unwrap_or_else(|_| Value::String(truncate(body, config.max_body_bytes))),
+                    false => Value::String(truncate(body, config.max_body_bytes)),
+                },
+            );
+
+            serde_json::json!({
+                "method": req.method,
+                "path": req.path_and_query(),
+                "headers": header_map,
+                "body": body,
+                "mock_id": mock.as_ref().map(|(id, _)| id),
+                "mock": mock.as_ref().map(|(_, description)| description),
+                "status": status,
+            })
+            .to_string()
+        }
+    }
+}
+
+/// Formats a body for the pretty request log. JSON bodies that do not exceed the size limit
+/// are pretty-printed.
+fn format_body(body: &[u8], is_json: bool, max_bytes: usize) -> String {
+    if is_json && body.len() <= max_bytes {
+        if let Ok(value) = serde_json::from_slice::<Value>(body) {
+            return serde_json::to_string_pretty(&value).unwrap_or_default();
+        }
+    }
+    truncate(body, max_bytes)
+}
+
+/// Returns the body as text, cut off after `max_bytes` bytes. Binary bodies are only described
+/// by their size.
+fn truncate(body: &[u8], max_bytes: usize) -> String {
+    let text = match std::str::from_utf8(body) {
+        Ok(text) => text,
+        Err(_) => return format!("<{} bytes of binary data>", body.len()),
+    };
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... ({} bytes)", &text[..end], body.len())
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::common::data::HttpMockRequest;
+    use crate::server::request_log::{format_request, LogFormat, RequestLogConfig};
+
+    fn request() -> HttpMockRequest {
+        HttpMockRequest::new("POST".to_string(), "/users".to_string())
+            .with_query_params(vec![("notify".to_string(), "true".to_string())])
+            .with_headers(vec![
+                ("content-type".to_string(), "application/json".to_string()),
+                ("Authorization".to_string(), "Bearer secret".to_string()),
+            ])
+            .with_body(br#"{"name":"Fred","tags":["a"]}"#.to_vec())
+    }
+
+    #[test]
+    fn pretty_format_test() {
+        // Arrange
+        let config = RequestLogConfig {
+            redact_headers: vec!["authorization".to_string()],
+            ..RequestLogConfig::default()
+        };
+
+        // Act
+        let text = format_request(
+            &config,
+            LogFormat::Pretty,
+            &request(),
+            Some((3, "POST /users".to_string())),
+            201,
+        );
+
+        // Assert
+        assert_eq!(
+            text,
+            "POST /users?notify=true matched mock 3 (POST /users), response status 201\n  \
+             content-type: application/json\n  \
+             Authorization: [REDACTED]\n\n  \
+             {\n    \"name\": \"Fred\",\n    \"tags\": [\n      \"a\"\n    ]\n  }"
+        );
+    }
+
+    #[test]
+    fn json_format_test() {
+        // Arrange
+        let config = RequestLogConfig {
+            redact_headers: vec!["authorization".to_string()],
+            max_body_bytes: 10,
+            ..RequestLogConfig::default()
+        };
+
+        // Act
+        let text = format_request(&config, LogFormat::Json, &request(), None, 404);
+
+        // Assert
+        assert!(!text.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "method": "POST",
+                "path": "/users?notify=true",
+                "headers": {
+                    "content-type": "application/json",
+                    "Authorization": "[REDACTED]"
+                },
+                "body": "{\"name\":\"F... (28 bytes)",
+                "mock_id": null,
+                "mock": null,
+                "status": 404
+            })
+        );
+    }
+}
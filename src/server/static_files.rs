@@ -0,0 +1,208 @@
+//! Serves the files of static directories for requests that do not match any mock.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::common::data::{HttpMockRequest, MockServerHttpResponse, StaticDirectory};
+
+/// Returns the response for a `GET` or `HEAD` request below the path prefix of a static
+/// directory. Returns `None` if no directory contains the requested file, so that the request
+/// is handled like any other request that does not match a mock. Paths that would escape the
+/// root directory are rejected with `403 Forbidden`.
+pub(crate) fn serve(
+    dirs: &[StaticDirectory],
+    req: &HttpMockRequest,
+) -> Option<MockServerHttpResponse> {
+    if req.method != "GET" && req.method != "HEAD" {
+        return None;
+    }
+
+    dirs.iter()
+        .filter(|dir| dir.namespace == req.namespace)
+        .filter_map(|dir| Some((dir, relative_path(&dir.path_prefix, &req.path)?)))
+        .find_map(|(dir, relative)| {
+            let file = match resolve(dir, &relative) {
+                Ok(file) => file?,
+                Err(()) => return Some(status_response(403)),
+            };
+            let body = std::fs::read(&file).ok()?;
+            let mut response = status_response(200);
+            response.headers = Some(vec![(
+                "content-type".to_string(),
+                content_type(&file).to_string(),
+            )]);
+            response.body = Some(body);
+            Some(response)
+        })
+}
+
+/// Returns the percent-decoded request path below the path prefix, if the request path starts
+/// with the prefix.
+fn relative_path(prefix: &str, path: &str) -> Option<String> {
+    let prefix = prefix.trim_end_matches('/');
+    let rest = path.strip_prefix(prefix)?;
+    if !rest.is_empty() && !rest.starts_with('/') {
+        return None;
+    }
+    percent_decode(rest)
+}
+
+/// Returns the file that a relative request path refers to. Returns an error if the path
+/// escapes the root directory and `None` if there is no such file.
+fn resolve(dir: &StaticDirectory, relative: &str) -> Result<Option<PathBuf>, ()> {
+    let mut file = PathBuf::from(&dir.root);
+    for component in Path::new(relative.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(segment) => file.push(segment),
+            Component::CurDir => {}
+            _ => return Err(()),
+        }
+    }
+
+    if relative.is_empty() || relative.ends_with('/') {
+        return Ok(dir.index_file.as_ref().map(|index| file.join(index)));
+    }
+    Ok(Some(file))
+}
+
+fn percent_decode(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'%' => {
+                let hex = std::str::from_utf8(bytes.get(idx + 1..idx + 3)?).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                idx += 3;
+            }
+            byte => {
+                decoded.push(byte);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+fn status_response(status: u16) -> MockServerHttpResponse {
+    let mut response = MockServerHttpResponse::new();
+    response.status = Some(status);
+    response
+}
+
+/// Returns the content type of a file based on its extension.
+fn content_type(file: &Path) -> &'static str {
+    let extension = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("html") | Some("htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") | Some("mjs") => "text/javascript; charset=utf-8",
+        Some("json") | Some("map") => "application/json",
+        Some("xml") => "application/xml",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("csv") => "text/csv; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("wasm") => "application/wasm",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::common::data::{HttpMockRequest, StaticDirectory};
+    use crate::server::static_files::{content_type, relative_path, serve};
+
+    fn dir(index_file: Option<&str>) -> StaticDirectory {
+        StaticDirectory {
+            path_prefix: "/assets".to_string(),
+            root: "tests/resources/static".to_string(),
+            index_file: index_file.map(|f| f.to_string()),
+            namespace: None,
+        }
+    }
+
+    fn status(dirs: &[StaticDirectory], method: &str, path: &str) -> Option<u16> {
+        let req = HttpMockRequest::new(method.to_string(), path.to_string());
+        serve(dirs, &req).map(|r| r.status.unwrap())
+    }
+
+    #[test]
+    fn relative_path_test() {
+        assert_eq!(
+            relative_path("/assets", "/assets/a.css"),
+            Some("/a.css".into())
+        );
+        assert_eq!(
+            relative_path("/assets/", "/assets/a.css"),
+            Some("/a.css".into())
+        );
+        assert_eq!(relative_path("/assets", "/assets"), Some("".into()));
+        assert_eq!(relative_path("/assets", "/assets%2Fa"), None);
+        assert_eq!(relative_path("/assets", "/assetsx/a.css"), None);
+        assert_eq!(relative_path("/", "/a%20b.css"), Some("/a b.css".into()));
+    }
+
+    #[test]
+    fn serve_test() {
+        let dirs = vec![dir(Some("index.html"))];
+
+        assert_eq!(status(&dirs, "GET", "/assets/css/style.css"), Some(200));
+        assert_eq!(status(&dirs, "HEAD", "/assets/css/style.css"), Some(200));
+        assert_eq!(status(&dirs, "GET", "/assets/"), Some(200));
+        assert_eq!(status(&dirs, "GET", "/assets/css/"), None);
+        assert_eq!(status(&dirs, "GET", "/assets/missing.css"), None);
+        assert_eq!(status(&dirs, "POST", "/assets/css/style.css"), None);
+        assert_eq!(status(&dirs, "GET", "/other/css/style.css"), None);
+        assert_eq!(status(&[dir(None)], "GET", "/assets/"), None);
+    }
+
+    #[test]
+    fn directory_traversal_test() {
+        let dirs = vec![dir(None)];
+
+        assert_eq!(
+            status(&dirs, "GET", "/assets/../simple_body.txt"),
+            Some(403)
+        );
+        assert_eq!(
+            status(&dirs, "GET", "/assets/css/%2E%2E/%2E%2E/simple_body.txt"),
+            Some(403)
+        );
+        assert_eq!(
+            status(&dirs, "GET", "/assets/css/..%2f..%2fsimple_body.txt"),
+            Some(403)
+        );
+    }
+
+    #[test]
+    fn content_type_test() {
+        assert_eq!(
+            content_type(Path::new("a/index.HTML")),
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(
+            content_type(Path::new("app.js")),
+            "text/javascript; charset=utf-8"
+        );
+        assert_eq!(content_type(Path::new("logo.png")), "image/png");
+        assert_eq!(
+            content_type(Path::new("LICENSE")),
+            "application/octet-stream"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! Counters for the requests that the mock server handled. All counters are atomics, so that
+//! collecting statistics does not slow down request handling.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::Duration;
+
+use crate::common::data::{LatencyStats, ServerStats};
+
+/// The number of latency buckets per doubling of the latency. Percentiles are therefore
+/// accurate to about 19% of their value.
+const BUCKETS_PER_DOUBLING: i32 = 4;
+
+/// The number of latency buckets. The smallest bucket ends at 1µs and the largest at about
+/// 33s. Longer latencies are counted in an additional overflow bucket.
+const LATENCY_BUCKETS: usize = 101;
+
+/// The number of response status counters. Status codes are three digit numbers.
+const STATUS_COUNTERS: usize = 1000;
+
+pub(crate) struct StatsCounters {
+    requests: AtomicU64,
+    unmatched: AtomicU64,
+    buffered_body_bytes: AtomicU64,
+    statuses: Vec<AtomicU64>,
+    latency_buckets: Vec<AtomicU64>,
+    latency_sum_nanos: AtomicU64,
+    latency_max_nanos: AtomicU64,
+}
+
+impl StatsCounters {
+    pub fn new() -> Self {
+        Self {
+            requests: AtomicU64::new(0),
+            unmatched: AtomicU64::new(0),
+            buffered_body_bytes: AtomicU64::new(0),
+            statuses: (0..STATUS_COUNTERS).map(|_| AtomicU64::new(0)).collect(),
+            latency_buckets: (0..=LATENCY_BUCKETS).map(|_| AtomicU64::new(0)).collect(),
+            latency_sum_nanos: AtomicU64::new(0),
+            latency_max_nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Counts a response that was sent along with the time it took to handle the request.
+    pub fn record_response(&self, status: u16, latency: Duration) {
+        self.requests.fetch_add(1, Relaxed);
+        if let Some(counter) = self.statuses.get(status as usize) {
+            counter.fetch_add(1, Relaxed);
+        }
+
+        let nanos = latency.as_nanos().min(u64::MAX as u128) as u64;
+        self.latency_buckets[bucket_index(nanos)].fetch_add(1, Relaxed);
+        self.latency_sum_nanos.fetch_add(nanos, Relaxed);
+        self.latency_max_nanos.fetch_max(nanos, Relaxed);
+    }
+
+    /// Counts a request that did not match any mock.
+    pub fn record_unmatched(&self) {
+        self.unmatched.fetch_add(1, Relaxed);
+    }
+
+    /// Counts request body bytes that were read into memory.
+    pub fn record_buffered_body(&self, bytes: usize) {
+        self.buffered_body_bytes.fetch_add(bytes as u64, Relaxed);
+    }
+
+    /// Returns the current statistics. The hit counts of the mocks are not tracked by the
+    /// counters and need to be provided.
+    pub fn snapshot(&self, mock_hits: BTreeMap<usize, u64>) -> ServerStats {
+        let status_counts = self
+            .statuses
+            .iter()
+            .enumerate()
+            .map(|(status, counter)| (status as u16, counter.load(Relaxed)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        let buckets: Vec<u64> = self
+            .latency_buckets
+            .iter()
+            .map(|counter| counter.load(Relaxed))
+            .collect();
+        let count: u64 = buckets.iter().sum();
+        let sum_nanos = self.latency_sum_nanos.load(Relaxed);
+        let max = Duration::from_nanos(self.latency_max_nanos.load(Relaxed));
+
+        ServerStats {
+            total_requests: self.requests.load(Relaxed),
+            unmatched_requests: self.unmatched.load(Relaxed),
+            buffered_body_bytes: self.buffered_body_bytes.load(Relaxed),
+            mock_hits,
+            status_counts,
+            latency: LatencyStats {
+                count,
+                total: Duration::from_nanos(sum_nanos),
+                mean: match count {
+                    0 => Duration::from_secs(0),
+                    _ => Duration::from_nanos(sum_nanos / count),
+                },
+                p50: percentile(&buckets, 0.5).min(max),
+                p90: percentile(&buckets, 0.9).min(max),
+                p99: percentile(&buckets, 0.99).min(max),
+                max,
+            },
+        }
+    }
+
+    /// Sets all counters to zero.
+    pub fn reset(&self) {
+        let counters = self.statuses.iter().chain(self.latency_buckets.iter());
+        for counter in counters {
+            counter.store(0, Relaxed);
+        }
+        self.requests.store(0, Relaxed);
+        self.unmatched.store(0, Relaxed);
+        self.buffered_body_bytes.store(0, Relaxed);
+        self.latency_sum_nanos.store(0, Relaxed);
+        self.latency_max_nanos.store(0, Relaxed);
+    }
+}
+
+/// Returns the upper bound of a latency bucket in nanoseconds.
+fn bucket_bound(index: usize) -> u64 {
+    (1000.0 * 2f64.powf(index as f64 / BUCKETS_PER_DOUBLING as f64)) as u64
+}
+
+/// Returns the index of the bucket that a latency is counted in.
+fn bucket_index(nanos: u64) -> usize {
+    let estimate = (nanos as f64 / 1000.0).log2() * BUCKETS_PER_DOUBLING as f64;
+    let mut index = (estimate.ceil().max(0.0) as usize).min(LATENCY_BUCKETS);
+    // Corrects rounding errors of the estimate at the bucket bounds.
+    while index > 0 && nanos <= bucket_bound(index - 1) {
+        index -= 1;
+    }
+    while index < LATENCY_BUCKETS && nanos > bucket_bound(index) {
+        index += 1;
+    }
+    index
+}
+
+/// Returns the upper bound of the bucket that contains the provided quantile of all latencies.
+fn percentile(buckets: &[u64], quantile: f64) -> Duration {
+    let count: u64 = buckets.iter().sum();
+    if count == 0 {
+        return Duration::from_secs(0);
+    }
+
+    let rank = ((count as f64) * quantile).ceil().max(1.0) as u64;
+    let mut seen = 0;
+    for (index, bucket_count) in buckets.iter().enumerate() {
+        seen += bucket_count;
+        if seen >= rank {
+            return Duration::from_nanos(match index < LATENCY_BUCKETS {
+                true => bucket_bound(index),
+                false => u64::MAX,
+            });
+        }
+    }
+    Duration::from_nanos(u64::MAX)
+}
+
+/// Renders statistics in the Prometheus text exposition format (version 0.0.4).
+pub(crate) fn to_prometheus(stats: &ServerStats) -> String {
+    let mut text = String::new();
+
+    text.push_str("# HELP httpmock_requests_total The number of requests that were served.\n");
+    text.push_str("# TYPE httpmock_requests_total counter\n");
+    writeln!(text, "httpmock_requests_total {}", stats.total_requests).unwrap();
+
+    text.push_str(
+        "# HELP httpmock_unmatched_requests_total The number of requests that did not match any mock.\n",
+    );
+    text.push_str("# TYPE httpmock_unmatched_requests_total counter\n");
+    writeln!(
+        text,
+        "httpmock_unmatched_requests_total {}",
+        stats.unmatched_requests
+    )
+    .unwrap();
+
+    text.push_str(
+        "# HELP httpmock_buffered_body_bytes_total The number of request body bytes that were read into memory.\n",
+    );
+    text.push_str("# TYPE httpmock_buffered_body_bytes_total counter\n");
+    writeln!(
+        text,
+        "httpmock_buffered_body_bytes_total {}",
+        stats.buffered_body_bytes
+    )
+    .unwrap();
+
+    text.push_str("# HELP httpmock_mock_hits_total The number of requests that matched a mock.\n");
+    text.push_str("# TYPE httpmock_mock_hits_total counter\n");
+    for (mock_id, hits) in &stats.mock_hits {
+        writeln!(
+            text,
+            "httpmock_mock_hits_total{{mock_id=\"{}\"}} {}",
+            mock_id, hits
+        )
+        .unwrap();
+    }
+
+    text.push_str("# HELP httpmock_responses_total The number of responses by status code.\n");
+    text.push_str("# TYPE httpmock_responses_total counter\n");
+    for (status, count) in &stats.status_counts {
+        writeln!(
+            text,
+            "httpmock_responses_total{{status=\"{}\"}} {}",
+            status, count
+        )
+        .unwrap();
+    }
+
+    let latency = &stats.latency;
+    text.push_str(
+        "# HELP httpmock_request_duration_seconds The time it took to handle a request.\n",
+    );
+    text.push_str("# TYPE httpmock_request_duration_seconds summary\n");
+    for (quantile, value) in &[
+        ("0.5", latency.p50),
+        ("0.9", latency.p90),
+        ("0.99", latency.p99),
+    ] {
+        writeln!(
+            text,
+            "httpmock_request_duration_seconds{{quantile=\"{}\"}} {}",
+            quantile,
+            value.as_secs_f64()
+        )
+        .unwrap();
+    }
+    writeln!(
+        text,
+        "httpmock_request_duration_seconds_sum {}",
+        latency.total.as_secs_f64()
+    )
+    .unwrap();
+    writeln!(
+        text,
+        "httpmock_request_duration_seconds_count {}",
+        latency.count
+    )
+    .unwrap();
+
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    use crate::server::stats::{bucket_bound, bucket_index, to_prometheus, StatsCounters};
+
+    #[test]
+    fn bucket_index_test() {
+        assert_eq!(bucket_index(0), 0);
+        assert_eq!(bucket_index(1000), 0);
+        assert_eq!(bucket_index(1001), 1);
+        assert_eq!(bucket_index(2000), 4);
+        assert_eq!(bucket_index(u64::MAX), 101);
+        assert!(bucket_bound(100) > 30_000_000_000);
+    }
+
+    #[test]
+    fn snapshot_test() {
+        // Arrange
+        let counters = StatsCounters::new();
+        for millis in 1..=100 {
+            counters.record_response(200, Duration::from_millis(millis));
+        }
+        counters.record_response(404, Duration::from_millis(1));
+        counters.record_unmatched();
+        counters.record_buffered_body(512);
+        counters.record_buffered_body(512);
+
+        // Act
+        let stats = counters.snapshot(BTreeMap::new());
+
+        // Assert
+        assert_eq!(stats.total_requests, 101);
+        assert_eq!(stats.unmatched_requests, 1);
+        assert_eq!(stats.buffered_body_bytes, 1024);
+        assert_eq!(stats.status_counts.get(&200), Some(&100));
+        assert_eq!(stats.status_counts.get(&404), Some(&1));
+        assert_eq!(stats.latency.count, 101);
+        assert_eq!(stats.latency.max, Duration::from_millis(100));
+
+        let within = |value: Duration, expected: u64| {
+            let expected = Duration::from_millis(expected);
+            value >= expected && value < expected.mul_f64(1.2)
+        };
+        assert!(within(stats.latency.p50, 50), "{:?}", stats.latency.p50);
+        assert!(within(stats.latency.p90, 90), "{:?}", stats.latency.p90);
+        assert!(within(stats.latency.p99, 99), "{:?}", stats.latency.p99);
+
+        counters.reset();
+        let stats = counters.snapshot(BTreeMap::new());
+        assert_eq!(stats.total_requests, 0);
+        assert_eq!(stats.buffered_body_bytes, 0);
+    }
+
+    #[test]
+    fn to_prometheus_test() {
+        // Arrange
+        let counters = StatsCounters::new();
+        counters.record_response(200, Duration::from_micros(1));
+        counters.record_response(404, Duration::from_micros(1));
+        counters.record_unmatched();
+        counters.record_buffered_body(7);
+        let mock_hits = vec![(3, 1)].into_iter().collect();
+
+        // Act
+        let text = to_prometheus(&counters.snapshot(mock_hits));
+
+        // Assert
+        assert!(
+            text.contains("# TYPE httpmock_requests_total counter\nhttpmock_requests_total 2\n")
+        );
+        assert!(text.contains("httpmock_unmatched_requests_total 1\n"));
+        assert!(text.contains("httpmock_buffered_body_bytes_total 7\n"));
+        assert!(text.contains("httpmock_mock_hits_total{mock_id=\"3\"} 1\n"));
+        assert!(text.contains("httpmock_responses_total{status=\"200\"} 1\n"));
+        assert!(text.contains("httpmock_responses_total{status=\"404\"} 1\n"));
+        assert!(text.contains("httpmock_request_duration_seconds{quantile=\"0.99\"} 0.000001\n"));
+        assert!(text.contains("httpmock_request_duration_seconds_sum 0.000002\n"));
+        assert!(text.contains("httpmock_request_duration_seconds_count 2\n"));
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::sync::{Arc, Mutex};
+
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, DnType, IsCa, KeyPair, KeyUsagePurpose,
+};
+use tokio_rustls::rustls::crypto::ring::default_provider;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tokio_rustls::rustls::{Error as RustlsError, RootCertStore, ServerConfig};
+use x509_parser::prelude::{FromDer, X509Certificate};
+
+use crate::common::data::ClientCertificate;
+
+/// Describes how the server verifies client certificates.
+pub(crate) struct ClientAuthConfig {
+    /// The PEM encoded CA certificates that client certificates must be issued by.
+    pub ca_pem: Vec<u8>,
+    /// Whether clients that do not present a certificate are rejected.
+    pub required: bool,
+}
+
+/// Builds a TLS server configuration from a PEM encoded certificate chain and private key.
+/// The certificate chain must start with the server certificate, followed by all
+/// intermediate certificates that should be presented to clients.
+pub(crate) fn build_tls_config(
+    cert_chain_pem: &[u8],
+    key_pem: &[u8],
+    alpn_protocols: &[String],
+    client_auth: Option<&ClientAuthConfig>,
+) -> Result<ServerConfig, String> {
+    let cert_chain = parse_certificates(cert_chain_pem)?;
+    let key = parse_private_key(key_pem)?;
+    let provider = Arc::new(default_provider());
+
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Cannot configure TLS protocol versions: {}", e))?;
+
+    let builder = match client_auth {
+        None => builder.with_no_client_auth(),
+        Some(client_auth) => {
+            let mut roots = RootCertStore::empty();
+            for cert in parse_certificates(&client_auth.ca_pem)? {
+                roots
+                    .add(cert)
+                    .map_err(|e| format!("Invalid client CA certificate: {}", e))?;
+            }
+
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+            let verifier = match client_auth.required {
+                true => verifier.build(),
+                false => verifier.allow_unauthenticated().build(),
+            }
+            .map_err(|e| format!("Cannot create client certificate verifier: {}", e))?;
+
+            builder.with_client_cert_verifier(verifier)
+        }
+    };
+
+    let mut config = builder
+        .with_single_cert(cert_chain, key)
+        .map_err(|e| match e {
+            RustlsError::InconsistentKeys(_) => {
+                "The TLS private key does not match the server certificate".to_string()
+            }
+            e => format!("Invalid TLS certificate or private key: {}", e),
+        })?;
+
+    config.alpn_protocols = alpn_protocols
+        .iter()
+        .map(|p| p.as_bytes().to_vec())
+        .collect();
+
+    Ok(config)
+}
+
+/// A certificate authority that issues certificates for the hosts that clients connect to
+/// through a proxy mock server. The certificate authority is generated on startup, so clients
+/// need to trust it explicitly.
+pub(crate) struct ProxyCa {
+    cert: Certificate,
+    key: KeyPair,
+    server_configs: Mutex<HashMap<String, Arc<ServerConfig>>>,
+}
+
+impl ProxyCa {
+    pub fn generate() -> Result<Self, String> {
+        let mut params = CertificateParams::new(Vec::<String>::new())
+            .map_err(|e| format!("Cannot create proxy CA certificate: {}", e))?;
+        params
+            .distinguished_name
+            .push(DnType::CommonName, "httpmock proxy CA");
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.key_usages = vec![
+            KeyUsagePurpose::KeyCertSign,
+            KeyUsagePurpose::CrlSign,
+            KeyUsagePurpose::DigitalSignature,
+        ];
+
+        let key =
+            KeyPair::generate().map_err(|e| format!("Cannot generate proxy CA key: {}", e))?;
+        let cert = params
+            .self_signed(&key)
+            .map_err(|e| format!("Cannot create proxy CA certificate: {}", e))?;
+
+        Ok(Self {
+            cert,
+            key,
+            server_configs: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns the PEM encoded certificate of the certificate authority.
+    pub fn cert_pem(&self) -> String {
+        self.cert.pem()
+    }
+
+    /// Returns a TLS server configuration with a certificate for the given host. Certificates
+    /// are issued once per host.
+    pub fn server_config(&self, host: &str) -> Result<Arc<ServerConfig>, String> {
+        let mut server_configs = self.server_configs.lock().unwrap();
+        if let Some(config) = server_configs.get(host) {
+            return Ok(config.clone());
+        }
+
+        let mut params = CertificateParams::new(vec![host.to_string()])
+            .map_err(|e| format!("Cannot create certificate for host {}: {}", host, e))?;
+        params.distinguished_name.push(DnType::CommonName, host);
+
+        let key = KeyPair::generate()
+            .map_err(|e| format!("Cannot generate key for host {}: {}", host, e))?;
+        let cert = params
+            .signed_by(&key, &self.cert, &self.key)
+            .map_err(|e| format!("Cannot create certificate for host {}: {}", host, e))?;
+
+        let cert_chain_pem = format!("{}{}", cert.pem(), self.cert.pem());
+        let alpn_protocols = ["h2".to_string(), "http/1.1".to_string()];
+        let config = Arc::new(build_tls_config(
+            cert_chain_pem.as_bytes(),
+            key.serialize_pem().as_bytes(),
+            &alpn_protocols,
+            None,
+        )?);
+
+        server_configs.insert(host.to_string(), config.clone());
+        Ok(config)
+    }
+}
+
+fn parse_certificates(pem: &[u8]) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(pem))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Cannot parse PEM encoded TLS certificate: {}", e))?;
+
+    if certs.is_empty() {
+        return Err("No certificate found in the provided TLS certificate PEM data".to_string());
+    }
+
+    Ok(certs)
+}
+
+fn parse_private_key(pem: &[u8]) -> Result<PrivateKeyDer<'static>, String> {
+    match rustls_pemfile::private_key(&mut BufReader::new(pem)) {
+        Ok(Some(key)) => Ok(key),
+        Ok(None) => Err("No private key found in the provided TLS key PEM data".to_string()),
+        Err(e) => Err(format!("Cannot parse PEM encoded TLS private key: {}", e)),
+    }
+}
+
+/// Extracts the subject information from a verified client certificate.
+pub(crate) fn parse_client_certificate(
+    der: &CertificateDer<'_>,
+) -> Result<ClientCertificate, String> {
+    let (_, cert) = X509Certificate::from_der(der.as_ref())
+        .map_err(|e| format!("Cannot parse client certificate: {}", e))?;
+
+    let common_name = cert
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .map(|cn| cn.to_string());
+
+    Ok(ClientCertificate {
+        subject: cert.subject().to_string(),
+        common_name,
+        der: der.as_ref().to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::server::tls::build_tls_config;
+
+    #[test]
+    fn build_tls_config_no_certificate_test() {
+        // Act
+        let result = build_tls_config(b"not a certificate", b"", &[], None);
+
+        // Assert
+        assert_eq!(
+            result.err().unwrap(),
+            "No certificate found in the provided TLS certificate PEM data"
+        );
+    }
+
+    #[test]
+    fn build_tls_config_malformed_certificate_test() {
+        // Arrange
+        let pem = b"-----BEGIN CERTIFICATE-----\n%%%%\n-----END CERTIFICATE-----\n";
+
+        // Act
+        let result = build_tls_config(pem, b"", &[], None);
+
+        // Assert
+        assert!(result
+            .err()
+            .unwrap()
+            .starts_with("Cannot parse PEM encoded TLS certificate"));
+    }
+}
@@ -1,17 +1,20 @@
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::str::FromStr;
+use std::sync::atomic::Ordering::Relaxed;
 use std::sync::Arc;
+use std::time::SystemTime;
 
-#[cfg(feature = "cookies")]
-use basic_cookies::Cookie;
 use serde_json::Value;
 
 use crate::common::data::{
-    ActiveMock, ClosestMatch, HttpMockRequest, Mismatch, MockDefinition, MockServerHttpResponse,
-    RequestRequirements,
+    describe_mock, ActiveMock, ClosestMatch, HttpMockRequest, MatchStage, Mismatch, MockDefinition,
+    MockServerHttpResponse, Reason, RecordedExchange, RequestRequirements, ServerStats,
+    StaticDirectory, TrafficEntry,
 };
-use crate::server::matchers::Matcher;
+use crate::common::har;
+use crate::server::matchers::{Matcher, MatchingRequest, ParsedRequest};
+use crate::server::static_files;
 use crate::server::util::{StringTreeMapExtension, TreeMapExtension};
 use crate::server::MockServerState;
 
@@ -32,20 +35,96 @@ pub(crate) fn add_new_mock(
     }
 
     let mock_id = state.create_new_id();
+    #[cfg(feature = "tracing")]
+    tracing::debug!(mock_id, "Adding new mock");
+    #[cfg(not(feature = "tracing"))]
     log::debug!("Adding new mock with ID={}", mock_id);
 
-    let mut mocks = state.mocks.lock().unwrap();
+    let mut mocks = state.mocks.write().unwrap();
     mocks.insert(mock_id, ActiveMock::new(mock_id, mock_def, is_static));
 
     Result::Ok(mock_id)
 }
 
+/// Adds a new mock like [add_new_mock](fn.add_new_mock.html), unless a mock has already been
+/// added with the same idempotency key. The ID of that mock is returned in this case, so that
+/// clients can repeat requests whose response got lost. The keys of the last 100 mocks are
+/// remembered.
+pub(crate) fn add_new_mock_once(
+    state: &MockServerState,
+    mock_def: MockDefinition,
+    idempotency_key: &str,
+) -> Result<usize, String> {
+    // The lock is held while the mock is added, so that concurrent repetitions wait.
+    let mut keys = state.idempotency_keys.lock().unwrap();
+    if let Some((_, mock_id)) = keys.iter().find(|(key, _)| key == idempotency_key) {
+        return Ok(*mock_id);
+    }
+
+    let mock_id = add_new_mock(state, mock_def, false)?;
+    if keys.len() >= 100 {
+        keys.pop_front();
+    }
+    keys.push_back((idempotency_key.to_string(), mock_id));
+    Ok(mock_id)
+}
+
+/// Adds a directory whose files are served for requests that do not match any mock.
+pub(crate) fn add_static_dir(state: &MockServerState, dir: StaticDirectory) -> Result<(), String> {
+    if !dir.path_prefix.starts_with('/') {
+        return Err(format!(
+            "Validation error: the path prefix '{}' must start with a slash",
+            dir.path_prefix
+        ));
+    }
+    if dir.root.is_empty() {
+        return Err("Validation error: the root directory must not be empty".to_string());
+    }
+
+    log::debug!("Serving directory '{}' at '{}'", dir.root, dir.path_prefix);
+    state.static_dirs.write().unwrap().push(dir);
+    Ok(())
+}
+
+/// Replaces the static mocks with the provided IDs by new static mocks. Both happens while
+/// holding the lock on the mocks, so that requests either see all old or all new mocks.
+/// Returns the IDs of the new mocks.
+pub(crate) fn replace_static_mocks(
+    state: &MockServerState,
+    old_ids: &[usize],
+    mock_defs: Vec<MockDefinition>,
+) -> Result<Vec<usize>, String> {
+    for mock_def in &mock_defs {
+        validate_mock_definition(mock_def).map_err(|e| format!("Validation error: {}", e))?;
+    }
+
+    let mut mocks = state.mocks.write().unwrap();
+    for id in old_ids {
+        mocks.remove(id);
+    }
+
+    let ids = mock_defs
+        .into_iter()
+        .map(|mock_def| {
+            let mock_id = state.create_new_id();
+            mocks.insert(mock_id, ActiveMock::new(mock_id, mock_def, true));
+            mock_id
+        })
+        .collect();
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(?old_ids, new_ids = ?ids, "Replaced static mocks");
+    #[cfg(not(feature = "tracing"))]
+    log::debug!("Replaced static mocks {:?} with {:?}", old_ids, ids);
+    Ok(ids)
+}
+
 /// Reads exactly one mock object.
 pub(crate) fn read_one_mock(
     state: &MockServerState,
     id: usize,
 ) -> Result<Option<ActiveMock>, String> {
-    let mocks = state.mocks.lock().unwrap();
+    let mocks = state.mocks.read().unwrap();
     let result = mocks.get(&id);
     match result {
         Some(found) => Ok(Some(found.clone())),
@@ -53,9 +132,86 @@ pub(crate) fn read_one_mock(
     }
 }
 
+/// Reads the number of requests that matched a mock without copying the mock, or `None` if
+/// there is no mock with this ID.
+pub(crate) fn read_mock_hits(state: &MockServerState, id: usize) -> Option<usize> {
+    let mocks = state.mocks.read().unwrap();
+    mocks.get(&id).map(|mock| mock.call_counter.load(Relaxed))
+}
+
+/// Reads all mock objects, ordered by their ID. If a namespace is given, only the mocks of
+/// this namespace are returned.
+pub(crate) fn read_all_mocks(state: &MockServerState, namespace: Option<&str>) -> Vec<ActiveMock> {
+    let mocks = state.mocks.read().unwrap();
+    mocks
+        .values()
+        .filter(|m| in_namespace(&m.definition.request.namespace, namespace))
+        .cloned()
+        .collect()
+}
+
+/// Reads the requests from the history that match a mock, starting with the oldest request.
+/// Returns `None` if there is no mock with the given id.
+pub(crate) fn read_mock_requests(
+    state: &MockServerState,
+    id: usize,
+) -> Option<Vec<HttpMockRequest>> {
+    let requirements = state
+        .mocks
+        .read()
+        .unwrap()
+        .get(&id)?
+        .matched_request()
+        .clone();
+    let history = state.history.lock().unwrap();
+    Some(
+        history
+            .iter()
+            .filter(|r| request_matches(state, (*r).clone(), &requirements))
+            .map(|r| HttpMockRequest::clone(r))
+            .collect(),
+    )
+}
+
+/// Reads the requests from the history that do not match any of the current mocks, starting
+/// with the oldest request. If a namespace is given, only the requests that were sent to this
+/// namespace are returned.
+pub(crate) fn read_unmatched_requests(
+    state: &MockServerState,
+    namespace: Option<&str>,
+) -> Vec<HttpMockRequest> {
+    let mocks = state.mocks.read().unwrap();
+    let history = state.history.lock().unwrap();
+    history
+        .iter()
+        .filter(|r| in_namespace(&r.namespace, namespace))
+        .filter(|r| {
+            let parsed = ParsedRequest::new(r);
+            !mocks
+                .values()
+                .any(|m| parsed_request_matches(state, &parsed, m.matched_request(), false))
+        })
+        .map(|r| HttpMockRequest::clone(r))
+        .collect()
+}
+
+/// Reads the request history, starting with the oldest request. If a namespace is given, only
+/// the requests that were sent to this namespace are returned.
+pub(crate) fn read_history(
+    state: &MockServerState,
+    namespace: Option<&str>,
+) -> Vec<HttpMockRequest> {
+    let history = state.history.lock().unwrap();
+    history
+        .iter()
+        .filter(|r| in_namespace(&r.namespace, namespace))
+        .map(|r| HttpMockRequest::clone(r))
+        .collect()
+}
+
 /// Deletes one mock by id. Returns the number of deleted elements.
 pub(crate) fn delete_one_mock(state: &MockServerState, id: usize) -> Result<bool, String> {
-    let mut mocks = state.mocks.lock().unwrap();
+    let mut mocks = state.mocks.write().unwrap();
     if let Some(m) = mocks.get(&id) {
         if m.is_static {
             return Err(format!("Cannot delete static mock with ID {}", id));
@@ -63,92 +219,350 @@ pub(crate) fn delete_one_mock(state: &MockServerState, id: usize) -> Result<bool
     }
     let result = mocks.remove(&id);
 
+    #[cfg(feature = "tracing")]
+    tracing::debug!(mock_id = id, "Deleted mock");
+    #[cfg(not(feature = "tracing"))]
     log::debug!("Deleted mock with id={}", id);
     Result::Ok(result.is_some())
 }
 
-/// Deletes all mocks.
-pub(crate) fn delete_all_mocks(state: &MockServerState) {
-    let mut mocks = state.mocks.lock().unwrap();
+/// Replaces the definition of a mock and resets its hit counter. Returns false if there is no
+/// mock with the given id.
+pub(crate) fn update_mock(
+    state: &MockServerState,
+    id: usize,
+    mock_def: MockDefinition,
+) -> Result<bool, String> {
+    if let Err(error_msg) = validate_mock_definition(&mock_def) {
+        return Err(format!("Validation error: {}", error_msg));
+    }
+
+    let mut mocks = state.mocks.write().unwrap();
+    match mocks.get(&id) {
+        None => return Ok(false),
+        Some(m) if m.is_static => return Err(format!("Cannot update static mock with ID {}", id)),
+        Some(_) => {}
+    }
+    mocks.insert(id, ActiveMock::new(id, mock_def, false));
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(mock_id = id, "Updated mock");
+    #[cfg(not(feature = "tracing"))]
+    log::debug!("Updated mock with id={}", id);
+    Ok(true)
+}
+
+/// Enables or disables a mock without resetting its hit counter. Returns false if there is no
+/// mock with the given id. Static mocks can be enabled and disabled as well, so that static
+/// mocks that are declared `disabled` can be switched on.
+pub(crate) fn set_mock_enabled(state: &MockServerState, id: usize, enabled: bool) -> bool {
+    let mut mocks = state.mocks.write().unwrap();
+    match mocks.get_mut(&id) {
+        None => return false,
+        Some(m) => m.definition.request.disabled = !enabled,
+    }
+
+    log::debug!(
+        "{} mock with id={}",
+        if enabled { "Enabled" } else { "Disabled" },
+        id
+    );
+    true
+}
+
+/// Deletes all mocks and static directories. If a namespace is given, only those of this
+/// namespace are deleted.
+pub(crate) fn delete_all_mocks(state: &MockServerState, namespace: Option<&str>) {
+    let mut mocks = state.mocks.write().unwrap();
     let ids: Vec<usize> = mocks
         .iter()
-        .filter(|(k, v)| !v.is_static)
+        .filter(|(_, v)| !v.is_static && in_namespace(&v.definition.request.namespace, namespace))
         .map(|(k, v)| *k)
         .collect();
 
     ids.iter().for_each(|k| {
         mocks.remove(k);
     });
-
+    state
+        .static_dirs
+        .write()
+        .unwrap()
+        .retain(|dir| !in_namespace(&dir.namespace, namespace));
+
+    #[cfg(feature = "tracing")]
+    tracing::debug!(mock_ids = ?ids, "Deleted all mocks");
+    #[cfg(not(feature = "tracing"))]
     log::trace!("Deleted all mocks");
 }
 
-/// Deletes the request history.
-pub(crate) fn delete_history(state: &MockServerState) {
+/// Deletes all mocks that are not static and the request history, or only those of a
+/// namespace.
+pub(crate) fn reset(state: &MockServerState, namespace: Option<&str>) {
+    delete_all_mocks(state, namespace);
+    delete_history(state, namespace);
+}
+
+/// Deletes the request history and resets the number of requests that were rejected because
+/// the server was overloaded as well as the request statistics. If a namespace is given, only
+/// the requests that were sent to this namespace are deleted and the server wide counters are
+/// kept.
+pub(crate) fn delete_history(state: &MockServerState, namespace: Option<&str>) {
+    if let Some(namespace) = namespace {
+        let namespace = Some(namespace.to_string());
+        state
+            .history
+            .lock()
+            .unwrap()
+            .retain(|r| r.namespace != namespace);
+        state
+            .traffic
+            .lock()
+            .unwrap()
+            .retain(|e| e.request.namespace != namespace);
+        log::trace!("Deleted request history of namespace {:?}", namespace);
+        return;
+    }
+
     let mut mocks = state.history.lock().unwrap();
     mocks.clear();
+    state.traffic.lock().unwrap().clear();
+    state.overload_rejections.store(0, Relaxed);
+    state.stats.reset();
     log::trace!("Deleted request history");
 }
 
+/// Returns true if a mock or request belongs to the namespace, or if no namespace is given.
+fn in_namespace(value: &Option<String>, namespace: Option<&str>) -> bool {
+    namespace.map_or(true, |namespace| value.as_deref() == Some(namespace))
+}
+
 /// Finds a mock that matches the current request and serve a response according to the mock
-/// specification. If no mock is found, an empty result is being returned.
+/// specification. Requests that do not match any mock are served from the static directories.
+/// If no mock or file is found, an empty result is being returned.
 pub(crate) fn find_mock(
     state: &MockServerState,
-    req: HttpMockRequest,
-) -> Result<Option<MockServerHttpResponse>, String> {
-    let req = Arc::new(req);
-    {
-        let mut history = state.history.lock().unwrap();
-        if history.len() > 100 {
-            history.remove(0);
-        }
-        history.push(req.clone());
-    }
+    req: Arc<HttpMockRequest>,
+) -> Result<Option<FoundResponse>, String> {
+    record_request(state, req.clone());
 
-    let mut mocks = state.mocks.lock().unwrap();
+    // Matching only requires a read lock, so that requests are matched concurrently.
+    let mocks = state.mocks.read().unwrap();
 
+    let parsed = ParsedRequest::new(&req);
+    let now = SystemTime::now();
     let result = mocks
         .values()
-        .find(|&mock| request_matches(&state, req.clone(), &mock.definition.request));
+        .filter(|mock| mock.definition.request.is_active(now))
+        .find(|&mock| parsed_request_matches(state, &parsed, mock.matched_request(), false));
 
-    let found_mock_id = match result {
-        Some(mock) => Some(mock.id),
-        None => None,
-    };
-
-    if let Some(found_id) = found_mock_id {
+    if let Some(mock) = result {
         log::debug!(
             "Matched mock with id={} to the following request: {:#?}",
-            found_id,
+            mock.id,
             req
         );
 
-        let mock = mocks.get_mut(&found_id).unwrap();
-        mock.call_counter += 1;
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("mock_id", mock.id);
 
-        return Ok(Some(mock.definition.response.clone()));
+        let request_index = mock
+            .call_counter
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+
+        return Ok(Some(FoundResponse {
+            mock: Some((mock.id, describe_mock(&mock.definition.request))),
+            response: mock.definition.response.for_request(request_index).clone(),
+        }));
+    }
+
+    drop(mocks);
+
+    let static_response = static_files::serve(&state.static_dirs.read().unwrap(), &req);
+    if let Some(response) = static_response {
+        return Ok(Some(FoundResponse {
+            mock: None,
+            response,
+        }));
     }
 
     log::debug!(
         "Could not match any mock to the following request: {:#?}",
         req
     );
+    state.stats.record_unmatched();
 
     Result::Ok(None)
 }
 
+/// Returns whether the body of a request is required to find its mock, i.e. whether any mock
+/// that matches the request apart from its body has requirements on the body.
+pub(crate) fn needs_body(state: &MockServerState, req: Arc<HttpMockRequest>) -> bool {
+    let mocks = state.mocks.read().unwrap();
+    let parsed = ParsedRequest::new(&req);
+    let now = SystemTime::now();
+    mocks
+        .values()
+        .filter(|mock| mock.definition.request.is_active(now))
+        .filter(|mock| mock.definition.request.requires_body())
+        .any(|mock| parsed_request_matches(state, &parsed, mock.matched_request(), true))
+}
+
+/// The response that was found for a request, along with the ID and the description of the
+/// mock that the request matched. Files from static directories do not belong to a mock.
+pub(crate) struct FoundResponse {
+    pub mock: Option<(usize, String)>,
+    pub response: MockServerHttpResponse,
+}
+
+/// Adds a request to the request history without matching it against any mock.
+pub(crate) fn record_request(state: &MockServerState, req: Arc<HttpMockRequest>) {
+    let mut history = state.history.lock().unwrap();
+    if history.len() > 100 {
+        history.remove(0);
+    }
+    history.push(req);
+}
+
+/// Adds a request along with the response that was sent to the traffic log, which is limited
+/// like the request history.
+pub(crate) fn record_traffic(state: &MockServerState, entry: Arc<TrafficEntry>) {
+    let mut traffic = state.traffic.lock().unwrap();
+    if traffic.len() > 100 {
+        traffic.remove(0);
+    }
+    traffic.push(entry);
+}
+
+/// Returns statistics about the served requests.
+pub(crate) fn read_stats(state: &MockServerState) -> ServerStats {
+    let mock_hits = state
+        .mocks
+        .read()
+        .unwrap()
+        .values()
+        .map(|mock| (mock.id, mock.call_counter.load(Relaxed) as u64))
+        .collect();
+    state.stats.snapshot(mock_hits)
+}
+
+/// Exports the traffic log as a HAR document. URLs use the `https` scheme if `https` is set.
+pub(crate) fn export_har(state: &MockServerState, https: bool) -> Value {
+    let traffic = state.traffic.lock().unwrap().clone();
+    har::to_har(&traffic, https)
+}
+
+/// Marks a request in the request history as forwarded to an upstream server.
+pub(crate) fn mark_forwarded(
+    state: &MockServerState,
+    req: &Arc<HttpMockRequest>,
+    upstream_status: Option<u16>,
+) {
+    let mut history = state.history.lock().unwrap();
+    if let Some(entry) = history.iter_mut().find(|entry| Arc::ptr_eq(entry, req)) {
+        let mut forwarded = HttpMockRequest::clone(entry);
+        forwarded.forwarded = true;
+        forwarded.upstream_status = upstream_status;
+        *entry = Arc::new(forwarded);
+    }
+}
+
+/// Adds a request that was forwarded to the upstream server along with the upstream response
+/// to the recordings.
+pub(crate) fn record_exchange(
+    state: &MockServerState,
+    request: Arc<HttpMockRequest>,
+    response: MockServerHttpResponse,
+) {
+    state
+        .recordings
+        .lock()
+        .unwrap()
+        .push(RecordedExchange { request, response });
+}
+
 /// Checks if a request matches a mock.
 fn request_matches(
     state: &MockServerState,
     req: Arc<HttpMockRequest>,
     mock: &RequestRequirements,
+) -> bool {
+    parsed_request_matches(state, &ParsedRequest::new(&req), mock, false)
+}
+
+/// Checks if a request matches a mock. A request that is matched against several mocks should
+/// be parsed only once. The requirements of the mock on the request body are ignored if
+/// `ignore_body` is set. The path requirements of the mock must already be normalized (see
+/// [RequestRequirements::with_normalized_paths]).
+fn parsed_request_matches(
+    state: &MockServerState,
+    req: &ParsedRequest,
+    mock: &RequestRequirements,
+    ignore_body: bool,
 ) -> bool {
     log::trace!("Matching incoming HTTP request");
-    state
-        .matchers
-        .iter()
-        .enumerate()
-        .all(|(i, x)| x.matches(&req, mock))
+    // Mocks never match requests that were sent to another namespace.
+    if req.req.namespace != mock.namespace {
+        return false;
+    }
+    let req = match matching_request(req, mock) {
+        Some(req) => req,
+        None => return false,
+    };
+    requirements_match(&state.matchers, &req, mock, ignore_body)
+}
+
+/// Checks if a request fulfills the requirements of a mock and at least one of their
+/// alternatives.
+fn requirements_match(
+    matchers: &Vec<Box<dyn Matcher + Sync + Send>>,
+    req: &MatchingRequest,
+    mock: &RequestRequirements,
+    ignore_body: bool,
+) -> bool {
+    let matched = matchers.iter().all(|matcher| {
+        if ignore_body && matcher.stage() == MatchStage::Body {
+            return true;
+        }
+        let matched = matcher.matches(req, mock);
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            matcher = matcher.entity_name(),
+            matched,
+            "Evaluated request matcher"
+        );
+        matched
+    });
+    matched
+        && mock.any_of.as_ref().map_or(true, |alternatives| {
+            alternatives
+                .iter()
+                .any(|alternative| requirements_match(matchers, req, alternative, ignore_body))
+        })
+}
+
+/// Returns the request with the base path of the mock removed from its path and the path
+/// normalized as requested by the mock. Returns `None` if the request path does not start with
+/// the base path.
+fn matching_request<'a>(
+    req: &'a ParsedRequest<'a>,
+    mock: &RequestRequirements,
+) -> Option<MatchingRequest<'a>> {
+    let path = match &mock.base_path {
+        None => req.req.path.as_str(),
+        Some(base_path) => match req.req.path.strip_prefix(base_path.as_str())? {
+            "" => "/",
+            rest if rest.starts_with('/') => rest,
+            _ => return None,
+        },
+    };
+
+    if mock.base_path.is_none() && !mock.path_case_insensitive && !mock.path_ignore_trailing_slash {
+        return Some(MatchingRequest::new(req, None).with_raw_query(mock.query_params_raw));
+    }
+    Some(
+        MatchingRequest::new(req, Some(mock.normalize_path(path)))
+            .with_raw_query(mock.query_params_raw),
+    )
 }
 
 /// Deletes the request history.
@@ -156,10 +570,12 @@ pub(crate) fn verify(
     state: &MockServerState,
     mock_rr: &RequestRequirements,
 ) -> Result<Option<ClosestMatch>, String> {
+    let mock_rr = &*mock_rr.with_normalized_paths();
     let mut history = state.history.lock().unwrap();
 
     let non_matching_requests: Vec<&Arc<HttpMockRequest>> = history
         .iter()
+        .filter(|a| a.namespace == mock_rr.namespace)
         .filter(|a| !request_matches(state, (*a).clone(), mock_rr))
         .collect();
 
@@ -172,7 +588,63 @@ pub(crate) fn verify(
     };
 
     let req = non_matching_requests.get(closes_match_request_idx).unwrap();
-    let mismatches = get_request_mismatches(req, &mock_rr, &state.matchers);
+    let parsed = ParsedRequest::new(req);
+    let mismatches = match (&mock_rr.base_path, matching_request(&parsed, mock_rr)) {
+        (Some(base_path), None) => {
+            let mut mismatches = vec![Mismatch {
+                title: format!(
+                    "The path does not start with the base path '{}' of the mock",
+                    base_path
+                ),
+                reason: Some(Reason {
+                    expected: base_path.clone(),
+                    actual: req.path.clone(),
+                    comparison: "starts with".into(),
+                    best_match: false,
+                }),
+                diff: None,
+                stage: Some(MatchStage::Path),
+            }];
+            let full = MatchingRequest::new(&parsed, None).with_raw_query(mock_rr.query_params_raw);
+            mismatches.extend(get_request_mismatches(&full, &mock_rr, &state.matchers));
+            mismatches
+        }
+        // Path mismatches show the full and the relative request path.
+        (Some(base_path), Some(relative)) => {
+            let mut mismatches: Vec<Mismatch> = state
+            .matchers
+            .iter()
+            .flat_map(|matcher| {
+                let mut mismatches = matcher.mismatches(&relative, mock_rr);
+                if matcher.entity_name() == "path" {
+                    for mismatch in mismatches.iter_mut() {
+                        mismatch.title = format!(
+                            "{} (the request path is '{}', which is '{}' without the base path '{}')",
+                            mismatch.title,
+                            req.path,
+                            relative.path(),
+                            base_path
+                        );
+                    }
+                }
+                mismatches
+            })
+            .collect();
+            mismatches.extend(get_alternative_mismatches(
+                &relative,
+                mock_rr,
+                &state.matchers,
+            ));
+            mismatches
+        }
+        (None, relative) => get_request_mismatches(
+            &relative.unwrap_or_else(|| {
+                MatchingRequest::new(&parsed, None).with_raw_query(mock_rr.query_params_raw)
+            }),
+            &mock_rr,
+            &state.matchers,
+        ),
+    };
 
     Ok(Some(ClosestMatch {
         request: HttpMockRequest::clone(&req),
@@ -183,18 +655,71 @@ pub(crate) fn verify(
 
 /// Validates a mock request.
 fn validate_mock_definition(req: &MockDefinition) -> Result<(), String> {
-    if let Some(_body) = &req.request.body {
-        if let Some(method) = &req.request.method {
-            if NON_BODY_METHODS.contains(&method.as_str()) {
-                return Err(String::from(
-                    "A body cannot be sent along with the specified method",
-                ));
-            }
+    if req.request.body.is_some() || req.request.body_base64.is_some() {
+        let is_non_body_method = |method: &String| NON_BODY_METHODS.contains(&method.as_str());
+        let rr = &req.request;
+        if rr.method.iter().any(is_non_body_method)
+            || rr
+                .methods
+                .as_ref()
+                .map_or(false, |m| m.iter().all(is_non_body_method))
+        {
+            return Err(String::from(
+                "A body cannot be sent along with the specified method",
+            ));
         }
     }
+    #[cfg(not(feature = "regex"))]
+    if uses_regex(&req.request) {
+        return Err(String::from(
+            "Regex matchers require the mock server to be built with the `regex` feature",
+        ));
+    }
+    #[cfg(not(feature = "json-schema"))]
+    if uses_json_schema(&req.request) {
+        return Err(String::from(
+            "JSON schema matchers require the mock server to be built with the `json-schema` \
+             feature",
+        ));
+    }
+    #[cfg(not(feature = "xml"))]
+    if req.request.body_xpaths.is_some() || req.request.xml_body.is_some() {
+        return Err(String::from(
+            "XML matchers require the mock server to be built with the `xml` feature",
+        ));
+    }
+    #[cfg(not(feature = "graphql"))]
+    if req.request.graphql_operation.is_some() {
+        return Err(String::from(
+            "GraphQL operation matchers require the mock server to be built with the `graphql` \
+             feature",
+        ));
+    }
     Ok(())
 }
 
+/// Returns true if the requirements or any of their `any_of` alternatives use a regex matcher.
+/// `any_of` is the only field that nests requirements: `When::all_of` merges its requirements
+/// into the mock and negated matchers are stored in the flat `*_not_*` fields.
+#[cfg(not(feature = "regex"))]
+fn uses_regex(rr: &RequestRequirements) -> bool {
+    rr.path_matches.is_some()
+        || rr.path_not_matches.is_some()
+        || rr.body_matches.is_some()
+        || rr.body_not_matches.is_some()
+        || rr.query_param_matches.is_some()
+        || rr.header_matches.is_some()
+        || rr.remote_addr_matches.is_some()
+        || rr.any_of.iter().flatten().any(uses_regex)
+}
+
+/// Returns true if the requirements or any of their `any_of` alternatives use a JSON schema
+/// matcher.
+#[cfg(not(feature = "json-schema"))]
+fn uses_json_schema(rr: &RequestRequirements) -> bool {
+    rr.json_body_schema.is_some() || rr.any_of.iter().flatten().any(uses_json_schema)
+}
+
 // Remember the maximum number of matchers that successfully matched
 fn get_distances(
     history: &Vec<&Arc<HttpMockRequest>>,
@@ -204,32 +729,87 @@ fn get_distances(
     history
         .iter()
         .enumerate()
-        .map(|(idx, req)| (idx, get_request_distance(req, mock_rr, matchers)))
+        .map(|(idx, req)| {
+            let parsed = ParsedRequest::new(req);
+            let req = matching_request(&parsed, mock_rr).unwrap_or_else(|| {
+                MatchingRequest::new(&parsed, None).with_raw_query(mock_rr.query_params_raw)
+            });
+            (idx, get_request_distance(&req, mock_rr, matchers))
+        })
         .collect()
 }
 
 fn get_request_mismatches(
-    req: &Arc<HttpMockRequest>,
+    req: &MatchingRequest,
     mock_rr: &RequestRequirements,
     matchers: &Vec<Box<dyn Matcher + Sync + Send>>,
 ) -> Vec<Mismatch> {
-    matchers
+    let mut mismatches: Vec<Mismatch> = matchers
         .iter()
         .map(|mat| mat.mismatches(req, mock_rr))
         .flatten()
         .into_iter()
+        .collect();
+    mismatches.extend(get_alternative_mismatches(req, mock_rr, matchers));
+    mismatches
+}
+
+/// Returns the mismatches of the alternative that is closest to the request, unless the
+/// request matches one of the alternatives.
+fn get_alternative_mismatches(
+    req: &MatchingRequest,
+    mock_rr: &RequestRequirements,
+    matchers: &Vec<Box<dyn Matcher + Sync + Send>>,
+) -> Vec<Mismatch> {
+    let alternatives = match &mock_rr.any_of {
+        None => return Vec::new(),
+        Some(alternatives) => alternatives,
+    };
+    if alternatives
+        .iter()
+        .any(|alternative| requirements_match(matchers, req, alternative, false))
+    {
+        return Vec::new();
+    }
+    let closest = alternatives
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, alternative)| get_request_distance(req, alternative, matchers));
+    let (idx, alternative) = match closest {
+        None => return Vec::new(),
+        Some(closest) => closest,
+    };
+    get_request_mismatches(req, alternative, matchers)
+        .into_iter()
+        .map(|mut mismatch| {
+            mismatch.title = format!(
+                "Alternative {} of {} (the closest one): {}",
+                idx + 1,
+                alternatives.len(),
+                mismatch.title
+            );
+            mismatch
+        })
         .collect()
 }
 
 fn get_request_distance(
-    req: &Arc<HttpMockRequest>,
+    req: &MatchingRequest,
     mock_rr: &RequestRequirements,
     matchers: &Vec<Box<dyn Matcher + Sync + Send>>,
 ) -> usize {
-    matchers
+    let distance: usize = matchers
         .iter()
         .map(|matcher| matcher.distance(req, mock_rr))
-        .sum()
+        .sum();
+    let alternative_distance = mock_rr.any_of.as_ref().map_or(0, |alternatives| {
+        alternatives
+            .iter()
+            .map(|alternative| get_request_distance(req, alternative, matchers))
+            .min()
+            .unwrap_or(0)
+    });
+    distance + alternative_distance
 }
 
 // Remember the maximum number of matchers that successfully matched
@@ -257,13 +837,18 @@ mod test {
     use std::rc::Rc;
     use std::sync::Arc;
 
+    #[cfg(feature = "regex")]
     use regex::Regex;
 
+    use serde_json::json;
+
     use crate::common::data::{
-        HttpMockRequest, MockDefinition, MockServerHttpResponse, Pattern, RequestRequirements,
+        HttpMockRequest, MatchStage, MockDefinition, MockServerHttpResponse, Pattern,
+        RequestRequirements,
     };
     use crate::server::web::handlers::{
-        add_new_mock, read_one_mock, request_matches, validate_mock_definition, verify,
+        add_new_mock, needs_body, read_one_mock, request_matches, set_mock_enabled,
+        validate_mock_definition, verify,
     };
     use crate::server::MockServerState;
     use crate::Method;
@@ -378,6 +963,25 @@ mod test {
         assert_eq!(true, does_match);
     }
 
+    /// This test makes sure that the path requirements of a mock with a base path are matched
+    /// against the request path without the base path.
+    #[test]
+    fn request_matches_base_path() {
+        // Arrange
+        let mut mock = RequestRequirements::new().with_path("/users".to_string());
+        mock.base_path = Some("/api/v2".to_string());
+        let matches = |path: &str| {
+            let req = HttpMockRequest::new("GET".to_string(), path.to_string());
+            request_matches(&MockServerState::default(), Arc::new(req), &mock)
+        };
+
+        // Act and Assert
+        assert_eq!(true, matches("/api/v2/users"));
+        assert_eq!(false, matches("/users"));
+        assert_eq!(false, matches("/api/v2x/users"));
+        assert_eq!(false, matches("/api/v2"));
+    }
+
     /// This test makes sure that a request is considered "not matched" if the paths of the
     /// request and the mock are not equal.
     #[test]
@@ -575,8 +1179,10 @@ mod test {
         let res = MockServerHttpResponse {
             body: None,
             delay: None,
+            sequence: None,
             status: Some(418),
             headers: None,
+            trailers: None,
         };
 
         let smr = MockDefinition::new(req, res);
@@ -602,8 +1208,10 @@ mod test {
         let res = MockServerHttpResponse {
             body: None,
             delay: None,
+            sequence: None,
             status: Some(418),
             headers: None,
+            trailers: None,
         };
 
         let smr = MockDefinition::new(req, res);
@@ -615,6 +1223,74 @@ mod test {
         assert_eq!(true, result.is_ok());
     }
 
+    /// This test ensures that regex matchers nested in `any_of` alternatives are rejected when
+    /// the server was built without the `regex` feature.
+    #[cfg(not(feature = "regex"))]
+    #[test]
+    fn validate_mock_definition_any_of_regex() {
+        // Arrange
+        let alternative = RequestRequirements::new().with_path_matches(vec![Pattern {
+            regex: "^/users/[0-9]+$".to_string(),
+        }]);
+        let req = RequestRequirements::new().with_any_of(vec![
+            RequestRequirements::new().with_path("/users".to_string()),
+            alternative,
+        ]);
+        let res = MockServerHttpResponse {
+            body: None,
+            delay: None,
+            sequence: None,
+            status: Some(418),
+            headers: None,
+            trailers: None,
+        };
+
+        let smr = MockDefinition::new(req, res);
+
+        // Act
+        let result = validate_mock_definition(&smr);
+
+        // Assert
+        assert_eq!(
+            result.unwrap_err(),
+            "Regex matchers require the mock server to be built with the `regex` feature"
+        );
+    }
+
+    /// This test ensures that JSON schema matchers nested in `any_of` alternatives are rejected
+    /// when the server was built without the `json-schema` feature.
+    #[cfg(not(feature = "json-schema"))]
+    #[test]
+    fn validate_mock_definition_any_of_json_schema() {
+        // Arrange
+        let alternative = RequestRequirements::new()
+            .with_json_body_schema(serde_json::json!({ "type": "object" }));
+        let req = RequestRequirements::new().with_any_of(vec![
+            RequestRequirements::new().with_path("/users".to_string()),
+            alternative,
+        ]);
+        let res = MockServerHttpResponse {
+            body: None,
+            delay: None,
+            sequence: None,
+            status: Some(418),
+            headers: None,
+            trailers: None,
+        };
+
+        let smr = MockDefinition::new(req, res);
+
+        // Act
+        let result = validate_mock_definition(&smr);
+
+        // Assert
+        assert_eq!(
+            result.unwrap_err(),
+            "JSON schema matchers require the mock server to be built with the `json-schema` \
+             feature"
+        );
+    }
+
     /// This test ensures that mock validation is being invoked.
     #[test]
     fn add_new_mock_validation_error() {
@@ -627,8 +1303,10 @@ mod test {
         let res = MockServerHttpResponse {
             body: None,
             delay: None,
+            sequence: None,
             status: Some(200),
             headers: None,
+            trailers: None,
         };
 
         let mock_def = MockDefinition::new(req, res);
@@ -641,6 +1319,93 @@ mod test {
         assert_eq!(result.err().unwrap().contains("Validation error"), true);
     }
 
+    /// This test ensures that the path requirements of a mock are normalized once when the mock
+    /// is added, while the definition of the mock keeps the paths as they were specified.
+    #[test]
+    fn add_new_mock_normalizes_paths_test() {
+        // Arrange
+        let state = MockServerState::default();
+        let mut req = RequestRequirements::new().with_path("/Users/".to_string());
+        req.path_case_insensitive = true;
+        req.path_ignore_trailing_slash = true;
+
+        let res = MockServerHttpResponse {
+            body: None,
+            delay: None,
+            sequence: None,
+            status: Some(200),
+            headers: None,
+            trailers: None,
+        };
+
+        // Act
+        let id = add_new_mock(&state, MockDefinition::new(req, res), false).unwrap();
+
+        // Assert
+        let mocks = state.mocks.read().unwrap();
+        let mock = mocks.get(&id).unwrap();
+        assert_eq!(mock.definition.request.path.as_deref(), Some("/Users/"));
+        assert_eq!(mock.matched_request().path.as_deref(), Some("/users"));
+    }
+
+    /// This test ensures that static mocks that are declared disabled can be enabled.
+    #[test]
+    fn set_static_mock_enabled_test() {
+        // Arrange
+        let state = MockServerState::default();
+        let mut req = RequestRequirements::new().with_path("/health".to_string());
+        req.disabled = true;
+
+        let res = MockServerHttpResponse {
+            body: None,
+            delay: None,
+            sequence: None,
+            status: Some(200),
+            headers: None,
+            trailers: None,
+        };
+
+        let id = add_new_mock(&state, MockDefinition::new(req, res), true).unwrap();
+
+        // Act
+        let found = set_mock_enabled(&state, id, true);
+
+        // Assert
+        assert!(found);
+        let mock = read_one_mock(&state, id).unwrap().unwrap();
+        assert!(!mock.definition.request.disabled);
+        assert!(mock.is_static);
+    }
+
+    /// This test ensures that the body of a request is only required if a mock that matches
+    /// the request apart from its body has requirements on the body.
+    #[test]
+    fn needs_body_test() {
+        // Arrange
+        let state = MockServerState::default();
+        let response = || MockServerHttpResponse {
+            body: None,
+            delay: None,
+            sequence: None,
+            status: Some(200),
+            headers: None,
+            trailers: None,
+        };
+        let path_only = RequestRequirements::new().with_path("/upload".to_string());
+        let with_body = RequestRequirements::new()
+            .with_path("/other".to_string())
+            .with_body("test".to_string());
+        add_new_mock(&state, MockDefinition::new(path_only, response()), false).unwrap();
+        add_new_mock(&state, MockDefinition::new(with_body, response()), false).unwrap();
+        let request =
+            |path: &str| Arc::new(HttpMockRequest::new("POST".to_string(), path.to_string()));
+
+        // Act and Assert
+        assert_eq!(false, needs_body(&state, request("/upload")));
+        assert_eq!(true, needs_body(&state, request("/other")));
+        assert_eq!(false, needs_body(&state, request("/unknown")));
+    }
+
     /// This test ensures that reading a non-existent mock does not result in an error but an
     /// empty result.
     #[test]
@@ -676,6 +1441,7 @@ mod test {
     }
 
     /// This test checks if matching "path_matches" is working as expected.
+    #[cfg(feature = "regex")]
     #[test]
     fn not_match_path_matches_test() {
         // Arrange
@@ -730,4 +1496,47 @@ mod test {
         assert_eq!(result.as_ref().unwrap().is_some(), true);
         assert_eq!(result.as_ref().unwrap().as_ref().unwrap().request_index, 0);
     }
+
+    #[test]
+    fn matchers_are_ordered_by_stage_test() {
+        // Arrange
+        let state = MockServerState::default();
+
+        // Act
+        let stages: Vec<MatchStage> = state.matchers.iter().map(|m| m.stage()).collect();
+
+        // Assert
+        let mut sorted = stages.clone();
+        sorted.sort();
+        assert_eq!(stages, sorted);
+    }
+
+    #[test]
+    fn verify_reports_stages_test() {
+        // Arrange
+        let state = MockServerState::default();
+        state.history.lock().unwrap().push(Arc::new(
+            HttpMockRequest::new("POST".to_string(), "/orders".to_string())
+                .with_body(br#"{"id":2}"#.to_vec()),
+        ));
+        let rr = RequestRequirements::new()
+            .with_method("GET".to_string())
+            .with_path("/users".to_string())
+            .with_json_body(json!({"id": 1}));
+
+        // Act
+        let closest_match = verify(&state, &rr).unwrap().unwrap();
+
+        // Assert
+        let stages: Vec<Option<MatchStage>> =
+            closest_match.mismatches.iter().map(|m| m.stage).collect();
+        assert_eq!(
+            stages,
+            vec![
+                Some(MatchStage::Method),
+                Some(MatchStage::Path),
+                Some(MatchStage::Body)
+            ]
+        );
+    }
 }
@@ -2,13 +2,20 @@ use std::collections::BTreeMap;
 
 use serde::Serialize;
 
+use futures_util::StreamExt;
+use hyper::Body;
+
 use crate::common::data::{
     ErrorResponse, HttpMockRequest, MockDefinition, MockRef, MockServerHttpResponse,
-    RequestRequirements,
+    RequestRequirements, StaticDirectory, TrafficEntry, TrafficResponse,
 };
+use crate::server::forward::Upstream;
+use crate::server::request_log;
+use crate::server::stats;
 use crate::server::web::handlers;
-use crate::server::{MockServerState, ServerRequestHeader, ServerResponse};
-use std::time::Instant;
+use crate::server::{MockServerState, ServerRequestHeader, ServerResponse, NAMESPACE_BASE_PATH};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
 use tokio::time::Duration;
 
 /// This route is responsible for adding a new mock
@@ -17,7 +24,11 @@ pub(crate) fn ping() -> Result<ServerResponse, String> {
 }
 
 /// This route is responsible for adding a new mock
-pub(crate) fn add(state: &MockServerState, body: Vec<u8>) -> Result<ServerResponse, String> {
+pub(crate) fn add(
+    state: &MockServerState,
+    body: Vec<u8>,
+    idempotency_key: Option<&str>,
+) -> Result<ServerResponse, String> {
     let mock_def: serde_json::Result<MockDefinition> = serde_json::from_slice(&body);
 
     if let Err(e) = mock_def {
@@ -25,7 +36,10 @@ pub(crate) fn add(state: &MockServerState, body: Vec<u8>) -> Result<ServerRespon
     }
     let mock_def = mock_def.unwrap();
 
-    let result = handlers::add_new_mock(&state, mock_def, false);
+    let result = match idempotency_key {
+        Some(key) => handlers::add_new_mock_once(&state, mock_def, key),
+        None => handlers::add_new_mock(&state, mock_def, false),
+    };
 
     match result {
         Err(e) => create_json_response(500, None, ErrorResponse::new(&e)),
@@ -33,6 +47,22 @@ pub(crate) fn add(state: &MockServerState, body: Vec<u8>) -> Result<ServerRespon
     }
 }
 
+/// This route is responsible for adding a static directory
+pub(crate) fn add_static_dir(
+    state: &MockServerState,
+    body: Vec<u8>,
+) -> Result<ServerResponse, String> {
+    let dir: StaticDirectory = match serde_json::from_slice(&body) {
+        Ok(dir) => dir,
+        Err(e) => return create_json_response(500, None, ErrorResponse::new(&e)),
+    };
+
+    match handlers::add_static_dir(state, dir) {
+        Err(e) => create_json_response(500, None, ErrorResponse::new(&e)),
+        Ok(()) => create_response(201, None, None),
+    }
+}
+
 /// This route is responsible for deleting mocks
 pub(crate) fn delete_one(state: &MockServerState, id: usize) -> Result<ServerResponse, String> {
     let result = handlers::delete_one_mock(state, id);
@@ -48,15 +78,57 @@ pub(crate) fn delete_one(state: &MockServerState, id: usize) -> Result<ServerRes
     }
 }
 
+/// This route is responsible for updating a mock
+pub(crate) fn update(
+    state: &MockServerState,
+    id: usize,
+    body: Vec<u8>,
+) -> Result<ServerResponse, String> {
+    let mock_def: serde_json::Result<MockDefinition> = serde_json::from_slice(&body);
+    if let Err(e) = mock_def {
+        return create_json_response(500, None, ErrorResponse::new(&e));
+    }
+
+    match handlers::update_mock(state, id, mock_def.unwrap()) {
+        Err(e) => create_json_response(500, None, ErrorResponse::new(&e)),
+        Ok(true) => create_json_response(200, None, MockRef { mock_id: id }),
+        Ok(false) => create_response(404, None, None),
+    }
+}
+
+/// This route is responsible for enabling or disabling a mock
+pub(crate) fn set_enabled(
+    state: &MockServerState,
+    id: usize,
+    body: Vec<u8>,
+) -> Result<ServerResponse, String> {
+    let enabled: serde_json::Result<bool> = serde_json::from_slice(&body);
+    if let Err(e) = enabled {
+        return create_json_response(500, None, ErrorResponse::new(&e));
+    }
+
+    if handlers::set_mock_enabled(state, id, enabled.unwrap()) {
+        create_response(202, None, None)
+    } else {
+        create_response(404, None, None)
+    }
+}
+
 /// This route is responsible for deleting all mocks
-pub(crate) fn delete_all_mocks(state: &MockServerState) -> Result<ServerResponse, String> {
-    handlers::delete_all_mocks(state);
+pub(crate) fn delete_all_mocks(
+    state: &MockServerState,
+    namespace: Option<&str>,
+) -> Result<ServerResponse, String> {
+    handlers::delete_all_mocks(state, namespace);
     create_response(202, None, None)
 }
 
-/// This route is responsible for deleting all mocks
-pub(crate) fn delete_history(state: &MockServerState) -> Result<ServerResponse, String> {
-    handlers::delete_history(state);
+/// This route is responsible for deleting the request history
+pub(crate) fn delete_history(
+    state: &MockServerState,
+    namespace: Option<&str>,
+) -> Result<ServerResponse, String> {
+    handlers::delete_history(state, namespace);
     create_response(202, None, None)
 }
 
@@ -72,6 +144,54 @@ pub(crate) fn read_one(state: &MockServerState, id: usize) -> Result<ServerRespo
     }
 }
 
+/// This route is responsible for listing all mocks
+pub(crate) fn read_all(
+    state: &MockServerState,
+    namespace: Option<&str>,
+) -> Result<ServerResponse, String> {
+    create_json_response(200, None, handlers::read_all_mocks(state, namespace))
+}
+
+/// This route is responsible for listing the request history
+pub(crate) fn read_history(
+    state: &MockServerState,
+    namespace: Option<&str>,
+) -> Result<ServerResponse, String> {
+    create_json_response(200, None, handlers::read_history(state, namespace))
+}
+
+/// This route is responsible for listing the requests that matched a mock
+pub(crate) fn read_mock_requests(
+    state: &MockServerState,
+    id: usize,
+) -> Result<ServerResponse, String> {
+    match handlers::read_mock_requests(state, id) {
+        Some(requests) => create_json_response(200, None, requests),
+        None => create_response(404, None, None),
+    }
+}
+
+/// This route is responsible for listing the requests that did not match any mock
+pub(crate) fn read_unmatched(
+    state: &MockServerState,
+    namespace: Option<&str>,
+) -> Result<ServerResponse, String> {
+    create_json_response(
+        200,
+        None,
+        handlers::read_unmatched_requests(state, namespace),
+    )
+}
+
+/// This route is responsible for deleting all mocks and the request history
+pub(crate) fn reset(
+    state: &MockServerState,
+    namespace: Option<&str>,
+) -> Result<ServerResponse, String> {
+    handlers::reset(state, namespace);
+    create_response(202, None, None)
+}
+
 /// This route is responsible for verification
 pub(crate) fn verify(state: &MockServerState, body: Vec<u8>) -> Result<ServerResponse, String> {
     let mock_rr: serde_json::Result<RequestRequirements> = serde_json::from_slice(&body);
@@ -94,17 +214,228 @@ pub(crate) async fn serve(
     state: &MockServerState,
     req: &ServerRequestHeader,
     body: Vec<u8>,
+    fallback_upstream: Option<&Upstream>,
 ) -> Result<ServerResponse, String> {
-    let handler_request_result = to_handler_request(&req, body);
-    let result = match handler_request_result {
-        Ok(handler_request) => {
-            let handler_response = handlers::find_mock(&state, handler_request);
-            let handler_response = postprocess_response(handler_response).await;
-            to_route_response(handler_response)
+    let started = (SystemTime::now(), Instant::now());
+    let forwarded_body = match fallback_upstream {
+        Some(_) => body.clone(),
+        None => Vec::new(),
+    };
+
+    let handler_request = match to_handler_request(&req, body) {
+        Ok(handler_request) => Arc::new(handler_request),
+        Err(e) => return create_json_response(500, None, ErrorResponse::new(&e)),
+    };
+
+    let found = handlers::find_mock(&state, handler_request.clone());
+    let mock = found
+        .as_ref()
+        .ok()
+        .and_then(|found| found.as_ref()?.mock.clone());
+    let handler_response = found.map(|found| found.map(|found| found.response));
+    let delay = match &handler_response {
+        Ok(Some(response)) => response.delay.unwrap_or_default(),
+        _ => Duration::from_secs(0),
+    };
+    let response = match (&handler_response, fallback_upstream) {
+        (Ok(None), Some(upstream)) => {
+            forward(state, req, forwarded_body, &handler_request, upstream).await
         }
-        Err(e) => create_json_response(500, None, ErrorResponse::new(&e)),
+        _ => to_route_response(postprocess_response(handler_response).await),
     };
-    return result;
+
+    response.map(|response| {
+        log_request(state, &handler_request, mock, &response);
+        log_traffic(state, handler_request, started, delay, response)
+    })
+}
+
+/// Forwards a request that did not match any mock to the fallback upstream server and marks it
+/// as forwarded in the request history.
+async fn forward(
+    state: &MockServerState,
+    req: &ServerRequestHeader,
+    body: Vec<u8>,
+    handler_request: &Arc<HttpMockRequest>,
+    upstream: &Upstream,
+) -> Result<ServerResponse, String> {
+    match upstream.forward_streaming(req, body).await {
+        Ok(response) => {
+            handlers::mark_forwarded(state, handler_request, Some(response.status));
+            Ok(response)
+        }
+        Err(e) => {
+            handlers::mark_forwarded(state, handler_request, None);
+            create_json_response(
+                502,
+                None,
+                ErrorResponse::new(&format!("Cannot forward request to upstream server: {}", e)),
+            )
+        }
+    }
+}
+
+/// This route is responsible for all requests while the mock server records traffic. Requests
+/// are forwarded to the upstream server instead of being matched against the mocks.
+pub(crate) async fn record(
+    state: &MockServerState,
+    req: &ServerRequestHeader,
+    body: Vec<u8>,
+    upstream: &Upstream,
+) -> Result<ServerResponse, String> {
+    let started = (SystemTime::now(), Instant::now());
+    let handler_request = match to_handler_request(req, body.clone()) {
+        Ok(handler_request) => Arc::new(handler_request),
+        Err(e) => return create_json_response(500, None, ErrorResponse::new(&e)),
+    };
+    handlers::record_request(state, handler_request.clone());
+
+    let response = match upstream.forward(req, body).await {
+        Ok(response) => {
+            handlers::record_exchange(state, handler_request.clone(), response.clone());
+            create_response(
+                response.status.unwrap_or(200),
+                response.headers,
+                response.body,
+            )
+        }
+        Err(e) => create_json_response(
+            502,
+            None,
+            ErrorResponse::new(&format!("Cannot forward request to upstream server: {}", e)),
+        ),
+    };
+
+    response.map(|response| {
+        log_request(state, &handler_request, None, &response);
+        log_traffic(
+            state,
+            handler_request,
+            started,
+            Duration::from_secs(0),
+            response,
+        )
+    })
+}
+
+/// This route is responsible for requests whose body exceeds the maximum body size. The request
+/// is recorded without its body and without being matched against any mock.
+pub(crate) fn payload_too_large(
+    state: &MockServerState,
+    req: &ServerRequestHeader,
+    max_body_bytes: usize,
+) -> Result<ServerResponse, String> {
+    let started = (SystemTime::now(), Instant::now());
+    let response = create_json_response(
+        413,
+        None,
+        ErrorResponse::new(&format!(
+            "Request body exceeds the maximum size of {} bytes",
+            max_body_bytes
+        )),
+    );
+
+    match to_handler_request(req, Vec::new()) {
+        Ok(mut handler_request) => {
+            handler_request.body = None;
+            handler_request.body_truncated = true;
+            let handler_request = Arc::new(handler_request);
+            handlers::record_request(state, handler_request.clone());
+            response.map(|response| {
+                log_traffic(
+                    state,
+                    handler_request,
+                    started,
+                    Duration::from_secs(0),
+                    response,
+                )
+            })
+        }
+        Err(e) => {
+            log::debug!("Cannot record request: {}", e);
+            response
+        }
+    }
+}
+
+/// This route is responsible for exporting the request history along with the responses as
+/// a HAR document
+pub(crate) fn export_har(state: &MockServerState, https: bool) -> Result<ServerResponse, String> {
+    create_json_response(200, None, handlers::export_har(state, https))
+}
+
+/// This route is responsible for returning statistics about the served requests
+pub(crate) fn read_stats(state: &MockServerState) -> Result<ServerResponse, String> {
+    create_json_response(200, None, handlers::read_stats(state))
+}
+
+/// This route is responsible for exposing statistics about the served requests in the
+/// Prometheus text format
+pub(crate) fn metrics(state: &MockServerState) -> Result<ServerResponse, String> {
+    let text = stats::to_prometheus(&handlers::read_stats(state));
+    create_response(
+        200,
+        Some(vec![(
+            "content-type".to_string(),
+            "text/plain; version=0.0.4".to_string(),
+        )]),
+        Some(text.into_bytes()),
+    )
+}
+
+/// Writes a request to the request log of the mock server, if it is enabled.
+fn log_request(
+    state: &MockServerState,
+    request: &HttpMockRequest,
+    mock: Option<(usize, String)>,
+    response: &ServerResponse,
+) {
+    let config = state.request_log.read().unwrap();
+    request_log::log_request(&config, request, mock, response.status);
+}
+
+/// Adds a request and the response that is sent to the traffic log and counts the response in
+/// the request statistics. The configured response delay is not counted as handling time. A
+/// streamed response body is added to the log while it is sent to the client.
+fn log_traffic(
+    state: &MockServerState,
+    request: Arc<HttpMockRequest>,
+    (started, timer): (SystemTime, Instant),
+    delay: Duration,
+    mut response: ServerResponse,
+) -> ServerResponse {
+    let wait = timer.elapsed();
+    state
+        .stats
+        .record_response(response.status, wait.checked_sub(delay).unwrap_or_default());
+
+    let entry = Arc::new(TrafficEntry {
+        started,
+        request,
+        response: Mutex::new(TrafficResponse {
+            status: response.status,
+            headers: response.headers.clone(),
+            body: response.body.clone(),
+            wait,
+            receive: Duration::from_secs(0),
+        }),
+    });
+
+    if let Some(body_stream) = response.body_stream.take() {
+        let streamed_entry = entry.clone();
+        let receive_timer = Instant::now();
+        response.body_stream = Some(Body::wrap_stream(body_stream.map(move |chunk| {
+            let mut logged = streamed_entry.response.lock().unwrap();
+            if let Ok(chunk) = &chunk {
+                logged.body.extend_from_slice(chunk);
+            }
+            logged.receive = receive_timer.elapsed();
+            chunk
+        })));
+    }
+
+    handlers::record_traffic(state, entry);
+    response
 }
 
 /// Maps the result of the serve handler to an HTTP response which the web framework understands
@@ -119,7 +450,12 @@ fn to_route_response(
                 None,
                 ErrorResponse::new(&"Request did not match any route or mock"),
             ),
-            Some(res) => create_response(res.status.unwrap_or(200), res.headers, res.body),
+            Some(res) => {
+                let mut response =
+                    create_response(res.status.unwrap_or(200), res.headers, res.body)?;
+                response.trailers = res.trailers.unwrap_or_default();
+                Ok(response)
+            }
         },
     }
 }
@@ -153,6 +489,16 @@ fn create_response(
     Ok(ServerResponse::new(status, headers, body))
 }
 
+/// Returns whether the body of a request that is not sent to the admin API is required to
+/// find its mock.
+pub(crate) fn needs_body(state: &MockServerState, req: &ServerRequestHeader) -> bool {
+    match to_handler_request(req, Vec::new()) {
+        Ok(handler_request) => handlers::needs_body(state, Arc::new(handler_request)),
+        // The request cannot be matched anyway, but it is rejected with the same error as usual.
+        Err(_) => true,
+    }
+}
+
 /// Maps the request of the serve handler to a request representation which the handlers understand
 fn to_handler_request(req: &ServerRequestHeader, body: Vec<u8>) -> Result<HttpMockRequest, String> {
     let query_params = extract_query_params(&req.query);
@@ -160,14 +506,57 @@ fn to_handler_request(req: &ServerRequestHeader, body: Vec<u8>) -> Result<HttpMo
         return Err(format!("error parsing query_params: {}", e));
     }
 
-    let request = HttpMockRequest::new(req.method.to_string(), req.path.to_string())
+    let (namespace, path) = split_namespace(&req.path);
+    let mut request = HttpMockRequest::new(req.method.to_string(), path.to_string())
         .with_headers(req.headers.clone())
         .with_query_params(query_params.unwrap())
-        .with_body(body);
+        .with_body(body)
+        .with_version(req.version.clone());
+    if req.body_skipped {
+        request.body = None;
+        request.body_truncated = true;
+    }
+
+    if let Some(cert) = &req.client_certificate {
+        request = request.with_client_certificate(cert.clone());
+    }
+
+    if let Some(host) = &req.host {
+        request = request.with_host(host.clone());
+    }
+
+    if let Some(listener) = &req.listener {
+        request = request.with_listener(listener.clone());
+    }
+
+    if !req.query.is_empty() {
+        request = request.with_raw_query(req.query.clone());
+    }
+
+    if let Some(remote_addr) = req.remote_addr {
+        request = request.with_remote_addr(remote_addr);
+    }
+
+    if let Some(namespace) = namespace {
+        request = request.with_namespace(namespace.to_string());
+    }
 
     Ok(request)
 }
 
+/// Splits the namespace prefix off a request path. Returns the namespace, if any, and the path
+/// within the namespace.
+fn split_namespace(path: &str) -> (Option<&str>, &str) {
+    let rest = match path.strip_prefix(NAMESPACE_BASE_PATH) {
+        Some(rest) if rest.starts_with('/') => &rest[1..],
+        _ => return (None, path),
+    };
+    match rest.find('/') {
+        Some(idx) => (Some(&rest[..idx]), &rest[idx..]),
+        None => (Some(rest), "/"),
+    }
+}
+
 /// Extracts all query parameters from the URI of the given request.
 fn extract_query_params(query_string: &str) -> Result<Vec<(String, String)>, String> {
     // HACK: There doesn't seem to be a way to just parse Query string with `url` crate
@@ -1,59 +1,81 @@
-use std::fs;
-use std::fs::read_dir;
+#[cfg(feature = "standalone")]
+use std::collections::BTreeMap;
+#[cfg(feature = "standalone")]
+use std::path::Path;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::sync::Arc;
+#[cfg(feature = "standalone")]
+use std::time::{Duration, SystemTime};
 
-use regex::Regex;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
-use tokio::time::Duration;
 
-use crate::common::data::{MockDefinition, MockServerHttpResponse, Pattern, RequestRequirements};
+#[cfg(feature = "https")]
 use crate::common::util::read_file;
-use crate::server::web::handlers::add_new_mock;
-use crate::server::{start_server, MockServerState};
-use crate::Method;
+#[cfg(feature = "standalone")]
+use crate::common::yaml::{is_mock_file, read_mock_dir, read_mock_file, YAMLMock};
+#[cfg(feature = "standalone")]
+use crate::server::web::handlers::replace_static_mocks;
+use crate::server::{start_server, MockServerState, ServerConfig};
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct NameValuePair {
-    name: String,
-    value: String,
+    pub(crate) name: String,
+    pub(crate) value: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct YAMLRequestRequirements {
-    pub path: Option<String>,
-    pub path_contains: Option<Vec<String>>,
-    pub path_matches: Option<Vec<String>>,
-    pub method: Option<Method>,
-    pub header: Option<Vec<NameValuePair>>,
-    pub header_exists: Option<Vec<String>>,
-    pub cookie: Option<Vec<NameValuePair>>,
-    pub cookie_exists: Option<Vec<String>>,
-    pub body: Option<String>,
-    pub json_body: Option<Value>,
-    pub json_body_partial: Option<Vec<Value>>,
-    pub body_contains: Option<Vec<String>>,
-    pub body_matches: Option<Vec<String>>,
-    pub query_param_exists: Option<Vec<String>>,
-    pub query_param: Option<Vec<NameValuePair>>,
-    pub x_www_form_urlencoded_key_exists: Option<Vec<String>>,
-    pub x_www_form_urlencoded_tuple: Option<Vec<NameValuePair>>,
+/// Holds the settings that a standalone mock server is started with.
+#[derive(Debug, Clone)]
+pub struct StandaloneServerConfig {
+    /// The TCP port the server listens on.
+    pub port: u16,
+    /// Whether the server listens on all network interfaces instead of only on localhost.
+    pub expose: bool,
+    /// A directory whose YAML and JSON files are loaded as static mocks on startup.
+    pub static_mock_dir_path: Option<PathBuf>,
+    /// Individual YAML or JSON files that are loaded as static mocks on startup.
+    pub static_mock_files: Vec<PathBuf>,
+    /// If enabled, the static mock directory and files are watched while the server is running.
+    /// Mocks of files that are added, changed or deleted are reloaded without a restart.
+    pub watch_mock_files: bool,
+    /// Whether every received request is logged.
+    pub print_access_log: bool,
+    /// The maximum number of requests that are kept in the request history.
+    pub history_limit: usize,
+    /// If enabled, the admin API that is used by
+    /// [MockServer::connect](../struct.MockServer.html#method.connect) is disabled, so that only
+    /// static mocks are served.
+    pub read_only: bool,
+    /// If enabled, the admin API endpoints that list all mocks and the request history are
+    /// disabled, so that such requests are matched against the mocks instead.
+    pub disable_introspection: bool,
+    /// The path to a PEM encoded certificate chain. If set together with `tls_key_file`, the
+    /// server serves HTTPS.
+    #[cfg(feature = "https")]
+    pub tls_cert_file: Option<PathBuf>,
+    /// The path to the PEM encoded private key of the server certificate.
+    #[cfg(feature = "https")]
+    pub tls_key_file: Option<PathBuf>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct YAMLHTTPResponse {
-    pub status: Option<u16>,
-    pub header: Option<Vec<NameValuePair>>,
-    pub body: Option<String>,
-    pub delay: Option<u64>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct YAMLMockDefinition {
-    when: YAMLRequestRequirements,
-    then: YAMLHTTPResponse,
+impl Default for StandaloneServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 5000,
+            expose: false,
+            static_mock_dir_path: None,
+            static_mock_files: Vec::new(),
+            watch_mock_files: false,
+            print_access_log: true,
+            history_limit: 100,
+            read_only: false,
+            disable_introspection: false,
+            #[cfg(feature = "https")]
+            tls_cert_file: None,
+            #[cfg(feature = "https")]
+            tls_key_file: None,
+        }
+    }
 }
 
 pub async fn start_standalone_server(
@@ -63,89 +85,317 @@ pub async fn start_standalone_server(
     print_access_log: bool,
     history_limit: usize,
 ) -> Result<(), String> {
-    let state = Arc::new(MockServerState::new(history_limit));
+    start_standalone_server_with_config(StandaloneServerConfig {
+        port,
+        expose,
+        static_mock_dir_path,
+        print_access_log,
+        history_limit,
+        ..StandaloneServerConfig::default()
+    })
+    .await
+}
+
+/// Starts a standalone mock server using the provided configuration. The server runs until
+/// a termination signal is received.
+pub async fn start_standalone_server_with_config(
+    config: StandaloneServerConfig,
+) -> Result<(), String> {
+    let state = Arc::new(MockServerState::new(config.history_limit));
+
+    #[cfg(feature = "standalone")]
+    let watcher = {
+        let mut watcher = MockFileWatcher::new(
+            state.clone(),
+            config.static_mock_dir_path.clone(),
+            config.static_mock_files.clone(),
+        );
+        watcher.load()?;
+
+        match config.watch_mock_files {
+            true => Some(tokio::spawn(watcher.watch())),
+            false => None,
+        }
+    };
+
+    let server_config = ServerConfig {
+        port: config.port,
+        expose: config.expose,
+        print_access_log: config.print_access_log,
+        read_only: config.read_only,
+        disable_introspection: config.disable_introspection,
+        #[cfg(feature = "https")]
+        tls: load_tls_config(&config)?,
+        ..ServerConfig::default()
+    };
+
+    let result = start_server(server_config, &state, None, None, None).await;
 
     #[cfg(feature = "standalone")]
-    static_mock_dir_path.map(|path| {
-        read_static_mocks(path)
-            .into_iter()
-            .map(|d| map_to_mock_definition(d))
-            .for_each(|static_mock| {
-                add_new_mock(&state, static_mock, true).expect("cannot add static mock");
-            })
-    });
-
-    start_server(port, expose, &state, None, print_access_log).await
+    if let Some(watcher) = watcher {
+        watcher.abort();
+    }
+
+    result
+}
+
+#[cfg(feature = "standalone")]
+const MOCK_FILE_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The state of a mock file that was loaded by a [MockFileWatcher].
+#[cfg(feature = "standalone")]
+struct LoadedMockFile {
+    fingerprint: Option<(SystemTime, u64)>,
+    name: Option<String>,
+    mock_ids: Vec<usize>,
 }
 
+/// Loads static mocks from files and keeps them up to date with the file system.
 #[cfg(feature = "standalone")]
-fn read_static_mocks(path: PathBuf) -> Vec<YAMLMockDefinition> {
-    let mut definitions = Vec::new();
-
-    let paths = read_dir(path).expect("cannot list files in directory");
-    for file_path in paths {
-        let file_path = file_path.unwrap().path();
-        if let Some(ext) = file_path.extension() {
-            if !"yaml".eq(ext) && !"yml".eq(ext) {
-                continue;
+struct MockFileWatcher {
+    state: Arc<MockServerState>,
+    dir: Option<PathBuf>,
+    files: Vec<PathBuf>,
+    loaded: BTreeMap<PathBuf, LoadedMockFile>,
+}
+
+#[cfg(feature = "standalone")]
+impl MockFileWatcher {
+    fn new(state: Arc<MockServerState>, dir: Option<PathBuf>, files: Vec<PathBuf>) -> Self {
+        Self {
+            state,
+            dir,
+            files,
+            loaded: BTreeMap::new(),
+        }
+    }
+
+    /// Loads all mock files. Fails if any of the files is invalid.
+    fn load(&mut self) -> Result<(), String> {
+        let mut mocks = Vec::new();
+        if let Some(dir) = &self.dir {
+            mocks.extend(read_mock_dir(dir)?);
+        }
+        for path in &self.files {
+            mocks.push(read_mock_file(path)?);
+        }
+
+        for YAMLMock {
+            name,
+            path,
+            definition,
+        } in mocks
+        {
+            let mock_ids =
+                replace_static_mocks(&self.state, &[], vec![definition]).map_err(|e| {
+                    format!(
+                        "Cannot add static mock from '{}': {}",
+                        path.to_string_lossy(),
+                        e
+                    )
+                })?;
+            self.loaded.insert(
+                path.clone(),
+                LoadedMockFile {
+                    fingerprint: fingerprint(&path),
+                    name: Some(name),
+                    mock_ids,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Periodically reloads the mocks of files that were changed.
+    async fn watch(mut self) {
+        log::info!("Watching mock files for changes");
+        loop {
+            tokio::time::sleep(MOCK_FILE_WATCH_INTERVAL).await;
+            self.poll();
+        }
+    }
+
+    /// Reloads the mocks of all files that were added, changed or deleted since the last call.
+    fn poll(&mut self) {
+        let mut current = BTreeMap::new();
+        if let Some(dir) = &self.dir {
+            if let Ok(entries) = std::fs::read_dir(dir) {
+                for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+                    if is_mock_file(&path) {
+                        current.insert(path.clone(), fingerprint(&path));
+                    }
+                }
+            }
+        }
+        for path in &self.files {
+            if path.exists() {
+                current.insert(path.clone(), fingerprint(path));
             }
         }
 
-        log::info!(
-            "Loading static mock file from '{}'",
-            file_path.to_string_lossy()
-        );
-        let content = read_file(file_path).expect("cannot read from file");
-        let content = String::from_utf8(content).expect("cannot convert file content");
+        let deleted: Vec<PathBuf> = self
+            .loaded
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in deleted {
+            let loaded = self.loaded.remove(&path).unwrap();
+            if let Err(e) = replace_static_mocks(&self.state, &loaded.mock_ids, Vec::new()) {
+                log::error!("Cannot remove mocks of '{}': {}", path.to_string_lossy(), e);
+            }
+            if let Some(name) = loaded.name {
+                log::info!(
+                    "Removed mock '{}' ('{}' was deleted)",
+                    name,
+                    path.to_string_lossy()
+                );
+            }
+        }
 
-        definitions.push(serde_yaml::from_str(&content).unwrap());
+        for (path, fingerprint) in current {
+            match self.loaded.get(&path) {
+                Some(loaded) if loaded.fingerprint == fingerprint => continue,
+                _ => self.reload(path, fingerprint),
+            }
+        }
     }
 
-    return definitions;
-}
+    /// Replaces the mocks of a file. If the file is invalid, its previous mocks are kept.
+    fn reload(&mut self, path: PathBuf, fingerprint: Option<(SystemTime, u64)>) {
+        let previous = self.loaded.remove(&path);
+        let (previous_name, previous_ids) = match previous {
+            Some(loaded) => (loaded.name, loaded.mock_ids),
+            None => (None, Vec::new()),
+        };
 
-#[cfg(feature = "standalone")]
-fn map_to_mock_definition(yaml_definition: YAMLMockDefinition) -> MockDefinition {
-    MockDefinition {
-        request: RequestRequirements {
-            path: yaml_definition.when.path,
-            path_contains: yaml_definition.when.path_contains,
-            path_matches: to_pattern_vec(yaml_definition.when.path_matches),
-            method: yaml_definition.when.method.map(|m| m.to_string()),
-            headers: to_pair_vec(yaml_definition.when.header),
-            header_exists: yaml_definition.when.header_exists,
-            cookies: to_pair_vec(yaml_definition.when.cookie),
-            cookie_exists: yaml_definition.when.cookie_exists,
-            body: yaml_definition.when.body,
-            json_body: yaml_definition.when.json_body,
-            json_body_includes: yaml_definition.when.json_body_partial,
-            body_contains: yaml_definition.when.body_contains,
-            body_matches: to_pattern_vec(yaml_definition.when.body_matches),
-            query_param_exists: yaml_definition.when.query_param_exists,
-            query_param: to_pair_vec(yaml_definition.when.query_param),
-            x_www_form_urlencoded: to_pair_vec(yaml_definition.when.x_www_form_urlencoded_tuple),
-            x_www_form_urlencoded_key_exists: yaml_definition.when.x_www_form_urlencoded_key_exists,
-            matchers: None,
-        },
-        response: MockServerHttpResponse {
-            status: yaml_definition.then.status,
-            headers: to_pair_vec(yaml_definition.then.header),
-            body: yaml_definition.then.body.map(|b| b.into_bytes()),
-            delay: yaml_definition.then.delay.map(|v| Duration::from_millis(v)),
-        },
+        let result = read_mock_file(&path).map_err(String::from).and_then(
+            |YAMLMock {
+                 name, definition, ..
+             }| {
+                replace_static_mocks(&self.state, &previous_ids, vec![definition])
+                    .map(|mock_ids| (name, mock_ids))
+            },
+        );
+
+        let loaded = match result {
+            Ok((name, mock_ids)) => {
+                match &previous_name {
+                    Some(_) => {
+                        log::info!("Changed mock '{}' from '{}'", name, path.to_string_lossy())
+                    }
+                    None => log::info!("Added mock '{}' from '{}'", name, path.to_string_lossy()),
+                }
+                LoadedMockFile {
+                    fingerprint,
+                    name: Some(name),
+                    mock_ids,
+                }
+            }
+            Err(e) => {
+                log::error!("{}; keeping the previously loaded mocks", e);
+                LoadedMockFile {
+                    fingerprint,
+                    name: previous_name,
+                    mock_ids: previous_ids,
+                }
+            }
+        };
+
+        self.loaded.insert(path, loaded);
     }
 }
 
+/// Identifies the version of a file by its modification time and size.
 #[cfg(feature = "standalone")]
-fn to_pattern_vec(vec: Option<Vec<String>>) -> Option<Vec<Pattern>> {
-    vec.map(|vec| {
-        vec.iter()
-            .map(|val| Pattern::from_regex(Regex::from_str(val).expect("cannot parse regex")))
-            .collect()
-    })
+fn fingerprint(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
 }
 
-#[cfg(feature = "standalone")]
-fn to_pair_vec(kvp: Option<Vec<NameValuePair>>) -> Option<Vec<(String, String)>> {
-    kvp.map(|vec| vec.into_iter().map(|nvp| (nvp.name, nvp.value)).collect())
+#[cfg(feature = "https")]
+fn load_tls_config(
+    config: &StandaloneServerConfig,
+) -> Result<Option<Arc<tokio_rustls::rustls::ServerConfig>>, String> {
+    let (cert_file, key_file) = match (&config.tls_cert_file, &config.tls_key_file) {
+        (Some(cert_file), Some(key_file)) => (cert_file, key_file),
+        (None, None) => return Ok(None),
+        _ => {
+            return Err(
+                "Both a TLS certificate file and a TLS key file are required to serve HTTPS"
+                    .to_string(),
+            )
+        }
+    };
+
+    let cert_pem = read_file(cert_file)
+        .map_err(|e| format!("Cannot read TLS certificate file {:?}: {}", cert_file, e))?;
+    let key_pem = read_file(key_file)
+        .map_err(|e| format!("Cannot read TLS key file {:?}: {}", key_file, e))?;
+    let alpn_protocols = ["h2".to_string(), "http/1.1".to_string()];
+
+    crate::server::tls::build_tls_config(&cert_pem, &key_pem, &alpn_protocols, None)
+        .map(|tls| Some(Arc::new(tls)))
+}
+
+#[cfg(all(test, feature = "standalone"))]
+mod test {
+    use crate::server::MockServerState;
+    use crate::standalone::MockFileWatcher;
+    use std::sync::Arc;
+
+    fn mock_paths(state: &MockServerState) -> Vec<String> {
+        let mocks = state.mocks.read().unwrap();
+        mocks
+            .values()
+            .map(|m| m.definition.request.path.clone().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn mock_file_watcher_test() {
+        // Arrange
+        let dir = std::env::temp_dir().join(format!("httpmock-watch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("a.yaml"),
+            "when:\n  path: /a\nthen:\n  status: 200\n",
+        )
+        .unwrap();
+
+        let state = Arc::new(MockServerState::default());
+        let mut watcher = MockFileWatcher::new(state.clone(), Some(dir.clone()), Vec::new());
+        watcher.load().unwrap();
+        assert_eq!(mock_paths(&state), vec!["/a"]);
+
+        // Act & Assert: A changed file replaces its mock
+        std::fs::write(
+            dir.join("a.yaml"),
+            "when:\n  path: /a2\nthen:\n  status: 200\n",
+        )
+        .unwrap();
+        watcher.poll();
+        assert_eq!(mock_paths(&state), vec!["/a2"]);
+
+        // Act & Assert: An invalid file keeps the previous mock
+        std::fs::write(dir.join("a.yaml"), "when:\n  pathh: /a3\nthen: {}\n").unwrap();
+        watcher.poll();
+        assert_eq!(mock_paths(&state), vec!["/a2"]);
+
+        // Act & Assert: A new JSON file adds a mock
+        std::fs::write(
+            dir.join("b.json"),
+            r#"{"when": {"path": "/b"}, "then": {}}"#,
+        )
+        .unwrap();
+        watcher.poll();
+        assert_eq!(mock_paths(&state), vec!["/a2", "/b"]);
+
+        // Act & Assert: A deleted file removes its mock
+        std::fs::remove_file(dir.join("a.yaml")).unwrap();
+        watcher.poll();
+        assert_eq!(mock_paths(&state), vec!["/b"]);
+    }
 }
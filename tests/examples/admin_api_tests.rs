@@ -0,0 +1,184 @@
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+use serde_json::{json, Value};
+
+fn send(server: &MockServer, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(server.url(format!("/__httpmock__{}", path)))
+        .header("content-type", "application/json")
+        .body(body.map(|b| b.to_string()).unwrap_or_default())
+        .unwrap();
+
+    let mut response = request.send().unwrap();
+    let text = response.text().unwrap();
+    let body = serde_json::from_str(&text).unwrap_or(Value::Null);
+    (response.status().as_u16(), body)
+}
+
+fn mock_definition(path: &str, status: u16) -> Value {
+    json!({
+        "request": { "method": "GET", "path": path },
+        "response": { "status": status }
+    })
+}
+
+#[test]
+fn admin_api_mock_crud_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    // Act & Assert: Create
+    let (status, body) = send(&server, "POST", "/mocks", Some(mock_definition("/a", 200)));
+    assert_eq!(status, 201);
+    let id = body["mock_id"].as_u64().unwrap();
+
+    // Act & Assert: List and fetch
+    let (status, body) = send(&server, "GET", "/mocks", None);
+    assert_eq!(status, 200);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+
+    let (status, body) = send(&server, "GET", &format!("/mocks/{}", id), None);
+    assert_eq!(status, 200);
+    assert_eq!(body["definition"]["request"]["path"], "/a");
+
+    // Act & Assert: Update
+    let (status, _) = send(
+        &server,
+        "PUT",
+        &format!("/mocks/{}", id),
+        Some(mock_definition("/b", 204)),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(isahc::get(server.url("/a")).unwrap().status(), 404);
+    assert_eq!(isahc::get(server.url("/b")).unwrap().status(), 204);
+
+    // Act & Assert: Hit count and matched requests
+    let (_, body) = send(&server, "GET", &format!("/mocks/{}", id), None);
+    assert_eq!(body["call_counter"], 1);
+
+    let (status, body) = send(&server, "GET", &format!("/mocks/{}/requests", id), None);
+    assert_eq!(status, 200);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["path"], "/b");
+
+    // Act & Assert: Delete
+    let (status, _) = send(&server, "DELETE", &format!("/mocks/{}", id), None);
+    assert_eq!(status, 202);
+    let (status, _) = send(&server, "GET", &format!("/mocks/{}", id), None);
+    assert_eq!(status, 404);
+    let (status, _) = send(
+        &server,
+        "PUT",
+        &format!("/mocks/{}", id),
+        Some(mock_definition("/c", 200)),
+    );
+    assert_eq!(status, 404);
+}
+
+#[test]
+fn admin_api_history_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path("/matched");
+        then.status(200);
+    });
+    isahc::get(server.url("/matched")).unwrap();
+    isahc::get(server.url("/unmatched")).unwrap();
+
+    // Act
+    let (history_status, history) = send(&server, "GET", "/history", None);
+    let (unmatched_status, unmatched) = send(&server, "GET", "/history/unmatched", None);
+    let (verify_status, closest_match) = send(
+        &server,
+        "POST",
+        "/verify",
+        Some(json!({ "path": "/matche" })),
+    );
+
+    // Assert
+    assert_eq!(history_status, 200);
+    assert_eq!(history.as_array().unwrap().len(), 2);
+    assert_eq!(unmatched_status, 200);
+    assert_eq!(unmatched.as_array().unwrap().len(), 1);
+    assert_eq!(unmatched[0]["path"], "/unmatched");
+    assert_eq!(verify_status, 200);
+    assert_eq!(closest_match["request"]["path"], "/matched");
+
+    // Act & Assert: Delete the history
+    let (status, _) = send(&server, "DELETE", "/history", None);
+    assert_eq!(status, 202);
+    let (_, history) = send(&server, "GET", "/history", None);
+    assert_eq!(history.as_array().unwrap().len(), 0);
+    m.assert_hits(1);
+}
+
+#[test]
+fn admin_api_reset_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+    isahc::get(server.url("/hello")).unwrap();
+
+    // Act
+    let (status, _) = send(&server, "POST", "/reset", None);
+
+    // Assert
+    assert_eq!(status, 202);
+    let (_, mocks) = send(&server, "GET", "/mocks", None);
+    let (_, history) = send(&server, "GET", "/history", None);
+    assert_eq!(mocks.as_array().unwrap().len(), 0);
+    assert_eq!(history.as_array().unwrap().len(), 0);
+    assert_eq!(isahc::get(server.url("/hello")).unwrap().status(), 404);
+}
+
+#[test]
+fn admin_api_errors_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    // Act
+    let (invalid_status, invalid) = send(&server, "POST", "/mocks", Some(json!({"request": 1})));
+    let (unknown_status, _) = send(&server, "GET", "/mocks/9999/requests", None);
+
+    // Assert
+    assert_eq!(invalid_status, 500);
+    assert!(invalid["message"].as_str().is_some());
+    assert_eq!(unknown_status, 404);
+}
+
+#[test]
+fn admin_api_idempotency_key_test() {
+    // Arrange
+    let server = MockServer::start();
+    let create = |key: &str| {
+        let request = Request::post(server.url("/__httpmock__/mocks"))
+            .header("content-type", "application/json")
+            .header("x-httpmock-idempotency-key", key)
+            .body(mock_definition("/a", 200).to_string())
+            .unwrap();
+        let mut response = request.send().unwrap();
+        let body: Value = serde_json::from_str(&response.text().unwrap()).unwrap();
+        (
+            response.status().as_u16(),
+            body["mock_id"].as_u64().unwrap(),
+        )
+    };
+
+    // Act
+    let (first_status, first_id) = create("key-1");
+    let (repeated_status, repeated_id) = create("key-1");
+    let (_, other_id) = create("key-2");
+
+    // Assert
+    assert_eq!(first_status, 201);
+    assert_eq!(repeated_status, 201);
+    assert_eq!(first_id, repeated_id);
+    assert_ne!(first_id, other_id);
+    let (_, body) = send(&server, "GET", "/mocks", None);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+}
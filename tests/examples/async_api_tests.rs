@@ -0,0 +1,123 @@
+use httpmock::prelude::*;
+use isahc::{get, get_async};
+
+/// Creates, asserts, deletes and resets mocks using only the async API.
+async fn administer_mocks_async() {
+    // Arrange
+    let server = MockServer::start_async().await;
+
+    let m = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/hello");
+            then.status(200);
+        })
+        .await;
+
+    // Act
+    let response = get_async(server.url("/hello")).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(m.hits_async().await, 1);
+    m.assert_async().await;
+    m.assert_hits_async(1).await;
+
+    // Delete the mock and send the request again
+    m.delete_async().await;
+    let response = get_async(server.url("/hello")).await.unwrap();
+    assert_eq!(response.status(), 404);
+
+    // Reset the server, which removes all remaining mocks
+    server
+        .mock_async(|when, then| {
+            when.path("/reset");
+            then.status(200);
+        })
+        .await;
+    server.reset_async().await;
+    let response = get_async(server.url("/reset")).await.unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn tokio_async_api_test() {
+    administer_mocks_async().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tokio_multi_thread_async_api_test() {
+    administer_mocks_async().await;
+}
+
+#[async_std::test]
+async fn async_std_async_api_test() {
+    administer_mocks_async().await;
+}
+
+#[test]
+fn blocking_api_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mut m = server.mock(|when, then| {
+        when.method(GET).path("/hello");
+        then.status(200);
+    });
+
+    // Act
+    let response = get(server.url("/hello")).unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(m.hits(), 1);
+    m.assert();
+
+    m.delete();
+    let response = get(server.url("/hello")).unwrap();
+    assert_eq!(response.status(), 404);
+
+    server.mock(|when, then| {
+        when.path("/reset");
+        then.status(200);
+    });
+    server.reset();
+    let response = get(server.url("/reset")).unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+#[should_panic(expected = "Please use MockServer::start_async(..).await instead.")]
+async fn blocking_start_in_tokio_runtime_test() {
+    MockServer::start();
+}
+
+#[tokio::test]
+#[should_panic(expected = "Please use MockServer::connect_async(..).await instead.")]
+async fn blocking_connect_in_tokio_runtime_test() {
+    MockServer::connect("127.0.0.1:5000");
+}
+
+#[tokio::test]
+#[should_panic(expected = "Please use MockServer::mock_async(..).await instead.")]
+async fn blocking_mock_in_tokio_runtime_test() {
+    let server = MockServer::start_async().await;
+
+    server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+}
+
+#[tokio::test]
+#[should_panic(expected = "Please use Mock::hits_async(..).await instead.")]
+async fn blocking_hits_in_tokio_runtime_test() {
+    let server = MockServer::start_async().await;
+    let m = server
+        .mock_async(|when, then| {
+            when.path("/hello");
+            then.status(200);
+        })
+        .await;
+
+    m.hits();
+}
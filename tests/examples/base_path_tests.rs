@@ -0,0 +1,106 @@
+use httpmock::prelude::*;
+use isahc::get;
+
+#[test]
+fn base_path_test() {
+    // Arrange
+    let server = MockServer::builder().base_path("/api/v2/").start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/users");
+        then.status(200);
+    });
+
+    // Act
+    let prefixed_response = get(server.url("/users")).unwrap();
+    let root_response = get(format!("http://{}/users", server.address())).unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(prefixed_response.status(), 200);
+    assert_eq!(root_response.status(), 404);
+    assert_eq!(
+        server.base_url(),
+        format!("http://{}/api/v2", server.address())
+    );
+    assert_eq!(m.url(), format!("http://{}/api/v2/users", server.address()));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn set_base_path_test() {
+    // Arrange
+    let mut server = MockServer::start();
+    server.set_base_path("api/v2");
+
+    let m = server.mock(|when, then| {
+        when.path_template("/users/{id}");
+        then.status(200);
+    });
+
+    // Act
+    let response = get(server.url("/users/42")).unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(response.status(), 200);
+}
+
+#[test]
+fn ignore_base_path_test() {
+    // Arrange
+    let server = MockServer::builder().base_path("/api/v2").start();
+
+    let m = server.mock(|when, then| {
+        when.path("/health").ignore_base_path();
+        then.status(200);
+    });
+
+    // Act
+    let root_response = get(format!("http://{}/health", server.address())).unwrap();
+    let prefixed_response = get(server.url("/health")).unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(root_response.status(), 200);
+    assert_eq!(prefixed_response.status(), 404);
+    assert_eq!(m.url(), format!("http://{}/health", server.address()));
+}
+
+#[test]
+#[should_panic(
+    expected = "(the request path is '/api/v2/userz', which is '/userz' without the base path '/api/v2')"
+)]
+fn base_path_mismatch_test() {
+    // Arrange
+    let server = MockServer::builder().base_path("/api/v2").start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users");
+        then.status(200);
+    });
+
+    // Act
+    get(server.url("/userz")).unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+#[should_panic(expected = "The path does not start with the base path '/api/v2' of the mock")]
+fn missing_base_path_test() {
+    // Arrange
+    let server = MockServer::builder().base_path("/api/v2").start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users");
+        then.status(200);
+    });
+
+    // Act
+    get(format!("http://{}/users", server.address())).unwrap();
+
+    // Assert
+    m.assert();
+}
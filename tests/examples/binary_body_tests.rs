@@ -1,5 +1,5 @@
 use httpmock::prelude::*;
-use isahc::Body;
+use isahc::{prelude::*, Body, Request};
 use std::io::Read;
 
 #[test]
@@ -23,6 +23,59 @@ fn binary_body_test() {
     assert_eq!(body_to_vec(response.body_mut()), binary_content.to_vec());
 }
 
+#[test]
+fn binary_request_body_test() {
+    // Arrange
+    let binary_content = b"\x80\x02\x03\xff\x00";
+
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST).path("/upload").body_bytes(binary_content);
+        then.status(201);
+    });
+
+    // Act
+    let send = |body: &[u8]| {
+        Request::post(server.url("/upload"))
+            .body(body.to_vec())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(send(binary_content), 201);
+    assert_eq!(send(b"\x80\x02\x03\xfe\x00"), 404);
+    assert_eq!(send(b"\x80\x02\x03"), 404);
+    m.assert_hits(1);
+}
+
+#[test]
+#[should_panic(
+    expected = "The body does not match: expected 3 bytes but got 4 bytes, which differ at offset 1 (expected 0x02 but got 0x00)"
+)]
+fn binary_request_body_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/upload").body_bytes(b"\x80\x02\x03");
+        then.status(201);
+    });
+
+    // Act
+    Request::post(server.url("/upload"))
+        .body(b"\x80\x00\x03\x04".to_vec())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    m.assert();
+}
+
 fn body_to_vec(body: &mut Body) -> Vec<u8> {
     let mut buf: Vec<u8> = Vec::new();
     body.read_to_end(&mut buf).expect("Cannot read from body");
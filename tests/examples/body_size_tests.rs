@@ -0,0 +1,223 @@
+use futures_util::stream;
+use httpmock::prelude::*;
+use hyper::{Body, Client, Request};
+use isahc::RequestExt;
+use std::io::Read;
+
+#[test]
+fn body_size_limit_test() {
+    // Arrange
+    let server = MockServer::builder().max_body_bytes(10).start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST).path("/upload").body_contains("0");
+        then.status(201);
+    });
+
+    // Act
+    let accepted = isahc::Request::post(server.url("/upload"))
+        .body("0123456789")
+        .unwrap()
+        .send()
+        .unwrap();
+    let rejected = isahc::Request::post(server.url("/upload"))
+        .body("0123456789A")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    assert_eq!(accepted.status(), 201);
+    assert_eq!(rejected.status(), 413);
+    m.assert_hits(1);
+}
+
+#[tokio::test]
+async fn chunked_body_size_limit_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .max_body_bytes(1024)
+        .start_async()
+        .await;
+
+    let m = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/upload").body_contains("x");
+            then.status(201);
+        })
+        .await;
+
+    // The body is sent in chunks without a Content-Length header.
+    let chunks = (0..10).map(|_| Ok::<_, std::io::Error>(vec![b'x'; 512]));
+    let request = Request::post(server.url("/upload"))
+        .body(Body::wrap_stream(stream::iter(chunks)))
+        .unwrap();
+
+    // Act
+    let response = Client::new().request(request).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 413);
+    m.assert_hits_async(0).await;
+}
+
+#[test]
+fn large_upload_without_body_matcher_test() {
+    // Arrange: The limit would allow to buffer the whole body.
+    let server = MockServer::builder()
+        .max_body_bytes(200 * 1024 * 1024)
+        .start();
+
+    let upload_mock = server.mock(|when, then| {
+        when.method(POST).path("/upload");
+        then.status(201);
+    });
+    let body_mock = server.mock(|when, then| {
+        when.method(POST).path("/other").body_contains("x");
+        then.status(200);
+    });
+
+    let size = 100 * 1024 * 1024;
+    let body = isahc::Body::from_reader_sized(std::io::repeat(b'x').take(size), size);
+
+    // Act
+    let response = isahc::Request::post(server.url("/upload"))
+        .body(body)
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 201);
+    upload_mock.assert();
+    body_mock.assert_hits(0);
+    // Only the part of the body that is recorded in the request history is read into memory.
+    let buffered = server.stats().buffered_body_bytes;
+    assert!(buffered <= 64 * 1024, "{} bytes were buffered", buffered);
+}
+
+#[test]
+fn body_len_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let exact_mock = server.mock(|when, then| {
+        when.method(POST).path("/exact").body_len(4);
+        then.status(201);
+    });
+
+    let range_mock = server.mock(|when, then| {
+        when.method(POST).path("/range").body_len_range(1..=4096);
+        then.status(201);
+    });
+
+    // Act
+    let exact = isahc::Request::post(server.url("/exact"))
+        .body("abcd")
+        .unwrap()
+        .send()
+        .unwrap();
+    let in_range = isahc::Request::post(server.url("/range"))
+        .body(vec![b'x'; 4096])
+        .unwrap()
+        .send()
+        .unwrap();
+    let too_large = isahc::Request::post(server.url("/range"))
+        .body(vec![b'x'; 4097])
+        .unwrap()
+        .send()
+        .unwrap();
+    let empty = isahc::Request::post(server.url("/range"))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    assert_eq!(exact.status(), 201);
+    assert_eq!(in_range.status(), 201);
+    assert_eq!(too_large.status(), 404);
+    assert_eq!(empty.status(), 404);
+    exact_mock.assert();
+    range_mock.assert_hits(1);
+}
+
+#[test]
+#[should_panic(expected = "The body length does not match")]
+fn body_len_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST).path("/upload").body_len_range(..4);
+        then.status(201);
+    });
+
+    // Act
+    isahc::Request::post(server.url("/upload"))
+        .body("abcd")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[tokio::test]
+async fn chunked_body_test() {
+    // Arrange
+    let server = MockServer::start_async().await;
+
+    let m = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/upload")
+                .chunked_body()
+                .body("chunk-1;chunk-2;chunk-3");
+            then.status(201);
+        })
+        .await;
+
+    let chunks = ["chunk-1;", "chunk-2;", "chunk-3"].map(Ok::<_, std::io::Error>);
+    let chunked = Request::post(server.url("/upload"))
+        .body(Body::wrap_stream(stream::iter(chunks)))
+        .unwrap();
+    let buffered = Request::post(server.url("/upload"))
+        .body(Body::from("chunk-1;chunk-2;chunk-3"))
+        .unwrap();
+
+    // Act
+    let chunked = Client::new().request(chunked).await.unwrap();
+    let buffered = Client::new().request(buffered).await.unwrap();
+
+    // Assert
+    assert_eq!(chunked.status(), 201);
+    assert_eq!(buffered.status(), 404);
+    m.assert_hits_async(1).await;
+}
+
+#[test]
+#[should_panic(
+    expected = "The transfer encoding does not match: expected a chunked body but the request \
+                has a Content-Length of 8 bytes"
+)]
+fn chunked_body_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST).path("/upload").chunked_body();
+        then.status(201);
+    });
+
+    // Act
+    isahc::Request::post(server.url("/upload"))
+        .body("buffered")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    m.assert();
+}
@@ -0,0 +1,188 @@
+use httpmock::prelude::*;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+#[test]
+fn keep_alive_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act: Send two requests on the same connection
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    let first = send_request(&mut stream, "/hello");
+    let second = send_request(&mut stream, "/hello");
+
+    // Assert
+    assert!(first.starts_with("HTTP/1.1 200"));
+    assert!(second.starts_with("HTTP/1.1 200"));
+    m.assert_hits(2);
+}
+
+#[test]
+fn connection_close_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200).connection_close();
+    });
+
+    // Act
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    let response = send_request(&mut stream, "/hello");
+
+    // Assert
+    assert!(response.to_lowercase().contains("connection: close"));
+    assert!(is_closed(&mut stream));
+    m.assert();
+}
+
+#[test]
+fn keep_alive_disabled_test() {
+    // Arrange
+    let server = MockServer::builder().keep_alive(false).start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    let response = send_request(&mut stream, "/hello");
+
+    // Assert
+    assert!(response.to_lowercase().contains("connection: close"));
+    assert!(is_closed(&mut stream));
+    m.assert();
+}
+
+#[test]
+fn idle_timeout_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .idle_timeout(Duration::from_millis(200))
+        .start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    let response = send_request(&mut stream, "/hello");
+    let start = Instant::now();
+
+    // Assert: The server closes the connection once it has been idle for the timeout
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(is_closed(&mut stream));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    m.assert();
+}
+
+#[test]
+fn http10_connection_close_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path("/hello").http_version("HTTP/1.0");
+        then.status(200)
+            .header("Transfer-Encoding", "chunked")
+            .body("hello");
+    });
+
+    // Act: HTTP/1.0 clients do not need to send a Host header
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    stream.write_all(b"GET /hello HTTP/1.0\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    // Assert: The response is not chunked and the connection is closed afterwards
+    let response = response.to_lowercase();
+    assert!(response.starts_with("http/1.0 200"));
+    assert!(response.contains("content-length: 5"));
+    assert!(!response.contains("transfer-encoding"));
+    assert!(response.ends_with("\r\n\r\nhello"));
+    m.assert();
+}
+
+#[test]
+fn http10_keep_alive_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    let mut responses = Vec::new();
+    for _ in 0..2 {
+        stream
+            .write_all(b"GET /hello HTTP/1.0\r\nConnection: keep-alive\r\n\r\n")
+            .unwrap();
+        responses.push(read_response_header(&mut stream));
+    }
+
+    // Assert
+    for response in responses {
+        assert!(response.starts_with("HTTP/1.0 200"));
+        assert!(response.to_lowercase().contains("connection: keep-alive"));
+    }
+    m.assert_hits(2);
+}
+
+#[test]
+fn http10_missing_host_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path("/hello").host("localhost");
+        then.status(200);
+    });
+
+    // Act
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    stream.write_all(b"GET /hello HTTP/1.0\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    // Assert: Host matchers do not match requests without a host
+    assert!(response.starts_with("HTTP/1.0 404"));
+    m.assert_hits(0);
+}
+
+/// Sends a GET request and reads the response header. Bodies are not expected.
+fn send_request(stream: &mut TcpStream, path: &str) -> String {
+    write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+    read_response_header(stream)
+}
+
+/// Reads a response header. Bodies are not expected.
+fn read_response_header(stream: &mut TcpStream) -> String {
+    let mut response = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !response.ends_with(b"\r\n\r\n") {
+        let n = stream.read(&mut buf).unwrap();
+        assert!(
+            n > 0,
+            "connection was closed before the response was received"
+        );
+        response.extend_from_slice(&buf[..n]);
+    }
+
+    String::from_utf8(response).unwrap()
+}
+
+/// Returns whether the server closed the connection within a few seconds.
+fn is_closed(stream: &mut TcpStream) -> bool {
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    matches!(stream.read(&mut [0u8; 1]), Ok(0))
+}
@@ -15,7 +15,7 @@ fn cookie_matching_test() {
     });
 
     // Act: Send the request and deserialize the response to JSON
-    let response = Request::get(&format!("http://{}", server.address()))
+    let response = Request::get(&server.base_url())
         .header(
             "Cookie",
             "OTHERCOOKIE1=01234; SESSIONID=298zf09hf012fh2; OTHERCOOKIE2=56789; HttpOnly",
@@ -29,3 +29,30 @@ fn cookie_matching_test() {
     mock.assert();
     assert_eq!(response.status(), 200);
 }
+
+#[test]
+fn cookie_formatting_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.path("/cookies")
+            .cookie("SESSIONID", "298zf09hf012fh2")
+            .cookie("theme", "dark")
+            .cookie_exists("consent");
+        then.status(200);
+    });
+
+    // Act: The cookies are spread over two headers with irregular whitespace
+    let response = Request::get(&server.url("/cookies"))
+        .header("Cookie", "theme=\"dark\";SESSIONID = 298zf09hf012fh2 ;")
+        .header("Cookie", "consent=yes")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    mock.assert();
+    assert_eq!(response.status(), 200);
+}
@@ -0,0 +1,68 @@
+use httpmock::prelude::*;
+
+#[cfg(feature = "regex")]
+#[test]
+fn mock_to_curl_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(PUT)
+            .path_matches(Regex::new("^/users/\\d+$").unwrap())
+            .query_param("notify", "true")
+            .header("x-api-key", "secret")
+            .cookie("session", "1")
+            .json_body(serde_json::json!({ "name": "Fred" }));
+        then.status(200);
+    });
+
+    // Act
+    let curl = mock.to_curl();
+
+    // Assert
+    assert_eq!(
+        curl,
+        format!(
+            "# the path must match the regex '^/users/\\d+$'\n\
+             curl -X PUT '{}/?notify=true' \\\n  \
+             -H 'x-api-key: secret' \\\n  \
+             -H 'Content-Type: application/json' \\\n  \
+             -b 'session=1' \\\n  \
+             --data-raw '{{\"name\":\"Fred\"}}'",
+            server.base_url()
+        )
+    );
+}
+
+#[test]
+fn export_curl_script_test() {
+    // Arrange
+    let server = MockServer::start();
+    let a = server.mock(|when, then| {
+        when.method(GET).path("/a");
+        then.status(200);
+    });
+    let b = server.mock(|when, then| {
+        when.method(DELETE).path("/b");
+        then.status(204);
+    });
+    let path = std::env::temp_dir()
+        .join(format!("httpmock-curl-{}", std::process::id()))
+        .join("requests.sh");
+
+    // Act
+    server.export_curl_script(&path);
+
+    // Assert
+    let script = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        script,
+        format!(
+            "#!/bin/sh\n\n# Mock {}\n{}\n\n# Mock {}\n{}\n",
+            a.id,
+            a.to_curl(),
+            b.id,
+            b.to_curl()
+        )
+    );
+    assert!(script.contains(&format!("curl -X DELETE '{}/b'", server.base_url())));
+}
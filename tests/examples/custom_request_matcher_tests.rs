@@ -1,8 +1,11 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
 use httpmock::prelude::*;
-use isahc::get;
+use httpmock::Matcher;
+use isahc::{get, prelude::*, Request};
 
 #[test]
-// TODO: Implement custom matcher
 fn my_custom_request_matcher_test() {
     // Arrange
     let server = MockServer::start();
@@ -19,3 +22,174 @@ fn my_custom_request_matcher_test() {
     mock.assert();
     assert_eq!(response.status(), 200);
 }
+
+#[test]
+fn custom_matcher_full_request_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.matches(|req| req.method == "POST")
+            .matches(|req| {
+                req.headers.as_ref().is_some_and(|headers| {
+                    headers
+                        .iter()
+                        .any(|(name, value)| name == "x-tenant" && value.starts_with("acme-"))
+                })
+            })
+            .matches(|req| req.body.as_ref().is_some_and(|body| body.len() % 2 == 0));
+        then.status(201);
+    });
+
+    // Act
+    let send = |tenant: &str, body: &str| {
+        Request::post(server.url("/orders"))
+            .header("X-Tenant", tenant)
+            .body(body.to_string())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(send("acme-1", "even"), 201);
+    assert_eq!(send("other", "even"), 404);
+    assert_eq!(send("acme-1", "odd"), 404);
+    assert_eq!(get(server.url("/orders")).unwrap().status(), 404);
+    m.assert_hits(1);
+}
+
+#[test]
+fn custom_matcher_capturing_closure_test() {
+    // Arrange
+    let server = MockServer::start();
+    let calls = Arc::new(AtomicUsize::new(0));
+    let allowed = ["/a".to_string(), "/b".to_string()];
+
+    let counter = calls.clone();
+    let m = server.mock(move |when, then| {
+        when.matches(move |req| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            allowed.contains(&req.path)
+        });
+        then.status(200);
+    });
+
+    // Act
+    let statuses: Vec<u16> = ["/a", "/b", "/c"]
+        .iter()
+        .map(|path| get(server.url(*path)).unwrap().status().as_u16())
+        .collect();
+
+    // Assert
+    assert_eq!(statuses, vec![200, 200, 404]);
+    assert!(calls.load(Ordering::SeqCst) >= 3);
+    m.assert_hits(2);
+}
+
+#[test]
+fn custom_matcher_panic_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let panicking = server.mock(|when, then| {
+        when.path("/items")
+            .matches(|_| -> bool { panic!("broken matcher") });
+        then.status(500);
+    });
+    let fallback = server.mock(|when, then| {
+        when.path("/items");
+        then.status(200);
+    });
+
+    // Act
+    let first = get(server.url("/items")).unwrap();
+    let second = get(server.url("/items")).unwrap();
+
+    // Assert
+    assert_eq!(first.status(), 200);
+    assert_eq!(second.status(), 200);
+    panicking.assert_hits(0);
+    fallback.assert_hits(2);
+}
+
+struct TenantHeader(&'static str);
+
+impl Matcher for TenantHeader {
+    fn matches(&self, req: &HttpMockRequest) -> bool {
+        req.headers
+            .iter()
+            .flatten()
+            .any(|(name, value)| name.eq_ignore_ascii_case("x-tenant") && value == self.0)
+    }
+
+    fn describe(&self) -> String {
+        format!("the tenant header '{}'", self.0)
+    }
+}
+
+struct PanickingMatcher;
+
+impl Matcher for PanickingMatcher {
+    fn matches(&self, _req: &HttpMockRequest) -> bool {
+        panic!("this matcher always panics")
+    }
+
+    fn describe(&self) -> String {
+        "a matcher that panics".to_string()
+    }
+}
+
+#[test]
+fn expect_matcher_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/orders")
+            .custom_matcher(Box::new(TenantHeader("acme")));
+        then.status(200);
+    });
+    let panicking = server.mock(|when, then| {
+        when.path("/panic")
+            .custom_matcher(Box::new(PanickingMatcher));
+        then.status(200);
+    });
+
+    // Act
+    let send = |path: &str, tenant: &str| {
+        Request::get(server.url(path))
+            .header("X-Tenant", tenant)
+            .body(())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(send("/orders", "acme"), 200);
+    assert_eq!(send("/orders", "other"), 404);
+    assert_eq!(send("/panic", "acme"), 404);
+    m.assert_hits(1);
+    panicking.assert_hits(0);
+}
+
+#[test]
+#[should_panic(expected = "The custom matcher does not match: expected the tenant header 'acme'")]
+fn expect_matcher_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.custom_matcher(Box::new(TenantHeader("acme")));
+        then.status(200);
+    });
+
+    // Act
+    get(server.url("/orders")).unwrap();
+
+    // Assert
+    m.assert();
+}
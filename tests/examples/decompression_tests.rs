@@ -0,0 +1,98 @@
+use std::io::Write;
+
+use flate2::write::{DeflateEncoder, GzEncoder};
+use flate2::Compression;
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+use serde_json::json;
+
+fn gzip(body: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body).unwrap();
+    encoder.finish().unwrap()
+}
+
+#[test]
+fn decompress_request_bodies_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .decompress_request_bodies(true)
+        .start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST)
+            .path("/users")
+            .header_missing("content-encoding")
+            .json_body(json!({ "name": "Fred" }));
+        then.status(201);
+    });
+
+    let mut deflate = DeflateEncoder::new(Vec::new(), Compression::default());
+    deflate.write_all(br#"{"name":"Fred"}"#).unwrap();
+
+    // Act
+    let send = |encoding: &str, body: Vec<u8>| {
+        Request::post(server.url("/users"))
+            .header("Content-Encoding", encoding)
+            .body(body)
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(send("gzip", gzip(br#"{"name":"Fred"}"#)), 201);
+    assert_eq!(send("deflate", deflate.finish().unwrap()), 201);
+    assert_eq!(send("gzip", gzip(br#"{"name":"Bob"}"#)), 404);
+    m.assert_hits(2);
+}
+
+#[test]
+fn compressed_request_bodies_are_not_decompressed_by_default_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users").body_contains("Fred");
+        then.status(201);
+    });
+
+    // Act
+    let response = Request::post(server.url("/users"))
+        .header("Content-Encoding", "gzip")
+        .body(gzip(br#"{"name":"Fred"}"#))
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 404);
+    m.assert_hits(0);
+}
+
+#[test]
+fn decompressed_body_size_limit_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .decompress_request_bodies(true)
+        .max_body_bytes(100)
+        .start();
+
+    let m = server.mock(|when, then| {
+        when.path("/upload").body_contains("a");
+        then.status(200);
+    });
+
+    // Act
+    let response = Request::post(server.url("/upload"))
+        .header("Content-Encoding", "gzip")
+        .body(gzip(&[b'a'; 1000]))
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 413);
+    m.assert_hits(0);
+}
@@ -12,12 +12,7 @@ fn explicit_delete_mock_test() {
     });
 
     // Act: Send the HTTP request
-    let response = get(&format!(
-        "http://{}:{}/health",
-        server.host(),
-        server.port()
-    ))
-    .unwrap();
+    let response = get(m.url()).unwrap();
 
     // Assert
     m.assert();
@@ -26,7 +21,7 @@ fn explicit_delete_mock_test() {
     // Delete the mock and send the request again
     m.delete();
 
-    let response = get(&format!("http://{}/health", server.address())).unwrap();
+    let response = get(&server.url("/health")).unwrap();
 
     // Assert that the request failed, because the mock has been deleted
     assert_eq!(response.status(), 404);
@@ -0,0 +1,59 @@
+use httpmock::prelude::*;
+
+#[test]
+fn mock_display_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(GET)
+            .path("/search")
+            .query_param("query", "metallica")
+            .header_exists("authorization");
+        then.status(200);
+    });
+
+    // Act
+    let output = mock.to_string();
+
+    // Assert
+    assert_eq!(
+        output,
+        format!(
+            "Mock {}: GET /search\n  \
+             method equals GET\n  \
+             path equals '/search'\n  \
+             query parameter 'query' equals 'metallica'\n  \
+             header 'authorization' exists",
+            mock.id
+        )
+    );
+    assert_eq!(
+        format!("{:?}", mock),
+        format!(
+            "Mock {{ id: {}, description: Some(\"GET /search\") }}",
+            mock.id
+        )
+    );
+}
+
+#[test]
+fn deleted_mock_display_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mut mock = server.mock(|when, then| {
+        when.method(POST).path("/users");
+        then.status(201);
+    });
+    mock.delete();
+
+    // Act
+    let output = mock.to_string();
+
+    // Assert
+    assert!(output.starts_with(&format!(
+        "Mock POST /users (ID {}) (cannot fetch the mock from the mock server",
+        mock.id
+    )));
+}
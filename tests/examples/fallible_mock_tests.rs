@@ -0,0 +1,91 @@
+use httpmock::prelude::*;
+use httpmock::MockError;
+
+use crate::simulate_standalone_server;
+
+#[test]
+fn try_mock_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    // Act
+    let mut mock = server
+        .try_mock(|when, then| {
+            when.method(GET).path("/search");
+            then.status(200);
+        })
+        .unwrap();
+    let response = isahc::get(server.url("/search")).unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(mock.try_hits(), Ok(1));
+    assert_eq!(mock.try_delete(), Ok(()));
+}
+
+#[test]
+fn try_mock_connection_failed_test() {
+    // Arrange
+    let server = MockServer::builder().start();
+    server.shutdown();
+
+    // Act
+    let result = server.try_mock(|when, then| {
+        when.method(POST).path("/users");
+        then.status(201);
+    });
+
+    // Assert
+    let err = result.err().unwrap();
+    assert!(matches!(err, MockError::ConnectionFailed { .. }));
+    assert!(err.to_string().contains("POST /users"));
+    assert!(err
+        .to_string()
+        .contains("The mock server has been shut down"));
+}
+
+#[test]
+fn try_mock_invalid_definition_test() {
+    // Arrange
+    simulate_standalone_server();
+    let server = MockServer::connect_from_env();
+
+    // Act
+    let result = server.try_mock(|when, then| {
+        when.method(GET).path("/custom").matches(|_| true);
+        then.status(200);
+    });
+
+    // Assert
+    match result.err().unwrap() {
+        MockError::InvalidMockDefinition { mock, reason } => {
+            assert_eq!(mock, "GET /custom");
+            assert!(reason.contains("Anonymous function request matchers are not supported"));
+        }
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn try_delete_server_rejected_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mut mock = server.mock(|when, then| {
+        when.method(DELETE).path("/users/1");
+        then.status(204);
+    });
+    mock.delete();
+
+    // Act
+    let delete_result = mock.try_delete();
+    let hits_result = mock.try_hits();
+
+    // Assert
+    let err = delete_result.err().unwrap();
+    assert!(matches!(err, MockError::ServerRejected { status: 404, .. }));
+    assert!(err.to_string().contains("DELETE /users/1"));
+    assert!(matches!(
+        hits_result,
+        Err(MockError::ServerRejected { status: 404, .. })
+    ));
+}
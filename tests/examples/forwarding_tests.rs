@@ -0,0 +1,107 @@
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+use serde_json::Value;
+
+#[test]
+fn forward_unmatched_test() {
+    // Arrange
+    let upstream = MockServer::start();
+    let upstream_mock = upstream.mock(|when, then| {
+        when.method(POST)
+            .path("/api/users")
+            .query_param("notify", "true")
+            .header("host", upstream.address().to_string())
+            .header("x-request-id", "42")
+            .body("name=fred");
+        then.status(201)
+            .header("x-upstream", "staging")
+            .body("created upstream");
+    });
+
+    let server = MockServer::builder()
+        .forward_unmatched_to(upstream.url("/api"))
+        .start();
+    let mock = server.mock(|when, then| {
+        when.path("/health");
+        then.status(200).body("mocked");
+    });
+
+    // Act
+    let mut mocked = isahc::get(server.url("/health")).unwrap();
+    let mut forwarded = Request::post(server.url("/users?notify=true"))
+        .header("x-request-id", "42")
+        .body("name=fred")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    mock.assert();
+    upstream_mock.assert();
+    assert_eq!(mocked.text().unwrap(), "mocked");
+    assert_eq!(forwarded.status(), 201);
+    assert_eq!(forwarded.headers().get("x-upstream").unwrap(), "staging");
+    assert_eq!(forwarded.text().unwrap(), "created upstream");
+}
+
+#[test]
+fn forward_unmatched_history_test() {
+    // Arrange
+    let upstream = MockServer::start();
+    let server = MockServer::builder()
+        .forward_unmatched_to(upstream.base_url())
+        .start();
+
+    // Act: The upstream does not have a mock for this path either
+    let response = isahc::get(server.url("/missing")).unwrap();
+    let history: Value = isahc::get(server.url("/__httpmock__/history"))
+        .unwrap()
+        .json()
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 404);
+    assert_eq!(history[0]["path"], "/missing");
+    assert_eq!(history[0]["forwarded"], true);
+    assert_eq!(history[0]["upstream_status"], 404);
+}
+
+#[test]
+fn forward_unmatched_streams_large_body_test() {
+    // Arrange
+    let body: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let upstream = MockServer::start();
+    upstream.mock(|when, then| {
+        when.path("/download");
+        then.status(200).body(body.clone());
+    });
+    let server = MockServer::builder()
+        .forward_unmatched_to(upstream.base_url())
+        .start();
+
+    // Act
+    let mut response = isahc::get(server.url("/download")).unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.bytes().unwrap(), body);
+}
+
+#[test]
+fn forward_unmatched_upstream_error_test() {
+    // Arrange: Nothing listens on the upstream port anymore
+    let upstream_url = MockServer::builder().start().base_url();
+    let server = MockServer::builder()
+        .forward_unmatched_to(upstream_url)
+        .start();
+
+    // Act
+    let mut response = isahc::get(server.url("/hello")).unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 502);
+    assert!(response
+        .text()
+        .unwrap()
+        .contains("Cannot forward request to upstream server"));
+}
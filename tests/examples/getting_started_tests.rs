@@ -26,6 +26,27 @@ fn getting_started_test() {
     assert_eq!(response.status(), 200);
 }
 
+#[test]
+fn mock_with_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let search_mock = server.mock_with(|when, then| {
+        when.method(GET)
+            .path("/search")
+            .query_param("query", "metallica");
+        then.status(204).header("X-Trace", "1");
+    });
+
+    // Act
+    let response = get(server.url("/search?query=metallica")).unwrap();
+
+    // Assert
+    search_mock.assert();
+    assert_eq!(response.status(), 204);
+    assert_eq!(response.headers()["X-Trace"], "1");
+}
+
 #[async_std::test]
 async fn async_getting_started_test() {
     // Start a local mock server for exclusive use by this test function.
@@ -42,7 +63,7 @@ async fn async_getting_started_test() {
         .await;
 
     // Send an HTTP request to the mock server. This simulates your code.
-    let url = format!("http://{}/hello", server.address());
+    let url = server.url("/hello");
     let response = get_async(&url).await.unwrap();
 
     // Ensure the specified mock responded exactly one time.
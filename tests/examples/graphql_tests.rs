@@ -0,0 +1,199 @@
+use httpmock::prelude::*;
+use httpmock::GraphQLError;
+use isahc::{prelude::*, Request};
+use serde_json::{json, Value};
+
+const SCHEMA: &str = "tests/resources/graphql/schema.graphql";
+
+fn post_graphql(server: &MockServer, query: &str) -> (u16, String, Value) {
+    let mut response = Request::post(server.url("/graphql"))
+        .header("content-type", "application/json")
+        .body(json!({ "query": query }).to_string())
+        .unwrap()
+        .send()
+        .unwrap();
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .map(|v| v.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let body = serde_json::from_str(&response.text().unwrap()).unwrap();
+    (response.status().as_u16(), content_type, body)
+}
+
+#[test]
+fn graphql_data_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.load_graphql_schema(SCHEMA);
+
+    let user_mock = server.mock(|when, then| {
+        when.path("/graphql")
+            .graphql_query("query GetUser { user(id: \"1\") { id name role } }");
+        then.graphql_data(json!({
+            "user": { "id": "1", "name": "Fred", "role": "ADMIN" }
+        }));
+    });
+    let create_mock = server.mock(|when, then| {
+        when.path("/graphql")
+            .graphql_query("mutation { createUser(name: \"Fred\") { id } }");
+        then.graphql_data(json!({ "createUser": { "id": "2" } }));
+    });
+
+    // Act
+    let (status, content_type, body) = post_graphql(
+        &server,
+        "query GetUser {\n  user(id: \"1\") {\n    id\n    name\n    role\n  }\n}",
+    );
+    let (_, _, created) = post_graphql(&server, "mutation{createUser(name:\"Fred\"){id}}");
+
+    // Assert
+    user_mock.assert();
+    create_mock.assert();
+    assert_eq!(status, 200);
+    assert_eq!(content_type, "application/json");
+    assert_eq!(
+        body,
+        json!({ "data": { "user": { "id": "1", "name": "Fred", "role": "ADMIN" } } })
+    );
+    assert_eq!(created, json!({ "data": { "createUser": { "id": "2" } } }));
+}
+
+#[test]
+fn graphql_errors_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.load_graphql_schema(SCHEMA);
+
+    server.mock(|when, then| {
+        when.path("/graphql");
+        then.graphql_data(json!({ "user": null }))
+            .graphql_errors(vec![GraphQLError::new("User not found")
+                .path("user")
+                .location(1, 3)
+                .extension("code", "NOT_FOUND")]);
+    });
+
+    // Act
+    let (_, _, body) = post_graphql(&server, "{ user(id: \"7\") { id } }");
+
+    // Assert
+    assert_eq!(
+        body,
+        json!({
+            "data": { "user": null },
+            "errors": [{
+                "message": "User not found",
+                "locations": [{ "line": 1, "column": 3 }],
+                "path": ["user"],
+                "extensions": { "code": "NOT_FOUND" }
+            }]
+        })
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "GraphQL data does not match the schema: field 'data.search[1].title' is expected to be of type 'String', but is 42"
+)]
+fn graphql_schema_mismatch_test() {
+    let server = MockServer::start();
+    server.load_graphql_schema(SCHEMA);
+
+    server.mock(|when, then| {
+        when.graphql_query("{ search(text: \"x\") { ... on Post { title } } }");
+        then.graphql_data(json!({
+            "search": [
+                { "__typename": "Post", "title": "Hello" },
+                { "__typename": "Post", "title": 42 }
+            ]
+        }));
+    });
+}
+
+#[test]
+#[should_panic(
+    expected = "GraphQL data does not match the schema: field 'data.createUser' is not defined on type 'Query'"
+)]
+fn graphql_operation_type_mismatch_test() {
+    let server = MockServer::start();
+    server.load_graphql_schema(SCHEMA);
+
+    server.mock(|_, then| {
+        then.graphql_data(json!({ "createUser": { "id": "2" } }));
+    });
+}
+
+#[test]
+fn graphql_operation_and_variables_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/graphql")
+            .graphql_operation("GetUser")
+            .graphql_variables(json!({ "id": "1" }));
+        then.graphql_data(json!({ "user": { "name": "Fred" } }));
+    });
+
+    // Act
+    let send = |body: Value| {
+        Request::post(server.url("/graphql"))
+            .body(body.to_string())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+            .as_u16()
+    };
+
+    // Assert
+    let query = "query GetUser($id: ID!) { user(id: $id) { name } }";
+    assert_eq!(
+        send(json!({ "query": query, "variables": { "id": "1" } })),
+        200
+    );
+    assert_eq!(
+        send(json!({
+            "query": "# reformatted\nquery GetUser($id: ID!) {\n  user(id: $id) {\n    name\n  }\n}",
+            "variables": { "id": "1" }
+        })),
+        200
+    );
+    assert_eq!(
+        send(json!({
+            "query": format!("{} query GetUsers {{ users {{ name }} }}", query),
+            "operationName": "GetUser",
+            "variables": { "id": "1" }
+        })),
+        200
+    );
+    assert_eq!(
+        send(json!({ "query": query, "variables": { "id": "2" } })),
+        404
+    );
+    assert_eq!(send(json!({ "query": query })), 404);
+    assert_eq!(
+        send(json!({ "query": "query GetUsers { users { name } }", "variables": { "id": "1" } })),
+        404
+    );
+    m.assert_hits(3);
+}
+
+#[test]
+#[should_panic(expected = "The GraphQL operation does not match")]
+fn graphql_operation_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/graphql").graphql_operation("GetUser");
+        then.graphql_data(json!({ "user": null }));
+    });
+
+    // Act
+    post_graphql(&server, "query GetUsers { users { name } }");
+
+    // Assert
+    m.assert();
+}
@@ -0,0 +1,106 @@
+use httpmock::prelude::*;
+use httpmock::GrpcCode;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::Channel;
+use tonic::{Code, Response, Status};
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct HelloRequest {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct HelloReply {
+    #[prost(string, tag = "1")]
+    message: String,
+}
+
+const SAY_HELLO: &str = "helloworld.Greeter/SayHello";
+
+/// Calls the SayHello method with a tonic client, like generated client code would.
+async fn say_hello(server: &MockServer, name: &str) -> Result<Response<HelloReply>, Status> {
+    let channel = Channel::from_shared(server.base_url())
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = tonic::client::Grpc::new(channel);
+    client.ready().await.unwrap();
+
+    let request = tonic::Request::new(HelloRequest {
+        name: name.to_string(),
+    });
+    let path = PathAndQuery::from_static("/helloworld.Greeter/SayHello");
+    client
+        .unary(
+            request,
+            path,
+            ProstCodec::<HelloRequest, HelloReply>::default(),
+        )
+        .await
+}
+
+#[tokio::test]
+async fn grpc_unary_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .http2_prior_knowledge(true)
+        .start_async()
+        .await;
+
+    let mock = server
+        .mock_grpc_async(SAY_HELLO, |when, then| {
+            when.grpc_request(&HelloRequest {
+                name: "Fred".to_string(),
+            });
+            then.grpc_response(&HelloReply {
+                message: "Hello Fred".to_string(),
+            });
+        })
+        .await;
+
+    // Act
+    let reply = say_hello(&server, "Fred").await;
+    let unmatched = say_hello(&server, "Wilma").await;
+
+    // Assert
+    mock.assert_async().await;
+    assert_eq!(reply.unwrap().into_inner().message, "Hello Fred");
+    assert!(unmatched.is_err());
+}
+
+#[tokio::test]
+async fn grpc_error_status_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .http2_prior_knowledge(true)
+        .start_async()
+        .await;
+
+    let mock = server
+        .mock_grpc_async(SAY_HELLO, |_, then| {
+            then.grpc_status(GrpcCode::Unavailable, "Try again: 100% busy");
+        })
+        .await;
+
+    // Act: The first call fails, so the client retries
+    let status = say_hello(&server, "Fred").await.unwrap_err();
+
+    mock.delete_async().await;
+    let retry_mock = server
+        .mock_grpc_async(SAY_HELLO, |_, then| {
+            then.grpc_response(&HelloReply {
+                message: "Hello Fred".to_string(),
+            });
+        })
+        .await;
+    let reply = say_hello(&server, "Fred").await.unwrap();
+
+    // Assert
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(status.message(), "Try again: 100% busy");
+    assert_eq!(reply.into_inner().message, "Hello Fred");
+    retry_mock.assert_async().await;
+}
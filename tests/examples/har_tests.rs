@@ -0,0 +1,165 @@
+use httpmock::prelude::*;
+use httpmock::HarOptions;
+use isahc::{prelude::*, Request};
+use serde_json::{json, Value};
+
+const SESSION: &str = "tests/resources/har/session.har";
+
+#[test]
+fn load_har_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mocks = server.load_har_with_options(SESSION, HarOptions::new().ignore_query_param("_"));
+
+    // Act: The job status is polled three times, the logo is fetched once
+    let bodies: Vec<String> = (0..3)
+        .map(|i| {
+            isahc::get(server.url(format!("/jobs/7?_={}&verbose=true", i)))
+                .unwrap()
+                .text()
+                .unwrap()
+        })
+        .collect();
+
+    let mut logo_response = isahc::get(server.url("/jobs/7/logo.png")).unwrap();
+
+    // Assert: Responses are replayed in recorded order and the last one is repeated
+    assert_eq!(mocks.len(), 2);
+    assert_eq!(
+        bodies,
+        vec![
+            r#"{"state":"running"}"#,
+            r#"{"state":"done"}"#,
+            r#"{"state":"done"}"#
+        ]
+    );
+    mocks[0].assert_hits(3);
+
+    assert_eq!(logo_response.status(), 200);
+    assert_eq!(
+        logo_response.headers().get("content-type").unwrap(),
+        "image/png"
+    );
+    let mut logo = Vec::new();
+    logo_response.copy_to(&mut logo).unwrap();
+    assert_eq!(logo, vec![0x89, b'P', b'N', b'G']);
+    mocks[1].assert();
+}
+
+#[test]
+fn har_response_headers_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.load_har_with_options(SESSION, HarOptions::new().ignore_query_param("_"));
+
+    // Act
+    let response = isahc::get(server.url("/jobs/7?verbose=true")).unwrap();
+
+    // Assert: Headers that do not apply to the replayed body are not sent
+    assert_eq!(response.headers().get("x-request-id").unwrap(), "a1");
+    assert!(response.headers().get("content-encoding").is_none());
+    assert!(response.headers().get("transfer-encoding").is_none());
+}
+
+#[test]
+fn har_without_sequences_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mocks = server.load_har_with_options(
+        SESSION,
+        HarOptions::new()
+            .ignore_query_param("_")
+            .match_header("accept")
+            .sequences(false),
+    );
+
+    // Act
+    let mut response = Request::get(server.url("/jobs/7?verbose=true"))
+        .header("accept", "application/json")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+    let unmatched = Request::get(server.url("/jobs/7?verbose=true"))
+        .header("accept", "text/html")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert: Only the first of the identical entries is served
+    assert_eq!(mocks.len(), 3);
+    assert_eq!(response.text().unwrap(), r#"{"state":"running"}"#);
+    assert_eq!(unmatched.status(), 404);
+    mocks[0].assert();
+    mocks[1].assert_hits(0);
+}
+
+#[test]
+fn export_har_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST).path("/users");
+        then.status(201)
+            .header("content-type", "application/json")
+            .body(r#"{"id":1}"#)
+            .delay(std::time::Duration::from_millis(50));
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/avatar.png");
+        then.status(200)
+            .header("content-type", "image/png")
+            .body(vec![0x89, b'P', b'N', b'G', 0xff]);
+    });
+    let path = std::env::temp_dir()
+        .join(format!("httpmock-har-{}", std::process::id()))
+        .join("traffic.har");
+
+    // Act
+    Request::post(server.url("/users?source=test"))
+        .header("content-type", "application/json")
+        .body(r#"{"name":"Fred"}"#)
+        .unwrap()
+        .send()
+        .unwrap();
+    isahc::get(server.url("/avatar.png")).unwrap();
+    isahc::get(server.url("/missing")).unwrap();
+    server.export_har(&path);
+
+    // Assert
+    let har: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let entries = har["log"]["entries"].as_array().unwrap();
+    assert_eq!(har["log"]["version"], "1.2");
+    assert_eq!(entries.len(), 3);
+
+    let created = &entries[0];
+    assert_eq!(created["request"]["method"], "POST");
+    assert_eq!(
+        created["request"]["url"],
+        format!("http://{}/users?source=test", server.address())
+    );
+    assert_eq!(
+        created["request"]["queryString"],
+        json!([{ "name": "source", "value": "test" }])
+    );
+    assert_eq!(created["request"]["postData"]["text"], r#"{"name":"Fred"}"#);
+    assert_eq!(created["response"]["status"], 201);
+    assert_eq!(created["response"]["content"]["text"], r#"{"id":1}"#);
+    assert!(created["timings"]["wait"].as_f64().unwrap() >= 50.0);
+    assert!(created["time"].as_f64().unwrap() >= 50.0);
+
+    let avatar = &entries[1]["response"]["content"];
+    assert_eq!(avatar["encoding"], "base64");
+    assert_eq!(avatar["text"], "iVBOR/8=");
+
+    assert_eq!(entries[2]["response"]["status"], 404);
+
+    // Assert: The exported file can be replayed
+    let replay = MockServer::start();
+    replay.load_har(&path);
+    let mut response = isahc::get(replay.url("/avatar.png")).unwrap();
+    let mut avatar = Vec::new();
+    response.copy_to(&mut avatar).unwrap();
+    assert_eq!(avatar, vec![0x89, b'P', b'N', b'G', 0xff]);
+}
@@ -0,0 +1,150 @@
+use futures_util::future::{join_all, poll_fn};
+use httpmock::prelude::*;
+use hyper::client::conn;
+use hyper::{Body, Request, StatusCode};
+use tokio::net::TcpStream;
+
+#[tokio::test]
+async fn http2_prior_knowledge_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .http2_prior_knowledge(true)
+        .start_async()
+        .await;
+
+    let m = server
+        .mock_async(|when, then| {
+            when.method(GET).path("/hello").http_version("HTTP/2");
+            then.status(200).body("world");
+        })
+        .await;
+
+    // Act
+    let stream = TcpStream::connect(server.address()).await.unwrap();
+    let (mut sender, connection) = conn::Builder::new()
+        .http2_only(true)
+        .handshake(stream)
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let request = Request::get(server.url("/hello"))
+        .body(Body::empty())
+        .unwrap();
+    let response = sender.send_request(request).await.unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+    // Assert
+    m.assert_async().await;
+    assert_eq!(body, "world");
+}
+
+#[tokio::test]
+async fn http2_multiplexed_streams_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .http2_prior_knowledge(true)
+        .start_async()
+        .await;
+
+    let users_mock = server
+        .mock_async(|when, then| {
+            when.path("/users").http_version("HTTP/2");
+            then.status(200)
+                .delay(std::time::Duration::from_millis(100));
+        })
+        .await;
+
+    let orders_mock = server
+        .mock_async(|when, then| {
+            when.path("/orders").http_version("HTTP/2");
+            then.status(201);
+        })
+        .await;
+
+    // Act: Send all requests concurrently as separate streams over a single connection.
+    let stream = TcpStream::connect(server.address()).await.unwrap();
+    let (mut sender, connection) = conn::Builder::new()
+        .http2_only(true)
+        .handshake(stream)
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let paths = vec!["/users", "/orders", "/users", "/users", "/orders"];
+    let mut pending_responses = Vec::new();
+    for path in paths {
+        poll_fn(|cx| sender.poll_ready(cx)).await.unwrap();
+        let request = Request::get(server.url(path)).body(Body::empty()).unwrap();
+        pending_responses.push(sender.send_request(request));
+    }
+    let responses: Vec<_> = join_all(pending_responses)
+        .await
+        .into_iter()
+        .map(|r| r.unwrap())
+        .collect();
+
+    // Assert
+    let statuses: Vec<StatusCode> = responses.iter().map(|r| r.status()).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            StatusCode::OK,
+            StatusCode::CREATED,
+            StatusCode::OK,
+            StatusCode::OK,
+            StatusCode::CREATED
+        ]
+    );
+    users_mock.assert_hits_async(3).await;
+    orders_mock.assert_hits_async(2).await;
+}
+
+#[tokio::test]
+async fn http2_prior_knowledge_disabled_test() {
+    // Arrange
+    let server = MockServer::builder().start_async().await;
+
+    let m = server
+        .mock_async(|when, then| {
+            when.path("/hello");
+            then.status(200);
+        })
+        .await;
+
+    // Act
+    let stream = TcpStream::connect(server.address()).await.unwrap();
+    let (mut sender, connection) = conn::Builder::new()
+        .http2_only(true)
+        .handshake(stream)
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let request = Request::get(server.url("/hello"))
+        .body(Body::empty())
+        .unwrap();
+    let result = sender.send_request(request).await;
+
+    // Assert
+    assert!(result.is_err());
+    m.assert_hits_async(0).await;
+}
+
+#[test]
+fn http1_version_test() {
+    // Arrange
+    let server = MockServer::builder().http2_prior_knowledge(true).start();
+
+    let http1_mock = server.mock(|when, then| {
+        when.path("/hello").http_version("HTTP/1.1");
+        then.status(200);
+    });
+
+    // Act
+    let response = isahc::get(server.url("/hello")).unwrap();
+
+    // Assert
+    http1_mock.assert();
+    assert_eq!(response.status(), 200);
+}
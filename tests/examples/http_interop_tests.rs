@@ -0,0 +1,51 @@
+use std::convert::TryFrom;
+
+use http::{header, HeaderValue, StatusCode};
+use httpmock::prelude::*;
+use isahc::{Request, RequestExt};
+
+#[test]
+fn http_types_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(http::Method::POST).path("/users").typed_header(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        then.status(StatusCode::CREATED)
+            .typed_header(header::LOCATION, HeaderValue::from_static("/users/1"));
+    });
+
+    // Act
+    let response = Request::post(server.url("/users"))
+        .header("content-type", "application/json")
+        .body("{}")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    mock.assert();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.headers()[header::LOCATION], "/users/1");
+}
+
+#[test]
+fn request_conversion_test() {
+    // Arrange
+    let request = HttpMockRequest::new("PUT".to_string(), "/search".to_string())
+        .with_headers(vec![("x-id".to_string(), "7".to_string())])
+        .with_query_params(vec![("q".to_string(), "a b".to_string())])
+        .with_body(b"payload".to_vec());
+
+    // Act
+    let converted = http::Request::<Vec<u8>>::try_from(&request).unwrap();
+
+    // Assert
+    assert_eq!(converted.method(), http::Method::PUT);
+    assert_eq!(converted.uri(), "/search?q=a+b");
+    assert_eq!(converted.headers()["x-id"], "7");
+    assert_eq!(converted.body(), b"payload");
+}
@@ -0,0 +1,323 @@
+use httpmock::prelude::*;
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use std::convert::TryFrom;
+use std::io::{BufReader, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+const CERT_CHAIN_FILE: &str = "tests/resources/certificates/server_chain.pem";
+const KEY_FILE: &str = "tests/resources/certificates/server_key.pem";
+const CA_FILE: &str = "tests/resources/certificates/ca.pem";
+const CLIENT_CERT_FILE: &str = "tests/resources/certificates/client_cert.pem";
+const CLIENT_KEY_FILE: &str = "tests/resources/certificates/client_key.pem";
+
+#[test]
+fn https_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .tls_cert_pem(std::fs::read(CERT_CHAIN_FILE).unwrap())
+        .tls_key_pem(std::fs::read(KEY_FILE).unwrap())
+        .start();
+
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200).body("world");
+    });
+
+    // Act: Send the request over TLS and trust only the test root CA. This only succeeds
+    // if the server presents its intermediate certificate as well.
+    let agent = ureq::AgentBuilder::new()
+        .tls_config(Arc::new(client_config(vec![])))
+        .build();
+    let response = agent.get(&server.url("/hello")).call().unwrap();
+
+    // Assert
+    m.assert();
+    assert!(server.url("/hello").starts_with("https://"));
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.into_string().unwrap(), "world");
+}
+
+#[test]
+fn https_pem_files_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .tls_cert_pem_file(CERT_CHAIN_FILE)
+        .tls_key_pem_file(KEY_FILE)
+        .start();
+
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act
+    let agent = ureq::AgentBuilder::new()
+        .tls_config(Arc::new(client_config(vec![])))
+        .build();
+    let response = agent.get(&server.url("/hello")).call().unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(response.status(), 200);
+}
+
+#[test]
+fn https_alpn_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .tls_cert_pem_file(CERT_CHAIN_FILE)
+        .tls_key_pem_file(KEY_FILE)
+        .tls_alpn_protocols(["http/1.1"])
+        .start();
+
+    // Act: Offer two protocols, of which the server only supports one.
+    let config = client_config(vec![b"h2".to_vec(), b"http/1.1".to_vec()]);
+    let conn = ClientConnection::new(Arc::new(config), ServerName::try_from("localhost").unwrap())
+        .unwrap();
+    let sock = TcpStream::connect(server.address()).unwrap();
+    let mut tls = StreamOwned::new(conn, sock);
+    tls.write_all(b"GET /__httpmock__/ping HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+
+    // Assert
+    assert_eq!(tls.conn.alpn_protocol(), Some(&b"http/1.1"[..]));
+}
+
+#[tokio::test]
+async fn https_http2_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .tls_cert_pem_file(CERT_CHAIN_FILE)
+        .tls_key_pem_file(KEY_FILE)
+        .start_async()
+        .await;
+
+    let m = server
+        .mock_async(|when, then| {
+            when.path("/hello").http_version("HTTP/2");
+            then.status(200).body("world");
+        })
+        .await;
+
+    // Act: Negotiate HTTP/2 using ALPN.
+    let config = client_config(vec![b"h2".to_vec(), b"http/1.1".to_vec()]);
+    let stream = tokio::net::TcpStream::connect(server.address())
+        .await
+        .unwrap();
+    let stream = tokio_rustls::TlsConnector::from(Arc::new(config))
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+
+    let (mut sender, connection) = hyper::client::conn::Builder::new()
+        .http2_only(true)
+        .handshake(stream)
+        .await
+        .unwrap();
+    tokio::spawn(connection);
+
+    let request = hyper::Request::get(server.url("/hello"))
+        .body(hyper::Body::empty())
+        .unwrap();
+    let response = sender.send_request(request).await.unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+    // Assert
+    m.assert_async().await;
+    assert_eq!(body, "world");
+}
+
+#[test]
+#[should_panic(expected = "Cannot parse PEM encoded TLS certificate")]
+fn https_malformed_certificate_test() {
+    MockServer::builder()
+        .tls_cert_pem("-----BEGIN CERTIFICATE-----\n%%%%\n-----END CERTIFICATE-----\n")
+        .tls_key_pem_file(KEY_FILE)
+        .start();
+}
+
+#[test]
+#[should_panic(expected = "The TLS private key does not match the server certificate")]
+fn https_mismatched_key_test() {
+    MockServer::builder()
+        .tls_cert_pem_file(CERT_CHAIN_FILE)
+        .tls_key_pem_file("tests/resources/certificates/unrelated_key.pem")
+        .start();
+}
+
+#[test]
+fn mutual_tls_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .tls_cert_pem_file(CERT_CHAIN_FILE)
+        .tls_key_pem_file(KEY_FILE)
+        .client_auth_required(std::fs::read(CA_FILE).unwrap())
+        .start();
+
+    let m = server.mock(|when, then| {
+        when.path("/hello").client_cert_cn("my-service");
+        then.status(200);
+    });
+
+    // Act
+    let agent = ureq::AgentBuilder::new()
+        .tls_config(Arc::new(client_auth_config(
+            CLIENT_CERT_FILE,
+            CLIENT_KEY_FILE,
+        )))
+        .build();
+    let response = agent.get(&server.url("/hello")).call().unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(response.status(), 200);
+}
+
+#[test]
+fn mutual_tls_client_certificate_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .tls_cert_pem_file(CERT_CHAIN_FILE)
+        .tls_key_pem_file(KEY_FILE)
+        .client_auth_required(std::fs::read(CA_FILE).unwrap())
+        .start();
+
+    let m = server.mock(|when, then| {
+        when.matches(|req: &HttpMockRequest| {
+            req.client_certificate
+                .as_ref()
+                .map(|c| c.subject.contains("O=httpmock") && !c.der.is_empty())
+                .unwrap_or(false)
+        });
+        then.status(200);
+    });
+
+    // Act
+    let agent = ureq::AgentBuilder::new()
+        .tls_config(Arc::new(client_auth_config(
+            CLIENT_CERT_FILE,
+            CLIENT_KEY_FILE,
+        )))
+        .build();
+    let response = agent.get(&server.url("/hello")).call().unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(response.status(), 200);
+}
+
+#[test]
+fn mutual_tls_rejected_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .tls_cert_pem_file(CERT_CHAIN_FILE)
+        .tls_key_pem_file(KEY_FILE)
+        .client_auth_required(std::fs::read(CA_FILE).unwrap())
+        .start();
+
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act: Send one request without any client certificate and one with a certificate that
+    // was not issued by the trusted CA.
+    let anonymous_agent = ureq::AgentBuilder::new()
+        .tls_config(Arc::new(client_config(vec![])))
+        .build();
+    let anonymous_result = anonymous_agent.get(&server.url("/hello")).call();
+
+    let untrusted_agent = ureq::AgentBuilder::new()
+        .tls_config(Arc::new(client_auth_config(
+            "tests/resources/certificates/untrusted_client_cert.pem",
+            "tests/resources/certificates/untrusted_client_key.pem",
+        )))
+        .build();
+    let untrusted_result = untrusted_agent.get(&server.url("/hello")).call();
+
+    // Assert
+    assert!(anonymous_result.is_err());
+    assert!(untrusted_result.is_err());
+    m.assert_hits(0);
+}
+
+#[test]
+fn mutual_tls_optional_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .tls_cert_pem_file(CERT_CHAIN_FILE)
+        .tls_key_pem_file(KEY_FILE)
+        .client_auth_optional(std::fs::read(CA_FILE).unwrap())
+        .start();
+
+    let cert_mock = server.mock(|when, then| {
+        when.path("/hello").client_cert_cn("my-service");
+        then.status(200);
+    });
+
+    let anonymous_mock = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(401);
+    });
+
+    // Act
+    let cert_agent = ureq::AgentBuilder::new()
+        .tls_config(Arc::new(client_auth_config(
+            CLIENT_CERT_FILE,
+            CLIENT_KEY_FILE,
+        )))
+        .build();
+    let cert_response = cert_agent.get(&server.url("/hello")).call().unwrap();
+
+    let anonymous_agent = ureq::AgentBuilder::new()
+        .tls_config(Arc::new(client_config(vec![])))
+        .build();
+    let anonymous_result = anonymous_agent.get(&server.url("/hello")).call();
+
+    // Assert
+    assert_eq!(cert_response.status(), 200);
+    assert!(matches!(anonymous_result, Err(ureq::Error::Status(401, _))));
+    cert_mock.assert();
+    anonymous_mock.assert();
+}
+
+fn root_cert_store() -> RootCertStore {
+    let ca_pem = std::fs::read(CA_FILE).unwrap();
+    let mut roots = RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut BufReader::new(ca_pem.as_slice())) {
+        roots.add(cert.unwrap()).unwrap();
+    }
+    roots
+}
+
+fn client_config(alpn_protocols: Vec<Vec<u8>>) -> ClientConfig {
+    let mut config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(root_cert_store())
+            .with_no_client_auth();
+    config.alpn_protocols = alpn_protocols;
+    config
+}
+
+fn client_auth_config(cert_file: &str, key_file: &str) -> ClientConfig {
+    let cert_pem = std::fs::read(cert_file).unwrap();
+    let certs = rustls_pemfile::certs(&mut BufReader::new(cert_pem.as_slice()))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key_pem = std::fs::read(key_file).unwrap();
+    let key = rustls_pemfile::private_key(&mut BufReader::new(key_pem.as_slice()))
+        .unwrap()
+        .unwrap();
+
+    ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(root_cert_store())
+        .with_client_auth_cert(certs, key)
+        .unwrap()
+}
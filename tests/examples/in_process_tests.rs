@@ -0,0 +1,88 @@
+use httpmock::prelude::*;
+use hyper::service::Service;
+use hyper::{Body, Request};
+use serde_json::json;
+
+#[tokio::test]
+async fn in_process_service_test() {
+    // Arrange
+    let server = MockServer::start_in_process_async().await;
+
+    let m = server
+        .mock_async(|when, then| {
+            when.method(POST)
+                .path("/users")
+                .header("content-type", "application/json")
+                .json_body(json!({ "name": "Fred" }));
+            then.status(201).body("created");
+        })
+        .await;
+
+    // Act: Send the request using the tower service API
+    let mut service = server.service();
+    let request = Request::post(server.url("/users"))
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{ "name": "Fred" }"#))
+        .unwrap();
+    futures_util::future::poll_fn(|cx| service.poll_ready(cx))
+        .await
+        .unwrap();
+    let response = service.call(request).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 201);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    assert_eq!(body, "created");
+    m.assert_async().await;
+}
+
+#[tokio::test]
+async fn in_process_unmatched_request_test() {
+    // Arrange
+    let server = MockServer::start_in_process_async().await;
+    let m = server
+        .mock_async(|when, then| {
+            when.path("/hello");
+            then.status(200);
+        })
+        .await;
+
+    // Act
+    let request = Request::get("/world").body(Body::empty()).unwrap();
+    let response = server.service().handle(request).await;
+
+    // Assert
+    assert_eq!(response.status(), 404);
+    m.assert_hits_async(0).await;
+}
+
+#[test]
+fn in_process_network_server_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act: Requests over the network and in-process reach the same mock
+    isahc::get(server.url("/hello")).unwrap();
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let response = runtime.block_on(
+        server
+            .service()
+            .handle(Request::get("/hello").body(Body::empty()).unwrap()),
+    );
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    m.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "has no TCP address")]
+fn in_process_address_test() {
+    let server = MockServer::start_in_process();
+    assert_eq!(server.base_url(), "http://localhost");
+    server.address();
+}
@@ -0,0 +1,51 @@
+use httpmock::prelude::*;
+use isahc::ReadResponseExt;
+use serde_json::Value;
+
+#[test]
+fn list_mocks_and_history_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/hello");
+        then.status(200);
+    });
+    isahc::get(server.url("/hello")).unwrap();
+
+    // Act
+    let mut mocks_response = isahc::get(server.url("/__httpmock__/mocks")).unwrap();
+    let mut history_response = isahc::get(server.url("/__httpmock__/history")).unwrap();
+
+    // Assert
+    assert_eq!(mocks_response.status(), 200);
+    let mocks: Value = serde_json::from_str(&mocks_response.text().unwrap()).unwrap();
+    assert_eq!(mocks[0]["id"], m.id);
+    assert_eq!(mocks[0]["call_counter"], 1);
+    assert_eq!(mocks[0]["definition"]["request"]["path"], "/hello");
+    assert_eq!(mocks[0]["definition"]["request"]["method"], "GET");
+
+    assert_eq!(history_response.status(), 200);
+    let history: Value = serde_json::from_str(&history_response.text().unwrap()).unwrap();
+    assert_eq!(history.as_array().unwrap().len(), 1);
+    assert_eq!(history[0]["path"], "/hello");
+
+    // Introspection requests are neither counted nor recorded.
+    m.assert_hits(1);
+}
+
+#[test]
+fn disable_introspection_test() {
+    // Arrange
+    let server = MockServer::builder().introspection(false).start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/__httpmock__/mocks");
+        then.status(200).body("mocked");
+    });
+
+    // Act
+    let mut response = isahc::get(server.url("/__httpmock__/mocks")).unwrap();
+
+    // Assert
+    assert_eq!(response.text().unwrap(), "mocked");
+    m.assert();
+}
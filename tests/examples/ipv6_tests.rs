@@ -0,0 +1,65 @@
+use httpmock::prelude::*;
+use isahc::ReadResponseExt;
+use std::net::{Ipv6Addr, SocketAddr};
+
+#[test]
+fn ipv6_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .bind_address(Ipv6Addr::LOCALHOST)
+        .start();
+
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200).body("world");
+    });
+
+    // Act
+    let mut response = isahc::get(server.url("/hello")).unwrap();
+
+    // Assert
+    assert!(server.address().is_ipv6());
+    assert_eq!(
+        server.url("/hello"),
+        format!("http://[::1]:{}/hello", server.port())
+    );
+    assert_eq!(
+        m.server_address().to_string(),
+        format!("[::1]:{}", server.port())
+    );
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().unwrap(), "world");
+    m.assert();
+}
+
+#[test]
+fn dual_stack_test() {
+    // Arrange
+    let server = MockServer::builder().dual_stack(true).start();
+
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    let ipv4 = SocketAddr::from(([127, 0, 0, 1], server.port()));
+    let ipv6 = SocketAddr::from((Ipv6Addr::LOCALHOST, server.port()));
+
+    // Act
+    let ipv4_response = isahc::get(format!("http://{}/hello", ipv4)).unwrap();
+    let ipv6_response = isahc::get(format!("http://{}/hello", ipv6)).unwrap();
+
+    // Assert
+    assert_eq!(ipv4_response.status(), 200);
+    assert_eq!(ipv6_response.status(), 200);
+    m.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "Dual-stack binding requires an IPv6 bind address")]
+fn dual_stack_with_ipv4_address_test() {
+    MockServer::builder()
+        .bind_address([127, 0, 0, 1])
+        .dual_stack(true)
+        .start();
+}
@@ -18,7 +18,7 @@ fn json_value_body_test() {
     });
 
     // Act: Send the request and deserialize the response to JSON
-    let mut response = Request::post(&format!("http://{}/users", server.address()))
+    let mut response = Request::post(&server.url("/users"))
         .header("content-type", "application/json")
         .body(json!({ "name": "Fred" }).to_string())
         .unwrap()
@@ -60,7 +60,7 @@ fn json_body_object_serde_test() {
     });
 
     // Act: Send the request and deserialize the response to JSON
-    let mut response = Request::post(&format!("http://{}/users", server.address()))
+    let mut response = Request::post(&server.url("/users"))
         .header("content-type", "application/json")
         .body(
             json!(&TestUser {
@@ -113,7 +113,7 @@ fn partial_json_body_test() {
     });
 
     // Simulates application that makes the request to the mock.
-    let uri = format!("http://{}/users", m.server_address());
+    let uri = m.url();
     let response = Request::post(&uri)
         .header("content-type", "application/json")
         .header("User-Agent", "rust-test")
@@ -134,3 +134,337 @@ fn partial_json_body_test() {
     m.assert();
     assert_eq!(response.status(), 201);
 }
+
+#[test]
+fn strict_partial_json_body_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST)
+            .path("/users")
+            .json_body_partial_strict(r#"{ "name": "Fred", "tags": ["admin"] }"#)
+            .json_body_partial_strict(r#"{ "address": { "city": "Berlin" } }"#);
+        then.status(201);
+    });
+
+    // Act
+    let send = |body: Value| {
+        Request::post(server.url("/users"))
+            .body(body.to_string())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(
+        send(json!({ "name": "Fred", "tags": ["admin"], "address": { "city": "Berlin" } })),
+        201
+    );
+    assert_eq!(
+        send(
+            json!({ "name": "Fred", "tags": ["admin"], "address": { "city": "Berlin" }, "age": 30 })
+        ),
+        404
+    );
+    assert_eq!(
+        send(json!({ "name": "Fred", "tags": ["admin", "dev"], "address": { "city": "Berlin" } })),
+        404
+    );
+    assert_eq!(send(json!({ "name": "Fred", "tags": ["admin"] })), 404);
+    m.assert_hits(1);
+}
+
+#[test]
+#[should_panic(expected = "The body does not match: unexpected fields $.address.zip, $.age")]
+fn strict_partial_json_body_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users")
+            .json_body_partial_strict(r#"{ "name": "Fred", "address": { "city": "Berlin" } }"#);
+        then.status(201);
+    });
+
+    // Act
+    isahc::post(
+        server.url("/users"),
+        r#"{"name": "Fred", "address": {"city": "Berlin", "zip": "10115"}, "age": 30}"#,
+    )
+    .unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+fn json_body_ignoring_paths_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST).path("/events").json_body_ignoring_paths(
+            json!({
+                "type": "login",
+                "timestamp": "2020-01-01T00:00:00Z",
+                "meta": { "request_id": "abc", "source": "web" }
+            }),
+            ["$.timestamp", "$.meta.request_id"],
+        );
+        then.status(201);
+    });
+
+    // Act
+    let send = |body: Value| {
+        Request::post(server.url("/events"))
+            .body(body.to_string())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(
+        send(json!({
+            "type": "login",
+            "timestamp": "2024-05-17T10:11:12Z",
+            "meta": { "request_id": "f81d4fae", "source": "web" }
+        })),
+        201
+    );
+    assert_eq!(
+        send(json!({ "type": "login", "meta": { "source": "web" } })),
+        201
+    );
+    assert_eq!(
+        send(json!({
+            "type": "logout",
+            "timestamp": "2024-05-17T10:11:12Z",
+            "meta": { "request_id": "f81d4fae", "source": "web" }
+        })),
+        404
+    );
+    m.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "the values at $.timestamp are ignored")]
+fn json_body_ignoring_paths_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/events")
+            .json_body_ignoring_paths(json!({ "type": "login", "timestamp": 0 }), ["$.timestamp"]);
+        then.status(201);
+    });
+
+    // Act
+    isahc::post(
+        server.url("/events"),
+        r#"{"type": "logout", "timestamp": 1700000000}"#,
+    )
+    .unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+fn json_body_path_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST)
+            .path("/json-path")
+            .json_body_path("$.user.addresses[0].zip", "10115")
+            .json_body_path("$.user.roles[*]", json!({ "name": "admin" }))
+            .json_body_path_exists("$.user.email");
+        then.status(201);
+    });
+
+    let body = json!({
+        "user": {
+            "email": "fred@example.com",
+            "addresses": [{ "zip": 10115 }, { "zip": 20095 }],
+            "roles": [{ "name": "dev" }, { "name": "admin" }]
+        }
+    });
+
+    // Act
+    let matching = isahc::post(server.url("/json-path"), body.to_string()).unwrap();
+    let mut other_zip = body.clone();
+    other_zip["user"]["addresses"][0]["zip"] = json!(20095);
+    let not_matching = isahc::post(server.url("/json-path"), other_zip.to_string()).unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(matching.status(), 201);
+    assert_eq!(not_matching.status(), 404);
+}
+
+#[test]
+#[should_panic(expected = "$.user.addresses[0].zip: expected \"10115\" but got 20095")]
+fn json_body_path_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/json-path-mismatch")
+            .json_body_path("$.user.addresses[0].zip", "10115");
+        then.status(201);
+    });
+
+    // Act
+    isahc::post(
+        server.url("/json-path-mismatch"),
+        r#"{"user": {"addresses": [{"zip": 20095}]}}"#,
+    )
+    .unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+#[should_panic(expected = "Invalid JSONPath 'user.name'")]
+fn invalid_json_body_path_test() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.json_body_path_exists("user.name");
+        then.status(200);
+    });
+}
+
+#[cfg(feature = "json-schema")]
+#[test]
+fn json_body_schema_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST)
+            .path("/json-schema")
+            .json_body_schema(json!({
+                "definitions": {
+                    "tag": { "type": "string", "pattern": "^[a-z]+$" }
+                },
+                "type": "object",
+                "required": ["name", "tags"],
+                "properties": {
+                    "name": { "type": "string" },
+                    "tags": { "type": "array", "items": { "$ref": "#/definitions/tag" } }
+                }
+            }));
+        then.status(201);
+    });
+
+    // Act
+    let valid = isahc::post(
+        server.url("/json-schema"),
+        r#"{"name": "Fred", "tags": ["admin"]}"#,
+    )
+    .unwrap();
+    let invalid = isahc::post(
+        server.url("/json-schema"),
+        r#"{"name": "Fred", "tags": ["Admin"]}"#,
+    )
+    .unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(valid.status(), 201);
+    assert_eq!(invalid.status(), 404);
+}
+
+#[cfg(feature = "json-schema")]
+#[test]
+#[should_panic(
+    expected = "The body does not match: $: \"name\" is a required property, $.age: -1 is less than the minimum of 0"
+)]
+fn json_body_schema_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/json-schema-mismatch").json_body_schema(json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": { "age": { "type": "integer", "minimum": 0 } }
+        }));
+        then.status(201);
+    });
+
+    // Act
+    isahc::post(server.url("/json-schema-mismatch"), r#"{"age": -1}"#).unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+fn ndjson_body_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/_bulk")
+            .ndjson_line_count(4)
+            .ndjson_body_line(0, json!({ "index": { "_id": "1" } }))
+            .ndjson_body_line(3, json!({ "name": "Wilma" }));
+        then.status(200);
+    });
+
+    // Act
+    let valid = isahc::post(
+        server.url("/_bulk"),
+        "{\"index\":{\"_id\":\"1\"}}\r\n{\"name\":\"Fred\"}\r\n\r\n{\"index\":{\"_id\":\"2\"}}\n{ \"name\": \"Wilma\" }\n",
+    )
+    .unwrap();
+    let too_short = isahc::post(
+        server.url("/_bulk"),
+        "{\"index\":{\"_id\":\"1\"}}\n{\"name\":\"Fred\"}\n",
+    )
+    .unwrap();
+    let not_ndjson = isahc::post(
+        server.url("/_bulk"),
+        "{\"index\":{\"_id\":\"1\"}}\nname=Fred\n{\"index\":{\"_id\":\"2\"}}\n{\"name\":\"Wilma\"}\n",
+    )
+    .unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(valid.status(), 200);
+    assert_eq!(too_short.status(), 404);
+    assert_eq!(not_ndjson.status(), 404);
+}
+
+#[test]
+#[should_panic(
+    expected = "The NDJSON body does not match: line 1: expected {\"name\":\"Fred\"} but got {\"name\":\"Barney\"}"
+)]
+fn ndjson_body_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/_bulk")
+            .ndjson_body_line(1, json!({ "name": "Fred" }));
+        then.status(200);
+    });
+
+    // Act
+    isahc::post(
+        server.url("/_bulk"),
+        "{\"index\":{\"_id\":\"1\"}}\n{\"name\":\"Barney\"}\n",
+    )
+    .unwrap();
+
+    // Assert
+    m.assert();
+}
@@ -0,0 +1,143 @@
+use httpmock::prelude::*;
+use std::net::TcpStream;
+
+#[test]
+fn named_listeners_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .listen_named("api", "127.0.0.1:0")
+        .listen_named("cdn", "127.0.0.1:0")
+        .start();
+
+    let api_mock = server.mock(|when, then| {
+        when.listener("api").path("/hello");
+        then.status(200).body("api");
+    });
+    let cdn_mock = server.mock(|when, then| {
+        when.listener("cdn").path("/hello");
+        then.status(200).body("cdn");
+    });
+    let shared_mock = server.mock(|when, then| {
+        when.path("/shared");
+        then.status(204);
+    });
+
+    // Act
+    let api_response = isahc::get(server.listener_url("api", "/hello")).unwrap();
+    let cdn_response = isahc::get(server.listener_url("cdn", "/hello")).unwrap();
+    let shared_api = isahc::get(server.listener_url("api", "/shared")).unwrap();
+    let shared_cdn = isahc::get(server.listener_url("cdn", "/shared")).unwrap();
+
+    // Assert
+    assert_eq!(api_response.status(), 200);
+    assert_eq!(cdn_response.status(), 200);
+    assert_eq!(shared_api.status(), 204);
+    assert_eq!(shared_cdn.status(), 204);
+    api_mock.assert();
+    cdn_mock.assert();
+    shared_mock.assert_hits(2);
+}
+
+#[test]
+fn listener_addresses_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .listen("127.0.0.1:0")
+        .listen_named("cdn", "127.0.0.1:0")
+        .start();
+
+    // Act
+    let addresses = server.addresses();
+
+    // Assert
+    assert_eq!(addresses.len(), 2);
+    assert_ne!(addresses[0], addresses[1]);
+    assert_eq!(server.address(), &addresses[0]);
+    assert_eq!(server.listener_address("cdn"), addresses[1]);
+}
+
+#[test]
+fn pause_resume_listeners_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .listen("127.0.0.1:0")
+        .listen("127.0.0.1:0")
+        .start();
+    let addresses = server.addresses();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act
+    server.pause();
+    let refused = addresses
+        .iter()
+        .all(|addr| TcpStream::connect(addr).is_err());
+    server.resume();
+
+    for address in &addresses {
+        isahc::get(format!("http://{}/hello", address)).unwrap();
+    }
+
+    // Assert
+    assert!(refused);
+    m.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "Invalid listen address: not an address")]
+fn invalid_listen_address_test() {
+    MockServer::builder().listen("not an address").start();
+}
+
+#[test]
+#[should_panic(expected = "no listener named \"cdn\"")]
+fn unknown_listener_test() {
+    let server = MockServer::builder().listen("127.0.0.1:0").start();
+    server.listener_address("cdn");
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn remote_addr_test() {
+    use std::io::{Read, Write};
+
+    // Arrange
+    let server = MockServer::start();
+    let mut first = TcpStream::connect(server.address()).unwrap();
+    let mut second = TcpStream::connect(server.address()).unwrap();
+    let first_addr = first.local_addr().unwrap();
+
+    let first_mock = server.mock(|when, then| {
+        when.remote_addr(first_addr)
+            .matches(move |req| req.remote_addr == Some(first_addr));
+        then.status(200);
+    });
+    let local_mock = server.mock(|when, then| {
+        when.remote_addr_matches(Regex::new(r"^127\.0\.0\.1:[0-9]+$").unwrap());
+        then.status(202);
+    });
+
+    // Act
+    let send = |stream: &mut TcpStream| {
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+    let first_response = send(&mut first);
+    let second_response = send(&mut second);
+
+    // Assert
+    assert!(first_response.starts_with("HTTP/1.1 200"));
+    assert!(second_response.starts_with("HTTP/1.1 202"));
+    first_mock.assert();
+    local_mock.assert();
+    assert_eq!(
+        first_mock.to_string().lines().nth(1).unwrap().trim(),
+        format!("remote address equals '{}'", first_addr)
+    );
+}
@@ -0,0 +1,192 @@
+use httpmock::prelude::*;
+use httpmock::Method;
+use isahc::{Request, RequestExt};
+
+#[test]
+fn custom_method_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method("PROPFIND").path("/files");
+        then.status(207);
+    });
+
+    // Act
+    let response = Request::builder()
+        .method("PROPFIND")
+        .uri(server.url("/files"))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    mock.assert();
+    assert_eq!(response.status(), 207);
+}
+
+#[test]
+fn mock_with_method_str_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = server.mock_with_method_str("PURGE", "/images/logo.png");
+
+    // Act
+    let response = Request::builder()
+        .method("PURGE")
+        .uri(server.url("/images/logo.png"))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+    let get_response = isahc::get(server.url("/images/logo.png")).unwrap();
+
+    // Assert
+    mock.assert();
+    assert_eq!(response.status(), 200);
+    assert_eq!(get_response.status(), 404);
+}
+
+#[test]
+fn method_case_sensitive_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.method(Method::Custom("purge".to_string()))
+            .path("/cache");
+        then.status(200);
+    });
+
+    // Act
+    let send = |method: &str| {
+        Request::builder()
+            .method(method)
+            .uri(server.url("/cache"))
+            .body(())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(send("PURGE"), 404);
+    assert_eq!(send("purge"), 200);
+    mock.assert_hits(1);
+}
+
+#[test]
+fn method_parse_test() {
+    assert_eq!("PATCH".parse::<Method>(), Ok(PATCH));
+    assert_eq!(
+        "PROPFIND".parse::<Method>(),
+        Ok(Method::Custom("PROPFIND".to_string()))
+    );
+    assert_eq!(
+        "get".parse::<Method>(),
+        Ok(Method::Custom("get".to_string()))
+    );
+    assert!("NOT VALID".parse::<Method>().is_err());
+    assert_eq!(Method::Custom("MKCOL".to_string()).to_string(), "MKCOL");
+    assert_eq!(HEAD.to_string(), "HEAD");
+}
+
+#[cfg(feature = "http-interop")]
+#[test]
+fn http_interop_test() {
+    use std::convert::TryFrom;
+
+    assert_eq!(Method::from(http::Method::PATCH), PATCH);
+    assert_eq!(
+        Method::from(http::Method::from_bytes(b"PROPFIND").unwrap()),
+        Method::Custom("PROPFIND".to_string())
+    );
+    assert_eq!(http::Method::try_from(PUT).unwrap(), http::Method::PUT);
+    assert!(http::Method::try_from(Method::Custom("NOT VALID".to_string())).is_err());
+}
+
+#[test]
+fn methods_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.methods(&[GET, HEAD]).path("/resource");
+        then.status(200);
+    });
+
+    // Act
+    let send = |method: &str| {
+        Request::builder()
+            .method(method)
+            .uri(server.url("/resource"))
+            .body(())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(send("GET"), 200);
+    assert_eq!(send("HEAD"), 200);
+    assert_eq!(send("POST"), 404);
+    mock.assert_hits(2);
+    assert_eq!(
+        mock.to_string(),
+        format!(
+            "Mock {}: GET|HEAD /resource\n  method is one of GET, HEAD\n  path equals '/resource'",
+            mock.id
+        )
+    );
+}
+
+#[test]
+fn any_method_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.path("/resource");
+        then.status(204);
+    });
+
+    // Act
+    let send = |method: &str| {
+        Request::builder()
+            .method(method)
+            .uri(server.url("/resource"))
+            .body(())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    for method in ["GET", "POST", "DELETE", "PROPFIND"].iter() {
+        assert_eq!(send(method), 204);
+    }
+    mock.assert_hits(4);
+}
+
+#[test]
+#[should_panic(expected = "PUT or PATCH")]
+fn methods_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = server.mock(|when, then| {
+        when.methods([PUT, PATCH]).path("/users/1");
+        then.status(200);
+    });
+
+    // Act
+    isahc::get(server.url("/users/1")).unwrap();
+
+    // Assert
+    mock.assert();
+}
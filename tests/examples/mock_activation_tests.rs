@@ -0,0 +1,85 @@
+use httpmock::prelude::*;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn active_from_until_test() {
+    // Arrange
+    let server = MockServer::start();
+    let now = SystemTime::now();
+
+    let upcoming = server.mock(|when, then| {
+        when.path("/health")
+            .active_from(now + Duration::from_millis(300));
+        then.status(200);
+    });
+    let expired = server.mock(|when, then| {
+        when.path("/legacy")
+            .active_until(now - Duration::from_secs(1));
+        then.status(200);
+    });
+
+    // Act
+    let before = isahc::get(server.url("/health")).unwrap();
+    thread::sleep(Duration::from_millis(300));
+    let after = isahc::get(server.url("/health")).unwrap();
+    let legacy = isahc::get(server.url("/legacy")).unwrap();
+
+    // Assert
+    assert_eq!(before.status(), 404);
+    assert_eq!(after.status(), 200);
+    assert_eq!(legacy.status(), 404);
+    upcoming.assert_hits(1);
+    expired.assert_hits(0);
+}
+
+#[test]
+fn enable_from_other_thread_test() {
+    // Arrange
+    let server = MockServer::start();
+    let upstream = server.mock(|when, then| {
+        when.path("/upstream").disabled();
+        then.status(200);
+    });
+    let fallback = server.mock(|when, then| {
+        when.path("/upstream");
+        then.status(503);
+    });
+    let offline = isahc::get(server.url("/upstream")).unwrap();
+
+    // Act: The upstream service comes online while the test is running
+    thread::scope(|s| {
+        s.spawn(|| upstream.enable());
+    });
+    let online = isahc::get(server.url("/upstream")).unwrap();
+
+    // Assert
+    assert_eq!(offline.status(), 503);
+    assert_eq!(online.status(), 200);
+    upstream.assert_hits(1);
+    fallback.assert_hits(1);
+}
+
+#[test]
+fn disable_keeps_hits_test() {
+    // Arrange
+    let server = MockServer::start();
+    let remote = MockServer::builder().connect(&server.address().to_string());
+
+    let m = remote.mock(|when, then| {
+        when.path("/health");
+        then.status(200);
+    });
+    assert_eq!(isahc::get(server.url("/health")).unwrap().status(), 200);
+
+    // Act: The mock is disabled through the admin API of the server
+    m.disable();
+    let disabled = isahc::get(server.url("/health")).unwrap();
+    m.enable();
+    let enabled = isahc::get(server.url("/health")).unwrap();
+
+    // Assert
+    assert_eq!(disabled.status(), 404);
+    assert_eq!(enabled.status(), 200);
+    m.assert_hits(2);
+}
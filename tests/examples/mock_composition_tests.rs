@@ -0,0 +1,189 @@
+use httpmock::prelude::*;
+use httpmock::{Then, When};
+use isahc::{prelude::*, Request};
+
+fn items(when: When) -> When {
+    when.method(GET)
+        .path("/items")
+        .header_exists("Authorization")
+}
+
+fn json(then: Then) -> Then {
+    then.header("content-type", "application/json")
+}
+
+#[test]
+fn shared_requirements_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let page1 = server.mock(|when, then| {
+        when.and(items).query_param("page", "1");
+        then.and(json).status(200).body(r#"{"page":1}"#);
+    });
+    let page2 = server.mock(|when, then| {
+        when.and(items).query_param("page", "2");
+        then.and(json).status(200).body(r#"{"page":2}"#);
+    });
+
+    let get = |url: String, authorized: bool| {
+        let mut request = Request::get(url);
+        if authorized {
+            request = request.header("Authorization", "Bearer 123");
+        }
+        request.body(()).unwrap().send().unwrap()
+    };
+
+    // Act
+    let mut first = get(server.url("/items?page=1"), true);
+    let unauthorized = get(server.url("/items?page=2"), false);
+
+    // Assert
+    assert_eq!(first.text().unwrap(), r#"{"page":1}"#);
+    assert_eq!(first.headers()["content-type"], "application/json");
+    assert_eq!(unauthorized.status(), 404);
+    page1.assert();
+    page2.assert_hits(0);
+}
+
+#[test]
+fn shared_requirements_are_independent_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.and(items).query_param("page", "1");
+        then.status(200);
+    });
+    let any_page = server.mock(|when, then| {
+        when.and(items);
+        then.status(204);
+    });
+
+    // Act: The second mock must not require the query parameter of the first one
+    let response = Request::get(server.url("/items"))
+        .header("Authorization", "Bearer 123")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 204);
+    any_page.assert();
+}
+
+#[test]
+fn any_of_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET).any_of([
+            |when: When| when.path("/v1/users"),
+            |when: When| when.path("/v2/users").header("x-api-version", "2"),
+        ]);
+        then.status(200);
+    });
+
+    let get = |path: &str, version: &str| {
+        Request::get(server.url(path))
+            .header("x-api-version", version)
+            .body(())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+            .as_u16()
+    };
+
+    // Act
+    let v1 = get("/v1/users", "1");
+    let v2 = get("/v2/users", "2");
+    let wrong_version = get("/v2/users", "1");
+    let other_path = get("/v3/users", "2");
+    let wrong_method = Request::post(server.url("/v1/users"))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap()
+        .status()
+        .as_u16();
+
+    // Assert
+    assert_eq!((v1, v2), (200, 200));
+    assert_eq!((wrong_version, other_path, wrong_method), (404, 404, 404));
+    m.assert_hits(2);
+    assert_eq!(
+        m.to_string(),
+        format!(
+            "Mock {}: GET /v1/users|/v2/users\n  method equals GET\n  \
+             any of (path equals '/v1/users') or \
+             (path equals '/v2/users' and header 'x-api-version' equals '2')",
+            m.id
+        )
+    );
+}
+
+#[test]
+fn nested_any_of_and_all_of_test() {
+    // Arrange
+    let server = MockServer::start();
+    let versions = ["1", "2"];
+
+    let m = server.mock(|when, then| {
+        when.path("/items").any_of([
+            Box::new(|when: When| when.and(items)) as Box<dyn FnOnce(When) -> When>,
+            Box::new(move |when: When| {
+                when.all_of([
+                    |when: When| when.method(POST),
+                    |when: When| when.header_exists("x-api-version"),
+                ])
+                .any_of(
+                    versions.map(|version| move |when: When| when.header("x-api-version", version)),
+                )
+            }),
+        ]);
+        then.status(200);
+    });
+
+    let send = |method: &str, headers: &[(&str, &str)]| {
+        let mut request = Request::builder().method(method).uri(server.url("/items"));
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        request.body(()).unwrap().send().unwrap().status().as_u16()
+    };
+
+    // Act
+    let authorized_get = send("GET", &[("Authorization", "Bearer 123")]);
+    let versioned_post = send("POST", &[("x-api-version", "2")]);
+    let unknown_version = send("POST", &[("x-api-version", "3")]);
+    let plain_get = send("GET", &[]);
+
+    // Assert
+    assert_eq!((authorized_get, versioned_post), (200, 200));
+    assert_eq!((unknown_version, plain_get), (404, 404));
+    m.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "Alternative 2 of 2 (the closest one): Expected query parameter")]
+fn any_of_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.any_of([
+            |when: When| when.path("/v1/users"),
+            |when: When| when.path("/v2/users").query_param("active", "true"),
+        ]);
+        then.status(200);
+    });
+
+    // Act
+    isahc::get(server.url("/v2/users?active=false")).unwrap();
+
+    // Assert
+    m.assert();
+}
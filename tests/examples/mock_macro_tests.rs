@@ -0,0 +1,104 @@
+// All tests in this file use APIs that require the `regex` feature.
+#![cfg_attr(not(feature = "regex"), allow(dead_code, unused_imports))]
+
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "regex")]
+use httpmock::mock;
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+
+#[cfg(feature = "regex")]
+#[test]
+fn mock_macro_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = mock!(server, GET "/users/{id}" => {
+        status: 200,
+        headers: { "X-Api-Version": "2" },
+        json: { "id": 1, "name": "Fred" },
+    });
+
+    // Act
+    let mut response = isahc::get(server.url("/users/1")).unwrap();
+
+    // Assert
+    mock.assert();
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["X-Api-Version"], "2");
+    assert_eq!(response.text().unwrap(), r#"{"id":1,"name":"Fred"}"#);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn mock_macro_request_requirements_test() {
+    // Arrange
+    let server = MockServer::start();
+    let id = 7;
+
+    let mock = mock!(server, POST "/users" {
+        query: { "dry_run": "false" },
+        headers: { "content-type": "application/json" },
+        json: { "id": id },
+    } => {
+        status: 201,
+        body: format!("created {}", id),
+        delay: Duration::from_millis(50),
+    });
+
+    let send = |query: &str| {
+        Request::post(server.url(format!("/users?dry_run={}", query)))
+            .header("content-type", "application/json")
+            .body(r#"{"id":7}"#)
+            .unwrap()
+            .send()
+            .unwrap()
+    };
+
+    // Act
+    let start = Instant::now();
+    let mut response = send("false");
+    let elapsed = start.elapsed();
+    let dry_run = send("true");
+
+    // Assert
+    mock.assert_hits(1);
+    assert_eq!(response.status(), 201);
+    assert_eq!(response.text().unwrap(), "created 7");
+    assert!(elapsed >= Duration::from_millis(50));
+    assert_eq!(dry_run.status(), 404);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn mock_macro_custom_method_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let mock = mock!(server, PROPFIND "/files" {
+        body: "<propfind/>",
+    } => {
+        status: 207,
+    });
+
+    // Act
+    let response = Request::builder()
+        .method("PROPFIND")
+        .uri(server.url("/files"))
+        .body("<propfind/>")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    mock.assert();
+    assert_eq!(response.status(), 207);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn mock_macro_compile_errors_test() {
+    let tests = trybuild::TestCases::new();
+    tests.compile_fail("tests/ui/mock_macro/*.rs");
+}
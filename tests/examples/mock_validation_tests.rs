@@ -0,0 +1,56 @@
+use httpmock::prelude::*;
+use httpmock::MockError;
+use serde_json::json;
+
+#[test]
+fn conflicting_requirements_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    // Act
+    let result = server.try_mock(|when, then| {
+        when.method(POST)
+            .path("/users")
+            .body("{}")
+            .json_body(json!({ "name": "Fred" }));
+        then.status(201);
+    });
+
+    // Assert
+    match result {
+        Err(MockError::InvalidMockDefinition { mock, reason }) => {
+            assert_eq!(mock, "POST /users");
+            assert!(reason.contains("body equals \"{}\" and JSON body equals {\"name\":\"Fred\"}"));
+        }
+        _ => panic!("expected an invalid mock definition"),
+    }
+}
+
+#[test]
+#[should_panic(expected = "path equals '/users' and path equals '/orders'")]
+fn redefined_path_test() {
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.path("/users").path("/orders");
+        then.status(200);
+    });
+}
+
+#[test]
+fn warn_on_conflicts_test() {
+    // Arrange
+    let server = MockServer::builder().warn_on_conflicts(true).start();
+
+    // Act
+    let m = server.mock(|when, then| {
+        when.method(GET).method(POST).path("/users");
+        then.status(200);
+    });
+
+    let response = isahc::post(server.url("/users"), ()).unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(response.status(), 200);
+}
@@ -1,16 +1,78 @@
+mod admin_api_tests;
+mod async_api_tests;
+mod base_path_tests;
 mod binary_body_tests;
+mod body_size_tests;
+mod connection_tests;
 mod cookie_tests;
+mod curl_tests;
 mod custom_request_matcher_tests;
+#[cfg(feature = "decompression")]
+mod decompression_tests;
 mod delay_tests;
 mod delete_mock_tests;
+mod display_tests;
+mod fallible_mock_tests;
 mod file_body_tests;
+mod forwarding_tests;
 mod getting_started_tests;
+#[cfg(feature = "graphql")]
+mod graphql_tests;
+#[cfg(feature = "grpc")]
+mod grpc_tests;
+mod har_tests;
 mod headers_tests;
+mod http2_tests;
+#[cfg(feature = "http-interop")]
+mod http_interop_tests;
+#[cfg(feature = "https")]
+mod https_tests;
+mod in_process_tests;
+mod introspection_tests;
+mod ipv6_tests;
 mod json_body_tests;
+mod listener_tests;
+mod method_tests;
+mod mock_activation_tests;
+mod mock_composition_tests;
+mod mock_macro_tests;
+mod mock_validation_tests;
+mod multipart_tests;
 mod multiserver_tests;
+mod namespace_tests;
+#[cfg(feature = "openapi")]
+mod openapi_tests;
+mod overload_tests;
+mod pact_tests;
+mod pause_tests;
+mod postman_tests;
+#[cfg(feature = "proto")]
+mod protobuf_body_tests;
+mod proxy_tests;
 mod query_param_tests;
+#[cfg(feature = "yaml")]
+mod recording_tests;
+mod remote_admin_tests;
+mod request_log_tests;
 mod showcase_tests;
+mod shutdown_tests;
+#[cfg(feature = "xml")]
+mod soap_tests;
 mod standalone_tests;
+mod static_dir_tests;
+mod stats_tests;
 mod string_body_tests;
+#[cfg(feature = "macros")]
+mod test_macro_tests;
+#[cfg(feature = "tracing")]
+mod tracing_tests;
+#[cfg(unix)]
+mod unix_socket_tests;
 mod url_matching_tests;
+mod verification_guard_tests;
+mod wiremock_tests;
 mod x_www_form_urlencoded_tests;
+#[cfg(feature = "xml")]
+mod xml_body_tests;
+#[cfg(feature = "yaml")]
+mod yaml_mock_tests;
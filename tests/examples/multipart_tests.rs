@@ -0,0 +1,100 @@
+use httpmock::prelude::*;
+use httpmock::MultipartPart;
+use isahc::{prelude::*, Request};
+
+/// A `multipart/form-data` body with the boundary `XYZ`, as it is sent for an HTML form with a
+/// file upload.
+const BODY: &str = "--XYZ\r\n\
+                    Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+                    Holiday\r\n\
+                    --XYZ\r\n\
+                    Content-Disposition: form-data; name=\"photo\"; filename=\"beach.png\"\r\n\
+                    Content-Type: image/png\r\n\r\n\
+                    PNG\r\n\
+                    --XYZ--\r\n";
+
+#[test]
+fn multipart_part_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST)
+            .path("/photos")
+            .multipart_part(MultipartPart::new("title").body("Holiday"))
+            .multipart_part(
+                MultipartPart::new("photo")
+                    .filename("beach.png")
+                    .content_type("image/png"),
+            );
+        then.status(201);
+    });
+
+    // Act
+    let response = Request::post(server.url("/photos"))
+        .header("Content-Type", "multipart/form-data; boundary=\"XYZ\"")
+        .body(BODY)
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(response.status(), 201);
+}
+
+#[test]
+fn multipart_part_exists_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/multipart-exists").multipart_part("photo");
+        then.status(201);
+    });
+
+    // Act
+    let multipart = Request::post(server.url("/multipart-exists"))
+        .header("Content-Type", "multipart/form-data; boundary=XYZ")
+        .body(BODY)
+        .unwrap()
+        .send()
+        .unwrap();
+    let plain = Request::post(server.url("/multipart-exists"))
+        .header("Content-Type", "text/plain")
+        .body(BODY)
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(multipart.status(), 201);
+    assert_eq!(plain.status(), 404);
+}
+
+#[test]
+#[should_panic(
+    expected = "The multipart body does not match: part 'photo': expected filename \"avatar.png\" but got \"beach.png\""
+)]
+fn multipart_part_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/multipart-mismatch")
+            .multipart_part(MultipartPart::new("photo").filename("avatar.png"));
+        then.status(201);
+    });
+
+    // Act
+    Request::post(server.url("/multipart-mismatch"))
+        .header("Content-Type", "multipart/form-data; boundary=XYZ")
+        .body(BODY)
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    m.assert();
+}
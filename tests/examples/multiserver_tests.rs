@@ -34,3 +34,42 @@ fn multi_server_test() {
     target_mock.assert();
     assert_eq!(response.status(), 200);
 }
+
+#[test]
+fn many_servers_start_quickly_test() {
+    use std::io::{Read, Write};
+
+    // Arrange
+    let start = std::time::Instant::now();
+
+    // Act: All servers share one background runtime, so starting a server does not spawn a thread.
+    let servers: Vec<MockServer> = (0..200).map(|_| MockServer::builder().start()).collect();
+
+    // Assert
+    assert!(
+        start.elapsed() < std::time::Duration::from_secs(1),
+        "Starting 200 servers took {:?}",
+        start.elapsed()
+    );
+    let addresses: std::collections::HashSet<_> = servers.iter().map(|s| *s.address()).collect();
+    assert_eq!(addresses.len(), servers.len());
+
+    // The requests are sent without curl, which cannot use the high file descriptor numbers that
+    // this many servers allocate.
+    for server in &servers {
+        let m = server.mock(|when, then| {
+            when.path("/ping");
+            then.status(200);
+        });
+
+        let mut stream = std::net::TcpStream::connect(server.address()).unwrap();
+        stream
+            .write_all(b"GET /ping HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        m.assert();
+    }
+}
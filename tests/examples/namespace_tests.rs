@@ -0,0 +1,61 @@
+use httpmock::prelude::*;
+use isahc::prelude::*;
+
+#[test]
+fn isolated_servers_test() {
+    // Arrange: A mock server that is shared like a standalone server in CI
+    let shared = MockServer::builder().start();
+    let address = shared.address().to_string();
+
+    let first = MockServer::connect_isolated(&address);
+    let second = MockServer::connect_isolated(&address);
+    assert_ne!(first.base_url(), second.base_url());
+
+    let first_mock = first.mock(|when, then| {
+        when.path("/hello");
+        then.status(200).body("first");
+    });
+    let second_mock = second.mock(|when, then| {
+        when.path("/hello");
+        then.status(200).body("second");
+    });
+
+    // Act
+    let mut first_response = isahc::get(first.url("/hello")).unwrap();
+    let mut second_response = isahc::get(second.url("/hello")).unwrap();
+    let shared_response = isahc::get(shared.url("/hello")).unwrap();
+
+    // Assert
+    assert_eq!(first_response.text().unwrap(), "first");
+    assert_eq!(second_response.text().unwrap(), "second");
+    assert_eq!(shared_response.status(), 404);
+    first_mock.assert_hits(1);
+    second_mock.assert_hits(1);
+}
+
+#[test]
+fn isolated_server_reset_test() {
+    // Arrange
+    let shared = MockServer::builder().start();
+    let address = shared.address().to_string();
+
+    let shared_mock = shared.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+    let isolated = MockServer::connect_isolated(&address);
+    isolated.mock(|when, then| {
+        when.path("/hello");
+        then.status(202);
+    });
+    let isolated_url = isolated.url("/hello");
+    assert_eq!(isahc::get(&isolated_url).unwrap().status(), 202);
+
+    // Act: Dropping the isolated server only deletes its own mocks
+    drop(isolated);
+
+    // Assert
+    assert_eq!(isahc::get(isolated_url).unwrap().status(), 404);
+    assert_eq!(isahc::get(shared.url("/hello")).unwrap().status(), 200);
+    shared_mock.assert_hits(1);
+}
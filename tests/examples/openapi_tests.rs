@@ -0,0 +1,83 @@
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+use serde_json::{json, Value};
+
+const SPEC: &str = "tests/resources/openapi/pets.yaml";
+
+#[test]
+fn mocks_from_openapi_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mocks = server.mocks_from_openapi(SPEC);
+
+    // Act
+    let mut get_response = Request::get(server.url("/v1/pets/42"))
+        .header("x-api-key", "secret")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let mut create_response = Request::post(server.url("/v1/pets"))
+        .header("content-type", "application/json")
+        .body(json!({"name": "Rex"}).to_string())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let delete_response = Request::delete(server.url("/v1/pets/42"))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    assert_eq!(mocks.len(), 3);
+
+    assert_eq!(get_response.status(), 200);
+    let pet: Value = get_response.json().unwrap();
+    assert_eq!(pet, json!({"id": 1, "name": "string", "tag": "string"}));
+    mocks["getPet"].assert();
+
+    assert_eq!(create_response.status(), 201);
+    let pet: Value = create_response.json().unwrap();
+    assert_eq!(pet, json!({"id": 7, "name": "Rex"}));
+    mocks["createPet"].assert();
+
+    assert_eq!(delete_response.status(), 204);
+    mocks["DELETE /pets/{petId}"].assert();
+}
+
+#[test]
+fn openapi_required_parameters_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mocks = server.mocks_from_openapi(SPEC);
+
+    // Act: The required x-api-key header is missing
+    let response = isahc::get(server.url("/v1/pets/42")).unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 404);
+    mocks["getPet"].assert_hits(0);
+}
+
+#[test]
+#[should_panic(expected = "$.name: 5 is not of type \"string\"")]
+fn openapi_schema_violation_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mocks = server.mocks_from_openapi(SPEC);
+
+    // Act
+    let response = Request::post(server.url("/v1/pets"))
+        .header("content-type", "application/json")
+        .body(json!({"name": 5}).to_string())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 404);
+    mocks["createPet"].assert();
+}
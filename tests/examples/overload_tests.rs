@@ -0,0 +1,95 @@
+use httpmock::prelude::*;
+use httpmock::OverloadBehavior;
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn overload_reject_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .max_connections(1)
+        .overload_behavior(OverloadBehavior::Reject)
+        .start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act: Occupy the only connection slot
+    let connection = TcpStream::connect(server.address()).unwrap();
+    let rejected = isahc::get(server.url("/hello"));
+
+    drop(connection);
+    thread::sleep(Duration::from_millis(100));
+    let accepted = isahc::get(server.url("/hello"));
+
+    // Assert
+    assert!(rejected.is_err());
+    assert_eq!(accepted.unwrap().status(), 200);
+    assert_eq!(server.overload_rejections(), 1);
+    m.assert_hits(1);
+}
+
+#[test]
+fn overload_respond_503_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .max_connections(1)
+        .overload_behavior(OverloadBehavior::Respond503)
+        .start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act
+    let connection = TcpStream::connect(server.address()).unwrap();
+    let first = isahc::get(server.url("/hello")).unwrap();
+    let second = isahc::get(server.url("/hello")).unwrap();
+
+    // Assert
+    assert_eq!(first.status(), 503);
+    assert_eq!(second.status(), 503);
+    assert_eq!(server.overload_rejections(), 2);
+    m.assert_hits(0);
+
+    drop(connection);
+    server.reset();
+    assert_eq!(server.overload_rejections(), 0);
+}
+
+#[test]
+fn overload_queue_test() {
+    // Arrange
+    let server = MockServer::builder().max_connections(1).start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act: The request waits until the occupied connection slot becomes free
+    let connection = TcpStream::connect(server.address()).unwrap();
+    let url = server.url("/hello");
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(isahc::get(url).map(|r| r.status()));
+    });
+
+    let queued = receiver.recv_timeout(Duration::from_millis(300));
+    drop(connection);
+    let served = receiver.recv_timeout(Duration::from_secs(5));
+
+    // Assert
+    assert!(queued.is_err());
+    assert_eq!(served.unwrap().unwrap(), 200);
+    assert_eq!(server.overload_rejections(), 0);
+    m.assert();
+}
+
+#[test]
+#[should_panic(expected = "The maximum number of connections must be greater than zero")]
+fn overload_zero_connections_test() {
+    MockServer::builder().max_connections(0).start();
+}
@@ -0,0 +1,102 @@
+use httpmock::prelude::*;
+use serde_json::Value;
+
+fn pact_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir()
+        .join(format!("httpmock-pacts-{}", std::process::id()))
+        .join(format!("{}.json", name))
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn write_pact_test() {
+    use isahc::{prelude::*, Request};
+    use serde_json::json;
+
+    // Arrange
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST)
+            .path_matches(Regex::new("^/users/\\d+$").unwrap())
+            .header_exists("authorization")
+            .json_body_partial(r#"{ "name": "Fred" }"#);
+        then.status(200).json_body(json!({ "updated": true }));
+    });
+    server.mock(|when, then| {
+        when.method(DELETE).path("/users/1");
+        then.status(204);
+    });
+    let path = pact_path("write_pact_test");
+
+    // Act
+    Request::post(server.url("/users/1"))
+        .header("authorization", "Bearer 1")
+        .header("content-type", "application/json")
+        .body(r#"{ "name": "Fred", "age": 30 }"#)
+        .unwrap()
+        .send()
+        .unwrap();
+    server.write_pact("web-app", "user-service", &path);
+
+    // Assert: The unused mock is not exported
+    let pact: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(pact["consumer"]["name"], "web-app");
+    assert_eq!(pact["provider"]["name"], "user-service");
+    assert_eq!(pact["metadata"]["pactSpecification"]["version"], "3.0.0");
+
+    let interactions = pact["interactions"].as_array().unwrap();
+    assert_eq!(interactions.len(), 1);
+    let request = &interactions[0]["request"];
+    assert_eq!(interactions[0]["description"], "POST /users/1 returns 200");
+    assert_eq!(request["path"], "/users/1");
+    assert_eq!(request["body"], json!({ "name": "Fred", "age": 30 }));
+    assert_eq!(
+        request["matchingRules"]["path"]["matchers"][0],
+        json!({ "match": "regex", "regex": "(?:^/users/\\d+$)" })
+    );
+    assert_eq!(
+        request["matchingRules"]["body"]["$"]["matchers"][0],
+        json!({ "match": "type" })
+    );
+    assert_eq!(
+        interactions[0]["response"]["body"],
+        json!({ "updated": true })
+    );
+}
+
+#[test]
+fn write_pact_merge_test() {
+    // Arrange
+    let path = pact_path("write_pact_merge_test");
+    let _ = std::fs::remove_file(&path);
+
+    let first = MockServer::start();
+    first.mock(|when, then| {
+        when.path("/a");
+        then.status(200);
+    });
+    let second = MockServer::start();
+    second.mock(|when, then| {
+        when.path("/b");
+        then.status(200);
+    });
+
+    // Act: Two tests contribute to the same contract
+    isahc::get(first.url("/a")).unwrap();
+    first.write_pact("web-app", "user-service", &path);
+    isahc::get(second.url("/b")).unwrap();
+    second.write_pact("web-app", "user-service", &path);
+
+    // Assert
+    let pact: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let descriptions: Vec<&Value> = pact["interactions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| &i["description"])
+        .collect();
+    assert_eq!(
+        descriptions,
+        vec!["GET /a returns 200", "GET /b returns 200"]
+    );
+}
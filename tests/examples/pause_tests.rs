@@ -0,0 +1,88 @@
+use httpmock::prelude::*;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn pause_resume_test() {
+    // Arrange
+    let server = MockServer::builder().start();
+    let address = *server.address();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+    assert_eq!(isahc::get(server.url("/hello")).unwrap().status(), 200);
+
+    // Act: Requests are refused while the server is paused
+    server.pause();
+    let refused = TcpStream::connect(address);
+    server.resume();
+
+    // Assert
+    assert!(refused.is_err());
+    assert_eq!(server.address(), &address);
+    assert_eq!(isahc::get(server.url("/hello")).unwrap().status(), 200);
+    m.assert_hits(2);
+}
+
+#[test]
+fn pause_closes_open_connections_test() {
+    // Arrange
+    let server = MockServer::builder().start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    stream
+        .write_all(b"GET /hello HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut buf = [0u8; 1024];
+    let n = stream.read(&mut buf).unwrap();
+    assert!(String::from_utf8_lossy(&buf[..n]).starts_with("HTTP/1.1 200"));
+
+    // Act: Requests on a kept-alive connection are not answered while paused
+    server.pause();
+    let _ = stream.write_all(b"GET /hello HTTP/1.1\r\nHost: localhost\r\n\r\n");
+    let read = stream.read(&mut buf);
+
+    // Assert
+    assert!(matches!(read, Ok(0) | Err(_)));
+    m.assert_hits(1);
+}
+
+#[test]
+fn pause_for_test() {
+    // Arrange
+    let server = MockServer::builder().start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act
+    let start = Instant::now();
+    server.pause_for(Duration::from_millis(300));
+    assert!(TcpStream::connect(server.address()).is_err());
+
+    let response = loop {
+        match isahc::get(server.url("/hello")) {
+            Ok(response) => break response,
+            Err(_) => thread::sleep(Duration::from_millis(20)),
+        }
+    };
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert!(start.elapsed() >= Duration::from_millis(300));
+    m.assert_hits(1);
+}
+
+#[test]
+#[should_panic(expected = "Cannot pause mock server")]
+fn pause_in_process_server_test() {
+    let server = MockServer::start_in_process();
+    server.pause();
+}
@@ -0,0 +1,61 @@
+// All tests in this file use APIs that require the `regex` feature.
+#![cfg_attr(not(feature = "regex"), allow(dead_code, unused_imports))]
+
+use std::collections::HashMap;
+
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+
+const COLLECTION: &str = "tests/resources/postman/users.postman_collection.json";
+
+#[cfg(feature = "regex")]
+#[test]
+fn load_postman_collection_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mocks = server.load_postman_collection(COLLECTION);
+
+    // Act
+    let mut found = isahc::get(server.url("/v1/users/7")).unwrap();
+    let created = Request::post(server.url("/v1/users"))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert: Requests without examples are skipped
+    let mut names: Vec<&String> = mocks.keys().collect();
+    names.sort();
+    assert_eq!(names, vec!["Users / Create", "Users / Get by id"]);
+
+    assert_eq!(found.status(), 200);
+    assert_eq!(found.text().unwrap(), r#"{"name":"Fred"}"#);
+    assert_eq!(
+        found.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+    mocks["Users / Get by id"].assert();
+
+    assert_eq!(created.status(), 201);
+    assert_eq!(created.headers().get("location").unwrap(), "/users/2");
+    mocks["Users / Create"].assert();
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn postman_variables_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mut variables = HashMap::new();
+    variables.insert("version".to_string(), "v2".to_string());
+    let mocks = server.load_postman_collection_with_variables(COLLECTION, variables);
+
+    // Act
+    let v1 = isahc::get(server.url("/v1/users/7")).unwrap();
+    let v2 = isahc::get(server.url("/v2/users/7")).unwrap();
+
+    // Assert: Provided variables take precedence over collection variables
+    assert_eq!(v1.status(), 404);
+    assert_eq!(v2.status(), 200);
+    mocks["Users / Get by id"].assert();
+}
@@ -0,0 +1,94 @@
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+use prost::Message;
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct CreateUser {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(uint32, tag = "2")]
+    age: u32,
+    #[prost(string, repeated, tag = "3")]
+    roles: Vec<String>,
+}
+
+fn post(server: &MockServer, body: Vec<u8>) -> u16 {
+    Request::post(server.url("/users"))
+        .header("content-type", "application/x-protobuf")
+        .body(body)
+        .unwrap()
+        .send()
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+#[test]
+fn protobuf_body_test() {
+    // Arrange
+    let server = MockServer::start();
+    let user = CreateUser {
+        name: "Fred".to_string(),
+        age: 30,
+        roles: vec!["admin".to_string(), "dev".to_string()],
+    };
+
+    let m = server.mock(|when, then| {
+        when.method(POST).path("/users").protobuf_body(&user);
+        then.status(201);
+    });
+
+    // The same message with the fields encoded in a different order, as other encoders may do.
+    let mut reordered = CreateUser {
+        age: 30,
+        ..Default::default()
+    }
+    .encode_to_vec();
+    reordered.extend(
+        CreateUser {
+            name: "Fred".to_string(),
+            roles: vec!["admin".to_string(), "dev".to_string()],
+            ..Default::default()
+        }
+        .encode_to_vec(),
+    );
+    let other_roles = CreateUser {
+        roles: vec!["dev".to_string(), "admin".to_string()],
+        ..user.clone()
+    };
+
+    // Act
+    let statuses = [
+        post(&server, user.encode_to_vec()),
+        post(&server, reordered),
+        post(&server, other_roles.encode_to_vec()),
+        post(&server, b"not protobuf".to_vec()),
+    ];
+
+    // Assert
+    assert_eq!(statuses, [201, 201, 404, 404]);
+    m.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "the fields 2 differ")]
+fn protobuf_body_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+    let user = CreateUser {
+        name: "Fred".to_string(),
+        age: 30,
+        roles: Vec::new(),
+    };
+
+    let m = server.mock(|when, then| {
+        when.path("/users").protobuf_body(&user);
+        then.status(201);
+    });
+
+    // Act
+    post(&server, CreateUser { age: 31, ..user }.encode_to_vec());
+
+    // Assert
+    m.assert();
+}
@@ -0,0 +1,74 @@
+use httpmock::prelude::*;
+use isahc::config::Configurable;
+use isahc::{HttpClient, ReadResponseExt};
+
+#[test]
+fn proxy_test() {
+    // Arrange
+    let proxy = MockServer::start_proxy();
+
+    let example_mock = proxy.mock(|when, then| {
+        when.host("example.com").path("/hello");
+        then.status(200).body("example.com");
+    });
+    let other_mock = proxy.mock(|when, then| {
+        when.host("other.example.org").path("/hello");
+        then.status(200).body("other.example.org");
+    });
+
+    let client = HttpClient::builder()
+        .proxy(Some(proxy.base_url().parse().unwrap()))
+        .build()
+        .unwrap();
+
+    // Act
+    let mut example_response = client.get("http://example.com/hello").unwrap();
+    let mut other_response = client.get("http://other.example.org:8080/hello").unwrap();
+
+    // Assert
+    assert_eq!(example_response.text().unwrap(), "example.com");
+    assert_eq!(other_response.text().unwrap(), "other.example.org");
+    example_mock.assert();
+    other_mock.assert();
+}
+
+#[cfg(feature = "https")]
+#[test]
+fn proxy_connect_test() {
+    use rustls::{ClientConfig, RootCertStore};
+    use std::sync::Arc;
+
+    // Arrange
+    let proxy = MockServer::start_proxy();
+
+    let m = proxy.mock(|when, then| {
+        when.method(GET).host("api.example.com").path("/users");
+        then.status(200).body("[]");
+    });
+
+    // Trust the certificate authority of the proxy only.
+    let mut roots = RootCertStore::empty();
+    let ca_pem = proxy.proxy_ca_pem();
+    for cert in rustls_pemfile::certs(&mut ca_pem.as_bytes()) {
+        roots.add(cert.unwrap()).unwrap();
+    }
+    let tls_config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+    let agent = ureq::AgentBuilder::new()
+        .proxy(ureq::Proxy::new(proxy.base_url()).unwrap())
+        .tls_config(Arc::new(tls_config))
+        .build();
+
+    // Act
+    let response = agent.get("https://api.example.com/users").call().unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.into_string().unwrap(), "[]");
+    m.assert();
+}
@@ -1,5 +1,6 @@
 use httpmock::prelude::*;
 use isahc::get as http_get;
+use serde_json::json;
 use ureq::get as httpget;
 
 #[test]
@@ -76,3 +77,271 @@ fn url_param_encoding_issue_56() {
     // Assert
     m.assert();
 }
+
+#[test]
+fn url_param_missing_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/search").query_param_missing("debug");
+        then.status(200);
+    });
+
+    // Act
+    let with_value = http_get(server.url("/search?query=Metallica&debug=true")).unwrap();
+    let without_value = http_get(server.url("/search?debug")).unwrap();
+    let missing = http_get(server.url("/search?query=Metallica")).unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(with_value.status(), 404);
+    assert_eq!(without_value.status(), 404);
+    assert_eq!(missing.status(), 200);
+}
+
+#[test]
+#[should_panic(
+    expected = "Expected query parameter with name 'debug' to be missing in the request but it was present."
+)]
+fn url_param_missing_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/search").query_param_missing("debug");
+        then.status(200);
+    });
+
+    // Act
+    http_get(server.url("/search?debug=true")).unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+fn url_param_values_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users")
+            .query_param_values("id", ["1", "2", "3"])
+            .query_param_values("tag", ["a b"]);
+        then.status(200);
+    });
+
+    // Act
+    let status = |query: &str| {
+        http_get(server.url(format!("/users?{}", query)))
+            .unwrap()
+            .status()
+            .as_u16()
+    };
+
+    // Assert
+    assert_eq!(status("id=1&id=2&id=3&tag=a+b"), 200);
+    assert_eq!(status("tag=a%20b&id=3&id=1&id=2"), 200);
+    assert_eq!(status("id=1&id=2&tag=a+b"), 404);
+    assert_eq!(status("id=1&id=2&id=3&id=4&tag=a+b"), 404);
+    assert_eq!(status("id=1&id=1&id=2&id=3&tag=a+b"), 404);
+    assert_eq!(status("id=1&id=2&id=3"), 404);
+    m.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "expected 'id' to have the values '1', '2', '3' but got '1', '3'")]
+fn url_param_values_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users")
+            .query_param_values("id", ["1", "2", "3"]);
+        then.status(200);
+    });
+
+    // Act
+    http_get(server.url("/users?id=3&id=1")).unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+fn query_string_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let any_order = server.mock(|when, then| {
+        when.path("/search").query_string("?tag=a+b&page=1");
+        then.status(200);
+    });
+    let ordered = server.mock(|when, then| {
+        when.path("/list").query_string_ordered("page=1&tag=a+b");
+        then.status(200);
+    });
+    let no_query = server.mock(|when, then| {
+        when.path("/all").query_string("");
+        then.status(200);
+    });
+
+    // Act
+    let status = |path_and_query: &str| {
+        http_get(server.url(path_and_query))
+            .unwrap()
+            .status()
+            .as_u16()
+    };
+
+    // Assert
+    assert_eq!(status("/search?page=1&tag=a%20b"), 200);
+    assert_eq!(status("/search?tag=a+b&page=1"), 200);
+    assert_eq!(status("/search?page=1&tag=a+b&debug=true"), 404);
+    assert_eq!(status("/search?page=1"), 404);
+    assert_eq!(status("/list?page=1&tag=a+b"), 200);
+    assert_eq!(status("/list?tag=a+b&page=1"), 404);
+    assert_eq!(status("/all"), 200);
+    assert_eq!(status("/all?page=1"), 404);
+    any_order.assert_hits(2);
+    ordered.assert_hits(1);
+    no_query.assert_hits(1);
+}
+
+#[test]
+#[should_panic(
+    expected = "expected 'page=1&tag=rust' but got 'page=1&tag=rust&debug=true' \
+                           (unexpected 'debug=true')"
+)]
+fn query_string_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/search").query_string("page=1&tag=rust");
+        then.status(200);
+    });
+
+    // Act
+    http_get(server.url("/search?page=1&tag=rust&debug=true")).unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+fn nested_query_param_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let param = server.mock(|when, then| {
+        when.path("/param").query_param("filter[status]", "open");
+        then.status(200);
+    });
+    let object = server.mock(|when, then| {
+        when.path("/object").query_object(json!({
+            "filter": { "status": "open", "page": 2 },
+            "tags": ["bug", "ui"],
+            "items": [{ "id": 1, "name": "a" }, { "id": 2 }]
+        }));
+        then.status(200);
+    });
+
+    // Act
+    let query = "filter[status]=open&filter[page]=2&sort=created&tags[]=bug&tags[]=ui\
+                 &items[][id]=1&items[][name]=a&items[][id]=2";
+    let encoded = query.replace('[', "%5B").replace(']', "%5D");
+    let status = |path: &str, query: &str| {
+        http_get(server.url(format!("{}?{}", path, query)))
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(status("/param", "filter[status]=open"), 200);
+    assert_eq!(status("/param", "filter%5Bstatus%5D=open"), 200);
+    assert_eq!(status("/param", "filter[status]=closed"), 404);
+    assert_eq!(status("/object", query), 200);
+    assert_eq!(status("/object", &encoded), 200);
+    assert_eq!(status("/object", &query.replace("page]=2", "page]=3")), 404);
+    assert_eq!(status("/object", "filter[status]=open&filter[page]=2"), 404);
+    param.assert_hits(2);
+    object.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "The query object does not match")]
+fn nested_query_param_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/issues")
+            .query_object(json!({ "filter": { "status": "open" } }));
+        then.status(200);
+    });
+
+    // Act
+    http_get(server.url("/issues?filter[status]=closed")).unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+fn query_params_raw_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let percent = server.mock(|when, then| {
+        when.path("/percent")
+            .query_params_raw()
+            .query_param("q", "hello%20world")
+            .query_param_exists("page%5B1%5D");
+        then.status(200);
+    });
+    let plus = server.mock(|when, then| {
+        when.path("/plus")
+            .query_params_raw()
+            .query_param("q", "hello+world");
+        then.status(200);
+    });
+    let decoded = server.mock(|when, then| {
+        when.path("/decoded").query_param("q", "hello world");
+        then.status(200);
+    });
+
+    // Act
+    let status = |url: &str| http_get(server.url(url)).unwrap().status();
+
+    // Assert
+    assert_eq!(status("/percent?q=hello%20world&page%5B1%5D"), 200);
+    assert_eq!(status("/percent?q=hello+world&page%5B1%5D"), 404);
+    assert_eq!(status("/percent?q=hello%20world&page[1]"), 404);
+    assert_eq!(status("/plus?q=hello+world"), 200);
+    assert_eq!(status("/plus?q=hello%20world"), 404);
+    assert_eq!(status("/decoded?q=hello+world"), 200);
+    assert_eq!(status("/decoded?q=hello%20world"), 200);
+    percent.assert_hits(1);
+    plus.assert_hits(1);
+    decoded.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "hello+world")]
+fn query_params_raw_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.query_params_raw().query_param("q", "hello%20world");
+        then.status(200);
+    });
+
+    // Act
+    http_get(server.url("/search?q=hello+world")).unwrap();
+
+    // Assert
+    m.assert();
+}
@@ -0,0 +1,108 @@
+use httpmock::prelude::*;
+use httpmock::{RecordingOptions, Regex};
+use isahc::{prelude::*, Request};
+use serde_json::{json, Value};
+
+fn recording_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir()
+        .join(format!("httpmock-recordings-{}", std::process::id()))
+        .join(format!("{}.yaml", name))
+}
+
+#[test]
+fn record_and_playback_test() {
+    // Arrange: A mock server stands in for the real upstream API
+    let upstream = MockServer::start();
+    let upstream_mock = upstream.mock(|when, then| {
+        when.method(GET)
+            .path("/repos/httpmock")
+            .query_param("page", "1");
+        then.status(200)
+            .header("content-type", "application/json")
+            .json_body(json!({ "name": "httpmock", "updated_at": "2022-03-01T10:00:00Z" }));
+    });
+    let path = recording_path("playback");
+
+    // Act: Record the traffic and save it
+    let recorder = MockServer::start_recording(upstream.base_url());
+    let recorded_response = isahc::get(recorder.url("/repos/httpmock?page=1")).unwrap();
+    recorder.save_recording_with_options(
+        &path,
+        RecordingOptions::new().normalize_json_field("$.updated_at", "NORMALIZED"),
+    );
+
+    let server = MockServer::playback(&path);
+    let mut response = isahc::get(server.url("/repos/httpmock?page=1")).unwrap();
+    let other_page = isahc::get(server.url("/repos/httpmock?page=2")).unwrap();
+
+    // Assert: The upstream was only contacted while recording
+    upstream_mock.assert_hits(1);
+    assert_eq!(recorded_response.status(), 200);
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/json"
+    );
+    assert_eq!(
+        response.json::<Value>().unwrap(),
+        json!({ "name": "httpmock", "updated_at": "NORMALIZED" })
+    );
+    assert_eq!(other_page.status(), 404);
+}
+
+#[test]
+fn recording_redacts_secrets_test() {
+    // Arrange
+    let upstream = MockServer::start();
+    upstream.mock(|when, then| {
+        when.method(POST).path("/login");
+        then.status(200)
+            .header("set-cookie", "session=8f2a")
+            .body(r#"{"token":"s3cr3t"}"#);
+    });
+    let path = recording_path("redacted");
+
+    // Act
+    let recorder = MockServer::start_recording(upstream.base_url());
+    Request::post(recorder.url("/login"))
+        .header("authorization", "Bearer t0k3n")
+        .body(r#"{"user":"fred","password":"hunter2"}"#)
+        .unwrap()
+        .send()
+        .unwrap();
+    recorder.save_recording_with_options(
+        &path,
+        RecordingOptions::new()
+            .match_header("authorization")
+            .redact_body(Regex::new(r#""(?:password|token)":"([^"]*)""#).unwrap()),
+    );
+
+    let server = MockServer::playback(&path);
+    let mut response = Request::post(server.url("/login"))
+        .header("authorization", "Bearer other")
+        .body(r#"{"user":"fred","password":"other"}"#)
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    let recording = std::fs::read_to_string(&path).unwrap();
+    for secret in &["t0k3n", "hunter2", "s3cr3t", "8f2a"] {
+        assert!(!recording.contains(secret), "{}", recording);
+    }
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().unwrap(), r#"{"token":"REDACTED"}"#);
+}
+
+#[test]
+fn recording_unreachable_upstream_test() {
+    // Arrange: Nothing listens on the upstream port anymore
+    let upstream_url = MockServer::builder().start().base_url();
+    let recorder = MockServer::start_recording(upstream_url);
+
+    // Act
+    let response = isahc::get(recorder.url("/hello")).unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 502);
+}
@@ -0,0 +1,172 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use httpmock::prelude::*;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Notify;
+
+/// A TCP proxy in front of a mock server that counts the connections it accepted. It can
+/// simulate a flaky network by resetting the next new connection or by dropping all requests.
+struct Proxy {
+    address: String,
+    connections: Arc<AtomicUsize>,
+    reset_next_connection: Arc<AtomicBool>,
+    close_connections: Arc<Notify>,
+    stall: Arc<AtomicBool>,
+}
+
+async fn start_proxy(upstream: SocketAddr) -> Proxy {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy = Proxy {
+        address: listener.local_addr().unwrap().to_string(),
+        connections: Arc::new(AtomicUsize::new(0)),
+        reset_next_connection: Arc::new(AtomicBool::new(false)),
+        close_connections: Arc::new(Notify::new()),
+        stall: Arc::new(AtomicBool::new(false)),
+    };
+
+    let (connections, reset_next_connection, close, stall) = (
+        proxy.connections.clone(),
+        proxy.reset_next_connection.clone(),
+        proxy.close_connections.clone(),
+        proxy.stall.clone(),
+    );
+    tokio::spawn(async move {
+        while let Ok((client, _)) = listener.accept().await {
+            connections.fetch_add(1, SeqCst);
+            let reset = reset_next_connection.swap(false, SeqCst);
+            tokio::spawn(forward(
+                client,
+                upstream,
+                reset,
+                close.clone(),
+                stall.clone(),
+            ));
+        }
+    });
+
+    proxy
+}
+
+async fn forward(
+    mut client: TcpStream,
+    upstream: SocketAddr,
+    reset: bool,
+    close: Arc<Notify>,
+    stall: Arc<AtomicBool>,
+) {
+    // Closing a connection without lingering resets it (without blocking).
+    socket2::SockRef::from(&client)
+        .set_linger(Some(Duration::ZERO))
+        .unwrap();
+    let mut server = TcpStream::connect(upstream).await.unwrap();
+    let (mut request, mut response) = (vec![0; 64 * 1024], vec![0; 64 * 1024]);
+    loop {
+        tokio::select! {
+            read = client.read(&mut request) => match read {
+                Ok(n) if n > 0 && reset => return,
+                Ok(_) if stall.load(SeqCst) => {}
+                Ok(n) if n > 0 => {
+                    if server.write_all(&request[..n]).await.is_err() {
+                        return;
+                    }
+                }
+                _ => return,
+            },
+            read = server.read(&mut response) => match read {
+                Ok(n) if n > 0 => {
+                    if client.write_all(&response[..n]).await.is_err() {
+                        return;
+                    }
+                }
+                _ => return,
+            },
+            _ = close.notified() => return,
+        }
+    }
+}
+
+#[tokio::test]
+async fn remote_admin_connection_reuse_test() {
+    // Arrange
+    let target = MockServer::builder().start_async().await;
+    let proxy = start_proxy(*target.address()).await;
+    let server = MockServer::builder().connect_async(&proxy.address).await;
+
+    // Act
+    for i in 0..10 {
+        let mock = server
+            .mock_async(|when, then| {
+                when.path(format!("/item/{}", i));
+                then.status(200);
+            })
+            .await;
+        assert_eq!(mock.hits_async().await, 0);
+        mock.delete_async().await;
+    }
+
+    // Assert
+    assert_eq!(proxy.connections.load(SeqCst), 1);
+}
+
+#[tokio::test]
+async fn remote_admin_retry_after_connection_reset_test() {
+    // Arrange
+    let target = MockServer::builder().start_async().await;
+    let proxy = start_proxy(*target.address()).await;
+    let server = MockServer::builder().connect_async(&proxy.address).await;
+
+    // The HTTP client repeats requests on its own if a reused connection turns out to be
+    // closed, but not if a new connection is reset.
+    proxy.reset_next_connection.store(true, SeqCst);
+    proxy.close_connections.notify_waiters();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // Act
+    let mock = server
+        .mock_async(|when, then| {
+            when.path("/retried");
+            then.status(200);
+        })
+        .await;
+
+    // Assert
+    assert_eq!(proxy.connections.load(SeqCst), 3);
+    assert_eq!(
+        isahc::get_async(target.url("/retried"))
+            .await
+            .unwrap()
+            .status(),
+        200
+    );
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn remote_admin_timeout_test() {
+    // Arrange
+    let target = MockServer::builder().start_async().await;
+    let proxy = start_proxy(*target.address()).await;
+    let server = MockServer::builder()
+        .admin_timeout(Duration::from_millis(200))
+        .admin_retries(0)
+        .connect_async(&proxy.address)
+        .await;
+    proxy.stall.store(true, SeqCst);
+    let started = Instant::now();
+
+    // Act
+    let result = server
+        .try_mock_async(|when, then| {
+            when.path("/stalled");
+            then.status(200);
+        })
+        .await;
+
+    // Assert
+    assert!(result.is_err());
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,113 @@
+use std::sync::Mutex;
+
+use httpmock::prelude::*;
+use httpmock::{LogFormat, LogLevel};
+use isahc::{prelude::*, Request};
+use serde_json::Value;
+
+/// Collects the messages of the request log.
+struct CapturingLogger;
+
+static MESSAGES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static LOGGER: CapturingLogger = CapturingLogger;
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.target() == "httpmock::server::request_log"
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            MESSAGES.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Returns the captured log messages that contain the given text. Tests run in parallel, so
+/// every test uses its own request path.
+fn messages_containing(text: &str) -> Vec<String> {
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(log::LevelFilter::Trace);
+    MESSAGES
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|message| message.contains(text))
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn pretty_request_log_test() {
+    // Arrange
+    messages_containing("");
+    let server = MockServer::start();
+    server.log_requests(LogLevel::Info, LogFormat::Pretty);
+    server.redact_headers(&["Authorization"]);
+
+    let m = server.mock(|when, then| {
+        when.method(POST).path("/pretty-log");
+        then.status(201);
+    });
+
+    // Act
+    Request::post(server.url("/pretty-log"))
+        .header("Authorization", "Bearer secret")
+        .header("Content-Type", "application/json")
+        .body(r#"{"name":"Fred"}"#)
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    m.assert();
+    let messages = messages_containing("/pretty-log");
+    assert_eq!(messages.len(), 1);
+    let message = &messages[0];
+    assert!(message.starts_with(&format!(
+        "POST /pretty-log matched mock {} (POST /pretty-log), response status 201",
+        m.id
+    )));
+    assert!(message.contains("authorization: [REDACTED]"));
+    assert!(!message.contains("secret"));
+    assert!(message.ends_with("  {\n    \"name\": \"Fred\"\n  }"));
+}
+
+#[test]
+fn json_request_log_test() {
+    // Arrange
+    messages_containing("");
+    let server = MockServer::start();
+    server.log_requests(LogLevel::Debug, LogFormat::Json);
+    server.log_body_limit(4);
+
+    // Act
+    isahc::post(server.url("/json-log?page=2"), "abcdefgh").unwrap();
+
+    // Assert
+    let messages = messages_containing("/json-log");
+    assert_eq!(messages.len(), 1);
+    let entry: Value = serde_json::from_str(&messages[0]).unwrap();
+    assert_eq!(entry["method"], "POST");
+    assert_eq!(entry["path"], "/json-log?page=2");
+    assert_eq!(entry["body"], "abcd... (8 bytes)");
+    assert_eq!(entry["mock_id"], Value::Null);
+    assert_eq!(entry["status"], 404);
+}
+
+#[test]
+fn stop_logging_requests_test() {
+    // Arrange
+    messages_containing("");
+    let server = MockServer::start();
+    server.log_requests(LogLevel::Info, LogFormat::Pretty);
+    server.stop_logging_requests();
+
+    // Act
+    isahc::get(server.url("/stopped-log")).unwrap();
+
+    // Assert
+    assert!(messages_containing("/stopped-log").is_empty());
+}
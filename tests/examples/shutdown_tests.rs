@@ -0,0 +1,151 @@
+use httpmock::prelude::*;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn shutdown_test() {
+    // Arrange
+    let server = MockServer::builder().start();
+    let port = server.port();
+    let url = server.url("/hello");
+
+    // Act
+    server.shutdown();
+
+    // Assert: The port is free again and no more requests are accepted.
+    assert!(TcpListener::bind(("127.0.0.1", port)).is_ok());
+    assert!(isahc::get(url).is_err());
+}
+
+#[test]
+fn shutdown_drains_in_flight_requests_test() {
+    // Arrange
+    let server = MockServer::builder().start();
+
+    server.mock(|when, then| {
+        when.path("/slow");
+        then.status(200).delay(Duration::from_millis(500));
+    });
+
+    let url = server.url("/slow");
+    let request = thread::spawn(move || isahc::get(url).map(|r| r.status()));
+    thread::sleep(Duration::from_millis(200));
+
+    // Act
+    server.shutdown();
+
+    // Assert
+    assert_eq!(request.join().unwrap().unwrap(), 200);
+}
+
+#[test]
+fn shutdown_timeout_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .shutdown_timeout(Duration::from_millis(100))
+        .start();
+
+    server.mock(|when, then| {
+        when.path("/slow");
+        then.status(200).delay(Duration::from_secs(5));
+    });
+
+    let url = server.url("/slow");
+    let request = thread::spawn(move || isahc::get(url).map(|r| r.status()));
+    thread::sleep(Duration::from_millis(200));
+
+    // Act
+    let start_time = Instant::now();
+    server.shutdown();
+
+    // Assert
+    assert!(start_time.elapsed() < Duration::from_secs(2));
+    assert!(request.join().unwrap().is_err());
+}
+
+#[test]
+#[should_panic(expected = "The mock server has been shut down")]
+fn shutdown_stale_handle_test() {
+    // Arrange
+    let server = MockServer::builder().start();
+    server.shutdown();
+
+    // Act
+    server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+}
+
+#[test]
+fn shutdown_pooled_server_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.shutdown();
+    drop(server);
+
+    // Act: The shut down server must not be reused by the pool.
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+    let response = isahc::get(server.url("/hello")).unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(response.status(), 200);
+}
+
+#[test]
+fn drop_deadline_test() {
+    // Arrange
+    let server = MockServer::builder()
+        .drop_deadline(Duration::from_millis(100))
+        .start();
+    let port = server.port();
+
+    server.mock(|when, then| {
+        when.path("/slow");
+        then.status(200).delay(Duration::from_secs(10));
+    });
+
+    let url = server.url("/slow");
+    let request = thread::spawn(move || isahc::get(url).map(|r| r.status()));
+    thread::sleep(Duration::from_millis(200));
+
+    // Act
+    let start_time = Instant::now();
+    drop(server);
+
+    // Assert: In-flight requests are aborted after the deadline and the port is free again.
+    assert!(start_time.elapsed() < Duration::from_secs(2));
+    assert!(TcpListener::bind(("127.0.0.1", port)).is_ok());
+    assert!(request.join().unwrap().is_err());
+}
+
+#[tokio::test]
+async fn drop_in_async_context_test() {
+    // Arrange
+    let server = MockServer::builder().start_async().await;
+    let port = server.port();
+    server
+        .mock_async(|when, then| {
+            when.path("/hello");
+            then.status(200);
+        })
+        .await
+        .assert_hits_async(0)
+        .await;
+
+    // Act: The server is stopped in the background without blocking the runtime.
+    drop(server);
+
+    // Assert
+    let start_time = Instant::now();
+    while TcpListener::bind(("127.0.0.1", port)).is_err() {
+        assert!(start_time.elapsed() < Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
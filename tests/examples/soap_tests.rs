@@ -0,0 +1,145 @@
+use httpmock::prelude::*;
+use httpmock::{SoapFaultCode, SoapVersion};
+use isahc::{prelude::*, Request};
+
+const GET_USER_1_1: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/">
+  <soap:Body>
+    <u:GetUser xmlns:u="urn:users">
+      <u:Id>42</u:Id>
+    </u:GetUser>
+  </soap:Body>
+</soap:Envelope>"#;
+
+const GET_USER_1_2: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope">
+  <env:Body>
+    <GetUser><Id>7</Id></GetUser>
+  </env:Body>
+</env:Envelope>"#;
+
+fn post_soap(
+    server: &MockServer,
+    content_type: &str,
+    action: Option<&str>,
+    body: &str,
+) -> (u16, String, String) {
+    let mut request = Request::post(server.url("/users")).header("content-type", content_type);
+    if let Some(action) = action {
+        request = request.header("SOAPAction", action);
+    }
+    let mut response = request.body(body.to_string()).unwrap().send().unwrap();
+    let content_type = response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    (
+        response.status().as_u16(),
+        content_type,
+        response.text().unwrap(),
+    )
+}
+
+#[test]
+fn soap_1_1_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users")
+            .soap_action(SoapVersion::V1_1, "urn:GetUser")
+            .soap_body_xpath("GetUser/Id", "42");
+        then.soap_body(
+            SoapVersion::V1_1,
+            "<GetUserResponse><Name>Fred</Name></GetUserResponse>",
+        );
+    });
+
+    // Act
+    let (status, content_type, body) = post_soap(
+        &server,
+        "text/xml; charset=utf-8",
+        Some("\"urn:GetUser\""),
+        GET_USER_1_1,
+    );
+    let (wrong_action_status, _, _) = post_soap(
+        &server,
+        "text/xml; charset=utf-8",
+        Some("\"urn:DeleteUser\""),
+        GET_USER_1_1,
+    );
+    let (wrong_id_status, _, _) = post_soap(
+        &server,
+        "text/xml",
+        Some("urn:GetUser"),
+        &GET_USER_1_1.replace("42", "43"),
+    );
+
+    // Assert
+    m.assert_hits(1);
+    assert_eq!(status, 200);
+    assert_eq!(content_type, "text/xml; charset=utf-8");
+    assert!(body.contains(
+        "<soap:Envelope xmlns:soap=\"http://schemas.xmlsoap.org/soap/envelope/\"><soap:Body>\
+         <GetUserResponse><Name>Fred</Name></GetUserResponse></soap:Body></soap:Envelope>"
+    ));
+    assert_eq!(wrong_action_status, 404);
+    assert_eq!(wrong_id_status, 404);
+}
+
+#[test]
+fn soap_1_2_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users")
+            .soap_action(SoapVersion::V1_2, "urn:GetUser")
+            .soap_body_xpath("//Id", "7");
+        then.soap_fault(SoapVersion::V1_2, SoapFaultCode::Sender, "Unknown user");
+    });
+
+    // Act
+    let (status, content_type, body) = post_soap(
+        &server,
+        "application/soap+xml; charset=utf-8; action=\"urn:GetUser\"",
+        None,
+        GET_USER_1_2,
+    );
+    let (v1_1_status, _, _) = post_soap(
+        &server,
+        "text/xml; charset=utf-8",
+        Some("\"urn:GetUser\""),
+        GET_USER_1_2,
+    );
+
+    // Assert
+    m.assert_hits(1);
+    assert_eq!(status, 400);
+    assert_eq!(content_type, "application/soap+xml; charset=utf-8");
+    assert!(body.contains("<soap:Value>soap:Sender</soap:Value>"));
+    assert!(body.contains("<soap:Text xml:lang=\"en\">Unknown user</soap:Text>"));
+    assert_eq!(v1_1_status, 404);
+}
+
+#[test]
+fn soap_1_1_fault_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    server.mock(|when, then| {
+        when.soap_action(SoapVersion::V1_1, "urn:GetUser");
+        then.soap_fault(SoapVersion::V1_1, SoapFaultCode::Receiver, "Try <later>");
+    });
+
+    // Act
+    let (status, content_type, body) =
+        post_soap(&server, "text/xml", Some("urn:GetUser"), GET_USER_1_1);
+
+    // Assert
+    assert_eq!(status, 500);
+    assert_eq!(content_type, "text/xml; charset=utf-8");
+    assert!(body.contains(
+        "<faultcode>soap:Server</faultcode><faultstring>Try &lt;later&gt;</faultstring>"
+    ));
+}
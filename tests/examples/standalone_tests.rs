@@ -78,7 +78,7 @@ async fn async_standalone_test() {
 }
 
 #[test]
-#[should_panic]
+#[should_panic(expected = "Anonymous function request matchers are not supported")]
 fn unsupported_features() {
     // Arrange
 
@@ -96,6 +96,28 @@ fn unsupported_features() {
     });
 }
 
+#[test]
+fn reset_standalone_test() {
+    // Arrange
+
+    // This starts up a standalone server in the background running on port 5000
+    simulate_standalone_server();
+
+    let server = MockServer::connect_from_env();
+    let m = server.mock(|when, then| {
+        when.path("/reset");
+        then.status(200);
+    });
+    assert_eq!(isahc::get(server.url("/reset")).unwrap().status(), 200);
+    m.assert();
+
+    // Act: Remove all mocks and the request history from the remote server
+    server.reset();
+
+    // Assert
+    assert_eq!(isahc::get(server.url("/reset")).unwrap().status(), 404);
+}
+
 #[test]
 fn binary_body_standalone_test() {
     // Arrange
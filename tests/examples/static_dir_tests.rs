@@ -0,0 +1,96 @@
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+use serde_json::Value;
+
+#[test]
+fn serve_dir_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.serve_dir_with_index("/assets", "tests/resources/static", "index.html");
+
+    // Act
+    let mut css = isahc::get(server.url("/assets/css/style.css")).unwrap();
+    let mut index = isahc::get(server.url("/assets/")).unwrap();
+    let missing = isahc::get(server.url("/assets/missing.css")).unwrap();
+    let traversal = isahc::get(server.url("/assets/..%2F..%2Fsimple_body.txt")).unwrap();
+
+    // Assert
+    assert_eq!(css.status(), 200);
+    assert_eq!(css.headers()["content-type"], "text/css; charset=utf-8");
+    assert_eq!(css.text().unwrap(), "body { color: #333; }\n");
+    assert_eq!(index.status(), 200);
+    assert_eq!(index.headers()["content-type"], "text/html; charset=utf-8");
+    assert!(index.text().unwrap().contains("<h1>Assets</h1>"));
+    assert_eq!(missing.status(), 404);
+    assert_eq!(traversal.status(), 403);
+}
+
+#[test]
+fn serve_dir_mocks_take_precedence_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.serve_dir("/assets", "tests/resources/static");
+
+    let mock = server.mock(|when, then| {
+        when.path("/assets/css/style.css");
+        then.status(200).body("mocked");
+    });
+
+    // Act
+    let mut mocked = isahc::get(server.url("/assets/css/style.css")).unwrap();
+    let index = isahc::get(server.url("/assets/")).unwrap();
+    let post = Request::post(server.url("/assets/index.html"))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+    let html = isahc::get(server.url("/assets/index.html")).unwrap();
+
+    // Assert
+    mock.assert();
+    assert_eq!(mocked.text().unwrap(), "mocked");
+    assert_eq!(index.status(), 404);
+    assert_eq!(post.status(), 404);
+    assert_eq!(html.status(), 200);
+
+    let history: Value = isahc::get(server.url("/__httpmock__/history"))
+        .unwrap()
+        .json()
+        .unwrap();
+    let paths: Vec<&str> = history
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![
+            "/assets/css/style.css",
+            "/assets/",
+            "/assets/index.html",
+            "/assets/index.html"
+        ]
+    );
+}
+
+#[test]
+fn serve_dir_reset_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.serve_dir("/", "tests/resources/static");
+    assert_eq!(isahc::get(server.url("/index.html")).unwrap().status(), 200);
+
+    // Act
+    server.reset();
+
+    // Assert
+    assert_eq!(isahc::get(server.url("/index.html")).unwrap().status(), 404);
+}
+
+#[test]
+#[should_panic(expected = "the path prefix 'assets' must start with a slash")]
+fn serve_dir_invalid_prefix_test() {
+    let server = MockServer::start();
+    server.serve_dir("assets", "tests/resources/static");
+}
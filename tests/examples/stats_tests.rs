@@ -0,0 +1,74 @@
+use std::time::Duration;
+
+use httpmock::prelude::*;
+use isahc::prelude::*;
+
+#[test]
+fn stats_test() {
+    // Arrange
+    let server = MockServer::start();
+    let hello = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200).delay(Duration::from_millis(200));
+    });
+    let missing = server.mock(|when, then| {
+        when.path("/missing");
+        then.status(404);
+    });
+
+    // Act
+    for _ in 0..3 {
+        isahc::get(server.url("/hello")).unwrap();
+    }
+    isahc::get(server.url("/missing")).unwrap();
+    isahc::get(server.url("/unknown")).unwrap();
+
+    let stats = server.stats();
+
+    // Assert
+    assert_eq!(stats.total_requests, 5);
+    assert_eq!(stats.unmatched_requests, 1);
+    assert_eq!(stats.mock_hits[&hello.id], 3);
+    assert_eq!(stats.mock_hits[&missing.id], 1);
+    assert_eq!(stats.status_counts[&200], 3);
+    assert_eq!(stats.status_counts[&404], 2);
+    assert_eq!(stats.latency.count, 5);
+    assert!(stats.latency.p50 <= stats.latency.p99);
+    assert!(stats.latency.p99 <= stats.latency.max);
+
+    // The configured delay is not counted as handling time.
+    assert!(stats.latency.max < Duration::from_millis(200));
+
+    // Resetting the server resets the statistics
+    server.reset();
+    assert_eq!(server.stats().total_requests, 0);
+}
+
+#[test]
+fn metrics_endpoint_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+    isahc::get(server.url("/hello")).unwrap();
+
+    // Act
+    let mut response = isahc::get(server.url("/__httpmock__/metrics")).unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "text/plain; version=0.0.4"
+    );
+    let text = response.text().unwrap();
+    assert!(text.contains("httpmock_requests_total 1\n"));
+    assert!(text.contains(&format!(
+        "httpmock_mock_hits_total{{mock_id=\"{}\"}} 1\n",
+        mock.id
+    )));
+    assert!(text.contains("httpmock_responses_total{status=\"200\"} 1\n"));
+    assert!(text.contains("httpmock_request_duration_seconds_count 1\n"));
+}
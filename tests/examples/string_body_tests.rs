@@ -1,6 +1,7 @@
 use httpmock::prelude::*;
 use isahc::{prelude::*, Request};
 
+#[cfg(feature = "regex")]
 #[test]
 fn body_test() {
     // Arrange
@@ -16,7 +17,7 @@ fn body_test() {
     });
 
     // Act: Send the request and deserialize the response to JSON
-    let response = Request::post(&format!("http://{}/books", server.address()))
+    let response = Request::post(&server.url("/books"))
         .body("The Fellowship of the Ring")
         .unwrap()
         .send()
@@ -26,3 +27,56 @@ fn body_test() {
     m.assert();
     assert_eq!(response.status(), 201);
 }
+
+#[cfg(feature = "regex")]
+#[test]
+fn body_negation_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users")
+            .body_not_contains("\"nickname\"")
+            .body_not_matches(Regex::new(r#""age":\s*-"#).unwrap());
+        then.status(201);
+    });
+
+    // Act
+    let send = |body: &str| {
+        Request::post(server.url("/users"))
+            .body(body.to_string())
+            .unwrap()
+            .send()
+            .unwrap()
+            .status()
+    };
+
+    // Assert
+    assert_eq!(send(r#"{ "name": "Fred", "age": 30 }"#), 201);
+    assert_eq!(send(r#"{ "name": "Fred", "nickname": "F" }"#), 404);
+    assert_eq!(send(r#"{ "name": "Fred", "age": -1 }"#), 404);
+    assert_eq!(isahc::get(server.url("/users")).unwrap().status(), 201);
+    m.assert_hits(2);
+}
+
+#[test]
+#[should_panic(expected = "does not contain")]
+fn body_not_contains_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/users").body_not_contains("nickname");
+        then.status(201);
+    });
+
+    // Act
+    Request::post(server.url("/users"))
+        .body(r#"{ "nickname": "F" }"#)
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    m.assert();
+}
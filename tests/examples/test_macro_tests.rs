@@ -0,0 +1,84 @@
+use httpmock::prelude::*;
+
+#[httpmock::test]
+fn sync_test(server: MockServer) {
+    // Arrange
+    let mock = server.mock(|when, then| {
+        when.path("/hello");
+        then.status(200);
+    });
+
+    // Act
+    let response = isahc::get(server.url("/hello")).unwrap();
+
+    // Assert
+    mock.assert();
+    assert_eq!(response.status(), 200);
+}
+
+#[httpmock::test(verify_all)]
+#[tokio::test]
+async fn tokio_test(server: MockServer) {
+    server
+        .mock_async(|when, then| {
+            when.path("/tokio");
+            then.status(200);
+        })
+        .await;
+
+    isahc::get_async(server.url("/tokio")).await.unwrap();
+}
+
+#[httpmock::test(verify_all)]
+#[async_std::test]
+async fn async_std_test(server: MockServer) {
+    server
+        .mock_async(|when, then| {
+            when.path("/async-std");
+            then.status(200);
+        })
+        .await;
+
+    isahc::get_async(server.url("/async-std")).await.unwrap();
+}
+
+#[httpmock::test]
+fn result_test(server: MockServer) -> Result<(), isahc::Error> {
+    server.mock(|when, then| {
+        when.path("/result");
+        then.status(200);
+    });
+
+    let response = isahc::get(server.url("/result"))?;
+    assert_eq!(response.status(), 200);
+    Ok(())
+}
+
+#[httpmock::test(verify_all)]
+#[should_panic(expected = "The following mocks have not received any request:\nGET /unused")]
+fn verify_all_test(server: MockServer) {
+    server.mock(|when, then| {
+        when.path("/used");
+        then.status(200);
+    });
+    server.mock(|when, then| {
+        when.method(GET).path("/unused");
+        then.status(200);
+    });
+
+    isahc::get(server.url("/used")).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "GET /unused")]
+fn explicit_verify_all_test() {
+    // Arrange
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(GET).path("/unused");
+        then.status(200);
+    });
+
+    // Act
+    server.verify_all();
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Mutex, Once};
+
+use httpmock::prelude::*;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+type Fields = HashMap<String, String>;
+
+/// A layer that captures the fields of all closed spans and of all events.
+struct CaptureLayer;
+
+lazy_static::lazy_static! {
+    static ref SPANS: Mutex<Vec<(&'static str, Fields)>> = Mutex::new(Vec::new());
+    static ref EVENTS: Mutex<Vec<Fields>> = Mutex::new(Vec::new());
+}
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl<'a> Visit for FieldVisitor<'a> {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for CaptureLayer {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        ctx.span(id).unwrap().extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = ctx.span(id).unwrap();
+        let mut extensions = span.extensions_mut();
+        values.record(&mut FieldVisitor(extensions.get_mut::<Fields>().unwrap()));
+    }
+
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        let mut fields = Fields::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        EVENTS.lock().unwrap().push(fields);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = ctx.span(&id).unwrap();
+        let fields = span.extensions_mut().remove::<Fields>().unwrap();
+        SPANS.lock().unwrap().push((span.name(), fields));
+    }
+}
+
+/// Installs the capture layer globally, because the mock server handles requests on its own
+/// threads.
+fn init_capture() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        tracing::subscriber::set_global_default(tracing_subscriber::registry().with(CaptureLayer))
+            .expect("Cannot install tracing subscriber");
+    });
+}
+
+#[test]
+fn request_span_test() {
+    // Arrange
+    init_capture();
+    let server = MockServer::start();
+    let mock = server.mock(|when, then| {
+        when.method(GET).path("/tracing/request-span");
+        then.status(202);
+    });
+
+    // Act
+    isahc::get(server.url("/tracing/request-span")).unwrap();
+
+    // Assert: The span is closed shortly after the response has been sent
+    let find_span = || {
+        SPANS
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(_, fields)| {
+                fields.get("path").map(|p| p.as_str()) == Some("\"/tracing/request-span\"")
+            })
+            .cloned()
+    };
+    let mut span = find_span();
+    for _ in 0..50 {
+        if span.is_some() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        span = find_span();
+    }
+
+    let (name, fields) = span.expect("No span was emitted for the request");
+    assert_eq!(name, "httpmock.request");
+    assert_eq!(fields["method"], "GET");
+    assert_eq!(fields["mock_id"], mock.id.to_string());
+    assert_eq!(fields["status"], "202");
+    assert!(fields.contains_key("duration_ms"));
+}
+
+#[test]
+fn mock_events_test() {
+    // Arrange
+    init_capture();
+    let server = MockServer::start();
+
+    // Act
+    let mut mock = server.mock(|when, then| {
+        when.path("/tracing/mock-events");
+        then.status(200);
+    });
+    isahc::get(server.url("/tracing/mock-events")).unwrap();
+    mock.delete();
+
+    // Assert
+    let events = EVENTS.lock().unwrap();
+    let mock_id = mock.id.to_string();
+    let has_event = |message: &str| {
+        events.iter().any(|fields| {
+            fields.get("message").map(|m| m.as_str()) == Some(message)
+                && fields.get("mock_id") == Some(&mock_id)
+        })
+    };
+    assert!(has_event("Adding new mock"));
+    assert!(has_event("Deleted mock"));
+    assert!(events.iter().any(|fields| {
+        fields.get("message").map(|m| m.as_str()) == Some("Evaluated request matcher")
+            && fields.get("matcher").map(|m| m.as_str()) == Some("\"path\"")
+    }));
+}
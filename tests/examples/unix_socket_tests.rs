@@ -0,0 +1,78 @@
+use httpmock::prelude::*;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn unix_socket_test() {
+    // Arrange
+    let path = socket_file_path("unix_socket_test");
+    let server = MockServer::start_unix(&path);
+
+    let m = server.mock(|when, then| {
+        when.method(POST)
+            .path("/containers/create")
+            .query_param("name", "test")
+            .header("host", "docker")
+            .json_body(serde_json::json!({ "Image": "alpine" }));
+        then.status(201)
+            .header("content-type", "application/json")
+            .json_body(serde_json::json!({ "Id": "1234" }));
+    });
+
+    // Act
+    let body = r#"{"Image":"alpine"}"#;
+    let response = send_request(
+        server.socket_path().unwrap(),
+        &format!(
+            "POST /containers/create?name=test HTTP/1.1\r\n\
+             Host: docker\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n{}",
+            body.len(),
+            body
+        ),
+    );
+
+    // Assert
+    m.assert();
+    assert!(response.starts_with("HTTP/1.1 201 Created"));
+    assert!(response.ends_with(r#"{"Id":"1234"}"#));
+    assert_eq!(server.socket_path(), Some(path.as_path()));
+    assert_eq!(server.url("/_ping"), "http://localhost/_ping");
+}
+
+#[test]
+fn unix_socket_cleanup_test() {
+    // Arrange
+    let path = socket_file_path("unix_socket_cleanup_test");
+    let server = MockServer::start_unix(&path);
+    assert!(path.exists());
+
+    // Act
+    drop(server);
+
+    // Assert
+    assert!(!path.exists());
+    assert!(UnixStream::connect(&path).is_err());
+}
+
+#[test]
+#[should_panic(expected = "has no TCP address")]
+fn unix_socket_address_test() {
+    let server = MockServer::start_unix(socket_file_path("unix_socket_address_test"));
+    server.address();
+}
+
+fn socket_file_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("httpmock-{}-{}.sock", name, std::process::id()))
+}
+
+fn send_request(socket_path: &Path, request: &str) -> String {
+    let mut stream = UnixStream::connect(socket_path).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
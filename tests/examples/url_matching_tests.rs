@@ -1,6 +1,7 @@
 use httpmock::prelude::*;
 use isahc::get;
 
+#[cfg(feature = "regex")]
 #[test]
 fn url_matching_test() {
     // Arrange
@@ -19,3 +20,162 @@ fn url_matching_test() {
     // Assert
     m.assert();
 }
+
+#[test]
+fn path_prefix_and_suffix_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let api = server.mock(|when, then| {
+        when.path_prefix("/api/v1/");
+        then.status(200);
+    });
+    let json_files = server.mock(|when, then| {
+        when.path_prefix("/files/").path_suffix(".json");
+        then.status(200);
+    });
+
+    // Act
+    let status = |path: &str| get(server.url(path)).unwrap().status();
+
+    // Assert
+    assert_eq!(status("/api/v1/users"), 200);
+    assert_eq!(status("/api/v1/orders/1"), 200);
+    assert_eq!(status("/api/v2/users"), 404);
+    assert_eq!(status("/API/v1/users"), 404);
+    assert_eq!(status("/files/report.json"), 200);
+    assert_eq!(status("/files/report.json.bak"), 404);
+    assert_eq!(status("/report.json"), 404);
+    api.assert_hits(2);
+    json_files.assert_hits(1);
+    assert_eq!(
+        json_files.to_string(),
+        format!(
+            "Mock {}: * /files/*.json\n  path starts with '/files/'\n  path ends with '.json'",
+            json_files.id
+        )
+    );
+}
+
+#[test]
+#[should_panic(expected = "starts with")]
+fn path_prefix_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path_prefix("/api/v1/");
+        then.status(200);
+    });
+
+    // Act
+    get(server.url("/api/v2/users")).unwrap();
+
+    // Assert
+    m.assert();
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn path_negation_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path_prefix("/api/")
+            .path_not_contains("/internal/")
+            .path_not_matches(Regex::new(r"/v[0-1]/").unwrap());
+        then.status(200);
+    });
+
+    // Act
+    let status = |path: &str| get(server.url(path)).unwrap().status();
+
+    // Assert
+    assert_eq!(status("/api/v2/users"), 200);
+    assert_eq!(status("/api/internal/users"), 404);
+    assert_eq!(status("/api/v1/users"), 404);
+    m.assert_hits(1);
+    assert_eq!(
+        m.to_string(),
+        format!(
+            "Mock {}: * /api/*\n  path starts with '/api/'\n  \
+             path does not contain '/internal/'\n  path does not match regex '/v[0-1]/'",
+            m.id
+        )
+    );
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn path_normalization_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let users = server.mock(|when, then| {
+        when.path("/users/")
+            .path_case_insensitive()
+            .path_ignore_trailing_slash();
+        then.status(200);
+    });
+    let api = server.mock(|when, then| {
+        when.path_prefix("/api/")
+            .path_matches(Regex::new("/Orders$").unwrap())
+            .path_case_insensitive();
+        then.status(200);
+    });
+
+    // Act
+    let status = |path: &str| get(server.url(path)).unwrap().status();
+
+    // Assert
+    assert_eq!(status("/users"), 200);
+    assert_eq!(status("/Users/"), 200);
+    assert_eq!(status("/users//"), 404);
+    assert_eq!(status("/API/orders"), 200);
+    assert_eq!(status("/api/ORDERS"), 200);
+    assert_eq!(status("/api/orders/"), 404);
+    users.assert_hits(2);
+    api.assert_hits(2);
+    assert_eq!(
+        users.to_string(),
+        format!(
+            "Mock {}: * /users/\n  path equals '/users/'\n  \
+             path is compared case-insensitively\n  trailing slash of the path is ignored",
+            users.id
+        )
+    );
+}
+
+#[test]
+fn url_helpers_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path("/search");
+        then.status(200);
+    });
+
+    // Assert
+    let base_url = format!("http://{}", server.address());
+    assert_eq!(server.base_url(), base_url);
+    assert_eq!(server.url(""), base_url);
+    assert_eq!(server.url("/"), format!("{}/", base_url));
+    assert_eq!(server.url("search"), format!("{}/search", base_url));
+    assert_eq!(server.url("/search"), format!("{}/search", base_url));
+    assert_eq!(m.url(), format!("{}/search", base_url));
+}
+
+#[test]
+#[should_panic(expected = "The mock does not match an exact path")]
+fn mock_url_without_exact_path_test() {
+    // Arrange
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.path_contains("search");
+        then.status(200);
+    });
+
+    // Act
+    m.url();
+}
@@ -0,0 +1,99 @@
+use httpmock::prelude::*;
+use isahc::get;
+
+#[test]
+fn verification_guard_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mut guard = server.verify_on_drop();
+
+    let search = server.mock(|when, then| {
+        when.path("/search");
+        then.status(200);
+    });
+    let health = server.mock(|when, then| {
+        when.path("/health");
+        then.status(200);
+    });
+    guard.expect(&search, 2);
+
+    // Act
+    get(server.url("/search")).unwrap();
+    get(server.url("/search")).unwrap();
+    get(server.url("/health")).unwrap();
+
+    // Assert
+    drop(guard);
+    health.assert();
+}
+
+#[test]
+#[should_panic(expected = "The following mocks have not received any request:\nGET /unused")]
+fn unused_mock_test() {
+    let server = MockServer::start();
+    let _guard = server.verify_on_drop();
+
+    server.mock(|when, then| {
+        when.method(GET).path("/unused");
+        then.status(200);
+    });
+}
+
+#[test]
+#[should_panic(expected = "The following requests did not match any mock:\nGET /missing?id=1")]
+fn unmatched_request_test() {
+    let server = MockServer::start();
+    let _guard = server.verify_on_drop();
+
+    get(server.url("/missing?id=1")).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Mock GET /search (ID 0) received 1 request(s), but 2 were expected.")]
+fn expected_hits_test() {
+    let server = MockServer::builder().start();
+    let mut guard = server.verify_on_drop();
+
+    let search = server.mock(|when, then| {
+        when.method(GET).path("/search");
+        then.status(200);
+    });
+    guard.expect(&search, 2);
+
+    get(server.url("/search")).unwrap();
+}
+
+#[test]
+fn disabled_checks_test() {
+    // Arrange
+    let server = MockServer::start();
+    let _guard = server
+        .verify_on_drop()
+        .check_unused_mocks(false)
+        .check_unmatched_requests(false);
+
+    server.mock(|when, then| {
+        when.path("/unused");
+        then.status(200);
+    });
+
+    // Act
+    let response = get(server.url("/missing")).unwrap();
+
+    // Assert
+    assert_eq!(response.status(), 404);
+}
+
+#[test]
+#[should_panic(expected = "original failure")]
+fn guard_does_not_mask_panics_test() {
+    let server = MockServer::start();
+    let _guard = server.verify_on_drop();
+
+    server.mock(|when, then| {
+        when.path("/unused");
+        then.status(200);
+    });
+
+    panic!("original failure");
+}
@@ -0,0 +1,66 @@
+// All tests in this file use APIs that require the `regex` feature.
+#![cfg_attr(not(feature = "regex"), allow(dead_code, unused_imports))]
+
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+use serde_json::{json, Value};
+
+const WIREMOCK_ROOT: &str = "tests/resources/wiremock";
+
+#[cfg(feature = "regex")]
+#[test]
+fn load_wiremock_mappings_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mocks = server.load_wiremock_mappings(WIREMOCK_ROOT);
+
+    // Act
+    let mut user_response = Request::get(server.url("/users/1"))
+        .header("accept", "application/json")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let mut search_response = isahc::get(server.url("/search?type=user&q=fred")).unwrap();
+    let invalid_search_response = isahc::get(server.url("/search?type=user&q=42")).unwrap();
+
+    // Assert
+    assert_eq!(mocks.len(), 4);
+
+    assert_eq!(user_response.status(), 200);
+    let user: Value = user_response.json().unwrap();
+    assert_eq!(user, json!({"id": 1, "name": "Fred"}));
+
+    assert_eq!(search_response.status(), 200);
+    assert_eq!(search_response.text().unwrap(), "hello");
+    assert_eq!(invalid_search_response.status(), 404);
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn wiremock_priority_and_json_path_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mocks = server.load_wiremock_mappings(format!("{}/mappings/users", WIREMOCK_ROOT));
+
+    // Act
+    let create = |body: Value| {
+        Request::post(server.url("/users"))
+            .header("content-type", "application/json")
+            .body(body.to_string())
+            .unwrap()
+            .send()
+            .unwrap()
+            .json::<Value>()
+            .unwrap()
+    };
+    let berlin_user = create(json!({"name": "Fred", "address": {"zip": "10115"}}));
+    let other_user = create(json!({"name": "Wilma", "address": {"zip": "80331"}}));
+
+    // Assert: The mapping with priority 1 is created (and therefore matched) first
+    assert_eq!(mocks.len(), 3);
+    assert_eq!(berlin_user, json!({"id": 2, "office": "Berlin"}));
+    assert_eq!(other_user, json!({"id": 3}));
+    mocks[0].assert();
+}
@@ -28,3 +28,30 @@ fn body_test() {
     m.assert();
     assert_eq!(response.status(), 202);
 }
+
+#[test]
+fn decoded_body_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST)
+            .path("/oauth/token")
+            .x_www_form_urlencoded_tuple("grant_type", "client_credentials")
+            .x_www_form_urlencoded_tuple("scope", "read:users write:users")
+            .x_www_form_urlencoded_key_exists("client_id");
+        then.status(200);
+    });
+
+    // Act: The fields are sent in a different order and with different encodings of spaces
+    let response = Request::post(server.url("/oauth/token"))
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body("client_id=app&scope=read%3Ausers+write%3Ausers&grant_type=client_credentials")
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    m.assert();
+    assert_eq!(response.status(), 200);
+}
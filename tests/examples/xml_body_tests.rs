@@ -0,0 +1,95 @@
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+
+const ORDER: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<o:order xmlns:o="urn:orders" status="open" id="42">
+  <!-- created by the web shop -->
+  <o:item sku="a-1"> Pen </o:item>
+</o:order>"#;
+
+fn post_order(server: &MockServer, path: &str, body: &str) -> u16 {
+    Request::post(server.url(path))
+        .header("Content-Type", "application/xml")
+        .body(body.to_string())
+        .unwrap()
+        .send()
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+#[test]
+fn xml_body_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(POST).path("/xml-orders").xml_body(
+            r#"<order xmlns="urn:orders" id="42" status="open"><item sku="a-1">Pen</item></order>"#,
+        );
+        then.status(201);
+    });
+
+    // Act
+    let equal = post_order(&server, "/xml-orders", ORDER);
+    let different = post_order(&server, "/xml-orders", &ORDER.replace("Pen", "Paper"));
+
+    // Assert
+    m.assert();
+    assert_eq!(equal, 201);
+    assert_eq!(different, 404);
+}
+
+#[test]
+#[should_panic(
+    expected = "The XML body does not match: /order/item: expected text \"Pen\" but got text \"Paper\""
+)]
+fn xml_body_mismatch_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/xml-mismatch").xml_body(
+            r#"<order xmlns="urn:orders" id="42" status="open"><item sku="a-1">Pen</item></order>"#,
+        );
+        then.status(201);
+    });
+
+    // Act
+    post_order(&server, "/xml-mismatch", &ORDER.replace("Pen", "Paper"));
+
+    // Assert
+    m.assert();
+}
+
+#[test]
+fn xpath_test() {
+    // Arrange
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.path("/xpath-orders")
+            .xpath("/o:order/item[@sku='a-1']", "Pen")
+            .xpath("/order/@id", "42");
+        then.status(201);
+    });
+
+    // Act
+    let matching = post_order(&server, "/xpath-orders", ORDER);
+    let other_item = post_order(&server, "/xpath-orders", &ORDER.replace("a-1", "b-2"));
+
+    // Assert
+    m.assert();
+    assert_eq!(matching, 201);
+    assert_eq!(other_item, 404);
+}
+
+#[test]
+#[should_panic(expected = "invalid XPath 'order/id': only absolute paths are supported")]
+fn invalid_xpath_test() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.xpath("order/id", "42");
+        then.status(200);
+    });
+}
@@ -0,0 +1,111 @@
+use httpmock::prelude::*;
+use isahc::{prelude::*, Request};
+
+#[test]
+fn load_mocks_from_dir_test() {
+    // Arrange
+    let server = MockServer::start();
+    let mocks = server.load_mocks_from_dir("tests/resources/mock_files");
+
+    // Act
+    let mut hello_response = Request::get(server.url("/hello?name=world"))
+        .header("accept", "text/plain")
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let mut user_response = Request::post(server.url("/users"))
+        .header("content-type", "application/json")
+        .body(r#"{"name": "Fred", "age": 42}"#)
+        .unwrap()
+        .send()
+        .unwrap();
+
+    let delete_response = Request::delete(server.url("/users/1"))
+        .body(())
+        .unwrap()
+        .send()
+        .unwrap();
+
+    // Assert
+    assert_eq!(mocks.len(), 3);
+
+    assert_eq!(hello_response.status(), 200);
+    assert_eq!(hello_response.text().unwrap(), "ohi!");
+    mocks["hello"].assert();
+
+    assert_eq!(user_response.status(), 201);
+    let user: serde_json::Value = user_response.json().unwrap();
+    assert_eq!(user, serde_json::json!({"id": 1, "name": "Fred"}));
+    mocks["create-user"].assert();
+
+    assert_eq!(delete_response.status(), 204);
+    mocks["delete_user"].assert();
+}
+
+#[test]
+#[should_panic(expected = "unknown field `pathh`")]
+fn load_invalid_mock_file_test() {
+    let dir = std::env::temp_dir().join(format!("httpmock-invalid-yaml-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("invalid.yaml"),
+        "when:\n  pathh: /hello\nthen:\n  status: 200\n",
+    )
+    .unwrap();
+
+    let server = MockServer::start();
+    server.load_mocks_from_dir(&dir);
+}
+
+#[test]
+fn export_and_import_mocks_test() {
+    // Arrange
+    let path = std::env::temp_dir().join(format!("httpmock-export-{}.yaml", std::process::id()));
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.method(POST)
+            .path("/users")
+            .header_matches("x-id", Regex::new("^[0-9]+$").unwrap())
+            .json_body_partial(r#"{"name": "Fred"}"#);
+        then.status(201).json_body(serde_json::json!({"id": 1}));
+    });
+    server.mock(|when, then| {
+        when.path_matches(Regex::new("^/users/[0-9]+$").unwrap());
+        then.status(404);
+    });
+
+    // Act
+    server.export_mocks(&path);
+    let restored = MockServer::start();
+    let mocks = restored.import_mocks(&path);
+
+    let mut create_response = Request::post(restored.url("/users"))
+        .header("x-id", "42")
+        .body(r#"{"name": "Fred", "age": 42}"#)
+        .unwrap()
+        .send()
+        .unwrap();
+    let get_response = isahc::get(restored.url("/users/1")).unwrap();
+
+    // Assert
+    assert_eq!(mocks.len(), 2);
+    assert_eq!(create_response.status(), 201);
+    assert_eq!(create_response.text().unwrap(), r#"{"id":1}"#);
+    assert_eq!(get_response.status(), 404);
+    mocks[0].assert();
+    mocks[1].assert();
+}
+
+#[test]
+#[should_panic(expected = "cannot be serialized")]
+fn export_custom_matcher_mock_test() {
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.matches(|req| req.path.contains("hello"));
+        then.status(200);
+    });
+
+    server.export_mocks(std::env::temp_dir().join("httpmock-export-custom-matcher.yaml"));
+}
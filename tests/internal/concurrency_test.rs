@@ -0,0 +1,38 @@
+use futures_util::future::join_all;
+use httpmock::prelude::*;
+use hyper::{Body, Client, Request};
+use std::time::{Duration, Instant};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_requests_test() {
+    // Arrange
+    let server = MockServer::start_async().await;
+    let m = server
+        .mock_async(|when, then| {
+            when.path("/slow");
+            then.status(200).delay(Duration::from_millis(200));
+        })
+        .await;
+
+    let client = Client::new();
+    let start = Instant::now();
+
+    // Act: Send 100 requests at once, each on its own connection
+    let responses = join_all((0..100).map(|_| {
+        let client = client.clone();
+        let request = Request::get(server.url("/slow"))
+            .header("connection", "close")
+            .body(Body::empty())
+            .unwrap();
+        tokio::spawn(async move { client.request(request).await })
+    }))
+    .await;
+
+    // Assert: The requests are served concurrently and not one after another
+    let elapsed = start.elapsed();
+    for response in responses {
+        assert_eq!(response.unwrap().unwrap().status(), 200);
+    }
+    assert!(elapsed < Duration::from_secs(3), "took {:?}", elapsed);
+    m.assert_hits_async(100).await;
+}
@@ -1,4 +1,6 @@
+mod concurrency_test;
 mod extensions_test;
 mod large_body_test;
 mod loop_test;
+mod pool_test;
 mod runtimes_test;
@@ -0,0 +1,33 @@
+use httpmock::prelude::*;
+
+#[test]
+fn pooled_server_isolation_test() {
+    // Arrange: Leave a mock and a recorded request behind on a pooled server.
+    let server = MockServer::start();
+    server.mock(|when, then| {
+        when.path("/pool-isolation");
+        then.status(200);
+    });
+    assert_eq!(
+        isahc::get(server.url("/pool-isolation")).unwrap().status(),
+        200
+    );
+    drop(server);
+
+    // Act: Lease a server from the pool, which may be the same server as before.
+    let server = MockServer::start();
+    let response = isahc::get(server.url("/pool-isolation")).unwrap();
+
+    // Assert: The leased server does not know the mock from before.
+    assert_eq!(response.status(), 404);
+}
+
+#[test]
+fn unpooled_server_test() {
+    // Arrange
+    let pooled = MockServer::start();
+    let unpooled = MockServer::builder().start();
+
+    // Assert
+    assert_ne!(pooled.address(), unpooled.address());
+}
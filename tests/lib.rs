@@ -1,3 +1,10 @@
+// Some of the older tests are written in a style that newer clippy versions warn about.
+#![allow(
+    clippy::bool_assert_comparison,
+    clippy::get_first,
+    clippy::needless_borrows_for_generic_args
+)]
+
 #[macro_use]
 extern crate lazy_static;
 
@@ -21,10 +28,10 @@ pub fn simulate_standalone_server() {
 lazy_static! {
     static ref STANDALONE_SERVER: Mutex<JoinHandle<Result<(), String>>> = Mutex::new(spawn(|| {
         let srv = start_standalone_server(5000, false, None, false, usize::MAX);
-        let mut runtime = tokio::runtime::Builder::new_current_thread()
+        let runtime = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
             .unwrap();
-        LocalSet::new().block_on(&mut runtime, srv)
+        LocalSet::new().block_on(&runtime, srv)
     }));
 }
@@ -0,0 +1,8 @@
+use httpmock::prelude::*;
+use httpmock::mock;
+
+fn main() {
+    let server = MockServer::start();
+
+    mock!(server, GET "/users" { cookie: { "session": "1" } } => { status: 200 });
+}